@@ -0,0 +1,128 @@
+//! 接收路徑的效能量測：以 1 Mbit/s 滿載一秒的訊框量為單位，
+//! 量測解析、過濾與解碼的吞吐量（frames/sec）。
+
+use can_tool::can::cantypes::CanFrame;
+use can_tool::can::config::{ByteOrder, CanbusConfigEntry};
+use can_tool::can::filter::FrameFilter;
+use can_tool::can::logfile::{format_candump_line, parse_candump_line};
+use can_tool::can::signals::SignalTable;
+use can_tool::can::stats::{frame_bits, BusStatistics};
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+const BITRATE: u64 = 1_000_000;
+
+/// 產生 1 Mbit/s 滿載一秒份量的 8 byte 訊框，ID 在 64 個之間輪替
+fn full_bus_second() -> Vec<CanFrame> {
+    let mut frames = Vec::new();
+    let mut bits = 0;
+    let mut timestamp = 1_700_000_000_000_000u64;
+    let mut i = 0u32;
+    while bits < BITRATE {
+        let id = 0x100 + (i % 64);
+        let mut frame = CanFrame::new(0, id, &(i as u64).to_le_bytes());
+        frame.timestamp = timestamp;
+        let frame_bits = frame_bits(&frame);
+        bits += frame_bits;
+        timestamp += frame_bits;
+        frames.push(frame);
+        i += 1;
+    }
+    frames
+}
+
+fn signal_entries() -> Vec<CanbusConfigEntry> {
+    (0..64)
+        .flat_map(|n| {
+            [
+                CanbusConfigEntry {
+                    key: format!("sig{}_a", n),
+                    id: 0x100 + n,
+                    index: 0,
+                    len: 2,
+                    endian: ByteOrder::Little,
+                    start_bit: None,
+                    bit_length: None,
+                    data_type: "uint16".to_string(),
+                    scale: 1.0,
+                    offset: 0.0,
+                    signed: None,
+                    values: Default::default(),
+                },
+                CanbusConfigEntry {
+                    key: format!("sig{}_b", n),
+                    id: 0x100 + n,
+                    index: 4,
+                    len: 4,
+                    endian: ByteOrder::Big,
+                    start_bit: None,
+                    bit_length: None,
+                    data_type: "int32".to_string(),
+                    scale: 1.0,
+                    offset: 0.0,
+                    signed: None,
+                    values: Default::default(),
+                },
+            ]
+        })
+        .collect()
+}
+
+fn frame_pipeline(c: &mut Criterion) {
+    let frames = full_bus_second();
+    let lines: Vec<String> = frames.iter().map(format_candump_line).collect();
+    let filter = FrameFilter::compile("id in [0x100, 0x110, 0x120] || (data[0] & 0x0F) == 3")
+        .expect("valid filter");
+    let mut signals = SignalTable::default();
+    signals.load(&signal_entries());
+
+    let mut group = c.benchmark_group("full_bus_1mbit");
+    group.throughput(Throughput::Elements(frames.len() as u64));
+
+    group.bench_function("parse_candump", |b| {
+        b.iter(|| {
+            for line in &lines {
+                black_box(parse_candump_line(line));
+            }
+        })
+    });
+    group.bench_function("filter", |b| {
+        b.iter(|| frames.iter().filter(|f| filter.matches(f)).count())
+    });
+    group.bench_function("decode_signals", |b| {
+        b.iter(|| {
+            for frame in &frames {
+                signals.process(black_box(frame));
+            }
+        })
+    });
+    group.bench_function("statistics", |b| {
+        b.iter(|| {
+            let mut stats = BusStatistics::default();
+            stats.set_bitrate(0, BITRATE as u32);
+            for frame in &frames {
+                stats.process(black_box(frame));
+            }
+            stats
+        })
+    });
+    group.bench_function("full_pipeline", |b| {
+        b.iter(|| {
+            let mut stats = BusStatistics::default();
+            for line in &lines {
+                let Some(frame) = parse_candump_line(line) else {
+                    continue;
+                };
+                if filter.matches(&frame) {
+                    continue;
+                }
+                signals.process(&frame);
+                stats.process(&frame);
+            }
+            stats
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, frame_pipeline);
+criterion_main!(benches);
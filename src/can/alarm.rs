@@ -0,0 +1,160 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::dbc::DbcDecoder;
+use crate::can::events::{EventCategory, EventLog, Severity};
+use crate::can::signals::SignalTable;
+use crate::can::watch::{WatchExpr, WatchState, WatchTransition};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 執行中可編輯的訊號警報規則，隨介面 profile 保存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AlarmRule {
+    pub enabled: bool,
+    /// 與監看相同的運算式，例如 `BatteryTemp > 60 for 2s`；DBC 訊號以 `訊息.訊號` 表示
+    pub expr: String,
+    pub hysteresis: f64,
+    /// 觸發時發出提示音
+    pub sound: bool,
+    /// 觸發時送出訊框
+    pub send: bool,
+    pub channel: u32,
+    pub id: u32,
+    /// 以空白分隔的十六進位位元組
+    pub data: String,
+}
+
+impl Default for AlarmRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            expr: String::new(),
+            hysteresis: 0.0,
+            sound: false,
+            send: false,
+            channel: 0,
+            id: 0,
+            data: String::new(),
+        }
+    }
+}
+
+fn parse_data(text: &str) -> Result<Vec<u8>, String> {
+    text.split_whitespace()
+        .map(|byte| {
+            u8::from_str_radix(byte, 16).map_err(|_| format!("Invalid data byte '{}'", byte))
+        })
+        .collect()
+}
+
+struct ActiveRule {
+    /// 在 `rules` 中的位置
+    index: usize,
+    state: WatchState,
+    frame: Option<CanFrame>,
+}
+
+/// 以 YAML 與 DBC 解碼值判斷警報規則，觸發與解除時各記錄一次事件
+#[derive(Default)]
+pub struct AlarmEngine {
+    rules: Vec<AlarmRule>,
+    active: Vec<ActiveRule>,
+    /// 尚未由介面播放的提示音
+    sound_pending: bool,
+}
+
+impl AlarmEngine {
+    /// 以新的規則取代目前的規則；任一規則無法解析時保留原本的規則，
+    /// 運算式空白或停用的規則不生效
+    pub fn set_rules(&mut self, rules: &[AlarmRule]) -> Result<(), String> {
+        let mut active = Vec::new();
+        for (index, rule) in rules.iter().enumerate() {
+            if !rule.enabled || rule.expr.trim().is_empty() {
+                continue;
+            }
+            let expr = WatchExpr::parse(&rule.expr)?;
+            let frame = if rule.send {
+                let data = parse_data(&rule.data).map_err(|e| format!("{}: {}", rule.expr, e))?;
+                Some(CanFrame::new(rule.channel, rule.id, &data))
+            } else {
+                None
+            };
+            active.push(ActiveRule {
+                index,
+                state: WatchState::new(expr, rule.hysteresis),
+                frame,
+            });
+        }
+        self.rules = rules.to_vec();
+        self.active = active;
+        Ok(())
+    }
+
+    pub fn rules(&self) -> &[AlarmRule] {
+        &self.rules
+    }
+
+    /// 更新所有規則，回傳剛觸發的規則要送出的訊框
+    pub fn check(
+        &mut self,
+        signals: &SignalTable,
+        dbc: &DbcDecoder,
+        now: u64,
+        events: &mut EventLog,
+    ) -> Vec<CanFrame> {
+        // YAML 訊號優先，其次為 `訊息.訊號` 形式的 DBC 訊號
+        let lookup = |key: &str| {
+            signals.get(key).and_then(|s| s.value).or_else(|| {
+                let (message, signal) = key.split_once('.')?;
+                dbc.value(message, signal).map(|d| d.value)
+            })
+        };
+        let mut frames = Vec::new();
+        for rule in &mut self.active {
+            let Some(transition) = rule.state.update_with(&lookup, now) else {
+                continue;
+            };
+            let config = &self.rules[rule.index];
+            match transition {
+                WatchTransition::Raised => {
+                    events.push(
+                        now,
+                        Severity::Alarm,
+                        EventCategory::Alarm,
+                        format!("{} (alarm raised)", config.expr),
+                    );
+                    self.sound_pending |= config.sound;
+                    frames.extend(rule.frame);
+                }
+                WatchTransition::Cleared => events.push(
+                    now,
+                    Severity::Info,
+                    EventCategory::Alarm,
+                    format!("{} (alarm cleared)", config.expr),
+                ),
+            }
+        }
+        frames
+    }
+
+    /// 規則目前是否處於警報中，依 `rules` 中的位置
+    pub fn is_active(&self, index: usize) -> bool {
+        self.active
+            .iter()
+            .any(|rule| rule.index == index && rule.state.is_active())
+    }
+
+    /// 警報中的規則引用的訊號，供介面以紅色標示
+    pub fn alarmed_signals(&self) -> HashSet<String> {
+        self.active
+            .iter()
+            .filter(|rule| rule.state.is_active())
+            .flat_map(|rule| rule.state.expr.keys().map(str::to_string))
+            .collect()
+    }
+
+    /// 取出尚未播放的提示音
+    pub fn take_sound(&mut self) -> bool {
+        std::mem::take(&mut self.sound_pending)
+    }
+}
@@ -0,0 +1,138 @@
+use crate::can::cantypes::{CanFrame, FrameDirection};
+use serde::{Deserialize, Serialize};
+
+/// 自動回應規則：收到指定 ID 且資料開頭符合的訊框時，延遲後送出回應訊框，
+/// 用於在測試台上模擬缺少的 ECU
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResponseRule {
+    pub enabled: bool,
+    /// 監聽的通道，None 為所有通道；回應由收到請求的通道送出
+    pub channel: Option<u32>,
+    pub request_id: u32,
+    /// 請求資料開頭需符合的十六進位位元組，空白表示不檢查
+    pub prefix: String,
+    pub response_id: u32,
+    /// 以空白分隔的十六進位位元組
+    pub response_data: String,
+    pub delay_ms: u64,
+}
+
+impl Default for ResponseRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channel: None,
+            request_id: 0,
+            prefix: String::new(),
+            response_id: 0,
+            response_data: String::new(),
+            delay_ms: 0,
+        }
+    }
+}
+
+fn parse_data(text: &str) -> Result<Vec<u8>, String> {
+    text.split_whitespace()
+        .map(|byte| {
+            u8::from_str_radix(byte, 16).map_err(|_| format!("Invalid data byte '{}'", byte))
+        })
+        .collect()
+}
+
+struct CompiledRule {
+    /// 在 `rules` 中的位置
+    index: usize,
+    channel: Option<u32>,
+    request_id: u32,
+    prefix: Vec<u8>,
+    response_id: u32,
+    response: Vec<u8>,
+    delay_us: u64,
+}
+
+impl CompiledRule {
+    fn matches(&self, frame: &CanFrame) -> bool {
+        self.channel.is_none_or(|channel| channel == frame.channel)
+            && frame.id == self.request_id
+            && !frame.rtr
+            && frame.payload().starts_with(&self.prefix)
+    }
+}
+
+/// 依規則自動回應收到的請求；延遲送出的訊框排在佇列中，由 `poll` 取出
+#[derive(Default)]
+pub struct AutoResponder {
+    rules: Vec<ResponseRule>,
+    compiled: Vec<CompiledRule>,
+    /// 待送出的回應，依到期時間（微秒）排序
+    pending: Vec<(u64, CanFrame)>,
+    /// 各規則的觸發次數，依 `rules` 中的位置
+    hits: Vec<u64>,
+}
+
+impl AutoResponder {
+    /// 以新的規則取代目前的規則；任一規則無法解析時保留原本的規則，
+    /// 停用的規則不生效。尚未送出的回應會被清除
+    pub fn set_rules(&mut self, rules: &[ResponseRule]) -> Result<(), String> {
+        let mut compiled = Vec::new();
+        for (index, rule) in rules.iter().enumerate() {
+            if !rule.enabled {
+                continue;
+            }
+            let context = |e: String| format!("Rule for 0x{:X}: {}", rule.request_id, e);
+            compiled.push(CompiledRule {
+                index,
+                channel: rule.channel,
+                request_id: rule.request_id,
+                prefix: parse_data(&rule.prefix).map_err(context)?,
+                response_id: rule.response_id,
+                response: parse_data(&rule.response_data).map_err(context)?,
+                delay_us: rule.delay_ms * 1000,
+            });
+        }
+        self.rules = rules.to_vec();
+        self.compiled = compiled;
+        self.pending.clear();
+        self.hits = vec![0; rules.len()];
+        Ok(())
+    }
+
+    pub fn rules(&self) -> &[ResponseRule] {
+        &self.rules
+    }
+
+    /// 檢查收到的訊框並排入符合規則的回應；自己送出的 TX 訊框不會觸發回應
+    pub fn process(&mut self, frame: &CanFrame) {
+        if frame.direction == FrameDirection::Tx {
+            return;
+        }
+        for rule in self.compiled.iter().filter(|rule| rule.matches(frame)) {
+            let response = CanFrame::new(frame.channel, rule.response_id, &rule.response);
+            let due = frame.timestamp + rule.delay_us;
+            let pos = self.pending.partition_point(|(d, _)| *d <= due);
+            self.pending.insert(pos, (due, response));
+            self.hits[rule.index] += 1;
+        }
+    }
+
+    /// 取出已到期的回應；匯流排安靜時也需定期呼叫
+    pub fn poll(&mut self, now_us: u64) -> Vec<CanFrame> {
+        let due = self.pending.partition_point(|(d, _)| *d <= now_us);
+        self.pending.drain(..due).map(|(_, frame)| frame).collect()
+    }
+
+    /// 下一筆待送回應的到期時間
+    pub fn next_due(&self) -> Option<u64> {
+        self.pending.first().map(|(due, _)| *due)
+    }
+
+    /// 規則的觸發次數，依 `rules` 中的位置
+    pub fn hits(&self, index: usize) -> u64 {
+        self.hits.get(index).copied().unwrap_or(0)
+    }
+
+    pub fn reset_counts(&mut self) {
+        self.hits.iter_mut().for_each(|hits| *hits = 0);
+    }
+}
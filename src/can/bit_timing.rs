@@ -0,0 +1,101 @@
+/// SJA1000 相容暫存器（ControlCAN Timing0/Timing1、PCAN BTR0BTR1）的時間量子時脈：
+/// 控制器 16 MHz 石英經固定除 2，tq = (BRP + 1) / 8 MHz
+pub const SJA1000_CLOCK_HZ: u32 = 8_000_000;
+
+const BRP_MAX: u32 = 64;
+const TSEG1_MAX: u32 = 16;
+const TSEG2_MAX: u32 = 8;
+/// 一個位元的 tq 數範圍，少於 8 時取樣點無法細調
+const BIT_TQ_MIN: u32 = 8;
+const BIT_TQ_MAX: u32 = 1 + TSEG1_MAX + TSEG2_MAX;
+
+/// SJA1000 的 BTR0/BTR1：BTR0 = SJW(2) | BRP(6)，BTR1 = SAM(1) | TSEG2(3) | TSEG1(4)，
+/// 各欄位皆為實際值減一
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sja1000Timing {
+    pub btr0: u8,
+    pub btr1: u8,
+}
+
+impl Sja1000Timing {
+    /// 由 PCAN 的 BTR0BTR1（高位元組為 BTR0）拆出
+    pub fn from_u16(value: u16) -> Self {
+        Self {
+            btr0: (value >> 8) as u8,
+            btr1: value as u8,
+        }
+    }
+
+    pub fn to_u16(self) -> u16 {
+        (self.btr0 as u16) << 8 | self.btr1 as u16
+    }
+
+    pub fn brp(self) -> u32 {
+        (self.btr0 & 0x3F) as u32 + 1
+    }
+
+    pub fn sjw(self) -> u32 {
+        (self.btr0 >> 6) as u32 + 1
+    }
+
+    pub fn tseg1(self) -> u32 {
+        (self.btr1 & 0x0F) as u32 + 1
+    }
+
+    pub fn tseg2(self) -> u32 {
+        ((self.btr1 >> 4) & 0x07) as u32 + 1
+    }
+
+    /// 每個位元取樣三次
+    pub fn triple_sampling(self) -> bool {
+        self.btr1 & 0x80 != 0
+    }
+
+    /// 一個位元的 tq 數（含同步段）
+    pub fn bit_tq(self) -> u32 {
+        1 + self.tseg1() + self.tseg2()
+    }
+
+    /// 實際位元率（bit/s）
+    pub fn bitrate(self) -> f64 {
+        SJA1000_CLOCK_HZ as f64 / (self.brp() * self.bit_tq()) as f64
+    }
+
+    /// 取樣點（%）
+    pub fn sample_point(self) -> f64 {
+        (1 + self.tseg1()) as f64 * 100.0 / self.bit_tq() as f64
+    }
+}
+
+/// 計算指定位元率與取樣點（%）的暫存器值：先取位元率誤差最小的組合，
+/// 再取取樣點最接近者，相同時使用較多 tq；SJW 固定為 1 與預設表一致，位元率為 0 時回傳 None
+pub fn compute_sja1000_timing(bitrate: u32, sample_point: f64) -> Option<Sja1000Timing> {
+    if bitrate == 0 {
+        return None;
+    }
+    let mut best: Option<((f64, f64), Sja1000Timing)> = None;
+    for brp in 1..=BRP_MAX {
+        for bit_tq in BIT_TQ_MIN..=BIT_TQ_MAX {
+            let actual = SJA1000_CLOCK_HZ as f64 / (brp * bit_tq) as f64;
+            let rate_error = (actual - bitrate as f64).abs();
+            // tq = 1（同步段）+ tseg1 + tseg2，取樣點在 tseg1 結束處
+            let tseg1 = ((sample_point / 100.0 * bit_tq as f64).round() as u32)
+                .saturating_sub(1)
+                .clamp(
+                    bit_tq.saturating_sub(1 + TSEG2_MAX).max(1),
+                    TSEG1_MAX.min(bit_tq - 2),
+                );
+            let tseg2 = bit_tq - 1 - tseg1;
+            let timing = Sja1000Timing {
+                btr0: (brp - 1) as u8,
+                btr1: ((tseg2 - 1) << 4 | (tseg1 - 1)) as u8,
+            };
+            let score = (rate_error, (timing.sample_point() - sample_point).abs());
+            // 由小到大走訪 brp，分數相同時保留先找到（tq 較多）的組合
+            if best.is_none_or(|(s, _)| score < s) {
+                best = Some((score, timing));
+            }
+        }
+    }
+    best.map(|(_, timing)| timing)
+}
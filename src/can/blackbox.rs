@@ -0,0 +1,281 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::logfile::format_candump_line;
+use crate::can::session::SessionInfo;
+use flume::{unbounded, Receiver, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime};
+
+/// 黑盒子紀錄檔的檔名前綴，清除舊檔時只會處理符合前綴的檔案
+pub const SEGMENT_PREFIX: &str = "blackbox_";
+/// 寫入緩衝的最長停留時間，程式異常結束時最多遺失這段期間的資料
+const FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 黑盒子記錄設定，隨介面 profile 保存
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlackBoxConfig {
+    pub dir: PathBuf,
+    /// 每個分段檔的最長時間
+    pub segment_minutes: u64,
+    /// 每個分段檔的大小上限（MB），0 表示不限
+    pub max_segment_mb: u64,
+    /// 保留最近幾小時的分段檔，0 表示全部保留
+    pub retention_hours: u64,
+    /// 開始接收時自動啟動記錄，不需開啟面板
+    pub auto_start: bool,
+}
+
+impl Default for BlackBoxConfig {
+    fn default() -> Self {
+        Self {
+            dir: PathBuf::from("blackbox"),
+            segment_minutes: 10,
+            max_segment_mb: 100,
+            retention_hours: 24,
+            auto_start: false,
+        }
+    }
+}
+
+/// 記錄執行緒的狀態，供介面顯示
+#[derive(Debug, Clone, Default)]
+pub struct BlackBoxStatus {
+    pub current_file: Option<PathBuf>,
+    /// 本次記錄建立的分段數
+    pub segments: u64,
+    pub frames: u64,
+    pub bytes: u64,
+    /// 依保留期限刪除的分段數
+    pub removed: u64,
+    pub last_error: Option<String>,
+}
+
+/// 持續寫入磁碟的背景記錄，與介面的資料緩衝區無關；
+/// 依時間或大小切換分段檔，並刪除超過保留期限的舊檔
+pub struct BlackBoxRecorder {
+    frame_tx: Sender<CanFrame>,
+    running: Arc<AtomicBool>,
+    status: Arc<Mutex<BlackBoxStatus>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl BlackBoxRecorder {
+    /// 建立記錄目錄並啟動寫入執行緒
+    pub fn start<F>(config: BlackBoxConfig, session: SessionInfo, log: F) -> Result<Self, String>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        fs::create_dir_all(&config.dir)
+            .map_err(|e| format!("Failed to create {}: {}", config.dir.display(), e))?;
+        let (frame_tx, frame_rx) = unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let status = Arc::new(Mutex::new(BlackBoxStatus::default()));
+        let running_flag = Arc::clone(&running);
+        let status_shared = Arc::clone(&status);
+        let handle = thread::spawn(move || {
+            let mut writer = SegmentWriter {
+                config,
+                session,
+                status: status_shared,
+                file: None,
+            };
+            writer.run(frame_rx, running_flag, log);
+        });
+        Ok(Self {
+            frame_tx,
+            running,
+            status,
+            handle: Some(handle),
+        })
+    }
+
+    /// 交由記錄執行緒寫入一筆訊框
+    pub fn record(&self, frame: &CanFrame) {
+        let _ = self.frame_tx.send(*frame);
+    }
+
+    pub fn status(&self) -> BlackBoxStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining black box thread: {:?}", e);
+            }
+        }
+    }
+}
+
+impl Drop for BlackBoxRecorder {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+struct OpenSegment {
+    writer: BufWriter<File>,
+    opened: Instant,
+    bytes: u64,
+}
+
+struct SegmentWriter {
+    config: BlackBoxConfig,
+    session: SessionInfo,
+    status: Arc<Mutex<BlackBoxStatus>>,
+    file: Option<OpenSegment>,
+}
+
+impl SegmentWriter {
+    fn run<F>(&mut self, frame_rx: Receiver<CanFrame>, running: Arc<AtomicBool>, log: F)
+    where
+        F: Fn(String),
+    {
+        log(format!(
+            "Black box recording to {} ({} min segments, keep {} h)",
+            self.config.dir.display(),
+            self.config.segment_minutes,
+            self.config.retention_hours
+        ));
+        let mut last_flush = Instant::now();
+        // 是否處於連續寫入失敗中（只記錄第一次錯誤）
+        let mut failing = false;
+        // 停止後仍寫完已排入的訊框
+        while running.load(Ordering::SeqCst) || !frame_rx.is_empty() {
+            match frame_rx.recv_timeout(Duration::from_millis(200)) {
+                Ok(frame) => match self.write(&frame) {
+                    Ok(()) => failing = false,
+                    Err(e) => {
+                        if !failing {
+                            log(format!("Black box write failed: {}", e));
+                        }
+                        failing = true;
+                        self.status.lock().unwrap().last_error = Some(e);
+                        self.file = None;
+                    }
+                },
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+            if last_flush.elapsed() >= FLUSH_INTERVAL {
+                last_flush = Instant::now();
+                if let Some(segment) = self.file.as_mut() {
+                    let _ = segment.writer.flush();
+                }
+                // 分段時間到但沒有新訊框時也要切換，讓舊檔可以依期限刪除
+                if self.segment_expired() {
+                    self.close();
+                }
+            }
+        }
+        self.close();
+        let status = self.status.lock().unwrap();
+        log(format!(
+            "Black box recording stopped: {} frames in {} segments",
+            status.frames, status.segments
+        ));
+    }
+
+    fn segment_expired(&self) -> bool {
+        self.file.as_ref().is_some_and(|segment| {
+            let max_bytes = self.config.max_segment_mb * 1024 * 1024;
+            segment.opened.elapsed() >= Duration::from_secs(self.config.segment_minutes.max(1) * 60)
+                || (max_bytes > 0 && segment.bytes >= max_bytes)
+        })
+    }
+
+    fn write(&mut self, frame: &CanFrame) -> Result<(), String> {
+        if self.segment_expired() {
+            self.close();
+        }
+        if self.file.is_none() {
+            self.open()?;
+        }
+        let segment = self.file.as_mut().unwrap();
+        let line = format_candump_line(frame);
+        writeln!(segment.writer, "{}", line).map_err(|e| e.to_string())?;
+        let written = line.len() as u64 + 1;
+        segment.bytes += written;
+        let mut status = self.status.lock().unwrap();
+        status.frames += 1;
+        status.bytes += written;
+        Ok(())
+    }
+
+    fn open(&mut self) -> Result<(), String> {
+        let name = format!(
+            "{}{}.log",
+            SEGMENT_PREFIX,
+            chrono::Local::now().format("%Y%m%d_%H%M%S_%3f")
+        );
+        let path = self.config.dir.join(name);
+        let file = File::create(&path)
+            .map_err(|e| format!("Failed to create {}: {}", path.display(), e))?;
+        let mut writer = BufWriter::new(file);
+        let mut bytes = 0;
+        for line in self.session.header_lines() {
+            writeln!(writer, "{}", line).map_err(|e| e.to_string())?;
+            bytes += line.len() as u64 + 1;
+        }
+        self.file = Some(OpenSegment {
+            writer,
+            opened: Instant::now(),
+            bytes,
+        });
+        let removed = self.prune(&path);
+        let mut status = self.status.lock().unwrap();
+        status.current_file = Some(path);
+        status.segments += 1;
+        status.removed += removed;
+        Ok(())
+    }
+
+    fn close(&mut self) {
+        if let Some(mut segment) = self.file.take() {
+            if let Err(e) = segment.writer.flush() {
+                self.status.lock().unwrap().last_error = Some(e.to_string());
+            }
+        }
+        self.status.lock().unwrap().current_file = None;
+    }
+
+    /// 刪除最後修改時間超過保留期限的分段檔，回傳刪除的數量
+    fn prune(&self, current: &Path) -> u64 {
+        if self.config.retention_hours == 0 {
+            return 0;
+        }
+        let Some(cutoff) =
+            SystemTime::now().checked_sub(Duration::from_secs(self.config.retention_hours * 3600))
+        else {
+            return 0;
+        };
+        let Ok(entries) = fs::read_dir(&self.config.dir) else {
+            return 0;
+        };
+        let mut removed = 0;
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_segment = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .is_some_and(|n| n.starts_with(SEGMENT_PREFIX) && n.ends_with(".log"));
+            let expired = entry
+                .metadata()
+                .and_then(|m| m.modified())
+                .is_ok_and(|modified| modified < cutoff);
+            if is_segment && expired && path != current && fs::remove_file(&path).is_ok() {
+                removed += 1;
+            }
+        }
+        removed
+    }
+}
@@ -0,0 +1,367 @@
+use crate::can::cantypes::{CanFrame, FrameDirection, FrameProtocol, MAX_PAYLOAD_LEN};
+use chrono::{Datelike, Local, NaiveDate, TimeZone, Timelike};
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::fs::{self, File};
+use std::io::{BufWriter, Read, Seek, SeekFrom, Write};
+
+const FILE_HEADER_SIZE: usize = 144;
+const OBJ_HEADER_BASE_SIZE: usize = 16;
+const OBJ_HEADER_V1_SIZE: usize = 16;
+const LOG_CONTAINER_SIZE: usize = 16;
+
+// 物件類型
+const CAN_MESSAGE: u32 = 1;
+const LOG_CONTAINER: u32 = 10;
+const CAN_MESSAGE2: u32 = 86;
+const CAN_FD_MESSAGE: u32 = 100;
+
+const NO_COMPRESSION: u16 = 0;
+const ZLIB_DEFLATE: u16 = 2;
+
+/// 物件標頭的時間單位
+const TIME_TEN_MICS: u32 = 1;
+const TIME_ONE_NANS: u32 = 2;
+
+// CAN_MESSAGE 的旗標與 CAN_FD_MESSAGE 的 FD 旗標
+const DIR_TX: u8 = 0x01;
+const REMOTE_FLAG: u8 = 0x80;
+const EDL: u8 = 0x01;
+const BRS: u8 = 0x02;
+const ESI: u8 = 0x04;
+const EXTENDED_ID_FLAG: u32 = 0x8000_0000;
+
+/// 每個容器壓縮前的大小
+const MAX_CONTAINER_SIZE: usize = 128 * 1024;
+
+/// Windows SYSTEMTIME，只保留到毫秒
+fn system_time(micros: u64) -> [u16; 8] {
+    let time = Local
+        .timestamp_micros(micros as i64)
+        .single()
+        .unwrap_or_else(Local::now);
+    [
+        time.year() as u16,
+        time.month() as u16,
+        time.weekday().num_days_from_sunday() as u16,
+        time.day() as u16,
+        time.hour() as u16,
+        time.minute() as u16,
+        time.second() as u16,
+        (time.nanosecond() / 1_000_000).min(999) as u16,
+    ]
+}
+
+fn system_time_micros(fields: [u16; 8]) -> Option<u64> {
+    let [year, month, _, day, hour, minute, second, millis] = fields;
+    let naive = NaiveDate::from_ymd_opt(year as i32, month as u32, day as u32)?.and_hms_milli_opt(
+        hour as u32,
+        minute as u32,
+        second as u32,
+        millis as u32,
+    )?;
+    let time = Local.from_local_datetime(&naive).earliest()?;
+    u64::try_from(time.timestamp_micros()).ok()
+}
+
+/// 將訊框編碼為 BLF 物件內容（不含物件標頭），回傳物件類型；通道從 1 起算
+fn encode_frame(frame: &CanFrame) -> (u32, Vec<u8>) {
+    let mut flags = 0;
+    if frame.direction == FrameDirection::Tx {
+        flags |= DIR_TX;
+    }
+    if frame.rtr {
+        flags |= REMOTE_FLAG;
+    }
+    let id = if frame.extended {
+        frame.id | EXTENDED_ID_FLAG
+    } else {
+        frame.id
+    };
+    let channel = (frame.channel + 1) as u16;
+    let mut data = Vec::with_capacity(84);
+    data.extend_from_slice(&channel.to_le_bytes());
+    data.push(flags);
+    if frame.protocol == FrameProtocol::Classic {
+        // RTR 的資料長度即為 DLC
+        data.push(if frame.rtr {
+            frame.data.len() as u8
+        } else {
+            frame.dlc()
+        });
+        data.extend_from_slice(&id.to_le_bytes());
+        let mut payload = [0u8; 8];
+        let len = frame.payload().len().min(8);
+        payload[..len].copy_from_slice(&frame.payload()[..len]);
+        data.extend_from_slice(&payload);
+        return (CAN_MESSAGE, data);
+    }
+    let mut fd_flags = EDL;
+    if frame.brs {
+        fd_flags |= BRS;
+    }
+    if frame.esi {
+        fd_flags |= ESI;
+    }
+    data.push(frame.dlc());
+    data.extend_from_slice(&id.to_le_bytes());
+    // frame_length 與 bit_count 未知
+    data.extend_from_slice(&0u32.to_le_bytes());
+    data.push(0);
+    data.push(fd_flags);
+    data.push(frame.payload().len() as u8);
+    data.extend_from_slice(&[0; 5]);
+    let mut payload = [0u8; MAX_PAYLOAD_LEN];
+    payload[..frame.payload().len()].copy_from_slice(frame.payload());
+    data.extend_from_slice(&payload);
+    (CAN_FD_MESSAGE, data)
+}
+
+/// Vector BLF 二進位紀錄檔的寫入器；訊框以 zlib 壓縮的容器分批寫入，
+/// 結束時回填檔頭的大小、筆數與起訖時間
+pub struct BlfWriter {
+    file: BufWriter<File>,
+    path: String,
+    /// 尚未寫入容器的物件
+    buffer: Vec<u8>,
+    /// 量測開始時間（第一筆訊框的時間，取整到毫秒）
+    start: Option<u64>,
+    last: u64,
+    object_count: u32,
+    uncompressed_size: u64,
+}
+
+impl BlfWriter {
+    pub fn create(path: &str) -> Result<Self, String> {
+        let file = File::create(path).map_err(|e| format!("Failed to create {}: {}", path, e))?;
+        let mut writer = Self {
+            file: BufWriter::new(file),
+            path: path.to_string(),
+            buffer: Vec::new(),
+            start: None,
+            last: 0,
+            object_count: 0,
+            uncompressed_size: FILE_HEADER_SIZE as u64,
+        };
+        // 先寫入空白檔頭，結束時回填
+        writer.write_all(&[0; FILE_HEADER_SIZE])?;
+        Ok(writer)
+    }
+
+    fn write_all(&mut self, bytes: &[u8]) -> Result<(), String> {
+        self.file
+            .write_all(bytes)
+            .map_err(|e| format!("Failed to write {}: {}", self.path, e))
+    }
+
+    pub fn write(&mut self, frame: &CanFrame) -> Result<(), String> {
+        let start = *self
+            .start
+            .get_or_insert(frame.timestamp - frame.timestamp % 1000);
+        self.last = self.last.max(frame.timestamp);
+        let (object_type, data) = encode_frame(frame);
+        let header_size = (OBJ_HEADER_BASE_SIZE + OBJ_HEADER_V1_SIZE) as u16;
+        let object_size = header_size as u32 + data.len() as u32;
+        let offset_ns = frame.timestamp.saturating_sub(start) * 1000;
+        self.buffer.extend_from_slice(b"LOBJ");
+        self.buffer.extend_from_slice(&header_size.to_le_bytes());
+        self.buffer.extend_from_slice(&1u16.to_le_bytes());
+        self.buffer.extend_from_slice(&object_size.to_le_bytes());
+        self.buffer.extend_from_slice(&object_type.to_le_bytes());
+        self.buffer.extend_from_slice(&TIME_ONE_NANS.to_le_bytes());
+        self.buffer.extend_from_slice(&[0; 4]);
+        self.buffer.extend_from_slice(&offset_ns.to_le_bytes());
+        self.buffer.extend_from_slice(&data);
+        self.buffer.resize(self.buffer.len() + data.len() % 4, 0);
+        self.object_count += 1;
+        self.flush_containers(false)
+    }
+
+    /// 將累積的物件寫成容器；`all` 為 false 時只寫入已滿的容器
+    fn flush_containers(&mut self, all: bool) -> Result<(), String> {
+        while self.buffer.len() >= MAX_CONTAINER_SIZE || (all && !self.buffer.is_empty()) {
+            let len = self.buffer.len().min(MAX_CONTAINER_SIZE);
+            let chunk: Vec<u8> = self.buffer.drain(..len).collect();
+            let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+            let compressed = encoder
+                .write_all(&chunk)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| format!("Failed to compress BLF container: {}", e))?;
+            let object_size = (OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE + compressed.len()) as u32;
+            let mut header = Vec::with_capacity(OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE);
+            header.extend_from_slice(b"LOBJ");
+            header.extend_from_slice(&(OBJ_HEADER_BASE_SIZE as u16).to_le_bytes());
+            header.extend_from_slice(&1u16.to_le_bytes());
+            header.extend_from_slice(&object_size.to_le_bytes());
+            header.extend_from_slice(&LOG_CONTAINER.to_le_bytes());
+            header.extend_from_slice(&ZLIB_DEFLATE.to_le_bytes());
+            header.extend_from_slice(&[0; 6]);
+            header.extend_from_slice(&(chunk.len() as u32).to_le_bytes());
+            header.extend_from_slice(&[0; 4]);
+            self.write_all(&header)?;
+            self.write_all(&compressed)?;
+            self.write_all(&vec![0; object_size as usize % 4])?;
+            self.uncompressed_size += (OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE + len) as u64;
+        }
+        Ok(())
+    }
+
+    /// 寫入剩餘的物件並回填檔頭，回傳寫入的訊框數
+    pub fn finish(mut self) -> Result<usize, String> {
+        self.flush_containers(true)?;
+        let path = self.path.clone();
+        let write_error = |e: std::io::Error| format!("Failed to write {}: {}", path, e);
+        let file_size = self.file.stream_position().map_err(write_error)?;
+        let start = self.start.unwrap_or_else(crate::can::cantypes::now_micros);
+        let mut header = Vec::with_capacity(FILE_HEADER_SIZE);
+        header.extend_from_slice(b"LOGG");
+        header.extend_from_slice(&(FILE_HEADER_SIZE as u32).to_le_bytes());
+        // 應用程式代碼與 python-can 相同，BLF 版本 2.6.8.1
+        header.extend_from_slice(&[5, 0, 0, 0, 2, 6, 8, 1]);
+        header.extend_from_slice(&file_size.to_le_bytes());
+        header.extend_from_slice(&self.uncompressed_size.to_le_bytes());
+        header.extend_from_slice(&self.object_count.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes());
+        for field in system_time(start)
+            .into_iter()
+            .chain(system_time(self.last.max(start)))
+        {
+            header.extend_from_slice(&field.to_le_bytes());
+        }
+        header.resize(FILE_HEADER_SIZE, 0);
+        self.file.seek(SeekFrom::Start(0)).map_err(write_error)?;
+        self.write_all(&header)?;
+        self.file.flush().map_err(write_error)?;
+        Ok(self.object_count as usize)
+    }
+}
+
+/// 將訊框寫成 BLF 檔，回傳寫入的筆數；BLF 沒有註解欄位，不寫入工作階段資料
+pub fn export_blf<'a, I>(path: &str, frames: I) -> Result<usize, String>
+where
+    I: IntoIterator<Item = &'a CanFrame>,
+{
+    let mut writer = BlfWriter::create(path)?;
+    for frame in frames {
+        writer.write(frame)?;
+    }
+    writer.finish()
+}
+
+fn u16_at(bytes: &[u8], pos: usize) -> u16 {
+    u16::from_le_bytes([bytes[pos], bytes[pos + 1]])
+}
+
+fn u32_at(bytes: &[u8], pos: usize) -> u32 {
+    u32::from_le_bytes(bytes[pos..pos + 4].try_into().unwrap())
+}
+
+fn u64_at(bytes: &[u8], pos: usize) -> u64 {
+    u64::from_le_bytes(bytes[pos..pos + 8].try_into().unwrap())
+}
+
+/// 解碼容器中的 CAN 物件；其他類型的物件回傳 None
+fn decode_object(object: &[u8], start: u64) -> Option<CanFrame> {
+    let header_size = u16_at(object, 4) as usize;
+    let object_type = u32_at(object, 12);
+    let offset = u64_at(object, 24);
+    let offset_us = match u32_at(object, 16) {
+        TIME_TEN_MICS => offset * 10,
+        TIME_ONE_NANS => offset / 1000,
+        _ => return None,
+    };
+    let data = object.get(header_size..)?;
+    let min_len = match object_type {
+        CAN_MESSAGE | CAN_MESSAGE2 => 16,
+        CAN_FD_MESSAGE => 84,
+        _ => return None,
+    };
+    if data.len() < min_len {
+        return None;
+    }
+    let channel = (u16_at(data, 0) as u32).saturating_sub(1);
+    let flags = data[2];
+    let dlc = data[3];
+    let raw_id = u32_at(data, 4);
+    let id = raw_id & !EXTENDED_ID_FLAG;
+    let mut frame = if object_type == CAN_FD_MESSAGE && data[13] & EDL != 0 {
+        let len = (data[14] as usize).min(MAX_PAYLOAD_LEN);
+        CanFrame::new_fd(channel, id, &data[20..20 + len], data[13] & BRS != 0)
+    } else if flags & REMOTE_FLAG != 0 {
+        CanFrame::new_remote(channel, id, dlc.min(8))
+    } else {
+        let payload = if object_type == CAN_FD_MESSAGE {
+            &data[20..20 + (data[14] as usize).min(8)]
+        } else {
+            &data[8..8 + (dlc as usize).min(8)]
+        };
+        CanFrame::new(channel, id, payload)
+    };
+    frame.esi = object_type == CAN_FD_MESSAGE && data[13] & ESI != 0;
+    frame.extended = raw_id & EXTENDED_ID_FLAG != 0;
+    if flags & DIR_TX != 0 {
+        frame.direction = FrameDirection::Tx;
+    }
+    frame.timestamp = start + offset_us;
+    Some(frame)
+}
+
+/// 讀取 BLF 檔中的 CAN 與 CAN FD 訊框
+pub fn read_blf(path: &str) -> Result<Vec<CanFrame>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let invalid = || format!("{}: not a valid BLF file", path);
+    if bytes.len() < FILE_HEADER_SIZE || &bytes[..4] != b"LOGG" {
+        return Err(invalid());
+    }
+    let mut start_fields = [0u16; 8];
+    for (i, field) in start_fields.iter_mut().enumerate() {
+        *field = u16_at(&bytes, 40 + i * 2);
+    }
+    let start = system_time_micros(start_fields).ok_or_else(invalid)?;
+
+    // 容器解壓縮後串接，物件可能跨越容器
+    let mut stream = Vec::new();
+    let mut pos = u32_at(&bytes, 4) as usize;
+    while pos + OBJ_HEADER_BASE_SIZE <= bytes.len() {
+        if &bytes[pos..pos + 4] != b"LOBJ" {
+            return Err(invalid());
+        }
+        let object_size = u32_at(&bytes, pos + 8) as usize;
+        let object = bytes
+            .get(pos..pos + object_size)
+            .filter(|_| object_size >= OBJ_HEADER_BASE_SIZE)
+            .ok_or_else(invalid)?;
+        if u32_at(object, 12) == LOG_CONTAINER {
+            let body = object
+                .get(OBJ_HEADER_BASE_SIZE + LOG_CONTAINER_SIZE..)
+                .ok_or_else(invalid)?;
+            match u16_at(object, OBJ_HEADER_BASE_SIZE) {
+                NO_COMPRESSION => stream.extend_from_slice(body),
+                ZLIB_DEFLATE => {
+                    ZlibDecoder::new(body)
+                        .read_to_end(&mut stream)
+                        .map_err(|e| format!("{}: {}", path, e))?;
+                }
+                method => return Err(format!("{}: unsupported compression {}", path, method)),
+            }
+        }
+        pos += object_size + object_size % 4;
+    }
+
+    let mut frames = Vec::new();
+    let mut pos = 0;
+    while pos + OBJ_HEADER_BASE_SIZE + OBJ_HEADER_V1_SIZE <= stream.len() {
+        if &stream[pos..pos + 4] != b"LOBJ" {
+            return Err(invalid());
+        }
+        let object_size = u32_at(&stream, pos + 8) as usize;
+        let object = stream
+            .get(pos..pos + object_size)
+            .filter(|_| object_size >= OBJ_HEADER_BASE_SIZE + OBJ_HEADER_V1_SIZE)
+            .ok_or_else(invalid)?;
+        frames.extend(decode_object(object, start));
+        pos += object_size + object_size % 4;
+    }
+    Ok(frames)
+}
@@ -0,0 +1,61 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::wire::encode_datagram;
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+
+/// 將每個收到的訊框以 UDP 送往 multicast 群組
+pub struct UdpBroadcaster {
+    socket: UdpSocket,
+    target: SocketAddr,
+    sent: u64,
+    failed: u64,
+}
+
+impl UdpBroadcaster {
+    /// 建立 broadcaster，`target` 例如 "239.255.0.1:5555"；
+    /// 只支援 IPv4，標準函式庫無法設定 IPv6 multicast 的 hop limit
+    pub fn new(target: &str, ttl: u32) -> Result<Self, String> {
+        let target: SocketAddr = target
+            .parse()
+            .map_err(|e| format!("Invalid multicast address '{}': {}", target, e))?;
+        if !target.ip().is_multicast() {
+            return Err(format!("{} is not a multicast address", target.ip()));
+        }
+        if target.is_ipv6() {
+            return Err(format!(
+                "{} is an IPv6 address; only IPv4 multicast (224.0.0.0/4) is supported",
+                target.ip()
+            ));
+        }
+        let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0))
+            .map_err(|e| format!("Failed to bind UDP socket: {}", e))?;
+        socket
+            .set_multicast_ttl_v4(ttl)
+            .map_err(|e| format!("Failed to set multicast TTL: {}", e))?;
+        Ok(Self {
+            socket,
+            target,
+            sent: 0,
+            failed: 0,
+        })
+    }
+
+    /// 送出一筆訊框；失敗只累計次數，避免在高流量下洗版 Log
+    pub fn send(&mut self, frame: &CanFrame) {
+        match self.socket.send_to(&encode_datagram(frame), self.target) {
+            Ok(_) => self.sent += 1,
+            Err(_) => self.failed += 1,
+        }
+    }
+
+    pub fn target(&self) -> SocketAddr {
+        self.target
+    }
+
+    pub fn sent(&self) -> u64 {
+        self.sent
+    }
+
+    pub fn failed(&self) -> u64 {
+        self.failed
+    }
+}
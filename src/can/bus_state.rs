@@ -0,0 +1,98 @@
+use crate::can::log_event::LogEvent;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+/// 錯誤計數器達到此值時進入 error warning
+const WARNING_LIMIT: u8 = 96;
+/// 錯誤計數器達到此值時進入 error passive
+const PASSIVE_LIMIT: u8 = 128;
+
+/// CAN 控制器的錯誤狀態
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BusState {
+    #[default]
+    ErrorActive,
+    ErrorWarning,
+    ErrorPassive,
+    BusOff,
+}
+
+impl BusState {
+    /// 依 TEC/REC 判斷狀態；計數器只有 8 位元，bus-off 需由控制器旗標得知
+    pub fn from_counters(tec: u8, rec: u8, bus_off: bool) -> Self {
+        let worst = tec.max(rec);
+        if bus_off {
+            BusState::BusOff
+        } else if worst >= PASSIVE_LIMIT {
+            BusState::ErrorPassive
+        } else if worst >= WARNING_LIMIT {
+            BusState::ErrorWarning
+        } else {
+            BusState::ErrorActive
+        }
+    }
+}
+
+impl fmt::Display for BusState {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BusState::ErrorActive => "Error Active",
+            BusState::ErrorWarning => "Error Warning",
+            BusState::ErrorPassive => "Error Passive",
+            BusState::BusOff => "Bus Off",
+        })
+    }
+}
+
+/// 控制器狀態與錯誤計數器，介面不提供計數器時為 None
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ControllerStatus {
+    pub state: BusState,
+    pub tec: Option<u8>,
+    pub rec: Option<u8>,
+}
+
+/// 各通道最新的控制器狀態，由接收執行緒更新、介面讀取狀態指示
+#[derive(Debug, Clone, Default)]
+pub struct BusStatusBoard {
+    channels: Arc<Mutex<BTreeMap<u32, ControllerStatus>>>,
+}
+
+impl BusStatusBoard {
+    /// 更新通道狀態，狀態改變時回傳要記入 Log 的事件；首次更新以 error active 為前一狀態
+    pub fn update(&self, channel: u32, status: ControllerStatus) -> Option<LogEvent> {
+        let previous = self
+            .channels
+            .lock()
+            .unwrap()
+            .insert(channel, status)
+            .map_or(BusState::ErrorActive, |s| s.state);
+        if previous == status.state {
+            return None;
+        }
+        Some(match status.state {
+            BusState::ErrorActive => LogEvent::BusRecovered { channel },
+            BusState::BusOff => LogEvent::BusOff { channel },
+            state => LogEvent::BusErrorState {
+                channel,
+                state,
+                tec: status.tec,
+                rec: status.rec,
+            },
+        })
+    }
+
+    pub fn snapshot(&self) -> Vec<(u32, ControllerStatus)> {
+        self.channels
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(&channel, &status)| (channel, status))
+            .collect()
+    }
+
+    pub fn clear(&self) {
+        self.channels.lock().unwrap().clear();
+    }
+}
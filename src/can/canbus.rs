@@ -1,28 +1,445 @@
+use crate::can::bus_state::{BusState, BusStatusBoard, ControllerStatus};
 use crate::can::cantypes::*;
+use crate::can::log_event::LogEvent;
+use crate::can::timesync::TimestampCorrector;
+use crate::can::version::LibraryVersion;
 use flume::Sender;
 use libloading::Library;
-use std::ffi::c_void;
+use std::ffi::{c_char, c_void, CString};
 use std::sync::{
-    atomic::{AtomicBool, Ordering},
-    Arc, Mutex,
+    atomic::{AtomicBool, AtomicU32, AtomicU64, Ordering},
+    Arc, Mutex, MutexGuard,
 };
-use std::{thread, time::Duration};
+use std::thread;
+use std::time::{Duration, Instant};
 
 const SUCCESS: i32 = 1;
 const PCAN_ERROR_OK: u32 = 0;
+/// PCAN 不指定頻道，用於全域參數查詢與關閉所有頻道
+const PCAN_NONEBUS: u32 = 0x00;
+/// VCI_CAN_OBJ.SendType：0 正常傳送，2 自發自收
+const VCI_SEND_NORMAL: u8 = 0;
+const VCI_SEND_SELF_RECEIVE: u8 = 2;
+/// ControlCAN 錯誤碼：CAN 控制器內部 FIFO 溢出
+const VCI_ERR_CAN_OVERFLOW: u32 = 0x0001;
+const PCAN_ERROR_OVERRUN: u32 = 0x0002;
+const PCAN_ERROR_QRCVEMPTY: u32 = 0x0020;
+const PCAN_ERROR_QOVERRUN: u32 = 0x0040;
+/// PCAN 匯流排狀態：錯誤計數器達到 light/heavy（warning）限制、error passive、bus-off
+const PCAN_ERROR_BUSLIGHT: u32 = 0x0004;
+const PCAN_ERROR_BUSHEAVY: u32 = 0x0008;
+const PCAN_ERROR_BUSPASSIVE: u32 = 0x40000;
+const PCAN_ERROR_BUSOFF: u32 = 0x0010;
+/// PCAN 頻道或硬體無效（例如轉接器被拔除）、頻道未初始化
+const PCAN_ERROR_ILLHANDLE: u32 = 0x1C00;
+const PCAN_ERROR_INITIALIZE: u32 = 0x4000000;
+/// PCAN 訊息類型：本機送出並回送的訊框
+const PCAN_MESSAGE_ECHO: u8 = 0x20;
+/// PCAN 訊息類型：遠端請求訊框
+const PCAN_MESSAGE_RTR: u8 = 0x01;
+/// PCAN 訊息類型：29-bit 延伸 ID
+const PCAN_MESSAGE_EXTENDED: u8 = 0x02;
+/// PCAN 訊息類型：CAN FD 訊框、位元率切換、錯誤狀態指示
+const PCAN_MESSAGE_FD: u8 = 0x04;
+const PCAN_MESSAGE_BRS: u8 = 0x08;
+const PCAN_MESSAGE_ESI: u8 = 0x10;
+/// PCAN 訊息類型：錯誤訊框與狀態訊息，不是匯流排上的資料訊框
+const PCAN_MESSAGE_ERRFRAME: u8 = 0x40;
+const PCAN_MESSAGE_STATUS: u8 = 0x80;
+
+/// 接收迴圈的設計目標：1 Mbit/s 滿載約 8000 frames/s（每通道）。
+///
+/// 迴圈先查詢待收筆數再一次讀出（每次最多 `RECEIVE_BATCH` 筆），只有在沒有待收訊框時
+/// 才等待 `IDLE_WAIT`；滿載時每次等待最多累積約 8 筆，遠低於裝置緩衝容量。
+/// 是否跟得上可由 `ReceiveStats` 的溢出次數與最大待收筆數確認。
+const RECEIVE_BATCH: usize = 1000;
+const IDLE_WAIT: Duration = Duration::from_millis(1);
+/// ControlCAN 查詢錯誤資訊（含 FIFO 溢出）與 PCAN 查詢匯流排狀態的間隔
+const ERR_INFO_INTERVAL: Duration = Duration::from_millis(100);
+/// 錯誤訊框彙整後回報的間隔，避免匯流排故障時洗版
+const ERROR_FRAME_REPORT_INTERVAL: Duration = Duration::from_secs(1);
+
+/// 接收路徑的計數快照
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReceiveStats {
+    /// 收到的訊框數
+    pub frames: u64,
+    /// 實際讀取（有資料）的次數
+    pub reads: u64,
+    /// 裝置或驅動回報的接收溢出次數，非 0 表示有訊框遺失
+    pub overruns: u64,
+    /// 觀察到的最大待收筆數
+    pub max_pending: u64,
+}
+
+/// 接收執行緒更新的計數
+#[derive(Debug, Default)]
+pub struct ReceiveCounters {
+    frames: AtomicU64,
+    reads: AtomicU64,
+    overruns: AtomicU64,
+    max_pending: AtomicU64,
+}
+
+impl ReceiveCounters {
+    fn record_read(&self, frames: u64, pending: u64) {
+        self.frames.fetch_add(frames, Ordering::Relaxed);
+        self.reads.fetch_add(1, Ordering::Relaxed);
+        self.max_pending.fetch_max(pending, Ordering::Relaxed);
+    }
+
+    fn record_overrun(&self) {
+        self.overruns.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ReceiveStats {
+        ReceiveStats {
+            frames: self.frames.load(Ordering::Relaxed),
+            reads: self.reads.load(Ordering::Relaxed),
+            overruns: self.overruns.load(Ordering::Relaxed),
+            max_pending: self.max_pending.load(Ordering::Relaxed),
+        }
+    }
+}
 
 /// 定義共通 CAN 介面操作
 pub trait CanInterface {
     /// 開啟裝置並初始化所有通道
-    fn open_device(&self, log_tx: Sender<String>) -> Result<(), String>;
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String>;
     /// 關閉裝置
-    fn close_device(&self, log_tx: Sender<String>);
+    fn close_device(&self, log_tx: Sender<LogEvent>);
     /// 啟動接收訊息（內部 spawn 執行緒，並儲存 JoinHandle）
-    fn start_receiving(&self, log_tx: Sender<String>, data_tx: Sender<String>);
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>);
     /// 停止接收訊息，並等待所有接收執行緒退出
     fn stop_receiving(&self);
     /// 讀取並回報板卡資訊
-    fn read_board_info(&self, log_tx: Sender<String>);
+    fn read_board_info(&self, log_tx: Sender<LogEvent>);
+    /// 送出單一訊框
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String>;
+    /// 介面是否會把送出的訊框以 TX 標記回送到接收端；
+    /// 回傳 false 時由 `SharedCan` 在本地回送
+    fn echoes_transmit(&self) -> bool {
+        false
+    }
+    /// 接收路徑的計數，不支援的介面回傳全 0
+    fn receive_stats(&self) -> ReceiveStats {
+        ReceiveStats::default()
+    }
+    /// 開啟裝置時查得的函式庫版本
+    fn library_versions(&self) -> Vec<LibraryVersion> {
+        Vec::new()
+    }
+    /// 各通道最新的控制器錯誤狀態，不支援的介面回傳空集合
+    fn bus_status(&self) -> Vec<(u32, ControllerStatus)> {
+        Vec::new()
+    }
+    /// 清除驅動中尚未讀取的接收訊框
+    fn clear_receive_buffer(&self) -> Result<(), String> {
+        Err("Clearing the receive buffer is not supported by this interface".to_string())
+    }
+    /// 檢查裝置是否仍可使用（例如 USB 轉接器被拔除），供自動重新連線判斷
+    fn check_device(&self) -> Result<(), String> {
+        Ok(())
+    }
+    /// 接收訊框所標示的通道（後端自己的編號）；單通道介面使用通道 0
+    fn channels(&self) -> Vec<u32> {
+        vec![0]
+    }
+}
+
+/// 接收中但有接收執行緒已結束，表示讀取發生無法恢復的錯誤
+pub fn receive_thread_stopped(
+    receiving: &AtomicBool,
+    join_handles: &Mutex<Vec<thread::JoinHandle<()>>>,
+) -> bool {
+    receiving.load(Ordering::SeqCst)
+        && join_handles
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|handle| handle.is_finished())
+}
+
+type TxListener = Box<dyn Fn(&CanFrame) + Send>;
+type TxErrorListener = Box<dyn Fn(&CanFrame, &str) + Send>;
+
+/// 同時啟動的介面通道重疊時，後加入的介面以此為單位位移通道
+pub const CHANNEL_BLOCK: u32 = 16;
+
+/// 啟動中的介面；`id` 在加入時分配，其他介面移除後仍不變
+pub struct ActiveInterface {
+    pub id: u32,
+    pub name: String,
+    /// 介面上顯示的通道 = 後端通道 + base，避免多個介面都使用通道 0 時無法區分
+    pub base: u32,
+    pub app: Box<dyn CanInterface + Send>,
+}
+
+impl ActiveInterface {
+    /// 介面上顯示的通道
+    pub fn channels(&self) -> Vec<u32> {
+        self.app
+            .channels()
+            .into_iter()
+            .map(|c| c + self.base)
+            .collect()
+    }
+
+    /// 開始接收；base 不為 0 時經由轉送執行緒在接收訊框的通道加上 base，
+    /// 後端的接收執行緒結束後轉送執行緒隨之結束
+    pub fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
+        if self.base == 0 {
+            self.app.start_receiving(log_tx, data_tx);
+            return;
+        }
+        let (relay_tx, relay_rx) = flume::unbounded::<CanFrame>();
+        let base = self.base;
+        thread::spawn(move || {
+            for mut frame in relay_rx.iter() {
+                frame.channel += base;
+                if data_tx.send(frame).is_err() {
+                    break;
+                }
+            }
+        });
+        self.app.start_receiving(log_tx, relay_tx);
+    }
+}
+
+/// GUI 與背景執行緒共用的 CAN 介面清單，可同時啟動多個裝置；
+/// 所有傳送都經由此處以便依通道選擇介面並通知 TX 監聽者
+#[derive(Clone, Default)]
+pub struct SharedCan {
+    apps: Arc<Mutex<Vec<ActiveInterface>>>,
+    next_id: Arc<AtomicU32>,
+    tx_listeners: Arc<Mutex<Vec<TxListener>>>,
+    tx_error_listeners: Arc<Mutex<Vec<TxErrorListener>>>,
+    echo_listeners: Arc<Mutex<Vec<TxListener>>>,
+}
+
+impl SharedCan {
+    /// 取得目前啟動中的介面
+    pub fn lock(&self) -> MutexGuard<'_, Vec<ActiveInterface>> {
+        self.apps.lock().unwrap()
+    }
+
+    /// 加入已開啟的介面並回傳其 id；通道與啟動中的介面重疊時分配新的 base。
+    /// 接收應在加入後以 `start_receiving` 啟動，才會套用 base
+    pub fn attach(&self, name: impl Into<String>, app: Box<dyn CanInterface + Send>) -> u32 {
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+        let mut apps = self.lock();
+        let base = free_base(&apps, &app.channels());
+        apps.push(ActiveInterface {
+            id,
+            name: name.into(),
+            base,
+            app,
+        });
+        id
+    }
+
+    /// 啟動指定介面的接收
+    pub fn start_receiving(&self, id: u32, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
+        if let Some(interface) = self.lock().iter().find(|a| a.id == id) {
+            interface.start_receiving(log_tx, data_tx);
+        }
+    }
+
+    /// 放回以 `detach` 暫時移出的介面，保留原本的 id 與 base；
+    /// 期間加入的介面已佔用它的通道時退回
+    pub fn restore(&self, interface: ActiveInterface) -> Result<(), ActiveInterface> {
+        let mut apps = self.lock();
+        let channels = interface.channels();
+        if apps
+            .iter()
+            .any(|a| a.channels().iter().any(|c| channels.contains(c)))
+        {
+            return Err(interface);
+        }
+        apps.push(interface);
+        Ok(())
+    }
+
+    /// 移出指定的介面，不會停止或關閉它
+    pub fn detach(&self, id: u32) -> Option<ActiveInterface> {
+        let mut apps = self.lock();
+        let index = apps.iter().position(|a| a.id == id)?;
+        Some(apps.remove(index))
+    }
+
+    /// 移出全部介面
+    pub fn detach_all(&self) -> Vec<ActiveInterface> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().is_empty()
+    }
+
+    /// 各介面的 id、名稱與顯示的通道
+    pub fn interfaces(&self) -> Vec<(u32, String, Vec<u32>)> {
+        self.lock()
+            .iter()
+            .map(|a| (a.id, a.name.clone(), a.channels()))
+            .collect()
+    }
+
+    /// 各介面接收計數的合計，未啟動時回傳 None
+    pub fn receive_stats(&self) -> Option<ReceiveStats> {
+        let apps = self.lock();
+        if apps.is_empty() {
+            return None;
+        }
+        Some(apps.iter().map(|a| a.app.receive_stats()).fold(
+            ReceiveStats::default(),
+            |total, stats| ReceiveStats {
+                frames: total.frames + stats.frames,
+                reads: total.reads + stats.reads,
+                overruns: total.overruns + stats.overruns,
+                max_pending: total.max_pending.max(stats.max_pending),
+            },
+        ))
+    }
+
+    /// 各介面的函式庫版本，未啟動時回傳空集合
+    pub fn library_versions(&self) -> Vec<LibraryVersion> {
+        let mut versions: Vec<LibraryVersion> = Vec::new();
+        for version in self.lock().iter().flat_map(|a| a.app.library_versions()) {
+            if !versions.contains(&version) {
+                versions.push(version);
+            }
+        }
+        versions
+    }
+
+    /// 各介面各通道的控制器狀態，未啟動時回傳空集合
+    pub fn bus_status(&self) -> Vec<(u32, ControllerStatus)> {
+        let mut status: Vec<_> = self
+            .lock()
+            .iter()
+            .flat_map(|a| {
+                a.app
+                    .bus_status()
+                    .into_iter()
+                    .map(|(channel, status)| (channel + a.base, status))
+            })
+            .collect();
+        status.sort_by_key(|&(channel, _)| channel);
+        status
+    }
+
+    /// 檢查各介面的裝置，回傳失效介面的 id 與原因
+    pub fn failed_devices(&self) -> Vec<(u32, String)> {
+        self.lock()
+            .iter()
+            .filter_map(|a| {
+                a.app
+                    .check_device()
+                    .err()
+                    .map(|e| (a.id, format!("{}: {}", a.name, e)))
+            })
+            .collect()
+    }
+
+    /// 清除各介面驅動中尚未讀取的訊框，回傳第一個錯誤
+    pub fn clear_receive_buffer(&self) -> Result<(), String> {
+        let apps = self.lock();
+        if apps.is_empty() {
+            return Err("CAN not started".to_string());
+        }
+        apps.iter()
+            .map(|a| a.app.clear_receive_buffer())
+            .fold(Ok(()), Result::and)
+    }
+
+    /// 註冊一個在每次成功傳送後被呼叫的監聽者
+    pub fn on_transmit<F>(&self, listener: F)
+    where
+        F: Fn(&CanFrame) + Send + 'static,
+    {
+        self.tx_listeners.lock().unwrap().push(Box::new(listener));
+    }
+
+    /// 註冊本地回送的監聽者：介面本身不回送送出的訊框時，
+    /// 每次成功傳送後以標記為 TX 的訊框呼叫，用於把傳送穿插進追蹤紀錄
+    pub fn on_local_echo<F>(&self, listener: F)
+    where
+        F: Fn(&CanFrame) + Send + 'static,
+    {
+        self.echo_listeners.lock().unwrap().push(Box::new(listener));
+    }
+
+    /// 註冊一個在介面回報傳送失敗時被呼叫的監聽者
+    pub fn on_transmit_error<F>(&self, listener: F)
+    where
+        F: Fn(&CanFrame, &str) + Send + 'static,
+    {
+        self.tx_error_listeners
+            .lock()
+            .unwrap()
+            .push(Box::new(listener));
+    }
+
+    /// 透過擁有該通道的介面送出訊框（通道先扣除介面的 base），
+    /// 只有一個介面時不論通道都由它送出；成功後以送出時間通知監聽者
+    pub fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        let (result, echoed) = {
+            let apps = self.lock();
+            let (app, backend_frame) =
+                match apps.iter().find(|a| a.channels().contains(&frame.channel)) {
+                    Some(a) => {
+                        let mut backend_frame = *frame;
+                        backend_frame.channel -= a.base;
+                        (&a.app, backend_frame)
+                    }
+                    None if apps.len() == 1 => (&apps[0].app, *frame),
+                    None if apps.is_empty() => return Err("CAN not started".to_string()),
+                    None => return Err(format!("No interface on channel {}", frame.channel)),
+                };
+            (app.send_frame(&backend_frame), app.echoes_transmit())
+        };
+        if let Err(e) = result {
+            for listener in self.tx_error_listeners.lock().unwrap().iter() {
+                listener(frame, &e);
+            }
+            return Err(e);
+        }
+        let mut sent = *frame;
+        sent.timestamp = now_micros();
+        sent.direction = FrameDirection::Tx;
+        for listener in self.tx_listeners.lock().unwrap().iter() {
+            listener(&sent);
+        }
+        if !echoed {
+            for listener in self.echo_listeners.lock().unwrap().iter() {
+                listener(&sent);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// 新介面的 base：取 `CHANNEL_BLOCK` 的倍數中最小、不與啟動中介面的通道重疊者
+fn free_base(apps: &[ActiveInterface], channels: &[u32]) -> u32 {
+    let used: Vec<u32> = apps.iter().flat_map(|a| a.channels()).collect();
+    (0..)
+        .map(|n| n * CHANNEL_BLOCK)
+        .find(|base| channels.iter().all(|c| !used.contains(&(c + base))))
+        .unwrap_or(0)
+}
+
+/// 將通用訊框轉為 Classic CAN 的 8 bytes 資料，不支援的協定回傳錯誤
+fn classic_payload(frame: &CanFrame) -> Result<[u8; 8], String> {
+    if frame.protocol != FrameProtocol::Classic || frame.data.len() > 8 {
+        return Err(format!(
+            "{:?} frames with {} bytes are not supported by this backend",
+            frame.protocol,
+            frame.data.len()
+        ));
+    }
+    let mut data = [0u8; 8];
+    data[..frame.data.len()].copy_from_slice(frame.payload());
+    Ok(data)
 }
 
 /// 封裝 ControlCAN 動態函式庫
@@ -33,7 +450,12 @@ pub struct CanLibrary {
     pub vci_init_can: unsafe extern "C" fn(u32, u32, u32, *const VciInitConfig) -> i32,
     pub vci_start_can: unsafe extern "C" fn(u32, u32, u32) -> i32,
     pub vci_receive: unsafe extern "C" fn(u32, u32, u32, *mut VciCanObj, u32, i32) -> i32,
+    pub vci_transmit: unsafe extern "C" fn(u32, u32, u32, *const VciCanObj, u32) -> i32,
     pub vci_read_board_info: unsafe extern "C" fn(u32, u32, *mut VciBoardInfo) -> i32,
+    pub vci_get_receive_num: unsafe extern "C" fn(u32, u32, u32) -> u32,
+    pub vci_read_err_info: unsafe extern "C" fn(u32, u32, u32, *mut VciErrInfo) -> i32,
+    pub vci_read_can_status: unsafe extern "C" fn(u32, u32, u32, *mut VciCanStatus) -> i32,
+    pub vci_clear_buffer: unsafe extern "C" fn(u32, u32, u32) -> i32,
 }
 
 impl CanLibrary {
@@ -53,9 +475,24 @@ impl CanLibrary {
                     .get(b"VCI_StartCAN")
                     .expect("Failed to get VCI_StartCAN"),
                 vci_receive: *lib.get(b"VCI_Receive").expect("Failed to get VCI_Receive"),
+                vci_transmit: *lib
+                    .get(b"VCI_Transmit")
+                    .expect("Failed to get VCI_Transmit"),
                 vci_read_board_info: *lib
                     .get(b"VCI_ReadBoardInfo")
                     .expect("Failed to get VCI_ReadBoardInfo"),
+                vci_get_receive_num: *lib
+                    .get(b"VCI_GetReceiveNum")
+                    .expect("Failed to get VCI_GetReceiveNum"),
+                vci_read_err_info: *lib
+                    .get(b"VCI_ReadErrInfo")
+                    .expect("Failed to get VCI_ReadErrInfo"),
+                vci_read_can_status: *lib
+                    .get(b"VCI_ReadCANStatus")
+                    .expect("Failed to get VCI_ReadCANStatus"),
+                vci_clear_buffer: *lib
+                    .get(b"VCI_ClearBuffer")
+                    .expect("Failed to get VCI_ClearBuffer"),
             })
         }
     }
@@ -69,6 +506,11 @@ pub struct CanApp {
     dev_type: u32,
     dev_index: u32,
     can_channels: Vec<(u32, VciCanBaudRate)>,
+    mode: ControllerMode,
+    self_reception: bool,
+    counters: Arc<ReceiveCounters>,
+    bus_status: BusStatusBoard,
+    versions: Mutex<Vec<LibraryVersion>>,
     join_handles: Arc<Mutex<Vec<thread::JoinHandle<()>>>>,
 }
 
@@ -83,10 +525,22 @@ impl CanApp {
             dev_type,
             dev_index,
             can_channels,
+            mode: ControllerMode::Normal,
+            self_reception: false,
+            counters: Arc::new(ReceiveCounters::default()),
+            bus_status: BusStatusBoard::default(),
+            versions: Mutex::new(Vec::new()),
             join_handles: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// 設定控制器模式與自發自收（送出的訊框同時回到本機接收）
+    pub fn with_mode(mut self, mode: ControllerMode, self_reception: bool) -> Self {
+        self.mode = mode;
+        self.self_reception = self_reception;
+        self
+    }
+
     /// 封裝 unsafe 呼叫：開啟裝置
     unsafe fn open_device_unsafe(&self) -> Result<(), String> {
         let status = (self.can_lib.vci_open_device)(self.dev_type, self.dev_index, 0);
@@ -107,7 +561,7 @@ impl CanApp {
             filter: 1,
             timing0,
             timing1,
-            mode: 0,
+            mode: self.mode.vci_mode(),
         };
         let init_status =
             (self.can_lib.vci_init_can)(self.dev_type, self.dev_index, channel, &config);
@@ -118,6 +572,16 @@ impl CanApp {
         }
     }
 
+    /// 開啟裝置讀取板卡資訊後立即關閉，用於啟動前得知裝置的通道數
+    pub fn probe_channel_count(&self) -> Result<u8, String> {
+        unsafe {
+            self.open_device_unsafe()?;
+            let board_info = self.read_board_info_unsafe();
+            (self.can_lib.vci_close_device)(self.dev_type, self.dev_index);
+            Ok(board_info?.can_num)
+        }
+    }
+
     /// 封裝 unsafe 呼叫：讀取板卡資訊
     unsafe fn read_board_info_unsafe(&self) -> Result<VciBoardInfo, String> {
         let mut board_info = VciBoardInfo::default();
@@ -132,26 +596,28 @@ impl CanApp {
 }
 
 impl CanInterface for CanApp {
-    fn open_device(&self, log_tx: Sender<String>) -> Result<(), String> {
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String> {
         unsafe {
             self.open_device_unsafe().map_err(|e| {
-                let _ = log_tx.send(e.clone());
+                let _ = log_tx.send(LogEvent::DeviceError { detail: e.clone() });
                 e
             })?;
-            let _ = log_tx.send("Device opened successfully".to_string());
+            let _ = log_tx.send(LogEvent::DeviceOpened {
+                backend: "ControlCAN".to_string(),
+            });
         }
 
         for &(channel, baud_rate) in &self.can_channels {
             unsafe {
                 self.init_channel(channel, baud_rate).map_err(|e| {
-                    let _ = log_tx.send(e.clone());
+                    let _ = log_tx.send(LogEvent::DeviceError { detail: e.clone() });
                     self.close_device(log_tx.clone());
                     e
                 })?;
-                let _ = log_tx.send(format!(
-                    "CAN Ch {} initialized (BaudRate: {:?})",
-                    channel, baud_rate
-                ));
+                let _ = log_tx.send(LogEvent::ChannelInitialized {
+                    channel,
+                    settings: format!("BaudRate: {:?}, Mode: {:?}", baud_rate, self.mode),
+                });
             }
         }
 
@@ -163,13 +629,22 @@ impl CanInterface for CanApp {
                     let serial_number = String::from_utf8_lossy(&board_info.str_serial_num)
                         .trim_matches('\0')
                         .to_string();
-                    let _ = log_tx.send(format!(
-                        "Board info: Serial={}, Firmware={}",
-                        serial_number, board_info.fw_version
-                    ));
+                    let _ = log_tx.send(LogEvent::BoardInfo {
+                        serial: serial_number,
+                        firmware: board_info.fw_version.to_string(),
+                    });
+                    // ControlCAN 沒有獨立的版本查詢，介面庫與驅動版本取自板卡資訊
+                    let versions = vec![
+                        LibraryVersion::from_vci("ControlCAN", board_info.in_version),
+                        LibraryVersion::from_vci("ControlCAN driver", board_info.dr_version),
+                    ];
+                    for version in &versions {
+                        version.report(&log_tx);
+                    }
+                    *self.versions.lock().unwrap() = versions;
                 }
                 Err(e) => {
-                    let _ = log_tx.send(e);
+                    let _ = log_tx.send(LogEvent::DeviceError { detail: e });
                     return Err("Failed to read board info".to_string());
                 }
             }
@@ -178,61 +653,139 @@ impl CanInterface for CanApp {
         Ok(())
     }
 
-    fn close_device(&self, log_tx: Sender<String>) {
+    fn close_device(&self, log_tx: Sender<LogEvent>) {
         unsafe {
             let status = (self.can_lib.vci_close_device)(self.dev_type, self.dev_index);
-            let _ = log_tx.send(format!("Device closed, Status: {}", status));
+            let _ = log_tx.send(LogEvent::DeviceClosed {
+                backend: "ControlCAN".to_string(),
+                status: Some(status as i64),
+            });
             self.is_can_initialized.store(false, Ordering::SeqCst);
         }
     }
 
-    fn start_receiving(&self, log_tx: Sender<String>, data_tx: Sender<String>) {
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
         self.receiving.store(true, Ordering::SeqCst);
         let dev_type = self.dev_type;
         let dev_index = self.dev_index;
         let receiving_flag = Arc::clone(&self.receiving);
         let can_lib = Arc::clone(&self.can_lib);
         let join_handles_clone = Arc::clone(&self.join_handles);
+        self.bus_status.clear();
 
         for &(channel, _) in &self.can_channels {
             let log_tx_clone = log_tx.clone();
             let data_tx_clone = data_tx.clone();
             let receiving_flag_channel = Arc::clone(&receiving_flag);
             let can_lib_channel = Arc::clone(&can_lib);
+            let counters = Arc::clone(&self.counters);
+            let bus_status = self.bus_status.clone();
             let handle = thread::spawn(move || {
-                // 啟動該通道
+                // 啟動該通道，先丟棄初始化後殘留在驅動中的舊訊框
                 unsafe {
+                    (can_lib_channel.vci_clear_buffer)(dev_type, dev_index, channel);
                     let start_status =
                         (can_lib_channel.vci_start_can)(dev_type, dev_index, channel);
                     if start_status != SUCCESS {
-                        let _ = log_tx_clone.send(format!(
-                            "CAN start failed on channel {}, Error Code: {}",
-                            channel, start_status
-                        ));
+                        let _ = log_tx_clone.send(LogEvent::ChannelStartFailed {
+                            channel,
+                            code: start_status as i64,
+                        });
                         return;
                     }
-                    let _ = log_tx_clone.send(format!("CAN Ch {} started", channel));
+                    let _ = log_tx_clone.send(LogEvent::ChannelStarted { channel });
                 }
+                let mut corrector = TimestampCorrector::controlcan();
+                let mut buffer = vec![VciCanObj::default(); RECEIVE_BATCH];
+                let mut last_err_check = Instant::now();
                 while receiving_flag_channel.load(Ordering::SeqCst) {
-                    let mut can_obj = VciCanObj::default();
+                    if last_err_check.elapsed() >= ERR_INFO_INTERVAL {
+                        last_err_check = Instant::now();
+                        let mut err_info = VciErrInfo::default();
+                        let status = unsafe {
+                            (can_lib_channel.vci_read_err_info)(
+                                dev_type,
+                                dev_index,
+                                channel,
+                                &mut err_info,
+                            )
+                        };
+                        if status == SUCCESS && err_info.err_code & VCI_ERR_CAN_OVERFLOW != 0 {
+                            counters.record_overrun();
+                            let _ = log_tx_clone.send(LogEvent::ReceiveOverrun {
+                                channel,
+                                code: None,
+                            });
+                        }
+                        // 錯誤計數器與 bus-off 旗標取自控制器暫存器
+                        let mut can_status = VciCanStatus::default();
+                        let status = unsafe {
+                            (can_lib_channel.vci_read_can_status)(
+                                dev_type,
+                                dev_index,
+                                channel,
+                                &mut can_status,
+                            )
+                        };
+                        if status == SUCCESS {
+                            let (tec, rec) = (can_status.reg_te_counter, can_status.reg_re_counter);
+                            let controller = ControllerStatus {
+                                state: BusState::from_counters(tec, rec, can_status.bus_off()),
+                                tec: Some(tec),
+                                rec: Some(rec),
+                            };
+                            if let Some(event) = bus_status.update(channel, controller) {
+                                let _ = log_tx_clone.send(event);
+                            }
+                        }
+                    }
+                    let pending = unsafe {
+                        (can_lib_channel.vci_get_receive_num)(dev_type, dev_index, channel)
+                    };
+                    if pending == 0 {
+                        thread::sleep(IDLE_WAIT);
+                        continue;
+                    }
                     let received_frames = unsafe {
                         (can_lib_channel.vci_receive)(
                             dev_type,
                             dev_index,
                             channel,
-                            &mut can_obj,
-                            1,
-                            500,
+                            buffer.as_mut_ptr(),
+                            (pending as usize).min(RECEIVE_BATCH) as u32,
+                            0,
                         )
                     };
-                    if received_frames > 0 {
-                        let data = &can_obj.data[..(can_obj.data_len as usize)];
-                        let msg = format!("CH={} ID=0x{:X}, Data={:?}", channel, can_obj.id, data);
-                        let _ = data_tx_clone.send(msg);
+                    if received_frames <= 0 {
+                        thread::sleep(IDLE_WAIT);
+                        continue;
+                    }
+                    counters.record_read(received_frames as u64, pending as u64);
+                    let host = now_micros();
+                    for can_obj in &buffer[..received_frames as usize] {
+                        let mut frame = CanFrame::new(
+                            channel,
+                            can_obj.id,
+                            &can_obj.data[..(can_obj.data_len.min(8) as usize)],
+                        );
+                        frame.extended = can_obj.extern_flag != 0;
+                        frame.rtr = can_obj.remote_flag != 0;
+                        // time_flag 為 1 時裝置時間戳記有效
+                        frame.timestamp = if can_obj.time_flag == 1 {
+                            let resyncs = corrector.resyncs();
+                            let timestamp = corrector.correct(can_obj.time_stamp, host);
+                            if corrector.resyncs() != resyncs {
+                                let _ = log_tx_clone.send(LogEvent::TimestampResync { channel });
+                            }
+                            frame.device_timestamp = Some(corrector.device_micros());
+                            timestamp
+                        } else {
+                            host
+                        };
+                        let _ = data_tx_clone.send(frame);
                     }
-                    thread::sleep(Duration::from_millis(10));
                 }
-                let _ = log_tx_clone.send(format!("CAN Ch {} stopped receiving", channel));
+                let _ = log_tx_clone.send(LogEvent::ChannelStopped { channel });
             });
             // 將執行緒的 JoinHandle 存起來
             join_handles_clone.lock().unwrap().push(handle);
@@ -245,14 +798,16 @@ impl CanInterface for CanApp {
         let mut handles = self.join_handles.lock().unwrap();
         while let Some(handle) = handles.pop() {
             if let Err(e) = handle.join() {
-                eprintln!("Error joining thread: {:?}", e);
+                tracing::error!("Error joining thread: {:?}", e);
             }
         }
     }
 
-    fn read_board_info(&self, log_tx: Sender<String>) {
+    fn read_board_info(&self, log_tx: Sender<LogEvent>) {
         if !self.is_can_initialized.load(Ordering::SeqCst) {
-            let _ = log_tx.send("Error: CAN not initialized; cannot read board info".to_string());
+            let _ = log_tx.send(LogEvent::NotInitialized {
+                backend: "ControlCAN".to_string(),
+            });
             return;
         }
         unsafe {
@@ -261,17 +816,91 @@ impl CanInterface for CanApp {
                     let serial_number = String::from_utf8_lossy(&board_info.str_serial_num)
                         .trim_matches('\0')
                         .to_string();
-                    let _ = log_tx.send(format!(
-                        "Board info: Serial={}, Firmware={}",
-                        serial_number, board_info.fw_version
-                    ));
+                    let _ = log_tx.send(LogEvent::BoardInfo {
+                        serial: serial_number,
+                        firmware: board_info.fw_version.to_string(),
+                    });
                 }
                 Err(e) => {
-                    let _ = log_tx.send(e);
+                    let _ = log_tx.send(LogEvent::DeviceError { detail: e });
                 }
             }
         }
     }
+
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        if !self.is_can_initialized.load(Ordering::SeqCst) {
+            return Err("CAN not initialized; cannot transmit".to_string());
+        }
+        let payload = classic_payload(frame)?;
+        let can_obj = VciCanObj {
+            id: frame.id,
+            send_type: if self.self_reception {
+                VCI_SEND_SELF_RECEIVE
+            } else {
+                VCI_SEND_NORMAL
+            },
+            remote_flag: frame.rtr as u8,
+            extern_flag: frame.extended as u8,
+            data_len: frame.data.len() as u8,
+            data: payload,
+            ..Default::default()
+        };
+        let sent = unsafe {
+            (self.can_lib.vci_transmit)(self.dev_type, self.dev_index, frame.channel, &can_obj, 1)
+        };
+        if sent != 1 {
+            Err(format!(
+                "CAN Ch {} transmit failed, Error Code: {}",
+                frame.channel, sent
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn receive_stats(&self) -> ReceiveStats {
+        self.counters.snapshot()
+    }
+
+    fn library_versions(&self) -> Vec<LibraryVersion> {
+        self.versions.lock().unwrap().clone()
+    }
+
+    fn bus_status(&self) -> Vec<(u32, ControllerStatus)> {
+        self.bus_status.snapshot()
+    }
+
+    fn channels(&self) -> Vec<u32> {
+        self.can_channels
+            .iter()
+            .map(|&(channel, _)| channel)
+            .collect()
+    }
+
+    fn check_device(&self) -> Result<(), String> {
+        // 拔除後 VCI_ReadBoardInfo 會失敗，接收迴圈本身只會讀到 0 筆
+        unsafe { self.read_board_info_unsafe() }
+            .map(|_| ())
+            .map_err(|_| "ControlCAN device not responding".to_string())
+    }
+
+    fn clear_receive_buffer(&self) -> Result<(), String> {
+        if !self.is_can_initialized.load(Ordering::SeqCst) {
+            return Err("CAN not initialized; cannot clear buffer".to_string());
+        }
+        for &(channel, _) in &self.can_channels {
+            let status =
+                unsafe { (self.can_lib.vci_clear_buffer)(self.dev_type, self.dev_index, channel) };
+            if status != SUCCESS {
+                return Err(format!(
+                    "CAN Ch {} clear buffer failed, Error Code: {}",
+                    channel, status
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
 /// 封裝 PCAN 動態函式庫
@@ -279,9 +908,16 @@ pub struct PcanLibrary {
     _lib: Arc<Library>,
     pub can_initialize: unsafe extern "C" fn(u32, u32, u32, u32, u32) -> u32,
     pub can_uninitialize: unsafe extern "C" fn(u32) -> u32,
-    pub can_read: unsafe extern "C" fn(u32, *mut PcanMsg) -> u32,
+    pub can_read: unsafe extern "C" fn(u32, *mut PcanMsg, *mut PcanTimestamp) -> u32,
+    pub can_write: unsafe extern "C" fn(u32, *const PcanMsg) -> u32,
     pub can_get_value: unsafe extern "C" fn(u32, u32, *mut c_void, u32) -> u32,
     pub can_set_value: unsafe extern "C" fn(u32, u32, *const c_void, u32) -> u32,
+    pub can_reset: unsafe extern "C" fn(u32) -> u32,
+    pub can_get_status: unsafe extern "C" fn(u32) -> u32,
+    /// FD 函式，舊版 PCANBasic 沒有這些函式時為 None
+    pub can_initialize_fd: Option<unsafe extern "C" fn(u32, *const c_char) -> u32>,
+    pub can_read_fd: Option<unsafe extern "C" fn(u32, *mut PcanMsgFd, *mut u64) -> u32>,
+    pub can_write_fd: Option<unsafe extern "C" fn(u32, *const PcanMsgFd) -> u32>,
 }
 
 impl PcanLibrary {
@@ -297,15 +933,150 @@ impl PcanLibrary {
                     .get(b"CAN_Uninitialize\0")
                     .expect("Failed to get CAN_Uninitialize"),
                 can_read: *lib.get(b"CAN_Read\0").expect("Failed to get CAN_Read"),
+                can_write: *lib.get(b"CAN_Write\0").expect("Failed to get CAN_Write"),
                 can_get_value: *lib
                     .get(b"CAN_GetValue\0")
                     .expect("Failed to get CAN_GetValue"),
                 can_set_value: *lib
                     .get(b"CAN_SetValue\0")
                     .expect("Failed to get CAN_SetValue"),
+                can_reset: *lib.get(b"CAN_Reset\0").expect("Failed to get CAN_Reset"),
+                can_get_status: *lib
+                    .get(b"CAN_GetStatus\0")
+                    .expect("Failed to get CAN_GetStatus"),
+                can_initialize_fd: lib.get(b"CAN_InitializeFD\0").ok().map(|f| *f),
+                can_read_fd: lib.get(b"CAN_ReadFD\0").ok().map(|f| *f),
+                can_write_fd: lib.get(b"CAN_WriteFD\0").ok().map(|f| *f),
             })
         }
     }
+
+    /// 讀取一筆訊息，FD 模式使用 CAN_ReadFD；失敗時回傳 PCAN 狀態碼。
+    /// 驅動提供的接收時間存入 `device_timestamp`
+    unsafe fn read_frame(&self, channel: u32, fd: bool) -> Result<PcanMessage, u32> {
+        let (id, msgtype, device_us, mut frame) = match self.can_read_fd.filter(|_| fd) {
+            Some(can_read_fd) => {
+                let mut msg = PcanMsgFd::default();
+                let mut timestamp = 0u64;
+                let status = can_read_fd(channel, &mut msg, &mut timestamp);
+                if status != PCAN_ERROR_OK {
+                    return Err(status);
+                }
+                let data = &msg.data[..dlc_to_len(msg.dlc)];
+                let frame = if msg.msgtype & PCAN_MESSAGE_FD != 0 {
+                    CanFrame::new_fd(channel, msg.id, data, msg.msgtype & PCAN_MESSAGE_BRS != 0)
+                } else {
+                    CanFrame::new(channel, msg.id, data)
+                };
+                (msg.id, msg.msgtype, timestamp, frame)
+            }
+            None => {
+                let mut msg = PcanMsg::default();
+                let mut timestamp = PcanTimestamp::default();
+                let status = (self.can_read)(channel, &mut msg, &mut timestamp);
+                if status != PCAN_ERROR_OK {
+                    return Err(status);
+                }
+                let frame = CanFrame::new(channel, msg.id, &msg.data[..(msg.len.min(8) as usize)]);
+                (msg.id, msg.msgtype, timestamp.total_micros(), frame)
+            }
+        };
+        // 狀態訊息的資料為 4 位元組 big-endian 的 PCAN 狀態碼；
+        // 錯誤訊框的 ID 為錯誤類型，資料依序為方向、ECC、REC、TEC
+        let byte = |index: usize| frame.payload().get(index).copied().unwrap_or(0);
+        if msgtype & PCAN_MESSAGE_STATUS != 0 {
+            return Ok(PcanMessage::Status(u32::from_be_bytes([
+                byte(0),
+                byte(1),
+                byte(2),
+                byte(3),
+            ])));
+        }
+        if msgtype & PCAN_MESSAGE_ERRFRAME != 0 {
+            return Ok(PcanMessage::ErrorFrame {
+                kind: id,
+                rec: byte(2),
+                tec: byte(3),
+            });
+        }
+        frame.timestamp = now_micros();
+        frame.device_timestamp = Some(device_us);
+        frame.extended = msgtype & PCAN_MESSAGE_EXTENDED != 0 || id > MAX_STANDARD_ID;
+        frame.rtr = msgtype & PCAN_MESSAGE_RTR != 0;
+        frame.esi = msgtype & PCAN_MESSAGE_ESI != 0;
+        if msgtype & PCAN_MESSAGE_ECHO != 0 {
+            frame.direction = FrameDirection::Tx;
+        }
+        Ok(PcanMessage::Frame(frame))
+    }
+}
+
+/// CAN_Read 讀出的訊息
+enum PcanMessage {
+    Frame(CanFrame),
+    /// 驅動回報的匯流排狀態變化
+    Status(u32),
+    /// 控制器偵測到的錯誤訊框
+    ErrorFrame {
+        kind: u32,
+        rec: u8,
+        tec: u8,
+    },
+}
+
+/// 由 PCAN 狀態碼判斷控制器錯誤狀態
+pub fn pcan_bus_state(status: u32) -> BusState {
+    if status & PCAN_ERROR_BUSOFF != 0 {
+        BusState::BusOff
+    } else if status & PCAN_ERROR_BUSPASSIVE != 0 {
+        BusState::ErrorPassive
+    } else if status & (PCAN_ERROR_BUSLIGHT | PCAN_ERROR_BUSHEAVY) != 0 {
+        BusState::ErrorWarning
+    } else {
+        BusState::ErrorActive
+    }
+}
+
+/// PCAN 錯誤訊框 ID 對應的錯誤類型
+pub fn pcan_error_kind(kind: u32) -> &'static str {
+    match kind {
+        1 => "bit error",
+        2 => "form error",
+        4 => "stuff error",
+        _ => "other error",
+    }
+}
+
+/// 通用訊框對應的 PCAN 訊息類型旗標
+fn pcan_msgtype(frame: &CanFrame) -> u8 {
+    let fd = frame.protocol == FrameProtocol::Fd;
+    (frame.rtr as u8 * PCAN_MESSAGE_RTR)
+        | (frame.extended as u8 * PCAN_MESSAGE_EXTENDED)
+        | (fd as u8 * PCAN_MESSAGE_FD)
+        | ((fd && frame.brs) as u8 * PCAN_MESSAGE_BRS)
+}
+
+/// 已連接的 PCAN 頻道
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcanChannel {
+    pub handle: u32,
+    pub device: String,
+    pub fd_capable: bool,
+    /// 頻道未被其他程式佔用
+    pub available: bool,
+}
+
+impl PcanChannel {
+    /// 下拉選單顯示的文字，例如 "PCAN_USBBUS2 - PCAN-USB FD (FD)"
+    pub fn label(&self) -> String {
+        format!(
+            "{} - {}{}{}",
+            pcan_channel_name(self.handle),
+            self.device,
+            if self.fd_capable { " (FD)" } else { "" },
+            if self.available { "" } else { " [occupied]" }
+        )
+    }
 }
 
 /// PCAN 應用程式，將頻道與波特率存入 struct 內
@@ -315,6 +1086,13 @@ pub struct PcanApp {
     pub is_can_initialized: Arc<AtomicBool>,
     channel: u32,
     baud_rate: PcanBaudRate,
+    listen_only: bool,
+    echo_frames: bool,
+    /// 設定時以 CAN_InitializeFD 開啟 FD 模式，`baud_rate` 不使用
+    fd_bitrate: Option<PcanFdBitrate>,
+    counters: Arc<ReceiveCounters>,
+    bus_status: BusStatusBoard,
+    versions: Mutex<Vec<LibraryVersion>>,
     join_handles: Arc<Mutex<Vec<thread::JoinHandle<()>>>>,
 }
 
@@ -328,15 +1106,95 @@ impl PcanApp {
             is_can_initialized: Arc::new(AtomicBool::new(false)),
             channel,
             baud_rate,
+            listen_only: false,
+            echo_frames: false,
+            fd_bitrate: None,
+            counters: Arc::new(ReceiveCounters::default()),
+            bus_status: BusStatusBoard::default(),
+            versions: Mutex::new(Vec::new()),
             join_handles: Arc::new(Mutex::new(Vec::new())),
         }
     }
 
+    /// 設定只聽模式與回送（echo）訊框，回送開啟時送出的訊框也會出現在接收端
+    pub fn with_options(mut self, listen_only: bool, echo_frames: bool) -> Self {
+        self.listen_only = listen_only;
+        self.echo_frames = echo_frames;
+        self
+    }
+
+    /// 以 FD 位元率字串開啟 CAN FD 模式，None 為 Classic CAN
+    pub fn with_fd(mut self, fd_bitrate: Option<PcanFdBitrate>) -> Self {
+        self.fd_bitrate = fd_bitrate;
+        self
+    }
+
+    /// 以 PCAN_ATTACHED_CHANNELS 列出已連接的頻道；找不到 PCANBasic 時回傳空清單
+    pub fn attached_channels() -> Vec<PcanChannel> {
+        const PCAN_ATTACHED_CHANNELS_COUNT: u32 = 0x2A;
+        const PCAN_ATTACHED_CHANNELS: u32 = 0x2B;
+        const PCAN_CHANNEL_AVAILABLE: u32 = 0x01;
+        const FEATURE_FD_CAPABLE: u32 = 0x01;
+        type GetValue = unsafe extern "C" fn(u32, u32, *mut c_void, u32) -> u32;
+        unsafe {
+            let Ok(lib) = Library::new("PCANBasic.dll") else {
+                return Vec::new();
+            };
+            let Ok(get_value) = lib.get::<GetValue>(b"CAN_GetValue\0") else {
+                return Vec::new();
+            };
+            let mut count = 0u32;
+            let status = get_value(
+                PCAN_NONEBUS,
+                PCAN_ATTACHED_CHANNELS_COUNT,
+                &mut count as *mut _ as *mut c_void,
+                4,
+            );
+            if status != PCAN_ERROR_OK || count == 0 {
+                return Vec::new();
+            }
+            let mut infos = vec![PcanChannelInformation::default(); count as usize];
+            let status = get_value(
+                PCAN_NONEBUS,
+                PCAN_ATTACHED_CHANNELS,
+                infos.as_mut_ptr() as *mut c_void,
+                std::mem::size_of_val(infos.as_slice()) as u32,
+            );
+            if status != PCAN_ERROR_OK {
+                return Vec::new();
+            }
+            infos
+                .iter()
+                .map(|info| PcanChannel {
+                    handle: info.channel_handle as u32,
+                    device: String::from_utf8_lossy(&info.device_name)
+                        .trim_matches('\0')
+                        .to_string(),
+                    fd_capable: info.device_features & FEATURE_FD_CAPABLE != 0,
+                    available: info.channel_condition & PCAN_CHANNEL_AVAILABLE != 0,
+                })
+                .collect()
+        }
+    }
+
     /// 封裝 unsafe 呼叫：初始化 PCAN 頻道
     unsafe fn initialize_channel(&self) -> Result<(), String> {
         self.force_close_internal();
-        let baudrate_value = self.baud_rate.to_u16() as u32;
-        let status = (self.can_lib.can_initialize)(self.channel, baudrate_value, 0, 0, 0);
+        let status = match &self.fd_bitrate {
+            Some(fd_bitrate) => {
+                let can_initialize_fd = self
+                    .can_lib
+                    .can_initialize_fd
+                    .ok_or("PCANBasic library does not support CAN FD")?;
+                let text = CString::new(fd_bitrate.text.as_str())
+                    .map_err(|_| "Invalid FD bitrate string".to_string())?;
+                can_initialize_fd(self.channel, text.as_ptr())
+            }
+            None => {
+                let baudrate_value = self.baud_rate.to_u16() as u32;
+                (self.can_lib.can_initialize)(self.channel, baudrate_value, 0, 0, 0)
+            }
+        };
         if status != PCAN_ERROR_OK {
             Err(format!(
                 "PCAN initialization failed, error code: 0x{:X}",
@@ -348,7 +1206,7 @@ impl PcanApp {
     }
 
     /// 封裝 unsafe 呼叫：配置 PCAN 參數
-    unsafe fn configure_channel(&self, log_tx: &Sender<String>) {
+    unsafe fn configure_channel(&self, log_tx: &Sender<LogEvent>) {
         const PCAN_MESSAGE_FILTER: u32 = 0x04;
         const PCAN_FILTER_OPEN: u32 = 1;
         let filter_status = (self.can_lib.can_set_value)(
@@ -357,90 +1215,275 @@ impl PcanApp {
             &PCAN_FILTER_OPEN as *const _ as *const c_void,
             4,
         );
-        if filter_status != PCAN_ERROR_OK {
-            let _ = log_tx.send("Failed to enable message filter.".to_string());
+        let _ = log_tx.send(if filter_status != PCAN_ERROR_OK {
+            LogEvent::OptionFailed {
+                option: "PCAN message filter".to_string(),
+                enabled: true,
+            }
         } else {
-            let _ = log_tx.send("PCAN message filter enabled.".to_string());
-        }
+            LogEvent::OptionSet {
+                option: "PCAN message filter".to_string(),
+                enabled: true,
+            }
+        });
         const PCAN_LISTEN_ONLY: u32 = 0x08;
         const PCAN_PARAMETER_OFF: u32 = 0;
+        const PCAN_PARAMETER_ON: u32 = 1;
+        let on_off = |enabled: bool| {
+            if enabled {
+                &PCAN_PARAMETER_ON
+            } else {
+                &PCAN_PARAMETER_OFF
+            }
+        };
         let listen_status = (self.can_lib.can_set_value)(
             self.channel,
             PCAN_LISTEN_ONLY,
-            &PCAN_PARAMETER_OFF as *const _ as *const c_void,
+            on_off(self.listen_only) as *const _ as *const c_void,
             4,
         );
-        if listen_status != PCAN_ERROR_OK {
-            let _ = log_tx.send("Failed to disable listen-only mode.".to_string());
+        let _ = log_tx.send(if listen_status != PCAN_ERROR_OK {
+            LogEvent::OptionFailed {
+                option: "PCAN listen-only mode".to_string(),
+                enabled: self.listen_only,
+            }
         } else {
-            let _ = log_tx.send("PCAN listen-only mode disabled.".to_string());
+            LogEvent::OptionSet {
+                option: "PCAN listen-only mode".to_string(),
+                enabled: self.listen_only,
+            }
+        });
+        const PCAN_ALLOW_ECHO_FRAMES: u32 = 0x2C;
+        if self.echo_frames {
+            let echo_status = (self.can_lib.can_set_value)(
+                self.channel,
+                PCAN_ALLOW_ECHO_FRAMES,
+                &PCAN_PARAMETER_ON as *const _ as *const c_void,
+                4,
+            );
+            let _ = log_tx.send(if echo_status != PCAN_ERROR_OK {
+                LogEvent::OptionFailed {
+                    option: "PCAN echo frames".to_string(),
+                    enabled: true,
+                }
+            } else {
+                LogEvent::OptionSet {
+                    option: "PCAN echo frames".to_string(),
+                    enabled: true,
+                }
+            });
         }
+        // 錯誤訊框在接收迴圈中彙整後記入 Log
+        const PCAN_ALLOW_ERROR_FRAMES: u32 = 0x20;
+        let error_status = (self.can_lib.can_set_value)(
+            self.channel,
+            PCAN_ALLOW_ERROR_FRAMES,
+            &PCAN_PARAMETER_ON as *const _ as *const c_void,
+            4,
+        );
+        let _ = log_tx.send(if error_status != PCAN_ERROR_OK {
+            LogEvent::OptionFailed {
+                option: "PCAN error frames".to_string(),
+                enabled: true,
+            }
+        } else {
+            LogEvent::OptionSet {
+                option: "PCAN error frames".to_string(),
+                enabled: true,
+            }
+        });
         const PCAN_BUSOFF_AUTORESET: u32 = 0x07;
-        const PCAN_PARAMETER_ON: u32 = 1;
         let reset_status = (self.can_lib.can_set_value)(
             self.channel,
             PCAN_BUSOFF_AUTORESET,
             &PCAN_PARAMETER_ON as *const _ as *const c_void,
             4,
         );
-        if reset_status != PCAN_ERROR_OK {
-            let _ = log_tx.send("Failed to enable Bus-Off auto-reset.".to_string());
+        let _ = log_tx.send(if reset_status != PCAN_ERROR_OK {
+            LogEvent::OptionFailed {
+                option: "Bus-Off auto-reset".to_string(),
+                enabled: true,
+            }
         } else {
-            let _ = log_tx.send("Bus-Off auto-reset enabled.".to_string());
-        }
+            LogEvent::OptionSet {
+                option: "Bus-Off auto-reset".to_string(),
+                enabled: true,
+            }
+        });
     }
 
-    /// 強制關閉所有 PCAN 頻道（內部呼叫）
+    /// 強制關閉本頻道（內部呼叫），清掉先前未正常關閉的狀態；
+    /// 不可用 PCAN_NONEBUS，會連同其他介面使用中的頻道一起關閉
     fn force_close_internal(&self) {
-        const PCAN_NONEBUS: u32 = 0x00;
         unsafe {
-            let _ = (self.can_lib.can_uninitialize)(PCAN_NONEBUS);
+            let _ = (self.can_lib.can_uninitialize)(self.channel);
         }
     }
+
+    /// 查詢 PCANBasic API 版本，不需先初始化頻道
+    fn api_version(&self) -> Option<LibraryVersion> {
+        const PCAN_PARAMETER_API_VERSION: u32 = 0x00000005;
+        let mut buffer = [0u8; 24];
+        let status = unsafe {
+            (self.can_lib.can_get_value)(
+                PCAN_NONEBUS,
+                PCAN_PARAMETER_API_VERSION,
+                buffer.as_mut_ptr() as *mut c_void,
+                24,
+            )
+        };
+        (status == PCAN_ERROR_OK).then(|| {
+            LibraryVersion::new(
+                "PCANBasic",
+                String::from_utf8_lossy(&buffer).trim_matches('\0'),
+            )
+        })
+    }
 }
 
 impl CanInterface for PcanApp {
-    fn open_device(&self, log_tx: Sender<String>) -> Result<(), String> {
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String> {
+        if let Some(version) = self.api_version() {
+            version.report(&log_tx);
+            *self.versions.lock().unwrap() = vec![version];
+        }
         unsafe {
             self.initialize_channel().map_err(|e| {
-                let _ = log_tx.send(e.clone());
+                let _ = log_tx.send(LogEvent::DeviceError { detail: e.clone() });
                 e
             })?;
-            let _ = log_tx.send(format!(
-                "PCAN channel 0x{:X} initialized with baud rate: {:?}",
-                self.channel, self.baud_rate
-            ));
+            let _ = log_tx.send(LogEvent::ChannelInitialized {
+                channel: self.channel,
+                settings: match &self.fd_bitrate {
+                    Some(fd_bitrate) => format!(
+                        "FD: {}K / {}K ({})",
+                        fd_bitrate.nominal / 1000,
+                        fd_bitrate.data / 1000,
+                        fd_bitrate.text
+                    ),
+                    None => format!("BaudRate: {:?}", self.baud_rate),
+                },
+            });
             self.is_can_initialized.store(true, Ordering::SeqCst);
             self.configure_channel(&log_tx);
         }
         Ok(())
     }
 
-    fn close_device(&self, log_tx: Sender<String>) {
+    fn close_device(&self, log_tx: Sender<LogEvent>) {
         unsafe {
             let status = (self.can_lib.can_uninitialize)(self.channel);
-            let _ = log_tx.send(format!("PCAN device closed, status: {}", status));
+            let _ = log_tx.send(LogEvent::DeviceClosed {
+                backend: "PCAN".to_string(),
+                status: Some(status as i64),
+            });
             self.is_can_initialized.store(false, Ordering::SeqCst);
         }
     }
 
-    fn start_receiving(&self, log_tx: Sender<String>, data_tx: Sender<String>) {
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
         self.receiving.store(true, Ordering::SeqCst);
         let channel = self.channel;
         let receiving_flag = Arc::clone(&self.receiving);
         let can_lib = Arc::clone(&self.can_lib);
         let join_handles_clone = Arc::clone(&self.join_handles);
+        let counters = Arc::clone(&self.counters);
+        let bus_status = self.bus_status.clone();
+        bus_status.clear();
+        let fd = self.fd_bitrate.is_some();
         let handle = thread::spawn(move || {
-            let _ = log_tx.send(format!("PCAN channel 0x{:X} ready for receiving", channel));
+            let _ = log_tx.send(LogEvent::ChannelStarted { channel });
+            // 錯誤計數器只能由錯誤訊框得知，狀態則來自狀態訊息與 CAN_GetStatus
+            let mut error_counters: Option<(u8, u8)> = None;
+            let update_state = |status: u32, error_counters: Option<(u8, u8)>| {
+                let controller = ControllerStatus {
+                    state: pcan_bus_state(status),
+                    tec: error_counters.map(|(tec, _)| tec),
+                    rec: error_counters.map(|(_, rec)| rec),
+                };
+                if let Some(event) = bus_status.update(channel, controller) {
+                    let _ = log_tx.send(event);
+                }
+            };
+            let mut error_frames = 0u64;
+            let mut last_error_kind = 0;
+            let mut last_status_check = Instant::now();
+            let mut last_error_report = Instant::now();
             while receiving_flag.load(Ordering::SeqCst) {
-                let mut pcan_msg = PcanMsg::default();
-                let status = unsafe { (can_lib.can_read)(channel, &mut pcan_msg) };
-                if status == PCAN_ERROR_OK {
-                    let data = &pcan_msg.data[..(pcan_msg.len as usize)];
-                    let msg = format!("PCAN: ID=0x{:X}, Data={:?}", pcan_msg.id, data);
-                    let _ = data_tx.send(msg);
+                if last_status_check.elapsed() >= ERR_INFO_INTERVAL {
+                    last_status_check = Instant::now();
+                    let status = unsafe { (can_lib.can_get_status)(channel) };
+                    update_state(status, error_counters);
+                }
+                if error_frames > 0 && last_error_report.elapsed() >= ERROR_FRAME_REPORT_INTERVAL {
+                    let _ = log_tx.send(LogEvent::ErrorFrames {
+                        channel,
+                        count: error_frames,
+                        last: pcan_error_kind(last_error_kind).to_string(),
+                    });
+                    error_frames = 0;
+                    last_error_report = Instant::now();
+                }
+                // 一次讀空驅動的接收佇列，佇列空了才等待；每輪最多讀 RECEIVE_BATCH 次，
+                // 溢位每輪只記錄一次
+                let mut batch = 0;
+                let mut reads = 0;
+                let mut overrun = None;
+                while reads < RECEIVE_BATCH {
+                    reads += 1;
+                    let status = match unsafe { can_lib.read_frame(channel, fd) } {
+                        Ok(PcanMessage::Frame(frame)) => {
+                            let _ = data_tx.send(frame);
+                            batch += 1;
+                            continue;
+                        }
+                        Ok(PcanMessage::Status(status)) => {
+                            if status & PCAN_ERROR_OVERRUN != 0 {
+                                overrun = Some(status);
+                            }
+                            update_state(status, error_counters);
+                            continue;
+                        }
+                        Ok(PcanMessage::ErrorFrame { kind, rec, tec }) => {
+                            if error_frames == 0 {
+                                last_error_report = Instant::now();
+                            }
+                            error_frames += 1;
+                            last_error_kind = kind;
+                            error_counters = Some((tec, rec));
+                            continue;
+                        }
+                        Err(status) => status,
+                    };
+                    // 狀態碼為位元旗標，佇列已空時也可能同時帶有溢位旗標
+                    let overrun_flags = status & (PCAN_ERROR_QOVERRUN | PCAN_ERROR_OVERRUN);
+                    if overrun_flags != 0 {
+                        overrun = Some(status);
+                    }
+                    if status & PCAN_ERROR_QRCVEMPTY != 0 {
+                        break;
+                    }
+                    if overrun_flags == 0 {
+                        let _ = log_tx.send(LogEvent::ReadFailed {
+                            channel,
+                            code: status,
+                        });
+                        break;
+                    }
+                }
+                if let Some(status) = overrun {
+                    counters.record_overrun();
+                    let _ = log_tx.send(LogEvent::ReceiveOverrun {
+                        channel,
+                        code: Some(status),
+                    });
+                }
+                if batch > 0 {
+                    counters.record_read(batch as u64, batch as u64);
+                }
+                if reads < RECEIVE_BATCH {
+                    thread::sleep(IDLE_WAIT);
                 }
-                thread::sleep(Duration::from_millis(10));
             }
         });
         join_handles_clone.lock().unwrap().push(handle);
@@ -451,34 +1494,105 @@ impl CanInterface for PcanApp {
         let mut handles = self.join_handles.lock().unwrap();
         while let Some(handle) = handles.pop() {
             if let Err(e) = handle.join() {
-                eprintln!("Error joining PCAN thread: {:?}", e);
+                tracing::error!("Error joining PCAN thread: {:?}", e);
             }
         }
     }
 
-    fn read_board_info(&self, log_tx: Sender<String>) {
+    fn read_board_info(&self, log_tx: Sender<LogEvent>) {
         if !self.is_can_initialized.load(Ordering::SeqCst) {
-            let _ = log_tx
-                .send("Error: PCAN device not initialized; cannot read board info".to_string());
+            let _ = log_tx.send(LogEvent::NotInitialized {
+                backend: "PCAN".to_string(),
+            });
             return;
         }
-        const PCAN_PARAMETER_API_VERSION: u32 = 0x00000005;
-        let mut buffer = [0u8; 24];
-        let status = unsafe {
-            (self.can_lib.can_get_value)(
-                self.channel,
-                PCAN_PARAMETER_API_VERSION,
-                buffer.as_mut_ptr() as *mut c_void,
-                24,
-            )
+        if let Some(version) = self.api_version() {
+            let _ = log_tx.send(LogEvent::ApiVersion {
+                library: version.library,
+                version: version.version,
+            });
+        } else {
+            let _ = log_tx.send(LogEvent::BoardInfoFailed {
+                backend: "PCAN".to_string(),
+            });
+        }
+    }
+
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        if !self.is_can_initialized.load(Ordering::SeqCst) {
+            return Err("PCAN device not initialized; cannot transmit".to_string());
+        }
+        let status = match self
+            .can_lib
+            .can_write_fd
+            .filter(|_| self.fd_bitrate.is_some())
+        {
+            Some(can_write_fd) => {
+                let mut pcan_msg = PcanMsgFd {
+                    id: frame.id,
+                    msgtype: pcan_msgtype(frame),
+                    dlc: frame.dlc(),
+                    ..Default::default()
+                };
+                pcan_msg.data[..frame.data.len()].copy_from_slice(frame.payload());
+                unsafe { can_write_fd(self.channel, &pcan_msg) }
+            }
+            None => {
+                let pcan_msg = PcanMsg {
+                    id: frame.id,
+                    msgtype: pcan_msgtype(frame),
+                    len: frame.data.len() as u8,
+                    data: classic_payload(frame)?,
+                };
+                unsafe { (self.can_lib.can_write)(self.channel, &pcan_msg) }
+            }
         };
-        if status == PCAN_ERROR_OK {
-            let version = String::from_utf8_lossy(&buffer)
-                .trim_matches('\0')
-                .to_string();
-            let _ = log_tx.send(format!("PCAN API Version: {}", version));
+        if status != PCAN_ERROR_OK {
+            Err(format!("PCAN write failed, error code: 0x{:X}", status))
         } else {
-            let _ = log_tx.send("Failed to read PCAN board info".to_string());
+            Ok(())
+        }
+    }
+
+    fn echoes_transmit(&self) -> bool {
+        self.echo_frames
+    }
+
+    fn receive_stats(&self) -> ReceiveStats {
+        self.counters.snapshot()
+    }
+
+    fn library_versions(&self) -> Vec<LibraryVersion> {
+        self.versions.lock().unwrap().clone()
+    }
+
+    fn bus_status(&self) -> Vec<(u32, ControllerStatus)> {
+        self.bus_status.snapshot()
+    }
+
+    fn channels(&self) -> Vec<u32> {
+        vec![self.channel]
+    }
+
+    fn check_device(&self) -> Result<(), String> {
+        let status = unsafe { (self.can_lib.can_get_status)(self.channel) };
+        if status & (PCAN_ERROR_ILLHANDLE | PCAN_ERROR_INITIALIZE) != 0 {
+            Err(format!("PCAN channel unavailable, status: 0x{:X}", status))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn clear_receive_buffer(&self) -> Result<(), String> {
+        if !self.is_can_initialized.load(Ordering::SeqCst) {
+            return Err("PCAN device not initialized; cannot clear buffer".to_string());
+        }
+        // CAN_Reset 清空驅動的收發佇列，不影響通道設定
+        let status = unsafe { (self.can_lib.can_reset)(self.channel) };
+        if status != PCAN_ERROR_OK {
+            Err(format!("PCAN reset failed, error code: 0x{:X}", status))
+        } else {
+            Ok(())
         }
     }
 }
@@ -0,0 +1,390 @@
+use crate::can::cantypes::CanFrame;
+use std::collections::BTreeMap;
+
+/// 預先定義連線組（CiA 301）的功能碼，COB-ID = 功能碼 + 節點編號
+const COB_NMT: u32 = 0x000;
+const COB_SYNC: u32 = 0x080;
+const COB_TIME: u32 = 0x100;
+const COB_SDO_TX: u32 = 0x580;
+const COB_SDO_RX: u32 = 0x600;
+const COB_HEARTBEAT: u32 = 0x700;
+/// LSS 佔用了節點 100、101 的心跳 ID
+const COB_LSS_SLAVE: u32 = 0x7E4;
+const COB_LSS_MASTER: u32 = 0x7E5;
+/// SDO 指令位元組高 3 bits 為 4 時是中止傳輸
+const SDO_ABORT: u8 = 4;
+
+/// 依 COB-ID 分類的 CANopen 訊框
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CobKind {
+    Nmt,
+    Sync,
+    Time,
+    Emcy(u8),
+    /// (PDO 編號 1..=4, 節點)
+    Tpdo(u8, u8),
+    Rpdo(u8, u8),
+    /// 伺服器（節點）傳給用戶端的 SDO
+    SdoResponse(u8),
+    /// 用戶端傳給伺服器（節點）的 SDO
+    SdoRequest(u8),
+    /// 心跳、開機訊息或節點守護
+    Heartbeat(u8),
+    LssMaster,
+    LssSlave,
+}
+
+impl CobKind {
+    pub fn node(&self) -> Option<u8> {
+        match *self {
+            CobKind::Emcy(node)
+            | CobKind::Tpdo(_, node)
+            | CobKind::Rpdo(_, node)
+            | CobKind::SdoResponse(node)
+            | CobKind::SdoRequest(node)
+            | CobKind::Heartbeat(node) => Some(node),
+            _ => None,
+        }
+    }
+}
+
+/// 依 COB-ID 分類訊框；29-bit ID 或不屬於預先定義連線組的 ID 回傳 None
+pub fn classify(frame: &CanFrame) -> Option<CobKind> {
+    if frame.extended {
+        return None;
+    }
+    let kind = match frame.id {
+        COB_NMT => CobKind::Nmt,
+        COB_SYNC => CobKind::Sync,
+        COB_TIME => CobKind::Time,
+        COB_LSS_SLAVE => CobKind::LssSlave,
+        COB_LSS_MASTER => CobKind::LssMaster,
+        id => {
+            let node = (id & 0x7F) as u8;
+            if node == 0 {
+                return None;
+            }
+            match id & 0x780 {
+                0x080 => CobKind::Emcy(node),
+                0x180 => CobKind::Tpdo(1, node),
+                0x200 => CobKind::Rpdo(1, node),
+                0x280 => CobKind::Tpdo(2, node),
+                0x300 => CobKind::Rpdo(2, node),
+                0x380 => CobKind::Tpdo(3, node),
+                0x400 => CobKind::Rpdo(3, node),
+                0x480 => CobKind::Tpdo(4, node),
+                0x500 => CobKind::Rpdo(4, node),
+                COB_SDO_TX => CobKind::SdoResponse(node),
+                COB_SDO_RX => CobKind::SdoRequest(node),
+                COB_HEARTBEAT => CobKind::Heartbeat(node),
+                _ => return None,
+            }
+        }
+    };
+    Some(kind)
+}
+
+/// 節點的 NMT 狀態（心跳訊息的狀態位元組）
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NmtState {
+    /// 開機訊息（boot-up），之後進入 Pre-operational
+    Initializing,
+    Stopped,
+    Operational,
+    PreOperational,
+    Unknown(u8),
+}
+
+impl NmtState {
+    pub fn from_byte(byte: u8) -> Self {
+        // bit 7 為節點守護的 toggle bit
+        match byte & 0x7F {
+            0 => NmtState::Initializing,
+            4 => NmtState::Stopped,
+            5 => NmtState::Operational,
+            127 => NmtState::PreOperational,
+            other => NmtState::Unknown(other),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        match self {
+            NmtState::Initializing => "Initializing".to_string(),
+            NmtState::Stopped => "Stopped".to_string(),
+            NmtState::Operational => "Operational".to_string(),
+            NmtState::PreOperational => "Pre-operational".to_string(),
+            NmtState::Unknown(state) => format!("Unknown ({})", state),
+        }
+    }
+}
+
+/// NMT 指令的名稱與指令後節點的狀態
+fn nmt_command(command: u8) -> Option<(&'static str, NmtState)> {
+    Some(match command {
+        0x01 => ("Start", NmtState::Operational),
+        0x02 => ("Stop", NmtState::Stopped),
+        0x80 => ("Enter Pre-operational", NmtState::PreOperational),
+        0x81 => ("Reset Node", NmtState::Initializing),
+        0x82 => ("Reset Communication", NmtState::Initializing),
+        _ => return None,
+    })
+}
+
+/// SDO 中止代碼的說明（CiA 301）
+pub fn abort_description(code: u32) -> &'static str {
+    match code {
+        0x0503_0000 => "Toggle bit not alternated",
+        0x0504_0000 => "SDO protocol timed out",
+        0x0504_0001 => "Command specifier not valid or unknown",
+        0x0504_0002 => "Invalid block size",
+        0x0504_0003 => "Invalid sequence number",
+        0x0504_0004 => "CRC error",
+        0x0504_0005 => "Out of memory",
+        0x0601_0000 => "Unsupported access to an object",
+        0x0601_0001 => "Attempt to read a write only object",
+        0x0601_0002 => "Attempt to write a read only object",
+        0x0602_0000 => "Object does not exist in the object dictionary",
+        0x0604_0041 => "Object cannot be mapped to the PDO",
+        0x0604_0042 => "Mapped objects would exceed PDO length",
+        0x0604_0043 => "General parameter incompatibility",
+        0x0604_0047 => "General internal incompatibility in the device",
+        0x0606_0000 => "Access failed due to a hardware error",
+        0x0607_0010 => "Data type does not match, length does not match",
+        0x0607_0012 => "Data type does not match, length too high",
+        0x0607_0013 => "Data type does not match, length too low",
+        0x0609_0011 => "Sub-index does not exist",
+        0x0609_0030 => "Invalid value for parameter",
+        0x0609_0031 => "Value of parameter written too high",
+        0x0609_0032 => "Value of parameter written too low",
+        0x0609_0036 => "Maximum value is less than minimum value",
+        0x060A_0023 => "Resource not available: SDO connection",
+        0x0800_0000 => "General error",
+        0x0800_0020 => "Data cannot be transferred or stored to the application",
+        0x0800_0021 => "Data cannot be transferred because of local control",
+        0x0800_0022 => "Data cannot be transferred because of the device state",
+        0x0800_0023 => "Object dictionary not present",
+        0x0800_0024 => "No data available",
+        _ => "Unknown abort code",
+    }
+}
+
+/// SDO 中止傳輸
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SdoAbort {
+    pub node: u8,
+    pub index: u16,
+    pub subindex: u8,
+    pub code: u32,
+    pub timestamp: u64,
+}
+
+impl SdoAbort {
+    pub fn description(&self) -> &'static str {
+        abort_description(self.code)
+    }
+}
+
+/// 解析 SDO 中止訊框（任一方向皆可）
+pub fn sdo_abort(frame: &CanFrame) -> Option<SdoAbort> {
+    let node = match classify(frame)? {
+        CobKind::SdoRequest(node) | CobKind::SdoResponse(node) => node,
+        _ => return None,
+    };
+    let data = frame.payload();
+    if data.len() < 8 || data[0] >> 5 != SDO_ABORT {
+        return None;
+    }
+    Some(SdoAbort {
+        node,
+        index: u16::from_le_bytes([data[1], data[2]]),
+        subindex: data[3],
+        code: u32::from_le_bytes([data[4], data[5], data[6], data[7]]),
+        timestamp: frame.timestamp,
+    })
+}
+
+fn describe_sdo(request: bool, data: &[u8]) -> String {
+    let Some(&command) = data.first() else {
+        return "empty".to_string();
+    };
+    let specifier = command >> 5;
+    let object = || match data.get(1..4) {
+        Some(d) => format!(" 0x{:04X}:{:02X}", u16::from_le_bytes([d[0], d[1]]), d[2]),
+        None => String::new(),
+    };
+    // 快速傳輸（expedited）的資料位於 byte 4..，n 為未使用的位元組數
+    let expedited = || {
+        if command & 0x02 == 0 || data.len() < 8 {
+            return String::new();
+        }
+        let unused = if command & 0x01 != 0 {
+            ((command >> 2) & 0x3) as usize
+        } else {
+            0
+        };
+        let value = data[4..8 - unused]
+            .iter()
+            .rev()
+            .fold(0u32, |acc, &b| (acc << 8) | b as u32);
+        format!(" = 0x{:X}", value)
+    };
+    if specifier == SDO_ABORT {
+        if data.len() < 8 {
+            return "abort".to_string();
+        }
+        let code = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+        return format!(
+            "abort{}: {} (0x{:08X})",
+            object(),
+            abort_description(code),
+            code
+        );
+    }
+    match (request, specifier) {
+        (true, 1) => format!("download{}{}", object(), expedited()),
+        (true, 2) => format!("upload{}", object()),
+        (true, 0) => "download segment".to_string(),
+        (true, 3) => "upload segment".to_string(),
+        (true, 5) => "block upload".to_string(),
+        (true, 6) => "block download".to_string(),
+        (false, 3) => format!("download ok{}", object()),
+        (false, 2) => format!("upload{}{}", object(), expedited()),
+        (false, 1) => "download segment ok".to_string(),
+        (false, 0) => "upload segment".to_string(),
+        (false, 5) => "block download".to_string(),
+        (false, 6) => "block upload".to_string(),
+        _ => format!("command 0x{:02X}", command),
+    }
+}
+
+/// 訊框的 CANopen 說明文字；不屬於 CANopen 預先定義連線組時回傳 None
+pub fn describe_frame(frame: &CanFrame) -> Option<String> {
+    let kind = classify(frame)?;
+    let data = frame.payload();
+    Some(match kind {
+        CobKind::Nmt => match (data.first(), data.get(1)) {
+            (Some(&command), Some(&node)) => {
+                let name = nmt_command(command).map_or("Unknown command", |(name, _)| name);
+                if node == 0 {
+                    format!("NMT {} all nodes", name)
+                } else {
+                    format!("NMT {} node {}", name, node)
+                }
+            }
+            _ => "NMT".to_string(),
+        },
+        CobKind::Sync => match data.first() {
+            Some(counter) => format!("SYNC counter {}", counter),
+            None => "SYNC".to_string(),
+        },
+        CobKind::Time => "TIME".to_string(),
+        CobKind::Emcy(node) => {
+            if data.len() < 3 {
+                format!("EMCY node {}", node)
+            } else if data[..3] == [0, 0, 0] {
+                format!("EMCY node {} error reset", node)
+            } else {
+                format!(
+                    "EMCY node {} code 0x{:04X} register 0x{:02X}",
+                    node,
+                    u16::from_le_bytes([data[0], data[1]]),
+                    data[2]
+                )
+            }
+        }
+        CobKind::Tpdo(pdo, node) => format!("TPDO{} node {}", pdo, node),
+        CobKind::Rpdo(pdo, node) => format!("RPDO{} node {}", pdo, node),
+        CobKind::SdoRequest(node) => format!("SDO node {} {}", node, describe_sdo(true, data)),
+        CobKind::SdoResponse(node) => {
+            format!("SDO node {} {}", node, describe_sdo(false, data))
+        }
+        CobKind::Heartbeat(node) if frame.rtr => format!("Node guarding request node {}", node),
+        CobKind::Heartbeat(node) => match data.first().map(|&b| NmtState::from_byte(b)) {
+            Some(NmtState::Initializing) => format!("Boot-up node {}", node),
+            Some(state) => format!("Heartbeat node {} {}", node, state.name()),
+            None => format!("Heartbeat node {}", node),
+        },
+        CobKind::LssMaster => "LSS master".to_string(),
+        CobKind::LssSlave => "LSS slave".to_string(),
+    })
+}
+
+/// 單一節點的最新狀況
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CanOpenNode {
+    pub state: Option<NmtState>,
+    /// 最後一次心跳或開機訊息的時間（微秒）
+    pub last_heartbeat: Option<u64>,
+    pub heartbeats: u64,
+    /// 最後一筆 EMCY 的（錯誤碼, 錯誤暫存器）
+    pub last_emcy: Option<(u16, u8)>,
+    pub emcy_count: u64,
+    pub last_abort: Option<SdoAbort>,
+    pub frames: u64,
+}
+
+/// CANopen 網路監看：依接收的訊框追蹤各節點的 NMT 狀態、EMCY 與 SDO 中止
+#[derive(Debug, Default)]
+pub struct CanOpenMonitor {
+    nodes: BTreeMap<u8, CanOpenNode>,
+}
+
+impl CanOpenMonitor {
+    /// 處理收到的訊框，屬於 CANopen 時回傳分類
+    pub fn process(&mut self, frame: &CanFrame) -> Option<CobKind> {
+        let kind = classify(frame)?;
+        let data = frame.payload();
+        if kind == CobKind::Nmt {
+            // NMT 指令沒有回應，依指令推定節點狀態；節點 0 代表所有已知節點
+            if let (Some(&command), Some(&target)) = (data.first(), data.get(1)) {
+                if let Some((_, state)) = nmt_command(command) {
+                    if target == 0 {
+                        self.nodes
+                            .values_mut()
+                            .for_each(|node| node.state = Some(state));
+                    } else {
+                        self.nodes.entry(target).or_default().state = Some(state);
+                    }
+                }
+            }
+            return Some(kind);
+        }
+        let Some(id) = kind.node() else {
+            return Some(kind);
+        };
+        let node = self.nodes.entry(id).or_default();
+        node.frames += 1;
+        match kind {
+            CobKind::Heartbeat(_) if !frame.rtr => {
+                if let Some(&state) = data.first() {
+                    node.state = Some(NmtState::from_byte(state));
+                    node.last_heartbeat = Some(frame.timestamp);
+                    node.heartbeats += 1;
+                }
+            }
+            CobKind::Emcy(_) if data.len() >= 3 => {
+                node.last_emcy = Some((u16::from_le_bytes([data[0], data[1]]), data[2]));
+                node.emcy_count += 1;
+            }
+            CobKind::SdoRequest(_) | CobKind::SdoResponse(_) => {
+                if let Some(abort) = sdo_abort(frame) {
+                    node.last_abort = Some(abort);
+                }
+            }
+            _ => {}
+        }
+        Some(kind)
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (u8, &CanOpenNode)> {
+        self.nodes.iter().map(|(&id, node)| (id, node))
+    }
+
+    pub fn node(&self, id: u8) -> Option<&CanOpenNode> {
+        self.nodes.get(&id)
+    }
+
+    pub fn clear(&mut self) {
+        self.nodes.clear();
+    }
+}
@@ -1,5 +1,10 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
 #[repr(C)]
-#[derive(Debug, Default)]
+#[derive(Debug, Default, Clone, Copy)]
 pub struct VciCanObj {
     pub id: u32,
     pub time_stamp: u32,
@@ -24,6 +29,37 @@ pub struct VciInitConfig {
     pub mode: u8,
 }
 
+/// VCI_ReadErrInfo 回傳的錯誤資訊
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VciErrInfo {
+    pub err_code: u32,
+    pub passive_err_data: [u8; 3],
+    pub ar_lost_err_data: u8,
+}
+
+/// VCI_ReadCANStatus 回傳的 SJA1000 控制器暫存器
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct VciCanStatus {
+    pub err_interrupt: u8,
+    pub reg_mode: u8,
+    pub reg_status: u8,
+    pub reg_al_capture: u8,
+    pub reg_ec_capture: u8,
+    pub reg_ew_limit: u8,
+    pub reg_re_counter: u8,
+    pub reg_te_counter: u8,
+    pub reserved: u32,
+}
+
+impl VciCanStatus {
+    /// 狀態暫存器 bit 7：控制器 bus-off
+    pub fn bus_off(&self) -> bool {
+        self.reg_status & 0x80 != 0
+    }
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct VciBoardInfo {
@@ -54,6 +90,28 @@ impl Default for VciBoardInfo {
     }
 }
 
+/// 控制器工作模式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ControllerMode {
+    #[default]
+    Normal,
+    /// 只聽模式，不送 ACK 也不傳送
+    ListenOnly,
+    /// 內部迴路（自測），送出的訊框直接回到接收端而不上匯流排
+    Loopback,
+}
+
+impl ControllerMode {
+    /// ControlCAN `VCI_INIT_CONFIG.Mode` 對應值
+    pub fn vci_mode(self) -> u8 {
+        match self {
+            ControllerMode::Normal => 0,
+            ControllerMode::ListenOnly => 1,
+            ControllerMode::Loopback => 2,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub enum VciCanBaudRate {
     Baud10K,
@@ -73,6 +131,11 @@ pub enum VciCanBaudRate {
     Baud666K,
     Baud800K,
     Baud1M,
+    /// 直接指定 Timing0/Timing1，用於預設表沒有的非標準波特率
+    Custom {
+        timing0: u8,
+        timing1: u8,
+    },
 }
 
 impl VciCanBaudRate {
@@ -95,6 +158,7 @@ impl VciCanBaudRate {
             VciCanBaudRate::Baud666K => (0x80, 0xB6),
             VciCanBaudRate::Baud800K => (0x00, 0x16),
             VciCanBaudRate::Baud1M => (0x00, 0x14),
+            VciCanBaudRate::Custom { timing0, timing1 } => (timing0, timing1),
         }
     }
 
@@ -132,6 +196,147 @@ pub struct PcanMsg {
     pub data: [u8; 8],
 }
 
+/// PCAN 接收時間（TPCANTimestamp）
+#[repr(C)]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PcanTimestamp {
+    pub millis: u32,
+    /// `millis` 溢位的次數
+    pub millis_overflow: u16,
+    pub micros: u16,
+}
+
+impl PcanTimestamp {
+    /// 換算為微秒
+    pub fn total_micros(&self) -> u64 {
+        let millis = ((self.millis_overflow as u64) << 32) + self.millis as u64;
+        millis * 1000 + self.micros as u64
+    }
+}
+
+/// PCAN FD 訊息（TPCANMsgFD），`dlc` 為 DLC 編碼而非資料長度
+#[repr(C)]
+#[derive(Debug)]
+pub struct PcanMsgFd {
+    pub id: u32,
+    pub msgtype: u8,
+    pub dlc: u8,
+    pub data: [u8; 64],
+}
+
+impl Default for PcanMsgFd {
+    fn default() -> Self {
+        Self {
+            id: 0,
+            msgtype: 0,
+            dlc: 0,
+            data: [0; 64],
+        }
+    }
+}
+
+/// PCAN FD 位元率字串（CAN_InitializeFD 的參數），例如
+/// `f_clock_mhz=80, nom_brp=2, nom_tseg1=63, nom_tseg2=16, nom_sjw=16, data_brp=2, ...`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PcanFdBitrate {
+    pub text: String,
+    /// 仲裁段位元率（bit/s）
+    pub nominal: u32,
+    /// 資料段位元率（bit/s）
+    pub data: u32,
+}
+
+impl PcanFdBitrate {
+    /// 解析並驗證位元率字串，計算仲裁段與資料段的位元率
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut values = HashMap::new();
+        for item in text.split(',').map(str::trim).filter(|s| !s.is_empty()) {
+            let (key, value) = item
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid FD bitrate item '{}'", item))?;
+            let value: u32 = value
+                .trim()
+                .parse()
+                .map_err(|_| format!("Invalid value in FD bitrate item '{}'", item))?;
+            values.insert(key.trim().to_string(), value);
+        }
+        let clock = match (values.get("f_clock"), values.get("f_clock_mhz")) {
+            (Some(&hz), _) => hz as u64,
+            (None, Some(&mhz)) => mhz as u64 * 1_000_000,
+            (None, None) => return Err("FD bitrate needs f_clock or f_clock_mhz".to_string()),
+        };
+        let bitrate = |prefix: &str| -> Result<u32, String> {
+            let get = |name: &str| {
+                let key = format!("{}_{}", prefix, name);
+                values
+                    .get(&key)
+                    .copied()
+                    .ok_or_else(|| format!("FD bitrate is missing {}", key))
+            };
+            let brp = get("brp")?;
+            let bits = 1 + get("tseg1")? + get("tseg2")?;
+            get("sjw")?;
+            if brp == 0 {
+                return Err(format!("FD bitrate {}_brp must not be 0", prefix));
+            }
+            Ok((clock / (brp as u64 * bits as u64)) as u32)
+        };
+        Ok(Self {
+            text: text.trim().to_string(),
+            nominal: bitrate("nom")?,
+            data: bitrate("data")?,
+        })
+    }
+}
+
+/// PCAN 頻道資訊（TPCANChannelInformation），以 PCAN_ATTACHED_CHANNELS 查詢
+#[repr(C)]
+#[derive(Debug, Clone, Copy)]
+pub struct PcanChannelInformation {
+    pub channel_handle: u16,
+    pub device_type: u8,
+    pub controller_number: u8,
+    pub device_features: u32,
+    pub device_name: [u8; 33],
+    pub device_id: u32,
+    pub channel_condition: u32,
+}
+
+impl Default for PcanChannelInformation {
+    fn default() -> Self {
+        Self {
+            channel_handle: 0,
+            device_type: 0,
+            controller_number: 0,
+            device_features: 0,
+            device_name: [0; 33],
+            device_id: 0,
+            channel_condition: 0,
+        }
+    }
+}
+
+/// PCAN 頻道代碼對應的名稱，例如 0x51 為 "PCAN_USBBUS1"
+pub fn pcan_channel_name(handle: u32) -> String {
+    let (bus, index) = match handle {
+        0x41..=0x48 => ("PCIBUS", handle - 0x40),
+        0x409..=0x410 => ("PCIBUS", handle - 0x400),
+        0x51..=0x58 => ("USBBUS", handle - 0x50),
+        0x509..=0x510 => ("USBBUS", handle - 0x500),
+        0x801..=0x810 => ("LANBUS", handle - 0x800),
+        _ => return format!("PCAN 0x{:X}", handle),
+    };
+    format!("PCAN_{}{}", bus, index)
+}
+
+/// 可手動選擇的 PCAN 頻道代碼：USBBUS1..16 與 PCIBUS1..16
+pub fn pcan_channel_handles() -> Vec<u32> {
+    [0x51..=0x58, 0x509..=0x510, 0x41..=0x48, 0x409..=0x410]
+        .into_iter()
+        .flatten()
+        .collect()
+}
+
 #[repr(C)]
 #[derive(Debug, Default)]
 pub struct PcanInitConfig {
@@ -170,26 +375,44 @@ impl Default for PcanBoardInfo {
 
 #[derive(Debug, Clone, Copy)]
 pub enum PcanBaudRate {
-    Baud1M = 0x0014,
-    Baud800K = 0x0016,
-    Baud500K = 0x001C,
-    Baud250K = 0x011C,
-    Baud125K = 0x031C,
-    Baud100K = 0x432F,
-    Baud95K = 0xC34E,
-    Baud83K = 0x852B,
-    Baud50K = 0x472F,
-    Baud47K = 0x1414,
-    Baud33K = 0x8B2F,
-    Baud20K = 0x532F,
-    Baud10K = 0x672F,
-    Baud5K = 0x7F7F,
+    Baud1M,
+    Baud800K,
+    Baud500K,
+    Baud250K,
+    Baud125K,
+    Baud100K,
+    Baud95K,
+    Baud83K,
+    Baud50K,
+    Baud47K,
+    Baud33K,
+    Baud20K,
+    Baud10K,
+    Baud5K,
+    /// 直接指定 BTR0BTR1（高位元組為 BTR0）
+    Custom(u16),
 }
 
 impl PcanBaudRate {
     /// 將 `PcanBaudRate` 轉換成 `u16` (適用於 PCAN API)
     pub fn to_u16(self) -> u16 {
-        self as u16
+        match self {
+            PcanBaudRate::Baud1M => 0x0014,
+            PcanBaudRate::Baud800K => 0x0016,
+            PcanBaudRate::Baud500K => 0x001C,
+            PcanBaudRate::Baud250K => 0x011C,
+            PcanBaudRate::Baud125K => 0x031C,
+            PcanBaudRate::Baud100K => 0x432F,
+            PcanBaudRate::Baud95K => 0xC34E,
+            PcanBaudRate::Baud83K => 0x852B,
+            PcanBaudRate::Baud50K => 0x472F,
+            PcanBaudRate::Baud47K => 0x1414,
+            PcanBaudRate::Baud33K => 0x8B2F,
+            PcanBaudRate::Baud20K => 0x532F,
+            PcanBaudRate::Baud10K => 0x672F,
+            PcanBaudRate::Baud5K => 0x7F7F,
+            PcanBaudRate::Custom(btr0btr1) => btr0btr1,
+        }
     }
 
     /// 從 `u32` 轉換成 `PcanBaudRate` (用戶輸入數字)
@@ -220,3 +443,225 @@ pub enum CanBaudRate {
     ControlCan(VciCanBaudRate),
     Pcan(PcanBaudRate),
 }
+
+/// 目前支援的最大 payload 長度（CAN FD）；XL 之後只需放大此值
+pub const MAX_PAYLOAD_LEN: usize = 64;
+
+/// CAN FD 合法的資料長度，依 DLC 9..=15 對應
+const FD_LENGTHS: [usize; 7] = [12, 16, 20, 24, 32, 48, 64];
+
+/// DLC 編碼轉換為資料長度（0..=8 為 Classic，9..=15 為 FD）
+pub fn dlc_to_len(dlc: u8) -> usize {
+    match dlc {
+        0..=8 => dlc as usize,
+        9..=15 => FD_LENGTHS[(dlc - 9) as usize],
+        _ => MAX_PAYLOAD_LEN,
+    }
+}
+
+/// 資料長度轉換為 DLC 編碼，非合法 FD 長度時取下一個可容納的長度
+pub fn len_to_dlc(len: usize) -> u8 {
+    if len <= 8 {
+        return len as u8;
+    }
+    FD_LENGTHS
+        .iter()
+        .position(|&fd_len| len <= fd_len)
+        .map(|i| 9 + i as u8)
+        .unwrap_or(15)
+}
+
+/// 訊框方向：收到的訊框或本機送出的訊框
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameDirection {
+    #[default]
+    Rx,
+    Tx,
+}
+
+/// 訊框協定標記
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrameProtocol {
+    #[default]
+    Classic,
+    Fd,
+    Xl,
+}
+
+impl FrameProtocol {
+    /// 此協定允許的最大 payload 長度
+    pub fn max_payload_len(self) -> usize {
+        match self {
+            FrameProtocol::Classic => 8,
+            FrameProtocol::Fd | FrameProtocol::Xl => MAX_PAYLOAD_LEN,
+        }
+    }
+}
+
+/// 可變長度的訊框資料，以固定容量儲存以保持 `Copy`
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct Payload {
+    len: u8,
+    bytes: [u8; MAX_PAYLOAD_LEN],
+}
+
+impl Payload {
+    /// 由位元組建立，超過 `MAX_PAYLOAD_LEN` 的部分會被截斷
+    pub fn new(data: &[u8]) -> Self {
+        let len = data.len().min(MAX_PAYLOAD_LEN);
+        let mut bytes = [0u8; MAX_PAYLOAD_LEN];
+        bytes[..len].copy_from_slice(&data[..len]);
+        Self {
+            len: len as u8,
+            bytes,
+        }
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        &self.bytes[..self.len as usize]
+    }
+
+    pub fn len(&self) -> usize {
+        self.len as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
+
+impl Default for Payload {
+    fn default() -> Self {
+        Self::new(&[])
+    }
+}
+
+impl fmt::Debug for Payload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_slice(), f)
+    }
+}
+
+impl Serialize for Payload {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.as_slice().serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Payload {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = Vec::<u8>::deserialize(deserializer)?;
+        if data.len() > MAX_PAYLOAD_LEN {
+            return Err(serde::de::Error::custom(format!(
+                "payload longer than {} bytes",
+                MAX_PAYLOAD_LEN
+            )));
+        }
+        Ok(Self::new(&data))
+    }
+}
+
+/// Standard（11-bit）ID 的最大值
+pub const MAX_STANDARD_ID: u32 = 0x7FF;
+
+/// 與後端無關的通用 CAN 訊框；各後端只在邊界轉換成自己的結構
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct CanFrame {
+    pub channel: u32,
+    pub id: u32,
+    #[serde(default)]
+    pub protocol: FrameProtocol,
+    /// FD 位元率切換（Bit Rate Switch）
+    #[serde(default)]
+    pub brs: bool,
+    /// FD 錯誤狀態指示（Error State Indicator）
+    #[serde(default)]
+    pub esi: bool,
+    /// 29-bit 延伸 ID；建立時 ID 超過 11 bits 會自動設定
+    #[serde(default)]
+    pub extended: bool,
+    /// 遠端請求訊框（RTR），資料內容無意義，只以長度表示 DLC
+    #[serde(default)]
+    pub rtr: bool,
+    pub data: Payload,
+    /// 主機收發時間（自 UNIX epoch 起的微秒數）
+    #[serde(default)]
+    pub timestamp: u64,
+    /// 裝置硬體時間戳記（微秒，已處理計數器回繞），裝置未提供時為 None；
+    /// 解析度依裝置而定，ControlCAN 為 0.1 ms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub device_timestamp: Option<u64>,
+    #[serde(default)]
+    pub direction: FrameDirection,
+}
+
+impl CanFrame {
+    /// 建立 Classic CAN 訊框，超過 8 bytes 的部分會被截斷
+    pub fn new(channel: u32, id: u32, payload: &[u8]) -> Self {
+        Self::with_protocol(channel, id, FrameProtocol::Classic, payload)
+    }
+
+    /// 建立 CAN FD 訊框，長度會補零至下一個合法的 FD 長度
+    pub fn new_fd(channel: u32, id: u32, payload: &[u8], brs: bool) -> Self {
+        let mut frame = Self::with_protocol(channel, id, FrameProtocol::Fd, payload);
+        frame.brs = brs;
+        frame
+    }
+
+    /// 建立遠端請求訊框（Classic CAN），DLC 最大為 8
+    pub fn new_remote(channel: u32, id: u32, dlc: u8) -> Self {
+        let mut frame = Self::new(channel, id, &[0u8; 8][..dlc.min(8) as usize]);
+        frame.rtr = true;
+        frame
+    }
+
+    fn with_protocol(channel: u32, id: u32, protocol: FrameProtocol, payload: &[u8]) -> Self {
+        let mut frame = Self {
+            channel,
+            id,
+            protocol,
+            extended: id > MAX_STANDARD_ID,
+            ..Default::default()
+        };
+        frame.set_payload(payload);
+        frame
+    }
+
+    /// 依協定上限設定資料；FD 會補零至合法長度
+    pub fn set_payload(&mut self, payload: &[u8]) {
+        let len = payload.len().min(self.protocol.max_payload_len());
+        let mut data = Payload::new(&payload[..len]);
+        if self.protocol == FrameProtocol::Fd {
+            let padded = dlc_to_len(len_to_dlc(len));
+            data = Payload::new(&[&payload[..len], &vec![0u8; padded - len][..]].concat());
+        }
+        self.data = data;
+    }
+
+    /// 回傳有效的資料位元組
+    pub fn payload(&self) -> &[u8] {
+        self.data.as_slice()
+    }
+
+    /// 依資料長度計算 DLC 編碼
+    pub fn dlc(&self) -> u8 {
+        len_to_dlc(self.data.len())
+    }
+
+    /// 與前一筆訊框的時間差（微秒）；兩筆都有裝置時間戳記時以裝置時間計算，
+    /// 不受主機排程與批次讀取的抖動影響
+    pub fn delta_us(&self, prev: &CanFrame) -> u64 {
+        match (self.device_timestamp, prev.device_timestamp) {
+            (Some(now), Some(before)) => now.saturating_sub(before),
+            _ => self.timestamp.saturating_sub(prev.timestamp),
+        }
+    }
+}
+
+/// 目前主機時間（自 UNIX epoch 起的微秒數）
+pub fn now_micros() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_micros() as u64)
+        .unwrap_or(0)
+}
@@ -0,0 +1,70 @@
+use crate::can::cantypes::CanFrame;
+use std::collections::VecDeque;
+
+/// 完整擷取預設的筆數上限（約 100 MB）
+pub const DEFAULT_CAPTURE_LIMIT: usize = 1_000_000;
+
+/// 本次工作階段收發的所有訊框，不受 Data 緩衝區容量限制，供匯出使用；
+/// 超過上限時才丟棄最舊的訊框並計數
+pub struct CaptureStore {
+    frames: VecDeque<CanFrame>,
+    limit: usize,
+    dropped: u64,
+    /// 第一筆訊框的時間，作為相對時間的起點
+    started: Option<u64>,
+}
+
+impl Default for CaptureStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_CAPTURE_LIMIT)
+    }
+}
+
+impl CaptureStore {
+    pub fn new(limit: usize) -> Self {
+        Self {
+            frames: VecDeque::new(),
+            limit: limit.max(1),
+            dropped: 0,
+            started: None,
+        }
+    }
+
+    pub fn record(&mut self, frame: &CanFrame) {
+        self.started.get_or_insert(frame.timestamp);
+        if self.frames.len() >= self.limit {
+            self.frames.pop_front();
+            self.dropped += 1;
+        }
+        self.frames.push_back(*frame);
+    }
+
+    /// 依收發順序列出擷取的訊框
+    pub fn frames(&self) -> impl Iterator<Item = &CanFrame> {
+        self.frames.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.frames.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// 因超過上限而丟棄的訊框數
+    pub fn dropped(&self) -> u64 {
+        self.dropped
+    }
+
+    /// 擷取開始（第一筆訊框）的時間，清除後重新計算
+    pub fn start_time(&self) -> Option<u64> {
+        self.started
+    }
+
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.dropped = 0;
+        self.started = None;
+    }
+}
@@ -0,0 +1,339 @@
+use crate::can::cantypes::{CanFrame, FrameDirection, FrameProtocol};
+use crate::can::session::SessionInfo;
+use chrono::{Local, NaiveDateTime, TimeZone};
+use flume::{unbounded, Receiver, RecvTimeoutError, Sender};
+use rusqlite::{params, params_from_iter, Connection, OpenFlags};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 每次交易最長的累積時間與筆數
+const COMMIT_INTERVAL: Duration = Duration::from_secs(1);
+const COMMIT_BATCH: usize = 5000;
+
+/// 資料表與索引；時間為 UNIX epoch 微秒，依時間、ID、通道建立索引
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS frames (
+    time_us INTEGER NOT NULL,
+    channel INTEGER NOT NULL,
+    id INTEGER NOT NULL,
+    extended INTEGER NOT NULL,
+    rtr INTEGER NOT NULL,
+    protocol INTEGER NOT NULL,
+    brs INTEGER NOT NULL,
+    esi INTEGER NOT NULL,
+    tx INTEGER NOT NULL,
+    data BLOB NOT NULL
+);
+CREATE INDEX IF NOT EXISTS frames_time ON frames(time_us);
+CREATE INDEX IF NOT EXISTS frames_id_time ON frames(id, time_us);
+CREATE INDEX IF NOT EXISTS frames_channel_time ON frames(channel, time_us);
+CREATE TABLE IF NOT EXISTS session (name TEXT PRIMARY KEY, value TEXT NOT NULL);
+";
+
+fn protocol_code(protocol: FrameProtocol) -> i64 {
+    match protocol {
+        FrameProtocol::Classic => 0,
+        FrameProtocol::Fd => 1,
+        FrameProtocol::Xl => 2,
+    }
+}
+
+fn db_error(path: &str) -> impl Fn(rusqlite::Error) -> String + '_ {
+    move |e| format!("{}: {}", path, e)
+}
+
+/// 解析查詢用的本地時間，格式為 `YYYY-MM-DD HH:MM[:SS[.fff]]`，回傳 UNIX epoch 微秒
+pub fn parse_local_time(text: &str) -> Result<u64, String> {
+    let text = text.trim();
+    let naive = ["%Y-%m-%d %H:%M:%S%.f", "%Y-%m-%d %H:%M"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(text, format).ok())
+        .ok_or_else(|| format!("Invalid time '{}', expected YYYY-MM-DD HH:MM:SS", text))?;
+    let time = Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .ok_or_else(|| format!("Invalid local time '{}'", text))?;
+    u64::try_from(time.timestamp_micros()).map_err(|_| format!("Time before 1970: '{}'", text))
+}
+
+/// 記錄執行緒的狀態，供介面顯示
+#[derive(Debug, Clone, Default)]
+pub struct CaptureDbStatus {
+    pub frames: u64,
+    pub last_error: Option<String>,
+}
+
+/// 將收發的訊框寫入 SQLite 資料庫，背景執行緒以交易批次寫入；
+/// 記錄中也可由其他連線查詢
+pub struct CaptureDb {
+    frame_tx: Sender<CanFrame>,
+    running: Arc<AtomicBool>,
+    status: Arc<Mutex<CaptureDbStatus>>,
+    handle: Option<thread::JoinHandle<()>>,
+    path: String,
+}
+
+impl CaptureDb {
+    /// 開啟（或建立）資料庫並啟動寫入執行緒；既有的資料會保留，新訊框接在後面
+    pub fn start<F>(path: &str, session: &SessionInfo, log: F) -> Result<Self, String>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let err = db_error(path);
+        let mut conn = Connection::open(path).map_err(&err)?;
+        // WAL 讓查詢不會阻塞寫入
+        conn.pragma_update(None, "journal_mode", "WAL")
+            .map_err(&err)?;
+        conn.pragma_update(None, "synchronous", "NORMAL")
+            .map_err(&err)?;
+        conn.execute_batch(SCHEMA).map_err(&err)?;
+        {
+            let tx = conn.transaction().map_err(&err)?;
+            for (name, value) in session.fields() {
+                if !value.is_empty() {
+                    tx.execute(
+                        "INSERT OR REPLACE INTO session (name, value) VALUES (?1, ?2)",
+                        params![name, value],
+                    )
+                    .map_err(&err)?;
+                }
+            }
+            tx.commit().map_err(&err)?;
+        }
+        let (frame_tx, frame_rx) = unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let status = Arc::new(Mutex::new(CaptureDbStatus::default()));
+        let running_flag = Arc::clone(&running);
+        let status_shared = Arc::clone(&status);
+        let db_path = path.to_string();
+        let handle = thread::spawn(move || {
+            log(format!("Capture database recording to {}", db_path));
+            run_writer(&mut conn, frame_rx, running_flag, &status_shared, &log);
+            log(format!(
+                "Capture database recording stopped: {} frames",
+                status_shared.lock().unwrap().frames
+            ));
+        });
+        Ok(Self {
+            frame_tx,
+            running,
+            status,
+            handle: Some(handle),
+            path: path.to_string(),
+        })
+    }
+
+    /// 交由寫入執行緒記錄一筆訊框
+    pub fn record(&self, frame: &CanFrame) {
+        let _ = self.frame_tx.send(*frame);
+    }
+
+    pub fn status(&self) -> CaptureDbStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// 停止並寫完已排入的訊框
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining capture database thread: {:?}", e);
+            }
+        }
+    }
+}
+
+impl Drop for CaptureDb {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn insert_batch(conn: &mut Connection, batch: &[CanFrame]) -> rusqlite::Result<()> {
+    let tx = conn.transaction()?;
+    {
+        let mut insert = tx.prepare_cached(
+            "INSERT INTO frames (time_us, channel, id, extended, rtr, protocol, brs, esi, tx, data)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+        )?;
+        for frame in batch {
+            insert.execute(params![
+                frame.timestamp as i64,
+                frame.channel,
+                frame.id,
+                frame.extended,
+                frame.rtr,
+                protocol_code(frame.protocol),
+                frame.brs,
+                frame.esi,
+                frame.direction == FrameDirection::Tx,
+                frame.data.as_slice(),
+            ])?;
+        }
+    }
+    tx.commit()
+}
+
+fn run_writer<F>(
+    conn: &mut Connection,
+    frame_rx: Receiver<CanFrame>,
+    running: Arc<AtomicBool>,
+    status: &Mutex<CaptureDbStatus>,
+    log: &F,
+) where
+    F: Fn(String),
+{
+    let mut batch = Vec::with_capacity(COMMIT_BATCH);
+    let mut last_commit = Instant::now();
+    // 停止後仍寫完已排入的訊框
+    while running.load(Ordering::SeqCst) || !frame_rx.is_empty() {
+        match frame_rx.recv_timeout(Duration::from_millis(200)) {
+            Ok(frame) => batch.push(frame),
+            Err(RecvTimeoutError::Timeout) => {}
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+        if batch.len() >= COMMIT_BATCH
+            || (!batch.is_empty() && last_commit.elapsed() >= COMMIT_INTERVAL)
+        {
+            commit(conn, &mut batch, status, log);
+            last_commit = Instant::now();
+        }
+    }
+    commit(conn, &mut batch, status, log);
+}
+
+fn commit<F>(
+    conn: &mut Connection,
+    batch: &mut Vec<CanFrame>,
+    status: &Mutex<CaptureDbStatus>,
+    log: &F,
+) where
+    F: Fn(String),
+{
+    if batch.is_empty() {
+        return;
+    }
+    let result = insert_batch(conn, batch);
+    let mut status = status.lock().unwrap();
+    match result {
+        Ok(()) => {
+            status.frames += batch.len() as u64;
+            status.last_error = None;
+        }
+        Err(e) => {
+            // 只記錄第一次錯誤，避免磁碟已滿時洗版
+            if status.last_error.is_none() {
+                log(format!("Capture database write failed: {}", e));
+            }
+            status.last_error = Some(e.to_string());
+        }
+    }
+    batch.clear();
+}
+
+/// 查詢條件，未指定的欄位不限制；時間為 UNIX epoch 微秒，`to_us` 包含在內
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptureQuery {
+    pub id: Option<u32>,
+    pub channel: Option<u32>,
+    pub from_us: Option<u64>,
+    pub to_us: Option<u64>,
+    /// 最多回傳的筆數，0 表示不限
+    pub limit: usize,
+}
+
+/// 資料庫內容摘要
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CaptureSummary {
+    pub frames: u64,
+    pub first_us: Option<u64>,
+    pub last_us: Option<u64>,
+}
+
+fn open_read_only(path: &str) -> Result<Connection, String> {
+    Connection::open_with_flags(
+        path,
+        OpenFlags::SQLITE_OPEN_READ_ONLY | OpenFlags::SQLITE_OPEN_NO_MUTEX,
+    )
+    .map_err(db_error(path))
+}
+
+pub fn summary(path: &str) -> Result<CaptureSummary, String> {
+    let conn = open_read_only(path)?;
+    conn.query_row(
+        "SELECT COUNT(*), MIN(time_us), MAX(time_us) FROM frames",
+        [],
+        |row| {
+            Ok(CaptureSummary {
+                frames: row.get::<_, i64>(0)? as u64,
+                first_us: row.get::<_, Option<i64>>(1)?.map(|t| t as u64),
+                last_us: row.get::<_, Option<i64>>(2)?.map(|t| t as u64),
+            })
+        },
+    )
+    .map_err(db_error(path))
+}
+
+/// 依條件查詢訊框，依時間排序；只讀取符合的列
+pub fn query(path: &str, query: &CaptureQuery) -> Result<Vec<CanFrame>, String> {
+    let conn = open_read_only(path)?;
+    let mut conditions = Vec::new();
+    let mut values: Vec<i64> = Vec::new();
+    for (column, op, value) in [
+        ("id", "=", query.id.map(i64::from)),
+        ("channel", "=", query.channel.map(i64::from)),
+        ("time_us", ">=", query.from_us.map(|t| t as i64)),
+        ("time_us", "<=", query.to_us.map(|t| t as i64)),
+    ] {
+        if let Some(value) = value {
+            values.push(value);
+            conditions.push(format!("{} {} ?{}", column, op, values.len()));
+        }
+    }
+    let mut sql =
+        "SELECT time_us, channel, id, extended, rtr, protocol, brs, esi, tx, data FROM frames"
+            .to_string();
+    if !conditions.is_empty() {
+        sql.push_str(" WHERE ");
+        sql.push_str(&conditions.join(" AND "));
+    }
+    sql.push_str(" ORDER BY time_us");
+    if query.limit > 0 {
+        sql.push_str(&format!(" LIMIT {}", query.limit));
+    }
+    let err = db_error(path);
+    let mut statement = conn.prepare(&sql).map_err(&err)?;
+    let rows = statement
+        .query_map(params_from_iter(values), |row| {
+            let channel: u32 = row.get(1)?;
+            let id: u32 = row.get(2)?;
+            let data: Vec<u8> = row.get(9)?;
+            let mut frame = match row.get::<_, i64>(5)? {
+                0 if row.get::<_, bool>(4)? => {
+                    CanFrame::new_remote(channel, id, data.len().min(8) as u8)
+                }
+                0 => CanFrame::new(channel, id, &data),
+                _ => CanFrame::new_fd(channel, id, &data, row.get(6)?),
+            };
+            if row.get::<_, i64>(5)? == 2 {
+                frame.protocol = FrameProtocol::Xl;
+            }
+            frame.timestamp = row.get::<_, i64>(0)? as u64;
+            frame.extended = row.get(3)?;
+            frame.esi = row.get(7)?;
+            if row.get::<_, bool>(8)? {
+                frame.direction = FrameDirection::Tx;
+            }
+            Ok(frame)
+        })
+        .map_err(&err)?;
+    rows.collect::<Result<Vec<_>, _>>().map_err(&err)
+}
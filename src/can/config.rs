@@ -1,37 +1,581 @@
+use crate::can::latency::LatencyPair;
+use crate::can::txsequence::TxSequence;
+use crate::can::units::UnitConversion;
+use crate::can::watch::WatchExpr;
 use serde::de::{self, Visitor};
 use serde::{Deserialize, Serialize};
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
 use std::fmt;
 use std::fs::File;
 use std::io::BufReader;
 
+/// 目前的設定檔格式版本
+///
+/// 版本紀錄：
+/// - 1：初版（未標示 version 的設定檔視為此版）
+/// - 2：`canbus_config` 的 `endian` 改為 `little` / `big`
+pub const CONFIG_VERSION: u32 = 2;
+
+/// 頂層允許的欄位，其他欄位會產生警告
+const KNOWN_FIELDS: [&str; 12] = [
+    "version",
+    "components",
+    "canbus_config",
+    "latency_pairs",
+    "heartbeats",
+    "counters",
+    "thresholds",
+    "watches",
+    "cycle_times",
+    "checksums",
+    "expected_sequences",
+    "sequences",
+];
+
+/// 合併多個設定檔時，各清單區塊用來判斷「同一項目」的欄位
+const MERGE_KEYS: [(&str, &[&str]); 11] = [
+    ("components", &["key", "type"]),
+    ("canbus_config", &["key"]),
+    ("latency_pairs", &["request_id", "response_id"]),
+    ("heartbeats", &["id"]),
+    ("counters", &["id", "index"]),
+    ("thresholds", &["key"]),
+    ("watches", &["expr"]),
+    ("cycle_times", &["id"]),
+    ("checksums", &["id", "index"]),
+    ("expected_sequences", &["name"]),
+    ("sequences", &["name"]),
+];
+
 /// 整個 YAML 設定檔結構
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Config {
+    /// 設定檔格式版本，載入時會先升級到 `CONFIG_VERSION`
+    #[serde(default = "current_version")]
+    pub version: u32,
     pub components: Vec<Component>,
     pub canbus_config: Vec<CanbusConfigEntry>,
+    /// 選用：請求/回應延遲量測配對
+    #[serde(default)]
+    pub latency_pairs: Vec<LatencyPair>,
+    /// 選用：心跳訊框監看
+    #[serde(default)]
+    pub heartbeats: Vec<HeartbeatConfig>,
+    /// 選用：滾動計數器檢查
+    #[serde(default)]
+    pub counters: Vec<CounterConfig>,
+    /// 選用：訊號門檻警報
+    #[serde(default)]
+    pub thresholds: Vec<ThresholdConfig>,
+    /// 選用：監看運算式警報
+    #[serde(default)]
+    pub watches: Vec<WatchConfig>,
+    /// 選用：一致性檢查的訊框週期
+    #[serde(default)]
+    pub cycle_times: Vec<CycleTimeConfig>,
+    /// 選用：一致性檢查的校驗碼位置
+    #[serde(default)]
+    pub checksums: Vec<ChecksumConfig>,
+    /// 選用：預期的訊息序列
+    #[serde(default)]
+    pub expected_sequences: Vec<SequenceConfig>,
+    /// 選用：可從介面啟動的傳送序列
+    #[serde(default)]
+    pub sequences: Vec<TxSequence>,
+}
+
+/// 元件種類與各自的設定，依 YAML 的 `type` 欄位區分
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type")]
+pub enum ComponentKind {
+    /// 文字顯示數值
+    Label,
+    /// 指針儀表，顯示範圍 min..max（設定檔單位）
+    Gauge { min: f64, max: f64 },
+    /// 指示燈，數值非 0 時以 color 點亮（名稱或 "#RRGGBB"）
+    Led {
+        #[serde(default = "default_led_color")]
+        color: String,
+    },
+    /// 最近數值的折線圖，保留 points 個點
+    Plot {
+        #[serde(default = "default_plot_points")]
+        points: usize,
+    },
+    /// 橫條，顯示範圍 min..max（設定檔單位）
+    Bar { min: f64, max: f64 },
 }
 
-/// YAML 中 components 區塊，描述 UI 元件（例如 Label）
+/// YAML 中 components 區塊，描述 UI 元件
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Component {
-    #[serde(rename = "type")]
-    pub comp_type: String,
+    #[serde(flatten)]
+    pub kind: ComponentKind,
     pub key: String,
     pub text: Option<String>,
     pub unit: Option<String>,
+    /// 版面配置：所屬群組標題，相同群組的元件放在同一區塊
+    #[serde(default)]
+    pub group: Option<String>,
+    /// 版面配置：列與欄（由 0 起算），未指定列的元件依序排在最後
+    #[serde(default)]
+    pub row: Option<u32>,
+    #[serde(default)]
+    pub col: Option<u32>,
+    /// 版面配置：橫跨的欄數，預設 1
+    #[serde(default)]
+    pub span: Option<u32>,
+    /// 額外的顯示單位換算，會與內建換算一起列在單位選單中
+    #[serde(default)]
+    pub conversions: Vec<UnitConversion>,
+    /// 依數值套用的樣式，以設定檔單位的原始值判斷
+    #[serde(default)]
+    pub styles: Vec<StyleRule>,
+}
+
+/// 條件格式：數值落在 [min, max) 時改用指定顏色或文字，未指定的邊界不限制
+///
+/// 多條規則同時符合時以後面的為準，例如先寫「低於 11.5 黃色」再寫「低於 10.5 紅色」。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StyleRule {
+    #[serde(default)]
+    pub min: Option<f64>,
+    #[serde(default)]
+    pub max: Option<f64>,
+    /// 顏色名稱或 "#RRGGBB"
+    #[serde(default)]
+    pub color: Option<String>,
+    /// 取代數值顯示的文字
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+impl StyleRule {
+    pub fn matches(&self, value: f64) -> bool {
+        self.min.is_none_or(|min| value >= min) && self.max.is_none_or(|max| value < max)
+    }
+}
+
+impl Component {
+    /// 目前數值適用的樣式
+    pub fn style_for(&self, value: f64) -> Option<&StyleRule> {
+        self.styles.iter().rev().find(|rule| rule.matches(value))
+    }
 }
 
 /// YAML 中 canbus_config 區塊，描述 CAN bus 資料萃取設定
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CanbusConfigEntry {
     pub key: String,
     #[serde(deserialize_with = "deserialize_hex_or_decimal")]
     pub id: u32,
+    /// 起始位元組與位元組數；指定 `start_bit`/`bit_length` 時可省略
+    #[serde(default)]
     pub index: u8,
+    #[serde(default)]
     pub len: u8,
-    pub endian: u8,
+    pub endian: ByteOrder,
+    /// 位元層級的位置，取代 index/len：Intel 為最低位元、Motorola 為最高位元（DBC 的鋸齒編號）
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_bit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bit_length: Option<u32>,
+    /// 整數型別（如 uint16、int8）或 float/double
     #[serde(rename = "type")]
     pub data_type: String,
+    /// 物理值 = 原始值 × scale + offset，例如溫度 (raw × 0.1) − 40
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    #[serde(default)]
+    pub offset: f64,
+    /// 整數是否為有號數；未指定時 `type` 以 "u" 開頭視為無號數，其餘為有號數
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signed: Option<bool>,
+    /// 狀態名稱，例如 `0: Off`、`1: Charging`；與 DBC 的 `VAL_` 相同以原始值為 key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub values: BTreeMap<i64, String>,
+}
+
+/// 訊號原始值的編碼方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RawType {
+    Unsigned,
+    Signed,
+    /// IEEE 754 單精度，長度需為 4
+    Float,
+    /// IEEE 754 倍精度，長度需為 8
+    Double,
+}
+
+impl CanbusConfigEntry {
+    pub fn raw_type(&self) -> RawType {
+        let data_type = self.data_type.to_ascii_lowercase();
+        match data_type.as_str() {
+            "float" | "float32" | "f32" => RawType::Float,
+            "double" | "float64" | "f64" => RawType::Double,
+            _ => match self.signed {
+                Some(true) => RawType::Signed,
+                Some(false) => RawType::Unsigned,
+                None if data_type.starts_with('u') => RawType::Unsigned,
+                None => RawType::Signed,
+            },
+        }
+    }
+
+    /// 物理值對應的狀態名稱；先換回原始值，非整數時沒有名稱
+    pub fn value_name(&self, value: f64) -> Option<&str> {
+        if self.values.is_empty() {
+            return None;
+        }
+        let raw = (value - self.offset) / self.scale;
+        let rounded = raw.round();
+        if (raw - rounded).abs() > 1e-6 {
+            return None;
+        }
+        self.values.get(&(rounded as i64)).map(String::as_str)
+    }
+
+    /// 訊號的起始位元與位元長度；未指定 `start_bit` 時由 index/len 換算
+    pub fn bit_layout(&self) -> (u32, u32) {
+        match (self.start_bit, self.bit_length) {
+            (Some(start_bit), Some(bit_length)) => (start_bit, bit_length),
+            _ => {
+                let start_bit = self.index as u32 * 8;
+                let bit_length = self.len as u32 * 8;
+                match self.endian {
+                    ByteOrder::Little => (start_bit, bit_length),
+                    // Motorola 的起始位元為第一個位元組的 bit 7
+                    ByteOrder::Big => (start_bit + 7, bit_length),
+                }
+            }
+        }
+    }
+
+    /// 訊號涵蓋到的位元組數（由訊框開頭算起）
+    pub fn byte_end(&self) -> usize {
+        let (start_bit, bit_length) = self.bit_layout();
+        self.endian
+            .bit_positions(start_bit, bit_length)
+            .map(|pos| pos as usize / 8 + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        match (self.start_bit, self.bit_length) {
+            (None, None) if self.len == 0 => {
+                return Err(format!(
+                    "Signal '{}': needs len (or start_bit and bit_length)",
+                    self.key
+                ));
+            }
+            // 與位元層級的位置相同，最多讀 64 位元且不超出 64-byte 訊框
+            (None, None) if self.len > 8 => {
+                return Err(format!("Signal '{}': len must be 1..=8", self.key));
+            }
+            (None, None) if self.byte_end() > 64 => {
+                return Err(format!(
+                    "Signal '{}': index + len is outside a 64-byte frame",
+                    self.key
+                ));
+            }
+            (Some(_), Some(bit_length)) if !(1..=64).contains(&bit_length) => {
+                return Err(format!("Signal '{}': bit_length must be 1..=64", self.key));
+            }
+            // 先檢查起始位元，計算位元範圍時才不會溢位
+            (Some(start_bit), Some(_)) if start_bit >= 512 || self.byte_end() > 64 => {
+                return Err(format!(
+                    "Signal '{}': start_bit is outside a 64-byte frame",
+                    self.key
+                ));
+            }
+            (Some(_), None) | (None, Some(_)) => {
+                return Err(format!(
+                    "Signal '{}': start_bit and bit_length must be given together",
+                    self.key
+                ));
+            }
+            _ => {}
+        }
+        if self.scale == 0.0 || !self.scale.is_finite() {
+            return Err(format!(
+                "Signal '{}': scale must be a non-zero number",
+                self.key
+            ));
+        }
+        let float_bits = match self.raw_type() {
+            RawType::Float => Some(32),
+            RawType::Double => Some(64),
+            RawType::Unsigned | RawType::Signed => None,
+        };
+        match float_bits {
+            Some(bits) if self.bit_layout().1 != bits => Err(format!(
+                "Signal '{}': type {} needs len {} (bit_length {})",
+                self.key,
+                self.data_type,
+                bits / 8,
+                bits
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// 多位元組訊號的位元組順序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ByteOrder {
+    /// Intel
+    Little,
+    /// Motorola
+    Big,
+}
+
+impl ByteOrder {
+    /// 訊號佔用的位元位置（DBC 編號，位元 n 為第 n / 8 個位元組的 bit n % 8），
+    /// 由最高位元排到最低位元；`start_bit` 的意義同 `CanbusConfigEntry::start_bit`
+    pub fn bit_positions(self, start_bit: u32, bit_length: u32) -> impl Iterator<Item = u32> {
+        let mut pos = match self {
+            ByteOrder::Little => start_bit.saturating_add(bit_length.saturating_sub(1)),
+            ByteOrder::Big => start_bit,
+        };
+        (0..bit_length).map(move |_| {
+            let current = pos;
+            pos = match self {
+                ByteOrder::Little => pos.wrapping_sub(1),
+                // 跨位元組時跳到下一個位元組的 bit 7
+                ByteOrder::Big if pos.is_multiple_of(8) => pos.saturating_add(15),
+                ByteOrder::Big => pos - 1,
+            };
+            current
+        })
+    }
+}
+
+/// 心跳監看：指定 ID 超過 timeout_ms 未出現即發出警報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HeartbeatConfig {
+    #[serde(deserialize_with = "deserialize_hex_or_decimal")]
+    pub id: u32,
+    pub timeout_ms: u64,
+}
+
+/// 滾動計數器：指定 ID 的第 index 個 byte 以 mask 取出計數值，應逐筆加一
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CounterConfig {
+    #[serde(deserialize_with = "deserialize_hex_or_decimal")]
+    pub id: u32,
+    pub index: u8,
+    #[serde(default = "default_counter_mask")]
+    pub mask: u8,
+}
+
+/// 訊號門檻：canbus_config 中 key 對應的數值超出 min/max 時發出警報
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ThresholdConfig {
+    pub key: String,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+}
+
+/// 監看運算式：條件成立（並持續 `for` 指定的時間）時發出警報
+///
+/// `hysteresis` 以訊號的設定檔單位表示，警報中時門檻放寬這麼多才解除。
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WatchConfig {
+    /// 例如 `temp > 90 for 5s`
+    pub expr: String,
+    #[serde(default)]
+    pub hysteresis: f64,
+    /// 選用：警報訊息，未指定時使用運算式
+    #[serde(default)]
+    pub message: Option<String>,
+}
+
+/// 週期：指定 ID 的訊框間隔應為 period_ms，誤差超過 tolerance_percent 視為違規
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CycleTimeConfig {
+    #[serde(deserialize_with = "deserialize_hex_or_decimal")]
+    pub id: u32,
+    pub period_ms: f64,
+    #[serde(default = "default_cycle_tolerance")]
+    pub tolerance_percent: f64,
+}
+
+/// 校驗碼演算法
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChecksumKind {
+    /// 其餘位元組 XOR
+    #[default]
+    Xor,
+    /// 其餘位元組相加取低 8 位元
+    Sum,
+    /// SAE J1850 CRC-8（多項式 0x1D，初值與最終 XOR 皆為 0xFF）
+    Crc8,
+}
+
+/// 校驗碼：指定 ID 的第 index 個 byte 為其餘資料位元組的校驗碼
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChecksumConfig {
+    #[serde(deserialize_with = "deserialize_hex_or_decimal")]
+    pub id: u32,
+    pub index: u8,
+    #[serde(default)]
+    pub kind: ChecksumKind,
+}
+
+/// 序列中的一個步驟：指定 ID 須在前一個步驟之後 within_ms 內出現
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceStep {
+    #[serde(deserialize_with = "deserialize_hex_or_decimal")]
+    pub id: u32,
+    /// 未指定時不限時間；第一個步驟為觸發條件，不使用此欄位
+    #[serde(default)]
+    pub within_ms: Option<u64>,
+    /// 顯示在偏差訊息中的名稱，例如 "wake-up"
+    #[serde(default)]
+    pub label: Option<String>,
+}
+
+/// 訊息序列（小型狀態機）：第一個步驟的 ID 出現即開始，其餘步驟須依序在時限內出現
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SequenceConfig {
+    pub name: String,
+    pub steps: Vec<SequenceStep>,
+}
+
+fn default_led_color() -> String {
+    "green".to_string()
+}
+
+fn default_plot_points() -> usize {
+    200
+}
+
+/// 解析顏色名稱或 "#RRGGBB"，回傳 RGB
+pub fn parse_color(text: &str) -> Option<[u8; 3]> {
+    let rgb = match text.to_ascii_lowercase().as_str() {
+        "red" => [220, 50, 50],
+        "green" => [60, 200, 60],
+        "blue" => [70, 130, 230],
+        "yellow" => [230, 210, 40],
+        "orange" => [240, 140, 30],
+        "white" => [240, 240, 240],
+        hex => {
+            let hex = hex.strip_prefix('#')?;
+            if hex.len() != 6 {
+                return None;
+            }
+            let value = u32::from_str_radix(hex, 16).ok()?;
+            [(value >> 16) as u8, (value >> 8) as u8, value as u8]
+        }
+    };
+    Some(rgb)
+}
+
+impl Config {
+    /// 檢查各元件的設定是否合理
+    pub fn validate(&self) -> Result<(), String> {
+        for entry in &self.canbus_config {
+            entry.validate()?;
+        }
+        for comp in &self.components {
+            match &comp.kind {
+                ComponentKind::Gauge { min, max } | ComponentKind::Bar { min, max }
+                    if min.partial_cmp(max) != Some(std::cmp::Ordering::Less) =>
+                {
+                    return Err(format!(
+                        "Component '{}': min ({}) must be less than max ({})",
+                        comp.key, min, max
+                    ));
+                }
+                ComponentKind::Led { color } if parse_color(color).is_none() => {
+                    return Err(format!(
+                        "Component '{}': unknown color '{}'",
+                        comp.key, color
+                    ));
+                }
+                ComponentKind::Plot { points: 0 } => {
+                    return Err(format!("Component '{}': points must be > 0", comp.key));
+                }
+                _ => {}
+            }
+            if let Some(conv) = comp.conversions.iter().find(|c| c.factor == 0.0) {
+                return Err(format!(
+                    "Component '{}': conversion to '{}' must have a non-zero factor",
+                    comp.key, conv.unit
+                ));
+            }
+            for rule in &comp.styles {
+                if let (Some(min), Some(max)) = (rule.min, rule.max) {
+                    if min >= max {
+                        return Err(format!(
+                            "Component '{}': style min ({}) must be less than max ({})",
+                            comp.key, min, max
+                        ));
+                    }
+                }
+                if let Some(color) = rule.color.as_deref().filter(|c| parse_color(c).is_none()) {
+                    return Err(format!(
+                        "Component '{}': unknown style color '{}'",
+                        comp.key, color
+                    ));
+                }
+            }
+        }
+        if let Some(cycle) = self.cycle_times.iter().find(|c| c.period_ms <= 0.0) {
+            return Err(format!(
+                "Cycle time 0x{:X}: period_ms must be > 0",
+                cycle.id
+            ));
+        }
+        for sequence in &self.expected_sequences {
+            if sequence.steps.is_empty() {
+                return Err(format!(
+                    "Sequence '{}': steps must not be empty",
+                    sequence.name
+                ));
+            }
+            if sequence.steps.iter().any(|s| s.within_ms == Some(0)) {
+                return Err(format!(
+                    "Sequence '{}': within_ms must be > 0",
+                    sequence.name
+                ));
+            }
+        }
+        for sequence in &self.sequences {
+            sequence.validate()?;
+        }
+        for watch in &self.watches {
+            let expr = WatchExpr::parse(&watch.expr).map_err(|e| format!("Watch: {}", e))?;
+            let unknown = expr
+                .keys()
+                .find(|key| !self.canbus_config.iter().any(|e| e.key == *key))
+                .map(|key| key.to_string());
+            if let Some(key) = unknown {
+                return Err(format!("Watch '{}': unknown signal '{}'", watch.expr, key));
+            }
+        }
+        Ok(())
+    }
+}
+
+fn current_version() -> u32 {
+    CONFIG_VERSION
+}
+
+fn default_counter_mask() -> u8 {
+    0x0F
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+fn default_cycle_tolerance() -> f64 {
+    20.0
 }
 
 /// 自訂 Visitor 用以解析 u32，支援十進位與十六進位格式（例如 "0xF2"）
@@ -71,10 +615,192 @@ where
     deserializer.deserialize_any(HexOrDecimalVisitor)
 }
 
-/// 載入 YAML 設定檔，並反序列化成 Config 結構
-pub fn load_config(file_path: &str) -> Result<Config, Box<dyn std::error::Error>> {
-    let file = File::open(file_path)?;
-    let reader = BufReader::new(file);
-    let config = serde_yaml::from_reader(reader)?;
-    Ok(config)
+/// v1 → v2：數字表示的 endian（0 為 little，其他為 big）改為名稱
+fn migrate_v1(root: &mut Mapping, warnings: &mut Vec<String>) {
+    let Some(Value::Sequence(entries)) = root.get_mut("canbus_config") else {
+        return;
+    };
+    let mut converted = 0;
+    for entry in entries.iter_mut() {
+        if let Some(endian) = entry.get_mut("endian") {
+            if let Some(n) = endian.as_u64() {
+                *endian = Value::from(if n == 0 { "little" } else { "big" });
+                converted += 1;
+            }
+        }
+    }
+    if converted > 0 {
+        warnings.push(format!(
+            "canbus_config: numeric 'endian' is deprecated, use 'little' or 'big' ({} entries converted)",
+            converted
+        ));
+    }
+}
+
+/// 各版本的升級步驟，第 n 項將版本 n+1 升級到 n+2
+const MIGRATIONS: [fn(&mut Mapping, &mut Vec<String>); 1] = [migrate_v1];
+
+/// 將任意版本的設定升級到 `CONFIG_VERSION`，回傳過程中的警告
+pub fn migrate(value: &mut Value) -> Result<Vec<String>, String> {
+    let root = value
+        .as_mapping_mut()
+        .ok_or_else(|| "Config must be a mapping at the top level".to_string())?;
+    let mut warnings = Vec::new();
+    let version = match root.get("version") {
+        None => {
+            warnings.push(format!(
+                "No 'version' field; assuming version 1 (current is {})",
+                CONFIG_VERSION
+            ));
+            1
+        }
+        Some(v) => v
+            .as_u64()
+            .and_then(|v| u32::try_from(v).ok())
+            .filter(|&v| v >= 1)
+            .ok_or_else(|| format!("Invalid config version: {:?}", v))?,
+    };
+    if version > CONFIG_VERSION {
+        return Err(format!(
+            "Config version {} is newer than supported version {}; please update the tool",
+            version, CONFIG_VERSION
+        ));
+    }
+    for key in root.keys() {
+        match key.as_str() {
+            Some(name) if KNOWN_FIELDS.contains(&name) => {}
+            _ => warnings.push(format!("Unknown top-level field {:?} is ignored", key)),
+        }
+    }
+    for migration in &MIGRATIONS[version as usize - 1..] {
+        migration(root, &mut warnings);
+    }
+    if version < CONFIG_VERSION {
+        warnings.push(format!(
+            "Config migrated from version {} to {}; save it with 'version: {}' to silence these warnings",
+            version, CONFIG_VERSION, CONFIG_VERSION
+        ));
+    }
+    root.insert(Value::from("version"), Value::from(CONFIG_VERSION));
+    Ok(warnings)
+}
+
+/// 載入 YAML 設定檔，升級到目前版本後反序列化成 Config 結構；
+/// 一併回傳升級與已淘汰欄位的警告
+pub fn load_config(file_path: &str) -> Result<(Config, Vec<String>), Box<dyn std::error::Error>> {
+    load_configs(&[file_path])
+}
+
+/// 依序載入多個設定檔並合併，例如共用的車輛基本設定加上專案的覆蓋設定
+///
+/// 各檔先個別升級到目前版本。清單區塊依 `MERGE_KEYS` 判斷同一項目：
+/// 後面檔案的項目取代前面檔案的同一項目（內容不同時產生警告），其餘項目依序附加；
+/// 其他欄位由後面的檔案覆蓋。
+pub fn load_configs(
+    file_paths: &[&str],
+) -> Result<(Config, Vec<String>), Box<dyn std::error::Error>> {
+    let mut merged = Mapping::new();
+    let mut warnings = Vec::new();
+    // (區塊, 項目) → 定義該項目的檔案
+    let mut origins: HashMap<(String, String), &str> = HashMap::new();
+    for &path in file_paths {
+        let file = File::open(path).map_err(|e| format!("{}: {}", path, e))?;
+        let mut value: Value = serde_yaml::from_reader(BufReader::new(file))
+            .map_err(|e| format!("{}: {}", path, e))?;
+        let file_warnings = migrate(&mut value).map_err(|e| format!("{}: {}", path, e))?;
+        if file_paths.len() > 1 {
+            warnings.extend(
+                file_warnings
+                    .into_iter()
+                    .map(|w| format!("{}: {}", path, w)),
+            );
+        } else {
+            warnings.extend(file_warnings);
+        }
+        let Value::Mapping(root) = value else {
+            continue;
+        };
+        for (key, value) in root {
+            let name = key.as_str().unwrap_or_default().to_string();
+            let fields = MERGE_KEYS.iter().find(|(n, _)| *n == name).map(|(_, f)| *f);
+            let (Some(fields), Value::Sequence(entries)) = (fields, &value) else {
+                if name != "version" && merged.get(&key).is_some_and(|old| *old != value) {
+                    warnings.push(format!("{}: '{}' overrides an earlier config", path, name));
+                }
+                merged.insert(key, value);
+                continue;
+            };
+            if !merged.get(&key).is_some_and(Value::is_sequence) {
+                merged.insert(key.clone(), Value::Sequence(Vec::new()));
+            }
+            let Some(Value::Sequence(target)) = merged.get_mut(&key) else {
+                continue;
+            };
+            for entry in entries {
+                let id = merge_key(entry, fields);
+                let previous = id
+                    .as_ref()
+                    .and_then(|id| origins.get(&(name.clone(), id.clone())))
+                    .filter(|&&origin| origin != path);
+                match (previous, &id) {
+                    (Some(&origin), Some(id)) => {
+                        let position = target
+                            .iter()
+                            .position(|e| merge_key(e, fields).as_ref() == Some(id));
+                        if let Some(position) = position {
+                            if target[position] != *entry {
+                                warnings.push(format!(
+                                    "{}: {} '{}' overrides the definition from {}",
+                                    path, name, id, origin
+                                ));
+                            }
+                            target[position] = entry.clone();
+                        } else {
+                            target.push(entry.clone());
+                        }
+                    }
+                    _ => target.push(entry.clone()),
+                }
+                if let Some(id) = id {
+                    origins.insert((name.clone(), id), path);
+                }
+            }
+        }
+    }
+    let config: Config = serde_yaml::from_value(Value::Mapping(merged))?;
+    config.validate()?;
+    // `訊息.訊號` 形式的 key 由 DBC 提供，載入設定檔時無從檢查
+    for comp in &config.components {
+        if !comp.key.contains('.') && !config.canbus_config.iter().any(|e| e.key == comp.key) {
+            warnings.push(format!(
+                "Component '{}' has no canbus_config entry and will show no value",
+                comp.key
+            ));
+        }
+    }
+    Ok((config, warnings))
+}
+
+/// 清單項目的識別字串，ID 類欄位統一以十六進位表示
+fn merge_key(entry: &Value, fields: &[&str]) -> Option<String> {
+    let parts = fields
+        .iter()
+        .map(|&field| {
+            let value = entry.get(field)?;
+            let number = match value {
+                Value::Number(n) => n.as_u64(),
+                Value::String(s) => s
+                    .strip_prefix("0x")
+                    .map_or_else(|| s.parse().ok(), |hex| u64::from_str_radix(hex, 16).ok()),
+                _ => None,
+            };
+            Some(match (number, value) {
+                (Some(n), _) if field.ends_with("id") => format!("0x{:X}", n),
+                (Some(n), _) => n.to_string(),
+                (None, Value::String(s)) => s.clone(),
+                (None, other) => serde_yaml::to_string(other).ok()?.trim().to_string(),
+            })
+        })
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
 }
@@ -0,0 +1,397 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::config::{ChecksumConfig, ChecksumKind, Config, CounterConfig, CycleTimeConfig};
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// 未定義 ID 的明細最多列出的數量
+const UNDEFINED_ID_LIST: usize = 10;
+
+/// 依校驗碼演算法計算 `data` 中除了 `skip` 位置以外所有位元組的校驗碼
+pub fn checksum(kind: ChecksumKind, data: &[u8], skip: usize) -> u8 {
+    let bytes = data
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != skip)
+        .map(|(_, b)| *b);
+    match kind {
+        ChecksumKind::Xor => bytes.fold(0, |acc, b| acc ^ b),
+        ChecksumKind::Sum => bytes.fold(0u8, |acc, b| acc.wrapping_add(b)),
+        ChecksumKind::Crc8 => {
+            let mut crc = 0xFFu8;
+            for b in bytes {
+                crc ^= b;
+                for _ in 0..8 {
+                    crc = if crc & 0x80 != 0 {
+                        (crc << 1) ^ 0x1D
+                    } else {
+                        crc << 1
+                    };
+                }
+            }
+            crc ^ 0xFF
+        }
+    }
+}
+
+/// 檢查項目
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CheckKind {
+    /// 資料長度不足以容納設定檔定義的訊號
+    Dlc,
+    /// 匯流排上出現設定檔未定義的 ID
+    UndefinedId,
+    CycleTime,
+    Counter,
+    Checksum,
+    /// 訊息序列，結果由 `SequenceValidator` 產生
+    Sequence,
+}
+
+impl CheckKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            CheckKind::Dlc => "DLC",
+            CheckKind::UndefinedId => "Undefined IDs",
+            CheckKind::CycleTime => "Cycle time",
+            CheckKind::Counter => "Counter",
+            CheckKind::Checksum => "Checksum",
+            CheckKind::Sequence => "Sequence",
+        }
+    }
+}
+
+/// 單一檢查的結果
+#[derive(Debug, Clone)]
+pub struct CheckResult {
+    pub kind: CheckKind,
+    /// 檢查對象，例如 "0x123"
+    pub target: String,
+    /// 已檢查的訊框數
+    pub checked: u64,
+    pub violations: u64,
+    pub detail: String,
+}
+
+impl CheckResult {
+    pub fn passed(&self) -> bool {
+        self.violations == 0
+    }
+
+    /// 尚未看到任何相關訊框
+    pub fn untested(&self) -> bool {
+        self.checked == 0
+    }
+
+    pub fn verdict(&self) -> &'static str {
+        if self.untested() {
+            "N/A"
+        } else if self.passed() {
+            "PASS"
+        } else {
+            "FAIL"
+        }
+    }
+}
+
+#[derive(Default)]
+struct Tally {
+    checked: u64,
+    violations: u64,
+}
+
+impl Tally {
+    fn record(&mut self, ok: bool) {
+        self.checked += 1;
+        if !ok {
+            self.violations += 1;
+        }
+    }
+}
+
+struct DlcCheck {
+    expected: usize,
+    tally: Tally,
+    shortest: Option<usize>,
+}
+
+struct CycleCheck {
+    config: CycleTimeConfig,
+    last: Option<CanFrame>,
+    tally: Tally,
+    min_ms: Option<f64>,
+    max_ms: Option<f64>,
+}
+
+struct CounterCheck {
+    config: CounterConfig,
+    last: Option<u8>,
+    tally: Tally,
+}
+
+struct ChecksumCheck {
+    config: ChecksumConfig,
+    tally: Tally,
+}
+
+/// 對即時流量執行的一致性檢查：DLC、未定義 ID、週期、計數器與校驗碼
+#[derive(Default)]
+pub struct ConformanceSuite {
+    dlc: BTreeMap<u32, DlcCheck>,
+    defined: BTreeSet<u32>,
+    undefined: BTreeMap<u32, u64>,
+    frames: u64,
+    cycles: Vec<CycleCheck>,
+    counters: Vec<CounterCheck>,
+    checksums: Vec<ChecksumCheck>,
+}
+
+impl ConformanceSuite {
+    /// 以 YAML 設定建立檢查項目，並清除先前的結果
+    pub fn load(&mut self, config: &Config) {
+        *self = Self::default();
+        for entry in &config.canbus_config {
+            let end = entry.byte_end();
+            let check = self.dlc.entry(entry.id).or_insert(DlcCheck {
+                expected: 0,
+                tally: Tally::default(),
+                shortest: None,
+            });
+            check.expected = check.expected.max(end);
+        }
+        self.cycles = config
+            .cycle_times
+            .iter()
+            .map(|c| CycleCheck {
+                config: c.clone(),
+                last: None,
+                tally: Tally::default(),
+                min_ms: None,
+                max_ms: None,
+            })
+            .collect();
+        self.counters = config
+            .counters
+            .iter()
+            .map(|c| CounterCheck {
+                config: c.clone(),
+                last: None,
+                tally: Tally::default(),
+            })
+            .collect();
+        self.checksums = config
+            .checksums
+            .iter()
+            .map(|c| ChecksumCheck {
+                config: c.clone(),
+                tally: Tally::default(),
+            })
+            .collect();
+        self.defined = self
+            .dlc
+            .keys()
+            .copied()
+            .chain(config.heartbeats.iter().map(|h| h.id))
+            .chain(config.cycle_times.iter().map(|c| c.id))
+            .chain(config.counters.iter().map(|c| c.id))
+            .chain(config.checksums.iter().map(|c| c.id))
+            .chain(
+                config
+                    .expected_sequences
+                    .iter()
+                    .flat_map(|s| s.steps.iter().map(|step| step.id)),
+            )
+            .collect();
+    }
+
+    /// 清除結果但保留檢查項目
+    pub fn reset(&mut self) {
+        for check in self.dlc.values_mut() {
+            check.tally = Tally::default();
+            check.shortest = None;
+        }
+        self.undefined.clear();
+        self.frames = 0;
+        for check in &mut self.cycles {
+            check.last = None;
+            check.tally = Tally::default();
+            check.min_ms = None;
+            check.max_ms = None;
+        }
+        for check in &mut self.counters {
+            check.last = None;
+            check.tally = Tally::default();
+        }
+        for check in &mut self.checksums {
+            check.tally = Tally::default();
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.defined.is_empty()
+    }
+
+    /// 處理一筆收到的訊框
+    pub fn process(&mut self, frame: &CanFrame) {
+        if self.defined.is_empty() {
+            return;
+        }
+        self.frames += 1;
+        let payload = frame.payload();
+        if !self.defined.contains(&frame.id) {
+            *self.undefined.entry(frame.id).or_default() += 1;
+        }
+        if let Some(check) = self.dlc.get_mut(&frame.id) {
+            check.tally.record(payload.len() >= check.expected);
+            check.shortest = Some(
+                check
+                    .shortest
+                    .map_or(payload.len(), |s| s.min(payload.len())),
+            );
+        }
+        for check in self.cycles.iter_mut().filter(|c| c.config.id == frame.id) {
+            if let Some(last) = check.last {
+                let period_ms = frame.delta_us(&last) as f64 / 1000.0;
+                let expected = check.config.period_ms;
+                let tolerance = expected * check.config.tolerance_percent / 100.0;
+                check
+                    .tally
+                    .record((period_ms - expected).abs() <= tolerance);
+                check.min_ms = Some(check.min_ms.map_or(period_ms, |m| m.min(period_ms)));
+                check.max_ms = Some(check.max_ms.map_or(period_ms, |m| m.max(period_ms)));
+            }
+            check.last = Some(*frame);
+        }
+        for check in self.counters.iter_mut().filter(|c| c.config.id == frame.id) {
+            let mask = check.config.mask;
+            let Some(&byte) = payload.get(check.config.index as usize) else {
+                continue;
+            };
+            if mask == 0 {
+                continue;
+            }
+            let shift = mask.trailing_zeros();
+            let value = (byte & mask) >> shift;
+            let modulo = (mask >> shift) as u16 + 1;
+            if let Some(last) = check.last {
+                check
+                    .tally
+                    .record(value == ((last as u16 + 1) % modulo) as u8);
+            }
+            check.last = Some(value);
+        }
+        for check in self
+            .checksums
+            .iter_mut()
+            .filter(|c| c.config.id == frame.id)
+        {
+            let index = check.config.index as usize;
+            let Some(&actual) = payload.get(index) else {
+                check.tally.record(false);
+                continue;
+            };
+            check
+                .tally
+                .record(actual == checksum(check.config.kind, payload, index));
+        }
+    }
+
+    /// 目前所有檢查的結果
+    pub fn results(&self) -> Vec<CheckResult> {
+        let mut results = Vec::new();
+        for (id, check) in &self.dlc {
+            results.push(CheckResult {
+                kind: CheckKind::Dlc,
+                target: format!("0x{:X}", id),
+                checked: check.tally.checked,
+                violations: check.tally.violations,
+                detail: match check.shortest {
+                    Some(shortest) => format!(
+                        "expected >= {} bytes, shortest {}",
+                        check.expected, shortest
+                    ),
+                    None => format!("expected >= {} bytes", check.expected),
+                },
+            });
+        }
+        if !self.defined.is_empty() {
+            let mut ids: Vec<String> = self
+                .undefined
+                .keys()
+                .take(UNDEFINED_ID_LIST)
+                .map(|id| format!("0x{:X}", id))
+                .collect();
+            if self.undefined.len() > UNDEFINED_ID_LIST {
+                ids.push(format!("... ({} total)", self.undefined.len()));
+            }
+            results.push(CheckResult {
+                kind: CheckKind::UndefinedId,
+                target: "all".to_string(),
+                checked: self.frames,
+                violations: self.undefined.values().sum(),
+                detail: ids.join(" "),
+            });
+        }
+        for check in &self.cycles {
+            let range = match (check.min_ms, check.max_ms) {
+                (Some(min), Some(max)) => format!(", observed {:.1}-{:.1} ms", min, max),
+                _ => String::new(),
+            };
+            results.push(CheckResult {
+                kind: CheckKind::CycleTime,
+                target: format!("0x{:X}", check.config.id),
+                checked: check.tally.checked,
+                violations: check.tally.violations,
+                detail: format!(
+                    "{} ms ±{}%{}",
+                    check.config.period_ms, check.config.tolerance_percent, range
+                ),
+            });
+        }
+        for check in &self.counters {
+            results.push(CheckResult {
+                kind: CheckKind::Counter,
+                target: format!("0x{:X}", check.config.id),
+                checked: check.tally.checked,
+                violations: check.tally.violations,
+                detail: format!(
+                    "byte {} mask 0x{:02X}",
+                    check.config.index, check.config.mask
+                ),
+            });
+        }
+        for check in &self.checksums {
+            results.push(CheckResult {
+                kind: CheckKind::Checksum,
+                target: format!("0x{:X}", check.config.id),
+                checked: check.tally.checked,
+                violations: check.tally.violations,
+                detail: format!("byte {} {:?}", check.config.index, check.config.kind),
+            });
+        }
+        results
+    }
+}
+
+/// 將檢查結果寫成 CSV
+pub fn export_results_csv(file_path: &str, results: &[CheckResult]) -> Result<(), String> {
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {}", file_path, e);
+    let file = File::create(file_path).map_err(write_error)?;
+    let mut writer = BufWriter::new(file);
+    writeln!(writer, "check,target,result,checked,violations,detail").map_err(write_error)?;
+    for r in results {
+        writeln!(
+            writer,
+            "{},{},{},{},{},\"{}\"",
+            r.kind.label(),
+            r.target,
+            r.verdict(),
+            r.checked,
+            r.violations,
+            r.detail.replace('"', "\"\"")
+        )
+        .map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)
+}
@@ -0,0 +1,183 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::{CanFrame, MAX_PAYLOAD_LEN};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 排程中的一筆訊框，`offset_us` 為相對於開始播放的時間
+#[derive(Debug, Clone)]
+pub struct ScheduledFrame {
+    pub offset_us: u64,
+    pub frame: CanFrame,
+}
+
+fn parse_u32(text: &str) -> Result<u32, String> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => text.parse(),
+    }
+    .map_err(|_| format!("invalid number '{}'", text))
+}
+
+pub(crate) fn parse_data(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text.chars().filter(|c| c.is_ascii_hexdigit()).collect();
+    let tokens: Vec<&str> = text.split_whitespace().collect();
+    // 支援 "01 02 03" 與 "010203" 兩種寫法
+    let bytes = if tokens.len() > 1 {
+        tokens
+            .iter()
+            .map(|t| u8::from_str_radix(t, 16).map_err(|_| format!("invalid byte '{}'", t)))
+            .collect::<Result<Vec<_>, _>>()?
+    } else {
+        if !digits.len().is_multiple_of(2) {
+            return Err(format!("odd number of hex digits in '{}'", text));
+        }
+        (0..digits.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap())
+            .collect()
+    };
+    if bytes.len() > MAX_PAYLOAD_LEN {
+        return Err(format!("payload longer than {} bytes", MAX_PAYLOAD_LEN));
+    }
+    Ok(bytes)
+}
+
+/// 讀取 CSV 傳送排程，欄位為 `time_offset,id,data[,channel]`
+///
+/// 時間以秒為單位；若標題列第一欄名稱以 "ms" 結尾則以毫秒解讀。
+/// 資料超過 8 bytes 的列會以 CAN FD 送出。回傳依時間排序的訊框。
+pub fn load_csv_schedule(
+    file_path: &str,
+    default_channel: u32,
+) -> Result<Vec<ScheduledFrame>, String> {
+    let file = File::open(file_path).map_err(|e| format!("Failed to open {}: {}", file_path, e))?;
+    let mut scale = 1_000_000.0;
+    let mut rows = Vec::new();
+    // 標題列只能是第一個非空白、非註解的行
+    let mut first = true;
+    for (number, line) in BufReader::new(file).lines().enumerate() {
+        let line = line.map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let header = std::mem::take(&mut first);
+        let Ok(offset) = fields[0].parse::<f64>() else {
+            if header {
+                // 標題列
+                if fields[0].to_ascii_lowercase().ends_with("ms") {
+                    scale = 1000.0;
+                }
+                continue;
+            }
+            return Err(format!("Line {}: invalid time '{}'", number + 1, fields[0]));
+        };
+        if fields.len() < 3 {
+            return Err(format!(
+                "Line {}: expected time_offset,id,data[,channel]",
+                number + 1
+            ));
+        }
+        if offset < 0.0 {
+            return Err(format!("Line {}: negative time offset", number + 1));
+        }
+        let id = parse_u32(fields[1]).map_err(|e| format!("Line {}: {}", number + 1, e))?;
+        let data = parse_data(fields[2]).map_err(|e| format!("Line {}: {}", number + 1, e))?;
+        let channel = match fields.get(3) {
+            Some(ch) if !ch.is_empty() => {
+                parse_u32(ch).map_err(|e| format!("Line {}: {}", number + 1, e))?
+            }
+            _ => default_channel,
+        };
+        let frame = if data.len() > 8 {
+            CanFrame::new_fd(channel, id, &data, false)
+        } else {
+            CanFrame::new(channel, id, &data)
+        };
+        rows.push(ScheduledFrame {
+            offset_us: (offset * scale).round() as u64,
+            frame,
+        });
+    }
+    // 穩定排序，同一時間的列保持檔案中的順序
+    rows.sort_by_key(|row| row.offset_us);
+    Ok(rows)
+}
+
+/// 執行中的一次性排程播放
+pub struct SchedulePlayback {
+    running: Arc<AtomicBool>,
+    sent: Arc<AtomicUsize>,
+    total: usize,
+}
+
+impl SchedulePlayback {
+    /// 在背景執行緒依各列的時間偏移送出，時間以播放開始為基準，不累積誤差
+    pub fn start<F>(rows: Vec<ScheduledFrame>, can_app: SharedCan, log: F) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let sent = Arc::new(AtomicUsize::new(0));
+        let total = rows.len();
+        let running_flag = Arc::clone(&running);
+        let sent_count = Arc::clone(&sent);
+        thread::spawn(move || {
+            log(format!("Schedule started ({} frames)", rows.len()));
+            let start = Instant::now();
+            for (index, row) in rows.iter().enumerate() {
+                let due = start + Duration::from_micros(row.offset_us);
+                loop {
+                    if !running_flag.load(Ordering::SeqCst) {
+                        log(format!("Schedule stopped after {} frames", index));
+                        return;
+                    }
+                    let now = Instant::now();
+                    if now >= due {
+                        break;
+                    }
+                    thread::sleep((due - now).min(Duration::from_millis(1)));
+                }
+                if let Err(e) = can_app.send_frame(&row.frame) {
+                    log(format!("Schedule aborted at row {}: {}", index + 1, e));
+                    running_flag.store(false, Ordering::SeqCst);
+                    return;
+                }
+                sent_count.fetch_add(1, Ordering::SeqCst);
+            }
+            log("Schedule finished".to_string());
+            running_flag.store(false, Ordering::SeqCst);
+        });
+        Self {
+            running,
+            sent,
+            total,
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    pub fn progress(&self) -> (usize, usize) {
+        (self.sent.load(Ordering::SeqCst), self.total)
+    }
+}
+
+impl Drop for SchedulePlayback {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,471 @@
+use crate::can::cantypes::{CanFrame, MAX_STANDARD_ID};
+use crate::can::config::ByteOrder;
+use crate::can::signals::{read_bits, write_bits};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+
+/// DBC 訊息 ID 的最高位元表示 29-bit 延伸 ID
+const DBC_EXTENDED_FLAG: u32 = 0x8000_0000;
+
+/// 訊號的多工設定
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Multiplex {
+    #[default]
+    None,
+    /// 多工選擇訊號（`M`）
+    Multiplexor,
+    /// 只在多工選擇值等於此值時有效（`m<n>`）
+    Multiplexed(u64),
+}
+
+/// DBC 中的一個訊號（`SG_`）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbcSignal {
+    pub name: String,
+    /// Intel 為最低位元的位置，Motorola 為最高位元的位置（DBC 的鋸齒編號）
+    pub start_bit: u32,
+    pub length: u32,
+    pub byte_order: ByteOrder,
+    pub signed: bool,
+    pub factor: f64,
+    pub offset: f64,
+    pub min: f64,
+    pub max: f64,
+    pub unit: String,
+    pub multiplex: Multiplex,
+    /// `VAL_` 定義的數值名稱，以原始值對應
+    pub value_names: Vec<(i64, String)>,
+}
+
+impl DbcSignal {
+    /// 依位元順序取出原始值（未做符號延伸）；資料長度不足時回傳 None
+    pub fn raw_value(&self, data: &[u8]) -> Option<u64> {
+        read_bits(data, self.start_bit, self.length, self.byte_order)
+    }
+
+    /// 物理值換算為原始值後寫入 `data`；超出訊號範圍或資料長度不足時回傳錯誤
+    pub fn encode(&self, value: f64, data: &mut [u8]) -> Result<(), String> {
+        if self.length == 0 || self.length > 64 || self.factor == 0.0 {
+            return Err(format!("Signal {}: unsupported definition", self.name));
+        }
+        // [0|0] 表示 DBC 未限制物理值範圍
+        if self.min < self.max && !(self.min..=self.max).contains(&value) {
+            return Err(format!(
+                "Signal {}: {} is out of range {}..={}",
+                self.name, value, self.min, self.max
+            ));
+        }
+        let raw = ((value - self.offset) / self.factor).round();
+        let bits = self.length as i32;
+        let (raw_min, raw_max) = if self.signed {
+            (-(2f64.powi(bits - 1)), 2f64.powi(bits - 1) - 1.0)
+        } else {
+            (0.0, 2f64.powi(bits) - 1.0)
+        };
+        if !(raw_min..=raw_max).contains(&raw) {
+            return Err(format!(
+                "Signal {}: raw value {} does not fit {} bits",
+                self.name, raw, self.length
+            ));
+        }
+        // 負數以二補數表示，只寫入訊號長度內的位元
+        let raw = if raw < 0.0 {
+            raw as i64 as u64
+        } else {
+            raw as u64
+        };
+        write_bits(data, self.start_bit, self.length, self.byte_order, raw)
+            .map_err(|pos| format!("Signal {}: bit {} is outside the message", self.name, pos))
+    }
+
+    /// 原始值依 `signed` 做符號延伸
+    pub fn signed_raw(&self, raw: u64) -> i64 {
+        if self.signed && self.length < 64 {
+            let shift = 64 - self.length;
+            ((raw << shift) as i64) >> shift
+        } else {
+            raw as i64
+        }
+    }
+
+    /// 解碼為物理值（raw × factor + offset），同時回傳原始值
+    pub fn decode(&self, data: &[u8]) -> Option<(f64, i64)> {
+        let raw = self.signed_raw(self.raw_value(data)?);
+        Some((raw as f64 * self.factor + self.offset, raw))
+    }
+
+    pub fn value_name(&self, raw: i64) -> Option<&str> {
+        self.value_names
+            .iter()
+            .find(|(value, _)| *value == raw)
+            .map(|(_, name)| name.as_str())
+    }
+}
+
+/// DBC 中的一個訊息（`BO_`）
+#[derive(Debug, Clone, PartialEq)]
+pub struct DbcMessage {
+    pub id: u32,
+    pub extended: bool,
+    pub name: String,
+    pub dlc: u8,
+    pub sender: String,
+    pub signals: Vec<DbcSignal>,
+}
+
+impl DbcMessage {
+    /// 解碼訊框資料中目前有效的訊號；多工訊號只在選擇值相符時輸出
+    pub fn decode<'a>(&'a self, data: &[u8]) -> Vec<(&'a DbcSignal, f64, i64)> {
+        let selector = self.multiplexor().and_then(|s| s.raw_value(data));
+        self.signals
+            .iter()
+            .filter(|s| self.is_active(s, selector))
+            .filter_map(|s| s.decode(data).map(|(value, raw)| (s, value, raw)))
+            .collect()
+    }
+
+    pub fn signal(&self, name: &str) -> Option<&DbcSignal> {
+        self.signals.iter().find(|s| s.name == name)
+    }
+
+    /// 依多工選擇值判斷訊號是否有效；多工選擇訊號本身一律有效
+    pub fn is_active(&self, signal: &DbcSignal, selector: Option<u64>) -> bool {
+        match signal.multiplex {
+            Multiplex::Multiplexed(value) => selector == Some(value),
+            _ => true,
+        }
+    }
+
+    /// 多工選擇訊號，沒有多工時為 None
+    pub fn multiplexor(&self) -> Option<&DbcSignal> {
+        self.signals
+            .iter()
+            .find(|s| s.multiplex == Multiplex::Multiplexor)
+    }
+
+    /// 依多工選擇訊號的物理值計算選擇值，沒有多工時為 None
+    pub fn selector(&self, values: &HashMap<String, f64>) -> Option<u64> {
+        let mux = self.multiplexor()?;
+        let value = values.get(&mux.name).copied().unwrap_or(mux.offset);
+        Some(((value - mux.offset) / mux.factor).round() as u64)
+    }
+
+    /// 以物理值編碼訊框資料，未指定的訊號為 0；多工訊號只寫入選擇值相符者
+    pub fn encode(&self, values: &HashMap<String, f64>) -> Result<Vec<u8>, String> {
+        let mut data = vec![0u8; self.dlc as usize];
+        let selector = self.selector(values);
+        for signal in &self.signals {
+            if !self.is_active(signal, selector) {
+                continue;
+            }
+            if let Some(&value) = values.get(&signal.name) {
+                signal.encode(value, &mut data)?;
+            }
+        }
+        Ok(data)
+    }
+
+    /// 建立可傳送的訊框，超過 8 bytes 的訊息以 CAN FD 送出
+    pub fn frame(&self, channel: u32, values: &HashMap<String, f64>) -> Result<CanFrame, String> {
+        let data = self.encode(values)?;
+        let mut frame = if data.len() > 8 {
+            CanFrame::new_fd(channel, self.id, &data, false)
+        } else {
+            CanFrame::new(channel, self.id, &data)
+        };
+        frame.extended = self.extended || self.id > MAX_STANDARD_ID;
+        Ok(frame)
+    }
+}
+
+/// 解析後的 DBC 檔，只保留解碼需要的訊息與訊號定義
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Dbc {
+    pub messages: Vec<DbcMessage>,
+}
+
+impl Dbc {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        // DBC 常以 Windows-1252 編碼存檔，非 UTF-8 的字元只影響註解與單位
+        Self::parse(&String::from_utf8_lossy(&bytes)).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// 解析 DBC 文字；不處理的區塊（節點、屬性、註解等）直接略過
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut dbc = Dbc::default();
+        let mut value_tables = Vec::new();
+        // 跨行的引號字串（例如多行註解）內的文字不解析
+        let mut in_string = false;
+        for (number, line) in text.lines().enumerate() {
+            let quoted = line.matches('"').count() % 2 == 1;
+            if in_string {
+                in_string = !quoted;
+                continue;
+            }
+            in_string = quoted;
+            let line = line.trim();
+            let error = |e: String| format!("line {}: {}", number + 1, e);
+            if let Some(rest) = line.strip_prefix("BO_ ") {
+                dbc.messages.push(parse_message(rest).map_err(error)?);
+            } else if let Some(rest) = line.strip_prefix("SG_ ") {
+                let signal = parse_signal(rest).map_err(error)?;
+                dbc.messages
+                    .last_mut()
+                    .ok_or_else(|| error("SG_ outside of a BO_ block".to_string()))?
+                    .signals
+                    .push(signal);
+            } else if let Some(rest) = line.strip_prefix("VAL_ ") {
+                value_tables.push(parse_value_table(rest).map_err(error)?);
+            }
+        }
+        for (id, signal_name, names) in value_tables {
+            let signal = dbc
+                .messages
+                .iter_mut()
+                .filter(|m| m.id == id)
+                .flat_map(|m| m.signals.iter_mut())
+                .find(|s| s.name == signal_name);
+            if let Some(signal) = signal {
+                signal.value_names = names;
+            }
+        }
+        Ok(dbc)
+    }
+
+    pub fn message(&self, id: u32) -> Option<&DbcMessage> {
+        self.messages.iter().find(|m| m.id == id)
+    }
+
+    pub fn message_by_name(&self, name: &str) -> Option<&DbcMessage> {
+        self.messages.iter().find(|m| m.name == name)
+    }
+
+    pub fn signal_count(&self) -> usize {
+        self.messages.iter().map(|m| m.signals.len()).sum()
+    }
+}
+
+/// DBC 中的 ID，去除延伸 ID 旗標並回傳是否為延伸 ID
+fn parse_dbc_id(text: &str) -> Result<(u32, bool), String> {
+    let id: u32 = text
+        .parse()
+        .map_err(|_| format!("Invalid message ID '{}'", text))?;
+    Ok((id & !DBC_EXTENDED_FLAG, id & DBC_EXTENDED_FLAG != 0))
+}
+
+/// `<id> <name>: <dlc> <sender>`
+fn parse_message(text: &str) -> Result<DbcMessage, String> {
+    let (head, tail) = text
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid BO_ definition '{}'", text))?;
+    let mut head = head.split_whitespace();
+    let (id, extended) = parse_dbc_id(head.next().unwrap_or_default())?;
+    let name = head
+        .next()
+        .ok_or_else(|| format!("BO_ {} has no name", id))?;
+    let mut tail = tail.split_whitespace();
+    let dlc = tail
+        .next()
+        .and_then(|dlc| dlc.parse().ok())
+        .ok_or_else(|| format!("BO_ {} has an invalid DLC", name))?;
+    Ok(DbcMessage {
+        id,
+        extended,
+        name: name.to_string(),
+        dlc,
+        sender: tail.next().unwrap_or_default().to_string(),
+        signals: Vec::new(),
+    })
+}
+
+/// `<name> [M|m<n>] : <start>|<len>@<order><sign> (<factor>,<offset>) [<min>|<max>] "<unit>" <receivers>`
+fn parse_signal(text: &str) -> Result<DbcSignal, String> {
+    let (head, body) = text
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid SG_ definition '{}'", text))?;
+    let mut head = head.split_whitespace();
+    let name = head
+        .next()
+        .ok_or_else(|| "SG_ has no name".to_string())?
+        .to_string();
+    let invalid = |what: &str| format!("Signal {}: invalid {}", name, what);
+    let multiplex = match head.next() {
+        None => Multiplex::None,
+        Some("M") => Multiplex::Multiplexor,
+        // 延伸多工（`m1M`）視為一般的多工訊號
+        Some(tag) => tag
+            .strip_prefix('m')
+            .map(|n| n.trim_end_matches('M'))
+            .and_then(|n| n.parse().ok())
+            .map(Multiplex::Multiplexed)
+            .ok_or_else(|| invalid("multiplex indicator"))?,
+    };
+
+    let body = body.trim();
+    let (layout, rest) = body
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| invalid("definition"))?;
+    let (start_bit, rest_layout) = layout.split_once('|').ok_or_else(|| invalid("layout"))?;
+    let (length, order) = rest_layout
+        .split_once('@')
+        .ok_or_else(|| invalid("layout"))?;
+    let byte_order = match order.get(..1) {
+        Some("1") => ByteOrder::Little,
+        Some("0") => ByteOrder::Big,
+        _ => return Err(invalid("byte order")),
+    };
+    let signed = match order.get(1..2) {
+        Some("-") => true,
+        Some("+") => false,
+        _ => return Err(invalid("value type")),
+    };
+
+    let between = |text: &str, open: char, close: char| -> Option<(String, usize)> {
+        let start = text.find(open)? + 1;
+        let end = start + text[start..].find(close)?;
+        Some((text[start..end].to_string(), end + 1))
+    };
+    let (scale, end) = between(rest, '(', ')').ok_or_else(|| invalid("factor/offset"))?;
+    let rest = &rest[end..];
+    let (range, end) = between(rest, '[', ']').ok_or_else(|| invalid("range"))?;
+    let rest = &rest[end..];
+    let (unit, _) = between(rest, '"', '"').ok_or_else(|| invalid("unit"))?;
+    let pair = |text: &str, sep: char, what: &str| -> Result<(f64, f64), String> {
+        let (a, b) = text.split_once(sep).ok_or_else(|| invalid(what))?;
+        let a = a.trim().parse().map_err(|_| invalid(what))?;
+        let b = b.trim().parse().map_err(|_| invalid(what))?;
+        Ok((a, b))
+    };
+    let (factor, offset) = pair(&scale, ',', "factor/offset")?;
+    let (min, max) = pair(&range, '|', "range")?;
+
+    Ok(DbcSignal {
+        start_bit: start_bit.parse().map_err(|_| invalid("start bit"))?,
+        length: length.parse().map_err(|_| invalid("length"))?,
+        name,
+        byte_order,
+        signed,
+        factor,
+        offset,
+        min,
+        max,
+        unit,
+        multiplex,
+        value_names: Vec::new(),
+    })
+}
+
+/// `VAL_` 的訊息 ID、訊號名稱與數值名稱
+type ValueTable = (u32, String, Vec<(i64, String)>);
+
+/// `<id> <signal> <value> "<name>" ... ;`
+fn parse_value_table(text: &str) -> Result<ValueTable, String> {
+    let mut parts = text.splitn(3, char::is_whitespace);
+    let (id, _) = parse_dbc_id(parts.next().unwrap_or_default())?;
+    let signal = parts.next().unwrap_or_default().to_string();
+    let mut rest = parts.next().unwrap_or_default().trim();
+    let mut names = Vec::new();
+    while let Some((value, tail)) = rest.split_once('"') {
+        let (name, tail) = tail
+            .split_once('"')
+            .ok_or_else(|| format!("Unterminated VAL_ entry for {}", signal))?;
+        let value = value
+            .trim()
+            .parse()
+            .map_err(|_| format!("Invalid VAL_ value '{}' for {}", value.trim(), signal))?;
+        names.push((value, name.to_string()));
+        rest = tail;
+    }
+    Ok((id, signal, names))
+}
+
+/// 訊號的最新解碼結果
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedSignal {
+    pub value: f64,
+    pub raw: i64,
+    /// 數值名稱（`VAL_`），沒有定義時為 None
+    pub label: Option<String>,
+    pub unit: String,
+    /// 最後更新時間（微秒）
+    pub timestamp: u64,
+    pub count: u64,
+}
+
+/// 以載入的 DBC 即時解碼收到的訊框，保留每個訊號的最新值
+#[derive(Debug, Default)]
+pub struct DbcDecoder {
+    dbc: Dbc,
+    /// 來源檔案路徑，未載入時為 None
+    path: Option<String>,
+    by_id: HashMap<u32, usize>,
+    /// 以（訊息名稱, 訊號名稱）排序
+    values: BTreeMap<(String, String), DecodedSignal>,
+}
+
+impl DbcDecoder {
+    /// 以新的 DBC 取代目前的定義並清除已解碼的值
+    pub fn set_dbc(&mut self, dbc: Dbc, path: Option<String>) {
+        self.by_id = dbc
+            .messages
+            .iter()
+            .enumerate()
+            .map(|(index, message)| (message.id, index))
+            .collect();
+        self.dbc = dbc;
+        self.path = path;
+        self.values.clear();
+    }
+
+    pub fn dbc(&self) -> &Dbc {
+        &self.dbc
+    }
+
+    pub fn path(&self) -> Option<&str> {
+        self.path.as_deref()
+    }
+
+    /// 解碼一筆訊框，更新對應訊息中所有有效的訊號
+    pub fn process(&mut self, frame: &CanFrame) {
+        let Some(message) = self.by_id.get(&frame.id).map(|&i| &self.dbc.messages[i]) else {
+            return;
+        };
+        if frame.rtr {
+            return;
+        }
+        for (signal, value, raw) in message.decode(frame.payload()) {
+            let entry = self
+                .values
+                .entry((message.name.clone(), signal.name.clone()))
+                .or_insert_with(|| DecodedSignal {
+                    value,
+                    raw,
+                    label: None,
+                    unit: signal.unit.clone(),
+                    timestamp: 0,
+                    count: 0,
+                });
+            entry.value = value;
+            entry.raw = raw;
+            entry.label = signal.value_name(raw).map(str::to_string);
+            entry.timestamp = frame.timestamp;
+            entry.count += 1;
+        }
+    }
+
+    /// 目前的解碼值，依訊息與訊號名稱排序
+    pub fn values(&self) -> impl Iterator<Item = (&str, &str, &DecodedSignal)> {
+        self.values
+            .iter()
+            .map(|((message, signal), decoded)| (message.as_str(), signal.as_str(), decoded))
+    }
+
+    pub fn value(&self, message: &str, signal: &str) -> Option<&DecodedSignal> {
+        self.values.get(&(message.to_string(), signal.to_string()))
+    }
+
+    pub fn clear_values(&mut self) {
+        self.values.clear();
+    }
+}
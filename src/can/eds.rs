@@ -0,0 +1,240 @@
+use crate::can::csv_schedule::parse_data;
+use std::collections::BTreeMap;
+use std::fs;
+
+/// CiA 301 的基本資料型別編號
+pub const BOOLEAN: u16 = 0x0001;
+pub const INTEGER8: u16 = 0x0002;
+pub const INTEGER16: u16 = 0x0003;
+pub const INTEGER32: u16 = 0x0004;
+pub const UNSIGNED8: u16 = 0x0005;
+pub const UNSIGNED16: u16 = 0x0006;
+pub const UNSIGNED32: u16 = 0x0007;
+pub const REAL32: u16 = 0x0008;
+pub const VISIBLE_STRING: u16 = 0x0009;
+pub const REAL64: u16 = 0x0011;
+pub const INTEGER64: u16 = 0x0015;
+pub const UNSIGNED64: u16 = 0x001B;
+
+/// 資料型別的名稱
+pub fn data_type_name(data_type: u16) -> &'static str {
+    match data_type {
+        BOOLEAN => "BOOLEAN",
+        INTEGER8 => "INTEGER8",
+        INTEGER16 => "INTEGER16",
+        INTEGER32 => "INTEGER32",
+        UNSIGNED8 => "UNSIGNED8",
+        UNSIGNED16 => "UNSIGNED16",
+        UNSIGNED32 => "UNSIGNED32",
+        REAL32 => "REAL32",
+        VISIBLE_STRING => "VISIBLE_STRING",
+        0x000A => "OCTET_STRING",
+        REAL64 => "REAL64",
+        INTEGER64 => "INTEGER64",
+        UNSIGNED64 => "UNSIGNED64",
+        _ => "DOMAIN",
+    }
+}
+
+/// 依資料型別顯示讀到的值（小端序）；長度不符或未知型別以十六進位顯示
+pub fn format_value(data_type: u16, data: &[u8]) -> String {
+    let fixed = |len: usize| {
+        (data.len() == len).then(|| {
+            let mut bytes = [0u8; 8];
+            bytes[..len].copy_from_slice(data);
+            bytes
+        })
+    };
+    let hex = || {
+        data.iter()
+            .map(|b| format!("{:02X}", b))
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+    let text = match data_type {
+        BOOLEAN => fixed(1).map(|b| (b[0] != 0).to_string()),
+        INTEGER8 => fixed(1).map(|b| (b[0] as i8).to_string()),
+        INTEGER16 => fixed(2).map(|b| i16::from_le_bytes([b[0], b[1]]).to_string()),
+        INTEGER32 => fixed(4).map(|b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string()),
+        INTEGER64 => fixed(8).map(|b| i64::from_le_bytes(b).to_string()),
+        UNSIGNED8 | UNSIGNED16 | UNSIGNED32 | UNSIGNED64 => (!data.is_empty() && data.len() <= 8)
+            .then(|| {
+                let value = data
+                    .iter()
+                    .rev()
+                    .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+                format!("{} (0x{:X})", value, value)
+            }),
+        REAL32 => fixed(4).map(|b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]).to_string()),
+        REAL64 => fixed(8).map(|b| f64::from_le_bytes(b).to_string()),
+        VISIBLE_STRING => Some(format!("\"{}\"", String::from_utf8_lossy(data))),
+        _ => None,
+    };
+    text.unwrap_or_else(hex)
+}
+
+/// 依資料型別將輸入的文字轉為要寫入的位元組；整數可用 "0x" 十六進位，
+/// 其他型別以十六進位位元組輸入（例如 "01 02 0A"）
+pub fn parse_value(data_type: u16, text: &str) -> Result<Vec<u8>, String> {
+    let text = text.trim();
+    let integer = |len: usize| -> Result<Vec<u8>, String> {
+        let value = match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            Some(hex) => u64::from_str_radix(hex, 16).map(|v| v as i64),
+            None => text.parse::<i64>(),
+        }
+        .map_err(|_| format!("Invalid integer: {}", text))?;
+        Ok(value.to_le_bytes()[..len].to_vec())
+    };
+    match data_type {
+        BOOLEAN => match text {
+            "1" | "true" => Ok(vec![1]),
+            "0" | "false" => Ok(vec![0]),
+            _ => Err(format!("Invalid boolean: {}", text)),
+        },
+        INTEGER8 | UNSIGNED8 => integer(1),
+        INTEGER16 | UNSIGNED16 => integer(2),
+        INTEGER32 | UNSIGNED32 => integer(4),
+        INTEGER64 | UNSIGNED64 => integer(8),
+        REAL32 => text
+            .parse::<f32>()
+            .map(|v| v.to_le_bytes().to_vec())
+            .map_err(|_| format!("Invalid number: {}", text)),
+        REAL64 => text
+            .parse::<f64>()
+            .map(|v| v.to_le_bytes().to_vec())
+            .map_err(|_| format!("Invalid number: {}", text)),
+        VISIBLE_STRING => Ok(text.as_bytes().to_vec()),
+        _ => parse_data(text),
+    }
+}
+
+/// 物件字典中可存取的項目（VAR 物件或 RECORD/ARRAY 的子項目）
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdsEntry {
+    pub index: u16,
+    pub subindex: u8,
+    /// 子項目的名稱為「物件名稱: 子項目名稱」
+    pub name: String,
+    pub data_type: u16,
+    /// ro、wo、rw、rwr、rww 或 const
+    pub access: String,
+    pub default_value: String,
+}
+
+impl EdsEntry {
+    pub fn readable(&self) -> bool {
+        self.access != "wo"
+    }
+
+    pub fn writable(&self) -> bool {
+        self.access.starts_with("rw") || self.access == "wo"
+    }
+}
+
+/// EDS（電子資料表，INI 格式）中的物件字典
+#[derive(Debug, Clone, Default)]
+pub struct Eds {
+    pub entries: BTreeMap<(u16, u8), EdsEntry>,
+    /// [DeviceInfo] 的 ProductName
+    pub product_name: String,
+}
+
+/// EDS 數值可寫成十進位、"0x" 十六進位或八進位（以 0 開頭）
+fn parse_number(text: &str) -> Option<u32> {
+    let text = text.trim();
+    if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        u32::from_str_radix(hex, 16).ok()
+    } else if text.len() > 1 && text.starts_with('0') {
+        u32::from_str_radix(&text[1..], 8).ok()
+    } else {
+        text.parse().ok()
+    }
+}
+
+impl Eds {
+    pub fn load(path: &str) -> Result<Self, String> {
+        let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        Self::parse(&String::from_utf8_lossy(&bytes)).map_err(|e| format!("{}: {}", path, e))
+    }
+
+    /// 解析 EDS 文字，只讀取物件區段（[1018]、[1018sub1]）與 [DeviceInfo]
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut sections: Vec<(String, BTreeMap<String, String>)> = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with(';') {
+                continue;
+            }
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                sections.push((name.trim().to_lowercase(), BTreeMap::new()));
+            } else if let Some((key, value)) = line.split_once('=') {
+                let Some((_, keys)) = sections.last_mut() else {
+                    return Err(format!("line {}: key outside of a section", number + 1));
+                };
+                keys.insert(key.trim().to_lowercase(), value.trim().to_string());
+            }
+        }
+
+        let mut eds = Eds::default();
+        let mut names = BTreeMap::new();
+        let mut parents = Vec::new();
+        for (section, keys) in &sections {
+            if section == "deviceinfo" {
+                eds.product_name = keys.get("productname").cloned().unwrap_or_default();
+                continue;
+            }
+            let (index, subindex) = match section.split_once("sub") {
+                Some((index, sub)) => (index, Some(sub)),
+                None => (section.as_str(), None),
+            };
+            let Ok(index) = u16::from_str_radix(index, 16) else {
+                continue;
+            };
+            let name = keys.get("parametername").cloned().unwrap_or_default();
+            let subindex = match subindex {
+                Some(sub) => u8::from_str_radix(sub, 16)
+                    .map_err(|_| format!("invalid section [{}]", section))?,
+                None => {
+                    names.insert(index, name.clone());
+                    // RECORD (0x8) 與 ARRAY (0x9) 的內容在子區段中
+                    let object_type = keys.get("objecttype").and_then(|t| parse_number(t));
+                    if matches!(object_type, Some(0x8) | Some(0x9)) {
+                        parents.push(index);
+                        continue;
+                    }
+                    0
+                }
+            };
+            let entry = EdsEntry {
+                index,
+                subindex,
+                name,
+                data_type: keys
+                    .get("datatype")
+                    .and_then(|t| parse_number(t))
+                    .unwrap_or(0) as u16,
+                access: keys
+                    .get("accesstype")
+                    .map(|a| a.to_lowercase())
+                    .unwrap_or_else(|| "rw".to_string()),
+                default_value: keys.get("defaultvalue").cloned().unwrap_or_default(),
+            };
+            eds.entries.insert((index, subindex), entry);
+        }
+        for entry in eds.entries.values_mut() {
+            if parents.contains(&entry.index) {
+                if let Some(parent) = names.get(&entry.index) {
+                    entry.name = format!("{}: {}", parent, entry.name);
+                }
+            }
+        }
+        if eds.entries.is_empty() {
+            return Err("no object dictionary entries".to_string());
+        }
+        Ok(eds)
+    }
+
+    pub fn entry(&self, index: u16, subindex: u8) -> Option<&EdsEntry> {
+        self.entries.get(&(index, subindex))
+    }
+}
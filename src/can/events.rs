@@ -0,0 +1,132 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::{Arc, Mutex};
+
+/// 事件紀錄的容量上限
+const EVENT_LOG_CAPACITY: usize = 5000;
+
+/// 事件嚴重程度
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Info,
+    Warning,
+    Alarm,
+}
+
+/// 事件分類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventCategory {
+    Threshold,
+    Heartbeat,
+    CounterGap,
+    Watch,
+    Silence,
+    Sequence,
+    Alarm,
+}
+
+/// 一筆運作事件（警報、匯流排狀態變化等）
+#[derive(Debug, Clone)]
+pub struct BusEvent {
+    pub seq: u64,
+    /// 發生時間（微秒）
+    pub timestamp: u64,
+    pub severity: Severity,
+    pub category: EventCategory,
+    pub message: String,
+    pub acknowledged: bool,
+}
+
+/// 與一般 Log 分開的事件紀錄，支援確認與清除
+#[derive(Debug, Default)]
+pub struct EventLog {
+    events: VecDeque<BusEvent>,
+    next_seq: u64,
+}
+
+/// GUI 與背景執行緒共用的事件紀錄
+pub type SharedEvents = Arc<Mutex<EventLog>>;
+
+impl EventLog {
+    pub fn push(
+        &mut self,
+        timestamp: u64,
+        severity: Severity,
+        category: EventCategory,
+        message: String,
+    ) {
+        if self.events.len() >= EVENT_LOG_CAPACITY {
+            self.events.pop_front();
+        }
+        self.next_seq += 1;
+        self.events.push_back(BusEvent {
+            seq: self.next_seq,
+            timestamp,
+            severity,
+            category,
+            message,
+            acknowledged: false,
+        });
+    }
+
+    pub fn events(&self) -> impl DoubleEndedIterator<Item = &BusEvent> {
+        self.events.iter()
+    }
+
+    pub fn unacknowledged(&self) -> usize {
+        self.events.iter().filter(|e| !e.acknowledged).count()
+    }
+
+    pub fn acknowledge(&mut self, seq: u64) {
+        if let Some(event) = self.events.iter_mut().find(|e| e.seq == seq) {
+            event.acknowledged = true;
+        }
+    }
+
+    pub fn acknowledge_all(&mut self) {
+        for event in self.events.iter_mut() {
+            event.acknowledged = true;
+        }
+    }
+
+    /// 清除已確認的事件
+    pub fn clear_acknowledged(&mut self) {
+        self.events.retain(|e| !e.acknowledged);
+    }
+
+    pub fn clear(&mut self) {
+        self.events.clear();
+    }
+
+    /// 匯出事件為 CSV，時間欄位以傳入的函式格式化
+    pub fn export_csv<F>(
+        &self,
+        file_path: &str,
+        format_time: F,
+    ) -> Result<(), Box<dyn std::error::Error>>
+    where
+        F: Fn(u64) -> String,
+    {
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        writeln!(
+            writer,
+            "seq,time,timestamp_us,severity,category,acknowledged,message"
+        )?;
+        for event in self.events.iter() {
+            writeln!(
+                writer,
+                "{},{},{},{:?},{:?},{},\"{}\"",
+                event.seq,
+                format_time(event.timestamp),
+                event.timestamp,
+                event.severity,
+                event.category,
+                event.acknowledged,
+                event.message.replace('"', "\"\"")
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
@@ -0,0 +1,311 @@
+use crate::can::cantypes::{CanFrame, FrameDirection, FrameProtocol};
+
+/// 過濾運算式中可用的訊框欄位
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Id,
+    Channel,
+    Dlc,
+    Len,
+    Fd,
+    Brs,
+    Tx,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CmpOp {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum BitOp {
+    And,
+    Or,
+    Xor,
+}
+
+#[derive(Debug, Clone)]
+enum Expr {
+    Number(i64),
+    Field(Field),
+    Data(Box<Expr>),
+    Bit(BitOp, Box<Expr>, Box<Expr>),
+    Cmp(CmpOp, Box<Expr>, Box<Expr>),
+    In(Box<Expr>, Vec<Expr>),
+    Not(Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    /// 取得數值；超出範圍的 data[n] 回傳 None，使整個比較不成立
+    fn value(&self, frame: &CanFrame) -> Option<i64> {
+        match self {
+            Expr::Number(n) => Some(*n),
+            Expr::Field(field) => Some(match field {
+                Field::Id => frame.id as i64,
+                Field::Channel => frame.channel as i64,
+                Field::Dlc => frame.dlc() as i64,
+                Field::Len => frame.payload().len() as i64,
+                Field::Fd => (frame.protocol == FrameProtocol::Fd) as i64,
+                Field::Brs => frame.brs as i64,
+                Field::Tx => (frame.direction == FrameDirection::Tx) as i64,
+            }),
+            Expr::Data(index) => {
+                let index = usize::try_from(index.value(frame)?).ok()?;
+                frame.payload().get(index).map(|&b| b as i64)
+            }
+            Expr::Bit(op, a, b) => {
+                let (a, b) = (a.value(frame)?, b.value(frame)?);
+                Some(match op {
+                    BitOp::And => a & b,
+                    BitOp::Or => a | b,
+                    BitOp::Xor => a ^ b,
+                })
+            }
+            _ => Some(self.test(frame) as i64),
+        }
+    }
+
+    fn test(&self, frame: &CanFrame) -> bool {
+        match self {
+            Expr::Cmp(op, a, b) => match (a.value(frame), b.value(frame)) {
+                (Some(a), Some(b)) => match op {
+                    CmpOp::Eq => a == b,
+                    CmpOp::Ne => a != b,
+                    CmpOp::Lt => a < b,
+                    CmpOp::Le => a <= b,
+                    CmpOp::Gt => a > b,
+                    CmpOp::Ge => a >= b,
+                },
+                _ => false,
+            },
+            Expr::In(a, list) => a
+                .value(frame)
+                .is_some_and(|a| list.iter().any(|e| e.value(frame) == Some(a))),
+            Expr::Not(e) => !e.test(frame),
+            Expr::And(a, b) => a.test(frame) && b.test(frame),
+            Expr::Or(a, b) => a.test(frame) || b.test(frame),
+            _ => self.value(frame).is_some_and(|v| v != 0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(i64),
+    Ident(String),
+    Op(&'static str),
+}
+
+const OPERATORS: [&str; 17] = [
+    "&&", "||", "==", "!=", "<=", ">=", "<", ">", "!", "&", "|", "^", "(", ")", "[", "]", ",",
+];
+
+fn tokenize(text: &str) -> Result<Vec<(usize, Token)>, String> {
+    let mut tokens = Vec::new();
+    let mut pos = 0;
+    while pos < text.len() {
+        let rest = &text[pos..];
+        let c = rest.chars().next().unwrap();
+        if c.is_whitespace() {
+            pos += c.len_utf8();
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            let literal = rest[..end].replace('_', "");
+            let number = match literal
+                .strip_prefix("0x")
+                .or_else(|| literal.strip_prefix("0X"))
+            {
+                Some(hex) => i64::from_str_radix(hex, 16),
+                None => literal.parse(),
+            }
+            .map_err(|_| format!("Invalid number '{}' at {}", &rest[..end], pos))?;
+            tokens.push((pos, Token::Number(number)));
+            pos += end;
+        } else if c.is_ascii_alphabetic() || c == '_' {
+            let end = rest
+                .find(|c: char| !c.is_ascii_alphanumeric() && c != '_')
+                .unwrap_or(rest.len());
+            tokens.push((pos, Token::Ident(rest[..end].to_ascii_lowercase())));
+            pos += end;
+        } else if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(*op)) {
+            tokens.push((pos, Token::Op(op)));
+            pos += op.len();
+        } else {
+            return Err(format!("Unexpected '{}' at {}", c, pos));
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(usize, Token)>,
+    pos: usize,
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(_, t)| t)
+    }
+
+    fn offset(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(p, _)| *p)
+    }
+
+    fn eat(&mut self, op: &str) -> bool {
+        if matches!(self.peek(), Some(Token::Op(o)) if *o == op) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, op: &str) -> Result<(), String> {
+        if self.eat(op) {
+            Ok(())
+        } else {
+            Err(format!("Expected '{}' at {}", op, self.offset()))
+        }
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        let mut expr = self.and()?;
+        while self.eat("||") {
+            expr = Expr::Or(Box::new(expr), Box::new(self.and()?));
+        }
+        Ok(expr)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        let mut expr = self.not()?;
+        while self.eat("&&") {
+            expr = Expr::And(Box::new(expr), Box::new(self.not()?));
+        }
+        Ok(expr)
+    }
+
+    fn not(&mut self) -> Result<Expr, String> {
+        if self.eat("!") {
+            return Ok(Expr::Not(Box::new(self.not()?)));
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.bits()?;
+        if matches!(self.peek(), Some(Token::Ident(name)) if name == "in") {
+            self.pos += 1;
+            self.expect("[")?;
+            let mut list = vec![self.bits()?];
+            while self.eat(",") {
+                list.push(self.bits()?);
+            }
+            self.expect("]")?;
+            return Ok(Expr::In(Box::new(left), list));
+        }
+        let op = match self.peek() {
+            Some(Token::Op("==")) => CmpOp::Eq,
+            Some(Token::Op("!=")) => CmpOp::Ne,
+            Some(Token::Op("<")) => CmpOp::Lt,
+            Some(Token::Op("<=")) => CmpOp::Le,
+            Some(Token::Op(">")) => CmpOp::Gt,
+            Some(Token::Op(">=")) => CmpOp::Ge,
+            _ => return Ok(left),
+        };
+        self.pos += 1;
+        Ok(Expr::Cmp(op, Box::new(left), Box::new(self.bits()?)))
+    }
+
+    fn bits(&mut self) -> Result<Expr, String> {
+        let mut expr = self.primary()?;
+        loop {
+            let op = match self.peek() {
+                Some(Token::Op("&")) => BitOp::And,
+                Some(Token::Op("|")) => BitOp::Or,
+                Some(Token::Op("^")) => BitOp::Xor,
+                _ => return Ok(expr),
+            };
+            self.pos += 1;
+            expr = Expr::Bit(op, Box::new(expr), Box::new(self.primary()?));
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        let offset = self.offset();
+        let token = self
+            .tokens
+            .get(self.pos)
+            .map(|(_, t)| t.clone())
+            .ok_or_else(|| format!("Unexpected end of expression at {}", offset))?;
+        self.pos += 1;
+        match token {
+            Token::Number(n) => Ok(Expr::Number(n)),
+            Token::Op("(") => {
+                let expr = self.or()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Token::Ident(name) => {
+                let field = match name.as_str() {
+                    "id" => Field::Id,
+                    "ch" | "channel" => Field::Channel,
+                    "dlc" => Field::Dlc,
+                    "len" => Field::Len,
+                    "fd" => Field::Fd,
+                    "brs" => Field::Brs,
+                    "tx" => Field::Tx,
+                    "data" => {
+                        self.expect("[")?;
+                        let index = self.or()?;
+                        self.expect("]")?;
+                        return Ok(Expr::Data(Box::new(index)));
+                    }
+                    _ => return Err(format!("Unknown field '{}' at {}", name, offset)),
+                };
+                Ok(Expr::Field(field))
+            }
+            Token::Op(op) => Err(format!("Unexpected '{}' at {}", op, offset)),
+        }
+    }
+}
+
+/// 編譯後的訊框過濾運算式，例如 `id == 0x123 && data[0] > 0x80 && ch == 1`
+///
+/// 欄位：id、ch/channel、dlc、len、fd、brs、tx（本機送出為 1）、data[n]；
+/// 運算子：== != < <= > >=、in [a, b]、& | ^、! && ||，數值可用十進位或 0x 十六進位。
+#[derive(Debug, Clone)]
+pub struct FrameFilter {
+    expr: Expr,
+}
+
+impl FrameFilter {
+    pub fn compile(text: &str) -> Result<Self, String> {
+        let mut parser = Parser {
+            tokens: tokenize(text)?,
+            pos: 0,
+            end: text.len(),
+        };
+        if parser.tokens.is_empty() {
+            return Err("Empty filter expression".to_string());
+        }
+        let expr = parser.or()?;
+        if parser.pos < parser.tokens.len() {
+            return Err(format!("Unexpected input at {}", parser.offset()));
+        }
+        Ok(Self { expr })
+    }
+
+    pub fn matches(&self, frame: &CanFrame) -> bool {
+        self.expr.test(frame)
+    }
+}
@@ -0,0 +1,456 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::{now_micros, CanFrame, FrameProtocol, MAX_STANDARD_ID};
+use crate::can::id_filter::{parse_id, IdFilter, IdFilterRule};
+use flume::{unbounded, Receiver, RecvTimeoutError, Sender};
+use rhai::{Array, Dynamic, Engine, Map, Scope, AST};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 腳本中處理每個轉送訊框的函式名稱
+pub const HOOK_FN: &str = "on_frame";
+
+/// 每次呼叫腳本可執行的運算數上限，避免無窮迴圈卡住轉送執行緒
+const MAX_SCRIPT_OPERATIONS: u64 = 100_000;
+/// 腳本函式呼叫的最大深度
+const MAX_SCRIPT_CALL_LEVELS: usize = 32;
+/// 29-bit 擴展 ID 的上限
+const MAX_EXTENDED_ID: u32 = 0x1FFF_FFFF;
+
+/// 範例腳本：原封不動轉送所有訊框
+pub const DEFAULT_SCRIPT: &str = r#"// frame: #{ channel, id, dlc, fd, brs, data }
+// 回傳 frame 轉送（可修改 id/data，設定 delay_ms 延遲送出），回傳 () 丟棄
+fn on_frame(frame) {
+    frame
+}
+"#;
+
+/// 轉送延遲直方圖各區間的上界（微秒），最後一格為超過最大上界
+pub const LATENCY_BUCKETS_US: [u64; 10] = [
+    50, 100, 200, 500, 1_000, 2_000, 5_000, 10_000, 20_000, 50_000,
+];
+
+/// 轉送延遲統計：從收到訊框到送出完成的時間，不含腳本指定的 delay_ms
+#[derive(Debug, Clone, Default)]
+pub struct ForwardLatency {
+    pub count: u64,
+    pub min_us: u64,
+    pub max_us: u64,
+    sum_us: u64,
+    pub histogram: [u64; LATENCY_BUCKETS_US.len() + 1],
+}
+
+impl ForwardLatency {
+    pub fn record(&mut self, latency_us: u64) {
+        if self.count == 0 {
+            self.min_us = latency_us;
+        }
+        self.count += 1;
+        self.min_us = self.min_us.min(latency_us);
+        self.max_us = self.max_us.max(latency_us);
+        self.sum_us += latency_us;
+        let bucket = LATENCY_BUCKETS_US.partition_point(|&edge| edge <= latency_us);
+        self.histogram[bucket] += 1;
+    }
+
+    pub fn mean_us(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum_us as f64 / self.count as f64
+        }
+    }
+
+    /// 直方圖第 index 格的標籤，例如 "<500 µs"
+    pub fn bucket_label(index: usize) -> String {
+        let format = |us: u64| {
+            if us >= 1000 {
+                format!("{} ms", us / 1000)
+            } else {
+                format!("{} µs", us)
+            }
+        };
+        match LATENCY_BUCKETS_US.get(index) {
+            Some(&edge) => format!("<{}", format(edge)),
+            None => format!(
+                ">={}",
+                format(LATENCY_BUCKETS_US[LATENCY_BUCKETS_US.len() - 1])
+            ),
+        }
+    }
+}
+
+/// 轉送方向
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum GatewayDirection {
+    #[default]
+    Both,
+    AToB,
+    BToA,
+}
+
+impl GatewayDirection {
+    pub const ALL: [GatewayDirection; 3] = [
+        GatewayDirection::Both,
+        GatewayDirection::AToB,
+        GatewayDirection::BToA,
+    ];
+
+    pub fn arrow(self) -> &'static str {
+        match self {
+            GatewayDirection::Both => "<->",
+            GatewayDirection::AToB => "->",
+            GatewayDirection::BToA => "<-",
+        }
+    }
+}
+
+/// 轉送時把 `from` 改為 `to` 的 ID 對應
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IdRemap {
+    pub from: u32,
+    pub to: u32,
+}
+
+/// 解析 ID 對應，以逗號或換行分隔的十六進位 `from=to`，例如 `100=200, 7DF=7E0`
+pub fn parse_remaps(text: &str) -> Result<Vec<IdRemap>, String> {
+    text.split([',', '\n'])
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let (from, to) = item
+                .split_once('=')
+                .ok_or_else(|| format!("Invalid ID remap '{}', expected from=to", item))?;
+            let remap = IdRemap {
+                from: parse_id(from)?,
+                to: parse_id(to)?,
+            };
+            if remap.to > MAX_EXTENDED_ID {
+                return Err(format!(
+                    "Invalid ID remap '{}', target exceeds 29 bits",
+                    item
+                ));
+            }
+            Ok(remap)
+        })
+        .collect()
+}
+
+/// Gateway 設定：在兩個通道之間轉送，依序套用 ID 過濾、ID 對應與腳本
+#[derive(Debug, Clone, Default)]
+pub struct GatewayConfig {
+    pub channel_a: u32,
+    pub channel_b: u32,
+    pub direction: GatewayDirection,
+    /// 以來源通道比對的 ID 名單，規則的 `channel` 為 None 時兩個方向都套用
+    pub filter: Vec<IdFilterRule>,
+    pub remaps: Vec<IdRemap>,
+    /// 選用的 rhai 腳本，需定義 `on_frame(frame)`
+    pub script: Option<String>,
+}
+
+impl GatewayConfig {
+    /// 收到的訊框要轉送到的通道，不在轉送方向上時回傳 None
+    fn target(&self, channel: u32) -> Option<u32> {
+        let (a_to_b, b_to_a) = match self.direction {
+            GatewayDirection::Both => (true, true),
+            GatewayDirection::AToB => (true, false),
+            GatewayDirection::BToA => (false, true),
+        };
+        if a_to_b && channel == self.channel_a {
+            Some(self.channel_b)
+        } else if b_to_a && channel == self.channel_b {
+            Some(self.channel_a)
+        } else {
+            None
+        }
+    }
+
+    fn remap(&self, frame: &mut CanFrame) {
+        if let Some(remap) = self.remaps.iter().find(|r| r.from == frame.id) {
+            frame.id = remap.to;
+            frame.extended |= remap.to > MAX_STANDARD_ID;
+        }
+    }
+}
+
+/// 腳本處理後的結果
+enum HookAction {
+    Forward(CanFrame, u64),
+    Drop,
+}
+
+/// 執行中的 Gateway，收到的訊框經由 `forward` 交給背景執行緒處理
+pub struct Gateway {
+    frame_tx: Sender<CanFrame>,
+    running: Arc<AtomicBool>,
+    latency: Arc<Mutex<ForwardLatency>>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl Gateway {
+    /// 啟動 Gateway；ID 名單無法解析或腳本編譯失敗時回傳錯誤
+    pub fn start<F>(config: GatewayConfig, can_app: SharedCan, log: F) -> Result<Self, String>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let mut filter = IdFilter::default();
+        filter.set_rules(&config.filter)?;
+        // 先行編譯以便立即回報語法錯誤，執行緒內會再建立自己的 Engine
+        if let Some(ref script) = config.script {
+            compile_hook(&hook_engine(), script)?;
+        }
+        let (frame_tx, frame_rx) = unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let running_flag = Arc::clone(&running);
+        let latency = Arc::new(Mutex::new(ForwardLatency::default()));
+        let latency_stats = Arc::clone(&latency);
+        let handle = thread::spawn(move || {
+            run_gateway(
+                config,
+                filter,
+                can_app,
+                frame_rx,
+                running_flag,
+                latency_stats,
+                log,
+            );
+        });
+        Ok(Self {
+            frame_tx,
+            running,
+            latency,
+            handle: Some(handle),
+        })
+    }
+
+    /// 交由 Gateway 處理一筆收到的訊框
+    pub fn forward(&self, frame: &CanFrame) {
+        let _ = self.frame_tx.send(*frame);
+    }
+
+    /// 目前的轉送延遲統計
+    pub fn latency(&self) -> ForwardLatency {
+        self.latency.lock().unwrap().clone()
+    }
+
+    pub fn reset_latency(&self) {
+        *self.latency.lock().unwrap() = ForwardLatency::default();
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining gateway thread: {:?}", e);
+            }
+        }
+    }
+}
+
+impl Drop for Gateway {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// 執行腳本用的 Engine，限制運算數與呼叫深度；超過限制時該訊框視為丟棄
+fn hook_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_SCRIPT_OPERATIONS);
+    engine.set_max_call_levels(MAX_SCRIPT_CALL_LEVELS);
+    engine
+}
+
+fn compile_hook(engine: &Engine, script: &str) -> Result<AST, String> {
+    let ast = engine
+        .compile(script)
+        .map_err(|e| format!("Gateway script error: {}", e))?;
+    if !ast.iter_functions().any(|f| f.name == HOOK_FN) {
+        return Err(format!("Gateway script must define fn {}(frame)", HOOK_FN));
+    }
+    Ok(ast)
+}
+
+fn run_gateway<F>(
+    config: GatewayConfig,
+    mut filter: IdFilter,
+    can_app: SharedCan,
+    frame_rx: Receiver<CanFrame>,
+    running: Arc<AtomicBool>,
+    latency: Arc<Mutex<ForwardLatency>>,
+    log: F,
+) where
+    F: Fn(String),
+{
+    let engine = hook_engine();
+    let ast = match config.script.as_deref().map(|s| compile_hook(&engine, s)) {
+        Some(Ok(ast)) => Some(ast),
+        Some(Err(e)) => {
+            log(e);
+            return;
+        }
+        None => None,
+    };
+    log(format!(
+        "Gateway started: CH{} {} CH{}{}",
+        config.channel_a,
+        config.direction.arrow(),
+        config.channel_b,
+        if ast.is_some() { " (scripted)" } else { "" }
+    ));
+
+    // 送出訊框並以收到時間（訊框時間戳記）計算轉送延遲
+    let send = |frame: &CanFrame, delay_ms: u64| match can_app.send_frame(frame) {
+        Ok(()) => latency.lock().unwrap().record(
+            now_micros()
+                .saturating_sub(frame.timestamp)
+                .saturating_sub(delay_ms * 1000),
+        ),
+        Err(e) => log(format!("Gateway forward failed: {}", e)),
+    };
+
+    // 延遲送出的訊框，依到期時間排序
+    let mut delayed: Vec<(Instant, CanFrame, u64)> = Vec::new();
+    while running.load(Ordering::SeqCst) {
+        let now = Instant::now();
+        while delayed.first().is_some_and(|(due, _, _)| *due <= now) {
+            let (_, frame, delay_ms) = delayed.remove(0);
+            send(&frame, delay_ms);
+        }
+        let timeout = delayed
+            .first()
+            .map(|(due, _, _)| due.saturating_duration_since(now))
+            .unwrap_or(Duration::from_millis(100))
+            .min(Duration::from_millis(100));
+
+        let frame = match frame_rx.recv_timeout(timeout) {
+            Ok(frame) => frame,
+            Err(RecvTimeoutError::Timeout) => continue,
+            Err(RecvTimeoutError::Disconnected) => break,
+        };
+        let Some(target) = config.target(frame.channel) else {
+            continue;
+        };
+        if !filter.accept(&frame) {
+            continue;
+        }
+        let mut out = frame;
+        out.channel = target;
+        config.remap(&mut out);
+
+        let action = match ast {
+            Some(ref ast) => match call_hook(&engine, ast, &out) {
+                Ok(action) => action,
+                Err(e) => {
+                    log(format!("Gateway script runtime error: {}", e));
+                    HookAction::Drop
+                }
+            },
+            None => HookAction::Forward(out, 0),
+        };
+        match action {
+            HookAction::Forward(out, 0) => send(&out, 0),
+            HookAction::Forward(out, delay_ms) => {
+                let due = Instant::now() + Duration::from_millis(delay_ms);
+                let pos = delayed.partition_point(|(d, _, _)| *d <= due);
+                delayed.insert(pos, (due, out, delay_ms));
+            }
+            HookAction::Drop => {}
+        }
+    }
+    let latency = latency.lock().unwrap();
+    log(format!(
+        "Gateway stopped: {} frames forwarded, latency avg {:.0} µs, max {} µs",
+        latency.count,
+        latency.mean_us(),
+        latency.max_us
+    ));
+}
+
+fn frame_to_map(frame: &CanFrame) -> Map {
+    let mut map = Map::new();
+    map.insert("channel".into(), Dynamic::from_int(frame.channel as i64));
+    map.insert("id".into(), Dynamic::from_int(frame.id as i64));
+    map.insert("dlc".into(), Dynamic::from_int(frame.dlc() as i64));
+    map.insert(
+        "fd".into(),
+        Dynamic::from_bool(frame.protocol == FrameProtocol::Fd),
+    );
+    map.insert("brs".into(), Dynamic::from_bool(frame.brs));
+    let data: Array = frame
+        .payload()
+        .iter()
+        .map(|&b| Dynamic::from_int(b as i64))
+        .collect();
+    map.insert("data".into(), data.into());
+    map
+}
+
+fn call_hook(engine: &Engine, ast: &AST, frame: &CanFrame) -> Result<HookAction, String> {
+    let mut scope = Scope::new();
+    let result: Dynamic = engine
+        .call_fn(&mut scope, ast, HOOK_FN, (frame_to_map(frame),))
+        .map_err(|e| e.to_string())?;
+
+    if result.is_unit() {
+        return Ok(HookAction::Drop);
+    }
+    if let Ok(forward) = result.as_bool() {
+        return Ok(if forward {
+            HookAction::Forward(*frame, 0)
+        } else {
+            HookAction::Drop
+        });
+    }
+    let map = result
+        .try_cast::<Map>()
+        .ok_or_else(|| format!("{} must return a frame map, bool or ()", HOOK_FN))?;
+    let get_int = |key: &str| map.get(key).and_then(|v| v.as_int().ok());
+
+    let mut payload = Vec::new();
+    match map.get("data") {
+        Some(value) => {
+            let array = value
+                .clone()
+                .try_cast::<Array>()
+                .ok_or("frame.data must be an array")?;
+            for byte in array {
+                let byte = byte
+                    .as_int()
+                    .map_err(|_| "frame.data must contain integers")?;
+                let byte = u8::try_from(byte)
+                    .map_err(|_| format!("frame.data value {} is outside 0..=255", byte))?;
+                payload.push(byte);
+            }
+        }
+        None => payload.extend_from_slice(frame.payload()),
+    }
+    let max_len = frame.protocol.max_payload_len();
+    if payload.len() > max_len {
+        return Err(format!(
+            "frame.data has {} bytes, this frame holds at most {}",
+            payload.len(),
+            max_len
+        ));
+    }
+    let mut out = *frame;
+    out.channel = get_int("channel").map_or(frame.channel, |v| v as u32);
+    if let Some(id) = get_int("id") {
+        out.id = u32::try_from(id)
+            .ok()
+            .filter(|&id| id <= MAX_EXTENDED_ID)
+            .ok_or_else(|| format!("frame.id {} is outside 0..=0x1FFFFFFF", id))?;
+        // 與 ID 對應相同，超出 11 位元的 ID 改以擴展格式送出
+        out.extended |= out.id > MAX_STANDARD_ID;
+    }
+    if let Some(brs) = map.get("brs").and_then(|v| v.as_bool().ok()) {
+        out.brs = brs;
+    }
+    out.set_payload(&payload);
+    let delay_ms = get_int("delay_ms").unwrap_or(0).max(0) as u64;
+    Ok(HookAction::Forward(out, delay_ms))
+}
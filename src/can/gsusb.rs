@@ -0,0 +1,495 @@
+use crate::can::canbus::{receive_thread_stopped, CanInterface};
+use crate::can::cantypes::{now_micros, CanFrame, FrameDirection, FrameProtocol};
+use crate::can::log_event::LogEvent;
+use crate::can::timesync::TimestampCorrector;
+use flume::Sender;
+use rusb::{DeviceHandle, Direction, GlobalContext, Recipient, RequestType};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+
+/// 已知使用 gs_usb 協定的裝置（VID, PID）：candleLight、CANable、CES CANext
+pub const GS_USB_DEVICES: [(u16, u16); 4] = [
+    (0x1D50, 0x606F),
+    (0x1209, 0x2323),
+    (0x1CD2, 0x606F),
+    (0x16D0, 0x10B8),
+];
+
+const INTERFACE: u8 = 0;
+const ENDPOINT_IN: u8 = 0x81;
+const ENDPOINT_OUT: u8 = 0x02;
+const USB_TIMEOUT: Duration = Duration::from_millis(100);
+
+// gs_usb 控制請求
+const BREQ_HOST_FORMAT: u8 = 0;
+const BREQ_BITTIMING: u8 = 1;
+const BREQ_MODE: u8 = 2;
+const BREQ_BT_CONST: u8 = 4;
+const BREQ_DEVICE_CONFIG: u8 = 5;
+
+const MODE_RESET: u32 = 0;
+const MODE_START: u32 = 1;
+const MODE_FLAG_LISTEN_ONLY: u32 = 1 << 0;
+const MODE_FLAG_HW_TIMESTAMP: u32 = 1 << 4;
+const FEATURE_LISTEN_ONLY: u32 = 1 << 0;
+const FEATURE_HW_TIMESTAMP: u32 = 1 << 4;
+
+const CAN_EFF_FLAG: u32 = 0x8000_0000;
+const CAN_RTR_FLAG: u32 = 0x4000_0000;
+const CAN_ERR_FLAG: u32 = 0x2000_0000;
+const CAN_EFF_MASK: u32 = 0x1FFF_FFFF;
+/// 裝置收到的訊框以此 echo_id 回報，其餘為本機送出訊框的回送
+const RX_ECHO_ID: u32 = 0xFFFF_FFFF;
+/// host frame 長度：echo_id、can_id、dlc、channel、flags、reserved、data[8]
+const HOST_FRAME_LEN: usize = 20;
+/// 開啟硬體時間戳記時，結尾多 4 bytes 的微秒計數
+const HOST_FRAME_TS_LEN: usize = 24;
+
+/// `BREQ_BT_CONST` 回傳的位元時序限制
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct BitTimingConst {
+    pub feature: u32,
+    pub fclk_can: u32,
+    pub tseg1_min: u32,
+    pub tseg1_max: u32,
+    pub tseg2_min: u32,
+    pub tseg2_max: u32,
+    pub sjw_max: u32,
+    pub brp_min: u32,
+    pub brp_max: u32,
+    pub brp_inc: u32,
+}
+
+impl BitTimingConst {
+    fn from_bytes(buf: &[u8; 40]) -> Self {
+        let word = |i: usize| u32::from_le_bytes(buf[i * 4..i * 4 + 4].try_into().unwrap());
+        Self {
+            feature: word(0),
+            fclk_can: word(1),
+            tseg1_min: word(2),
+            tseg1_max: word(3),
+            tseg2_min: word(4),
+            tseg2_max: word(5),
+            sjw_max: word(6),
+            brp_min: word(7),
+            brp_max: word(8),
+            brp_inc: word(9),
+        }
+    }
+}
+
+/// `BREQ_BITTIMING` 的內容；tseg1 = prop_seg + phase_seg1
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BitTiming {
+    pub prop_seg: u32,
+    pub phase_seg1: u32,
+    pub phase_seg2: u32,
+    pub sjw: u32,
+    pub brp: u32,
+}
+
+impl BitTiming {
+    fn to_bytes(self) -> Vec<u8> {
+        [
+            self.prop_seg,
+            self.phase_seg1,
+            self.phase_seg2,
+            self.sjw,
+            self.brp,
+        ]
+        .iter()
+        .flat_map(|v| v.to_le_bytes())
+        .collect()
+    }
+}
+
+/// 依裝置時脈計算波特率的位元時序，取取樣點最接近 87.5% 的組合；無法整除時回傳 None
+pub fn compute_bit_timing(limits: &BitTimingConst, bitrate: u32) -> Option<BitTiming> {
+    let mut best: Option<(u32, BitTiming)> = None;
+    let mut brp = limits.brp_min.max(1);
+    while brp <= limits.brp_max {
+        let divisor = brp as u64 * bitrate as u64;
+        if divisor > 0 && (limits.fclk_can as u64).is_multiple_of(divisor) {
+            let tq = (limits.fclk_can as u64 / divisor) as u32;
+            // tq = 1（同步段）+ tseg1 + tseg2
+            let tseg2 = (tq as f64 * 0.125).round() as u32;
+            let tseg2 = tseg2.clamp(limits.tseg2_min, limits.tseg2_max);
+            let tseg1 = tq.saturating_sub(1 + tseg2);
+            if tseg1 >= limits.tseg1_min.max(2) && tseg1 <= limits.tseg1_max {
+                // 與 87.5% 的差距（千分比）
+                let error = ((1 + tseg1) * 1000 / tq).abs_diff(875);
+                let timing = BitTiming {
+                    prop_seg: 1,
+                    phase_seg1: tseg1 - 1,
+                    phase_seg2: tseg2,
+                    sjw: tseg2.min(limits.sjw_max).max(1),
+                    brp,
+                };
+                if best.is_none_or(|(e, _)| error < e) {
+                    best = Some((error, timing));
+                }
+            }
+        }
+        brp += limits.brp_inc.max(1);
+    }
+    best.map(|(_, timing)| timing)
+}
+
+/// 將訊框編碼為 gs_usb host frame
+pub fn encode_host_frame(frame: &CanFrame, channel: u8, echo_id: u32) -> Result<Vec<u8>, String> {
+    if frame.protocol != FrameProtocol::Classic || frame.data.len() > 8 {
+        return Err("gs_usb backend supports classic CAN frames only".to_string());
+    }
+    let mut can_id = frame.id & CAN_EFF_MASK;
+    if frame.extended {
+        can_id |= CAN_EFF_FLAG;
+    }
+    if frame.rtr {
+        can_id |= CAN_RTR_FLAG;
+    }
+    let mut buf = Vec::with_capacity(HOST_FRAME_LEN);
+    buf.extend_from_slice(&echo_id.to_le_bytes());
+    buf.extend_from_slice(&can_id.to_le_bytes());
+    buf.push(frame.data.len() as u8);
+    buf.push(channel);
+    buf.extend_from_slice(&[0, 0]);
+    let mut data = [0u8; 8];
+    if !frame.rtr {
+        data[..frame.data.len()].copy_from_slice(frame.payload());
+    }
+    buf.extend_from_slice(&data);
+    Ok(buf)
+}
+
+/// 解碼裝置送來的 host frame，回傳訊框與硬體時間戳記；錯誤訊框與格式錯誤回傳 None
+pub fn decode_host_frame(buf: &[u8]) -> Option<(CanFrame, Option<u32>)> {
+    if buf.len() < HOST_FRAME_LEN {
+        return None;
+    }
+    let word = |i: usize| u32::from_le_bytes(buf[i..i + 4].try_into().unwrap());
+    let echo_id = word(0);
+    let can_id = word(4);
+    if can_id & CAN_ERR_FLAG != 0 {
+        return None;
+    }
+    let dlc = buf[8].min(8);
+    let channel = buf[9] as u32;
+    let id = can_id & CAN_EFF_MASK;
+    let mut frame = if can_id & CAN_RTR_FLAG != 0 {
+        CanFrame::new_remote(channel, id, dlc)
+    } else {
+        CanFrame::new(channel, id, &buf[12..12 + dlc as usize])
+    };
+    frame.extended = can_id & CAN_EFF_FLAG != 0;
+    if echo_id != RX_ECHO_ID {
+        frame.direction = FrameDirection::Tx;
+    }
+    let timestamp = (buf.len() >= HOST_FRAME_TS_LEN).then(|| word(20));
+    Some((frame, timestamp))
+}
+
+/// candleLight / gs_usb 韌體的 USB 轉接器，直接透過 libusb 存取，不需廠商驅動函式庫
+pub struct GsUsbApp {
+    device_index: usize,
+    channel: u8,
+    bitrate_kbps: u32,
+    listen_only: bool,
+    handle: Mutex<Option<Arc<DeviceHandle<GlobalContext>>>>,
+    hw_timestamp: AtomicBool,
+    echo_id: AtomicU32,
+    receiving: Arc<AtomicBool>,
+    join_handles: Mutex<Vec<thread::JoinHandle<()>>>,
+}
+
+impl GsUsbApp {
+    /// `device_index` 為 `list_devices` 中的索引，`channel` 為裝置上的通道（由 0 起算）
+    pub fn new(device_index: usize, channel: u8, bitrate_kbps: u32) -> Self {
+        Self {
+            device_index,
+            channel,
+            bitrate_kbps,
+            listen_only: false,
+            handle: Mutex::new(None),
+            hw_timestamp: AtomicBool::new(false),
+            echo_id: AtomicU32::new(0),
+            receiving: Arc::new(AtomicBool::new(false)),
+            join_handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_listen_only(mut self, listen_only: bool) -> Self {
+        self.listen_only = listen_only;
+        self
+    }
+
+    /// 已連接的 gs_usb 裝置，格式為 "Bus 001 Device 004 (1d50:606f)"
+    pub fn list_devices() -> Vec<String> {
+        Self::devices()
+            .iter()
+            .map(|device| {
+                let (vid, pid) = device
+                    .device_descriptor()
+                    .map(|d| (d.vendor_id(), d.product_id()))
+                    .unwrap_or_default();
+                format!(
+                    "Bus {:03} Device {:03} ({:04x}:{:04x})",
+                    device.bus_number(),
+                    device.address(),
+                    vid,
+                    pid
+                )
+            })
+            .collect()
+    }
+
+    fn devices() -> Vec<rusb::Device<GlobalContext>> {
+        rusb::devices()
+            .map(|list| {
+                list.iter()
+                    .filter(|device| {
+                        device.device_descriptor().is_ok_and(|d| {
+                            GS_USB_DEVICES.contains(&(d.vendor_id(), d.product_id()))
+                        })
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn control_out(
+        handle: &DeviceHandle<GlobalContext>,
+        request: u8,
+        value: u16,
+        data: &[u8],
+    ) -> Result<(), String> {
+        let request_type =
+            rusb::request_type(Direction::Out, RequestType::Vendor, Recipient::Interface);
+        handle
+            .write_control(
+                request_type,
+                request,
+                value,
+                INTERFACE as u16,
+                data,
+                USB_TIMEOUT,
+            )
+            .map(|_| ())
+            .map_err(|e| format!("gs_usb request {} failed: {}", request, e))
+    }
+
+    fn control_in(
+        handle: &DeviceHandle<GlobalContext>,
+        request: u8,
+        value: u16,
+        buf: &mut [u8],
+    ) -> Result<(), String> {
+        let request_type =
+            rusb::request_type(Direction::In, RequestType::Vendor, Recipient::Interface);
+        let read = handle
+            .read_control(
+                request_type,
+                request,
+                value,
+                INTERFACE as u16,
+                buf,
+                USB_TIMEOUT,
+            )
+            .map_err(|e| format!("gs_usb request {} failed: {}", request, e))?;
+        if read < buf.len() {
+            return Err(format!("gs_usb request {}: short response", request));
+        }
+        Ok(())
+    }
+
+    /// 開啟裝置、設定位元時序並啟動通道，回傳啟用的模式旗標
+    fn open_handle(&self) -> Result<(DeviceHandle<GlobalContext>, u32), String> {
+        let device = Self::devices()
+            .into_iter()
+            .nth(self.device_index)
+            .ok_or_else(|| format!("gs_usb device #{} not found", self.device_index))?;
+        let handle = device
+            .open()
+            .map_err(|e| format!("Failed to open gs_usb device: {}", e))?;
+        // Linux 上可能已由 gs_usb 核心驅動接管
+        let _ = handle.set_auto_detach_kernel_driver(true);
+        handle
+            .claim_interface(INTERFACE)
+            .map_err(|e| format!("Failed to claim gs_usb interface: {}", e))?;
+
+        Self::control_out(&handle, BREQ_HOST_FORMAT, 1, &0x0000_BEEFu32.to_le_bytes())?;
+        let mut config = [0u8; 12];
+        Self::control_in(&handle, BREQ_DEVICE_CONFIG, 1, &mut config)?;
+        let channels = config[3] as u32 + 1;
+        if self.channel as u32 >= channels {
+            return Err(format!(
+                "gs_usb device has {} channel(s), channel {} requested",
+                channels, self.channel
+            ));
+        }
+        let mut bt_const = [0u8; 40];
+        Self::control_in(&handle, BREQ_BT_CONST, self.channel as u16, &mut bt_const)?;
+        let limits = BitTimingConst::from_bytes(&bt_const);
+        let timing = compute_bit_timing(&limits, self.bitrate_kbps * 1000).ok_or_else(|| {
+            format!(
+                "No bit timing for {}K with a {} Hz CAN clock",
+                self.bitrate_kbps, limits.fclk_can
+            )
+        })?;
+
+        let channel = self.channel as u16;
+        Self::control_out(&handle, BREQ_MODE, channel, &mode_bytes(MODE_RESET, 0))?;
+        Self::control_out(&handle, BREQ_BITTIMING, channel, &timing.to_bytes())?;
+        let mut flags = 0;
+        if limits.feature & FEATURE_HW_TIMESTAMP != 0 {
+            flags |= MODE_FLAG_HW_TIMESTAMP;
+        }
+        if self.listen_only {
+            if limits.feature & FEATURE_LISTEN_ONLY == 0 {
+                return Err("gs_usb device does not support listen-only mode".to_string());
+            }
+            flags |= MODE_FLAG_LISTEN_ONLY;
+        }
+        Self::control_out(&handle, BREQ_MODE, channel, &mode_bytes(MODE_START, flags))?;
+        Ok((handle, flags))
+    }
+}
+
+fn mode_bytes(mode: u32, flags: u32) -> Vec<u8> {
+    [mode.to_le_bytes(), flags.to_le_bytes()].concat()
+}
+
+impl CanInterface for GsUsbApp {
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String> {
+        let (handle, flags) = self.open_handle().inspect_err(|e| {
+            let _ = log_tx.send(LogEvent::DeviceError { detail: e.clone() });
+        })?;
+        self.hw_timestamp
+            .store(flags & MODE_FLAG_HW_TIMESTAMP != 0, Ordering::SeqCst);
+        *self.handle.lock().unwrap() = Some(Arc::new(handle));
+        let _ = log_tx.send(LogEvent::DeviceOpened {
+            backend: "gs_usb".to_string(),
+        });
+        let _ = log_tx.send(LogEvent::ChannelInitialized {
+            channel: self.channel as u32,
+            settings: format!(
+                "BaudRate: {}K, Listen-only: {}",
+                self.bitrate_kbps, self.listen_only
+            ),
+        });
+        Ok(())
+    }
+
+    fn close_device(&self, log_tx: Sender<LogEvent>) {
+        if let Some(handle) = self.handle.lock().unwrap().take() {
+            let _ = Self::control_out(
+                &handle,
+                BREQ_MODE,
+                self.channel as u16,
+                &mode_bytes(MODE_RESET, 0),
+            );
+            let _ = log_tx.send(LogEvent::DeviceClosed {
+                backend: "gs_usb".to_string(),
+                status: None,
+            });
+        }
+    }
+
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
+        let Some(handle) = self.handle.lock().unwrap().clone() else {
+            let _ = log_tx.send(LogEvent::NotInitialized {
+                backend: "gs_usb".to_string(),
+            });
+            return;
+        };
+        self.receiving.store(true, Ordering::SeqCst);
+        let receiving_flag = Arc::clone(&self.receiving);
+        let hw_timestamp = self.hw_timestamp.load(Ordering::SeqCst);
+        let channel = self.channel as u32;
+        let thread_handle = thread::spawn(move || {
+            let _ = log_tx.send(LogEvent::ChannelStarted { channel });
+            // gs_usb 硬體時間戳記為 32 位元微秒計數
+            let mut corrector = TimestampCorrector::new(1.0, 32);
+            let mut buf = [0u8; 64];
+            while receiving_flag.load(Ordering::SeqCst) {
+                match handle.read_bulk(ENDPOINT_IN, &mut buf, USB_TIMEOUT) {
+                    Ok(len) => {
+                        let host = now_micros();
+                        let Some((mut frame, device_ts)) = decode_host_frame(&buf[..len]) else {
+                            continue;
+                        };
+                        frame.timestamp = match device_ts.filter(|_| hw_timestamp) {
+                            Some(raw) => {
+                                let timestamp = corrector.correct(raw, host);
+                                frame.device_timestamp = Some(corrector.device_micros());
+                                timestamp
+                            }
+                            None => host,
+                        };
+                        let _ = data_tx.send(frame);
+                    }
+                    Err(rusb::Error::Timeout) => {}
+                    Err(e) => {
+                        let _ = log_tx.send(LogEvent::DeviceError {
+                            detail: format!("gs_usb read failed: {}", e),
+                        });
+                        break;
+                    }
+                }
+            }
+            let _ = log_tx.send(LogEvent::ChannelStopped { channel });
+        });
+        self.join_handles.lock().unwrap().push(thread_handle);
+    }
+
+    fn stop_receiving(&self) {
+        self.receiving.store(false, Ordering::SeqCst);
+        let mut handles = self.join_handles.lock().unwrap();
+        while let Some(handle) = handles.pop() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining gs_usb thread: {:?}", e);
+            }
+        }
+    }
+
+    fn channels(&self) -> Vec<u32> {
+        vec![self.channel as u32]
+    }
+
+    fn check_device(&self) -> Result<(), String> {
+        if receive_thread_stopped(&self.receiving, &self.join_handles) {
+            Err("gs_usb receive loop stopped".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_board_info(&self, log_tx: Sender<LogEvent>) {
+        let description = match Self::list_devices().get(self.device_index) {
+            Some(device) => format!("gs_usb adapter {}, channel {}", device, self.channel),
+            None => format!("gs_usb device #{} not connected", self.device_index),
+        };
+        let _ = log_tx.send(LogEvent::BackendInfo { description });
+    }
+
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        let Some(handle) = self.handle.lock().unwrap().clone() else {
+            return Err("gs_usb device not open; cannot transmit".to_string());
+        };
+        // 韌體原樣回送 echo_id，只需避開代表接收的 0xFFFFFFFF
+        let echo_id = self.echo_id.fetch_add(1, Ordering::SeqCst) % 0xFFFF;
+        let buf = encode_host_frame(frame, self.channel, echo_id)?;
+        handle
+            .write_bulk(ENDPOINT_OUT, &buf, USB_TIMEOUT)
+            .map(|_| ())
+            .map_err(|e| format!("gs_usb write failed: {}", e))
+    }
+
+    /// 裝置會以 echo_id 回送每筆送出的訊框
+    fn echoes_transmit(&self) -> bool {
+        true
+    }
+}
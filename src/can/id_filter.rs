@@ -0,0 +1,152 @@
+use crate::can::cantypes::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// 名單類型
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FilterMode {
+    /// 白名單：只保留名單中的 ID
+    #[default]
+    Pass,
+    /// 黑名單：丟棄名單中的 ID
+    Block,
+}
+
+/// 一條 ID 名單規則；`ids` 為逗號分隔的十六進位 ID 或範圍，例如 `100-1FF, 7DF`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct IdFilterRule {
+    pub enabled: bool,
+    /// 套用的通道，None 為所有通道
+    pub channel: Option<u32>,
+    pub mode: FilterMode,
+    pub ids: String,
+}
+
+impl Default for IdFilterRule {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            channel: None,
+            mode: FilterMode::Pass,
+            ids: String::new(),
+        }
+    }
+}
+
+/// 解析十六進位 ID，可帶 0x 前綴
+pub fn parse_id(text: &str) -> Result<u32, String> {
+    let text = text.trim();
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    u32::from_str_radix(digits, 16).map_err(|_| format!("Invalid ID '{}'", text))
+}
+
+/// 解析 ID 名單，回傳包含兩端的範圍
+pub fn parse_id_ranges(text: &str) -> Result<Vec<(u32, u32)>, String> {
+    text.split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(|item| {
+            let (start, end) = match item.split_once('-') {
+                Some((start, end)) => (parse_id(start)?, parse_id(end)?),
+                None => {
+                    let id = parse_id(item)?;
+                    (id, id)
+                }
+            };
+            if start > end {
+                return Err(format!("Invalid ID range '{}'", item));
+            }
+            Ok((start, end))
+        })
+        .collect()
+}
+
+struct CompiledRule {
+    channel: Option<u32>,
+    mode: FilterMode,
+    ranges: Vec<(u32, u32)>,
+}
+
+impl CompiledRule {
+    fn applies(&self, frame: &CanFrame) -> bool {
+        self.channel.is_none_or(|channel| channel == frame.channel)
+    }
+
+    fn contains(&self, id: u32) -> bool {
+        self.ranges
+            .iter()
+            .any(|&(start, end)| (start..=end).contains(&id))
+    }
+}
+
+/// 接收執行緒與資料緩衝區之間的軟體 ID 過濾；
+/// 通道有白名單時只保留其中的 ID，黑名單中的 ID 一律丟棄
+#[derive(Default)]
+pub struct IdFilter {
+    rules: Vec<IdFilterRule>,
+    compiled: Vec<CompiledRule>,
+    /// 各通道被丟棄的訊框數
+    blocked: BTreeMap<u32, u64>,
+}
+
+impl IdFilter {
+    /// 以新的規則取代目前的規則；任一規則無法解析時保留原本的規則，
+    /// 停用或沒有 ID 的規則不生效
+    pub fn set_rules(&mut self, rules: &[IdFilterRule]) -> Result<(), String> {
+        let mut compiled = Vec::new();
+        for rule in rules.iter().filter(|rule| rule.enabled) {
+            let ranges = parse_id_ranges(&rule.ids)?;
+            if !ranges.is_empty() {
+                compiled.push(CompiledRule {
+                    channel: rule.channel,
+                    mode: rule.mode,
+                    ranges,
+                });
+            }
+        }
+        self.rules = rules.to_vec();
+        self.compiled = compiled;
+        Ok(())
+    }
+
+    pub fn rules(&self) -> &[IdFilterRule] {
+        &self.rules
+    }
+
+    /// 判斷訊框是否通過過濾，未通過時計入丟棄數
+    pub fn accept(&mut self, frame: &CanFrame) -> bool {
+        let mut has_pass_list = false;
+        let mut passed = false;
+        let mut blocked = false;
+        for rule in self.compiled.iter().filter(|r| r.applies(frame)) {
+            match rule.mode {
+                FilterMode::Pass => {
+                    has_pass_list = true;
+                    passed |= rule.contains(frame.id);
+                }
+                FilterMode::Block => blocked |= rule.contains(frame.id),
+            }
+        }
+        let accepted = !blocked && (passed || !has_pass_list);
+        if !accepted {
+            *self.blocked.entry(frame.channel).or_default() += 1;
+        }
+        accepted
+    }
+
+    /// 各通道被丟棄的訊框數
+    pub fn blocked(&self) -> impl Iterator<Item = (u32, u64)> + '_ {
+        self.blocked
+            .iter()
+            .map(|(&channel, &count)| (channel, count))
+    }
+
+    pub fn reset_counts(&mut self) {
+        self.blocked.clear();
+    }
+}
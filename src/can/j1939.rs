@@ -0,0 +1,465 @@
+use crate::can::cantypes::CanFrame;
+use std::collections::HashMap;
+
+/// 傳輸協定連線管理（TP.CM）與資料傳送（TP.DT）的 PGN
+pub const PGN_TP_CM: u32 = 0xEC00;
+pub const PGN_TP_DT: u32 = 0xEB00;
+/// TP.CM 控制位元組
+const TP_CM_RTS: u8 = 16;
+const TP_CM_BAM: u8 = 32;
+const TP_CM_ABORT: u8 = 255;
+/// 全域位址（廣播）
+pub const GLOBAL_ADDRESS: u8 = 0xFF;
+/// 兩個 TP.DT 封包的最長間隔（T1 = 750 ms），逾時的連線會被丟棄
+const TP_TIMEOUT_US: u64 = 750_000;
+/// 多封包訊息的最大長度（255 個封包 × 7 bytes）
+const TP_MAX_SIZE: usize = 1785;
+
+/// 由 29-bit CAN ID 拆出的 J1939 欄位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct J1939Id {
+    pub priority: u8,
+    pub pgn: u32,
+    pub source: u8,
+    /// PDU1 格式（PF < 240）的目的位址，PDU2 為廣播沒有目的位址
+    pub destination: Option<u8>,
+}
+
+impl J1939Id {
+    pub fn from_can_id(id: u32) -> Self {
+        let priority = ((id >> 26) & 0x7) as u8;
+        // EDP 與 DP 位元成為 PGN 的 bit 17..16
+        let data_page = ((id >> 24) & 0x3) << 16;
+        let pdu_format = (id >> 16) & 0xFF;
+        let pdu_specific = ((id >> 8) & 0xFF) as u8;
+        let (pgn, destination) = if pdu_format < 240 {
+            (data_page | (pdu_format << 8), Some(pdu_specific))
+        } else {
+            (data_page | (pdu_format << 8) | pdu_specific as u32, None)
+        };
+        Self {
+            priority,
+            pgn,
+            source: (id & 0xFF) as u8,
+            destination,
+        }
+    }
+}
+
+/// 常見 PGN 的名稱（J1939-71 的縮寫）
+pub fn pgn_name(pgn: u32) -> Option<&'static str> {
+    Some(match pgn {
+        0xE800 => "ACK",
+        0xEA00 => "Request",
+        0xEB00 => "TP.DT",
+        0xEC00 => "TP.CM",
+        0xEE00 => "Address Claimed",
+        0xF001 => "EBC1",
+        0xF002 => "ETC1",
+        0xF003 => "EEC2",
+        0xF004 => "EEC1",
+        0xFECA => "DM1",
+        0xFECB => "DM2",
+        0xFEDA => "SOFT",
+        0xFEE5 => "HOURS",
+        0xFEE6 => "TD",
+        0xFEE9 => "LFC1",
+        0xFEEC => "VI",
+        0xFEEE => "ET1",
+        0xFEEF => "EFL/P1",
+        0xFEF1 => "CCVS1",
+        0xFEF2 => "LFE1",
+        0xFEF5 => "AMB",
+        0xFEF6 => "IC1",
+        0xFEF7 => "VEP1",
+        0xFEFC => "DD1",
+        _ => return None,
+    })
+}
+
+/// SPN 定義：位於 PGN 資料中的位置（byte 由 0 起算，Intel 位元順序）與換算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpnDef {
+    pub spn: u32,
+    pub pgn: u32,
+    pub name: &'static str,
+    pub byte: usize,
+    pub bit: u32,
+    pub length: u32,
+    pub resolution: f64,
+    pub offset: f64,
+    pub unit: &'static str,
+}
+
+const fn spn(
+    spn: u32,
+    pgn: u32,
+    name: &'static str,
+    (byte, bit, length): (usize, u32, u32),
+    resolution: f64,
+    offset: f64,
+    unit: &'static str,
+) -> SpnDef {
+    SpnDef {
+        spn,
+        pgn,
+        name,
+        byte,
+        bit,
+        length,
+        resolution,
+        offset,
+        unit,
+    }
+}
+
+/// 內建的常用 SPN
+pub const SPNS: &[SpnDef] = &[
+    spn(899, 0xF004, "Engine Torque Mode", (0, 0, 4), 1.0, 0.0, ""),
+    spn(
+        512,
+        0xF004,
+        "Driver's Demand Engine Torque",
+        (1, 0, 8),
+        1.0,
+        -125.0,
+        "%",
+    ),
+    spn(
+        513,
+        0xF004,
+        "Actual Engine Torque",
+        (2, 0, 8),
+        1.0,
+        -125.0,
+        "%",
+    ),
+    spn(190, 0xF004, "Engine Speed", (3, 0, 16), 0.125, 0.0, "rpm"),
+    spn(
+        1483,
+        0xF004,
+        "Source Address of Controlling Device",
+        (5, 0, 8),
+        1.0,
+        0.0,
+        "",
+    ),
+    spn(
+        91,
+        0xF003,
+        "Accelerator Pedal Position 1",
+        (1, 0, 8),
+        0.4,
+        0.0,
+        "%",
+    ),
+    spn(
+        92,
+        0xF003,
+        "Engine Percent Load At Current Speed",
+        (2, 0, 8),
+        1.0,
+        0.0,
+        "%",
+    ),
+    spn(
+        110,
+        0xFEEE,
+        "Engine Coolant Temperature",
+        (0, 0, 8),
+        1.0,
+        -40.0,
+        "degC",
+    ),
+    spn(
+        174,
+        0xFEEE,
+        "Engine Fuel Temperature",
+        (1, 0, 8),
+        1.0,
+        -40.0,
+        "degC",
+    ),
+    spn(
+        175,
+        0xFEEE,
+        "Engine Oil Temperature",
+        (2, 0, 16),
+        0.03125,
+        -273.0,
+        "degC",
+    ),
+    spn(
+        100,
+        0xFEEF,
+        "Engine Oil Pressure",
+        (3, 0, 8),
+        4.0,
+        0.0,
+        "kPa",
+    ),
+    spn(
+        84,
+        0xFEF1,
+        "Wheel-Based Vehicle Speed",
+        (1, 0, 16),
+        1.0 / 256.0,
+        0.0,
+        "km/h",
+    ),
+    spn(
+        183,
+        0xFEF2,
+        "Engine Fuel Rate",
+        (0, 0, 16),
+        0.05,
+        0.0,
+        "L/h",
+    ),
+    spn(
+        108,
+        0xFEF5,
+        "Barometric Pressure",
+        (0, 0, 8),
+        0.5,
+        0.0,
+        "kPa",
+    ),
+    spn(
+        171,
+        0xFEF5,
+        "Ambient Air Temperature",
+        (3, 0, 16),
+        0.03125,
+        -273.0,
+        "degC",
+    ),
+    spn(
+        102,
+        0xFEF6,
+        "Intake Manifold Pressure",
+        (1, 0, 8),
+        2.0,
+        0.0,
+        "kPa",
+    ),
+    spn(
+        105,
+        0xFEF6,
+        "Intake Manifold Temperature",
+        (2, 0, 8),
+        1.0,
+        -40.0,
+        "degC",
+    ),
+    spn(168, 0xFEF7, "Battery Potential", (4, 0, 16), 0.05, 0.0, "V"),
+    spn(96, 0xFEFC, "Fuel Level 1", (1, 0, 8), 0.4, 0.0, "%"),
+    spn(
+        247,
+        0xFEE5,
+        "Engine Total Hours of Operation",
+        (0, 0, 32),
+        0.05,
+        0.0,
+        "h",
+    ),
+];
+
+impl SpnDef {
+    /// 解碼物理值；資料不足或原始值落在錯誤 / 無效範圍（例如 1 byte 的 0xFB..=0xFF）時為 None
+    pub fn decode(&self, data: &[u8]) -> Option<f64> {
+        let end_bit = self.byte as u32 * 8 + self.bit + self.length;
+        if self.length == 0 || self.length > 32 || end_bit.div_ceil(8) as usize > data.len() {
+            return None;
+        }
+        let raw = data[self.byte..end_bit.div_ceil(8) as usize]
+            .iter()
+            .rev()
+            .fold(0u64, |acc, &b| (acc << 8) | b as u64);
+        let raw = (raw >> self.bit) & ((1u64 << self.length) - 1);
+        // 有效範圍：8 bits 以上為最大值的 250/255，4 bits 以下扣除最上面兩個值
+        let valid_max = match self.length {
+            8 => 0xFA,
+            16 => 0xFAFF,
+            32 => 0xFAFF_FFFF,
+            bits => (1u64 << bits) - 3,
+        };
+        (raw <= valid_max).then_some(raw as f64 * self.resolution + self.offset)
+    }
+}
+
+/// 解碼 PGN 資料中已知的 SPN，回傳（定義, 物理值）
+pub fn decode_spns(pgn: u32, data: &[u8]) -> Vec<(&'static SpnDef, f64)> {
+    SPNS.iter()
+        .filter(|def| def.pgn == pgn)
+        .filter_map(|def| def.decode(data).map(|value| (def, value)))
+        .collect()
+}
+
+/// 重組完成的多封包訊息（BAM 或 RTS/CTS）
+#[derive(Debug, Clone, PartialEq)]
+pub struct J1939Message {
+    pub channel: u32,
+    pub pgn: u32,
+    pub source: u8,
+    pub destination: u8,
+    pub data: Vec<u8>,
+    /// 最後一個 TP.DT 封包的時間
+    pub timestamp: u64,
+}
+
+struct TransportSession {
+    pgn: u32,
+    size: usize,
+    packets: u8,
+    data: Vec<u8>,
+    next_sequence: u8,
+    last_us: u64,
+}
+
+/// 依序餵入訊框，重組 TP.CM/TP.DT 傳送的多封包訊息；
+/// RTS/CTS 只被動監聽資料封包，不回應 CTS
+#[derive(Default)]
+pub struct TransportReassembler {
+    /// 以（通道, 來源位址, 目的位址）區分同時進行的連線
+    sessions: HashMap<(u32, u8, u8), TransportSession>,
+}
+
+impl TransportReassembler {
+    /// 處理一筆訊框，完成一則訊息時回傳；非 TP 訊框回傳 None
+    pub fn process(&mut self, frame: &CanFrame) -> Option<J1939Message> {
+        if !frame.extended || frame.rtr {
+            return None;
+        }
+        let id = J1939Id::from_can_id(frame.id);
+        let destination = id.destination.unwrap_or(GLOBAL_ADDRESS);
+        let key = (frame.channel, id.source, destination);
+        let data = frame.payload();
+        match id.pgn {
+            PGN_TP_CM if data.len() >= 8 => {
+                match data[0] {
+                    TP_CM_BAM | TP_CM_RTS => {
+                        let size = u16::from_le_bytes([data[1], data[2]]) as usize;
+                        let pgn = u32::from_le_bytes([data[5], data[6], data[7], 0]);
+                        if size <= TP_MAX_SIZE && data[3] > 0 {
+                            self.sessions.insert(
+                                key,
+                                TransportSession {
+                                    pgn,
+                                    size,
+                                    packets: data[3],
+                                    data: Vec::with_capacity(data[3] as usize * 7),
+                                    next_sequence: 1,
+                                    last_us: frame.timestamp,
+                                },
+                            );
+                        }
+                    }
+                    TP_CM_ABORT => {
+                        // 中止可能由任一端送出
+                        self.sessions.remove(&key);
+                        self.sessions
+                            .remove(&(frame.channel, destination, id.source));
+                    }
+                    _ => {}
+                }
+                None
+            }
+            PGN_TP_DT if !data.is_empty() => {
+                let session = self.sessions.get_mut(&key)?;
+                let expired = frame.timestamp.saturating_sub(session.last_us) > TP_TIMEOUT_US;
+                if expired || data[0] != session.next_sequence {
+                    // RTS/CTS 重送的封包序號會重複，直接忽略
+                    if expired || data[0] > session.next_sequence {
+                        self.sessions.remove(&key);
+                    }
+                    return None;
+                }
+                session.data.extend_from_slice(&data[1..]);
+                session.next_sequence = session.next_sequence.wrapping_add(1);
+                session.last_us = frame.timestamp;
+                if data[0] < session.packets {
+                    return None;
+                }
+                let mut session = self.sessions.remove(&key)?;
+                session.data.truncate(session.size);
+                Some(J1939Message {
+                    channel: frame.channel,
+                    pgn: session.pgn,
+                    source: id.source,
+                    destination,
+                    data: session.data,
+                    timestamp: frame.timestamp,
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+/// 是否為傳輸協定（TP.CM / TP.DT）訊框
+pub fn is_transport(frame: &CanFrame) -> bool {
+    frame.extended && matches!(J1939Id::from_can_id(frame.id).pgn, PGN_TP_CM | PGN_TP_DT)
+}
+
+fn describe_pgn(pgn: u32) -> String {
+    match pgn_name(pgn) {
+        Some(name) => format!("PGN=0x{:04X} ({})", pgn, name),
+        None => format!("PGN=0x{:04X}", pgn),
+    }
+}
+
+fn describe_spns(pgn: u32, data: &[u8]) -> String {
+    decode_spns(pgn, data)
+        .iter()
+        .map(|(def, value)| {
+            format!(" {}={} {}", def.name, value, def.unit)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 追蹤畫面附加的 J1939 說明，例如 `P=3 PGN=0xF004 (EEC1) SA=0x00 | Engine Speed=1200 rpm`；
+/// 11-bit 訊框回傳 None
+pub fn describe_frame(frame: &CanFrame) -> Option<String> {
+    if !frame.extended {
+        return None;
+    }
+    let id = J1939Id::from_can_id(frame.id);
+    let mut text = format!(
+        "P={} {} SA=0x{:02X}",
+        id.priority,
+        describe_pgn(id.pgn),
+        id.source
+    );
+    if let Some(destination) = id.destination {
+        text.push_str(&format!(" DA=0x{:02X}", destination));
+    }
+    let spns = describe_spns(id.pgn, frame.payload());
+    if !spns.is_empty() {
+        text.push_str(" |");
+        text.push_str(&spns);
+    }
+    Some(text)
+}
+
+/// 重組訊息的顯示文字
+pub fn describe_message(message: &J1939Message) -> String {
+    let mut text = format!(
+        "[J1939] CH={} {} SA=0x{:02X} DA=0x{:02X}, {} bytes, Data={:?}",
+        message.channel,
+        describe_pgn(message.pgn),
+        message.source,
+        message.destination,
+        message.data.len(),
+        message.data
+    );
+    let spns = describe_spns(message.pgn, &message.data);
+    if !spns.is_empty() {
+        text.push_str(" |");
+        text.push_str(&spns);
+    }
+    text
+}
@@ -0,0 +1,487 @@
+use crate::can::canbus::{receive_thread_stopped, CanInterface};
+use crate::can::cantypes::{now_micros, CanFrame, FrameProtocol, MAX_STANDARD_ID};
+use crate::can::log_event::LogEvent;
+use crate::can::timesync::TimestampCorrector;
+use crate::can::version::LibraryVersion;
+use flume::Sender;
+use libloading::Library;
+use std::ffi::{c_char, c_void, CStr};
+use std::path::Path;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+
+/// J2534 協定編號：CAN
+const PROTOCOL_CAN: u32 = 0x05;
+/// PassThruConnect 旗標：同時接收 11-bit 與 29-bit 訊框
+const CAN_ID_BOTH: u32 = 0x0800;
+/// TxFlags / RxStatus：29-bit ID
+const CAN_29BIT_ID: u32 = 0x0100;
+/// RxStatus：本機送出訊框的回送、多訊框訊息的開頭、傳送完成通知
+const TX_MSG_TYPE: u32 = 0x01;
+const START_OF_MESSAGE: u32 = 0x02;
+const TX_INDICATION: u32 = 0x08;
+const PASS_FILTER: u32 = 0x01;
+const CLEAR_RX_BUFFER: u32 = 0x08;
+
+const STATUS_NOERROR: i32 = 0x00;
+const ERR_TIMEOUT: i32 = 0x09;
+const ERR_BUFFER_EMPTY: i32 = 0x10;
+const ERR_BUFFER_OVERFLOW: i32 = 0x11;
+
+/// 一次讀取的最大訊息數與等待時間（毫秒）
+const READ_BATCH: usize = 32;
+const READ_TIMEOUT_MS: u32 = 10;
+const WRITE_TIMEOUT_MS: u32 = 100;
+/// PASSTHRU_MSG 的資料區大小
+const MSG_DATA_SIZE: usize = 4128;
+
+/// J2534 的 PASSTHRU_MSG；CAN 訊息的資料前 4 bytes 為大端序的 CAN ID
+#[repr(C)]
+#[derive(Clone)]
+pub struct PassThruMsg {
+    pub protocol_id: u32,
+    pub rx_status: u32,
+    pub tx_flags: u32,
+    /// 裝置的微秒計數
+    pub timestamp: u32,
+    pub data_size: u32,
+    pub extra_data_index: u32,
+    pub data: [u8; MSG_DATA_SIZE],
+}
+
+impl Default for PassThruMsg {
+    fn default() -> Self {
+        Self {
+            protocol_id: PROTOCOL_CAN,
+            rx_status: 0,
+            tx_flags: 0,
+            timestamp: 0,
+            data_size: 0,
+            extra_data_index: 0,
+            data: [0; MSG_DATA_SIZE],
+        }
+    }
+}
+
+/// 將訊框編碼為 PASSTHRU_MSG；J2534 的 CAN 協定不支援 FD 與遠端訊框
+pub fn encode_msg(frame: &CanFrame) -> Result<PassThruMsg, String> {
+    if frame.protocol == FrameProtocol::Fd {
+        return Err("J2534 CAN channel does not support CAN FD frames".to_string());
+    }
+    if frame.rtr {
+        return Err("J2534 CAN channel does not support remote frames".to_string());
+    }
+    let payload = frame.payload();
+    let mut msg = PassThruMsg {
+        tx_flags: if frame.extended { CAN_29BIT_ID } else { 0 },
+        data_size: 4 + payload.len() as u32,
+        extra_data_index: 4 + payload.len() as u32,
+        ..Default::default()
+    };
+    msg.data[..4].copy_from_slice(&frame.id.to_be_bytes());
+    msg.data[4..4 + payload.len()].copy_from_slice(payload);
+    Ok(msg)
+}
+
+/// 解碼收到的 PASSTHRU_MSG；回送、傳送完成通知與非 CAN 訊息回傳 None
+pub fn decode_msg(msg: &PassThruMsg, channel: u32) -> Option<CanFrame> {
+    if msg.protocol_id != PROTOCOL_CAN
+        || msg.rx_status & (TX_MSG_TYPE | START_OF_MESSAGE | TX_INDICATION) != 0
+    {
+        return None;
+    }
+    let size = msg.data_size as usize;
+    if !(4..=12).contains(&size) {
+        return None;
+    }
+    let id = u32::from_be_bytes([msg.data[0], msg.data[1], msg.data[2], msg.data[3]]);
+    let mut frame = CanFrame::new(channel, id & 0x1FFF_FFFF, &msg.data[4..size]);
+    frame.extended = msg.rx_status & CAN_29BIT_ID != 0 || id > MAX_STANDARD_ID;
+    Some(frame)
+}
+
+type PassThruOpen = unsafe extern "system" fn(*const c_void, *mut u32) -> i32;
+type PassThruClose = unsafe extern "system" fn(u32) -> i32;
+type PassThruConnect = unsafe extern "system" fn(u32, u32, u32, u32, *mut u32) -> i32;
+type PassThruDisconnect = unsafe extern "system" fn(u32) -> i32;
+type PassThruMsgs = unsafe extern "system" fn(u32, *mut PassThruMsg, *mut u32, u32) -> i32;
+type PassThruStartMsgFilter = unsafe extern "system" fn(
+    u32,
+    u32,
+    *const PassThruMsg,
+    *const PassThruMsg,
+    *const PassThruMsg,
+    *mut u32,
+) -> i32;
+type PassThruIoctl = unsafe extern "system" fn(u32, u32, *const c_void, *mut c_void) -> i32;
+type PassThruReadVersion =
+    unsafe extern "system" fn(u32, *mut c_char, *mut c_char, *mut c_char) -> i32;
+type PassThruGetLastError = unsafe extern "system" fn(*mut c_char) -> i32;
+
+/// 封裝 J2534 pass-thru DLL（04.04 API）
+pub struct J2534Library {
+    _lib: Library,
+    open: PassThruOpen,
+    close: PassThruClose,
+    connect: PassThruConnect,
+    disconnect: PassThruDisconnect,
+    read_msgs: PassThruMsgs,
+    write_msgs: PassThruMsgs,
+    start_msg_filter: PassThruStartMsgFilter,
+    ioctl: PassThruIoctl,
+    read_version: Option<PassThruReadVersion>,
+    get_last_error: Option<PassThruGetLastError>,
+}
+
+impl J2534Library {
+    pub fn load(path: &str) -> Result<Self, String> {
+        unsafe {
+            let lib = Library::new(path).map_err(|e| format!("Failed to load {}: {}", path, e))?;
+            let get = |name: &str| -> Result<*const c_void, String> {
+                lib.get::<*const c_void>(format!("{}\0", name).as_bytes())
+                    .map(|symbol| *symbol)
+                    .map_err(|_| format!("{} does not export {}", path, name))
+            };
+            Ok(Self {
+                open: std::mem::transmute::<*const c_void, PassThruOpen>(get("PassThruOpen")?),
+                close: std::mem::transmute::<*const c_void, PassThruClose>(get("PassThruClose")?),
+                connect: std::mem::transmute::<*const c_void, PassThruConnect>(get(
+                    "PassThruConnect",
+                )?),
+                disconnect: std::mem::transmute::<*const c_void, PassThruDisconnect>(get(
+                    "PassThruDisconnect",
+                )?),
+                read_msgs: std::mem::transmute::<*const c_void, PassThruMsgs>(get(
+                    "PassThruReadMsgs",
+                )?),
+                write_msgs: std::mem::transmute::<*const c_void, PassThruMsgs>(get(
+                    "PassThruWriteMsgs",
+                )?),
+                start_msg_filter: std::mem::transmute::<*const c_void, PassThruStartMsgFilter>(
+                    get("PassThruStartMsgFilter")?,
+                ),
+                ioctl: std::mem::transmute::<*const c_void, PassThruIoctl>(get("PassThruIoctl")?),
+                read_version: lib
+                    .get::<PassThruReadVersion>(b"PassThruReadVersion\0")
+                    .ok()
+                    .map(|f| *f),
+                get_last_error: lib
+                    .get::<PassThruGetLastError>(b"PassThruGetLastError\0")
+                    .ok()
+                    .map(|f| *f),
+                _lib: lib,
+            })
+        }
+    }
+
+    /// 錯誤碼與 PassThruGetLastError 的說明
+    fn error(&self, function: &str, status: i32) -> String {
+        let mut buffer = [0 as c_char; 80];
+        let description = self
+            .get_last_error
+            .filter(|get_last_error| unsafe { get_last_error(buffer.as_mut_ptr()) } == 0)
+            .map(|_| {
+                unsafe { CStr::from_ptr(buffer.as_ptr()) }
+                    .to_string_lossy()
+                    .into_owned()
+            })
+            .unwrap_or_default();
+        format!(
+            "{} failed, error code: 0x{:02X} {}",
+            function, status, description
+        )
+        .trim_end()
+        .to_string()
+    }
+
+    /// (韌體, DLL, API) 版本
+    fn versions(&self, device_id: u32) -> Option<(String, String, String)> {
+        let read_version = self.read_version?;
+        let mut firmware = [0 as c_char; 80];
+        let mut dll = [0 as c_char; 80];
+        let mut api = [0 as c_char; 80];
+        let status = unsafe {
+            read_version(
+                device_id,
+                firmware.as_mut_ptr(),
+                dll.as_mut_ptr(),
+                api.as_mut_ptr(),
+            )
+        };
+        let text = |buffer: &[c_char; 80]| {
+            unsafe { CStr::from_ptr(buffer.as_ptr()) }
+                .to_string_lossy()
+                .into_owned()
+        };
+        (status == STATUS_NOERROR).then(|| (text(&firmware), text(&dll), text(&api)))
+    }
+}
+
+/// 已開啟的裝置與 CAN 通道
+struct Connection {
+    lib: Arc<J2534Library>,
+    device_id: u32,
+    channel_id: u32,
+}
+
+/// SAE J2534 pass-thru 裝置（廠商提供的 DLL）；只使用一個 CAN 通道（0）
+pub struct J2534App {
+    dll_path: String,
+    bitrate_kbps: u32,
+    connection: Mutex<Option<Connection>>,
+    versions: Mutex<Vec<LibraryVersion>>,
+    receiving: Arc<AtomicBool>,
+    join_handles: Mutex<Vec<thread::JoinHandle<()>>>,
+}
+
+impl J2534App {
+    /// `dll_path` 為廠商的 pass-thru DLL，例如登錄在
+    /// HKLM\SOFTWARE\PassThruSupport.04.04 下的 FunctionLibrary
+    pub fn new(dll_path: &str, bitrate_kbps: u32) -> Self {
+        Self {
+            dll_path: dll_path.to_string(),
+            bitrate_kbps,
+            connection: Mutex::new(None),
+            versions: Mutex::new(Vec::new()),
+            receiving: Arc::new(AtomicBool::new(false)),
+            join_handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// DLL 的檔名，用於顯示
+    pub fn dll_name(path: &str) -> &str {
+        Path::new(path)
+            .file_name()
+            .and_then(|name| name.to_str())
+            .unwrap_or(path)
+    }
+
+    fn connect(&self) -> Result<Connection, String> {
+        let lib = Arc::new(J2534Library::load(&self.dll_path)?);
+        let mut device_id = 0;
+        let status = unsafe { (lib.open)(std::ptr::null(), &mut device_id) };
+        if status != STATUS_NOERROR {
+            return Err(lib.error("PassThruOpen", status));
+        }
+        let mut channel_id = 0;
+        let status = unsafe {
+            (lib.connect)(
+                device_id,
+                PROTOCOL_CAN,
+                CAN_ID_BOTH,
+                self.bitrate_kbps * 1000,
+                &mut channel_id,
+            )
+        };
+        if status != STATUS_NOERROR {
+            let error = lib.error("PassThruConnect", status);
+            unsafe { (lib.close)(device_id) };
+            return Err(error);
+        }
+        // CAN 通道預設擋下所有訊息，需要一個全部通過的過濾器
+        let pass_all = PassThruMsg {
+            data_size: 4,
+            ..Default::default()
+        };
+        let mut filter_id = 0;
+        let status = unsafe {
+            (lib.start_msg_filter)(
+                channel_id,
+                PASS_FILTER,
+                &pass_all,
+                &pass_all,
+                std::ptr::null(),
+                &mut filter_id,
+            )
+        };
+        if status != STATUS_NOERROR {
+            let error = lib.error("PassThruStartMsgFilter", status);
+            unsafe {
+                (lib.disconnect)(channel_id);
+                (lib.close)(device_id);
+            }
+            return Err(error);
+        }
+        Ok(Connection {
+            lib,
+            device_id,
+            channel_id,
+        })
+    }
+}
+
+impl CanInterface for J2534App {
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String> {
+        let connection = self.connect().inspect_err(|e| {
+            let _ = log_tx.send(LogEvent::DeviceError { detail: e.clone() });
+        })?;
+        let _ = log_tx.send(LogEvent::DeviceOpened {
+            backend: format!("J2534 ({})", Self::dll_name(&self.dll_path)),
+        });
+        if let Some((firmware, dll, api)) = connection.lib.versions(connection.device_id) {
+            let versions = vec![
+                LibraryVersion::new("J2534 firmware", firmware),
+                LibraryVersion::new("J2534 DLL", dll),
+                LibraryVersion::new("J2534 API", api),
+            ];
+            for version in &versions {
+                version.report(&log_tx);
+            }
+            *self.versions.lock().unwrap() = versions;
+        }
+        let _ = log_tx.send(LogEvent::ChannelInitialized {
+            channel: 0,
+            settings: format!("BaudRate: {}K", self.bitrate_kbps),
+        });
+        *self.connection.lock().unwrap() = Some(connection);
+        Ok(())
+    }
+
+    fn close_device(&self, log_tx: Sender<LogEvent>) {
+        if let Some(connection) = self.connection.lock().unwrap().take() {
+            let status = unsafe {
+                (connection.lib.disconnect)(connection.channel_id);
+                (connection.lib.close)(connection.device_id)
+            };
+            let _ = log_tx.send(LogEvent::DeviceClosed {
+                backend: "J2534".to_string(),
+                status: Some(status as i64),
+            });
+        }
+    }
+
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
+        let Some((lib, channel_id)) = self
+            .connection
+            .lock()
+            .unwrap()
+            .as_ref()
+            .map(|c| (Arc::clone(&c.lib), c.channel_id))
+        else {
+            let _ = log_tx.send(LogEvent::NotInitialized {
+                backend: "J2534".to_string(),
+            });
+            return;
+        };
+        self.receiving.store(true, Ordering::SeqCst);
+        let receiving_flag = Arc::clone(&self.receiving);
+        let handle = thread::spawn(move || {
+            let _ = log_tx.send(LogEvent::ChannelStarted { channel: 0 });
+            // PASSTHRU_MSG 的時間戳記為 32 位元微秒計數
+            let mut corrector = TimestampCorrector::new(1.0, 32);
+            let mut msgs = vec![PassThruMsg::default(); READ_BATCH];
+            while receiving_flag.load(Ordering::SeqCst) {
+                let mut count = READ_BATCH as u32;
+                let status = unsafe {
+                    (lib.read_msgs)(channel_id, msgs.as_mut_ptr(), &mut count, READ_TIMEOUT_MS)
+                };
+                let host = now_micros();
+                // 逾時或緩衝區空時仍可能帶回部分訊息
+                for msg in &msgs[..(count as usize).min(READ_BATCH)] {
+                    if let Some(mut frame) = decode_msg(msg, 0) {
+                        frame.timestamp = corrector.correct(msg.timestamp, host);
+                        frame.device_timestamp = Some(corrector.device_micros());
+                        let _ = data_tx.send(frame);
+                    }
+                }
+                match status {
+                    STATUS_NOERROR | ERR_TIMEOUT | ERR_BUFFER_EMPTY => {}
+                    ERR_BUFFER_OVERFLOW => {
+                        let _ = log_tx.send(LogEvent::ReceiveOverrun {
+                            channel: 0,
+                            code: Some(status as u32),
+                        });
+                    }
+                    _ => {
+                        let _ = log_tx.send(LogEvent::DeviceError {
+                            detail: lib.error("PassThruReadMsgs", status),
+                        });
+                        break;
+                    }
+                }
+            }
+            let _ = log_tx.send(LogEvent::ChannelStopped { channel: 0 });
+        });
+        self.join_handles.lock().unwrap().push(handle);
+    }
+
+    fn stop_receiving(&self) {
+        self.receiving.store(false, Ordering::SeqCst);
+        let mut handles = self.join_handles.lock().unwrap();
+        while let Some(handle) = handles.pop() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining J2534 thread: {:?}", e);
+            }
+        }
+    }
+
+    fn check_device(&self) -> Result<(), String> {
+        if receive_thread_stopped(&self.receiving, &self.join_handles) {
+            Err("J2534 receive loop stopped".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_board_info(&self, log_tx: Sender<LogEvent>) {
+        let open = self.connection.lock().unwrap().is_some();
+        let _ = log_tx.send(LogEvent::BackendInfo {
+            description: format!(
+                "J2534 pass-thru device via {} ({})",
+                self.dll_path,
+                if open { "open" } else { "closed" }
+            ),
+        });
+    }
+
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        let mut msg = encode_msg(frame)?;
+        let connection = self.connection.lock().unwrap();
+        let Some(connection) = connection.as_ref() else {
+            return Err("J2534 device not open; cannot transmit".to_string());
+        };
+        let mut count = 1;
+        let status = unsafe {
+            (connection.lib.write_msgs)(
+                connection.channel_id,
+                &mut msg,
+                &mut count,
+                WRITE_TIMEOUT_MS,
+            )
+        };
+        if status != STATUS_NOERROR {
+            Err(connection.lib.error("PassThruWriteMsgs", status))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn library_versions(&self) -> Vec<LibraryVersion> {
+        self.versions.lock().unwrap().clone()
+    }
+
+    fn clear_receive_buffer(&self) -> Result<(), String> {
+        let connection = self.connection.lock().unwrap();
+        let Some(connection) = connection.as_ref() else {
+            return Err("J2534 device not open; cannot clear buffer".to_string());
+        };
+        let status = unsafe {
+            (connection.lib.ioctl)(
+                connection.channel_id,
+                CLEAR_RX_BUFFER,
+                std::ptr::null(),
+                std::ptr::null_mut(),
+            )
+        };
+        if status != STATUS_NOERROR {
+            Err(connection
+                .lib
+                .error("PassThruIoctl(CLEAR_RX_BUFFER)", status))
+        } else {
+            Ok(())
+        }
+    }
+}
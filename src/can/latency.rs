@@ -0,0 +1,148 @@
+use crate::can::cantypes::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+/// 每組保留的延遲樣本數（供圖表使用）
+const LATENCY_HISTORY: usize = 200;
+/// 超過此時間仍未收到回應，視為逾時
+const DEFAULT_TIMEOUT_MS: f64 = 1000.0;
+
+/// 請求/回應 ID 配對，例如 0x7E0 → 0x7E8
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LatencyPair {
+    #[serde(deserialize_with = "crate::can::config::deserialize_hex_or_decimal")]
+    pub request_id: u32,
+    #[serde(deserialize_with = "crate::can::config::deserialize_hex_or_decimal")]
+    pub response_id: u32,
+}
+
+/// 單一配對的量測結果
+#[derive(Debug, Clone)]
+pub struct LatencyStats {
+    pub pair: LatencyPair,
+    pub count: u64,
+    pub timeouts: u64,
+    pub last_ms: f64,
+    pub min_ms: f64,
+    pub max_ms: f64,
+    pub sum_ms: f64,
+    /// 最近的延遲樣本（毫秒）
+    pub history: VecDeque<f64>,
+    pending_since: Option<u64>,
+    /// 由設定檔載入，重新載入時會被取代
+    from_config: bool,
+}
+
+impl LatencyStats {
+    fn new(pair: LatencyPair) -> Self {
+        Self {
+            pair,
+            count: 0,
+            timeouts: 0,
+            last_ms: 0.0,
+            min_ms: f64::MAX,
+            max_ms: 0.0,
+            sum_ms: 0.0,
+            history: VecDeque::with_capacity(LATENCY_HISTORY),
+            pending_since: None,
+            from_config: false,
+        }
+    }
+
+    pub fn mean_ms(&self) -> f64 {
+        if self.count == 0 {
+            0.0
+        } else {
+            self.sum_ms / self.count as f64
+        }
+    }
+
+    fn add_sample(&mut self, latency_ms: f64) {
+        self.count += 1;
+        self.last_ms = latency_ms;
+        self.min_ms = self.min_ms.min(latency_ms);
+        self.max_ms = self.max_ms.max(latency_ms);
+        self.sum_ms += latency_ms;
+        if self.history.len() >= LATENCY_HISTORY {
+            self.history.pop_front();
+        }
+        self.history.push_back(latency_ms);
+    }
+}
+
+/// 依據收發訊框的時間戳記計算請求/回應延遲
+#[derive(Debug)]
+pub struct LatencyTracker {
+    stats: Vec<LatencyStats>,
+    timeout_ms: f64,
+}
+
+impl Default for LatencyTracker {
+    fn default() -> Self {
+        Self {
+            stats: Vec::new(),
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+        }
+    }
+}
+
+impl LatencyTracker {
+    pub fn add_pair(&mut self, pair: LatencyPair) {
+        if !self.stats.iter().any(|s| s.pair == pair) {
+            self.stats.push(LatencyStats::new(pair));
+        }
+    }
+
+    /// 以設定檔的配對取代上次由設定檔載入的配對；仍在設定中的配對保留量測結果，
+    /// 手動加入的配對不受影響
+    pub fn set_config_pairs(&mut self, pairs: &[LatencyPair]) {
+        self.stats
+            .retain(|s| !s.from_config || pairs.contains(&s.pair));
+        for pair in pairs {
+            if !self.stats.iter().any(|s| s.pair == *pair) {
+                let mut stat = LatencyStats::new(*pair);
+                stat.from_config = true;
+                self.stats.push(stat);
+            }
+        }
+    }
+
+    pub fn remove_pair(&mut self, index: usize) {
+        if index < self.stats.len() {
+            self.stats.remove(index);
+        }
+    }
+
+    /// 清除所有量測結果，保留配對設定
+    pub fn reset(&mut self) {
+        for stat in self.stats.iter_mut() {
+            let from_config = stat.from_config;
+            *stat = LatencyStats::new(stat.pair);
+            stat.from_config = from_config;
+        }
+    }
+
+    pub fn stats(&self) -> &[LatencyStats] {
+        &self.stats
+    }
+
+    /// 處理一筆收到或送出的訊框；請求開始計時，回應結束計時
+    pub fn observe(&mut self, frame: &CanFrame) {
+        for stat in self.stats.iter_mut() {
+            if let Some(since) = stat.pending_since {
+                let elapsed_ms = frame.timestamp.saturating_sub(since) as f64 / 1000.0;
+                if elapsed_ms > self.timeout_ms {
+                    stat.timeouts += 1;
+                    stat.pending_since = None;
+                }
+            }
+            if frame.id == stat.pair.response_id {
+                if let Some(since) = stat.pending_since.take() {
+                    stat.add_sample(frame.timestamp.saturating_sub(since) as f64 / 1000.0);
+                }
+            } else if frame.id == stat.pair.request_id {
+                stat.pending_since = Some(frame.timestamp);
+            }
+        }
+    }
+}
@@ -0,0 +1,388 @@
+use crate::can::bus_state::BusState;
+use serde::{Deserialize, Serialize};
+
+/// 訊息顯示語言
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Language {
+    #[default]
+    English,
+    /// 繁體中文
+    Chinese,
+}
+
+impl Language {
+    pub const ALL: [Language; 2] = [Language::English, Language::Chinese];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Language::English => "English",
+            Language::Chinese => "中文",
+        }
+    }
+}
+
+/// 事件嚴重程度，對應 tracing 的等級
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogSeverity {
+    Info,
+    Warning,
+    Error,
+}
+
+/// 後端回報的紀錄事件，每種事件有固定代碼，供腳本與測試比對；
+/// 代碼開頭 I 為資訊、W 為警告、E 為錯誤，已發布的代碼不可重新指派
+#[derive(Debug, Clone, PartialEq)]
+pub enum LogEvent {
+    DeviceOpened {
+        backend: String,
+    },
+    DeviceClosed {
+        backend: String,
+        status: Option<i64>,
+    },
+    ChannelInitialized {
+        channel: u32,
+        settings: String,
+    },
+    ChannelStarted {
+        channel: u32,
+    },
+    ChannelStopped {
+        channel: u32,
+    },
+    BoardInfo {
+        serial: String,
+        firmware: String,
+    },
+    ApiVersion {
+        library: String,
+        version: String,
+    },
+    BackendInfo {
+        description: String,
+    },
+    OptionSet {
+        option: String,
+        enabled: bool,
+    },
+    RemoteConnected {
+        address: String,
+    },
+    RemoteDisconnected {
+        address: String,
+    },
+    BusRecovered {
+        channel: u32,
+    },
+    /// 裝置失效後第幾次嘗試重新開啟成功
+    Reconnected {
+        attempt: u32,
+    },
+    OptionFailed {
+        option: String,
+        enabled: bool,
+    },
+    NotInitialized {
+        backend: String,
+    },
+    ReceiveOverrun {
+        channel: u32,
+        code: Option<u32>,
+    },
+    TimestampResync {
+        channel: u32,
+    },
+    NotConnected,
+    LibraryVersionWarning {
+        library: String,
+        version: String,
+        reason: String,
+    },
+    /// 控制器進入 error warning 或 error passive
+    BusErrorState {
+        channel: u32,
+        state: BusState,
+        tec: Option<u8>,
+        rec: Option<u8>,
+    },
+    /// 最近一段時間收到的錯誤訊框數與最後一筆的錯誤類型
+    ErrorFrames {
+        channel: u32,
+        count: u64,
+        last: String,
+    },
+    ReconnectFailed {
+        attempt: u32,
+        detail: String,
+    },
+    DeviceError {
+        detail: String,
+    },
+    ChannelStartFailed {
+        channel: u32,
+        code: i64,
+    },
+    BoardInfoFailed {
+        backend: String,
+    },
+    ReadFailed {
+        channel: u32,
+        code: u32,
+    },
+    RemoteError {
+        detail: String,
+    },
+    RemoteConnectionLost {
+        detail: String,
+    },
+    BusOff {
+        channel: u32,
+    },
+    /// 接收期間裝置失效，例如 USB 轉接器被拔除
+    DeviceLost {
+        detail: String,
+    },
+}
+
+impl LogEvent {
+    /// 穩定的事件代碼
+    pub fn code(&self) -> &'static str {
+        match self {
+            LogEvent::DeviceOpened { .. } => "I101",
+            LogEvent::DeviceClosed { .. } => "I102",
+            LogEvent::ChannelInitialized { .. } => "I103",
+            LogEvent::ChannelStarted { .. } => "I104",
+            LogEvent::ChannelStopped { .. } => "I105",
+            LogEvent::BoardInfo { .. } => "I106",
+            LogEvent::ApiVersion { .. } => "I107",
+            LogEvent::BackendInfo { .. } => "I108",
+            LogEvent::OptionSet { .. } => "I109",
+            LogEvent::RemoteConnected { .. } => "I110",
+            LogEvent::RemoteDisconnected { .. } => "I111",
+            LogEvent::BusRecovered { .. } => "I112",
+            LogEvent::Reconnected { .. } => "I113",
+            LogEvent::OptionFailed { .. } => "W201",
+            LogEvent::NotInitialized { .. } => "W202",
+            LogEvent::ReceiveOverrun { .. } => "W203",
+            LogEvent::TimestampResync { .. } => "W204",
+            LogEvent::NotConnected => "W205",
+            LogEvent::LibraryVersionWarning { .. } => "W206",
+            LogEvent::BusErrorState { .. } => "W207",
+            LogEvent::ErrorFrames { .. } => "W208",
+            LogEvent::ReconnectFailed { .. } => "W209",
+            LogEvent::DeviceError { .. } => "E301",
+            LogEvent::ChannelStartFailed { .. } => "E302",
+            LogEvent::BoardInfoFailed { .. } => "E303",
+            LogEvent::ReadFailed { .. } => "E304",
+            LogEvent::RemoteError { .. } => "E305",
+            LogEvent::RemoteConnectionLost { .. } => "E306",
+            LogEvent::BusOff { .. } => "E307",
+            LogEvent::DeviceLost { .. } => "E308",
+        }
+    }
+
+    pub fn severity(&self) -> LogSeverity {
+        match self.code().as_bytes()[0] {
+            b'E' => LogSeverity::Error,
+            b'W' => LogSeverity::Warning,
+            _ => LogSeverity::Info,
+        }
+    }
+
+    /// 依語言格式化為顯示文字（不含代碼）
+    pub fn format(&self, language: Language) -> String {
+        let zh = language == Language::Chinese;
+        let on_off = |enabled: bool| match (zh, enabled) {
+            (false, true) => "enabled",
+            (false, false) => "disabled",
+            (true, true) => "啟用",
+            (true, false) => "停用",
+        };
+        match self {
+            LogEvent::DeviceOpened { backend } if zh => format!("{} 裝置已開啟", backend),
+            LogEvent::DeviceOpened { backend } => format!("{} device opened", backend),
+            LogEvent::DeviceClosed { backend, status } => {
+                let status = status.map(|s| s.to_string()).unwrap_or_default();
+                if zh {
+                    format!("{} 裝置已關閉 {}", backend, status)
+                } else {
+                    format!("{} device closed {}", backend, status)
+                }
+                .trim_end()
+                .to_string()
+            }
+            LogEvent::ChannelInitialized { channel, settings } if zh => {
+                format!("通道 {} 已初始化（{}）", channel, settings)
+            }
+            LogEvent::ChannelInitialized { channel, settings } => {
+                format!("Channel {} initialized ({})", channel, settings)
+            }
+            LogEvent::ChannelStarted { channel } if zh => format!("通道 {} 開始接收", channel),
+            LogEvent::ChannelStarted { channel } => format!("Channel {} started", channel),
+            LogEvent::ChannelStopped { channel } if zh => format!("通道 {} 停止接收", channel),
+            LogEvent::ChannelStopped { channel } => {
+                format!("Channel {} stopped receiving", channel)
+            }
+            LogEvent::BoardInfo { serial, firmware } if zh => {
+                format!("板卡資訊：序號={}，韌體={}", serial, firmware)
+            }
+            LogEvent::BoardInfo { serial, firmware } => {
+                format!("Board info: Serial={}, Firmware={}", serial, firmware)
+            }
+            LogEvent::ApiVersion { library, version } if zh => {
+                format!("{} 函式庫版本：{}", library, version)
+            }
+            LogEvent::ApiVersion { library, version } => {
+                format!("{} library version: {}", library, version)
+            }
+            LogEvent::BackendInfo { description } => description.clone(),
+            LogEvent::OptionSet { option, enabled } if zh => {
+                format!("{} 已{}", option, on_off(*enabled))
+            }
+            LogEvent::OptionSet { option, enabled } => {
+                format!("{} {}", option, on_off(*enabled))
+            }
+            LogEvent::RemoteConnected { address } if zh => {
+                format!("已連線到遠端伺服器 {}", address)
+            }
+            LogEvent::RemoteConnected { address } => {
+                format!("Connected to remote server {}", address)
+            }
+            LogEvent::RemoteDisconnected { address } if zh => {
+                format!("已中斷遠端伺服器 {}", address)
+            }
+            LogEvent::RemoteDisconnected { address } => {
+                format!("Disconnected from remote server {}", address)
+            }
+            LogEvent::BusRecovered { channel } if zh => {
+                format!("通道 {} 已回到 error active 狀態", channel)
+            }
+            LogEvent::BusRecovered { channel } => {
+                format!("Channel {} back to error active", channel)
+            }
+            LogEvent::Reconnected { attempt } if zh => {
+                format!("第 {} 次嘗試後已重新連線裝置", attempt)
+            }
+            LogEvent::Reconnected { attempt } => {
+                format!("Device reconnected after {} attempt(s)", attempt)
+            }
+            LogEvent::OptionFailed { option, enabled } if zh => {
+                format!("無法{} {}", on_off(*enabled), option)
+            }
+            LogEvent::OptionFailed { option, enabled } => {
+                format!("Failed to set {} ({})", option, on_off(*enabled))
+            }
+            LogEvent::NotInitialized { backend } if zh => format!("{} 尚未初始化", backend),
+            LogEvent::NotInitialized { backend } => format!("{} not initialized", backend),
+            LogEvent::ReceiveOverrun { channel, code } => {
+                let code = code.map(|c| format!(" (0x{:X})", c)).unwrap_or_default();
+                if zh {
+                    format!("通道 {} 接收溢出{}，有訊框遺失", channel, code)
+                } else {
+                    format!("Channel {} receive overrun{}; frames lost", channel, code)
+                }
+            }
+            LogEvent::TimestampResync { channel } if zh => {
+                format!("通道 {} 裝置時間跳動，已重新對齊主機時鐘", channel)
+            }
+            LogEvent::TimestampResync { channel } => format!(
+                "Channel {} device timestamp jumped; resynchronized to host clock",
+                channel
+            ),
+            LogEvent::NotConnected if zh => "尚未連線到遠端伺服器".to_string(),
+            LogEvent::NotConnected => "Remote server not connected".to_string(),
+            LogEvent::LibraryVersionWarning {
+                library,
+                version,
+                reason,
+            } if zh => format!("{} 版本 {} 有已知問題：{}", library, version, reason),
+            LogEvent::LibraryVersionWarning {
+                library,
+                version,
+                reason,
+            } => format!(
+                "{} version {} has known issues: {}",
+                library, version, reason
+            ),
+            LogEvent::BusErrorState {
+                channel,
+                state,
+                tec,
+                rec,
+            } => {
+                let counters = match (tec, rec) {
+                    (Some(tec), Some(rec)) => format!(" (TEC={}, REC={})", tec, rec),
+                    _ => String::new(),
+                };
+                if zh {
+                    format!("通道 {} 控制器進入 {}{}", channel, state, counters)
+                } else {
+                    format!("Channel {} controller is {}{}", channel, state, counters)
+                }
+            }
+            LogEvent::ErrorFrames {
+                channel,
+                count,
+                last,
+            } if zh => format!(
+                "通道 {} 收到 {} 個錯誤訊框（最後為 {}）",
+                channel, count, last
+            ),
+            LogEvent::ErrorFrames {
+                channel,
+                count,
+                last,
+            } => format!(
+                "Channel {} received {} error frames (last: {})",
+                channel, count, last
+            ),
+            LogEvent::ReconnectFailed { attempt, detail } if zh => {
+                format!("第 {} 次重新連線失敗：{}", attempt, detail)
+            }
+            LogEvent::ReconnectFailed { attempt, detail } => {
+                format!("Reconnect attempt {} failed: {}", attempt, detail)
+            }
+            LogEvent::DeviceError { detail } => detail.clone(),
+            LogEvent::ChannelStartFailed { channel, code } if zh => {
+                format!("通道 {} 啟動失敗，錯誤碼：{}", channel, code)
+            }
+            LogEvent::ChannelStartFailed { channel, code } => {
+                format!("Channel {} start failed, error code: {}", channel, code)
+            }
+            LogEvent::BoardInfoFailed { backend } if zh => format!("無法讀取 {} 板卡資訊", backend),
+            LogEvent::BoardInfoFailed { backend } => {
+                format!("Failed to read {} board info", backend)
+            }
+            LogEvent::ReadFailed { channel, code } if zh => {
+                format!("通道 0x{:X} 讀取失敗，錯誤碼：0x{:X}", channel, code)
+            }
+            LogEvent::ReadFailed { channel, code } => {
+                format!(
+                    "Channel 0x{:X} read failed, error code: 0x{:X}",
+                    channel, code
+                )
+            }
+            LogEvent::RemoteError { detail } if zh => format!("遠端錯誤：{}", detail),
+            LogEvent::RemoteError { detail } => format!("Remote error: {}", detail),
+            LogEvent::RemoteConnectionLost { detail } if zh => format!("遠端連線中斷：{}", detail),
+            LogEvent::RemoteConnectionLost { detail } => {
+                format!("Remote connection lost: {}", detail)
+            }
+            LogEvent::BusOff { channel } if zh => {
+                format!("通道 {} 控制器 bus-off，已停止收發", channel)
+            }
+            LogEvent::DeviceLost { detail } if zh => {
+                format!("裝置失效，嘗試重新連線：{}", detail)
+            }
+            LogEvent::DeviceLost { detail } => {
+                format!("Device lost, trying to reconnect: {}", detail)
+            }
+            LogEvent::BusOff { channel } => {
+                format!(
+                    "Channel {} controller is bus-off; no frames sent or received",
+                    channel
+                )
+            }
+        }
+    }
+}
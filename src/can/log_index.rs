@@ -0,0 +1,167 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::logfile::parse_candump_line;
+use crate::can::session::SessionInfo;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// 每個索引區塊包含的訊框數
+pub const BLOCK_SIZE: usize = 1024;
+
+/// 區塊的起始位置
+#[derive(Debug, Clone, Copy)]
+struct Block {
+    offset: u64,
+    first_timestamp: u64,
+}
+
+/// 單一 (通道, ID) 出現在哪些區塊，以及在每個區塊內的筆數
+#[derive(Debug, Clone, Default)]
+pub struct IdEntry {
+    pub count: u64,
+    blocks: Vec<(u32, u32)>,
+}
+
+/// 大型紀錄檔的稀疏索引：只記錄每 `BLOCK_SIZE` 筆訊框的檔案位置與各 ID 分佈，
+/// 不將整個檔案載入記憶體，需要時再從檔案讀出所需的區塊
+#[derive(Debug)]
+pub struct LogIndex {
+    path: PathBuf,
+    blocks: Vec<Block>,
+    frame_count: u64,
+    ids: BTreeMap<(u32, u32), IdEntry>,
+    session: SessionInfo,
+}
+
+impl LogIndex {
+    /// 串流讀取整個檔案建立索引；`progress` 回報已讀取的位元組數，`cancel` 可中止
+    pub fn build(path: &Path, progress: &AtomicU64, cancel: &AtomicBool) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("Failed to open {}: {}", path.display(), e))?;
+        let mut reader = BufReader::with_capacity(1 << 20, file);
+        let mut index = Self {
+            path: path.to_path_buf(),
+            blocks: Vec::new(),
+            frame_count: 0,
+            ids: BTreeMap::new(),
+            session: SessionInfo::default(),
+        };
+        let mut offset = 0u64;
+        let mut line = String::new();
+        loop {
+            if cancel.load(Ordering::Relaxed) {
+                return Err("Indexing cancelled".to_string());
+            }
+            line.clear();
+            let read = reader
+                .read_line(&mut line)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+            if read == 0 {
+                break;
+            }
+            if index.frame_count == 0 && line.starts_with('#') {
+                index.session.parse_header_line(&line);
+            } else if let Some(frame) = parse_candump_line(&line) {
+                let position = (index.frame_count % BLOCK_SIZE as u64) as usize;
+                if position == 0 {
+                    index.blocks.push(Block {
+                        offset,
+                        first_timestamp: frame.timestamp,
+                    });
+                }
+                let block = (index.blocks.len() - 1) as u32;
+                let entry = index.ids.entry((frame.channel, frame.id)).or_default();
+                entry.count += 1;
+                match entry.blocks.last_mut() {
+                    Some((b, count)) if *b == block => *count += 1,
+                    _ => entry.blocks.push((block, 1)),
+                }
+                index.frame_count += 1;
+            }
+            offset += read as u64;
+            progress.store(offset, Ordering::Relaxed);
+        }
+        Ok(index)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn frame_count(&self) -> u64 {
+        self.frame_count
+    }
+
+    /// 檔案開頭記錄的工作階段資料
+    pub fn session(&self) -> &SessionInfo {
+        &self.session
+    }
+
+    /// 檔案中的 (通道, ID) 與其筆數
+    pub fn ids(&self) -> &BTreeMap<(u32, u32), IdEntry> {
+        &self.ids
+    }
+
+    /// 第一筆與最後一個區塊的起始時間（微秒）
+    pub fn time_range(&self) -> Option<(u64, u64)> {
+        Some((
+            self.blocks.first()?.first_timestamp,
+            self.blocks.last()?.first_timestamp,
+        ))
+    }
+
+    /// 從檔案讀出一個區塊的所有訊框
+    pub fn read_block(&self, block: u32) -> Result<Vec<CanFrame>, String> {
+        let start = self
+            .blocks
+            .get(block as usize)
+            .ok_or_else(|| format!("Block {} out of range", block))?;
+        let mut file = File::open(&self.path)
+            .map_err(|e| format!("Failed to open {}: {}", self.path.display(), e))?;
+        file.seek(SeekFrom::Start(start.offset))
+            .map_err(|e| format!("Failed to seek: {}", e))?;
+        let mut frames = Vec::with_capacity(BLOCK_SIZE);
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("Failed to read: {}", e))?;
+            if let Some(frame) = parse_candump_line(&line) {
+                frames.push(frame);
+                if frames.len() == BLOCK_SIZE {
+                    break;
+                }
+            }
+        }
+        Ok(frames)
+    }
+
+    /// 第 `row` 筆訊框所在的區塊與區塊內位置；`filter` 為 (通道, ID) 時只計算該 ID 的訊框
+    pub fn locate(&self, row: u64, filter: Option<(u32, u32)>) -> Option<(u32, usize)> {
+        match filter {
+            None => (row < self.frame_count).then(|| {
+                (
+                    (row / BLOCK_SIZE as u64) as u32,
+                    (row % BLOCK_SIZE as u64) as usize,
+                )
+            }),
+            Some(key) => {
+                let mut remaining = row;
+                for &(block, count) in &self.ids.get(&key)?.blocks {
+                    if remaining < count as u64 {
+                        return Some((block, remaining as usize));
+                    }
+                    remaining -= count as u64;
+                }
+                None
+            }
+        }
+    }
+
+    /// 套用過濾條件後的總筆數
+    pub fn row_count(&self, filter: Option<(u32, u32)>) -> u64 {
+        match filter {
+            None => self.frame_count,
+            Some(key) => self.ids.get(&key).map_or(0, |e| e.count),
+        }
+    }
+}
@@ -0,0 +1,366 @@
+use crate::can::blf;
+use crate::can::cantypes::{
+    now_micros, CanFrame, FrameDirection, FrameProtocol, MAX_PAYLOAD_LEN, MAX_STANDARD_ID,
+};
+use crate::can::session::SessionInfo;
+use chrono::{Local, TimeZone};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// 解析 candump 紀錄檔的一行，例如 `(1436509052.249713) can0 123#DEADBEEF`
+///
+/// 支援 CAN FD 的 `ID##<flags><data>` 寫法，通道取自介面名稱尾端的數字；
+/// 無法解析的行回傳 None。
+pub fn parse_candump_line(line: &str) -> Option<CanFrame> {
+    let mut parts = line.split_whitespace();
+    let time = parts.next()?.strip_prefix('(')?.strip_suffix(')')?;
+    let iface = parts.next()?;
+    let body = parts.next()?;
+
+    let (secs, frac) = time.split_once('.').unwrap_or((time, "0"));
+    let frac_us: u64 = format!("{:0<6}", &frac[..frac.len().min(6)]).parse().ok()?;
+    let timestamp = secs.parse::<u64>().ok()? * 1_000_000 + frac_us;
+
+    let digits = iface
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(iface, |pos| &iface[pos + 1..]);
+    let channel = digits.parse().unwrap_or(0);
+
+    let (id_text, rest) = body.split_once('#')?;
+    let id = u32::from_str_radix(id_text, 16).ok()?;
+    // candump 以 8 位數表示延伸 ID
+    let extended = id_text.len() == 8 || id > MAX_STANDARD_ID;
+    let (fd, flags, data_text) = match rest.strip_prefix('#') {
+        Some(fd_rest) => {
+            let flags = u8::from_str_radix(fd_rest.get(..1)?, 16).ok()?;
+            (true, flags, &fd_rest[1..])
+        }
+        None => (false, 0, rest),
+    };
+    // 遠端訊框（R）沒有資料，R 之後可帶 DLC，例如 `123#R4`
+    if let Some(dlc_text) = data_text.strip_prefix('R') {
+        if fd {
+            return None;
+        }
+        let dlc = if dlc_text.is_empty() {
+            0
+        } else {
+            dlc_text.parse::<u8>().ok().filter(|&dlc| dlc <= 8)?
+        };
+        let mut frame = CanFrame::new_remote(channel, id, dlc);
+        frame.extended = extended;
+        frame.timestamp = timestamp;
+        return Some(frame);
+    }
+    if !data_text.len().is_multiple_of(2) || data_text.len() / 2 > MAX_PAYLOAD_LEN {
+        return None;
+    }
+    let data: Vec<u8> = (0..data_text.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&data_text[i..i + 2], 16))
+        .collect::<Result<_, _>>()
+        .ok()?;
+    let mut frame = if fd {
+        CanFrame::new_fd(channel, id, &data, flags & 0x01 != 0)
+    } else if data.len() <= 8 {
+        CanFrame::new(channel, id, &data)
+    } else {
+        return None;
+    };
+    frame.esi = fd && flags & 0x02 != 0;
+    frame.extended = extended;
+    frame.timestamp = timestamp;
+    Some(frame)
+}
+
+/// 將訊框格式化為 candump 紀錄檔的一行，介面名稱為 `can<通道>`
+pub fn format_candump_line(frame: &CanFrame) -> String {
+    let data: String = frame
+        .payload()
+        .iter()
+        .map(|b| format!("{:02X}", b))
+        .collect();
+    let id = if frame.extended {
+        format!("{:08X}", frame.id)
+    } else {
+        format!("{:03X}", frame.id)
+    };
+    let body = if frame.rtr {
+        match frame.data.len() {
+            0 => format!("{}#R", id),
+            dlc => format!("{}#R{}", id, dlc),
+        }
+    } else if frame.protocol == FrameProtocol::Classic {
+        format!("{}#{}", id, data)
+    } else {
+        let flags = frame.brs as u8 | (frame.esi as u8) << 1;
+        format!("{}##{:X}{}", id, flags, data)
+    };
+    format!(
+        "({}.{:06}) can{} {}",
+        frame.timestamp / 1_000_000,
+        frame.timestamp % 1_000_000,
+        frame.channel,
+        body
+    )
+}
+
+/// 訊框匯出格式
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExportFormat {
+    Candump,
+    Csv,
+    /// Vector ASCII 紀錄檔（CANoe / CANalyzer）
+    Asc,
+    /// PEAK 追蹤檔 2.1 版（PCAN-View）
+    Trc,
+    /// Vector 二進位紀錄檔，適合長時間擷取
+    Blf,
+}
+
+impl ExportFormat {
+    /// 依副檔名判斷格式，未知的副檔名視為 candump
+    pub fn from_path(path: &str) -> Self {
+        let path = path.to_ascii_lowercase();
+        if path.ends_with(".csv") {
+            ExportFormat::Csv
+        } else if path.ends_with(".asc") {
+            ExportFormat::Asc
+        } else if path.ends_with(".trc") {
+            ExportFormat::Trc
+        } else if path.ends_with(".blf") {
+            ExportFormat::Blf
+        } else {
+            ExportFormat::Candump
+        }
+    }
+}
+
+fn hex_bytes(data: &[u8]) -> String {
+    data.iter()
+        .map(|b| format!("{:02X}", b))
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn direction_name(frame: &CanFrame) -> &'static str {
+    match frame.direction {
+        FrameDirection::Rx => "Rx",
+        FrameDirection::Tx => "Tx",
+    }
+}
+
+/// 將訊框格式化為 ASC 的一行；`base` 為量測開始時間，通道從 1 起算
+pub fn format_asc_line(frame: &CanFrame, base: u64) -> String {
+    let offset = frame.timestamp.saturating_sub(base);
+    let time = format!("{:>4}.{:06}", offset / 1_000_000, offset % 1_000_000);
+    let id = if frame.extended {
+        format!("{:X}x", frame.id)
+    } else {
+        format!("{:X}", frame.id)
+    };
+    let channel = frame.channel + 1;
+    if frame.protocol != FrameProtocol::Classic {
+        // EDL、BRS、ESI 旗標；訊息長度與位元時序未知時填 0
+        let flags = 1u32 << 12 | (frame.brs as u32) << 13 | (frame.esi as u32) << 14;
+        return format!(
+            "{} CANFD {:>3} {:<4} {:>8} {:>32} {} {} {:x} {:>2} {} {:>8} {:>4} {:>8X} {:>8} {:>8} {:>8} {:>8} {:>8}",
+            time,
+            channel,
+            direction_name(frame),
+            id,
+            "",
+            frame.brs as u8,
+            frame.esi as u8,
+            frame.dlc(),
+            frame.payload().len(),
+            hex_bytes(frame.payload()),
+            0,
+            0,
+            flags,
+            0,
+            0,
+            0,
+            0,
+            0
+        );
+    }
+    let body = if frame.rtr {
+        format!("r {:x}", frame.data.len())
+    } else {
+        format!("d {:x} {}", frame.dlc(), hex_bytes(frame.payload()))
+    };
+    format!(
+        "{} {}  {:<15} {:<4} {}",
+        time,
+        channel,
+        id,
+        direction_name(frame),
+        body
+    )
+    .trim_end()
+    .to_string()
+}
+
+/// 將訊框格式化為 TRC 2.1 的一行（欄位 N,O,T,B,I,d,R,L,D）；`number` 從 1 起算
+pub fn format_trc_line(number: usize, frame: &CanFrame, base: u64) -> String {
+    let offset = frame.timestamp.saturating_sub(base);
+    let kind = match (frame.protocol, frame.rtr, frame.brs, frame.esi) {
+        (_, true, _, _) => "RR",
+        (FrameProtocol::Classic, ..) => "DT",
+        // 兩種格式都沒有 XL，與 candump 相同以 FD 表示
+        (_, _, false, false) => "FD",
+        (_, _, true, false) => "FB",
+        (_, _, false, true) => "FE",
+        (_, _, true, true) => "BI",
+    };
+    let id = if frame.extended {
+        format!("{:08X}", frame.id)
+    } else {
+        format!("{:04X}", frame.id)
+    };
+    let dlc = if frame.rtr {
+        frame.data.len() as u8
+    } else {
+        frame.dlc()
+    };
+    let data = if frame.rtr {
+        String::new()
+    } else {
+        hex_bytes(frame.payload())
+    };
+    format!(
+        "{:>7} {:>13}.{:03} {} {:<2} {:>8} {} -  {:<4} {}",
+        number,
+        offset / 1000,
+        offset % 1000,
+        kind,
+        frame.channel + 1,
+        id,
+        direction_name(frame),
+        dlc,
+        data
+    )
+    .trim_end()
+    .to_string()
+}
+
+/// 各格式的檔頭；`base` 為第一筆訊框的時間，工作階段資料轉為該格式的註解
+fn header_lines(format: ExportFormat, base: u64, session: &SessionInfo) -> Vec<String> {
+    let comments = session.header_lines();
+    let start = Local
+        .timestamp_micros(base as i64)
+        .single()
+        .unwrap_or_else(Local::now);
+    match format {
+        ExportFormat::Candump | ExportFormat::Blf => comments,
+        ExportFormat::Csv => {
+            let mut lines = comments;
+            lines.push("timestamp_us,channel,dir,id,protocol,brs,ext,rtr,len,data".to_string());
+            lines
+        }
+        ExportFormat::Asc => {
+            let date = start.format("%a %b %d %I:%M:%S%.3f %P %Y");
+            let mut lines = vec![
+                format!("date {}", date),
+                "base hex  timestamps absolute".to_string(),
+                "internal events logged".to_string(),
+            ];
+            lines.extend(
+                comments
+                    .iter()
+                    .map(|line| format!("//{}", line.trim_start_matches('#'))),
+            );
+            lines.push(format!("Begin Triggerblock {}", date));
+            lines.push("   0.000000 Start of measurement".to_string());
+            lines
+        }
+        ExportFormat::Trc => {
+            // 起始時間以 1899-12-30 起的天數表示（OLE 日期）
+            let local_secs = start.naive_local().and_utc().timestamp_micros() as f64 / 1e6;
+            let mut lines = vec![
+                ";$FILEVERSION=2.1".to_string(),
+                format!(";$STARTTIME={:.10}", local_secs / 86400.0 + 25569.0),
+                ";$COLUMNS=N,O,T,B,I,d,R,L,D".to_string(),
+                ";".to_string(),
+                format!(
+                    ";   Start time: {}.0",
+                    start.format("%d.%m.%Y %H:%M:%S%.3f")
+                ),
+            ];
+            lines.extend(
+                comments
+                    .iter()
+                    .map(|line| format!(";  {}", line.trim_start_matches('#'))),
+            );
+            lines.extend(
+                [
+                    ";-------------------------------------------------------------------------------",
+                    ";   Message   Time    Type    ID     Rx/Tx",
+                    ";   Number    Offset  |  Bus  [hex]  |  Reserved",
+                    ";   |         [ms]    |  |    |      |  |  Data Length Code",
+                    ";   |         |       |  |    |      |  |  |    Data [hex] ...",
+                    ";   |         |       |  |    |      |  |  |    |",
+                    ";---+-- ------+------ +- +- --+----- +- +- +--- +- -- -- -- -- -- -- --",
+                ]
+                .map(String::from),
+            );
+            lines
+        }
+    }
+}
+
+/// 將訊框寫入檔案，回傳寫入的筆數；工作階段資料以註解行寫在檔案開頭，
+/// ASC 與 TRC 的時間以第一筆訊框為起點，BLF 不含工作階段資料
+pub fn export_frames<'a, I>(
+    file_path: &str,
+    frames: I,
+    format: ExportFormat,
+    session: &SessionInfo,
+) -> Result<usize, String>
+where
+    I: IntoIterator<Item = &'a CanFrame>,
+{
+    if format == ExportFormat::Blf {
+        return blf::export_blf(file_path, frames);
+    }
+    let file =
+        File::create(file_path).map_err(|e| format!("Failed to create {}: {}", file_path, e))?;
+    let mut writer = BufWriter::new(file);
+    let write_error = |e: std::io::Error| format!("Failed to write {}: {}", file_path, e);
+    let mut frames = frames.into_iter().peekable();
+    let base = frames.peek().map_or_else(now_micros, |f| f.timestamp);
+    for line in header_lines(format, base, session) {
+        writeln!(writer, "{}", line).map_err(write_error)?;
+    }
+    let mut count = 0;
+    for frame in frames {
+        match format {
+            ExportFormat::Candump => writeln!(writer, "{}", format_candump_line(frame)),
+            ExportFormat::Csv => writeln!(
+                writer,
+                "{},{},{:?},0x{:X},{:?},{},{},{},{},{}",
+                frame.timestamp,
+                frame.channel,
+                frame.direction,
+                frame.id,
+                frame.protocol,
+                frame.brs,
+                frame.extended,
+                frame.rtr,
+                frame.payload().len(),
+                hex_bytes(frame.payload())
+            ),
+            ExportFormat::Asc => writeln!(writer, "{}", format_asc_line(frame, base)),
+            ExportFormat::Trc => writeln!(writer, "{}", format_trc_line(count + 1, frame, base)),
+            ExportFormat::Blf => unreachable!(),
+        }
+        .map_err(write_error)?;
+        count += 1;
+    }
+    if format == ExportFormat::Asc {
+        writeln!(writer, "End TriggerBlock").map_err(write_error)?;
+    }
+    writer.flush().map_err(write_error)?;
+    Ok(count)
+}
@@ -0,0 +1,343 @@
+use crate::can::cantypes::{now_micros, CanFrame};
+use crate::can::config::CanbusConfigEntry;
+use crate::can::dbc::Dbc;
+use crate::can::session::SessionInfo;
+use crate::can::signals::extract_value;
+use crate::can::units::DisplayUnits;
+use std::collections::HashMap;
+use std::fs;
+
+/// 區塊標頭：id、保留、長度、連結數
+const BLOCK_HEADER_SIZE: usize = 24;
+const ID_BLOCK_SIZE: usize = 64;
+const HD_LINKS: usize = 6;
+const HD_DATA_SIZE: usize = 32;
+const HD_SIZE: usize = BLOCK_HEADER_SIZE + HD_LINKS * 8 + HD_DATA_SIZE;
+
+// 通道設定
+const CN_TYPE_VALUE: u8 = 0;
+const CN_TYPE_MASTER: u8 = 2;
+const CN_SYNC_NONE: u8 = 0;
+const CN_SYNC_TIME: u8 = 1;
+/// IEEE 754 little endian 浮點數
+const CN_DATA_FLOAT_LE: u8 = 4;
+
+/// 每筆紀錄為時間（秒）與數值，皆為 f64
+const RECORD_SIZE: usize = 16;
+
+/// 一個訊號的解碼結果，時間為 UNIX epoch 微秒
+#[derive(Debug, Clone, PartialEq)]
+pub struct SignalSeries {
+    pub name: String,
+    pub unit: String,
+    pub samples: Vec<(u64, f64)>,
+}
+
+/// 以 YAML 訊號與 DBC 解碼訊框，產生每個訊號的時間序列；
+/// YAML 訊號以所選的顯示單位輸出，DBC 訊號命名為 `訊息.訊號`，沒有資料的訊號不輸出
+pub fn decode_signals<'a, I>(
+    frames: I,
+    entries: &[CanbusConfigEntry],
+    dbc: &Dbc,
+    units: &DisplayUnits,
+) -> Vec<SignalSeries>
+where
+    I: IntoIterator<Item = &'a CanFrame>,
+{
+    let mut series: Vec<SignalSeries> = Vec::new();
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    let mut index = |name: String, unit: &str| {
+        *by_name.entry(name).or_insert_with_key(|name| {
+            series.push(SignalSeries {
+                name: name.clone(),
+                unit: unit.to_string(),
+                samples: Vec::new(),
+            });
+            series.len() - 1
+        })
+    };
+    // 先建立所有訊號，輸出順序與定義順序相同
+    let mut yaml: HashMap<u32, Vec<(&CanbusConfigEntry, usize)>> = HashMap::new();
+    for entry in entries {
+        let i = index(entry.key.clone(), units.unit(&entry.key));
+        yaml.entry(entry.id).or_default().push((entry, i));
+    }
+    let mut messages: HashMap<u32, Vec<usize>> = HashMap::new();
+    for message in &dbc.messages {
+        let indices = message
+            .signals
+            .iter()
+            .map(|s| index(format!("{}.{}", message.name, s.name), &s.unit))
+            .collect();
+        messages.insert(message.id, indices);
+    }
+
+    for frame in frames {
+        if frame.rtr {
+            continue;
+        }
+        for &(entry, i) in yaml.get(&frame.id).into_iter().flatten() {
+            if let Some(value) = extract_value(entry, frame.payload()) {
+                let value = units.convert(&entry.key, value);
+                series[i].samples.push((frame.timestamp, value));
+            }
+        }
+        let Some(message) = dbc.message(frame.id) else {
+            continue;
+        };
+        let selector = message
+            .multiplexor()
+            .and_then(|s| s.raw_value(frame.payload()));
+        for (signal, &i) in message.signals.iter().zip(&messages[&frame.id]) {
+            if !message.is_active(signal, selector) {
+                continue;
+            }
+            if let Some((value, _)) = signal.decode(frame.payload()) {
+                series[i].samples.push((frame.timestamp, value));
+            }
+        }
+    }
+    series.retain(|s| !s.samples.is_empty());
+    series
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// 依序寫入區塊，每個區塊對齊 8 bytes，回傳區塊位置
+struct BlockWriter {
+    bytes: Vec<u8>,
+}
+
+impl BlockWriter {
+    fn block(&mut self, id: &[u8; 2], links: &[u64], data: &[u8]) -> u64 {
+        let offset = self.bytes.len() as u64;
+        let length = BLOCK_HEADER_SIZE + links.len() * 8 + data.len();
+        self.bytes.extend_from_slice(b"##");
+        self.bytes.extend_from_slice(id);
+        self.bytes.extend_from_slice(&[0; 4]);
+        self.bytes.extend_from_slice(&(length as u64).to_le_bytes());
+        self.bytes
+            .extend_from_slice(&(links.len() as u64).to_le_bytes());
+        for link in links {
+            self.bytes.extend_from_slice(&link.to_le_bytes());
+        }
+        self.bytes.extend_from_slice(data);
+        self.bytes.resize(self.bytes.len().next_multiple_of(8), 0);
+        offset
+    }
+
+    /// TX 或 MD 區塊，文字以 NUL 結尾
+    fn text(&mut self, id: &[u8; 2], text: &str) -> u64 {
+        let mut data = text.as_bytes().to_vec();
+        data.push(0);
+        self.block(id, &[], &data)
+    }
+
+    fn channel(&mut self, next: u64, name: &str, unit: &str, master: bool) -> u64 {
+        let name = self.text(b"TX", name);
+        let unit = if unit.is_empty() {
+            0
+        } else {
+            self.text(b"TX", unit)
+        };
+        let mut data = Vec::with_capacity(72);
+        if master {
+            data.extend_from_slice(&[CN_TYPE_MASTER, CN_SYNC_TIME]);
+        } else {
+            data.extend_from_slice(&[CN_TYPE_VALUE, CN_SYNC_NONE]);
+        }
+        data.push(CN_DATA_FLOAT_LE);
+        data.push(0);
+        let byte_offset: u32 = if master { 0 } else { 8 };
+        data.extend_from_slice(&byte_offset.to_le_bytes());
+        data.extend_from_slice(&64u32.to_le_bytes());
+        // flags、invalidation bit、precision、attachment 數皆為 0
+        data.extend_from_slice(&[0; 12]);
+        // 數值與界限範圍未設定
+        data.extend_from_slice(&[0; 48]);
+        // next, composition, name, source, conversion, data, unit, comment
+        self.block(b"CN", &[next, 0, name, 0, 0, 0, unit, 0], &data)
+    }
+}
+
+/// 將訊號寫成 ASAM MDF 4.10 檔，回傳寫入的訊號數；
+/// 每個訊號各自一個 data group（時間與數值皆為 f64），時間以最早的樣本為起點
+pub fn export_mf4(
+    path: &str,
+    series: &[SignalSeries],
+    session: &SessionInfo,
+) -> Result<usize, String> {
+    if series.is_empty() {
+        return Err("No decoded signals to export; load a YAML config or DBC first".to_string());
+    }
+    let start_us = series
+        .iter()
+        .filter_map(|s| s.samples.iter().map(|&(t, _)| t).min())
+        .min()
+        .unwrap_or(0);
+
+    let mut writer = BlockWriter {
+        bytes: Vec::with_capacity(ID_BLOCK_SIZE + HD_SIZE),
+    };
+    writer.bytes.extend_from_slice(b"MDF     4.10    can_tool");
+    writer.bytes.extend_from_slice(&[0; 4]);
+    writer.bytes.extend_from_slice(&410u16.to_le_bytes());
+    writer.bytes.resize(ID_BLOCK_SIZE, 0);
+    // HD 必須緊接在 ID 之後，先保留空間，寫完其他區塊再回填
+    writer.bytes.resize(ID_BLOCK_SIZE + HD_SIZE, 0);
+
+    let tool = format!(
+        "<tool_id>can_tool</tool_id><tool_vendor>can_tool</tool_vendor><tool_version>{}</tool_version>",
+        env!("CARGO_PKG_VERSION")
+    );
+    let fh_comment = writer.text(
+        b"MD",
+        &format!(
+            "<FHcomment><TX>Decoded signals export</TX>{}</FHcomment>",
+            tool
+        ),
+    );
+    let mut fh_data = Vec::with_capacity(16);
+    fh_data.extend_from_slice(&(now_micros() * 1000).to_le_bytes());
+    fh_data.resize(16, 0);
+    let file_history = writer.block(b"FH", &[0, fh_comment], &fh_data);
+    let hd_comment = match session.header_lines() {
+        lines if lines.is_empty() => 0,
+        lines => writer.text(
+            b"MD",
+            &format!(
+                "<HDcomment><TX>{}</TX></HDcomment>",
+                xml_escape(&lines.join("\n"))
+            ),
+        ),
+    };
+
+    // 由最後一個訊號往前寫，讓每個 data group 可以直接連到下一個
+    let mut next_group = 0;
+    for signal in series.iter().rev() {
+        let mut records = Vec::with_capacity(signal.samples.len() * RECORD_SIZE);
+        for &(time, value) in &signal.samples {
+            let seconds = time.saturating_sub(start_us) as f64 / 1_000_000.0;
+            records.extend_from_slice(&seconds.to_le_bytes());
+            records.extend_from_slice(&value.to_le_bytes());
+        }
+        let data = writer.block(b"DT", &[], &records);
+        let value = writer.channel(0, &signal.name, &signal.unit, false);
+        let time = writer.channel(value, "t", "s", true);
+        let mut cg_data = Vec::with_capacity(32);
+        cg_data.extend_from_slice(&0u64.to_le_bytes());
+        cg_data.extend_from_slice(&(signal.samples.len() as u64).to_le_bytes());
+        // flags、path separator 與保留欄位
+        cg_data.extend_from_slice(&[0; 8]);
+        cg_data.extend_from_slice(&(RECORD_SIZE as u32).to_le_bytes());
+        cg_data.extend_from_slice(&0u32.to_le_bytes());
+        let acquisition = writer.text(b"TX", &signal.name);
+        let channel_group = writer.block(b"CG", &[0, time, acquisition, 0, 0, 0], &cg_data);
+        next_group = writer.block(b"DG", &[next_group, channel_group, data, 0], &[0; 8]);
+    }
+
+    let mut header = Vec::with_capacity(HD_SIZE);
+    header.extend_from_slice(b"##HD");
+    header.extend_from_slice(&[0; 4]);
+    header.extend_from_slice(&(HD_SIZE as u64).to_le_bytes());
+    header.extend_from_slice(&(HD_LINKS as u64).to_le_bytes());
+    for link in [next_group, file_history, 0, 0, 0, hd_comment] {
+        header.extend_from_slice(&link.to_le_bytes());
+    }
+    // 起始時間為 UTC 奈秒，時區與角度、距離欄位不使用
+    header.extend_from_slice(&(start_us * 1000).to_le_bytes());
+    header.resize(HD_SIZE, 0);
+    writer.bytes[ID_BLOCK_SIZE..ID_BLOCK_SIZE + HD_SIZE].copy_from_slice(&header);
+
+    fs::write(path, &writer.bytes).map_err(|e| format!("Failed to write {}: {}", path, e))?;
+    Ok(series.len())
+}
+
+fn u64_at(bytes: &[u8], pos: usize) -> Option<u64> {
+    Some(u64::from_le_bytes(
+        bytes.get(pos..pos + 8)?.try_into().ok()?,
+    ))
+}
+
+fn f64_at(bytes: &[u8], pos: usize) -> Option<f64> {
+    u64_at(bytes, pos).map(f64::from_bits)
+}
+
+/// 讀取區塊，回傳（連結, 資料）；位置、id 不符或連結少於 `min_links` 時回傳 None
+fn read_block<'a>(
+    bytes: &'a [u8],
+    offset: u64,
+    id: &[u8; 2],
+    min_links: usize,
+) -> Option<(Vec<u64>, &'a [u8])> {
+    let pos = usize::try_from(offset).ok()?;
+    if bytes.get(pos..pos + 4)? != [b'#', b'#', id[0], id[1]] {
+        return None;
+    }
+    let length = usize::try_from(u64_at(bytes, pos + 8)?).ok()?;
+    let link_count = usize::try_from(u64_at(bytes, pos + 16)?).ok()?;
+    if link_count < min_links {
+        return None;
+    }
+    let data_start = pos + BLOCK_HEADER_SIZE + link_count * 8;
+    let links = (0..link_count)
+        .map(|i| u64_at(bytes, pos + BLOCK_HEADER_SIZE + i * 8))
+        .collect::<Option<Vec<_>>>()?;
+    Some((links, bytes.get(data_start..pos + length)?))
+}
+
+fn read_text(bytes: &[u8], offset: u64) -> Option<String> {
+    if offset == 0 {
+        return Some(String::new());
+    }
+    let (_, data) = read_block(bytes, offset, b"TX", 0)?;
+    let end = data.iter().position(|&b| b == 0).unwrap_or(data.len());
+    Some(String::from_utf8_lossy(&data[..end]).into_owned())
+}
+
+/// 讀取 `export_mf4` 寫出的 MDF 4 檔（每個 data group 一個時間通道與一個 f64 數值通道）
+pub fn read_mf4(path: &str) -> Result<Vec<SignalSeries>, String> {
+    let bytes = fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let invalid = || format!("{}: not a supported MDF 4 file", path);
+    if !bytes.starts_with(b"MDF     4.") {
+        return Err(invalid());
+    }
+    let (hd_links, hd_data) =
+        read_block(&bytes, ID_BLOCK_SIZE as u64, b"HD", HD_LINKS).ok_or_else(invalid)?;
+    let start_us = u64_at(hd_data, 0).ok_or_else(invalid)? / 1000;
+    let mut series = Vec::new();
+    let mut group = hd_links[0];
+    while group != 0 {
+        let (dg_links, _) = read_block(&bytes, group, b"DG", 4).ok_or_else(invalid)?;
+        let (cg_links, cg_data) = read_block(&bytes, dg_links[1], b"CG", 6).ok_or_else(invalid)?;
+        let cycles = u64_at(cg_data, 8).ok_or_else(invalid)? as usize;
+        let (time_links, _) = read_block(&bytes, cg_links[1], b"CN", 8).ok_or_else(invalid)?;
+        let (value_links, _) = read_block(&bytes, time_links[0], b"CN", 8).ok_or_else(invalid)?;
+        let (_, records) = read_block(&bytes, dg_links[2], b"DT", 0).ok_or_else(invalid)?;
+        if records.len() < cycles * RECORD_SIZE {
+            return Err(invalid());
+        }
+        let samples = records
+            .chunks_exact(RECORD_SIZE)
+            .take(cycles)
+            .filter_map(|record| {
+                let seconds = f64_at(record, 0)?;
+                Some((
+                    start_us + (seconds * 1_000_000.0).round() as u64,
+                    f64_at(record, 8)?,
+                ))
+            })
+            .collect();
+        series.push(SignalSeries {
+            name: read_text(&bytes, value_links[2]).ok_or_else(invalid)?,
+            unit: read_text(&bytes, value_links[6]).ok_or_else(invalid)?,
+            samples,
+        });
+        group = dg_links[0];
+    }
+    Ok(series)
+}
@@ -1,3 +1,60 @@
+pub mod alarm;
+pub mod autoresponse;
+pub mod bit_timing;
+pub mod blackbox;
+pub mod blf;
+pub mod broadcast;
+pub mod bus_state;
 pub mod canbus;
+pub mod canopen;
 pub mod cantypes;
+pub mod capture;
+pub mod capture_db;
 pub mod config;
+pub mod conformance;
+pub mod csv_schedule;
+pub mod dbc;
+pub mod eds;
+pub mod events;
+pub mod filter;
+pub mod gateway;
+pub mod gsusb;
+pub mod id_filter;
+pub mod j1939;
+pub mod j2534;
+pub mod latency;
+pub mod log_event;
+pub mod log_index;
+pub mod logfile;
+pub mod mdf;
+pub mod monitor;
+pub mod nmea2000;
+pub mod obd;
+pub mod plot;
+pub mod presets;
+pub mod receive_list;
+pub mod reconnect;
+pub mod remote;
+pub mod replay;
+pub mod sampler;
+pub mod scheduler;
+pub mod sdo;
+pub mod search;
+pub mod selftest;
+pub mod sequence;
+pub mod session;
+pub mod signals;
+pub mod silence;
+pub mod sim;
+pub mod slcan;
+pub mod snapshot;
+pub mod stats;
+pub mod timeline;
+pub mod timesync;
+pub mod trigger;
+pub mod txmacro;
+pub mod txsequence;
+pub mod units;
+pub mod version;
+pub mod watch;
+pub mod wire;
@@ -0,0 +1,202 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::config::{CounterConfig, HeartbeatConfig, ThresholdConfig, WatchConfig};
+use crate::can::events::{EventCategory, EventLog, Severity};
+use crate::can::signals::SignalTable;
+use crate::can::watch::{WatchExpr, WatchState, WatchTransition};
+
+struct HeartbeatState {
+    config: HeartbeatConfig,
+    last_seen: Option<u64>,
+    lost: bool,
+}
+
+struct CounterState {
+    config: CounterConfig,
+    last: Option<u8>,
+}
+
+struct ThresholdState {
+    config: ThresholdConfig,
+    violated: bool,
+}
+
+/// 監看心跳訊框、滾動計數器與訊號門檻，異常時寫入事件紀錄
+#[derive(Default)]
+pub struct BusMonitor {
+    heartbeats: Vec<HeartbeatState>,
+    counters: Vec<CounterState>,
+    thresholds: Vec<ThresholdState>,
+    watches: Vec<(WatchConfig, WatchState)>,
+}
+
+impl BusMonitor {
+    /// 以 YAML 設定取代目前的監看項目
+    pub fn load(
+        &mut self,
+        heartbeats: &[HeartbeatConfig],
+        counters: &[CounterConfig],
+        thresholds: &[ThresholdConfig],
+        watches: &[WatchConfig],
+    ) {
+        self.heartbeats = heartbeats
+            .iter()
+            .map(|config| HeartbeatState {
+                config: config.clone(),
+                last_seen: None,
+                lost: false,
+            })
+            .collect();
+        self.counters = counters
+            .iter()
+            .map(|config| CounterState {
+                config: config.clone(),
+                last: None,
+            })
+            .collect();
+        self.thresholds = thresholds
+            .iter()
+            .map(|config| ThresholdState {
+                config: config.clone(),
+                violated: false,
+            })
+            .collect();
+        // 運算式已在 `Config::validate` 檢查過，解析失敗的項目直接略過
+        self.watches = watches
+            .iter()
+            .filter_map(|config| {
+                let expr = WatchExpr::parse(&config.expr).ok()?;
+                Some((config.clone(), WatchState::new(expr, config.hysteresis)))
+            })
+            .collect();
+    }
+
+    /// 處理一筆收到的訊框
+    pub fn process(&mut self, frame: &CanFrame, events: &mut EventLog) {
+        for hb in self
+            .heartbeats
+            .iter_mut()
+            .filter(|hb| hb.config.id == frame.id)
+        {
+            if hb.lost {
+                events.push(
+                    frame.timestamp,
+                    Severity::Info,
+                    EventCategory::Heartbeat,
+                    format!("Heartbeat 0x{:X} restored", frame.id),
+                );
+                hb.lost = false;
+            }
+            hb.last_seen = Some(frame.timestamp);
+        }
+
+        for counter in self.counters.iter_mut().filter(|c| c.config.id == frame.id) {
+            let Some(&byte) = frame.payload().get(counter.config.index as usize) else {
+                continue;
+            };
+            let mask = counter.config.mask;
+            if mask == 0 {
+                continue;
+            }
+            let shift = mask.trailing_zeros();
+            let value = (byte & mask) >> shift;
+            let modulo = (mask >> shift) as u16 + 1;
+            if let Some(last) = counter.last {
+                let expected = ((last as u16 + 1) % modulo) as u8;
+                if value != expected {
+                    events.push(
+                        frame.timestamp,
+                        Severity::Warning,
+                        EventCategory::CounterGap,
+                        format!(
+                            "Counter gap on 0x{:X}: expected {}, got {}",
+                            frame.id, expected, value
+                        ),
+                    );
+                }
+            }
+            counter.last = Some(value);
+        }
+    }
+
+    /// 定期檢查心跳是否逾時
+    pub fn poll(&mut self, now: u64, events: &mut EventLog) {
+        for hb in self.heartbeats.iter_mut() {
+            let Some(last_seen) = hb.last_seen else {
+                continue;
+            };
+            if !hb.lost && now.saturating_sub(last_seen) > hb.config.timeout_ms * 1000 {
+                hb.lost = true;
+                events.push(
+                    now,
+                    Severity::Alarm,
+                    EventCategory::Heartbeat,
+                    format!(
+                        "Heartbeat 0x{:X} lost (no frame for {} ms)",
+                        hb.config.id, hb.config.timeout_ms
+                    ),
+                );
+            }
+        }
+    }
+
+    /// 檢查訊號是否超出門檻，僅在進入與離開超限狀態時各記錄一次
+    pub fn check_thresholds(&mut self, signals: &SignalTable, now: u64, events: &mut EventLog) {
+        for th in self.thresholds.iter_mut() {
+            let Some(value) = signals.get(&th.config.key).and_then(|s| s.value) else {
+                continue;
+            };
+            let below = th.config.min.is_some_and(|min| value < min);
+            let above = th.config.max.is_some_and(|max| value > max);
+            let violated = below || above;
+            if violated == th.violated {
+                continue;
+            }
+            th.violated = violated;
+            if violated {
+                events.push(
+                    now,
+                    Severity::Alarm,
+                    EventCategory::Threshold,
+                    format!(
+                        "{} = {} {} limit {}",
+                        th.config.key,
+                        value,
+                        if below { "below" } else { "above" },
+                        if below { th.config.min } else { th.config.max }.unwrap_or_default()
+                    ),
+                );
+            } else {
+                events.push(
+                    now,
+                    Severity::Info,
+                    EventCategory::Threshold,
+                    format!("{} = {} back within limits", th.config.key, value),
+                );
+            }
+        }
+    }
+
+    /// 更新監看運算式，僅在警報觸發與解除時各記錄一次
+    pub fn check_watches(&mut self, signals: &SignalTable, now: u64, events: &mut EventLog) {
+        for (config, state) in self.watches.iter_mut() {
+            let Some(transition) = state.update(signals, now) else {
+                continue;
+            };
+            let name = config.message.as_deref().unwrap_or(&config.expr);
+            match transition {
+                WatchTransition::Raised => events.push(
+                    now,
+                    Severity::Alarm,
+                    EventCategory::Watch,
+                    format!("{} (watch raised)", name),
+                ),
+                WatchTransition::Cleared => events.push(
+                    now,
+                    Severity::Info,
+                    EventCategory::Watch,
+                    format!("{} (watch cleared)", name),
+                ),
+            }
+        }
+    }
+}
@@ -0,0 +1,448 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::j1939::J1939Id;
+use std::collections::HashMap;
+use std::f64::consts::PI;
+
+/// Fast packet 的最大長度（第一個封包 6 bytes + 31 個後續封包 × 7 bytes）
+const FAST_PACKET_MAX_SIZE: usize = 223;
+/// 兩個封包的最長間隔，逾時的訊息會被丟棄
+const FAST_PACKET_TIMEOUT_US: u64 = 750_000;
+
+/// 1e-4 rad 換算為度
+const ANGLE: f64 = 1e-4 * 180.0 / PI;
+/// 0.01 m/s 換算為節
+const SPEED: f64 = 0.01 * 3600.0 / 1852.0;
+const KELVIN: f64 = -273.15;
+
+/// 以 fast packet 傳送的 PGN；130816..=131071 為專屬 fast packet PGN
+const FAST_PACKET_PGNS: &[u32] = &[
+    126208, 126464, 126720, 126996, 126998, 127233, 127237, 127489, 127496, 127497, 127498, 127503,
+    127504, 127506, 127507, 127509, 127510, 127511, 127512, 127513, 127514, 128275, 128520, 129029,
+    129038, 129039, 129040, 129041, 129044, 129045, 129284, 129285, 129301, 129302, 129538, 129540,
+    129541, 129542, 129545, 129547, 129549, 129551, 129556, 129792, 129793, 129794, 129795, 129796,
+    129797, 129798, 129799, 129800, 129801, 129802, 129803, 129804, 129805, 129806, 129807, 129808,
+    129809, 129810, 130074, 130320, 130321, 130322, 130323, 130324, 130567, 130577, 130578,
+];
+
+pub fn is_fast_packet_pgn(pgn: u32) -> bool {
+    FAST_PACKET_PGNS.contains(&pgn) || (130816..=131071).contains(&pgn)
+}
+
+/// 常見 PGN 的名稱（NMEA 2000 慣例以十進位表示 PGN）
+pub fn pgn_name(pgn: u32) -> Option<&'static str> {
+    Some(match pgn {
+        59392 => "ISO Acknowledgement",
+        59904 => "ISO Request",
+        60160 => "ISO Transport Protocol, Data Transfer",
+        60416 => "ISO Transport Protocol, Connection Management",
+        60928 => "ISO Address Claim",
+        126208 => "Group Function",
+        126464 => "PGN List",
+        126992 => "System Time",
+        126993 => "Heartbeat",
+        126996 => "Product Information",
+        126998 => "Configuration Information",
+        127233 => "Man Overboard Notification",
+        127245 => "Rudder",
+        127250 => "Vessel Heading",
+        127251 => "Rate of Turn",
+        127257 => "Attitude",
+        127258 => "Magnetic Variation",
+        127488 => "Engine Parameters, Rapid Update",
+        127489 => "Engine Parameters, Dynamic",
+        127493 => "Transmission Parameters, Dynamic",
+        127505 => "Fluid Level",
+        127508 => "Battery Status",
+        128259 => "Speed",
+        128267 => "Water Depth",
+        128275 => "Distance Log",
+        129025 => "Position, Rapid Update",
+        129026 => "COG & SOG, Rapid Update",
+        129029 => "GNSS Position Data",
+        129038 => "AIS Class A Position Report",
+        129039 => "AIS Class B Position Report",
+        129283 => "Cross Track Error",
+        129284 => "Navigation Data",
+        129539 => "GNSS DOPs",
+        129540 => "GNSS Satellites in View",
+        129794 => "AIS Class A Static and Voyage Related Data",
+        129809 => "AIS Class B Static Data, Part A",
+        129810 => "AIS Class B Static Data, Part B",
+        130306 => "Wind Data",
+        130310 => "Environmental Parameters",
+        130311 => "Environmental Parameters",
+        130312 => "Temperature",
+        130313 => "Humidity",
+        130314 => "Actual Pressure",
+        130316 => "Temperature, Extended Range",
+        _ => return None,
+    })
+}
+
+/// PGN 資料中的欄位：位置（byte 由 0 起算，Intel 位元順序）與換算
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FieldDef {
+    pub pgn: u32,
+    pub name: &'static str,
+    pub byte: usize,
+    pub bit: u32,
+    pub length: u32,
+    pub signed: bool,
+    pub resolution: f64,
+    pub offset: f64,
+    pub unit: &'static str,
+}
+
+const fn unsigned(
+    pgn: u32,
+    name: &'static str,
+    (byte, bit, length): (usize, u32, u32),
+    resolution: f64,
+    offset: f64,
+    unit: &'static str,
+) -> FieldDef {
+    FieldDef {
+        pgn,
+        name,
+        byte,
+        bit,
+        length,
+        signed: false,
+        resolution,
+        offset,
+        unit,
+    }
+}
+
+const fn signed(
+    pgn: u32,
+    name: &'static str,
+    (byte, bit, length): (usize, u32, u32),
+    resolution: f64,
+    offset: f64,
+    unit: &'static str,
+) -> FieldDef {
+    FieldDef {
+        signed: true,
+        ..unsigned(pgn, name, (byte, bit, length), resolution, offset, unit)
+    }
+}
+
+/// 內建的標準 PGN 欄位；角度換算為度、速度為節、溫度為攝氏
+pub const FIELDS: &[FieldDef] = &[
+    unsigned(126992, "Date", (2, 0, 16), 1.0, 0.0, "days"),
+    unsigned(126992, "Time", (4, 0, 32), 1e-4, 0.0, "s"),
+    unsigned(127250, "Heading", (1, 0, 16), ANGLE, 0.0, "deg"),
+    signed(127250, "Deviation", (3, 0, 16), ANGLE, 0.0, "deg"),
+    signed(127250, "Variation", (5, 0, 16), ANGLE, 0.0, "deg"),
+    signed(
+        127251,
+        "Rate",
+        (1, 0, 32),
+        3.125e-8 * 180.0 / PI * 60.0,
+        0.0,
+        "deg/min",
+    ),
+    signed(127257, "Yaw", (1, 0, 16), ANGLE, 0.0, "deg"),
+    signed(127257, "Pitch", (3, 0, 16), ANGLE, 0.0, "deg"),
+    signed(127257, "Roll", (5, 0, 16), ANGLE, 0.0, "deg"),
+    unsigned(127488, "Instance", (0, 0, 8), 1.0, 0.0, ""),
+    unsigned(127488, "Speed", (1, 0, 16), 0.25, 0.0, "rpm"),
+    unsigned(127488, "Boost Pressure", (3, 0, 16), 0.1, 0.0, "kPa"),
+    signed(127488, "Tilt/Trim", (5, 0, 8), 1.0, 0.0, "%"),
+    unsigned(127489, "Instance", (0, 0, 8), 1.0, 0.0, ""),
+    unsigned(127489, "Oil Pressure", (1, 0, 16), 0.1, 0.0, "kPa"),
+    unsigned(127489, "Oil Temperature", (3, 0, 16), 0.1, KELVIN, "degC"),
+    unsigned(127489, "Temperature", (5, 0, 16), 0.01, KELVIN, "degC"),
+    signed(127489, "Alternator Potential", (7, 0, 16), 0.01, 0.0, "V"),
+    signed(127489, "Fuel Rate", (9, 0, 16), 0.1, 0.0, "L/h"),
+    unsigned(
+        127489,
+        "Total Engine Hours",
+        (11, 0, 32),
+        1.0 / 3600.0,
+        0.0,
+        "h",
+    ),
+    unsigned(127505, "Instance", (0, 0, 4), 1.0, 0.0, ""),
+    unsigned(127505, "Type", (0, 4, 4), 1.0, 0.0, ""),
+    signed(127505, "Level", (1, 0, 16), 0.004, 0.0, "%"),
+    unsigned(127505, "Capacity", (3, 0, 32), 0.1, 0.0, "L"),
+    unsigned(127508, "Instance", (0, 0, 8), 1.0, 0.0, ""),
+    signed(127508, "Voltage", (1, 0, 16), 0.01, 0.0, "V"),
+    signed(127508, "Current", (3, 0, 16), 0.1, 0.0, "A"),
+    unsigned(127508, "Temperature", (5, 0, 16), 0.01, KELVIN, "degC"),
+    unsigned(
+        128259,
+        "Speed Water Referenced",
+        (1, 0, 16),
+        SPEED,
+        0.0,
+        "kn",
+    ),
+    unsigned(
+        128259,
+        "Speed Ground Referenced",
+        (3, 0, 16),
+        SPEED,
+        0.0,
+        "kn",
+    ),
+    unsigned(128267, "Depth", (1, 0, 32), 0.01, 0.0, "m"),
+    signed(128267, "Offset", (5, 0, 16), 0.001, 0.0, "m"),
+    signed(129025, "Latitude", (0, 0, 32), 1e-7, 0.0, "deg"),
+    signed(129025, "Longitude", (4, 0, 32), 1e-7, 0.0, "deg"),
+    unsigned(129026, "COG", (2, 0, 16), ANGLE, 0.0, "deg"),
+    unsigned(129026, "SOG", (4, 0, 16), SPEED, 0.0, "kn"),
+    unsigned(129029, "Date", (1, 0, 16), 1.0, 0.0, "days"),
+    unsigned(129029, "Time", (3, 0, 32), 1e-4, 0.0, "s"),
+    signed(129029, "Latitude", (7, 0, 64), 1e-16, 0.0, "deg"),
+    signed(129029, "Longitude", (15, 0, 64), 1e-16, 0.0, "deg"),
+    signed(129029, "Altitude", (23, 0, 64), 1e-6, 0.0, "m"),
+    unsigned(130306, "Wind Speed", (1, 0, 16), SPEED, 0.0, "kn"),
+    unsigned(130306, "Wind Angle", (3, 0, 16), ANGLE, 0.0, "deg"),
+    unsigned(130306, "Reference", (5, 0, 3), 1.0, 0.0, ""),
+    unsigned(
+        130310,
+        "Water Temperature",
+        (1, 0, 16),
+        0.01,
+        KELVIN,
+        "degC",
+    ),
+    unsigned(
+        130310,
+        "Outside Air Temperature",
+        (3, 0, 16),
+        0.01,
+        KELVIN,
+        "degC",
+    ),
+    unsigned(130310, "Atmospheric Pressure", (5, 0, 16), 1.0, 0.0, "hPa"),
+    unsigned(130312, "Instance", (1, 0, 8), 1.0, 0.0, ""),
+    unsigned(130312, "Source", (2, 0, 8), 1.0, 0.0, ""),
+    unsigned(
+        130312,
+        "Actual Temperature",
+        (3, 0, 16),
+        0.01,
+        KELVIN,
+        "degC",
+    ),
+    unsigned(130312, "Set Temperature", (5, 0, 16), 0.01, KELVIN, "degC"),
+];
+
+impl FieldDef {
+    /// 解碼物理值；資料不足或原始值為「無資料」（全為 1，有號數為最大正值）
+    /// 與「超出範圍」（前者減 1，8 bits 以上才有）時為 None
+    pub fn decode(&self, data: &[u8]) -> Option<f64> {
+        let end_bit = self.byte as u32 * 8 + self.bit + self.length;
+        if self.length == 0 || self.length > 64 || end_bit.div_ceil(8) as usize > data.len() {
+            return None;
+        }
+        let raw = data[self.byte..end_bit.div_ceil(8) as usize]
+            .iter()
+            .rev()
+            .fold(0u128, |acc, &b| (acc << 8) | b as u128);
+        let raw = ((raw >> self.bit) & ((1u128 << self.length) - 1)) as u64;
+        let reserved = if self.length >= 8 { 2 } else { 1 };
+        let value = if self.signed {
+            let max = (1u64 << (self.length - 1)) - 1;
+            if raw <= max && raw > max - reserved {
+                return None;
+            }
+            // 符號延伸
+            let shift = 64 - self.length;
+            ((raw << shift) as i64 >> shift) as f64
+        } else {
+            let max = if self.length == 64 {
+                u64::MAX
+            } else {
+                (1u64 << self.length) - 1
+            };
+            if raw > max - reserved {
+                return None;
+            }
+            raw as f64
+        };
+        Some(value * self.resolution + self.offset)
+    }
+}
+
+/// 解碼 PGN 資料中已知的欄位，回傳（定義, 物理值）
+pub fn decode_fields(pgn: u32, data: &[u8]) -> Vec<(&'static FieldDef, f64)> {
+    FIELDS
+        .iter()
+        .filter(|def| def.pgn == pgn)
+        .filter_map(|def| def.decode(data).map(|value| (def, value)))
+        .collect()
+}
+
+/// NMEA 2000 訊息（單一訊框或重組完成的 fast packet）
+#[derive(Debug, Clone, PartialEq)]
+pub struct Nmea2000Message {
+    pub channel: u32,
+    pub pgn: u32,
+    pub priority: u8,
+    pub source: u8,
+    pub destination: Option<u8>,
+    pub data: Vec<u8>,
+    /// 最後一個封包的時間
+    pub timestamp: u64,
+}
+
+struct FastPacketSession {
+    sequence: u8,
+    size: usize,
+    next_frame: u8,
+    data: Vec<u8>,
+    last_us: u64,
+}
+
+/// 依序餵入訊框，重組以 fast packet 傳送的訊息
+#[derive(Default)]
+pub struct FastPacketReassembler {
+    /// 以（通道, 來源位址, PGN）區分同時進行的傳送
+    sessions: HashMap<(u32, u8, u32), FastPacketSession>,
+}
+
+impl FastPacketReassembler {
+    /// 處理一筆訊框，完成一則訊息時回傳；非 fast packet 訊框回傳 None
+    pub fn process(&mut self, frame: &CanFrame) -> Option<Nmea2000Message> {
+        if !is_fast_packet(frame) {
+            return None;
+        }
+        let id = J1939Id::from_can_id(frame.id);
+        let key = (frame.channel, id.source, id.pgn);
+        let data = frame.payload();
+        let sequence = *data.first()? >> 5;
+        let counter = data[0] & 0x1F;
+        if counter == 0 {
+            // 第一個封包：byte 1 為總長度，之後為 6 bytes 資料
+            let size = *data.get(1)? as usize;
+            if size > FAST_PACKET_MAX_SIZE {
+                return None;
+            }
+            self.sessions.insert(
+                key,
+                FastPacketSession {
+                    sequence,
+                    size,
+                    next_frame: 1,
+                    data: data[2..].to_vec(),
+                    last_us: frame.timestamp,
+                },
+            );
+        } else {
+            let session = self.sessions.get_mut(&key)?;
+            let expired = frame.timestamp.saturating_sub(session.last_us) > FAST_PACKET_TIMEOUT_US;
+            if expired || sequence != session.sequence || counter != session.next_frame {
+                // 漏掉封包的訊息無法完成
+                self.sessions.remove(&key);
+                return None;
+            }
+            session.data.extend_from_slice(&data[1..]);
+            session.next_frame += 1;
+            session.last_us = frame.timestamp;
+        }
+        let session = self.sessions.get(&key)?;
+        if session.data.len() < session.size {
+            return None;
+        }
+        let mut session = self.sessions.remove(&key)?;
+        session.data.truncate(session.size);
+        Some(Nmea2000Message {
+            channel: frame.channel,
+            pgn: id.pgn,
+            priority: id.priority,
+            source: id.source,
+            destination: id.destination,
+            data: session.data,
+            timestamp: frame.timestamp,
+        })
+    }
+}
+
+/// 是否為 fast packet PGN 的訊框
+pub fn is_fast_packet(frame: &CanFrame) -> bool {
+    frame.extended && !frame.rtr && is_fast_packet_pgn(J1939Id::from_can_id(frame.id).pgn)
+}
+
+fn describe_pgn(pgn: u32) -> String {
+    match pgn_name(pgn) {
+        Some(name) => format!("PGN={} ({})", pgn, name),
+        None => format!("PGN={}", pgn),
+    }
+}
+
+/// 物理值最多顯示 6 位小數
+fn format_value(value: f64) -> String {
+    let text = format!("{:.6}", value);
+    text.trim_end_matches('0').trim_end_matches('.').to_string()
+}
+
+fn describe_fields(pgn: u32, data: &[u8]) -> String {
+    decode_fields(pgn, data)
+        .iter()
+        .map(|(def, value)| {
+            format!(" {}={} {}", def.name, format_value(*value), def.unit)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// 追蹤畫面附加的 NMEA 2000 說明，例如 `P=2 PGN=127250 (Vessel Heading) SA=0x01 | Heading=90 deg`；
+/// fast packet 的單一封包只顯示封包序號，11-bit 訊框回傳 None
+pub fn describe_frame(frame: &CanFrame) -> Option<String> {
+    if !frame.extended {
+        return None;
+    }
+    let id = J1939Id::from_can_id(frame.id);
+    let mut text = format!(
+        "P={} {} SA=0x{:02X}",
+        id.priority,
+        describe_pgn(id.pgn),
+        id.source
+    );
+    if let Some(destination) = id.destination {
+        text.push_str(&format!(" DA=0x{:02X}", destination));
+    }
+    let data = frame.payload();
+    if is_fast_packet(frame) {
+        if let Some(&header) = data.first() {
+            text.push_str(&format!(
+                " | fast packet seq {} frame {}",
+                header >> 5,
+                header & 0x1F
+            ));
+        }
+        return Some(text);
+    }
+    let fields = describe_fields(id.pgn, data);
+    if !fields.is_empty() {
+        text.push_str(" |");
+        text.push_str(&fields);
+    }
+    Some(text)
+}
+
+/// 重組訊息的顯示文字
+pub fn describe_message(message: &Nmea2000Message) -> String {
+    let mut text = format!(
+        "[NMEA2000] CH={} P={} {} SA=0x{:02X}, {} bytes, Data={:?}",
+        message.channel,
+        message.priority,
+        describe_pgn(message.pgn),
+        message.source,
+        message.data.len(),
+        message.data
+    );
+    let fields = describe_fields(message.pgn, &message.data);
+    if !fields.is_empty() {
+        text.push_str(" |");
+        text.push_str(&fields);
+    }
+    text
+}
@@ -0,0 +1,331 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use std::collections::BTreeMap;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+
+/// OBD-II 功能性請求（廣播給所有 ECU）的 ID
+pub const OBD_REQUEST_ID: u32 = 0x7DF;
+/// ECU 回應 ID 的範圍，0x7E8 為引擎 ECU
+pub const OBD_RESPONSE_IDS: std::ops::RangeInclusive<u32> = 0x7E8..=0x7EF;
+/// Mode 01：目前的數據
+const MODE_CURRENT_DATA: u8 = 0x01;
+/// 正面回應的 mode 為請求值加 0x40
+const POSITIVE_RESPONSE: u8 = 0x40;
+/// ISO 15765-2 未規定填充值，0x55 為常見的選擇
+const PADDING: u8 = 0x55;
+
+/// 標準 PID 的定義（SAE J1979），`decode` 的輸入為回應中 PID 之後的資料位元組
+#[derive(Debug, Clone, Copy)]
+pub struct PidDef {
+    pub pid: u8,
+    pub name: &'static str,
+    pub unit: &'static str,
+    pub bytes: usize,
+    pub decode: fn(&[u8]) -> f64,
+}
+
+fn word(data: &[u8]) -> f64 {
+    (data[0] as f64) * 256.0 + data[1] as f64
+}
+
+fn percent(data: &[u8]) -> f64 {
+    data[0] as f64 * 100.0 / 255.0
+}
+
+fn temperature(data: &[u8]) -> f64 {
+    data[0] as f64 - 40.0
+}
+
+pub const PIDS: &[PidDef] = &[
+    PidDef {
+        pid: 0x04,
+        name: "Engine Load",
+        unit: "%",
+        bytes: 1,
+        decode: percent,
+    },
+    PidDef {
+        pid: 0x05,
+        name: "Coolant Temperature",
+        unit: "degC",
+        bytes: 1,
+        decode: temperature,
+    },
+    PidDef {
+        pid: 0x0B,
+        name: "Intake Manifold Pressure",
+        unit: "kPa",
+        bytes: 1,
+        decode: |d| d[0] as f64,
+    },
+    PidDef {
+        pid: 0x0C,
+        name: "Engine Speed",
+        unit: "rpm",
+        bytes: 2,
+        decode: |d| word(d) / 4.0,
+    },
+    PidDef {
+        pid: 0x0D,
+        name: "Vehicle Speed",
+        unit: "km/h",
+        bytes: 1,
+        decode: |d| d[0] as f64,
+    },
+    PidDef {
+        pid: 0x0F,
+        name: "Intake Air Temperature",
+        unit: "degC",
+        bytes: 1,
+        decode: temperature,
+    },
+    PidDef {
+        pid: 0x10,
+        name: "MAF Air Flow Rate",
+        unit: "g/s",
+        bytes: 2,
+        decode: |d| word(d) / 100.0,
+    },
+    PidDef {
+        pid: 0x11,
+        name: "Throttle Position",
+        unit: "%",
+        bytes: 1,
+        decode: percent,
+    },
+    PidDef {
+        pid: 0x1F,
+        name: "Run Time Since Engine Start",
+        unit: "s",
+        bytes: 2,
+        decode: word,
+    },
+    PidDef {
+        pid: 0x2F,
+        name: "Fuel Tank Level",
+        unit: "%",
+        bytes: 1,
+        decode: percent,
+    },
+    PidDef {
+        pid: 0x42,
+        name: "Control Module Voltage",
+        unit: "V",
+        bytes: 2,
+        decode: |d| word(d) / 1000.0,
+    },
+    PidDef {
+        pid: 0x46,
+        name: "Ambient Air Temperature",
+        unit: "degC",
+        bytes: 1,
+        decode: temperature,
+    },
+    PidDef {
+        pid: 0x5C,
+        name: "Engine Oil Temperature",
+        unit: "degC",
+        bytes: 1,
+        decode: temperature,
+    },
+];
+
+pub fn pid_def(pid: u8) -> Option<&'static PidDef> {
+    PIDS.iter().find(|def| def.pid == pid)
+}
+
+/// Mode 01 的請求訊框（單一訊框，未用的位元組填充）
+pub fn request_frame(channel: u32, pid: u8) -> CanFrame {
+    let mut data = [PADDING; 8];
+    data[..3].copy_from_slice(&[0x02, MODE_CURRENT_DATA, pid]);
+    CanFrame::new(channel, OBD_REQUEST_ID, &data)
+}
+
+/// 解析 Mode 01 的回應，回傳（ECU 編號 0..=7, PID, 物理值）；
+/// 其他訊框或未知的 PID 回傳 None
+pub fn parse_response(frame: &CanFrame) -> Option<(u8, u8, f64)> {
+    if !OBD_RESPONSE_IDS.contains(&frame.id) || frame.extended {
+        return None;
+    }
+    let data = frame.payload();
+    // 單一訊框 PCI：高 4 bits 為 0，低 4 bits 為長度
+    let len = *data.first()? as usize;
+    if len > 7 || data.len() < len + 1 || len < 2 {
+        return None;
+    }
+    if data[1] != MODE_CURRENT_DATA + POSITIVE_RESPONSE {
+        return None;
+    }
+    let def = pid_def(data[2])?;
+    let values = data
+        .get(3..3 + def.bytes)
+        .filter(|_| len >= 2 + def.bytes)?;
+    Some((
+        (frame.id - OBD_RESPONSE_IDS.start()) as u8,
+        def.pid,
+        (def.decode)(values),
+    ))
+}
+
+/// PID 的最新值
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ObdValue {
+    pub value: f64,
+    /// 回應的 ECU（0x7E8 + ecu）
+    pub ecu: u8,
+    /// 最後更新時間（微秒）
+    pub timestamp: u64,
+    pub count: u64,
+}
+
+/// OBD-II 即時數據：要輪詢的 PID 與收到的回應
+#[derive(Debug)]
+pub struct ObdMonitor {
+    pids: Vec<u8>,
+    values: BTreeMap<u8, ObdValue>,
+    requests: u64,
+}
+
+impl Default for ObdMonitor {
+    fn default() -> Self {
+        Self {
+            pids: vec![0x0C, 0x0D, 0x05, 0x04, 0x11],
+            values: BTreeMap::new(),
+            requests: 0,
+        }
+    }
+}
+
+impl ObdMonitor {
+    /// 目前勾選的 PID，依輪詢順序
+    pub fn pids(&self) -> &[u8] {
+        &self.pids
+    }
+
+    pub fn is_selected(&self, pid: u8) -> bool {
+        self.pids.contains(&pid)
+    }
+
+    pub fn set_selected(&mut self, pid: u8, selected: bool) {
+        if selected && !self.is_selected(pid) {
+            self.pids.push(pid);
+        } else if !selected {
+            self.pids.retain(|&p| p != pid);
+        }
+    }
+
+    /// 處理收到的訊框，是 Mode 01 回應時更新數值並回傳 true
+    pub fn process(&mut self, frame: &CanFrame) -> bool {
+        let Some((ecu, pid, value)) = parse_response(frame) else {
+            return false;
+        };
+        let entry = self.values.entry(pid).or_insert(ObdValue {
+            value,
+            ecu,
+            timestamp: 0,
+            count: 0,
+        });
+        entry.value = value;
+        entry.ecu = ecu;
+        entry.timestamp = frame.timestamp;
+        entry.count += 1;
+        true
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = (&PidDef, &ObdValue)> {
+        self.values
+            .iter()
+            .filter_map(|(&pid, value)| pid_def(pid).map(|def| (def, value)))
+    }
+
+    pub fn value(&self, pid: u8) -> Option<&ObdValue> {
+        self.values.get(&pid)
+    }
+
+    pub fn requests(&self) -> u64 {
+        self.requests
+    }
+
+    pub fn clear(&mut self) {
+        self.values.clear();
+        self.requests = 0;
+    }
+}
+
+/// 依序輪詢 `ObdMonitor` 中勾選的 PID；回應由接收流程交給 `ObdMonitor::process`
+pub struct ObdPoller {
+    running: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ObdPoller {
+    /// 每隔 `interval` 送出下一個 PID 的請求，勾選的 PID 可在執行中修改
+    pub fn start<F>(
+        monitor: Arc<Mutex<ObdMonitor>>,
+        can_app: SharedCan,
+        channel: u32,
+        interval: Duration,
+        log: F,
+    ) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let running_flag = Arc::clone(&running);
+        let handle = thread::spawn(move || {
+            log(format!("OBD-II polling started on CH{}", channel));
+            let mut index = 0;
+            // 是否處於連續傳送失敗中（只記錄第一次錯誤）
+            let mut failing = false;
+            while running_flag.load(Ordering::SeqCst) {
+                let pid = {
+                    let monitor = monitor.lock().unwrap();
+                    let pids = monitor.pids();
+                    index = if index >= pids.len() { 0 } else { index };
+                    pids.get(index).copied()
+                };
+                if let Some(pid) = pid {
+                    match can_app.send_frame(&request_frame(channel, pid)) {
+                        Ok(()) => {
+                            failing = false;
+                            monitor.lock().unwrap().requests += 1;
+                        }
+                        Err(e) => {
+                            if !failing {
+                                log(format!("OBD-II request failed: {}", e));
+                            }
+                            failing = true;
+                        }
+                    }
+                    index += 1;
+                }
+                thread::sleep(interval);
+            }
+            log("OBD-II polling stopped".to_string());
+        });
+        Self {
+            running,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn stop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining OBD-II thread: {:?}", e);
+            }
+        }
+    }
+}
+
+impl Drop for ObdPoller {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,86 @@
+use crate::can::dbc::DbcDecoder;
+use crate::can::signals::SignalTable;
+use std::collections::VecDeque;
+
+/// 每條曲線保留的樣本數上限
+pub const MAX_PLOT_SAMPLES: usize = 100_000;
+
+/// 一條曲線：訊號 key 與收到的（時間, 值）
+#[derive(Debug, Clone)]
+pub struct PlotTrace {
+    pub key: String,
+    pub samples: VecDeque<(u64, f64)>,
+}
+
+/// 訊號目前的值與更新時間；YAML 訊號優先，其次為 `訊息.訊號` 形式的 DBC 訊號
+pub fn signal_value(signals: &SignalTable, dbc: &DbcDecoder, key: &str) -> Option<(f64, u64)> {
+    if let Some(state) = signals.get(key) {
+        return state.value.map(|value| (value, state.updated_at));
+    }
+    let (message, signal) = key.split_once('.')?;
+    dbc.value(message, signal).map(|d| (d.value, d.timestamp))
+}
+
+/// 即時曲線圖的資料：由接收執行緒在每筆訊框解碼後記錄選擇的訊號，
+/// 介面暫停時資料仍持續累積
+#[derive(Debug, Default)]
+pub struct SignalPlot {
+    traces: Vec<PlotTrace>,
+}
+
+impl SignalPlot {
+    /// 加入一條曲線，已存在時回傳 false
+    pub fn add(&mut self, key: &str) -> bool {
+        if self.traces.iter().any(|t| t.key == key) {
+            return false;
+        }
+        self.traces.push(PlotTrace {
+            key: key.to_string(),
+            samples: VecDeque::new(),
+        });
+        true
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.traces.retain(|t| t.key != key);
+    }
+
+    /// 以新的訊號清單取代目前的曲線，保留仍在清單中的資料
+    pub fn set_keys(&mut self, keys: &[String]) {
+        self.traces.retain(|t| keys.contains(&t.key));
+        for key in keys {
+            self.add(key);
+        }
+    }
+
+    pub fn keys(&self) -> Vec<String> {
+        self.traces.iter().map(|t| t.key.clone()).collect()
+    }
+
+    pub fn traces(&self) -> &[PlotTrace] {
+        &self.traces
+    }
+
+    /// 清除所有曲線的樣本
+    pub fn clear(&mut self) {
+        for trace in &mut self.traces {
+            trace.samples.clear();
+        }
+    }
+
+    /// 在訊框解碼後呼叫，記錄被這筆訊框更新的訊號
+    pub fn record(&mut self, signals: &SignalTable, dbc: &DbcDecoder, timestamp: u64) {
+        for trace in &mut self.traces {
+            let Some((value, updated)) = signal_value(signals, dbc, &trace.key) else {
+                continue;
+            };
+            if updated != timestamp || trace.samples.back().is_some_and(|&(t, _)| t == updated) {
+                continue;
+            }
+            if trace.samples.len() >= MAX_PLOT_SAMPLES {
+                trace.samples.pop_front();
+            }
+            trace.samples.push_back((updated, value));
+        }
+    }
+}
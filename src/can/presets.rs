@@ -0,0 +1,168 @@
+/// ControlCAN 支援的波特率（K）
+pub const CONTROL_CAN_BAUD_RATES: [u32; 17] = [
+    10, 20, 33, 40, 50, 66, 80, 83, 100, 125, 200, 250, 400, 500, 666, 800, 1000,
+];
+/// PCANBasic 支援的波特率（K）
+pub const PCAN_BAUD_RATES: [u32; 14] =
+    [5, 10, 20, 33, 47, 50, 83, 95, 100, 125, 250, 500, 800, 1000];
+/// ControlCAN 裝置類型代碼與名稱（VCI_OpenDevice 的 DevType）
+pub const CONTROL_CAN_DEV_TYPES: [(u32, &str); 5] = [
+    (3, "USBCAN-I"),
+    (4, "USBCAN-II / CANalyst-II"),
+    (20, "USBCAN-E-U"),
+    (21, "USBCAN-2E-U"),
+    (22, "USBCAN-4E-U"),
+];
+
+/// PCAN FD 常用位元率（仲裁段 / 資料段），以 80 MHz 時脈、取樣點 80% 計算
+pub const PCAN_FD_BITRATES: [(&str, &str); 4] = [
+    (
+        "500K / 2M",
+        "f_clock_mhz=80, nom_brp=2, nom_tseg1=63, nom_tseg2=16, nom_sjw=16, data_brp=2, data_tseg1=15, data_tseg2=4, data_sjw=4",
+    ),
+    (
+        "500K / 4M",
+        "f_clock_mhz=80, nom_brp=2, nom_tseg1=63, nom_tseg2=16, nom_sjw=16, data_brp=1, data_tseg1=15, data_tseg2=4, data_sjw=4",
+    ),
+    (
+        "1M / 5M",
+        "f_clock_mhz=80, nom_brp=1, nom_tseg1=63, nom_tseg2=16, nom_sjw=16, data_brp=1, data_tseg1=11, data_tseg2=4, data_sjw=4",
+    ),
+    (
+        "250K / 2M",
+        "f_clock_mhz=80, nom_brp=4, nom_tseg1=63, nom_tseg2=16, nom_sjw=16, data_brp=2, data_tseg1=15, data_tseg2=4, data_sjw=4",
+    ),
+];
+
+/// SLCAN `Sn` 指令支援的波特率（K）
+pub const SLCAN_BAUD_RATES: [u32; 9] = [10, 20, 50, 100, 125, 250, 500, 800, 1000];
+
+/// gs_usb 裝置常用的波特率（K），實際位元時序依裝置時脈計算
+pub const GS_USB_BAUD_RATES: [u32; 9] = [10, 20, 50, 100, 125, 250, 500, 800, 1000];
+
+/// J2534 PassThruConnect 常用的 CAN 波特率（K）
+pub const J2534_BAUD_RATES: [u32; 6] = [33, 83, 125, 250, 500, 1000];
+
+/// 轉接器使用的驅動函式庫
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AdapterBackend {
+    ControlCan,
+    Pcan,
+    /// 序列埠 SLCAN（Lawicel）協定，不需驅動函式庫
+    Slcan,
+    /// candleLight / gs_usb 韌體，經由 libusb 存取
+    GsUsb,
+}
+
+/// 常見轉接器的預設組態，選擇後預先填入設定面板
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AdapterPreset {
+    pub name: &'static str,
+    pub backend: AdapterBackend,
+    /// ControlCAN 的裝置類型（VCI_USBCAN2 = 4），PCAN 不使用
+    pub dev_type: u32,
+    pub channels: usize,
+    /// 支援的波特率（K）
+    pub baud_rates: &'static [u32],
+    pub default_baud: u32,
+    /// 需要放在程式目錄的驅動函式庫
+    pub dll: &'static str,
+    pub can_fd: bool,
+}
+
+pub const ADAPTER_PRESETS: &[AdapterPreset] = &[
+    AdapterPreset {
+        name: "USBCAN-I",
+        backend: AdapterBackend::ControlCan,
+        dev_type: 3,
+        channels: 1,
+        baud_rates: &CONTROL_CAN_BAUD_RATES,
+        default_baud: 500,
+        dll: "ControlCAN.dll",
+        can_fd: false,
+    },
+    AdapterPreset {
+        name: "USBCAN-II",
+        backend: AdapterBackend::ControlCan,
+        dev_type: 4,
+        channels: 2,
+        baud_rates: &CONTROL_CAN_BAUD_RATES,
+        default_baud: 500,
+        dll: "ControlCAN.dll",
+        can_fd: false,
+    },
+    AdapterPreset {
+        name: "USBCAN-2E-U",
+        backend: AdapterBackend::ControlCan,
+        dev_type: 21,
+        channels: 2,
+        baud_rates: &CONTROL_CAN_BAUD_RATES,
+        default_baud: 500,
+        dll: "ControlCAN.dll",
+        can_fd: false,
+    },
+    AdapterPreset {
+        name: "CANalyst-II",
+        backend: AdapterBackend::ControlCan,
+        dev_type: 4,
+        channels: 2,
+        baud_rates: &CONTROL_CAN_BAUD_RATES,
+        default_baud: 500,
+        dll: "ControlCAN.dll",
+        can_fd: false,
+    },
+    AdapterPreset {
+        name: "PCAN-USB",
+        backend: AdapterBackend::Pcan,
+        dev_type: 0,
+        channels: 1,
+        baud_rates: &PCAN_BAUD_RATES,
+        default_baud: 500,
+        dll: "PCANBasic.dll",
+        can_fd: false,
+    },
+    AdapterPreset {
+        name: "PCAN-USB FD",
+        backend: AdapterBackend::Pcan,
+        dev_type: 0,
+        channels: 1,
+        baud_rates: &PCAN_BAUD_RATES,
+        default_baud: 500,
+        dll: "PCANBasic.dll",
+        can_fd: true,
+    },
+    AdapterPreset {
+        name: "CANable / USBtin (SLCAN)",
+        backend: AdapterBackend::Slcan,
+        dev_type: 0,
+        channels: 1,
+        baud_rates: &SLCAN_BAUD_RATES,
+        default_baud: 500,
+        dll: "SLCAN firmware",
+        can_fd: false,
+    },
+    AdapterPreset {
+        name: "candleLight (gs_usb)",
+        backend: AdapterBackend::GsUsb,
+        dev_type: 0,
+        channels: 1,
+        baud_rates: &GS_USB_BAUD_RATES,
+        default_baud: 500,
+        dll: "libusb (WinUSB driver on Windows)",
+        can_fd: false,
+    },
+];
+
+impl AdapterPreset {
+    /// 設定面板顯示的說明
+    pub fn summary(&self) -> String {
+        format!(
+            "{} channel(s), {}K-{}K, requires {}{}",
+            self.channels,
+            self.baud_rates.first().copied().unwrap_or_default(),
+            self.baud_rates.last().copied().unwrap_or_default(),
+            self.dll,
+            if self.can_fd { ", CAN FD capable" } else { "" }
+        )
+    }
+}
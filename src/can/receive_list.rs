@@ -0,0 +1,76 @@
+use crate::can::cantypes::{CanFrame, MAX_PAYLOAD_LEN};
+use std::collections::BTreeMap;
+
+/// 同一個 ID 最新的訊框與統計
+#[derive(Debug, Clone)]
+pub struct IdEntry {
+    pub frame: CanFrame,
+    pub count: u64,
+    /// 與前一筆的時間差（微秒），只收到一筆時為 None
+    pub cycle_us: Option<u64>,
+    /// 各位元組最後一次改變的主機時間，用於短暫標示變化
+    changed_at: [u64; MAX_PAYLOAD_LEN],
+}
+
+impl IdEntry {
+    /// 位元組是否在 `now_us` 之前的 `window_us` 內改變過
+    pub fn byte_changed(&self, index: usize, now_us: u64, window_us: u64) -> bool {
+        self.changed_at
+            .get(index)
+            .is_some_and(|&time| time > 0 && now_us.saturating_sub(time) < window_us)
+    }
+}
+
+/// 依通道與 ID 分組的接收清單，每個 ID 只保留最新的訊框，
+/// 與 Data 緩衝區不同，計數涵蓋整個工作階段
+#[derive(Clone, Default)]
+pub struct ReceiveList {
+    entries: BTreeMap<(u32, bool, u32), IdEntry>,
+}
+
+impl ReceiveList {
+    pub fn process(&mut self, frame: &CanFrame) {
+        let key = (frame.channel, frame.extended, frame.id);
+        match self.entries.get_mut(&key) {
+            Some(entry) => {
+                let old = entry.frame.payload();
+                for (index, byte) in frame.payload().iter().enumerate() {
+                    if old.get(index) != Some(byte) {
+                        entry.changed_at[index] = frame.timestamp;
+                    }
+                }
+                entry.cycle_us = Some(frame.delta_us(&entry.frame));
+                entry.count += 1;
+                entry.frame = *frame;
+            }
+            None => {
+                self.entries.insert(
+                    key,
+                    IdEntry {
+                        frame: *frame,
+                        count: 1,
+                        cycle_us: None,
+                        changed_at: [0; MAX_PAYLOAD_LEN],
+                    },
+                );
+            }
+        }
+    }
+
+    /// 依通道、標準/延伸與 ID 排序
+    pub fn entries(&self) -> impl Iterator<Item = &IdEntry> {
+        self.entries.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
@@ -0,0 +1,121 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use crate::can::log_event::LogEvent;
+use flume::Sender;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 等待時檢查是否已停止的間隔
+const POLL_STEP: Duration = Duration::from_millis(50);
+
+/// 裝置監看：定期檢查各個介面，失效時以原本的設定關閉並重新開啟，
+/// 避免轉接器被拔除後程式停在無法收發的狀態；其他介面照常運作
+pub struct ReconnectWatchdog {
+    pub check_interval: Duration,
+    /// 重新開啟失敗後再次嘗試前的等待時間
+    pub retry_interval: Duration,
+}
+
+impl Default for ReconnectWatchdog {
+    fn default() -> Self {
+        Self {
+            check_interval: Duration::from_secs(1),
+            retry_interval: Duration::from_secs(2),
+        }
+    }
+}
+
+impl ReconnectWatchdog {
+    /// 在接收期間持續執行，`running` 回傳 false 時結束；`enabled` 關閉時略過檢查並放棄重試
+    pub fn run<F>(
+        &self,
+        can: &SharedCan,
+        log_tx: &Sender<LogEvent>,
+        data_tx: &Sender<CanFrame>,
+        enabled: &AtomicBool,
+        running: F,
+    ) where
+        F: Fn() -> bool,
+    {
+        // 失效中的介面 id → 下一次的嘗試次數與時間
+        let mut lost: BTreeMap<u32, (u32, Instant)> = BTreeMap::new();
+        while wait(self.check_interval, &running) {
+            if !enabled.load(Ordering::SeqCst) {
+                lost.clear();
+                continue;
+            }
+            for (id, detail) in can.failed_devices() {
+                lost.entry(id).or_insert_with(|| {
+                    let _ = log_tx.send(LogEvent::DeviceLost { detail });
+                    (1, Instant::now())
+                });
+            }
+            lost.retain(|&id, (attempt, next_try)| {
+                if Instant::now() < *next_try || !running() {
+                    return true;
+                }
+                match reopen(can, id, log_tx, data_tx, &running) {
+                    // 介面已被移除
+                    None => false,
+                    Some(Ok(())) => {
+                        let _ = log_tx.send(LogEvent::Reconnected { attempt: *attempt });
+                        false
+                    }
+                    Some(Err(detail)) => {
+                        let _ = log_tx.send(LogEvent::ReconnectFailed {
+                            attempt: *attempt,
+                            detail,
+                        });
+                        *attempt += 1;
+                        *next_try = Instant::now() + self.retry_interval;
+                        true
+                    }
+                }
+            });
+        }
+    }
+}
+
+/// 等待一段時間，期間停止時提早回傳 false
+fn wait<F: Fn() -> bool>(duration: Duration, running: &F) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < duration {
+        if !running() {
+            return false;
+        }
+        thread::sleep(POLL_STEP.min(duration));
+    }
+    running()
+}
+
+/// 停止接收並關閉裝置後以相同設定重新開啟；期間介面暫時移出 `SharedCan`，
+/// 傳送不會選到它，也不會阻擋介面讀取狀態。介面已被移除時回傳 None
+fn reopen<F: Fn() -> bool>(
+    can: &SharedCan,
+    id: u32,
+    log_tx: &Sender<LogEvent>,
+    data_tx: &Sender<CanFrame>,
+    running: &F,
+) -> Option<Result<(), String>> {
+    let interface = can.detach(id)?;
+    let app = &interface.app;
+    app.stop_receiving();
+    app.close_device(log_tx.clone());
+    let result = app.open_device(log_tx.clone());
+    if result.is_ok() {
+        interface.start_receiving(log_tx.clone(), data_tx.clone());
+    }
+    // 重新開啟期間按下停止，或通道已被期間新加入的介面使用時，關閉這個介面
+    let rejected = if running() {
+        can.restore(interface).err()
+    } else {
+        Some(interface)
+    };
+    if let Some(interface) = rejected {
+        interface.app.stop_receiving();
+        interface.app.close_device(log_tx.clone());
+    }
+    Some(result)
+}
@@ -0,0 +1,399 @@
+use crate::can::canbus::{receive_thread_stopped, CanInterface, SharedCan};
+use crate::can::cantypes::CanFrame;
+use crate::can::log_event::LogEvent;
+use crate::can::wire::{MessageReader, WireMessage};
+use flume::{Receiver, Sender};
+use std::io::{ErrorKind, Write};
+use std::net::{Shutdown, SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(2);
+/// 每個用戶端待寫入的訊息上限，佇列滿表示用戶端跟不上，連線會被中斷
+pub const CLIENT_QUEUE_LEN: usize = 4096;
+
+/// 遠端伺服器預設的監聽位址，只接受本機連線
+pub const DEFAULT_BIND_ADDR: &str = "127.0.0.1:29536";
+
+/// 已連線的用戶端：訊息經由佇列交給該用戶端的寫入執行緒，推送不會被慢速的連線卡住
+struct Client {
+    queue: Sender<Arc<[u8]>>,
+    stream: TcpStream,
+}
+
+/// 遠端伺服器：將本機收到的訊框推送給所有連線的用戶端，並代為送出用戶端的訊框
+pub struct RemoteServer {
+    local_addr: SocketAddr,
+    allows_transmit: bool,
+    clients: Arc<Mutex<Vec<Client>>>,
+    running: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl RemoteServer {
+    /// 在 `bind_addr`（例如 "127.0.0.1:29536"）開始監聽；`transmit_token` 為 None 時拒絕
+    /// 用戶端的傳送請求，否則用戶端須先以相同的 token 驗證才能送出訊框
+    pub fn start<F>(
+        bind_addr: &str,
+        can_app: SharedCan,
+        transmit_token: Option<String>,
+        log: F,
+    ) -> Result<Self, String>
+    where
+        F: Fn(String) + Send + Sync + 'static,
+    {
+        if transmit_token.as_ref().is_some_and(|t| t.is_empty()) {
+            return Err("Remote transmit token must not be empty".to_string());
+        }
+        let listener = TcpListener::bind(bind_addr)
+            .map_err(|e| format!("Failed to listen on {}: {}", bind_addr, e))?;
+        listener
+            .set_nonblocking(true)
+            .map_err(|e| format!("Failed to configure listener: {}", e))?;
+        let local_addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read listener address: {}", e))?;
+        let clients = Arc::new(Mutex::new(Vec::new()));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let clients_accept = Arc::clone(&clients);
+        let running_accept = Arc::clone(&running);
+        let log = Arc::new(log);
+        let allows_transmit = transmit_token.is_some();
+        let transmit_token: Arc<Option<String>> = Arc::new(transmit_token);
+        let handle = thread::spawn(move || {
+            log(format!("Remote server listening on {}", local_addr));
+            while running_accept.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((stream, peer)) => {
+                        let _ = stream.set_nonblocking(false);
+                        let _ = stream.set_nodelay(true);
+                        let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+                        let (reader, writer) = match (stream.try_clone(), stream.try_clone()) {
+                            (Ok(reader), Ok(writer)) => (reader, writer),
+                            (Err(e), _) | (_, Err(e)) => {
+                                log(format!("Remote client {} rejected: {}", peer, e));
+                                continue;
+                            }
+                        };
+                        log(format!("Remote client connected: {}", peer));
+                        let (queue, pending) = flume::bounded(CLIENT_QUEUE_LEN);
+                        thread::spawn(move || write_client(writer, pending));
+                        clients_accept.lock().unwrap().push(Client {
+                            queue: queue.clone(),
+                            stream,
+                        });
+                        let can_app = can_app.clone();
+                        let running = Arc::clone(&running_accept);
+                        let log = Arc::clone(&log);
+                        let transmit_token = Arc::clone(&transmit_token);
+                        thread::spawn(move || {
+                            serve_client(reader, queue, can_app, &transmit_token, running);
+                            log(format!("Remote client disconnected: {}", peer));
+                        });
+                    }
+                    Err(e) if e.kind() == ErrorKind::WouldBlock => thread::sleep(POLL_INTERVAL),
+                    Err(e) => {
+                        log(format!("Remote server accept failed: {}", e));
+                        thread::sleep(POLL_INTERVAL);
+                    }
+                }
+            }
+            log("Remote server stopped".to_string());
+        });
+
+        Ok(Self {
+            local_addr,
+            allows_transmit,
+            clients,
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    /// 將一筆收到的訊框放入所有用戶端的佇列，不等待網路寫入；
+    /// 佇列已滿或寫入執行緒已結束的連線會被中斷並移除
+    pub fn publish(&self, frame: &CanFrame) {
+        let bytes: Arc<[u8]> = WireMessage::Frame(*frame).encode().into();
+        self.clients.lock().unwrap().retain(|client| {
+            let queued = client.queue.try_send(Arc::clone(&bytes)).is_ok();
+            if !queued {
+                let _ = client.stream.shutdown(Shutdown::Both);
+            }
+            queued
+        });
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    /// 是否接受已驗證用戶端的傳送請求
+    pub fn allows_transmit(&self) -> bool {
+        self.allows_transmit
+    }
+
+    pub fn client_count(&self) -> usize {
+        self.clients.lock().unwrap().len()
+    }
+}
+
+impl Drop for RemoteServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        for client in self.clients.lock().unwrap().drain(..) {
+            let _ = client.stream.shutdown(Shutdown::Both);
+        }
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining remote server thread: {:?}", e);
+            }
+        }
+    }
+}
+
+/// 用戶端的寫入執行緒：依序寫出佇列中的訊息，寫入失敗或逾時時中斷連線
+fn write_client(mut stream: TcpStream, pending: Receiver<Arc<[u8]>>) {
+    for bytes in pending.iter() {
+        if stream.write_all(&bytes).is_err() {
+            break;
+        }
+    }
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+/// 處理單一用戶端送來的驗證與 Transmit 請求，回覆經由該用戶端的佇列送出
+fn serve_client(
+    mut stream: TcpStream,
+    queue: Sender<Arc<[u8]>>,
+    can_app: SharedCan,
+    transmit_token: &Option<String>,
+    running: Arc<AtomicBool>,
+) {
+    let _ = stream.set_read_timeout(Some(POLL_INTERVAL));
+    let mut reader = MessageReader::default();
+    let mut authorized = false;
+    let reply = |text: &str| {
+        let _ = queue.try_send(WireMessage::Error(text.to_string()).encode().into());
+    };
+    'serve: while running.load(Ordering::SeqCst) {
+        let messages = match reader.poll(&mut stream) {
+            Ok(messages) => messages,
+            Err(_) => break,
+        };
+        for message in messages {
+            match message {
+                WireMessage::Auth(token) => match transmit_token {
+                    Some(expected) if token_matches(&token, expected) => authorized = true,
+                    Some(_) => {
+                        reply("Remote transmit token rejected");
+                        break 'serve;
+                    }
+                    None => reply("Remote transmit is disabled on this server"),
+                },
+                WireMessage::Transmit(_) if transmit_token.is_none() => {
+                    reply("Remote transmit is disabled on this server")
+                }
+                WireMessage::Transmit(_) if !authorized => {
+                    reply("Remote transmit requires a token")
+                }
+                WireMessage::Transmit(frame) => {
+                    if let Err(e) = can_app.send_frame(&frame) {
+                        reply(&e);
+                    }
+                }
+                WireMessage::Frame(_) | WireMessage::Error(_) => {}
+            }
+        }
+    }
+    // 寫入執行緒可能還在送出最後的錯誤訊息，稍候再中斷連線
+    thread::sleep(POLL_INTERVAL);
+    let _ = stream.shutdown(Shutdown::Both);
+}
+
+/// 比較 token，耗時不依第一個不同字元的位置而變
+fn token_matches(token: &str, expected: &str) -> bool {
+    token.len() == expected.len()
+        && token
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// 遠端 CAN 後端：連線到另一個實例的遠端伺服器，收發都經由網路轉送
+pub struct RemoteCanApp {
+    address: String,
+    /// 伺服器允許遠端傳送時設定的共用 token
+    token: Option<String>,
+    stream: Arc<Mutex<Option<TcpStream>>>,
+    receiving: Arc<AtomicBool>,
+    join_handles: Arc<Mutex<Vec<thread::JoinHandle<()>>>>,
+}
+
+impl RemoteCanApp {
+    /// 建立新的 RemoteCanApp，`address` 例如 "192.168.1.20:29536"
+    pub fn new(address: &str) -> Self {
+        Self {
+            address: address.to_string(),
+            token: None,
+            stream: Arc::new(Mutex::new(None)),
+            receiving: Arc::new(AtomicBool::new(false)),
+            join_handles: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// 連線後以 `token` 取得傳送權限，空字串表示不驗證（只接收）
+    pub fn with_token(mut self, token: &str) -> Self {
+        self.token = (!token.is_empty()).then(|| token.to_string());
+        self
+    }
+
+    fn connect(&self) -> Result<TcpStream, String> {
+        let addr = self
+            .address
+            .to_socket_addrs()
+            .map_err(|e| format!("Invalid remote address '{}': {}", self.address, e))?
+            .next()
+            .ok_or_else(|| format!("Remote address '{}' did not resolve", self.address))?;
+        let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)
+            .map_err(|e| format!("Failed to connect to {}: {}", addr, e))?;
+        let _ = stream.set_nodelay(true);
+        let _ = stream.set_write_timeout(Some(WRITE_TIMEOUT));
+        if let Some(ref token) = self.token {
+            WireMessage::Auth(token.clone())
+                .write_to(&mut &stream)
+                .map_err(|e| format!("Failed to authenticate with {}: {}", addr, e))?;
+        }
+        Ok(stream)
+    }
+}
+
+impl CanInterface for RemoteCanApp {
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String> {
+        let stream = self.connect().inspect_err(|e| {
+            let _ = log_tx.send(LogEvent::DeviceError { detail: e.clone() });
+        })?;
+        *self.stream.lock().unwrap() = Some(stream);
+        let _ = log_tx.send(LogEvent::RemoteConnected {
+            address: self.address.clone(),
+        });
+        Ok(())
+    }
+
+    fn close_device(&self, log_tx: Sender<LogEvent>) {
+        if let Some(stream) = self.stream.lock().unwrap().take() {
+            let _ = stream.shutdown(Shutdown::Both);
+            let _ = log_tx.send(LogEvent::RemoteDisconnected {
+                address: self.address.clone(),
+            });
+        }
+    }
+
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
+        let mut stream = match self.stream.lock().unwrap().as_ref().map(|s| s.try_clone()) {
+            Some(Ok(stream)) => stream,
+            Some(Err(e)) => {
+                let _ = log_tx.send(LogEvent::RemoteConnectionLost {
+                    detail: e.to_string(),
+                });
+                return;
+            }
+            None => {
+                let _ = log_tx.send(LogEvent::NotConnected);
+                return;
+            }
+        };
+        let _ = stream.set_read_timeout(Some(POLL_INTERVAL));
+        self.receiving.store(true, Ordering::SeqCst);
+        let receiving_flag = Arc::clone(&self.receiving);
+        let handle = thread::spawn(move || {
+            let mut reader = MessageReader::default();
+            while receiving_flag.load(Ordering::SeqCst) {
+                match reader.poll(&mut stream) {
+                    Ok(messages) => {
+                        for message in messages {
+                            match message {
+                                WireMessage::Frame(frame) => {
+                                    let _ = data_tx.send(frame);
+                                }
+                                WireMessage::Error(e) => {
+                                    let _ = log_tx.send(LogEvent::RemoteError { detail: e });
+                                }
+                                WireMessage::Transmit(_) | WireMessage::Auth(_) => {}
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let _ = log_tx.send(LogEvent::RemoteConnectionLost {
+                            detail: e.to_string(),
+                        });
+                        break;
+                    }
+                }
+            }
+        });
+        self.join_handles.lock().unwrap().push(handle);
+    }
+
+    fn stop_receiving(&self) {
+        self.receiving.store(false, Ordering::SeqCst);
+        let mut handles = self.join_handles.lock().unwrap();
+        while let Some(handle) = handles.pop() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining remote thread: {:?}", e);
+            }
+        }
+    }
+
+    fn check_device(&self) -> Result<(), String> {
+        if receive_thread_stopped(&self.receiving, &self.join_handles) {
+            Err("Remote receive loop stopped".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_board_info(&self, log_tx: Sender<LogEvent>) {
+        let connected = self.stream.lock().unwrap().is_some();
+        let _ = log_tx.send(LogEvent::BackendInfo {
+            description: format!(
+                "Remote backend: {} ({})",
+                self.address,
+                if connected {
+                    "connected"
+                } else {
+                    "disconnected"
+                }
+            ),
+        });
+    }
+
+    /// 伺服器停止讀取時寫入會在 `WRITE_TIMEOUT` 後失敗；訊息可能只寫出一部分，
+    /// 因此逾時後中斷連線，不再送出後續訊框
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        let mut guard = self.stream.lock().unwrap();
+        let Some(stream) = guard.as_mut() else {
+            return Err("Remote server not connected".to_string());
+        };
+        match WireMessage::Transmit(*frame).write_to(stream) {
+            Ok(()) => Ok(()),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {
+                let _ = stream.shutdown(Shutdown::Both);
+                *guard = None;
+                Err(format!(
+                    "Remote transmit timed out after {} s; disconnected from {}",
+                    WRITE_TIMEOUT.as_secs(),
+                    self.address
+                ))
+            }
+            Err(e) => Err(format!("Remote transmit failed: {}", e)),
+        }
+    }
+}
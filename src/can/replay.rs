@@ -0,0 +1,176 @@
+use crate::can::blf;
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use crate::can::logfile::parse_candump_line;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 播放速度範圍
+pub const MIN_SPEED: f64 = 0.1;
+pub const MAX_SPEED: f64 = 10.0;
+
+/// 讀取要重播的紀錄檔：`.blf` 為 BLF，其他視為 candump；回傳依時間排序的訊框
+pub fn load_log(path: &str) -> Result<Vec<CanFrame>, String> {
+    let mut frames = if path.to_ascii_lowercase().ends_with(".blf") {
+        blf::read_blf(path)?
+    } else {
+        let file = File::open(path).map_err(|e| format!("Failed to open {}: {}", path, e))?;
+        let mut frames = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line.map_err(|e| format!("Failed to read {}: {}", path, e))?;
+            frames.extend(parse_candump_line(&line));
+        }
+        frames
+    };
+    if frames.is_empty() {
+        return Err(format!("{}: no frames found", path));
+    }
+    // 穩定排序，同一時間的訊框保持檔案中的順序
+    frames.sort_by_key(|f| f.timestamp);
+    Ok(frames)
+}
+
+/// 重播狀態，供介面顯示
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReplayState {
+    Playing,
+    Paused,
+    Finished,
+}
+
+/// 執行中的紀錄檔重播，依原始訊框間隔送出，可暫停並即時調整速度
+pub struct LogReplay {
+    running: Arc<AtomicBool>,
+    paused: Arc<AtomicBool>,
+    /// f32 位元表示的播放倍率
+    speed: Arc<AtomicU32>,
+    sent: Arc<AtomicUsize>,
+    /// 已播放到的紀錄時間（相對第一筆，微秒）
+    position_us: Arc<AtomicU64>,
+    total: usize,
+    duration_us: u64,
+}
+
+impl LogReplay {
+    /// 在背景執行緒重播；`channel` 為 Some 時所有訊框改由該通道送出
+    pub fn start<F>(
+        frames: Vec<CanFrame>,
+        can_app: SharedCan,
+        channel: Option<u32>,
+        speed: f64,
+        log: F,
+    ) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let first = frames.first().map_or(0, |f| f.timestamp);
+        let replay = Self {
+            running: Arc::new(AtomicBool::new(true)),
+            paused: Arc::new(AtomicBool::new(false)),
+            speed: Arc::new(AtomicU32::new(0)),
+            sent: Arc::new(AtomicUsize::new(0)),
+            position_us: Arc::new(AtomicU64::new(0)),
+            total: frames.len(),
+            duration_us: frames.last().map_or(0, |f| f.timestamp - first),
+        };
+        replay.set_speed(speed);
+        let running = Arc::clone(&replay.running);
+        let paused = Arc::clone(&replay.paused);
+        let speed = Arc::clone(&replay.speed);
+        let sent = Arc::clone(&replay.sent);
+        let position_us = Arc::clone(&replay.position_us);
+        thread::spawn(move || {
+            log(format!("Replay started ({} frames)", frames.len()));
+            // 以累積的紀錄時間為準，暫停與變速時不會產生跳躍
+            let mut played_us = 0.0;
+            let mut last = Instant::now();
+            for (index, frame) in frames.iter().enumerate() {
+                let due_us = (frame.timestamp - first) as f64;
+                loop {
+                    if !running.load(Ordering::SeqCst) {
+                        log(format!("Replay stopped after {} frames", index));
+                        return;
+                    }
+                    let now = Instant::now();
+                    if !paused.load(Ordering::SeqCst) {
+                        let factor = f32::from_bits(speed.load(Ordering::SeqCst)) as f64;
+                        played_us += (now - last).as_secs_f64() * 1e6 * factor;
+                    }
+                    last = now;
+                    position_us.store(played_us.min(due_us) as u64, Ordering::SeqCst);
+                    if played_us >= due_us {
+                        break;
+                    }
+                    thread::sleep(Duration::from_millis(1));
+                }
+                let mut frame = *frame;
+                if let Some(channel) = channel {
+                    frame.channel = channel;
+                }
+                if let Err(e) = can_app.send_frame(&frame) {
+                    log(format!("Replay aborted at frame {}: {}", index + 1, e));
+                    running.store(false, Ordering::SeqCst);
+                    return;
+                }
+                sent.fetch_add(1, Ordering::SeqCst);
+            }
+            log("Replay finished".to_string());
+            running.store(false, Ordering::SeqCst);
+        });
+        replay
+    }
+
+    pub fn state(&self) -> ReplayState {
+        if !self.running.load(Ordering::SeqCst) {
+            ReplayState::Finished
+        } else if self.paused.load(Ordering::SeqCst) {
+            ReplayState::Paused
+        } else {
+            ReplayState::Playing
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::SeqCst);
+    }
+
+    /// 倍率限制在 0.1x 到 10x
+    pub fn set_speed(&self, speed: f64) {
+        let speed = speed.clamp(MIN_SPEED, MAX_SPEED) as f32;
+        self.speed.store(speed.to_bits(), Ordering::SeqCst);
+    }
+
+    pub fn speed(&self) -> f64 {
+        f32::from_bits(self.speed.load(Ordering::SeqCst)) as f64
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    /// (已送出, 總筆數)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.sent.load(Ordering::SeqCst), self.total)
+    }
+
+    /// (已播放的紀錄時間, 紀錄總長)，單位微秒
+    pub fn position(&self) -> (u64, u64) {
+        (self.position_us.load(Ordering::SeqCst), self.duration_us)
+    }
+}
+
+impl Drop for LogReplay {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,129 @@
+use crate::can::cantypes::now_micros;
+use crate::can::signals::SignalTable;
+use crate::can::units::DisplayUnits;
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 以固定頻率取樣所有訊號（保持最後一筆值）並寫入 CSV，
+/// 不受訊框到達時間影響，產生等間隔的資料
+pub struct FixedRateSampler {
+    running: Arc<AtomicBool>,
+    rows: Arc<AtomicU64>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl FixedRateSampler {
+    /// 開始取樣並寫入 `file_path`；欄位為啟動當下 SignalTable 中的所有訊號，
+    /// 以啟動當下選擇的顯示單位輸出
+    pub fn start<F>(
+        file_path: &str,
+        rate_hz: f64,
+        signals: Arc<Mutex<SignalTable>>,
+        units: DisplayUnits,
+        log: F,
+    ) -> Result<Self, String>
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        if !(rate_hz > 0.0 && rate_hz <= 1000.0) {
+            return Err(format!(
+                "Sample rate must be in (0, 1000] Hz, got {}",
+                rate_hz
+            ));
+        }
+        let keys: Vec<String> = signals
+            .lock()
+            .unwrap()
+            .signals()
+            .map(|s| s.key.clone())
+            .collect();
+        if keys.is_empty() {
+            return Err("No signals defined; load a YAML config first".to_string());
+        }
+        let file = File::create(file_path)
+            .map_err(|e| format!("Failed to create {}: {}", file_path, e))?;
+        let mut writer = BufWriter::new(file);
+        let header: Vec<String> = keys
+            .iter()
+            .map(|key| match units.unit(key) {
+                "" => key.clone(),
+                unit => format!("{} [{}]", key, unit),
+            })
+            .collect();
+        writeln!(writer, "time_s,timestamp_us,{}", header.join(","))
+            .map_err(|e| format!("Failed to write CSV header: {}", e))?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let rows = Arc::new(AtomicU64::new(0));
+        let running_flag = Arc::clone(&running);
+        let rows_count = Arc::clone(&rows);
+        let period = Duration::from_secs_f64(1.0 / rate_hz);
+        let handle = thread::spawn(move || {
+            let start = Instant::now();
+            let mut tick: u32 = 0;
+            while running_flag.load(Ordering::SeqCst) {
+                // 以起始時間為基準排程，避免累積誤差
+                let due = start + period * tick;
+                let now = Instant::now();
+                if due > now {
+                    thread::sleep((due - now).min(Duration::from_millis(50)));
+                    continue;
+                }
+                let row = {
+                    let table = signals.lock().unwrap();
+                    keys.iter()
+                        .map(|key| {
+                            table
+                                .get(key)
+                                .and_then(|s| s.value)
+                                .map(|v| units.convert(key, v).to_string())
+                                .unwrap_or_default()
+                        })
+                        .collect::<Vec<_>>()
+                        .join(",")
+                };
+                let time_s = (period * tick).as_secs_f64();
+                if let Err(e) = writeln!(writer, "{:.6},{},{}", time_s, now_micros(), row) {
+                    log(format!("Sampler write failed: {}", e));
+                    break;
+                }
+                rows_count.fetch_add(1, Ordering::SeqCst);
+                tick += 1;
+            }
+            if let Err(e) = writer.flush() {
+                log(format!("Sampler flush failed: {}", e));
+            }
+            running_flag.store(false, Ordering::SeqCst);
+        });
+        Ok(Self {
+            running,
+            rows,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn rows(&self) -> u64 {
+        self.rows.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for FixedRateSampler {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining sampler thread: {:?}", e);
+            }
+        }
+    }
+}
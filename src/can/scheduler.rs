@@ -0,0 +1,226 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 排程器檢查到期訊息的間隔
+const TICK: Duration = Duration::from_millis(1);
+
+/// 傳送速率限制；數值為 0 表示不限制
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    /// 每秒最多訊框數
+    pub max_fps: f64,
+    /// 允許的突發訊框數（token bucket 容量）
+    pub burst: u32,
+    /// 相鄰兩筆訊框的最小間隔（毫秒）
+    pub min_gap_ms: f64,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            max_fps: 0.0,
+            burst: 1,
+            min_gap_ms: 0.0,
+        }
+    }
+}
+
+/// 以 token bucket 實作的速率限制器；時間由呼叫端傳入，方便以固定時間測試
+#[derive(Debug)]
+pub struct RateLimiter {
+    limit: RateLimit,
+    tokens: f64,
+    last_refill: Instant,
+    last_send: Option<Instant>,
+}
+
+impl RateLimiter {
+    /// 由 `now` 開始計算補充，初始為滿的 bucket
+    pub fn new(limit: RateLimit, now: Instant) -> Self {
+        Self {
+            limit,
+            tokens: limit.burst.max(1) as f64,
+            last_refill: now,
+            last_send: None,
+        }
+    }
+
+    /// 更新限制設定，保留目前累積的 token
+    pub fn set_limit(&mut self, limit: RateLimit) {
+        if limit != self.limit {
+            self.limit = limit;
+            self.tokens = self.tokens.min(limit.burst.max(1) as f64);
+        }
+    }
+
+    /// 現在是否可以送出一筆訊框
+    pub fn ready(&mut self, now: Instant) -> bool {
+        if self.limit.min_gap_ms > 0.0 {
+            let gap = Duration::from_secs_f64(self.limit.min_gap_ms / 1000.0);
+            if self.last_send.is_some_and(|last| now < last + gap) {
+                return false;
+            }
+        }
+        if self.limit.max_fps > 0.0 {
+            let elapsed = now
+                .saturating_duration_since(self.last_refill)
+                .as_secs_f64();
+            self.tokens =
+                (self.tokens + elapsed * self.limit.max_fps).min(self.limit.burst.max(1) as f64);
+            self.last_refill = now;
+            if self.tokens < 1.0 {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// 記錄已送出一筆訊框
+    pub fn consume(&mut self, now: Instant) {
+        if self.limit.max_fps > 0.0 {
+            self.tokens -= 1.0;
+        }
+        self.last_send = Some(now);
+    }
+}
+
+/// 週期傳送的訊息
+#[derive(Debug, Clone)]
+pub struct PeriodicMessage {
+    pub frame: CanFrame,
+    pub period_ms: u64,
+    pub enabled: bool,
+    /// 此訊息自己的速率限制
+    pub limit: RateLimit,
+}
+
+/// 各訊息的執行狀態，依索引對應 `PeriodicMessage`
+struct MessageState {
+    next_due: Instant,
+    limiter: RateLimiter,
+    /// 目前這一筆是否已因限速而延後（每筆只計一次）
+    deferred: bool,
+    /// 是否處於連續傳送失敗中（只記錄第一次錯誤）
+    failing: bool,
+}
+
+/// 週期傳送排程器：每則訊息依週期送出，並受個別與全域速率限制約束；
+/// 超過限制的訊框延後送出而不丟棄
+pub struct TxScheduler {
+    running: Arc<AtomicBool>,
+    sent: Arc<AtomicU64>,
+    throttled: Arc<AtomicU64>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl TxScheduler {
+    /// `messages` 與 `global_limit` 可在執行中修改，下一個週期即生效
+    pub fn start<F>(
+        messages: Arc<Mutex<Vec<PeriodicMessage>>>,
+        global_limit: Arc<Mutex<RateLimit>>,
+        can_app: SharedCan,
+        log: F,
+    ) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let running = Arc::new(AtomicBool::new(true));
+        let sent = Arc::new(AtomicU64::new(0));
+        let throttled = Arc::new(AtomicU64::new(0));
+        let running_flag = Arc::clone(&running);
+        let sent_count = Arc::clone(&sent);
+        let throttled_count = Arc::clone(&throttled);
+        let handle = thread::spawn(move || {
+            log("TX scheduler started".to_string());
+            let mut states: Vec<MessageState> = Vec::new();
+            let mut global = RateLimiter::new(*global_limit.lock().unwrap(), Instant::now());
+            while running_flag.load(Ordering::SeqCst) {
+                let now = Instant::now();
+                global.set_limit(*global_limit.lock().unwrap());
+                let messages = messages.lock().unwrap().clone();
+                // 訊息清單變動時重新建立狀態，新訊息立即到期
+                states.truncate(messages.len());
+                while states.len() < messages.len() {
+                    states.push(MessageState {
+                        next_due: now,
+                        limiter: RateLimiter::new(messages[states.len()].limit, now),
+                        deferred: false,
+                        failing: false,
+                    });
+                }
+
+                for (message, state) in messages.iter().zip(states.iter_mut()) {
+                    state.limiter.set_limit(message.limit);
+                    if !message.enabled || now < state.next_due {
+                        continue;
+                    }
+                    if !state.limiter.ready(now) || !global.ready(now) {
+                        if !state.deferred {
+                            state.deferred = true;
+                            throttled_count.fetch_add(1, Ordering::Relaxed);
+                        }
+                        continue;
+                    }
+                    state.deferred = false;
+                    match can_app.send_frame(&message.frame) {
+                        Ok(()) => {
+                            state.failing = false;
+                            sent_count.fetch_add(1, Ordering::Relaxed);
+                        }
+                        Err(e) => {
+                            if !state.failing {
+                                log(format!(
+                                    "Scheduled send of 0x{:X} failed: {}",
+                                    message.frame.id, e
+                                ));
+                            }
+                            state.failing = true;
+                        }
+                    }
+                    state.limiter.consume(now);
+                    global.consume(now);
+                    // 依原排程推進，落後太多時從現在重新起算
+                    let period = Duration::from_millis(message.period_ms.max(1));
+                    state.next_due += period;
+                    if state.next_due + period < now {
+                        state.next_due = now + period;
+                    }
+                }
+                thread::sleep(TICK);
+            }
+            log("TX scheduler stopped".to_string());
+        });
+        Self {
+            running,
+            sent,
+            throttled,
+            handle: Some(handle),
+        }
+    }
+
+    pub fn sent(&self) -> u64 {
+        self.sent.load(Ordering::Relaxed)
+    }
+
+    /// 因速率限制而延後的訊框數
+    pub fn throttled(&self) -> u64 {
+        self.throttled.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for TxScheduler {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining scheduler thread: {:?}", e);
+            }
+        }
+    }
+}
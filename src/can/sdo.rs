@@ -0,0 +1,387 @@
+use crate::can::canbus::SharedCan;
+use crate::can::canopen::abort_description;
+use crate::can::cantypes::CanFrame;
+use flume::{unbounded, Receiver, RecvTimeoutError, Sender};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 用戶端送往節點的 SDO COB-ID 基底，節點回應為 0x580 + 節點編號
+const SDO_REQUEST_BASE: u32 = 0x600;
+const SDO_RESPONSE_BASE: u32 = 0x580;
+/// 等待節點回應的時間
+pub const SDO_TIMEOUT: Duration = Duration::from_secs(1);
+/// 中止代碼：逾時、指令無效、toggle bit 錯誤、一般錯誤
+const ABORT_TIMEOUT: u32 = 0x0504_0000;
+const ABORT_COMMAND: u32 = 0x0504_0001;
+const ABORT_TOGGLE: u32 = 0x0503_0000;
+const ABORT_GENERAL: u32 = 0x0800_0000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Phase {
+    /// 已送出起始請求，等待起始回應
+    Initiate,
+    /// 分段上傳中，收到的資料與下一個 toggle bit
+    Upload { data: Vec<u8>, toggle: bool },
+    /// 分段下載中，已送出的位元組數與下一個 toggle bit
+    Download { sent: usize, toggle: bool },
+}
+
+/// 收到回應後的下一步
+#[derive(Debug, Clone, PartialEq)]
+pub enum SdoStep {
+    /// 送出下一個請求訊框
+    Send(CanFrame),
+    /// 傳輸完成，上傳時為讀到的資料，下載時為空
+    Done(Vec<u8>),
+    /// 傳輸失敗；節點中止時不需回送，用戶端中止時附上要送出的中止訊框
+    Failed(Option<CanFrame>, String),
+}
+
+/// SDO 傳輸的狀態機（快速與分段傳輸），不涉及傳送與計時，方便單獨驗證
+#[derive(Debug, Clone)]
+pub struct SdoTransfer {
+    channel: u32,
+    node: u8,
+    index: u16,
+    subindex: u8,
+    /// 下載的資料；None 表示上傳（讀取）
+    download: Option<Vec<u8>>,
+    phase: Phase,
+}
+
+impl SdoTransfer {
+    /// 讀取物件字典的項目
+    pub fn upload(channel: u32, node: u8, index: u16, subindex: u8) -> Self {
+        Self {
+            channel,
+            node,
+            index,
+            subindex,
+            download: None,
+            phase: Phase::Initiate,
+        }
+    }
+
+    /// 寫入物件字典的項目，4 bytes 以內以快速傳輸送出
+    pub fn download(channel: u32, node: u8, index: u16, subindex: u8, data: Vec<u8>) -> Self {
+        Self {
+            channel,
+            node,
+            index,
+            subindex,
+            download: Some(data),
+            phase: Phase::Initiate,
+        }
+    }
+
+    pub fn node(&self) -> u8 {
+        self.node
+    }
+
+    pub fn is_upload(&self) -> bool {
+        self.download.is_none()
+    }
+
+    /// 項目的文字表示，例如 "0x1018:01"
+    pub fn object(&self) -> String {
+        format!("0x{:04X}:{:02X}", self.index, self.subindex)
+    }
+
+    fn frame(&self, data: [u8; 8]) -> CanFrame {
+        CanFrame::new(self.channel, SDO_REQUEST_BASE + self.node as u32, &data)
+    }
+
+    /// 帶有索引與子索引的起始訊框
+    fn initiate_frame(&self, command: u8, data: [u8; 4]) -> CanFrame {
+        let [lo, hi] = self.index.to_le_bytes();
+        let [d0, d1, d2, d3] = data;
+        self.frame([command, lo, hi, self.subindex, d0, d1, d2, d3])
+    }
+
+    /// 用戶端中止傳輸的訊框
+    pub fn abort_frame(&self, code: u32) -> CanFrame {
+        self.initiate_frame(0x80, code.to_le_bytes())
+    }
+
+    fn abort(&self, code: u32) -> SdoStep {
+        SdoStep::Failed(
+            Some(self.abort_frame(code)),
+            format!("{} (0x{:08X})", abort_description(code), code),
+        )
+    }
+
+    /// 第一個請求訊框
+    pub fn start(&mut self) -> CanFrame {
+        self.phase = Phase::Initiate;
+        match &self.download {
+            None => self.initiate_frame(0x40, [0; 4]),
+            Some(data) if data.len() <= 4 => {
+                // e=1, s=1, n = 未使用的位元組數
+                let mut bytes = [0u8; 4];
+                bytes[..data.len()].copy_from_slice(data);
+                self.initiate_frame(0x23 | (((4 - data.len()) as u8) << 2), bytes)
+            }
+            Some(data) => self.initiate_frame(0x21, (data.len() as u32).to_le_bytes()),
+        }
+    }
+
+    /// 處理一筆收到的訊框；不是此節點的 SDO 回應時回傳 None
+    pub fn handle(&mut self, frame: &CanFrame) -> Option<SdoStep> {
+        if frame.extended || frame.id != SDO_RESPONSE_BASE + self.node as u32 {
+            return None;
+        }
+        let data = frame.payload();
+        if data.len() < 8 {
+            return Some(self.abort(ABORT_COMMAND));
+        }
+        let command = data[0];
+        if command >> 5 == 4 {
+            let code = u32::from_le_bytes([data[4], data[5], data[6], data[7]]);
+            return Some(SdoStep::Failed(
+                None,
+                format!("{} (0x{:08X})", abort_description(code), code),
+            ));
+        }
+        let step = match self.phase.clone() {
+            Phase::Initiate => self.initiate_response(data),
+            Phase::Upload {
+                data: received,
+                toggle,
+            } => self.upload_segment(data, received, toggle),
+            Phase::Download { sent, toggle } => self.download_segment(data, sent, toggle),
+        };
+        Some(step)
+    }
+
+    fn initiate_response(&mut self, data: &[u8]) -> SdoStep {
+        let command = data[0];
+        if u16::from_le_bytes([data[1], data[2]]) != self.index || data[3] != self.subindex {
+            return self.abort(ABORT_GENERAL);
+        }
+        match (&self.download, command >> 5) {
+            (None, 2) if command & 0x02 != 0 => {
+                // 快速上傳，s=1 時 n 為未使用的位元組數
+                let len = if command & 0x01 != 0 {
+                    4 - ((command >> 2) & 0x3) as usize
+                } else {
+                    4
+                };
+                SdoStep::Done(data[4..4 + len].to_vec())
+            }
+            (None, 2) => {
+                self.phase = Phase::Upload {
+                    data: Vec::new(),
+                    toggle: false,
+                };
+                SdoStep::Send(self.frame([0x60, 0, 0, 0, 0, 0, 0, 0]))
+            }
+            (Some(bytes), 3) if bytes.len() <= 4 => SdoStep::Done(Vec::new()),
+            (Some(_), 3) => self.next_download_segment(0, false),
+            _ => self.abort(ABORT_COMMAND),
+        }
+    }
+
+    fn upload_segment(&mut self, data: &[u8], mut received: Vec<u8>, toggle: bool) -> SdoStep {
+        let command = data[0];
+        if command >> 5 != 0 {
+            return self.abort(ABORT_COMMAND);
+        }
+        if (command & 0x10 != 0) != toggle {
+            return self.abort(ABORT_TOGGLE);
+        }
+        let unused = ((command >> 1) & 0x7) as usize;
+        received.extend_from_slice(&data[1..8 - unused]);
+        if command & 0x01 != 0 {
+            return SdoStep::Done(received);
+        }
+        let toggle = !toggle;
+        self.phase = Phase::Upload {
+            data: received,
+            toggle,
+        };
+        SdoStep::Send(self.frame([0x60 | ((toggle as u8) << 4), 0, 0, 0, 0, 0, 0, 0]))
+    }
+
+    fn download_segment(&mut self, data: &[u8], sent: usize, toggle: bool) -> SdoStep {
+        let command = data[0];
+        if command >> 5 != 1 {
+            return self.abort(ABORT_COMMAND);
+        }
+        // 確認的是上一段，因此比對上一段的 toggle bit
+        if (command & 0x10 != 0) == toggle {
+            return self.abort(ABORT_TOGGLE);
+        }
+        if sent >= self.download.as_ref().map_or(0, |d| d.len()) {
+            return SdoStep::Done(Vec::new());
+        }
+        self.next_download_segment(sent, toggle)
+    }
+
+    /// 送出從 `sent` 開始的下一段（最多 7 bytes），最後一段設定 c bit
+    fn next_download_segment(&mut self, sent: usize, toggle: bool) -> SdoStep {
+        let data = self.download.as_deref().unwrap_or_default();
+        let chunk = &data[sent..(sent + 7).min(data.len())];
+        let last = sent + chunk.len() >= data.len();
+        let mut bytes = [0u8; 8];
+        bytes[0] = ((toggle as u8) << 4) | (((7 - chunk.len()) as u8) << 1) | last as u8;
+        bytes[1..1 + chunk.len()].copy_from_slice(chunk);
+        self.phase = Phase::Download {
+            sent: sent + chunk.len(),
+            toggle: !toggle,
+        };
+        SdoStep::Send(self.frame(bytes))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SdoStatus {
+    Running,
+    /// 完成，上傳時為讀到的資料
+    Done(Vec<u8>),
+    Failed(String),
+}
+
+/// 執行中的 SDO 傳輸，收到的訊框經由 `observe` 交給狀態機
+pub struct SdoClient {
+    frame_tx: Sender<CanFrame>,
+    running: Arc<AtomicBool>,
+    status: Arc<Mutex<SdoStatus>>,
+    description: String,
+    upload: bool,
+}
+
+impl SdoClient {
+    /// 在背景執行緒執行傳輸，每一步最多等待 `timeout`
+    pub fn start<F>(
+        mut transfer: SdoTransfer,
+        can_app: SharedCan,
+        timeout: Duration,
+        log: F,
+    ) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let (frame_tx, frame_rx) = unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let status = Arc::new(Mutex::new(SdoStatus::Running));
+        let upload = transfer.is_upload();
+        let description = format!(
+            "SDO {} node {} {}",
+            if upload { "read" } else { "write" },
+            transfer.node(),
+            transfer.object()
+        );
+        {
+            let running = Arc::clone(&running);
+            let status = Arc::clone(&status);
+            let description = description.clone();
+            thread::spawn(move || {
+                let result = run_transfer(&mut transfer, &can_app, &frame_rx, &running, timeout);
+                match &result {
+                    SdoStatus::Done(data) if transfer.is_upload() => {
+                        log(format!("{} done, {} bytes", description, data.len()))
+                    }
+                    SdoStatus::Done(_) => log(format!("{} done", description)),
+                    SdoStatus::Failed(e) => log(format!("{} failed: {}", description, e)),
+                    SdoStatus::Running => {}
+                }
+                *status.lock().unwrap() = result;
+                running.store(false, Ordering::SeqCst);
+            });
+        }
+        Self {
+            frame_tx,
+            running,
+            status,
+            description,
+            upload,
+        }
+    }
+
+    /// 交由傳輸比對一筆收到的訊框
+    pub fn observe(&self, frame: &CanFrame) {
+        if self.is_running() {
+            let _ = self.frame_tx.send(*frame);
+        }
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn status(&self) -> SdoStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    pub fn is_upload(&self) -> bool {
+        self.upload
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+}
+
+impl Drop for SdoClient {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn run_transfer(
+    transfer: &mut SdoTransfer,
+    can_app: &SharedCan,
+    frame_rx: &Receiver<CanFrame>,
+    running: &AtomicBool,
+    timeout: Duration,
+) -> SdoStatus {
+    let mut frame = transfer.start();
+    loop {
+        // 送出前的訊框不可能是這筆請求的回應
+        frame_rx.drain();
+        if let Err(e) = can_app.send_frame(&frame) {
+            return SdoStatus::Failed(format!("transmit failed: {}", e));
+        }
+        let deadline = Instant::now() + timeout;
+        let step = loop {
+            if !running.load(Ordering::SeqCst) {
+                let _ = can_app.send_frame(&transfer.abort_frame(ABORT_GENERAL));
+                return SdoStatus::Failed("stopped".to_string());
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                let _ = can_app.send_frame(&transfer.abort_frame(ABORT_TIMEOUT));
+                return SdoStatus::Failed(format!("no response within {} ms", timeout.as_millis()));
+            }
+            match frame_rx.recv_timeout(remaining.min(Duration::from_millis(10))) {
+                Ok(received) => {
+                    if let Some(step) = transfer.handle(&received) {
+                        break step;
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => {
+                    return SdoStatus::Failed("stopped".to_string())
+                }
+            }
+        };
+        match step {
+            SdoStep::Send(next) => frame = next,
+            SdoStep::Done(data) => return SdoStatus::Done(data),
+            SdoStep::Failed(abort, reason) => {
+                if let Some(abort) = abort {
+                    let _ = can_app.send_frame(&abort);
+                }
+                return SdoStatus::Failed(reason);
+            }
+        }
+    }
+}
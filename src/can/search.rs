@@ -0,0 +1,79 @@
+use crate::can::cantypes::CanFrame;
+use regex::Regex;
+
+/// 十六進位位元組樣式，以 `?` 表示任意半位元組，例如 "12 ?? 3? AB"
+#[derive(Debug, Clone)]
+pub struct BytePattern {
+    /// 每個位元組的 (值, 遮罩)
+    bytes: Vec<(u8, u8)>,
+}
+
+impl BytePattern {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let digits: Vec<char> = text
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != ',')
+            .collect();
+        if digits.is_empty() {
+            return Err("Empty byte pattern".to_string());
+        }
+        if !digits.len().is_multiple_of(2) {
+            return Err("Byte pattern must contain whole bytes (two hex digits each)".to_string());
+        }
+        let nibble = |c: char| -> Result<(u8, u8), String> {
+            if c == '?' {
+                Ok((0, 0))
+            } else {
+                c.to_digit(16)
+                    .map(|d| (d as u8, 0x0F))
+                    .ok_or_else(|| format!("Invalid hex digit '{}'", c))
+            }
+        };
+        let bytes = digits
+            .chunks(2)
+            .map(|pair| {
+                let (hi, hi_mask) = nibble(pair[0])?;
+                let (lo, lo_mask) = nibble(pair[1])?;
+                Ok(((hi << 4) | lo, (hi_mask << 4) | lo_mask))
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+        Ok(Self { bytes })
+    }
+
+    /// 樣式是否出現在資料中的任一位置
+    pub fn find_in(&self, data: &[u8]) -> bool {
+        data.windows(self.bytes.len()).any(|window| {
+            window
+                .iter()
+                .zip(&self.bytes)
+                .all(|(b, (value, mask))| b & mask == *value)
+        })
+    }
+}
+
+/// 搜尋條件：位元組樣式比對 payload，正規表示式比對訊框的顯示文字
+#[derive(Debug, Clone)]
+pub enum SearchQuery {
+    Bytes(BytePattern),
+    Text(Regex),
+}
+
+impl SearchQuery {
+    pub fn bytes(text: &str) -> Result<Self, String> {
+        BytePattern::parse(text).map(SearchQuery::Bytes)
+    }
+
+    pub fn regex(text: &str) -> Result<Self, String> {
+        Regex::new(text)
+            .map(SearchQuery::Text)
+            .map_err(|e| format!("Invalid regex: {}", e))
+    }
+
+    /// `text` 為訊框在畫面上的顯示文字，只有正規表示式搜尋會用到
+    pub fn matches(&self, frame: &CanFrame, text: &str) -> bool {
+        match self {
+            SearchQuery::Bytes(pattern) => pattern.find_in(frame.payload()),
+            SearchQuery::Text(regex) => regex.is_match(text),
+        }
+    }
+}
@@ -0,0 +1,148 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 自測訊框使用的 ID
+pub const SELF_TEST_ID: u32 = 0x7FE;
+/// 相鄰兩筆自測訊框的間隔
+const SEND_INTERVAL: Duration = Duration::from_millis(5);
+/// 最後一筆送出後等待回收的時間
+const RECEIVE_TIMEOUT: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum SelfTestStatus {
+    Idle,
+    Running,
+    Passed,
+    Failed(String),
+}
+
+/// 自測狀態：送出一組已知內容的訊框，確認每一筆都能原封不動地收回
+#[derive(Debug)]
+pub struct SelfTest {
+    status: SelfTestStatus,
+    expected: Vec<CanFrame>,
+    received: Vec<bool>,
+    corrupted: usize,
+}
+
+impl Default for SelfTest {
+    fn default() -> Self {
+        Self {
+            status: SelfTestStatus::Idle,
+            expected: Vec::new(),
+            received: Vec::new(),
+            corrupted: 0,
+        }
+    }
+}
+
+/// 第 `seq` 筆自測訊框的內容，包含互補位元以檢查資料線
+fn test_payload(seq: u8) -> [u8; 8] {
+    [seq, !seq, 0x55, 0xAA, seq ^ 0x5A, 0x00, 0xFF, 0xC3]
+}
+
+impl SelfTest {
+    pub fn status(&self) -> &SelfTestStatus {
+        &self.status
+    }
+
+    /// (已收回筆數, 總筆數)
+    pub fn progress(&self) -> (usize, usize) {
+        (
+            self.received.iter().filter(|&&r| r).count(),
+            self.expected.len(),
+        )
+    }
+
+    /// 處理一筆收到的訊框
+    pub fn observe(&mut self, frame: &CanFrame) {
+        if self.status != SelfTestStatus::Running || frame.id != SELF_TEST_ID {
+            return;
+        }
+        let Some(&seq) = frame.payload().first() else {
+            self.corrupted += 1;
+            return;
+        };
+        match self.expected.get(seq as usize) {
+            Some(expected)
+                if expected.channel == frame.channel && expected.payload() == frame.payload() =>
+            {
+                self.received[seq as usize] = true;
+            }
+            _ => self.corrupted += 1,
+        }
+    }
+
+    fn begin(&mut self, frames: Vec<CanFrame>) {
+        self.received = vec![false; frames.len()];
+        self.expected = frames;
+        self.corrupted = 0;
+        self.status = SelfTestStatus::Running;
+    }
+
+    fn is_complete(&self) -> bool {
+        self.received.iter().all(|&r| r)
+    }
+
+    fn finish(&mut self) -> SelfTestStatus {
+        let (received, total) = self.progress();
+        self.status = if received == total && self.corrupted == 0 {
+            SelfTestStatus::Passed
+        } else if received == 0 && self.corrupted == 0 {
+            SelfTestStatus::Failed(format!(
+                "No test frames received back (0/{}); enable loopback or self-reception",
+                total
+            ))
+        } else {
+            SelfTestStatus::Failed(format!(
+                "{}/{} frames received, {} corrupted",
+                received, total, self.corrupted
+            ))
+        };
+        self.status.clone()
+    }
+}
+
+/// 在背景執行緒執行自測；收到的訊框需由接收流程交給 `SelfTest::observe`
+pub fn run_self_test<F>(
+    test: Arc<Mutex<SelfTest>>,
+    can_app: SharedCan,
+    channel: u32,
+    count: u8,
+    log: F,
+) where
+    F: Fn(String) + Send + 'static,
+{
+    let frames: Vec<CanFrame> = (0..count)
+        .map(|seq| CanFrame::new(channel, SELF_TEST_ID, &test_payload(seq)))
+        .collect();
+    test.lock().unwrap().begin(frames.clone());
+    thread::spawn(move || {
+        log(format!(
+            "Self test started on CH{} ({} frames)",
+            channel,
+            frames.len()
+        ));
+        for frame in frames.iter() {
+            if let Err(e) = can_app.send_frame(frame) {
+                let mut test = test.lock().unwrap();
+                test.status = SelfTestStatus::Failed(format!("Transmit failed: {}", e));
+                log(format!("Self test failed: transmit failed: {}", e));
+                return;
+            }
+            thread::sleep(SEND_INTERVAL);
+        }
+        let deadline = Instant::now() + RECEIVE_TIMEOUT;
+        while Instant::now() < deadline && !test.lock().unwrap().is_complete() {
+            thread::sleep(Duration::from_millis(10));
+        }
+        match test.lock().unwrap().finish() {
+            SelfTestStatus::Passed => log("Self test passed".to_string()),
+            SelfTestStatus::Failed(reason) => log(format!("Self test failed: {}", reason)),
+            _ => {}
+        }
+    });
+}
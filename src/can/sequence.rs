@@ -0,0 +1,218 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::config::{SequenceConfig, SequenceStep};
+use crate::can::conformance::{CheckKind, CheckResult};
+use crate::can::events::{EventCategory, EventLog, Severity};
+
+use std::collections::VecDeque;
+
+/// 保留的偏差紀錄數量上限
+const DEVIATION_CAPACITY: usize = 1000;
+
+/// 一次偏離預期序列的紀錄
+#[derive(Debug, Clone)]
+pub struct SequenceDeviation {
+    pub sequence: String,
+    pub timestamp: u64,
+    /// 發生偏差時等待的步驟（由 1 起算）
+    pub step: usize,
+    pub message: String,
+}
+
+struct SequenceRun {
+    config: SequenceConfig,
+    /// 等待中的步驟索引，0 表示尚未觸發
+    next: usize,
+    /// 上一個步驟出現的時間
+    step_time: u64,
+    started: u64,
+    completed: u64,
+    deviations: u64,
+}
+
+fn describe(step: &SequenceStep) -> String {
+    match &step.label {
+        Some(label) => format!("0x{:X} ({})", step.id, label),
+        None => format!("0x{:X}", step.id),
+    }
+}
+
+impl SequenceRun {
+    fn start(&mut self, timestamp: u64) {
+        self.started += 1;
+        self.step_time = timestamp;
+        self.next = 1;
+        self.finish_if_done();
+    }
+
+    fn finish_if_done(&mut self) {
+        if self.next >= self.config.steps.len() {
+            self.completed += 1;
+            self.next = 0;
+        }
+    }
+}
+
+/// 依 YAML 定義的訊息序列驗證即時流量，記錄被測裝置偏離的位置
+#[derive(Default)]
+pub struct SequenceValidator {
+    runs: Vec<SequenceRun>,
+    deviations: VecDeque<SequenceDeviation>,
+}
+
+impl SequenceValidator {
+    /// 以 YAML 設定取代目前的序列，並清除先前的結果
+    pub fn load(&mut self, sequences: &[SequenceConfig]) {
+        self.runs = sequences
+            .iter()
+            .filter(|s| !s.steps.is_empty())
+            .map(|config| SequenceRun {
+                config: config.clone(),
+                next: 0,
+                step_time: 0,
+                started: 0,
+                completed: 0,
+                deviations: 0,
+            })
+            .collect();
+        self.deviations.clear();
+    }
+
+    /// 清除結果但保留序列定義
+    pub fn reset(&mut self) {
+        for run in &mut self.runs {
+            run.next = 0;
+            run.started = 0;
+            run.completed = 0;
+            run.deviations = 0;
+        }
+        self.deviations.clear();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.runs.is_empty()
+    }
+
+    /// 處理一筆收到的訊框
+    pub fn process(&mut self, frame: &CanFrame, events: &mut EventLog) {
+        for i in 0..self.runs.len() {
+            let run = &mut self.runs[i];
+            let steps = &run.config.steps;
+            if run.next == 0 {
+                if frame.id == steps[0].id {
+                    run.start(frame.timestamp);
+                }
+                continue;
+            }
+            let expected = &steps[run.next];
+            let elapsed_us = frame.timestamp.saturating_sub(run.step_time);
+            let message = if frame.id == expected.id {
+                match expected.within_ms.filter(|ms| elapsed_us > ms * 1000) {
+                    Some(limit) => format!(
+                        "step {} {} arrived after {:.1} ms (limit {} ms)",
+                        run.next + 1,
+                        describe(expected),
+                        elapsed_us as f64 / 1000.0,
+                        limit
+                    ),
+                    None => {
+                        run.next += 1;
+                        run.step_time = frame.timestamp;
+                        run.finish_if_done();
+                        continue;
+                    }
+                }
+            } else if frame.id == steps[0].id {
+                format!(
+                    "restarted by {} while waiting for step {} {}",
+                    describe(&steps[0]),
+                    run.next + 1,
+                    describe(expected)
+                )
+            } else if let Some(later) = steps[run.next + 1..].iter().find(|s| s.id == frame.id) {
+                // 先前步驟的 ID 重複出現（例如週期訊框）不視為偏差，只檢查跳過步驟
+                format!(
+                    "expected step {} {}, got {}",
+                    run.next + 1,
+                    describe(expected),
+                    describe(later)
+                )
+            } else {
+                continue;
+            };
+            let restart = frame.id == steps[0].id;
+            self.deviate(i, frame.timestamp, message, events);
+            if restart {
+                self.runs[i].start(frame.timestamp);
+            }
+        }
+    }
+
+    /// 定期檢查等待中的步驟是否已逾時
+    pub fn poll(&mut self, now: u64, events: &mut EventLog) {
+        for i in 0..self.runs.len() {
+            let run = &self.runs[i];
+            if run.next == 0 {
+                continue;
+            }
+            let expected = &run.config.steps[run.next];
+            let Some(limit) = expected.within_ms else {
+                continue;
+            };
+            if now.saturating_sub(run.step_time) > limit * 1000 {
+                let message = format!(
+                    "step {} {} not seen within {} ms",
+                    run.next + 1,
+                    describe(expected),
+                    limit
+                );
+                self.deviate(i, now, message, events);
+            }
+        }
+    }
+
+    /// 記錄偏差並回到等待觸發的狀態
+    fn deviate(&mut self, index: usize, timestamp: u64, message: String, events: &mut EventLog) {
+        let run = &mut self.runs[index];
+        run.deviations += 1;
+        let deviation = SequenceDeviation {
+            sequence: run.config.name.clone(),
+            timestamp,
+            step: run.next + 1,
+            message,
+        };
+        run.next = 0;
+        events.push(
+            timestamp,
+            Severity::Warning,
+            EventCategory::Sequence,
+            format!("Sequence '{}': {}", deviation.sequence, deviation.message),
+        );
+        if self.deviations.len() >= DEVIATION_CAPACITY {
+            self.deviations.pop_front();
+        }
+        self.deviations.push_back(deviation);
+    }
+
+    pub fn deviations(&self) -> impl DoubleEndedIterator<Item = &SequenceDeviation> {
+        self.deviations.iter()
+    }
+
+    /// 每個序列一筆檢查結果，與一致性檢查一起顯示
+    pub fn results(&self) -> Vec<CheckResult> {
+        self.runs
+            .iter()
+            .map(|run| CheckResult {
+                kind: CheckKind::Sequence,
+                target: run.config.name.clone(),
+                checked: run.started,
+                violations: run.deviations,
+                detail: format!(
+                    "{} steps, {} of {} runs completed",
+                    run.config.steps.len(),
+                    run.completed,
+                    run.started
+                ),
+            })
+            .collect()
+    }
+}
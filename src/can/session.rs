@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// 擷取工作階段的說明資料，寫入匯出的紀錄檔、快照與報告，日後可追溯擷取來源
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SessionInfo {
+    pub operator: String,
+    /// 被測裝置（DUT）識別，例如序號或料號
+    pub dut: String,
+    pub description: String,
+    /// 自由格式備註，可多行
+    pub notes: String,
+}
+
+impl SessionInfo {
+    pub fn is_empty(&self) -> bool {
+        self.fields()
+            .iter()
+            .all(|(_, value)| value.trim().is_empty())
+    }
+
+    /// 欄位名稱與內容，依顯示順序
+    pub fn fields(&self) -> [(&'static str, &str); 4] {
+        [
+            ("operator", &self.operator),
+            ("dut", &self.dut),
+            ("description", &self.description),
+            ("notes", &self.notes),
+        ]
+    }
+
+    /// 紀錄檔開頭的註解行，例如 `# operator: Alice`；多行備註每行各自一行
+    pub fn header_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (name, value) in self.fields() {
+            for line in value.lines().filter(|l| !l.trim().is_empty()) {
+                lines.push(format!("# {}: {}", name, line.trim_end()));
+            }
+        }
+        lines
+    }
+
+    /// 由 `header_lines` 產生的註解行還原；不認得的行回傳 false
+    pub fn parse_header_line(&mut self, line: &str) -> bool {
+        let Some((name, value)) = line
+            .trim_end()
+            .strip_prefix("# ")
+            .and_then(|rest| rest.split_once(": "))
+        else {
+            return false;
+        };
+        let field = match name {
+            "operator" => &mut self.operator,
+            "dut" => &mut self.dut,
+            "description" => &mut self.description,
+            "notes" => &mut self.notes,
+            _ => return false,
+        };
+        if !field.is_empty() {
+            field.push('\n');
+        }
+        field.push_str(value);
+        true
+    }
+}
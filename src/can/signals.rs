@@ -0,0 +1,298 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::config::{ByteOrder, CanbusConfigEntry, RawType};
+use crate::can::units::DisplayUnits;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+
+/// 依位元位置讀出原始值（未做符號延伸）；長度為 0、超過 64 或資料不足時回傳 None
+pub fn read_bits(
+    data: &[u8],
+    start_bit: u32,
+    bit_length: u32,
+    byte_order: ByteOrder,
+) -> Option<u64> {
+    if bit_length == 0 || bit_length > 64 {
+        return None;
+    }
+    let mut raw = 0u64;
+    for pos in byte_order.bit_positions(start_bit, bit_length) {
+        let byte = data.get(pos as usize / 8)?;
+        raw = (raw << 1) | ((byte >> (pos % 8)) & 1) as u64;
+    }
+    Some(raw)
+}
+
+/// 將原始值的低 `bit_length` 個位元寫入對應位置，其他位元不變；
+/// 位元落在資料之外時回傳該位元位置
+pub fn write_bits(
+    data: &mut [u8],
+    start_bit: u32,
+    bit_length: u32,
+    byte_order: ByteOrder,
+    raw: u64,
+) -> Result<(), u32> {
+    for (i, pos) in byte_order.bit_positions(start_bit, bit_length).enumerate() {
+        let byte = data.get_mut(pos as usize / 8).ok_or(pos)?;
+        let mask = 1u8 << (pos % 8);
+        if (raw >> (bit_length - 1 - i as u32)) & 1 == 1 {
+            *byte |= mask;
+        } else {
+            *byte &= !mask;
+        }
+    }
+    Ok(())
+}
+
+/// 依 `CanbusConfigEntry` 從訊框資料萃取物理值
+///
+/// 位置由 index/len（位元組）或 start_bit/bit_length（位元）指定，
+/// `endian` 為 little（Intel）或 big（Motorola）；
+/// `type` 以 "u" 開頭（如 uint16）視為無號數，其餘依長度做符號延伸，可用 `signed` 覆寫；
+/// float/double 以 IEEE 754 解讀。原始值再乘上 `scale` 加上 `offset`。
+pub fn extract_value(entry: &CanbusConfigEntry, data: &[u8]) -> Option<f64> {
+    let (start_bit, bits) = entry.bit_layout();
+    let raw = read_bits(data, start_bit, bits, entry.endian)?;
+    let value = match entry.raw_type() {
+        RawType::Unsigned => raw as f64,
+        // 依實際長度做符號延伸
+        RawType::Signed => {
+            let shift = 64 - bits;
+            ((raw << shift) as i64 >> shift) as f64
+        }
+        RawType::Float if bits == 32 => f32::from_bits(raw as u32) as f64,
+        RawType::Double if bits == 64 => f64::from_bits(raw),
+        RawType::Float | RawType::Double => return None,
+    };
+    Some(value * entry.scale + entry.offset)
+}
+
+/// 原始值可表示的範圍，依長度與型別
+fn raw_range(entry: &CanbusConfigEntry) -> (f64, f64) {
+    let bits = entry.bit_layout().1.clamp(1, 64);
+    match entry.raw_type() {
+        RawType::Unsigned => (0.0, (u64::MAX >> (64 - bits)) as f64),
+        RawType::Signed => {
+            let half = (1u64 << (bits - 1)) as f64;
+            (-half, half - 1.0)
+        }
+        RawType::Float => (f32::MIN as f64, f32::MAX as f64),
+        RawType::Double => (f64::MIN, f64::MAX),
+    }
+}
+
+/// 訊號物理值可表示的範圍，依長度、型別與 scale/offset
+pub fn value_range(entry: &CanbusConfigEntry) -> (f64, f64) {
+    let (min, max) = raw_range(entry);
+    let (a, b) = (
+        min * entry.scale + entry.offset,
+        max * entry.scale + entry.offset,
+    );
+    (a.min(b), a.max(b))
+}
+
+/// `extract_value` 的反向：將物理值換回原始值，整數型別四捨五入後寫入訊號的位元，
+/// 同一位元組中其他訊號的位元不變；資料長度不足時補 0
+pub fn encode_value(
+    entry: &CanbusConfigEntry,
+    value: f64,
+    data: &mut Vec<u8>,
+) -> Result<(), String> {
+    let (start_bit, bits) = entry.bit_layout();
+    if bits == 0 || bits > 64 {
+        return Err(format!(
+            "Signal '{}': unsupported length of {} bits",
+            entry.key, bits
+        ));
+    }
+    let scaled = (value - entry.offset) / entry.scale;
+    let raw_type = entry.raw_type();
+    let raw_value = match raw_type {
+        RawType::Unsigned | RawType::Signed => scaled.round(),
+        RawType::Float | RawType::Double => scaled,
+    };
+    let (min, max) = raw_range(entry);
+    if !(min..=max).contains(&raw_value) {
+        let (min, max) = value_range(entry);
+        return Err(format!(
+            "Signal '{}': {} is out of range {}..={}",
+            entry.key, value, min, max
+        ));
+    }
+    let end = entry.byte_end();
+    if data.len() < end {
+        data.resize(end, 0);
+    }
+    let raw = match raw_type {
+        RawType::Float => (raw_value as f32).to_bits() as u64,
+        RawType::Double => raw_value.to_bits(),
+        // 負數以二補數表示，只寫入訊號長度內的位元
+        _ if raw_value < 0.0 => raw_value as i64 as u64,
+        _ => raw_value as u64,
+    };
+    write_bits(data, start_bit, bits, entry.endian, raw)
+        .map_err(|pos| format!("Signal '{}': bit {} is outside the message", entry.key, pos))
+}
+
+/// 單一訊號的目前值與自上次重設以來的統計
+#[derive(Debug, Clone)]
+pub struct SignalState {
+    pub key: String,
+    pub value: Option<f64>,
+    /// 目前值在 `values` 中的狀態名稱，沒有定義時為 None
+    pub label: Option<String>,
+    /// 最後更新時間（微秒）
+    pub updated_at: u64,
+    pub min: f64,
+    pub max: f64,
+    pub sum: f64,
+    pub count: u64,
+    /// 統計起算時間（微秒），0 表示尚未收到任何值
+    pub stats_since: u64,
+}
+
+impl SignalState {
+    fn new(key: &str) -> Self {
+        Self {
+            key: key.to_string(),
+            value: None,
+            label: None,
+            updated_at: 0,
+            min: f64::INFINITY,
+            max: f64::NEG_INFINITY,
+            sum: 0.0,
+            count: 0,
+            stats_since: 0,
+        }
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        (self.count > 0).then(|| self.sum / self.count as f64)
+    }
+
+    fn update(&mut self, value: f64, timestamp: u64) {
+        self.value = Some(value);
+        self.updated_at = timestamp;
+        if self.count == 0 && self.stats_since == 0 {
+            self.stats_since = timestamp;
+        }
+        self.min = self.min.min(value);
+        self.max = self.max.max(value);
+        self.sum += value;
+        self.count += 1;
+    }
+
+    /// 重設統計，保留目前值；統計自下一筆值起算
+    fn reset_stats(&mut self, now: u64) {
+        self.min = f64::INFINITY;
+        self.max = f64::NEG_INFINITY;
+        self.sum = 0.0;
+        self.count = 0;
+        self.stats_since = now;
+    }
+}
+
+/// 解碼 YAML canbus_config 中所有訊號並保存其狀態
+#[derive(Debug, Default)]
+pub struct SignalTable {
+    entries: Vec<CanbusConfigEntry>,
+    by_id: HashMap<u32, Vec<usize>>,
+    states: BTreeMap<String, SignalState>,
+}
+
+impl SignalTable {
+    /// 以新的設定取代目前的訊號定義
+    pub fn load(&mut self, entries: &[CanbusConfigEntry]) {
+        self.entries = entries.to_vec();
+        self.by_id.clear();
+        self.states.clear();
+        for (i, entry) in self.entries.iter().enumerate() {
+            self.by_id.entry(entry.id).or_default().push(i);
+            self.states
+                .entry(entry.key.clone())
+                .or_insert_with(|| SignalState::new(&entry.key));
+        }
+    }
+
+    /// 處理一筆訊框，更新所有對應的訊號
+    pub fn process(&mut self, frame: &CanFrame) {
+        let Some(indices) = self.by_id.get(&frame.id) else {
+            return;
+        };
+        for &i in indices {
+            let entry = &self.entries[i];
+            if let Some(value) = extract_value(entry, frame.payload()) {
+                if let Some(state) = self.states.get_mut(&entry.key) {
+                    state.update(value, frame.timestamp);
+                    state.label = entry.value_name(value).map(str::to_string);
+                }
+            }
+        }
+    }
+
+    /// 目前載入的訊號定義
+    pub fn entries(&self) -> &[CanbusConfigEntry] {
+        &self.entries
+    }
+
+    pub fn signals(&self) -> impl Iterator<Item = &SignalState> {
+        self.states.values()
+    }
+
+    pub fn get(&self, key: &str) -> Option<&SignalState> {
+        self.states.get(key)
+    }
+
+    /// 重設所有訊號的統計
+    pub fn reset_all(&mut self, now: u64) {
+        for state in self.states.values_mut() {
+            state.reset_stats(now);
+        }
+    }
+
+    /// 重設單一訊號的統計
+    pub fn reset(&mut self, key: &str, now: u64) {
+        if let Some(state) = self.states.get_mut(key) {
+            state.reset_stats(now);
+        }
+    }
+
+    /// 匯出目前值與統計為 CSV，數值以 `units` 中選擇的顯示單位輸出
+    pub fn export_csv(
+        &self,
+        file_path: &str,
+        units: &DisplayUnits,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        writeln!(writer, "key,unit,value,min,max,avg,samples,stats_since_us")?;
+        for state in self.signals() {
+            let convert = |v: f64| units.convert(&state.key, v);
+            let (min, max) = if state.count > 0 {
+                let (a, b) = (convert(state.min), convert(state.max));
+                (a.min(b).to_string(), a.max(b).to_string())
+            } else {
+                (String::new(), String::new())
+            };
+            writeln!(
+                writer,
+                "{},{},{},{},{},{},{},{}",
+                state.key,
+                units.unit(&state.key),
+                state
+                    .value
+                    .map(|v| convert(v).to_string())
+                    .unwrap_or_default(),
+                min,
+                max,
+                state
+                    .mean()
+                    .map(|v| convert(v).to_string())
+                    .unwrap_or_default(),
+                state.count,
+                state.stats_since
+            )?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
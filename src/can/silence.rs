@@ -0,0 +1,108 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::events::{EventCategory, EventLog, Severity};
+
+use std::collections::{BTreeMap, VecDeque};
+
+/// 保留的靜默區間數量上限
+const GAP_CAPACITY: usize = 1000;
+pub const DEFAULT_SILENCE_THRESHOLD_MS: u64 = 1000;
+
+/// 一段沒有收到訊框的期間
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SilentGap {
+    pub channel: u32,
+    /// 最後一筆訊框的時間（微秒）
+    pub start_us: u64,
+    /// 恢復通訊的時間，仍在靜默中為 None
+    pub end_us: Option<u64>,
+}
+
+impl SilentGap {
+    pub fn duration_us(&self, now: u64) -> u64 {
+        self.end_us.unwrap_or(now).saturating_sub(self.start_us)
+    }
+}
+
+/// 偵測各通道超過門檻時間沒有訊框的靜默期間（例如休眠/喚醒測試），
+/// 進入與結束靜默時各寫入一筆事件
+pub struct SilenceDetector {
+    pub threshold_ms: u64,
+    last_seen: BTreeMap<u32, u64>,
+    gaps: VecDeque<SilentGap>,
+}
+
+impl Default for SilenceDetector {
+    fn default() -> Self {
+        Self {
+            threshold_ms: DEFAULT_SILENCE_THRESHOLD_MS,
+            last_seen: BTreeMap::new(),
+            gaps: VecDeque::new(),
+        }
+    }
+}
+
+impl SilenceDetector {
+    /// 收到訊框時更新；若該通道正處於靜默，結束該區間
+    pub fn process(&mut self, frame: &CanFrame, events: &mut EventLog) {
+        self.last_seen.insert(frame.channel, frame.timestamp);
+        if let Some(gap) = self
+            .gaps
+            .iter_mut()
+            .rev()
+            .find(|g| g.channel == frame.channel && g.end_us.is_none())
+        {
+            gap.end_us = Some(frame.timestamp);
+            events.push(
+                frame.timestamp,
+                Severity::Info,
+                EventCategory::Silence,
+                format!(
+                    "CAN {} traffic resumed after {:.3} s of silence",
+                    frame.channel,
+                    gap.duration_us(frame.timestamp) as f64 / 1e6
+                ),
+            );
+        }
+    }
+
+    /// 定期檢查各通道是否已超過門檻沒有訊框
+    pub fn poll(&mut self, now: u64, events: &mut EventLog) {
+        let threshold_us = self.threshold_ms * 1000;
+        for (&channel, &last) in &self.last_seen {
+            if now.saturating_sub(last) <= threshold_us
+                || self
+                    .gaps
+                    .iter()
+                    .any(|g| g.channel == channel && g.end_us.is_none())
+            {
+                continue;
+            }
+            if self.gaps.len() >= GAP_CAPACITY {
+                self.gaps.pop_front();
+            }
+            self.gaps.push_back(SilentGap {
+                channel,
+                start_us: last,
+                end_us: None,
+            });
+            events.push(
+                now,
+                Severity::Warning,
+                EventCategory::Silence,
+                format!(
+                    "CAN {} silent: no frames for more than {} ms",
+                    channel, self.threshold_ms
+                ),
+            );
+        }
+    }
+
+    pub fn gaps(&self) -> impl DoubleEndedIterator<Item = &SilentGap> {
+        self.gaps.iter()
+    }
+
+    pub fn clear(&mut self) {
+        self.last_seen.clear();
+        self.gaps.clear();
+    }
+}
@@ -0,0 +1,286 @@
+use crate::can::canbus::CanInterface;
+use crate::can::cantypes::{now_micros, CanFrame, FrameDirection};
+use crate::can::log_event::LogEvent;
+use flume::{Receiver, RecvTimeoutError, Sender};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// 模擬訊框的資料內容
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadPattern {
+    /// 小端序遞增計數
+    Counter,
+    /// 虛擬亂數
+    Random,
+    /// 固定資料
+    Constant,
+    /// 前兩個位元組為小端序 u16 正弦波（週期 10 秒）
+    Sine,
+}
+
+impl PayloadPattern {
+    pub const ALL: [PayloadPattern; 4] = [
+        PayloadPattern::Counter,
+        PayloadPattern::Random,
+        PayloadPattern::Constant,
+        PayloadPattern::Sine,
+    ];
+}
+
+/// 週期性產生的模擬訊框
+#[derive(Debug, Clone, PartialEq)]
+pub struct SimMessage {
+    pub channel: u32,
+    pub id: u32,
+    pub period_ms: u64,
+    /// 資料長度（0..=8）
+    pub len: usize,
+    pub pattern: PayloadPattern,
+    /// `Constant` 使用的資料
+    pub data: [u8; 8],
+}
+
+impl SimMessage {
+    pub fn new(id: u32, period_ms: u64, pattern: PayloadPattern) -> Self {
+        Self {
+            channel: 0,
+            id,
+            period_ms,
+            len: 8,
+            pattern,
+            data: [0; 8],
+        }
+    }
+}
+
+/// 依週期產生模擬流量，時間以啟動後經過的微秒數表示，方便重現
+pub struct TrafficGenerator {
+    messages: Vec<SimMessage>,
+    /// 每個訊息下一次送出的時間與已送出的次數
+    next_due: Vec<(u64, u64)>,
+    rng: u64,
+}
+
+impl TrafficGenerator {
+    pub fn new(messages: Vec<SimMessage>) -> Self {
+        let next_due = vec![(0, 0); messages.len()];
+        Self {
+            messages,
+            next_due,
+            rng: 0x2545_F491_4F6C_DD1D,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.messages.is_empty()
+    }
+
+    /// 最早的下一次送出時間
+    pub fn next_due_us(&self) -> Option<u64> {
+        self.next_due.iter().map(|&(due, _)| due).min()
+    }
+
+    /// 產生 `elapsed_us` 之前到期的訊框，依到期時間排序；時間戳記為到期時間
+    pub fn poll(&mut self, elapsed_us: u64) -> Vec<CanFrame> {
+        let mut frames = Vec::new();
+        for i in 0..self.messages.len() {
+            let period_us = self.messages[i].period_ms.max(1) * 1000;
+            while self.next_due[i].0 <= elapsed_us {
+                let (due, count) = self.next_due[i];
+                let mut frame = self.frame(i, count);
+                frame.timestamp = due;
+                frames.push(frame);
+                self.next_due[i] = (due + period_us, count + 1);
+            }
+        }
+        frames.sort_by_key(|f| f.timestamp);
+        frames
+    }
+
+    fn frame(&mut self, index: usize, count: u64) -> CanFrame {
+        let message = &self.messages[index];
+        let len = message.len.min(8);
+        let mut data = [0u8; 8];
+        match message.pattern {
+            PayloadPattern::Counter => data = count.to_le_bytes(),
+            PayloadPattern::Random => {
+                // xorshift64
+                for byte in data.iter_mut() {
+                    self.rng ^= self.rng << 13;
+                    self.rng ^= self.rng >> 7;
+                    self.rng ^= self.rng << 17;
+                    *byte = self.rng as u8;
+                }
+            }
+            PayloadPattern::Constant => data = message.data,
+            PayloadPattern::Sine => {
+                let t = (count * message.period_ms.max(1)) as f64 / 10_000.0;
+                let value = 32768.0 + 32767.0 * (t * std::f64::consts::TAU).sin();
+                data[..2].copy_from_slice(&(value as u16).to_le_bytes());
+            }
+        }
+        CanFrame::new(message.channel, message.id, &data[..len])
+    }
+}
+
+/// 模擬的 CAN 介面，不需要硬體
+///
+/// 透過 `injector()` 送入的訊框會出現在接收端；開啟自我接收時送出的訊框也會回送。
+/// 以 `with_traffic` 設定的訊息會在接收期間週期性產生。
+pub struct SimCanApp {
+    inject_tx: Sender<CanFrame>,
+    inject_rx: Receiver<CanFrame>,
+    self_reception: bool,
+    traffic: Vec<SimMessage>,
+    open: AtomicBool,
+    receiving: Arc<AtomicBool>,
+    join_handles: Mutex<Vec<thread::JoinHandle<()>>>,
+}
+
+impl Default for SimCanApp {
+    fn default() -> Self {
+        let (inject_tx, inject_rx) = flume::unbounded();
+        Self {
+            inject_tx,
+            inject_rx,
+            self_reception: true,
+            traffic: Vec::new(),
+            open: AtomicBool::new(false),
+            receiving: Arc::new(AtomicBool::new(false)),
+            join_handles: Mutex::new(Vec::new()),
+        }
+    }
+}
+
+impl SimCanApp {
+    pub fn new(self_reception: bool) -> Self {
+        Self {
+            self_reception,
+            ..Default::default()
+        }
+    }
+
+    /// 接收期間週期性產生的模擬流量
+    pub fn with_traffic(mut self, traffic: Vec<SimMessage>) -> Self {
+        self.traffic = traffic;
+        self
+    }
+
+    /// 注入模擬接收訊框的傳送端，可交給其他執行緒使用
+    pub fn injector(&self) -> Sender<CanFrame> {
+        self.inject_tx.clone()
+    }
+}
+
+impl CanInterface for SimCanApp {
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String> {
+        self.open.store(true, Ordering::SeqCst);
+        let _ = log_tx.send(LogEvent::DeviceOpened {
+            backend: "Simulated".to_string(),
+        });
+        Ok(())
+    }
+
+    fn close_device(&self, log_tx: Sender<LogEvent>) {
+        if self.open.swap(false, Ordering::SeqCst) {
+            let _ = log_tx.send(LogEvent::DeviceClosed {
+                backend: "Simulated".to_string(),
+                status: None,
+            });
+        }
+    }
+
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
+        self.receiving.store(true, Ordering::SeqCst);
+        let receiving_flag = Arc::clone(&self.receiving);
+        let inject_rx = self.inject_rx.clone();
+        let mut generator = TrafficGenerator::new(self.traffic.clone());
+        let handle = thread::spawn(move || {
+            let _ = log_tx.send(LogEvent::ChannelStarted { channel: 0 });
+            let started = Instant::now();
+            let start_us = now_micros();
+            'receive: while receiving_flag.load(Ordering::SeqCst) {
+                let elapsed_us = started.elapsed().as_micros() as u64;
+                for mut frame in generator.poll(elapsed_us) {
+                    frame.timestamp += start_us;
+                    if data_tx.send(frame).is_err() {
+                        break 'receive;
+                    }
+                }
+                let wait = generator
+                    .next_due_us()
+                    .map_or(POLL_INTERVAL, |due| {
+                        Duration::from_micros(due.saturating_sub(elapsed_us))
+                    })
+                    .min(POLL_INTERVAL);
+                match inject_rx.recv_timeout(wait) {
+                    Ok(mut frame) => {
+                        if frame.timestamp == 0 {
+                            frame.timestamp = now_micros();
+                        }
+                        if data_tx.send(frame).is_err() {
+                            break;
+                        }
+                    }
+                    Err(RecvTimeoutError::Timeout) => continue,
+                    Err(RecvTimeoutError::Disconnected) => break,
+                }
+            }
+            let _ = log_tx.send(LogEvent::ChannelStopped { channel: 0 });
+        });
+        self.join_handles.lock().unwrap().push(handle);
+    }
+
+    fn stop_receiving(&self) {
+        self.receiving.store(false, Ordering::SeqCst);
+        let mut handles = self.join_handles.lock().unwrap();
+        while let Some(handle) = handles.pop() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining simulator thread: {:?}", e);
+            }
+        }
+    }
+
+    fn read_board_info(&self, log_tx: Sender<LogEvent>) {
+        let _ = log_tx.send(LogEvent::BackendInfo {
+            description: "Simulated backend (no hardware)".to_string(),
+        });
+    }
+
+    fn echoes_transmit(&self) -> bool {
+        self.self_reception
+    }
+
+    /// 通道 0 與模擬流量使用的通道
+    fn channels(&self) -> Vec<u32> {
+        let mut channels: Vec<u32> = self.traffic.iter().map(|m| m.channel).collect();
+        channels.push(0);
+        channels.sort_unstable();
+        channels.dedup();
+        channels
+    }
+
+    fn clear_receive_buffer(&self) -> Result<(), String> {
+        self.inject_rx.drain();
+        Ok(())
+    }
+
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        if !self.open.load(Ordering::SeqCst) {
+            return Err("Simulated device not open".to_string());
+        }
+        if self.self_reception {
+            let mut echo = *frame;
+            echo.timestamp = now_micros();
+            echo.direction = FrameDirection::Tx;
+            let _ = self.inject_tx.send(echo);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,275 @@
+use crate::can::canbus::{receive_thread_stopped, CanInterface};
+use crate::can::cantypes::{now_micros, CanFrame, FrameProtocol, MAX_STANDARD_ID};
+use crate::can::log_event::LogEvent;
+use crate::can::presets::SLCAN_BAUD_RATES;
+use flume::Sender;
+use serialport::SerialPort;
+use std::io::{ErrorKind, Read};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+use std::time::Duration;
+
+/// USB CDC 轉接器忽略序列埠速率，實體 UART 轉接器多數預設為此值
+const SERIAL_BAUD: u32 = 115_200;
+const READ_TIMEOUT: Duration = Duration::from_millis(100);
+/// 超過此長度仍未收到結束字元時丟棄緩衝（最長的合法訊息為 `T` + 8 + 1 + 16 = 26 字元）
+const MAX_LINE_LEN: usize = 64;
+
+/// 將訊框編碼為 SLCAN 指令（不含結尾的 `\r`），僅支援 Classic CAN
+pub fn encode_frame(frame: &CanFrame) -> Result<String, String> {
+    if frame.protocol != FrameProtocol::Classic || frame.data.len() > 8 {
+        return Err("SLCAN supports classic CAN frames only".to_string());
+    }
+    let extended = frame.extended || frame.id > MAX_STANDARD_ID;
+    let mut text = match (extended, frame.rtr) {
+        (false, false) => format!("t{:03X}", frame.id),
+        (false, true) => format!("r{:03X}", frame.id),
+        (true, false) => format!("T{:08X}", frame.id),
+        (true, true) => format!("R{:08X}", frame.id),
+    };
+    text.push_str(&frame.data.len().to_string());
+    if !frame.rtr {
+        for byte in frame.payload() {
+            text.push_str(&format!("{:02X}", byte));
+        }
+    }
+    Ok(text)
+}
+
+/// 解析一行 SLCAN 接收訊息（`tiiildd..`、`Tiiiiiiiildd..`、`riiil`、`Riiiiiiiil`），
+/// 其他回應（例如 `z`、版本查詢）回傳 None；行尾可帶 4 位數的時間戳記，會被忽略
+pub fn parse_frame(line: &str) -> Option<CanFrame> {
+    let kind = line.chars().next()?;
+    let (id_len, extended, rtr) = match kind {
+        't' => (3, false, false),
+        'T' => (8, true, false),
+        'r' => (3, false, true),
+        'R' => (8, true, true),
+        _ => return None,
+    };
+    let id = u32::from_str_radix(line.get(1..1 + id_len)?, 16).ok()?;
+    let dlc: u8 = line.get(1 + id_len..2 + id_len)?.parse().ok()?;
+    if dlc > 8 || (!extended && id > MAX_STANDARD_ID) {
+        return None;
+    }
+    let mut frame = if rtr {
+        CanFrame::new_remote(0, id, dlc)
+    } else {
+        let start = 2 + id_len;
+        let data_text = line.get(start..start + dlc as usize * 2)?;
+        let data: Vec<u8> = (0..data_text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&data_text[i..i + 2], 16))
+            .collect::<Result<_, _>>()
+            .ok()?;
+        CanFrame::new(0, id, &data)
+    };
+    frame.extended = extended;
+    Some(frame)
+}
+
+/// 以 `\r` 切割序列埠資料流；`\x07`（BEL）為轉接器回報的指令錯誤
+#[derive(Default)]
+pub struct LineSplitter {
+    buffer: Vec<u8>,
+}
+
+impl LineSplitter {
+    /// 加入讀到的位元組，回傳完整的行（不含結束字元）
+    pub fn push(&mut self, bytes: &[u8]) -> Vec<String> {
+        let mut lines = Vec::new();
+        for &byte in bytes {
+            match byte {
+                b'\r' | b'\n' | 0x07 => {
+                    if !self.buffer.is_empty() {
+                        lines.push(String::from_utf8_lossy(&self.buffer).into_owned());
+                        self.buffer.clear();
+                    }
+                }
+                _ if self.buffer.len() >= MAX_LINE_LEN => self.buffer.clear(),
+                _ => self.buffer.push(byte),
+            }
+        }
+        lines
+    }
+}
+
+/// SLCAN（Lawicel）序列埠轉接器，例如 CANable、USBtin；只有一個通道（0）
+pub struct SlcanApp {
+    port_name: String,
+    bitrate_kbps: u32,
+    listen_only: bool,
+    port: Mutex<Option<Box<dyn SerialPort>>>,
+    receiving: Arc<AtomicBool>,
+    join_handles: Mutex<Vec<thread::JoinHandle<()>>>,
+}
+
+impl SlcanApp {
+    /// `port_name` 例如 "COM3" 或 "/dev/ttyACM0"；波特率須為 `SLCAN_BAUD_RATES` 之一
+    pub fn new(port_name: &str, bitrate_kbps: u32) -> Self {
+        Self {
+            port_name: port_name.to_string(),
+            bitrate_kbps,
+            listen_only: false,
+            port: Mutex::new(None),
+            receiving: Arc::new(AtomicBool::new(false)),
+            join_handles: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// 以 `L` 指令開啟通道，轉接器不回 ACK 也不送出訊框
+    pub fn with_listen_only(mut self, listen_only: bool) -> Self {
+        self.listen_only = listen_only;
+        self
+    }
+
+    /// 系統上可用的序列埠名稱
+    pub fn available_ports() -> Vec<String> {
+        serialport::available_ports()
+            .map(|ports| ports.into_iter().map(|p| p.port_name).collect())
+            .unwrap_or_default()
+    }
+
+    fn command(port: &mut dyn SerialPort, command: &str) -> Result<(), String> {
+        port.write_all(command.as_bytes())
+            .and_then(|_| port.write_all(b"\r"))
+            .and_then(|_| port.flush())
+            .map_err(|e| format!("SLCAN command '{}' failed: {}", command, e))
+    }
+}
+
+impl CanInterface for SlcanApp {
+    fn open_device(&self, log_tx: Sender<LogEvent>) -> Result<(), String> {
+        // `Sn` 的 n 即為波特率在表中的索引
+        let bitrate_index = SLCAN_BAUD_RATES
+            .iter()
+            .position(|&rate| rate == self.bitrate_kbps)
+            .ok_or_else(|| format!("Unsupported SLCAN baud rate {}K", self.bitrate_kbps))?;
+        let open = || -> Result<Box<dyn SerialPort>, String> {
+            let mut port = serialport::new(&self.port_name, SERIAL_BAUD)
+                .timeout(READ_TIMEOUT)
+                .open()
+                .map_err(|e| format!("Failed to open {}: {}", self.port_name, e))?;
+            // 先關閉通道以清除上次未正常結束的狀態，此時的錯誤回應可忽略
+            Self::command(port.as_mut(), "C")?;
+            Self::command(port.as_mut(), &format!("S{}", bitrate_index))?;
+            Self::command(port.as_mut(), if self.listen_only { "L" } else { "O" })?;
+            Ok(port)
+        };
+        let port = open().inspect_err(|e| {
+            let _ = log_tx.send(LogEvent::DeviceError { detail: e.clone() });
+        })?;
+        *self.port.lock().unwrap() = Some(port);
+        let _ = log_tx.send(LogEvent::DeviceOpened {
+            backend: "SLCAN".to_string(),
+        });
+        let _ = log_tx.send(LogEvent::ChannelInitialized {
+            channel: 0,
+            settings: format!(
+                "Port: {}, BaudRate: {}K, Listen-only: {}",
+                self.port_name, self.bitrate_kbps, self.listen_only
+            ),
+        });
+        Ok(())
+    }
+
+    fn close_device(&self, log_tx: Sender<LogEvent>) {
+        if let Some(mut port) = self.port.lock().unwrap().take() {
+            let _ = Self::command(port.as_mut(), "C");
+            let _ = log_tx.send(LogEvent::DeviceClosed {
+                backend: "SLCAN".to_string(),
+                status: None,
+            });
+        }
+    }
+
+    fn start_receiving(&self, log_tx: Sender<LogEvent>, data_tx: Sender<CanFrame>) {
+        let mut reader = match self.port.lock().unwrap().as_ref().map(|p| p.try_clone()) {
+            Some(Ok(reader)) => reader,
+            Some(Err(e)) => {
+                let _ = log_tx.send(LogEvent::DeviceError {
+                    detail: format!("Failed to clone {}: {}", self.port_name, e),
+                });
+                return;
+            }
+            None => {
+                let _ = log_tx.send(LogEvent::NotInitialized {
+                    backend: "SLCAN".to_string(),
+                });
+                return;
+            }
+        };
+        self.receiving.store(true, Ordering::SeqCst);
+        let receiving_flag = Arc::clone(&self.receiving);
+        let handle = thread::spawn(move || {
+            let _ = log_tx.send(LogEvent::ChannelStarted { channel: 0 });
+            let mut splitter = LineSplitter::default();
+            let mut buf = [0u8; 1024];
+            while receiving_flag.load(Ordering::SeqCst) {
+                match reader.read(&mut buf) {
+                    Ok(n) => {
+                        let timestamp = now_micros();
+                        for line in splitter.push(&buf[..n]) {
+                            if let Some(mut frame) = parse_frame(&line) {
+                                frame.timestamp = timestamp;
+                                let _ = data_tx.send(frame);
+                            }
+                        }
+                    }
+                    Err(e) if e.kind() == ErrorKind::TimedOut => {}
+                    Err(e) => {
+                        let _ = log_tx.send(LogEvent::DeviceError {
+                            detail: format!("SLCAN read failed: {}", e),
+                        });
+                        break;
+                    }
+                }
+            }
+            let _ = log_tx.send(LogEvent::ChannelStopped { channel: 0 });
+        });
+        self.join_handles.lock().unwrap().push(handle);
+    }
+
+    fn stop_receiving(&self) {
+        self.receiving.store(false, Ordering::SeqCst);
+        let mut handles = self.join_handles.lock().unwrap();
+        while let Some(handle) = handles.pop() {
+            if let Err(e) = handle.join() {
+                tracing::error!("Error joining SLCAN thread: {:?}", e);
+            }
+        }
+    }
+
+    fn check_device(&self) -> Result<(), String> {
+        if receive_thread_stopped(&self.receiving, &self.join_handles) {
+            Err("SLCAN receive loop stopped".to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    fn read_board_info(&self, log_tx: Sender<LogEvent>) {
+        let open = self.port.lock().unwrap().is_some();
+        let _ = log_tx.send(LogEvent::BackendInfo {
+            description: format!(
+                "SLCAN adapter on {} ({})",
+                self.port_name,
+                if open { "open" } else { "closed" }
+            ),
+        });
+    }
+
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        if self.listen_only {
+            return Err("SLCAN channel is listen-only; cannot transmit".to_string());
+        }
+        let command = encode_frame(frame)?;
+        match self.port.lock().unwrap().as_mut() {
+            Some(port) => Self::command(port.as_mut(), &command),
+            None => Err("SLCAN device not open; cannot transmit".to_string()),
+        }
+    }
+}
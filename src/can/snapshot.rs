@@ -0,0 +1,236 @@
+use crate::can::session::SessionInfo;
+use crate::can::signals::SignalTable;
+use crate::can::stats::BusStatistics;
+use crate::can::units::DisplayUnits;
+use crate::can::version::LibraryVersion;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SignalSnapshot {
+    pub key: String,
+    pub unit: String,
+    pub value: Option<f64>,
+    pub updated_at_us: u64,
+    pub min: Option<f64>,
+    pub max: Option<f64>,
+    pub avg: Option<f64>,
+    pub samples: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct IdSnapshot {
+    pub channel: u32,
+    pub id: u32,
+    pub count: u64,
+    pub mean_period_ms: Option<f64>,
+    pub last_period_ms: Option<f64>,
+    pub last_seen_us: u64,
+    pub last_data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChannelSnapshot {
+    pub channel: u32,
+    pub frames: u64,
+    pub bytes: u64,
+    pub tx_frames: u64,
+    pub errors: u64,
+    pub bus_load_percent: f64,
+    pub last_seen_us: u64,
+    /// 統計起算時間（微秒）
+    pub since_us: u64,
+}
+
+/// 某一時刻的完整狀態報告：解碼訊號、各 ID 統計與通道狀態
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Snapshot {
+    pub taken_at: String,
+    pub timestamp_us: u64,
+    pub interface: String,
+    /// 裝置函式庫版本，供重現問題時比對
+    pub libraries: Vec<LibraryVersion>,
+    pub session: SessionInfo,
+    pub receiving: bool,
+    pub channels: Vec<ChannelSnapshot>,
+    pub signals: Vec<SignalSnapshot>,
+    pub ids: Vec<IdSnapshot>,
+}
+
+impl Snapshot {
+    pub fn capture(
+        taken_at: String,
+        timestamp_us: u64,
+        interface: &str,
+        receiving: bool,
+        signals: &SignalTable,
+        units: &DisplayUnits,
+        stats: &BusStatistics,
+    ) -> Self {
+        Self {
+            taken_at,
+            timestamp_us,
+            interface: interface.to_string(),
+            libraries: Vec::new(),
+            session: SessionInfo::default(),
+            receiving,
+            channels: stats
+                .channels()
+                .map(|c| ChannelSnapshot {
+                    channel: c.channel,
+                    frames: c.frames,
+                    bytes: c.bytes,
+                    tx_frames: c.tx_frames,
+                    errors: c.errors,
+                    bus_load_percent: c.bus_load,
+                    last_seen_us: c.last_seen,
+                    since_us: c.since,
+                })
+                .collect(),
+            signals: signals
+                .signals()
+                .map(|s| {
+                    let convert = |v: f64| units.convert(&s.key, v);
+                    let (a, b) = (convert(s.min), convert(s.max));
+                    SignalSnapshot {
+                        key: s.key.clone(),
+                        unit: units.unit(&s.key).to_string(),
+                        value: s.value.map(convert),
+                        updated_at_us: s.updated_at,
+                        min: (s.count > 0).then_some(a.min(b)),
+                        max: (s.count > 0).then_some(a.max(b)),
+                        avg: s.mean().map(convert),
+                        samples: s.count,
+                    }
+                })
+                .collect(),
+            ids: stats
+                .ids()
+                .map(|s| IdSnapshot {
+                    channel: s.channel,
+                    id: s.id,
+                    count: s.count,
+                    mean_period_ms: s.mean_period_ms(),
+                    last_period_ms: s.last_period_us.map(|us| us as f64 / 1000.0),
+                    last_seen_us: s.last_seen,
+                    last_data: s.last_frame.payload().to_vec(),
+                })
+                .collect(),
+        }
+    }
+
+    /// 附上目前介面的函式庫版本
+    pub fn with_libraries(mut self, libraries: Vec<LibraryVersion>) -> Self {
+        self.libraries = libraries;
+        self
+    }
+
+    /// 附上工作階段資料
+    pub fn with_session(mut self, session: SessionInfo) -> Self {
+        self.session = session;
+        self
+    }
+
+    /// 讀回以 JSON 儲存的快照，例如比對測試前後的狀態
+    pub fn load(file_path: &str) -> Result<Self, Box<dyn std::error::Error>> {
+        let reader = BufReader::new(File::open(file_path)?);
+        Ok(serde_json::from_reader(reader)?)
+    }
+
+    /// 依副檔名寫出報告：`.csv` 為分段 CSV，其他為 JSON
+    pub fn save(&self, file_path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = BufWriter::new(File::create(file_path)?);
+        if file_path.to_ascii_lowercase().ends_with(".csv") {
+            self.write_csv(&mut writer)?;
+        } else {
+            serde_json::to_writer_pretty(&mut writer, self)?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+
+    fn write_csv<W: Write>(&self, w: &mut W) -> std::io::Result<()> {
+        let opt = |v: Option<f64>| v.map(|v| v.to_string()).unwrap_or_default();
+        writeln!(w, "# snapshot")?;
+        writeln!(w, "taken_at,timestamp_us,interface,receiving")?;
+        writeln!(
+            w,
+            "{},{},{},{}",
+            self.taken_at, self.timestamp_us, self.interface, self.receiving
+        )?;
+        writeln!(w)?;
+        if !self.libraries.is_empty() {
+            writeln!(w, "# libraries")?;
+            writeln!(w, "library,version")?;
+            for l in &self.libraries {
+                writeln!(w, "{},{}", l.library, l.version)?;
+            }
+            writeln!(w)?;
+        }
+        if !self.session.is_empty() {
+            writeln!(w, "# session")?;
+            writeln!(w, "field,value")?;
+            for (name, value) in self.session.fields() {
+                writeln!(w, "{},\"{}\"", name, value.replace('"', "\"\""))?;
+            }
+            writeln!(w)?;
+        }
+        writeln!(w, "# channels")?;
+        writeln!(
+            w,
+            "channel,frames,bytes,tx_frames,errors,bus_load_percent,last_seen_us"
+        )?;
+        for c in &self.channels {
+            writeln!(
+                w,
+                "{},{},{},{},{},{:.2},{}",
+                c.channel,
+                c.frames,
+                c.bytes,
+                c.tx_frames,
+                c.errors,
+                c.bus_load_percent,
+                c.last_seen_us
+            )?;
+        }
+        writeln!(w)?;
+        writeln!(w, "# signals")?;
+        writeln!(w, "key,unit,value,updated_at_us,min,max,avg,samples")?;
+        for s in &self.signals {
+            writeln!(
+                w,
+                "{},{},{},{},{},{},{},{}",
+                s.key,
+                s.unit,
+                opt(s.value),
+                s.updated_at_us,
+                opt(s.min),
+                opt(s.max),
+                opt(s.avg),
+                s.samples
+            )?;
+        }
+        writeln!(w)?;
+        writeln!(w, "# ids")?;
+        writeln!(
+            w,
+            "channel,id,count,mean_period_ms,last_period_ms,last_seen_us,last_data"
+        )?;
+        for s in &self.ids {
+            let data: Vec<String> = s.last_data.iter().map(|b| format!("{:02X}", b)).collect();
+            writeln!(
+                w,
+                "{},0x{:X},{},{},{},{},{}",
+                s.channel,
+                s.id,
+                s.count,
+                opt(s.mean_period_ms),
+                opt(s.last_period_ms),
+                s.last_seen_us,
+                data.join(" ")
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,227 @@
+use crate::can::cantypes::{CanFrame, FrameProtocol};
+use std::collections::BTreeMap;
+
+/// 匯流排負載的計算區間（微秒）
+const LOAD_WINDOW_US: u64 = 1_000_000;
+
+/// 單一 (通道, ID) 的接收統計
+#[derive(Debug, Clone)]
+pub struct IdStats {
+    pub channel: u32,
+    pub id: u32,
+    pub count: u64,
+    /// 首次與最後收到時間（微秒）
+    pub first_seen: u64,
+    pub last_seen: u64,
+    pub last_frame: CanFrame,
+    /// 最近一次的週期（微秒），優先以裝置時間戳記計算
+    pub last_period_us: Option<u64>,
+    pub min_period_us: Option<u64>,
+    pub max_period_us: Option<u64>,
+    period_total_us: u64,
+    /// 週期與平均值差的平方和（Welford），用於計算標準差
+    period_m2: f64,
+}
+
+impl IdStats {
+    /// 平均週期（毫秒），少於兩筆時無法計算
+    pub fn mean_period_ms(&self) -> Option<f64> {
+        (self.count > 1).then(|| self.period_total_us as f64 / 1000.0 / (self.count - 1) as f64)
+    }
+
+    /// 週期的標準差（毫秒），少於三筆時無法計算
+    pub fn period_std_ms(&self) -> Option<f64> {
+        (self.count > 2).then(|| (self.period_m2 / (self.count - 2) as f64).sqrt() / 1000.0)
+    }
+
+    /// 週期的抖動，最大與最小週期的差（毫秒）
+    pub fn jitter_ms(&self) -> Option<f64> {
+        let (min, max) = (self.min_period_us?, self.max_period_us?);
+        Some((max - min) as f64 / 1000.0)
+    }
+}
+
+/// 估算一筆訊框在匯流排上佔用的位元數（含 stuffing 的概略值）
+///
+/// FD 訊框的資料段以仲裁速率估算，結果會偏高。
+pub fn frame_bits(frame: &CanFrame) -> u64 {
+    let overhead: u64 = if frame.extended { 67 } else { 47 };
+    // 遠端訊框不帶資料段
+    let data_bits = if frame.rtr {
+        0
+    } else {
+        frame.payload().len() as u64 * 8
+    };
+    let crc_extra: u64 = if frame.protocol == FrameProtocol::Classic {
+        0
+    } else {
+        10
+    };
+    // 平均約 10% 的 bit stuffing
+    (overhead + crc_extra + data_bits) * 11 / 10
+}
+
+/// 單一通道的統計
+#[derive(Debug, Clone, Default)]
+pub struct ChannelStats {
+    pub channel: u32,
+    pub frames: u64,
+    pub bytes: u64,
+    pub tx_frames: u64,
+    pub errors: u64,
+    pub last_seen: u64,
+    /// 通道位元速率（bit/s），0 表示未知
+    pub bitrate: u32,
+    /// 最近一個區間的匯流排負載（%）
+    pub bus_load: f64,
+    /// 最近一個區間的收發訊框數（每秒）
+    pub frame_rate: f64,
+    /// 統計起算時間（微秒）
+    pub since: u64,
+    window_start: u64,
+    window_bits: u64,
+    window_frames: u64,
+}
+
+impl ChannelStats {
+    fn new(channel: u32, bitrate: u32, now: u64) -> Self {
+        Self {
+            channel,
+            bitrate,
+            since: now,
+            window_start: now,
+            ..Default::default()
+        }
+    }
+
+    /// 累計一筆訊框的位元數，區間結束時更新負載
+    fn add_bits(&mut self, bits: u64, timestamp: u64) {
+        self.roll_window(timestamp);
+        self.window_bits += bits;
+        self.window_frames += 1;
+    }
+
+    fn roll_window(&mut self, now: u64) {
+        let elapsed = now.saturating_sub(self.window_start);
+        if elapsed < LOAD_WINDOW_US {
+            return;
+        }
+        self.bus_load = if self.bitrate > 0 {
+            (self.window_bits as f64 * 1_000_000.0 / elapsed as f64) / self.bitrate as f64 * 100.0
+        } else {
+            0.0
+        };
+        self.frame_rate = self.window_frames as f64 * 1_000_000.0 / elapsed as f64;
+        self.window_start = now;
+        self.window_bits = 0;
+        self.window_frames = 0;
+    }
+}
+
+/// 依通道與 ID 累計收發的訊框，每個通道可獨立重設
+#[derive(Debug, Default)]
+pub struct BusStatistics {
+    ids: BTreeMap<(u32, u32), IdStats>,
+    channels: BTreeMap<u32, ChannelStats>,
+    bitrates: BTreeMap<u32, u32>,
+}
+
+impl BusStatistics {
+    /// 設定通道的位元速率，用於計算匯流排負載
+    pub fn set_bitrate(&mut self, channel: u32, bitrate: u32) {
+        self.bitrates.insert(channel, bitrate);
+        if let Some(stats) = self.channels.get_mut(&channel) {
+            stats.bitrate = bitrate;
+        }
+    }
+
+    fn channel_mut(&mut self, channel: u32, now: u64) -> &mut ChannelStats {
+        let bitrate = self.bitrates.get(&channel).copied().unwrap_or(0);
+        self.channels
+            .entry(channel)
+            .or_insert_with(|| ChannelStats::new(channel, bitrate, now))
+    }
+
+    /// 處理一筆收到的訊框
+    pub fn process(&mut self, frame: &CanFrame) {
+        let stats = self
+            .ids
+            .entry((frame.channel, frame.id))
+            .or_insert_with(|| IdStats {
+                channel: frame.channel,
+                id: frame.id,
+                count: 0,
+                first_seen: frame.timestamp,
+                last_seen: frame.timestamp,
+                last_frame: *frame,
+                last_period_us: None,
+                min_period_us: None,
+                max_period_us: None,
+                period_total_us: 0,
+                period_m2: 0.0,
+            });
+        if stats.count > 0 {
+            let period = frame.delta_us(&stats.last_frame);
+            stats.last_period_us = Some(period);
+            stats.min_period_us = Some(stats.min_period_us.map_or(period, |min| min.min(period)));
+            stats.max_period_us = Some(stats.max_period_us.map_or(period, |max| max.max(period)));
+            // 目前已有 count - 1 個週期
+            let old_mean = stats.period_total_us as f64 / (stats.count - 1).max(1) as f64;
+            stats.period_total_us += period;
+            let new_mean = stats.period_total_us as f64 / stats.count as f64;
+            stats.period_m2 += (period as f64 - old_mean) * (period as f64 - new_mean);
+        }
+        stats.count += 1;
+        stats.last_seen = frame.timestamp;
+        stats.last_frame = *frame;
+
+        let channel = self.channel_mut(frame.channel, frame.timestamp);
+        channel.frames += 1;
+        channel.bytes += frame.payload().len() as u64;
+        channel.last_seen = frame.timestamp;
+        channel.add_bits(frame_bits(frame), frame.timestamp);
+    }
+
+    /// 記錄一筆成功送出的訊框
+    pub fn record_tx(&mut self, frame: &CanFrame) {
+        let channel = self.channel_mut(frame.channel, frame.timestamp);
+        channel.tx_frames += 1;
+        channel.add_bits(frame_bits(frame), frame.timestamp);
+    }
+
+    /// 記錄通道上的一次錯誤（例如傳送失敗）
+    pub fn record_error(&mut self, channel: u32, now: u64) {
+        self.channel_mut(channel, now).errors += 1;
+    }
+
+    /// 匯流排安靜時也要定期更新負載
+    pub fn poll(&mut self, now: u64) {
+        for channel in self.channels.values_mut() {
+            channel.roll_window(now);
+        }
+    }
+
+    /// 重設所有通道的統計，保留位元速率
+    pub fn reset(&mut self, now: u64) {
+        let channels: Vec<u32> = self.channels.keys().copied().collect();
+        for channel in channels {
+            self.reset_channel(channel, now);
+        }
+    }
+
+    /// 重設單一通道的統計（含該通道的各 ID 統計）
+    pub fn reset_channel(&mut self, channel: u32, now: u64) {
+        self.ids.retain(|&(ch, _), _| ch != channel);
+        if let Some(stats) = self.channels.get_mut(&channel) {
+            *stats = ChannelStats::new(channel, stats.bitrate, now);
+        }
+    }
+
+    pub fn ids(&self) -> impl Iterator<Item = &IdStats> {
+        self.ids.values()
+    }
+
+    pub fn channels(&self) -> impl Iterator<Item = &ChannelStats> {
+        self.channels.values()
+    }
+}
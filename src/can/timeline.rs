@@ -0,0 +1,70 @@
+/// 時間軸的區段數上限，超過時相鄰區段合併、區段寬度加倍
+const MAX_BUCKETS: usize = 512;
+const INITIAL_BUCKET_US: u64 = 100_000;
+
+/// 整段擷取的訊框密度：固定數量的時間區段，擷取越久每個區段越寬
+#[derive(Debug, Clone)]
+pub struct TrafficTimeline {
+    start_us: Option<u64>,
+    bucket_us: u64,
+    counts: Vec<u32>,
+}
+
+impl Default for TrafficTimeline {
+    fn default() -> Self {
+        Self {
+            start_us: None,
+            bucket_us: INITIAL_BUCKET_US,
+            counts: Vec::new(),
+        }
+    }
+}
+
+impl TrafficTimeline {
+    pub fn record(&mut self, timestamp: u64) {
+        let start = *self.start_us.get_or_insert(timestamp);
+        // 早於起點的訊框（例如裝置時間校正）計入第一個區段
+        let mut index = (timestamp.saturating_sub(start) / self.bucket_us) as usize;
+        while index >= MAX_BUCKETS {
+            self.coarsen();
+            index = (timestamp.saturating_sub(start) / self.bucket_us) as usize;
+        }
+        if index >= self.counts.len() {
+            self.counts.resize(index + 1, 0);
+        }
+        self.counts[index] = self.counts[index].saturating_add(1);
+    }
+
+    fn coarsen(&mut self) {
+        self.counts = self
+            .counts
+            .chunks(2)
+            .map(|pair| pair.iter().fold(0u32, |a, &b| a.saturating_add(b)))
+            .collect();
+        self.bucket_us *= 2;
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// 第一筆訊框的時間
+    pub fn start_us(&self) -> Option<u64> {
+        self.start_us
+    }
+
+    /// 最後一個區段的結束時間
+    pub fn end_us(&self) -> Option<u64> {
+        self.start_us
+            .map(|start| start + self.counts.len() as u64 * self.bucket_us)
+    }
+
+    pub fn bucket_us(&self) -> u64 {
+        self.bucket_us
+    }
+
+    /// 各區段的訊框數，依時間排列
+    pub fn counts(&self) -> &[u32] {
+        &self.counts
+    }
+}
@@ -0,0 +1,102 @@
+/// P 項增益：每筆樣本修正相位誤差的比例
+const KP: f64 = 0.01;
+/// 速率修正上限（±1000 ppm）
+const MAX_RATE: f64 = 1e-3;
+/// 計算 I 項時樣本間隔的下限（微秒），避免密集樣本造成過度修正
+const MIN_SAMPLE_US: f64 = 1000.0;
+/// 誤差超過此值（微秒）視為裝置重置或時鐘跳動，重新對齊
+const RESYNC_THRESHOLD_US: f64 = 1_000_000.0;
+
+/// 將裝置時間戳記計數器轉成與主機時鐘對齊、單調遞增的微秒時間戳記
+///
+/// 處理計數器溢位回繞，並以緩慢的 PI 控制（類似 PLL）修正裝置時鐘相對主機的
+/// 相位與速率漂移，長時間擷取時仍與主機牆上時鐘一致，同時保留裝置時間的細緻間隔。
+#[derive(Debug)]
+pub struct TimestampCorrector {
+    tick_us: f64,
+    wrap_ticks: u64,
+    last_raw: Option<u64>,
+    wraps: u64,
+    /// 上一筆的裝置時間與對應的估計主機時間
+    last_device_us: f64,
+    estimate_us: f64,
+    /// 裝置時鐘相對主機的速率修正（比例）
+    rate: f64,
+    last_output: u64,
+    resyncs: u64,
+}
+
+impl TimestampCorrector {
+    /// `tick_us` 為計數器每一刻度的微秒數，`counter_bits` 為計數器位元數
+    pub fn new(tick_us: f64, counter_bits: u32) -> Self {
+        Self {
+            tick_us,
+            wrap_ticks: 1u64 << counter_bits,
+            last_raw: None,
+            wraps: 0,
+            last_device_us: 0.0,
+            estimate_us: 0.0,
+            rate: 0.0,
+            last_output: 0,
+            resyncs: 0,
+        }
+    }
+
+    /// ControlCAN：32 位元計數器，單位 0.1 ms
+    pub fn controlcan() -> Self {
+        Self::new(100.0, 32)
+    }
+
+    /// 最近一筆的裝置時間（微秒，已處理回繞），尚未有樣本時為 0
+    pub fn device_micros(&self) -> u64 {
+        self.last_device_us as u64
+    }
+
+    /// 目前估計的裝置時鐘速率修正（ppm），正值表示主機時鐘較快
+    pub fn rate_ppm(&self) -> f64 {
+        self.rate * 1e6
+    }
+
+    /// 因時間跳動而重新對齊的次數
+    pub fn resyncs(&self) -> u64 {
+        self.resyncs
+    }
+
+    fn unwrap(&mut self, raw: u64) -> u64 {
+        if let Some(last) = self.last_raw {
+            // 大幅倒退才視為回繞，小幅倒退視為順序錯亂
+            if raw < last && last - raw > self.wrap_ticks / 2 {
+                self.wraps += 1;
+            }
+        }
+        self.last_raw = Some(raw);
+        self.wraps * self.wrap_ticks + raw
+    }
+
+    /// 以裝置計數值與主機接收時間（微秒）計算校正後的時間戳記
+    pub fn correct(&mut self, raw: u32, host_us: u64) -> u64 {
+        let first = self.last_raw.is_none();
+        let device_us = self.unwrap(raw as u64) as f64 * self.tick_us;
+        let host = host_us as f64;
+        if first {
+            self.last_device_us = device_us;
+            self.estimate_us = host;
+        }
+        let elapsed = device_us - self.last_device_us;
+        let predicted = self.estimate_us + elapsed * (1.0 + self.rate);
+        let error = host - predicted;
+        if error.abs() > RESYNC_THRESHOLD_US {
+            // 裝置重置或時鐘跳動，直接對齊主機時間
+            self.resyncs += 1;
+            self.estimate_us = host;
+        } else {
+            self.estimate_us = predicted + KP * error;
+            // 以樣本間隔正規化的 I 項，使收斂行為不受訊框頻率影響
+            let ki = KP * KP / 4.0 / elapsed.max(MIN_SAMPLE_US);
+            self.rate = (self.rate + ki * error).clamp(-MAX_RATE, MAX_RATE);
+        }
+        self.last_device_us = device_us;
+        self.last_output = self.last_output.max(self.estimate_us.max(0.0) as u64);
+        self.last_output
+    }
+}
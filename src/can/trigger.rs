@@ -0,0 +1,168 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::capture::DEFAULT_CAPTURE_LIMIT;
+use crate::can::filter::FrameFilter;
+use std::collections::VecDeque;
+
+/// 觸發擷取的設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriggerConfig {
+    /// 觸發條件，使用與追蹤過濾相同的運算式，例如 `id == 0x123 && data[0] == 0xFF`
+    pub condition: String,
+    /// 觸發前保留的訊框數
+    pub pre_trigger: usize,
+    /// 由觸發訊框起算記錄幾筆後停止，0 表示不限
+    pub stop_frames: usize,
+    /// 觸發後記錄幾秒後停止，0 表示不限
+    pub stop_seconds: f64,
+}
+
+impl Default for TriggerConfig {
+    fn default() -> Self {
+        Self {
+            condition: String::new(),
+            pre_trigger: 1000,
+            stop_frames: 0,
+            stop_seconds: 10.0,
+        }
+    }
+}
+
+/// 觸發擷取的狀態
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TriggerState {
+    Idle,
+    /// 等待觸發，持續保留最近的訊框
+    Armed,
+    Triggered,
+    /// 已停止，擷取結果可匯出
+    Done,
+}
+
+/// 條件觸發擷取：等待符合條件的訊框，連同觸發前的環形緩衝區一起記錄，
+/// 達到筆數或時間後停止，用於捕捉偶發的異常
+pub struct TriggerCapture {
+    config: TriggerConfig,
+    filter: Option<FrameFilter>,
+    state: TriggerState,
+    pre: VecDeque<CanFrame>,
+    captured: Vec<CanFrame>,
+    /// 觸發訊框的時間與其在 captured 中的位置
+    trigger: Option<(u64, usize)>,
+}
+
+impl Default for TriggerCapture {
+    fn default() -> Self {
+        Self {
+            config: TriggerConfig::default(),
+            filter: None,
+            state: TriggerState::Idle,
+            pre: VecDeque::new(),
+            captured: Vec::new(),
+            trigger: None,
+        }
+    }
+}
+
+impl TriggerCapture {
+    /// 以新設定開始等待觸發，清除上一次的結果
+    pub fn arm(&mut self, config: TriggerConfig) -> Result<(), String> {
+        self.filter = Some(FrameFilter::compile(&config.condition)?);
+        self.config = config;
+        self.pre.clear();
+        self.captured.clear();
+        self.trigger = None;
+        self.state = TriggerState::Armed;
+        Ok(())
+    }
+
+    /// 取消等待並清除結果
+    pub fn disarm(&mut self) {
+        self.state = TriggerState::Idle;
+        self.pre.clear();
+        self.captured.clear();
+        self.trigger = None;
+    }
+
+    /// 手動結束觸發後的記錄
+    pub fn stop(&mut self) {
+        if self.state == TriggerState::Triggered {
+            self.state = TriggerState::Done;
+        }
+    }
+
+    pub fn process(&mut self, frame: &CanFrame) {
+        match self.state {
+            TriggerState::Armed => {
+                if self.filter.as_ref().is_some_and(|f| f.matches(frame)) {
+                    self.captured.extend(self.pre.drain(..));
+                    self.trigger = Some((frame.timestamp, self.captured.len()));
+                    self.captured.push(*frame);
+                    self.state = TriggerState::Triggered;
+                    self.check_frame_limit();
+                } else if self.config.pre_trigger > 0 {
+                    if self.pre.len() >= self.config.pre_trigger {
+                        self.pre.pop_front();
+                    }
+                    self.pre.push_back(*frame);
+                }
+            }
+            TriggerState::Triggered => {
+                self.poll(frame.timestamp);
+                if self.state == TriggerState::Triggered {
+                    self.captured.push(*frame);
+                    self.check_frame_limit();
+                }
+            }
+            TriggerState::Idle | TriggerState::Done => {}
+        }
+    }
+
+    /// 檢查觸發後的時間限制；匯流排安靜時也需定期呼叫
+    pub fn poll(&mut self, now_us: u64) {
+        let Some((time, _)) = self.trigger else {
+            return;
+        };
+        let limit_us = (self.config.stop_seconds * 1_000_000.0) as u64;
+        if self.state == TriggerState::Triggered
+            && limit_us > 0
+            && now_us.saturating_sub(time) >= limit_us
+        {
+            self.state = TriggerState::Done;
+        }
+    }
+
+    fn check_frame_limit(&mut self) {
+        let Some((_, index)) = self.trigger else {
+            return;
+        };
+        let recorded = self.captured.len() - index;
+        let limit = self.config.stop_frames;
+        // 未設定筆數時仍以完整擷取的上限保護記憶體
+        if (limit > 0 && recorded >= limit) || self.captured.len() >= DEFAULT_CAPTURE_LIMIT {
+            self.state = TriggerState::Done;
+        }
+    }
+
+    pub fn state(&self) -> TriggerState {
+        self.state
+    }
+
+    pub fn config(&self) -> &TriggerConfig {
+        &self.config
+    }
+
+    /// 等待觸發期間已保留的訊框數
+    pub fn pre_trigger_len(&self) -> usize {
+        self.pre.len()
+    }
+
+    /// 觸發前與觸發後記錄的訊框，依收發順序
+    pub fn captured(&self) -> &[CanFrame] {
+        &self.captured
+    }
+
+    /// 觸發訊框的時間與其在 `captured` 中的位置
+    pub fn trigger(&self) -> Option<(u64, usize)> {
+        self.trigger
+    }
+}
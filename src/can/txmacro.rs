@@ -0,0 +1,111 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 巨集中的單一步驟：距離上一步的延遲與要送出的訊框
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MacroStep {
+    pub delay_ms: u64,
+    pub frame: CanFrame,
+}
+
+/// 一段具名的傳送巨集
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxMacro {
+    pub name: String,
+    pub steps: Vec<MacroStep>,
+}
+
+/// 錄製手動傳送的訊框與其間隔時間
+#[derive(Debug, Default)]
+pub struct MacroRecorder {
+    last_sent: Option<Instant>,
+    steps: Vec<MacroStep>,
+}
+
+impl MacroRecorder {
+    /// 記錄一筆手動傳送的訊框，第一筆的延遲為 0
+    pub fn record(&mut self, frame: CanFrame) {
+        let now = Instant::now();
+        let delay_ms = self
+            .last_sent
+            .map(|last| now.duration_since(last).as_millis() as u64)
+            .unwrap_or(0);
+        self.last_sent = Some(now);
+        self.steps.push(MacroStep { delay_ms, frame });
+    }
+
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// 結束錄製並產生巨集
+    pub fn finish(self, name: &str) -> TxMacro {
+        TxMacro {
+            name: name.to_string(),
+            steps: self.steps,
+        }
+    }
+}
+
+/// 在背景執行緒依原始間隔重播巨集，回傳的旗標可用來中止或查詢是否仍在執行
+pub fn play_macro<F>(tx_macro: TxMacro, can_app: SharedCan, log: F) -> Arc<AtomicBool>
+where
+    F: Fn(String) + Send + 'static,
+{
+    let running = Arc::new(AtomicBool::new(true));
+    let running_flag = Arc::clone(&running);
+    thread::spawn(move || {
+        log(format!("Macro '{}' started", tx_macro.name));
+        for (index, step) in tx_macro.steps.iter().enumerate() {
+            // 以小段睡眠等待，讓 stop 可以即時生效
+            let deadline = Instant::now() + Duration::from_millis(step.delay_ms);
+            while Instant::now() < deadline && running_flag.load(Ordering::SeqCst) {
+                thread::sleep(Duration::from_millis(1));
+            }
+            if !running_flag.load(Ordering::SeqCst) {
+                log(format!("Macro '{}' stopped", tx_macro.name));
+                return;
+            }
+            if let Err(e) = can_app.send_frame(&step.frame) {
+                log(format!(
+                    "Macro '{}' aborted at step {}: {}",
+                    tx_macro.name,
+                    index + 1,
+                    e
+                ));
+                running_flag.store(false, Ordering::SeqCst);
+                return;
+            }
+        }
+        log(format!("Macro '{}' finished", tx_macro.name));
+        running_flag.store(false, Ordering::SeqCst);
+    });
+    running
+}
+
+/// 將巨集清單存成 YAML
+pub fn save_macros(file_path: &str, macros: &[TxMacro]) -> Result<(), Box<dyn std::error::Error>> {
+    let writer = BufWriter::new(File::create(file_path)?);
+    serde_yaml::to_writer(writer, macros)?;
+    Ok(())
+}
+
+/// 從 YAML 載入巨集清單
+pub fn load_macros(file_path: &str) -> Result<Vec<TxMacro>, Box<dyn std::error::Error>> {
+    let reader = BufReader::new(File::open(file_path)?);
+    let macros = serde_yaml::from_reader(reader)?;
+    Ok(macros)
+}
@@ -0,0 +1,277 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use crate::can::csv_schedule::parse_data;
+use flume::{unbounded, Receiver, RecvTimeoutError, Sender};
+use serde::{Deserialize, Serialize};
+use std::sync::{
+    atomic::{AtomicBool, AtomicUsize, Ordering},
+    Arc,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 傳送序列中的一個步驟，YAML 中寫成 `- send: {...}`、`- wait: 100` 等形式
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TxStep {
+    /// 送出一筆訊框
+    Send(SendStep),
+    /// 等待指定的毫秒數
+    Wait(u64),
+    /// 等待收到指定 ID 的訊框（自上一個 send 之後），逾時則中止序列
+    WaitFor(WaitForStep),
+    /// 重複執行內含的步驟
+    Repeat(RepeatStep),
+}
+
+/// 資料超過 8 bytes 時以 CAN FD 送出
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SendStep {
+    #[serde(default)]
+    pub channel: u32,
+    #[serde(deserialize_with = "crate::can::config::deserialize_hex_or_decimal")]
+    pub id: u32,
+    /// 十六進位資料，例如 "01 02 03"
+    #[serde(default)]
+    pub data: String,
+    #[serde(default)]
+    pub brs: bool,
+}
+
+impl SendStep {
+    pub fn frame(&self) -> Result<CanFrame, String> {
+        let data = parse_data(&self.data).map_err(|e| format!("send 0x{:X}: {}", self.id, e))?;
+        Ok(if data.len() > 8 {
+            CanFrame::new_fd(self.channel, self.id, &data, self.brs)
+        } else {
+            CanFrame::new(self.channel, self.id, &data)
+        })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WaitForStep {
+    #[serde(deserialize_with = "crate::can::config::deserialize_hex_or_decimal")]
+    pub id: u32,
+    /// 未指定時任何通道皆可
+    #[serde(default)]
+    pub channel: Option<u32>,
+    #[serde(default = "default_wait_timeout")]
+    pub timeout_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepeatStep {
+    pub count: u32,
+    pub steps: Vec<TxStep>,
+}
+
+fn default_wait_timeout() -> u64 {
+    1000
+}
+
+/// 設定檔 `sequences` 區塊中的一段具名傳送序列
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TxSequence {
+    pub name: String,
+    #[serde(with = "serde_yaml::with::singleton_map_recursive")]
+    pub steps: Vec<TxStep>,
+}
+
+impl TxSequence {
+    /// 檢查資料格式與重複次數，於載入設定檔時呼叫
+    pub fn validate(&self) -> Result<(), String> {
+        fn check(steps: &[TxStep]) -> Result<(), String> {
+            for step in steps {
+                match step {
+                    TxStep::Send(send) => {
+                        send.frame()?;
+                    }
+                    TxStep::Repeat(repeat) if repeat.count == 0 => {
+                        return Err("repeat count must be > 0".to_string());
+                    }
+                    TxStep::Repeat(repeat) => check(&repeat.steps)?,
+                    TxStep::Wait(_) | TxStep::WaitFor(_) => {}
+                }
+            }
+            Ok(())
+        }
+        if self.steps.is_empty() {
+            return Err(format!("Sequence '{}': steps must not be empty", self.name));
+        }
+        check(&self.steps).map_err(|e| format!("Sequence '{}': {}", self.name, e))
+    }
+
+    /// 執行一次會送出的訊框總數（含重複）
+    pub fn frame_count(&self) -> usize {
+        fn count(steps: &[TxStep]) -> usize {
+            steps
+                .iter()
+                .map(|step| match step {
+                    TxStep::Send(_) => 1,
+                    TxStep::Repeat(repeat) => repeat.count as usize * count(&repeat.steps),
+                    TxStep::Wait(_) | TxStep::WaitFor(_) => 0,
+                })
+                .sum()
+        }
+        count(&self.steps)
+    }
+}
+
+/// 序列提前結束的原因
+enum Halt {
+    Stopped,
+    Failed(String),
+}
+
+struct Runner {
+    can_app: SharedCan,
+    frame_rx: Receiver<CanFrame>,
+    running: Arc<AtomicBool>,
+    sent: Arc<AtomicUsize>,
+}
+
+impl Runner {
+    fn run(&self, steps: &[TxStep]) -> Result<(), Halt> {
+        for step in steps {
+            if !self.running.load(Ordering::SeqCst) {
+                return Err(Halt::Stopped);
+            }
+            match step {
+                TxStep::Send(send) => {
+                    let frame = send.frame().map_err(Halt::Failed)?;
+                    // 送出前的訊框不可能是這筆訊框的回應
+                    self.frame_rx.drain();
+                    self.can_app.send_frame(&frame).map_err(Halt::Failed)?;
+                    self.sent.fetch_add(1, Ordering::SeqCst);
+                }
+                TxStep::Wait(ms) => self.wait(*ms)?,
+                TxStep::WaitFor(wait) => self.wait_for(wait)?,
+                TxStep::Repeat(repeat) => {
+                    for _ in 0..repeat.count {
+                        self.run(&repeat.steps)?;
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// 以小段睡眠等待，讓 stop 可以即時生效
+    fn wait(&self, ms: u64) -> Result<(), Halt> {
+        let deadline = Instant::now() + Duration::from_millis(ms);
+        loop {
+            if !self.running.load(Ordering::SeqCst) {
+                return Err(Halt::Stopped);
+            }
+            let now = Instant::now();
+            if now >= deadline {
+                return Ok(());
+            }
+            thread::sleep((deadline - now).min(Duration::from_millis(1)));
+        }
+    }
+
+    fn wait_for(&self, wait: &WaitForStep) -> Result<(), Halt> {
+        let deadline = Instant::now() + Duration::from_millis(wait.timeout_ms);
+        loop {
+            if !self.running.load(Ordering::SeqCst) {
+                return Err(Halt::Stopped);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return Err(Halt::Failed(format!(
+                    "no frame 0x{:X} within {} ms",
+                    wait.id, wait.timeout_ms
+                )));
+            }
+            match self
+                .frame_rx
+                .recv_timeout(remaining.min(Duration::from_millis(10)))
+            {
+                Ok(frame)
+                    if frame.id == wait.id && wait.channel.is_none_or(|ch| ch == frame.channel) =>
+                {
+                    return Ok(());
+                }
+                Ok(_) | Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return Err(Halt::Stopped),
+            }
+        }
+    }
+}
+
+/// 執行中的傳送序列，收到的訊框經由 `observe` 交給 `wait_for` 步驟比對
+pub struct SequencePlayback {
+    name: String,
+    frame_tx: Sender<CanFrame>,
+    running: Arc<AtomicBool>,
+    sent: Arc<AtomicUsize>,
+    total: usize,
+}
+
+impl SequencePlayback {
+    /// 在背景執行緒執行序列
+    pub fn start<F>(sequence: TxSequence, can_app: SharedCan, log: F) -> Self
+    where
+        F: Fn(String) + Send + 'static,
+    {
+        let (frame_tx, frame_rx) = unbounded();
+        let running = Arc::new(AtomicBool::new(true));
+        let sent = Arc::new(AtomicUsize::new(0));
+        let runner = Runner {
+            can_app,
+            frame_rx,
+            running: Arc::clone(&running),
+            sent: Arc::clone(&sent),
+        };
+        let name = sequence.name.clone();
+        let total = sequence.frame_count();
+        thread::spawn(move || {
+            log(format!("Sequence '{}' started", sequence.name));
+            match runner.run(&sequence.steps) {
+                Ok(()) => log(format!("Sequence '{}' finished", sequence.name)),
+                Err(Halt::Stopped) => log(format!("Sequence '{}' stopped", sequence.name)),
+                Err(Halt::Failed(e)) => log(format!("Sequence '{}' aborted: {}", sequence.name, e)),
+            }
+            runner.running.store(false, Ordering::SeqCst);
+        });
+        Self {
+            name,
+            frame_tx,
+            running,
+            sent,
+            total,
+        }
+    }
+
+    /// 交由序列比對一筆收到的訊框
+    pub fn observe(&self, frame: &CanFrame) {
+        if self.is_running() {
+            let _ = self.frame_tx.send(*frame);
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running.load(Ordering::SeqCst)
+    }
+
+    pub fn stop(&self) {
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    /// 已送出 / 總共要送出的訊框數
+    pub fn progress(&self) -> (usize, usize) {
+        (self.sent.load(Ordering::SeqCst), self.total)
+    }
+}
+
+impl Drop for SequencePlayback {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -0,0 +1,171 @@
+use crate::can::config::Component;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+fn default_factor() -> f64 {
+    1.0
+}
+
+/// 線性單位換算：顯示值 = 原始值 * factor + offset
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct UnitConversion {
+    pub unit: String,
+    #[serde(default = "default_factor")]
+    pub factor: f64,
+    #[serde(default)]
+    pub offset: f64,
+}
+
+impl UnitConversion {
+    fn new(unit: &str, factor: f64, offset: f64) -> Self {
+        Self {
+            unit: unit.to_string(),
+            factor,
+            offset,
+        }
+    }
+
+    pub fn apply(&self, value: f64) -> f64 {
+        value * self.factor + self.offset
+    }
+
+    /// `apply` 的反向換算
+    pub fn invert(&self, value: f64) -> f64 {
+        (value - self.offset) / self.factor
+    }
+}
+
+/// 常見單位的內建換算
+pub fn builtin_conversions(unit: &str) -> Vec<UnitConversion> {
+    match unit.trim().to_ascii_lowercase().as_str() {
+        "km/h" | "kph" => vec![
+            UnitConversion::new("mph", 0.621_371, 0.0),
+            UnitConversion::new("m/s", 1.0 / 3.6, 0.0),
+        ],
+        "mph" => vec![
+            UnitConversion::new("km/h", 1.609_344, 0.0),
+            UnitConversion::new("m/s", 0.447_04, 0.0),
+        ],
+        "m/s" => vec![
+            UnitConversion::new("km/h", 3.6, 0.0),
+            UnitConversion::new("mph", 2.236_936, 0.0),
+        ],
+        "°c" | "degc" | "c" => vec![
+            UnitConversion::new("°F", 1.8, 32.0),
+            UnitConversion::new("K", 1.0, 273.15),
+        ],
+        "°f" | "degf" | "f" => vec![
+            UnitConversion::new("°C", 5.0 / 9.0, -160.0 / 9.0),
+            UnitConversion::new("K", 5.0 / 9.0, 273.15 - 160.0 / 9.0),
+        ],
+        "kpa" => vec![
+            UnitConversion::new("bar", 0.01, 0.0),
+            UnitConversion::new("psi", 0.145_038, 0.0),
+        ],
+        "bar" => vec![
+            UnitConversion::new("kPa", 100.0, 0.0),
+            UnitConversion::new("psi", 14.503_77, 0.0),
+        ],
+        "psi" => vec![
+            UnitConversion::new("kPa", 6.894_757, 0.0),
+            UnitConversion::new("bar", 0.068_948, 0.0),
+        ],
+        "l/min" | "lpm" => vec![UnitConversion::new("gpm", 0.264_172, 0.0)],
+        "nm" => vec![UnitConversion::new("lbf·ft", 0.737_562, 0.0)],
+        _ => Vec::new(),
+    }
+}
+
+#[derive(Debug, Clone)]
+struct SignalUnits {
+    base: String,
+    options: Vec<UnitConversion>,
+    /// None 表示使用原始單位
+    selected: Option<usize>,
+}
+
+/// 每個訊號可選的顯示單位與目前選擇，供元件、圖表與匯出共用
+#[derive(Debug, Clone, Default)]
+pub struct DisplayUnits {
+    signals: HashMap<String, SignalUnits>,
+}
+
+impl DisplayUnits {
+    /// 依 components 的 unit 與 conversions 建立可選單位，保留仍有效的選擇
+    pub fn load(&mut self, comps: &[Component]) {
+        let previous = std::mem::take(&mut self.signals);
+        for comp in comps {
+            let base = comp.unit.clone().unwrap_or_default();
+            let mut options = comp.conversions.clone();
+            for builtin in builtin_conversions(&base) {
+                if !options.iter().any(|o| o.unit == builtin.unit) {
+                    options.push(builtin);
+                }
+            }
+            let selected = previous.get(&comp.key).and_then(|p| {
+                let unit = &p.options.get(p.selected?)?.unit;
+                options.iter().position(|o| &o.unit == unit)
+            });
+            self.signals.insert(
+                comp.key.clone(),
+                SignalUnits {
+                    base,
+                    options,
+                    selected,
+                },
+            );
+        }
+    }
+
+    /// 可選的單位名稱，第一個為原始單位
+    pub fn options(&self, key: &str) -> Vec<&str> {
+        match self.signals.get(key) {
+            Some(s) => std::iter::once(s.base.as_str())
+                .chain(s.options.iter().map(|o| o.unit.as_str()))
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// 選擇顯示單位；指定原始單位或未知名稱時回到原始單位
+    pub fn select(&mut self, key: &str, unit: &str) {
+        if let Some(s) = self.signals.get_mut(key) {
+            s.selected = s.options.iter().position(|o| o.unit == unit);
+        }
+    }
+
+    /// 目前顯示的單位名稱
+    pub fn unit(&self, key: &str) -> &str {
+        match self.signals.get(key) {
+            Some(s) => s
+                .selected
+                .and_then(|i| s.options.get(i))
+                .map_or(s.base.as_str(), |o| o.unit.as_str()),
+            None => "",
+        }
+    }
+
+    /// 已選擇非原始單位的訊號與其單位
+    pub fn selections(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.signals.iter().filter_map(|(key, s)| {
+            let option = s.options.get(s.selected?)?;
+            Some((key.as_str(), option.unit.as_str()))
+        })
+    }
+
+    /// 將原始值換算為目前顯示單位
+    pub fn convert(&self, key: &str, value: f64) -> f64 {
+        self.signals
+            .get(key)
+            .and_then(|s| s.options.get(s.selected?))
+            .map_or(value, |o| o.apply(value))
+    }
+
+    /// 將目前顯示單位的數值換算回原始值
+    pub fn to_raw(&self, key: &str, value: f64) -> f64 {
+        self.signals
+            .get(key)
+            .and_then(|s| s.options.get(s.selected?))
+            .map_or(value, |o| o.invert(value))
+    }
+}
@@ -0,0 +1,78 @@
+use crate::can::log_event::LogEvent;
+use flume::Sender;
+use serde::{Deserialize, Serialize};
+
+/// 已知有問題的函式庫版本：低於 `below` 的版本會在載入時發出警告
+struct KnownIssue {
+    library: &'static str,
+    below: (u32, u32),
+    reason: &'static str,
+}
+
+const KNOWN_ISSUES: &[KnownIssue] = &[
+    KnownIssue {
+        library: "PCANBasic",
+        below: (4, 0),
+        reason: "no CAN FD support and unreliable CAN_GetValue parameters; update PCAN-Basic",
+    },
+    KnownIssue {
+        library: "PCANBasic",
+        below: (4, 4),
+        reason: "PCAN_ALLOW_ECHO_FRAMES is not supported; transmitted frames are echoed locally",
+    },
+];
+
+/// 載入的裝置函式庫與其版本
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibraryVersion {
+    pub library: String,
+    pub version: String,
+}
+
+impl LibraryVersion {
+    pub fn new(library: &str, version: impl Into<String>) -> Self {
+        Self {
+            library: library.to_string(),
+            version: version.into(),
+        }
+    }
+
+    /// ControlCAN 板卡資訊中的版本欄位，例如 0x0230 表示 V2.30
+    pub fn from_vci(library: &str, raw: u16) -> Self {
+        Self::new(library, format!("V{:X}.{:02X}", raw >> 8, raw & 0xFF))
+    }
+
+    /// 主、次版本號，版本字串無法解析時回傳 None
+    pub fn major_minor(&self) -> Option<(u32, u32)> {
+        let text = self.version.trim_start_matches(['V', 'v']);
+        let mut parts = text.split(['.', ',', ' ']);
+        let major = parts.next()?.trim().parse().ok()?;
+        let minor = parts.next().map_or(Some(0), |p| p.trim().parse().ok())?;
+        Some((major, minor))
+    }
+
+    /// 此版本已知的問題說明，多筆符合時回傳最嚴重（門檻最低）的一筆
+    pub fn known_issue(&self) -> Option<&'static str> {
+        let version = self.major_minor()?;
+        KNOWN_ISSUES
+            .iter()
+            .filter(|issue| issue.library == self.library && version < issue.below)
+            .min_by_key(|issue| issue.below)
+            .map(|issue| issue.reason)
+    }
+
+    /// 回報版本，已知有問題時另外發出警告
+    pub fn report(&self, log_tx: &Sender<LogEvent>) {
+        let _ = log_tx.send(LogEvent::ApiVersion {
+            library: self.library.clone(),
+            version: self.version.clone(),
+        });
+        if let Some(reason) = self.known_issue() {
+            let _ = log_tx.send(LogEvent::LibraryVersionWarning {
+                library: self.library.clone(),
+                version: self.version.clone(),
+                reason: reason.to_string(),
+            });
+        }
+    }
+}
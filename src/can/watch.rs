@@ -0,0 +1,232 @@
+use crate::can::signals::SignalTable;
+
+/// 比較運算子
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum CompareOp {
+    Greater,
+    GreaterEq,
+    Less,
+    LessEq,
+    Equal,
+    NotEqual,
+}
+
+impl CompareOp {
+    fn parse(text: &str) -> Option<Self> {
+        Some(match text {
+            ">" => CompareOp::Greater,
+            ">=" => CompareOp::GreaterEq,
+            "<" => CompareOp::Less,
+            "<=" => CompareOp::LessEq,
+            "==" => CompareOp::Equal,
+            "!=" => CompareOp::NotEqual,
+            _ => return None,
+        })
+    }
+}
+
+/// 單一比較：`訊號 運算子 數值`
+#[derive(Debug, Clone, PartialEq)]
+struct Comparison {
+    key: String,
+    op: CompareOp,
+    value: f64,
+}
+
+impl Comparison {
+    /// `hysteresis` 為警報中時放寬的幅度：`>` 要降到 value - h 以下才解除，`<` 反之
+    fn eval<F>(&self, lookup: &F, hysteresis: f64) -> bool
+    where
+        F: Fn(&str) -> Option<f64>,
+    {
+        let Some(actual) = lookup(&self.key) else {
+            return false;
+        };
+        match self.op {
+            CompareOp::Greater => actual > self.value - hysteresis,
+            CompareOp::GreaterEq => actual >= self.value - hysteresis,
+            CompareOp::Less => actual < self.value + hysteresis,
+            CompareOp::LessEq => actual <= self.value + hysteresis,
+            CompareOp::Equal => actual == self.value,
+            CompareOp::NotEqual => actual != self.value,
+        }
+    }
+}
+
+/// 監看運算式，例如 `temp > 90 for 5s`、`rpm > 6000 and oil_pressure < 1.5`
+///
+/// 比較之間以 `and` / `or`（或 `&&` / `||`）連接，`and` 優先；
+/// 結尾的 `for <時間>`（ms、s、min）表示條件須連續成立這麼久才發出警報。
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchExpr {
+    /// 以 or 連接的 and 群組
+    any_of: Vec<Vec<Comparison>>,
+    /// 最短持續時間（微秒）
+    pub min_duration_us: u64,
+}
+
+fn parse_duration_us(text: &str) -> Result<u64, String> {
+    let split = text
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(text.len());
+    let (number, unit) = text.split_at(split);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid duration '{}'", text))?;
+    let scale = match unit {
+        "ms" => 1_000.0,
+        "s" | "" => 1_000_000.0,
+        "min" => 60_000_000.0,
+        _ => return Err(format!("Unknown duration unit '{}'", unit)),
+    };
+    Ok((number * scale) as u64)
+}
+
+/// 在運算子前後補空白，讓 `temp>90` 也能以空白切開
+fn tokenize(text: &str) -> Vec<String> {
+    let mut spaced = String::with_capacity(text.len() * 2);
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if matches!(c, '>' | '<' | '=' | '!') {
+            spaced.push(' ');
+            spaced.push(c);
+            if chars.peek() == Some(&'=') {
+                spaced.push(chars.next().unwrap());
+            }
+            spaced.push(' ');
+        } else {
+            spaced.push(c);
+        }
+    }
+    spaced.split_whitespace().map(|s| s.to_string()).collect()
+}
+
+impl WatchExpr {
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut tokens = tokenize(text);
+        let mut min_duration_us = 0;
+        if let Some(pos) = tokens.iter().position(|t| t.eq_ignore_ascii_case("for")) {
+            min_duration_us = parse_duration_us(&tokens[pos + 1..].concat())?;
+            tokens.truncate(pos);
+        }
+        let mut any_of = vec![Vec::new()];
+        let mut rest = tokens.as_slice();
+        loop {
+            let [key, op, value, tail @ ..] = rest else {
+                return Err(format!(
+                    "Expected '<signal> <op> <value>' in '{}'",
+                    text.trim()
+                ));
+            };
+            let op = CompareOp::parse(op)
+                .ok_or_else(|| format!("Unknown operator '{}' in '{}'", op, text.trim()))?;
+            let value: f64 = value
+                .parse()
+                .map_err(|_| format!("Invalid number '{}' in '{}'", value, text.trim()))?;
+            any_of.last_mut().unwrap().push(Comparison {
+                key: key.clone(),
+                op,
+                value,
+            });
+            match tail {
+                [] => break,
+                [joiner, next @ ..] => {
+                    match joiner.to_ascii_lowercase().as_str() {
+                        "and" | "&&" => {}
+                        "or" | "||" => any_of.push(Vec::new()),
+                        other => {
+                            return Err(format!("Unexpected '{}' in '{}'", other, text.trim()))
+                        }
+                    }
+                    rest = next;
+                }
+            }
+        }
+        Ok(Self {
+            any_of,
+            min_duration_us,
+        })
+    }
+
+    /// 運算式引用的訊號
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.any_of.iter().flatten().map(|c| c.key.as_str())
+    }
+
+    pub fn eval(&self, signals: &SignalTable, hysteresis: f64) -> bool {
+        self.eval_with(&|key| signals.get(key).and_then(|s| s.value), hysteresis)
+    }
+
+    /// 以 `lookup` 取得訊號值，供 YAML 以外的訊號來源（例如 DBC）使用
+    pub fn eval_with<F>(&self, lookup: &F, hysteresis: f64) -> bool
+    where
+        F: Fn(&str) -> Option<f64>,
+    {
+        self.any_of
+            .iter()
+            .any(|group| group.iter().all(|c| c.eval(lookup, hysteresis)))
+    }
+}
+
+/// 監看狀態轉換
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WatchTransition {
+    Raised,
+    Cleared,
+}
+
+/// 監看運算式的狀態：條件持續成立 `min_duration_us` 才觸發，
+/// 觸發後以放寬 `hysteresis` 的條件判斷是否解除，避免數值在門檻附近抖動造成大量警報
+#[derive(Debug, Clone)]
+pub struct WatchState {
+    pub expr: WatchExpr,
+    pub hysteresis: f64,
+    /// 條件開始成立的時間
+    pending_since: Option<u64>,
+    active: bool,
+}
+
+impl WatchState {
+    pub fn new(expr: WatchExpr, hysteresis: f64) -> Self {
+        Self {
+            expr,
+            hysteresis: hysteresis.abs(),
+            pending_since: None,
+            active: false,
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// 以目前訊號值更新狀態，狀態改變時回傳轉換
+    pub fn update(&mut self, signals: &SignalTable, now: u64) -> Option<WatchTransition> {
+        self.update_with(&|key| signals.get(key).and_then(|s| s.value), now)
+    }
+
+    /// 同 `update`，訊號值由 `lookup` 取得
+    pub fn update_with<F>(&mut self, lookup: &F, now: u64) -> Option<WatchTransition>
+    where
+        F: Fn(&str) -> Option<f64>,
+    {
+        if self.active {
+            if self.expr.eval_with(lookup, self.hysteresis) {
+                return None;
+            }
+            self.active = false;
+            self.pending_since = None;
+            return Some(WatchTransition::Cleared);
+        }
+        if !self.expr.eval_with(lookup, 0.0) {
+            self.pending_since = None;
+            return None;
+        }
+        let since = *self.pending_since.get_or_insert(now);
+        if now.saturating_sub(since) >= self.expr.min_duration_us {
+            self.active = true;
+            return Some(WatchTransition::Raised);
+        }
+        None
+    }
+}
@@ -0,0 +1,196 @@
+use crate::can::cantypes::{
+    CanFrame, FrameDirection, FrameProtocol, Payload, MAX_PAYLOAD_LEN, MAX_STANDARD_ID,
+};
+use std::io::{self, ErrorKind, Read, Write};
+
+/// 資料包格式版本
+pub const DATAGRAM_VERSION: u8 = 1;
+/// 固定標頭長度
+pub const DATAGRAM_HEADER_LEN: usize = 20;
+
+const FLAG_FD: u8 = 0x01;
+const FLAG_BRS: u8 = 0x02;
+const FLAG_ESI: u8 = 0x04;
+const FLAG_XL: u8 = 0x08;
+const FLAG_TX: u8 = 0x10;
+const FLAG_EXT: u8 = 0x20;
+const FLAG_RTR: u8 = 0x40;
+
+/// 將訊框編碼為精簡的二進位資料包（小端序），供 UDP 廣播與遠端連線共用：
+///
+/// | offset | size | 欄位 |
+/// |--------|------|------|
+/// | 0      | 1    | 版本 (`DATAGRAM_VERSION`) |
+/// | 1      | 1    | 旗標 (bit0 FD, bit1 BRS, bit2 ESI, bit3 XL, bit4 TX, bit5 EXT, bit6 RTR) |
+/// | 2      | 1    | 資料長度 N |
+/// | 3      | 1    | 保留 |
+/// | 4      | 4    | 通道 |
+/// | 8      | 4    | ID |
+/// | 12     | 8    | 時間戳記（微秒） |
+/// | 20     | N    | 資料 |
+pub fn encode_datagram(frame: &CanFrame) -> Vec<u8> {
+    let mut flags = 0u8;
+    match frame.protocol {
+        FrameProtocol::Classic => {}
+        FrameProtocol::Fd => flags |= FLAG_FD,
+        FrameProtocol::Xl => flags |= FLAG_XL,
+    }
+    if frame.brs {
+        flags |= FLAG_BRS;
+    }
+    if frame.esi {
+        flags |= FLAG_ESI;
+    }
+    if frame.direction == FrameDirection::Tx {
+        flags |= FLAG_TX;
+    }
+    if frame.extended {
+        flags |= FLAG_EXT;
+    }
+    if frame.rtr {
+        flags |= FLAG_RTR;
+    }
+    let payload = frame.payload();
+    let mut buf = Vec::with_capacity(DATAGRAM_HEADER_LEN + payload.len());
+    buf.push(DATAGRAM_VERSION);
+    buf.push(flags);
+    buf.push(payload.len() as u8);
+    buf.push(0);
+    buf.extend_from_slice(&frame.channel.to_le_bytes());
+    buf.extend_from_slice(&frame.id.to_le_bytes());
+    buf.extend_from_slice(&frame.timestamp.to_le_bytes());
+    buf.extend_from_slice(payload);
+    buf
+}
+
+/// 解碼 `encode_datagram` 產生的資料包，格式錯誤時回傳 None
+pub fn decode_datagram(buf: &[u8]) -> Option<CanFrame> {
+    if buf.len() < DATAGRAM_HEADER_LEN || buf[0] != DATAGRAM_VERSION {
+        return None;
+    }
+    let flags = buf[1];
+    let len = buf[2] as usize;
+    if len > MAX_PAYLOAD_LEN || buf.len() < DATAGRAM_HEADER_LEN + len {
+        return None;
+    }
+    let protocol = if flags & FLAG_XL != 0 {
+        FrameProtocol::Xl
+    } else if flags & FLAG_FD != 0 {
+        FrameProtocol::Fd
+    } else {
+        FrameProtocol::Classic
+    };
+    let id = u32::from_le_bytes(buf[8..12].try_into().ok()?);
+    Some(CanFrame {
+        channel: u32::from_le_bytes(buf[4..8].try_into().ok()?),
+        id,
+        protocol,
+        brs: flags & FLAG_BRS != 0,
+        esi: flags & FLAG_ESI != 0,
+        // 舊版傳送端不設 EXT 旗標，超過 11 bits 的 ID 一律視為延伸 ID
+        extended: flags & FLAG_EXT != 0 || id > MAX_STANDARD_ID,
+        rtr: flags & FLAG_RTR != 0,
+        data: Payload::new(&buf[DATAGRAM_HEADER_LEN..DATAGRAM_HEADER_LEN + len]),
+        timestamp: u64::from_le_bytes(buf[12..20].try_into().ok()?),
+        device_timestamp: None,
+        direction: if flags & FLAG_TX != 0 {
+            FrameDirection::Tx
+        } else {
+            FrameDirection::Rx
+        },
+    })
+}
+
+const MSG_FRAME: u8 = 0x01;
+const MSG_TRANSMIT: u8 = 0x02;
+const MSG_ERROR: u8 = 0x03;
+const MSG_AUTH: u8 = 0x04;
+
+/// 遠端連線（TCP）上的訊息，每筆以 `[u16 長度][u8 類型][內容]` 傳送
+#[derive(Debug, Clone, PartialEq)]
+pub enum WireMessage {
+    /// 伺服器 → 用戶端：收到的訊框
+    Frame(CanFrame),
+    /// 用戶端 → 伺服器：請求送出訊框
+    Transmit(CanFrame),
+    /// 伺服器 → 用戶端：錯誤訊息
+    Error(String),
+    /// 用戶端 → 伺服器：傳送權限的共用 token，需在 Transmit 之前送出
+    Auth(String),
+}
+
+impl WireMessage {
+    pub fn encode(&self) -> Vec<u8> {
+        let (kind, body) = match self {
+            WireMessage::Frame(frame) => (MSG_FRAME, encode_datagram(frame)),
+            WireMessage::Transmit(frame) => (MSG_TRANSMIT, encode_datagram(frame)),
+            WireMessage::Error(text) => (MSG_ERROR, text.as_bytes().to_vec()),
+            WireMessage::Auth(token) => (MSG_AUTH, token.as_bytes().to_vec()),
+        };
+        let mut buf = Vec::with_capacity(3 + body.len());
+        buf.extend_from_slice(&((body.len() + 1) as u16).to_le_bytes());
+        buf.push(kind);
+        buf.extend_from_slice(&body);
+        buf
+    }
+
+    fn decode(kind: u8, body: &[u8]) -> Option<Self> {
+        match kind {
+            MSG_FRAME => decode_datagram(body).map(WireMessage::Frame),
+            MSG_TRANSMIT => decode_datagram(body).map(WireMessage::Transmit),
+            MSG_ERROR => Some(WireMessage::Error(
+                String::from_utf8_lossy(body).to_string(),
+            )),
+            MSG_AUTH => Some(WireMessage::Auth(String::from_utf8_lossy(body).to_string())),
+            _ => None,
+        }
+    }
+
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        writer.write_all(&self.encode())
+    }
+}
+
+/// 從設有讀取逾時的串流中累積資料，逾時不會破壞訊息邊界
+#[derive(Debug, Default)]
+pub struct MessageReader {
+    buf: Vec<u8>,
+}
+
+impl MessageReader {
+    /// 讀取目前可得的資料並回傳所有完整訊息；連線關閉時回傳 `UnexpectedEof`
+    pub fn poll<R: Read>(&mut self, reader: &mut R) -> io::Result<Vec<WireMessage>> {
+        let mut chunk = [0u8; 4096];
+        match reader.read(&mut chunk) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    ErrorKind::UnexpectedEof,
+                    "connection closed",
+                ))
+            }
+            Ok(n) => self.buf.extend_from_slice(&chunk[..n]),
+            Err(e) if matches!(e.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(e) => return Err(e),
+        }
+
+        let mut messages = Vec::new();
+        let mut offset = 0;
+        while self.buf.len() >= offset + 2 {
+            let len = u16::from_le_bytes([self.buf[offset], self.buf[offset + 1]]) as usize;
+            if len == 0 {
+                return Err(io::Error::new(ErrorKind::InvalidData, "empty message"));
+            }
+            if self.buf.len() < offset + 2 + len {
+                break;
+            }
+            let kind = self.buf[offset + 2];
+            let body = &self.buf[offset + 3..offset + 2 + len];
+            if let Some(message) = WireMessage::decode(kind, body) {
+                messages.push(message);
+            }
+            offset += 2 + len;
+        }
+        self.buf.drain(..offset);
+        Ok(messages)
+    }
+}
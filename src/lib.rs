@@ -0,0 +1,3 @@
+pub mod can;
+pub mod logging;
+pub mod ui;
@@ -0,0 +1,125 @@
+use chrono::Local;
+use std::collections::VecDeque;
+use std::fmt::Write as _;
+use std::io::Write as _;
+use std::sync::{Arc, Mutex};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::Layer;
+
+/// 寫入記錄檔的目錄
+pub const LOG_DIR: &str = "logs";
+
+/// Log 面板的一筆紀錄
+#[derive(Debug, Clone)]
+pub struct LogEntry {
+    pub level: Level,
+    pub text: String,
+}
+
+/// GUI 與背景執行緒共用的 Log 緩衝區
+pub type SharedLog = Arc<Mutex<VecDeque<LogEntry>>>;
+
+/// 記錄檔輸出的開關，開啟後每日輪替
+#[derive(Clone, Default)]
+pub struct LogFile(Arc<Mutex<Option<RollingFileAppender>>>);
+
+impl LogFile {
+    pub fn is_enabled(&self) -> bool {
+        self.0.lock().unwrap().is_some()
+    }
+
+    pub fn set_enabled(&self, enabled: bool) -> Result<(), String> {
+        let mut file = self.0.lock().unwrap();
+        if !enabled {
+            *file = None;
+        } else if file.is_none() {
+            let appender = RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix("can_tool")
+                .filename_suffix("log")
+                .build(LOG_DIR)
+                .map_err(|e| format!("Failed to open log file in {}: {}", LOG_DIR, e))?;
+            *file = Some(appender);
+        }
+        Ok(())
+    }
+}
+
+/// 收集事件的訊息與結構化欄位
+#[derive(Default)]
+struct FieldVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for FieldVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
+
+/// 將 tracing 事件送到 Log 面板、記錄檔，警告以上同時輸出到 stderr
+struct GuiLayer {
+    logs: SharedLog,
+    capacity: usize,
+    file: LogFile,
+}
+
+impl<S: Subscriber> Layer<S> for GuiLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        let mut visitor = FieldVisitor::default();
+        event.record(&mut visitor);
+        // 未指定 target 時以模組名稱的最後一段作為標籤
+        let target = metadata.target().rsplit("::").next().unwrap_or_default();
+        let text = format!(
+            "[{}] {}{}",
+            target.to_uppercase(),
+            visitor.message,
+            visitor.fields
+        );
+        let level = *metadata.level();
+        let time = Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+
+        if level <= Level::WARN {
+            eprintln!("{} {:5} {}", time, level, text);
+        }
+        if let Some(file) = self.file.0.lock().unwrap().as_mut() {
+            let _ = writeln!(file, "{} {:5} {}", time, level, text);
+        }
+        let mut logs = self.logs.lock().unwrap();
+        if logs.len() >= self.capacity {
+            logs.pop_front();
+        }
+        logs.push_back(LogEntry { level, text });
+    }
+}
+
+/// 安裝全域的 tracing subscriber，回傳記錄檔開關；只有第一次呼叫有效
+pub fn init(logs: SharedLog, capacity: usize) -> LogFile {
+    let file = LogFile::default();
+    let layer = GuiLayer {
+        logs,
+        capacity,
+        file: file.clone(),
+    }
+    .with_filter(tracing_subscriber::filter::LevelFilter::DEBUG);
+    let _ = tracing_subscriber::registry().with(layer).try_init();
+    file
+}
@@ -1,11 +1,88 @@
-mod can;
-use crate::can::canbus::*;
-use crate::can::cantypes::*;
-use crate::can::config;
+use can_tool::can::alarm::AlarmEngine;
+use can_tool::can::autoresponse::AutoResponder;
+use can_tool::can::bit_timing::Sja1000Timing;
+use can_tool::can::blackbox::BlackBoxRecorder;
+use can_tool::can::broadcast::UdpBroadcaster;
+use can_tool::can::bus_state::{BusState, ControllerStatus};
+use can_tool::can::canbus::*;
+use can_tool::can::canopen::CanOpenMonitor;
+use can_tool::can::cantypes::*;
+use can_tool::can::capture::CaptureStore;
+use can_tool::can::capture_db::CaptureDb;
+use can_tool::can::config;
+use can_tool::can::conformance::ConformanceSuite;
+use can_tool::can::dbc::DbcDecoder;
+use can_tool::can::events::{EventLog, SharedEvents};
+use can_tool::can::gateway::Gateway;
+use can_tool::can::gsusb::GsUsbApp;
+use can_tool::can::id_filter::IdFilter;
+use can_tool::can::j2534::J2534App;
+use can_tool::can::latency::LatencyTracker;
+use can_tool::can::log_event::{Language, LogEvent, LogSeverity};
+use can_tool::can::logfile::{export_frames, ExportFormat};
+use can_tool::can::mdf;
+use can_tool::can::monitor::BusMonitor;
+use can_tool::can::obd::ObdMonitor;
+use can_tool::can::plot::SignalPlot;
+use can_tool::can::presets::{
+    AdapterBackend, AdapterPreset, ADAPTER_PRESETS, CONTROL_CAN_DEV_TYPES, GS_USB_BAUD_RATES,
+    J2534_BAUD_RATES, PCAN_BAUD_RATES, PCAN_FD_BITRATES, SLCAN_BAUD_RATES,
+};
+use can_tool::can::receive_list::ReceiveList;
+use can_tool::can::reconnect::ReconnectWatchdog;
+use can_tool::can::remote::{RemoteCanApp, RemoteServer};
+use can_tool::can::sdo::SdoClient;
+use can_tool::can::selftest::SelfTest;
+use can_tool::can::sequence::SequenceValidator;
+use can_tool::can::session::SessionInfo;
+use can_tool::can::signals::SignalTable;
+use can_tool::can::silence::SilenceDetector;
+use can_tool::can::sim::{PayloadPattern, SimCanApp, SimMessage};
+use can_tool::can::slcan::SlcanApp;
+use can_tool::can::snapshot::Snapshot;
+use can_tool::can::stats::BusStatistics;
+use can_tool::can::timeline::TrafficTimeline;
+use can_tool::can::trigger::TriggerCapture;
+use can_tool::can::txsequence::{SequencePlayback, TxSequence};
+use can_tool::can::units::DisplayUnits;
+use can_tool::logging::{self, LogEntry, LogFile, SharedLog};
+use can_tool::ui;
+use can_tool::ui::alarm_panel::{self, AlarmPanel};
+use can_tool::ui::autoresponse_panel::AutoResponsePanel;
+use can_tool::ui::bit_timing_calc::{baud_rate_combo, BitTimingCalculator};
+use can_tool::ui::blackbox_panel::BlackBoxPanel;
+use can_tool::ui::broadcast_panel::BroadcastPanel;
+use can_tool::ui::bus_stats_panel::BusStatsPanel;
+use can_tool::ui::canopen_panel::CanOpenPanel;
+use can_tool::ui::capture_db_panel::CaptureDbPanel;
+use can_tool::ui::channel_list::ChannelList;
+use can_tool::ui::dashboard::{Dashboard, Reading};
+use can_tool::ui::dbc_panel::DbcPanel;
+use can_tool::ui::events_panel::EventsPanel;
+use can_tool::ui::filter_box::TermFilter;
+use can_tool::ui::gateway_panel::GatewayPanel;
+use can_tool::ui::id_filter_panel::IdFilterPanel;
+use can_tool::ui::id_stats_panel::IdStatsPanel;
+use can_tool::ui::latency_panel::LatencyPanel;
+use can_tool::ui::layout::{ProfileLayout, UiLayout, DEFAULT_PROFILE, LAYOUT_FILE};
+use can_tool::ui::log_viewer::LogViewer;
+use can_tool::ui::obd_panel::ObdPanel;
+use can_tool::ui::plot_panel::PlotPanel;
+use can_tool::ui::remote_panel::RemoteServerPanel;
+use can_tool::ui::replay_panel::ReplayPanel;
+use can_tool::ui::report::{config_listing, signal_plot, CaptureReport};
+use can_tool::ui::report_panel::ReportPanel;
+use can_tool::ui::sampler_panel::SamplerPanel;
+use can_tool::ui::scheduler_panel::SchedulerPanel;
+use can_tool::ui::selftest_panel::SelfTestPanel;
+use can_tool::ui::trace_view::TraceView;
+use can_tool::ui::trigger_panel::TriggerPanel;
+use can_tool::ui::tx_panel::TxPanel;
 
 use eframe::egui;
-use flume::{unbounded, RecvTimeoutError};
+use flume::{unbounded, RecvTimeoutError, Sender};
 use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
 use std::thread;
 use std::time::Duration;
@@ -17,55 +94,558 @@ use rfd::FileDialog;
 enum CanApi {
     ControlCan,
     Pcan,
+    Slcan,
+    GsUsb,
+    J2534,
+    Remote,
+    Sim,
 }
 
-const CONTROL_CAN_BAUD_RATES: [u32; 17] = [
-    10, 20, 33, 40, 50, 66, 80, 83, 100, 125, 200, 250, 400, 500, 666, 800, 1000,
-];
-const PCAN_BAUD_RATES: [u32; 14] = [5, 10, 20, 33, 47, 50, 83, 95, 100, 125, 250, 500, 800, 1000];
+/// PCAN 預設頻道（PCAN_USBBUS1）
+const PCAN_CHANNEL: u32 = 0x51;
+/// ControlCAN 裝置類型預設值（VCI_USBCAN2）與索引
+const CONTROLCAN_DEV_TYPE: u32 = 4;
+const CONTROLCAN_DEV_INDEX: u32 = 0;
 
-const DATA_BUFFER_CAPACITY: usize = 1000;
+const DATA_BUFFER_CAPACITY: usize = 100_000;
 const LOG_BUFFER_CAPACITY: usize = 1000;
 
+/// 後端紀錄事件轉為 tracing 事件，附上事件代碼供比對
+fn emit_backend_event(event: &LogEvent, language: Language) {
+    let text = event.format(language);
+    let code = event.code();
+    match event.severity() {
+        LogSeverity::Info => tracing::info!(target: "can", code, "{}", text),
+        LogSeverity::Warning => tracing::warn!(target: "can", code, "{}", text),
+        LogSeverity::Error => tracing::error!(target: "can", code, "{}", text),
+    }
+}
+
+/// 將訊框加入 Data 緩衝區，超過容量時丟棄最舊的
+/// 同時記入完整擷取，供匯出超出緩衝區的訊框
+fn push_frame(
+    buffer: &Mutex<VecDeque<CanFrame>>,
+    capture: &Mutex<CaptureStore>,
+    receive_list: &Mutex<ReceiveList>,
+    frame: CanFrame,
+) {
+    capture.lock().unwrap().record(&frame);
+    receive_list.lock().unwrap().process(&frame);
+    let mut buffer = buffer.lock().unwrap();
+    if buffer.len() >= DATA_BUFFER_CAPACITY {
+        buffer.pop_front();
+    }
+    buffer.push_back(frame);
+}
+
+/// 匯流排錯誤狀態指示：每個通道一個標籤，滑鼠移上顯示錯誤計數器
+fn show_bus_status(ui: &mut egui::Ui, status: &[(u32, ControllerStatus)]) {
+    if status.is_empty() {
+        return;
+    }
+    ui.separator();
+    for (channel, status) in status {
+        let color = match status.state {
+            BusState::ErrorActive => egui::Color32::GREEN,
+            BusState::ErrorWarning => egui::Color32::YELLOW,
+            BusState::ErrorPassive => egui::Color32::from_rgb(255, 140, 0),
+            BusState::BusOff => egui::Color32::RED,
+        };
+        let response = ui.colored_label(color, format!("● CH{} {}", channel, status.state));
+        if let (Some(tec), Some(rec)) = (status.tec, status.rec) {
+            response.on_hover_text(format!("TEC: {}, REC: {}", tec, rec));
+        }
+    }
+}
+
+/// 凍結畫面時的 Data 與 Log 內容；擷取與記錄在背景持續進行
+struct FrozenView {
+    /// 凍結的時間
+    at: u64,
+    data: Mutex<VecDeque<CanFrame>>,
+    receive_list: Mutex<ReceiveList>,
+    logs: VecDeque<LogEntry>,
+}
+
 struct CanGui {
     api: CanApi,
-    controlcan_ch1: u32,
-    controlcan_baud1: u32,
-    controlcan_ch2: u32,
-    controlcan_baud2: u32,
+    /// 目前選擇的轉接器預設，None 為自訂
+    adapter_preset: Option<&'static AdapterPreset>,
+    controlcan_dev_type: u32,
+    /// 同類型的多個轉接器依插入順序編號
+    controlcan_dev_index: u32,
+    controlcan_channels: ChannelList,
+    controlcan_mode: ControllerMode,
+    controlcan_self_reception: bool,
+    /// PCAN 頻道代碼，例如 0x51 為 PCAN_USBBUS1
+    pcan_channel: u32,
+    pcan_channels: Vec<PcanChannel>,
     pcan_baud: u32,
+    /// 自訂的 BTR0BTR1，設定時取代 `pcan_baud`
+    pcan_timing: Option<Sja1000Timing>,
+    pcan_calculator: BitTimingCalculator,
+    pcan_calculator_open: bool,
+    pcan_listen_only: bool,
+    pcan_echo: bool,
+    /// 以 CAN_InitializeFD 開啟，位元率由 `pcan_fd_bitrate` 字串指定
+    pcan_fd: bool,
+    pcan_fd_bitrate: String,
+    /// SLCAN 序列埠名稱，例如 COM3 或 /dev/ttyACM0
+    slcan_port: String,
+    slcan_baud: u32,
+    slcan_listen_only: bool,
+    slcan_ports: Vec<String>,
+    /// gs_usb 裝置索引（依 `GsUsbApp::list_devices` 順序）與通道
+    gsusb_device: usize,
+    gsusb_channel: u8,
+    gsusb_baud: u32,
+    gsusb_listen_only: bool,
+    gsusb_devices: Vec<String>,
+    /// J2534 pass-thru DLL 的路徑
+    j2534_dll: String,
+    j2534_baud: u32,
+    remote_address: String,
+    /// 遠端伺服器允許傳送時的共用 token，空字串表示只接收
+    remote_token: String,
+    /// 模擬介面產生的流量
+    sim_traffic: Vec<SimMessage>,
     is_receiving: Arc<Mutex<bool>>,
-    can_app: Arc<Mutex<Option<Box<dyn CanInterface + Send>>>>,
-    logs: Arc<Mutex<VecDeque<String>>>,
-    data: Arc<Mutex<VecDeque<String>>>,
+    /// 接收期間的 log 與訊框通道，加入其他裝置時沿用
+    can_senders: Mutex<Option<(Sender<LogEvent>, Sender<CanFrame>)>>,
+    can_app: SharedCan,
+    /// 裝置失效時自動以原本的設定重新開啟
+    auto_reconnect: Arc<AtomicBool>,
+    logs: SharedLog,
+    log_file: LogFile,
+    /// Log 面板顯示的最低等級
+    log_level: tracing::Level,
+    /// Log 面板的快速過濾
+    log_terms: TermFilter,
+    /// 後端訊息的顯示語言
+    language: Arc<Mutex<Language>>,
+    data: Arc<Mutex<VecDeque<CanFrame>>>,
+    /// 本次執行收發的所有訊框，Data 緩衝區只保留最近的部分
+    capture: Arc<Mutex<CaptureStore>>,
+    /// 分組顯示用的接收清單，每個 ID 保留最新的訊框
+    receive_list: Arc<Mutex<ReceiveList>>,
     // 新增一個欄位，用來儲存載入 YAML 中的 components
     yaml_components: Option<Vec<config::Component>>,
+    dashboard: Dashboard,
+    tx_panel: TxPanel,
+    latency: Arc<Mutex<LatencyTracker>>,
+    latency_panel: LatencyPanel,
+    show_latency: bool,
+    gateway: Arc<Mutex<Option<Gateway>>>,
+    gateway_panel: GatewayPanel,
+    show_gateway: bool,
+    broadcaster: Arc<Mutex<Option<UdpBroadcaster>>>,
+    broadcast_panel: BroadcastPanel,
+    show_broadcast: bool,
+    /// 接收流程的軟體 ID 白名單/黑名單
+    id_filter: Arc<Mutex<IdFilter>>,
+    id_filter_panel: IdFilterPanel,
+    show_id_filter: bool,
+    /// 執行中定義的訊號警報規則（YAML 與 DBC 訊號）
+    alarms: Arc<Mutex<AlarmEngine>>,
+    alarm_panel: AlarmPanel,
+    show_alarms: bool,
+    auto_responder: Arc<Mutex<AutoResponder>>,
+    auto_response_panel: AutoResponsePanel,
+    show_auto_response: bool,
+    plot: Arc<Mutex<SignalPlot>>,
+    plot_panel: PlotPanel,
+    show_plot: bool,
+    /// 條件觸發擷取
+    trigger: Arc<Mutex<TriggerCapture>>,
+    trigger_panel: TriggerPanel,
+    show_trigger: bool,
+    /// 背景黑盒子記錄，與 data 緩衝區無關
+    blackbox: Arc<Mutex<Option<BlackBoxRecorder>>>,
+    blackbox_panel: BlackBoxPanel,
+    show_blackbox: bool,
+    /// SQLite 擷取資料庫記錄
+    capture_db: Arc<Mutex<Option<CaptureDb>>>,
+    capture_db_panel: CaptureDbPanel,
+    show_capture_db: bool,
+    /// DBC 即時解碼，與 YAML canbus_config 的訊號各自獨立
+    dbc: Arc<Mutex<DbcDecoder>>,
+    dbc_panel: DbcPanel,
+    show_dbc: bool,
+    obd: Arc<Mutex<ObdMonitor>>,
+    obd_panel: ObdPanel,
+    show_obd: bool,
+    canopen: Arc<Mutex<CanOpenMonitor>>,
+    sdo_client: Arc<Mutex<Option<SdoClient>>>,
+    canopen_panel: CanOpenPanel,
+    show_canopen: bool,
+    remote_server: Arc<Mutex<Option<RemoteServer>>>,
+    remote_server_panel: RemoteServerPanel,
+    show_remote_server: bool,
+    signals: Arc<Mutex<SignalTable>>,
+    show_watch: bool,
+    sampler_panel: SamplerPanel,
+    show_sampler: bool,
+    events: SharedEvents,
+    monitor: Arc<Mutex<BusMonitor>>,
+    conformance: Arc<Mutex<ConformanceSuite>>,
+    sequences: Arc<Mutex<SequenceValidator>>,
+    show_conformance: bool,
+    silence: Arc<Mutex<SilenceDetector>>,
+    timeline: Arc<Mutex<TrafficTimeline>>,
+    /// 設定檔中的傳送序列與目前執行中的序列
+    tx_sequences: Vec<TxSequence>,
+    tx_sequence: Arc<Mutex<Option<SequencePlayback>>>,
+    show_tx_sequences: bool,
+    show_timeline: bool,
+    events_panel: EventsPanel,
+    show_events: bool,
+    stats: Arc<Mutex<BusStatistics>>,
+    units: Arc<Mutex<DisplayUnits>>,
+    trace: TraceView,
+    /// 額外開啟的追蹤畫面（編號, 是否開啟, 畫面），各自有獨立的顯示過濾
+    extra_traces: Vec<(usize, bool, TraceView)>,
+    next_trace: usize,
+    /// 匯出緩衝區時只輸出符合過濾條件的訊框
+    export_filtered: bool,
+    scheduler_panel: SchedulerPanel,
+    show_scheduler: bool,
+    replay_panel: ReplayPanel,
+    show_replay: bool,
+    self_test: Arc<Mutex<SelfTest>>,
+    self_test_panel: SelfTestPanel,
+    show_self_test: bool,
+    show_channel_stats: bool,
+    bus_stats_panel: BusStatsPanel,
+    show_bus_stats: bool,
+    id_stats_panel: IdStatsPanel,
+    show_id_stats: bool,
+    log_viewer: LogViewer,
+    show_log_viewer: bool,
+    report_panel: ReportPanel,
+    show_report: bool,
+    /// 寫入匯出檔與報告的工作階段資料
+    session: SessionInfo,
+    /// 最後載入的 YAML 設定檔，依合併順序排列（第一個為基本設定）
+    config_paths: Vec<std::path::PathBuf>,
+    layout: UiLayout,
+    /// 目前的介面 profile，載入 YAML 設定後為設定檔名
+    profile: String,
+    frozen: Option<FrozenView>,
 }
 
 impl Default for CanGui {
     fn default() -> Self {
-        Self {
+        let logs: SharedLog = Arc::new(Mutex::new(VecDeque::with_capacity(LOG_BUFFER_CAPACITY)));
+        let log_file = logging::init(Arc::clone(&logs), LOG_BUFFER_CAPACITY);
+        let can_app = SharedCan::default();
+        let latency = Arc::new(Mutex::new(LatencyTracker::default()));
+        let stats = Arc::new(Mutex::new(BusStatistics::default()));
+        let data = Arc::new(Mutex::new(VecDeque::with_capacity(DATA_BUFFER_CAPACITY)));
+        let capture = Arc::new(Mutex::new(CaptureStore::default()));
+        let receive_list = Arc::new(Mutex::new(ReceiveList::default()));
+        {
+            // 介面不回送時，送出的訊框以 TX 標記直接穿插進 Data 紀錄
+            let data = Arc::clone(&data);
+            let capture = Arc::clone(&capture);
+            let receive_list = Arc::clone(&receive_list);
+            can_app.on_local_echo(move |frame| push_frame(&data, &capture, &receive_list, *frame));
+        }
+        {
+            let latency = Arc::clone(&latency);
+            can_app.on_transmit(move |frame| latency.lock().unwrap().observe(frame));
+        }
+        {
+            let stats_tx = Arc::clone(&stats);
+            can_app.on_transmit(move |frame| stats_tx.lock().unwrap().record_tx(frame));
+            let stats_err = Arc::clone(&stats);
+            can_app.on_transmit_error(move |frame, _| {
+                stats_err
+                    .lock()
+                    .unwrap()
+                    .record_error(frame.channel, now_micros())
+            });
+        }
+        let mut gui = Self {
             api: CanApi::ControlCan,
-            controlcan_ch1: 0,
-            controlcan_baud1: 250,
-            controlcan_ch2: 1,
-            controlcan_baud2: 500,
+            adapter_preset: None,
+            controlcan_dev_type: CONTROLCAN_DEV_TYPE,
+            controlcan_dev_index: CONTROLCAN_DEV_INDEX,
+            controlcan_channels: ChannelList::default(),
+            controlcan_mode: ControllerMode::Normal,
+            controlcan_self_reception: false,
+            pcan_channel: PCAN_CHANNEL,
+            pcan_channels: PcanApp::attached_channels(),
             pcan_baud: 250,
+            pcan_timing: None,
+            pcan_calculator: BitTimingCalculator::default(),
+            pcan_calculator_open: false,
+            pcan_listen_only: false,
+            pcan_echo: false,
+            pcan_fd: false,
+            pcan_fd_bitrate: PCAN_FD_BITRATES[0].1.to_string(),
+            slcan_port: String::new(),
+            slcan_baud: 500,
+            slcan_listen_only: false,
+            slcan_ports: SlcanApp::available_ports(),
+            gsusb_device: 0,
+            gsusb_channel: 0,
+            gsusb_baud: 500,
+            gsusb_listen_only: false,
+            gsusb_devices: GsUsbApp::list_devices(),
+            j2534_dll: String::new(),
+            j2534_baud: 500,
+            remote_address: "127.0.0.1:29536".to_string(),
+            remote_token: String::new(),
+            sim_traffic: vec![
+                SimMessage::new(0x100, 10, PayloadPattern::Counter),
+                SimMessage::new(0x200, 100, PayloadPattern::Sine),
+                SimMessage::new(0x18FEF100, 1000, PayloadPattern::Random),
+            ],
             is_receiving: Arc::new(Mutex::new(false)),
-            can_app: Arc::new(Mutex::new(None)),
-            logs: Arc::new(Mutex::new(VecDeque::with_capacity(LOG_BUFFER_CAPACITY))),
-            data: Arc::new(Mutex::new(VecDeque::with_capacity(DATA_BUFFER_CAPACITY))),
+            can_senders: Mutex::new(None),
+            auto_reconnect: Arc::new(AtomicBool::new(true)),
+            can_app,
+            logs,
+            log_file,
+            log_level: tracing::Level::INFO,
+            log_terms: TermFilter::default(),
+            language: Arc::new(Mutex::new(Language::default())),
+            data,
+            capture,
+            receive_list,
             yaml_components: None,
+            dashboard: Dashboard::default(),
+            tx_panel: TxPanel::default(),
+            latency,
+            latency_panel: LatencyPanel::default(),
+            show_latency: false,
+            gateway: Arc::new(Mutex::new(None)),
+            gateway_panel: GatewayPanel::default(),
+            show_gateway: false,
+            broadcaster: Arc::new(Mutex::new(None)),
+            broadcast_panel: BroadcastPanel::default(),
+            show_broadcast: false,
+            id_filter: Arc::new(Mutex::new(IdFilter::default())),
+            id_filter_panel: IdFilterPanel::default(),
+            show_id_filter: false,
+            alarms: Arc::new(Mutex::new(AlarmEngine::default())),
+            alarm_panel: AlarmPanel::default(),
+            show_alarms: false,
+            auto_responder: Arc::new(Mutex::new(AutoResponder::default())),
+            auto_response_panel: AutoResponsePanel::default(),
+            show_auto_response: false,
+            plot: Arc::new(Mutex::new(SignalPlot::default())),
+            plot_panel: PlotPanel::default(),
+            show_plot: false,
+            trigger: Arc::new(Mutex::new(TriggerCapture::default())),
+            trigger_panel: TriggerPanel::default(),
+            show_trigger: false,
+            blackbox: Arc::new(Mutex::new(None)),
+            blackbox_panel: BlackBoxPanel::default(),
+            show_blackbox: false,
+            capture_db: Arc::new(Mutex::new(None)),
+            capture_db_panel: CaptureDbPanel::default(),
+            show_capture_db: false,
+            dbc: Arc::new(Mutex::new(DbcDecoder::default())),
+            dbc_panel: DbcPanel::default(),
+            show_dbc: false,
+            obd: Arc::new(Mutex::new(ObdMonitor::default())),
+            obd_panel: ObdPanel::default(),
+            show_obd: false,
+            canopen: Arc::new(Mutex::new(CanOpenMonitor::default())),
+            sdo_client: Arc::new(Mutex::new(None)),
+            canopen_panel: CanOpenPanel::default(),
+            show_canopen: false,
+            remote_server: Arc::new(Mutex::new(None)),
+            remote_server_panel: RemoteServerPanel::default(),
+            show_remote_server: false,
+            signals: Arc::new(Mutex::new(SignalTable::default())),
+            show_watch: false,
+            sampler_panel: SamplerPanel::default(),
+            show_sampler: false,
+            events: Arc::new(Mutex::new(EventLog::default())),
+            monitor: Arc::new(Mutex::new(BusMonitor::default())),
+            conformance: Arc::new(Mutex::new(ConformanceSuite::default())),
+            sequences: Arc::new(Mutex::new(SequenceValidator::default())),
+            show_conformance: false,
+            silence: Arc::new(Mutex::new(SilenceDetector::default())),
+            timeline: Arc::new(Mutex::new(TrafficTimeline::default())),
+            tx_sequences: Vec::new(),
+            tx_sequence: Arc::new(Mutex::new(None)),
+            show_tx_sequences: false,
+            show_timeline: true,
+            events_panel: EventsPanel::default(),
+            show_events: false,
+            stats,
+            units: Arc::new(Mutex::new(DisplayUnits::default())),
+            trace: TraceView::default(),
+            extra_traces: Vec::new(),
+            next_trace: 2,
+            export_filtered: true,
+            scheduler_panel: SchedulerPanel::default(),
+            show_scheduler: false,
+            replay_panel: ReplayPanel::default(),
+            show_replay: false,
+            self_test: Arc::new(Mutex::new(SelfTest::default())),
+            self_test_panel: SelfTestPanel::default(),
+            show_self_test: false,
+            show_channel_stats: false,
+            bus_stats_panel: BusStatsPanel::default(),
+            show_bus_stats: false,
+            id_stats_panel: IdStatsPanel::default(),
+            show_id_stats: false,
+            log_viewer: LogViewer::default(),
+            show_log_viewer: false,
+            report_panel: ReportPanel::default(),
+            show_report: false,
+            session: SessionInfo::default(),
+            config_paths: Vec::new(),
+            layout: UiLayout::default(),
+            profile: DEFAULT_PROFILE.to_string(),
+            frozen: None,
+        };
+        match UiLayout::load(LAYOUT_FILE) {
+            Ok(layout) => gui.layout = layout,
+            Err(e) => tracing::warn!(target: "layout", "{}", e),
+        }
+        if let Some(layout) = gui.layout.profile(DEFAULT_PROFILE).cloned() {
+            gui.apply_layout(&layout);
         }
+        gui
     }
 }
 
 impl CanGui {
+    /// 可開關的畫面與其在配置檔中的名稱
+    fn view_flags(&mut self) -> [(&'static str, &mut bool); 28] {
+        [
+            ("latency", &mut self.show_latency),
+            ("gateway", &mut self.show_gateway),
+            ("broadcast", &mut self.show_broadcast),
+            ("id_filter", &mut self.show_id_filter),
+            ("alarms", &mut self.show_alarms),
+            ("auto_response", &mut self.show_auto_response),
+            ("plot", &mut self.show_plot),
+            ("trigger", &mut self.show_trigger),
+            ("blackbox", &mut self.show_blackbox),
+            ("capture_db", &mut self.show_capture_db),
+            ("dbc", &mut self.show_dbc),
+            ("obd", &mut self.show_obd),
+            ("canopen", &mut self.show_canopen),
+            ("remote_server", &mut self.show_remote_server),
+            ("watch", &mut self.show_watch),
+            ("sampler", &mut self.show_sampler),
+            ("events", &mut self.show_events),
+            ("scheduler", &mut self.show_scheduler),
+            ("replay", &mut self.show_replay),
+            ("self_test", &mut self.show_self_test),
+            ("channel_stats", &mut self.show_channel_stats),
+            ("bus_stats", &mut self.show_bus_stats),
+            ("id_stats", &mut self.show_id_stats),
+            ("log_viewer", &mut self.show_log_viewer),
+            ("report", &mut self.show_report),
+            ("conformance", &mut self.show_conformance),
+            ("timeline", &mut self.show_timeline),
+            ("tx_sequences", &mut self.show_tx_sequences),
+        ]
+    }
+
+    fn capture_layout(&mut self) -> ProfileLayout {
+        let open_views = self
+            .view_flags()
+            .into_iter()
+            .filter(|(_, open)| **open)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        ProfileLayout {
+            open_views,
+            trace_filter: self.trace.filter_text().to_string(),
+            trace_terms: self.trace.terms_text().to_string(),
+            log_terms: self.log_terms.text().to_string(),
+            extra_traces: self
+                .extra_traces
+                .iter()
+                .map(|(_, _, trace)| trace.filter_text().to_string())
+                .collect(),
+            export_filtered: self.export_filtered,
+            log_level: self.log_level.to_string(),
+            language: *self.language.lock().unwrap(),
+            units: self
+                .units
+                .lock()
+                .unwrap()
+                .selections()
+                .map(|(key, unit)| (key.to_string(), unit.to_string()))
+                .collect(),
+            blackbox: self.blackbox_panel.config().clone(),
+            id_filters: self.id_filter_panel.rules().to_vec(),
+            alarms: self.alarm_panel.rules().to_vec(),
+            auto_responses: self.auto_response_panel.rules().to_vec(),
+            plot_signals: self.plot.lock().unwrap().keys(),
+        }
+    }
+
+    fn apply_layout(&mut self, layout: &ProfileLayout) {
+        for (name, open) in self.view_flags() {
+            *open = layout.open_views.contains(name);
+        }
+        self.trace.set_filter_text(&layout.trace_filter);
+        self.trace.set_terms_text(&layout.trace_terms);
+        self.log_terms.set_text(&layout.log_terms);
+        self.extra_traces.clear();
+        self.next_trace = 2;
+        for text in &layout.extra_traces {
+            let mut trace = TraceView::default();
+            trace.set_filter_text(text);
+            self.extra_traces.push((self.next_trace, true, trace));
+            self.next_trace += 1;
+        }
+        self.export_filtered = layout.export_filtered;
+        if let Ok(level) = layout.log_level.parse() {
+            self.log_level = level;
+        }
+        *self.language.lock().unwrap() = layout.language;
+        let mut units = self.units.lock().unwrap();
+        for (key, unit) in &layout.units {
+            units.select(key, unit);
+        }
+        self.blackbox_panel.set_config(layout.blackbox.clone());
+        self.id_filter_panel
+            .set_rules(layout.id_filters.clone(), &self.id_filter);
+        self.alarm_panel
+            .set_rules(layout.alarms.clone(), &self.alarms);
+        self.auto_response_panel
+            .set_rules(layout.auto_responses.clone(), &self.auto_responder);
+        self.plot.lock().unwrap().set_keys(&layout.plot_signals);
+    }
+
+    /// 將目前的介面狀態存入目前的 profile 並寫檔
+    fn save_layout(&mut self) {
+        let layout = self.capture_layout();
+        self.layout.set_profile(&self.profile, layout);
+        if let Err(e) = self.layout.save(LAYOUT_FILE) {
+            tracing::error!(target: "layout", "{}", e);
+        }
+    }
+
+    /// 儲存目前 profile 後切換，新 profile 有紀錄時還原其介面狀態
+    fn switch_profile(&mut self, profile: &str) {
+        if profile == self.profile {
+            return;
+        }
+        self.save_layout();
+        self.profile = profile.to_string();
+        if let Some(layout) = self.layout.profile(profile).cloned() {
+            self.apply_layout(&layout);
+            tracing::info!(target: "layout", profile, "Restored layout");
+        }
+    }
+
     fn start_can(&self) {
         {
             let mut rec = self.is_receiving.lock().unwrap();
             if *rec {
-                eprintln!("CAN communication is already running.");
+                tracing::warn!(target: "can", "CAN communication is already running");
                 return;
             }
             *rec = true;
@@ -78,24 +658,47 @@ impl CanGui {
         let data_rx = Arc::new(data_rx);
 
         let is_receiving_clone = Arc::clone(&self.is_receiving);
-        let logs_store = Arc::clone(&self.logs);
         let data_store = Arc::clone(&self.data);
+        let capture = Arc::clone(&self.capture);
+        let receive_list = Arc::clone(&self.receive_list);
+        let latency = Arc::clone(&self.latency);
+        let gateway = Arc::clone(&self.gateway);
+        let broadcaster = Arc::clone(&self.broadcaster);
+        let id_filter = Arc::clone(&self.id_filter);
+        let trigger = Arc::clone(&self.trigger);
+        let alarms = Arc::clone(&self.alarms);
+        let alarm_can = self.can_app.clone();
+        let auto_responder = Arc::clone(&self.auto_responder);
+        let plot = Arc::clone(&self.plot);
+        let response_can = self.can_app.clone();
+        let blackbox = Arc::clone(&self.blackbox);
+        let capture_db = Arc::clone(&self.capture_db);
+        let remote_server = Arc::clone(&self.remote_server);
+        let signals = Arc::clone(&self.signals);
+        let dbc = Arc::clone(&self.dbc);
+        let obd = Arc::clone(&self.obd);
+        let canopen = Arc::clone(&self.canopen);
+        let sdo_client = Arc::clone(&self.sdo_client);
+        let events = Arc::clone(&self.events);
+        let monitor = Arc::clone(&self.monitor);
+        let conformance = Arc::clone(&self.conformance);
+        let sequences = Arc::clone(&self.sequences);
+        let silence = Arc::clone(&self.silence);
+        let timeline = Arc::clone(&self.timeline);
+        let tx_sequence = Arc::clone(&self.tx_sequence);
+        let stats = Arc::clone(&self.stats);
+        let self_test = Arc::clone(&self.self_test);
 
         {
             let log_rx = Arc::clone(&log_rx);
             let is_receiving = Arc::clone(&is_receiving_clone);
-            let logs_store = Arc::clone(&logs_store);
+            let language = Arc::clone(&self.language);
+            // 後端透過 log 通道回報的事件轉為 tracing 事件
             thread::spawn(move || {
                 let timeout = Duration::from_millis(100);
                 while *is_receiving.lock().unwrap() {
                     match log_rx.recv_timeout(timeout) {
-                        Ok(msg) => {
-                            let mut logs = logs_store.lock().unwrap();
-                            if logs.len() >= LOG_BUFFER_CAPACITY {
-                                logs.pop_front();
-                            }
-                            logs.push_back(format!("[LOG] {}", msg));
-                        }
+                        Ok(event) => emit_backend_event(&event, *language.lock().unwrap()),
                         Err(RecvTimeoutError::Timeout) => continue,
                         Err(RecvTimeoutError::Disconnected) => break,
                     }
@@ -108,63 +711,363 @@ impl CanGui {
             let is_receiving = Arc::clone(&is_receiving_clone);
             let data_store = Arc::clone(&data_store);
             thread::spawn(move || {
-                let timeout = Duration::from_millis(100);
+                // 送出已到期的自動回應
+                let send_responses = |now: u64| {
+                    for response in auto_responder.lock().unwrap().poll(now) {
+                        if let Err(e) = response_can.send_frame(&response) {
+                            tracing::warn!(target: "autoresponse", "Failed to send response: {}", e);
+                        }
+                    }
+                };
                 while *is_receiving.lock().unwrap() {
+                    // 有待送的延遲回應時提早醒來，避免回應時間被拉長
+                    let timeout = auto_responder
+                        .lock()
+                        .unwrap()
+                        .next_due()
+                        .map(|due| Duration::from_micros(due.saturating_sub(now_micros())))
+                        .unwrap_or(Duration::from_millis(100))
+                        .min(Duration::from_millis(100));
                     match data_rx.recv_timeout(timeout) {
-                        Ok(data_msg) => {
-                            let mut data_buf = data_store.lock().unwrap();
-                            if data_buf.len() >= DATA_BUFFER_CAPACITY {
-                                data_buf.pop_front();
+                        Ok(frame) => {
+                            self_test.lock().unwrap().observe(&frame);
+                            if !id_filter.lock().unwrap().accept(&frame) {
+                                continue;
+                            }
+                            timeline.lock().unwrap().record(frame.timestamp);
+                            trigger.lock().unwrap().process(&frame);
+                            if let Some(ref recorder) = *blackbox.lock().unwrap() {
+                                recorder.record(&frame);
+                            }
+                            if let Some(ref db) = *capture_db.lock().unwrap() {
+                                db.record(&frame);
+                            }
+                            silence
+                                .lock()
+                                .unwrap()
+                                .process(&frame, &mut events.lock().unwrap());
+                            // 介面回送的 TX 訊框已在傳送時計入延遲與統計，只進追蹤紀錄
+                            if frame.direction == FrameDirection::Tx {
+                                if let Some(ref server) = *remote_server.lock().unwrap() {
+                                    server.publish(&frame);
+                                }
+                                push_frame(&data_store, &capture, &receive_list, frame);
+                                continue;
+                            }
+                            latency.lock().unwrap().observe(&frame);
+                            stats.lock().unwrap().process(&frame);
+                            dbc.lock().unwrap().process(&frame);
+                            obd.lock().unwrap().process(&frame);
+                            canopen.lock().unwrap().process(&frame);
+                            let alarm_frames;
+                            {
+                                let mut signals = signals.lock().unwrap();
+                                signals.process(&frame);
+                                let mut monitor = monitor.lock().unwrap();
+                                let mut events = events.lock().unwrap();
+                                monitor.process(&frame, &mut events);
+                                monitor.check_thresholds(&signals, frame.timestamp, &mut events);
+                                monitor.check_watches(&signals, frame.timestamp, &mut events);
+                                alarm_frames = alarms.lock().unwrap().check(
+                                    &signals,
+                                    &dbc.lock().unwrap(),
+                                    frame.timestamp,
+                                    &mut events,
+                                );
+                                plot.lock().unwrap().record(
+                                    &signals,
+                                    &dbc.lock().unwrap(),
+                                    frame.timestamp,
+                                );
+                                monitor.poll(now_micros(), &mut events);
+                                silence.lock().unwrap().poll(now_micros(), &mut events);
+                                let mut sequences = sequences.lock().unwrap();
+                                sequences.process(&frame, &mut events);
+                                sequences.poll(now_micros(), &mut events);
+                            }
+                            for alarm_frame in alarm_frames {
+                                if let Err(e) = alarm_can.send_frame(&alarm_frame) {
+                                    tracing::warn!(target: "alarm", "Failed to send alarm frame: {}", e);
+                                }
                             }
-                            data_buf.push_back(format!("[DATA] {}", data_msg));
+                            auto_responder.lock().unwrap().process(&frame);
+                            send_responses(now_micros());
+                            conformance.lock().unwrap().process(&frame);
+                            if let Some(ref gw) = *gateway.lock().unwrap() {
+                                gw.forward(&frame);
+                            }
+                            if let Some(ref playback) = *tx_sequence.lock().unwrap() {
+                                playback.observe(&frame);
+                            }
+                            if let Some(ref client) = *sdo_client.lock().unwrap() {
+                                client.observe(&frame);
+                            }
+                            if let Some(ref mut udp) = *broadcaster.lock().unwrap() {
+                                udp.send(&frame);
+                            }
+                            if let Some(ref server) = *remote_server.lock().unwrap() {
+                                server.publish(&frame);
+                            }
+                            push_frame(&data_store, &capture, &receive_list, frame);
+                        }
+                        Err(RecvTimeoutError::Timeout) => {
+                            // 匯流排安靜時仍需檢查心跳逾時並更新負載
+                            stats.lock().unwrap().poll(now_micros());
+                            trigger.lock().unwrap().poll(now_micros());
+                            send_responses(now_micros());
+                            let mut events = events.lock().unwrap();
+                            monitor.lock().unwrap().poll(now_micros(), &mut events);
+                            silence.lock().unwrap().poll(now_micros(), &mut events);
+                            sequences.lock().unwrap().poll(now_micros(), &mut events);
                         }
-                        Err(RecvTimeoutError::Timeout) => continue,
                         Err(RecvTimeoutError::Disconnected) => break,
                     }
                 }
             });
         }
 
-        let dev_type: u32 = 4;
-        let dev_index: u32 = 0;
+        match self.open_interface(&log_tx, &data_tx) {
+            Ok((_, base)) => self.set_bitrates(base),
+            Err(err) => {
+                tracing::error!(target: "can", error = %err, "Open device failed");
+                *is_receiving_clone.lock().unwrap() = false;
+                return;
+            }
+        }
+        *self.can_senders.lock().unwrap() = Some((log_tx.clone(), data_tx.clone()));
+
+        {
+            let can = self.can_app.clone();
+            let enabled = Arc::clone(&self.auto_reconnect);
+            let is_receiving = Arc::clone(&is_receiving_clone);
+            thread::spawn(move || {
+                ReconnectWatchdog::default().run(&can, &log_tx, &data_tx, &enabled, || {
+                    *is_receiving.lock().unwrap()
+                });
+            });
+        }
+
+        // 自動記錄：與 Data 緩衝區無關，長時間擷取不會遺失
+        if self.blackbox_panel.config().auto_start {
+            self.blackbox_panel
+                .start_recording(&mut self.blackbox.lock().unwrap(), &self.session);
+        }
+    }
+
+    /// 依序載入並合併設定檔，套用到訊號、監看與檢查等各模組
+    fn load_config_files(&mut self, paths: Vec<std::path::PathBuf>) {
+        let names: Vec<&str> = paths.iter().filter_map(|p| p.to_str()).collect();
+        let (cfg, warnings) = match config::load_configs(&names) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                tracing::error!(target: "config", error = %e, "Failed to load config");
+                return;
+            }
+        };
+        for warning in warnings {
+            tracing::warn!(target: "config", "{}", warning);
+        }
+        tracing::info!(
+            target: "config",
+            paths = %names.join(" + "),
+            version = cfg.version,
+            "Loaded config"
+        );
+        tracing::debug!(target: "config", "{:?}", cfg);
+        self.latency
+            .lock()
+            .unwrap()
+            .set_config_pairs(&cfg.latency_pairs);
+        self.signals.lock().unwrap().load(&cfg.canbus_config);
+        self.units.lock().unwrap().load(&cfg.components);
+        self.monitor.lock().unwrap().load(
+            &cfg.heartbeats,
+            &cfg.counters,
+            &cfg.thresholds,
+            &cfg.watches,
+        );
+        self.conformance.lock().unwrap().load(&cfg);
+        self.sequences.lock().unwrap().load(&cfg.expected_sequences);
+        // 元件的數值由 SignalTable 依 canbus_config 解碼，收到訊框前顯示 --
+        self.yaml_components = Some(cfg.components);
+        self.tx_sequences = cfg.sequences;
+        // profile 以基本設定檔命名，疊加覆蓋設定時沿用
+        let profile = paths
+            .first()
+            .and_then(|p| p.file_stem())
+            .map_or(DEFAULT_PROFILE.to_string(), |s| {
+                s.to_string_lossy().into_owned()
+            });
+        self.config_paths = paths;
+        self.switch_profile(&profile);
+    }
 
+    /// 依目前後端的設定記錄各通道的位元率，用於計算匯流排負載；`base` 為介面的通道位移
+    fn set_bitrates(&self, base: u32) {
+        let mut stats = self.stats.lock().unwrap();
+        let mut set_bitrate =
+            |channel: u32, bitrate: u32| stats.set_bitrate(channel + base, bitrate);
         match self.api {
             CanApi::ControlCan => {
-                let channels = vec![
-                    (
-                        self.controlcan_ch1,
-                        VciCanBaudRate::from_u32(self.controlcan_baud1)
-                            .unwrap_or(VciCanBaudRate::Baud250K),
-                    ),
-                    (
-                        self.controlcan_ch2,
-                        VciCanBaudRate::from_u32(self.controlcan_baud2)
-                            .unwrap_or(VciCanBaudRate::Baud1M),
-                    ),
-                ];
-                let can_app = CanApp::new(dev_type, dev_index, channels);
-                if let Err(err) = can_app.open_device(log_tx.clone()) {
-                    eprintln!("ControlCAN open device failed: {}", err);
-                    *is_receiving_clone.lock().unwrap() = false;
-                    return;
+                for (channel, setting) in self.controlcan_channels.enabled() {
+                    set_bitrate(channel, setting.bitrate());
                 }
-                can_app.start_receiving(log_tx.clone(), data_tx.clone());
-                let mut can_app_guard = self.can_app.lock().unwrap();
-                *can_app_guard = Some(Box::new(can_app));
             }
             CanApi::Pcan => {
-                let channel: u32 = 0x51;
-                let pcan_baud =
-                    PcanBaudRate::from_u32(self.pcan_baud).unwrap_or(PcanBaudRate::Baud250K);
-                let can_app = PcanApp::new(channel, pcan_baud);
-                if let Err(err) = can_app.open_device(log_tx.clone()) {
-                    eprintln!("PCAN open device failed: {}", err);
-                    *is_receiving_clone.lock().unwrap() = false;
-                    return;
+                let bitrate = match self.pcan_fd_bitrate() {
+                    Ok(Some(fd_bitrate)) => fd_bitrate.nominal,
+                    _ => match self.pcan_timing {
+                        Some(timing) => timing.bitrate().round() as u32,
+                        None => self.pcan_baud * 1000,
+                    },
+                };
+                set_bitrate(self.pcan_channel, bitrate);
+            }
+            CanApi::Slcan => set_bitrate(0, self.slcan_baud * 1000),
+            CanApi::GsUsb => set_bitrate(self.gsusb_channel as u32, self.gsusb_baud * 1000),
+            CanApi::J2534 => set_bitrate(0, self.j2534_baud * 1000),
+            CanApi::Remote | CanApi::Sim => {}
+        }
+    }
+
+    /// 以目前選擇的後端建立介面，加入 `can_app` 並開始接收，回傳介面名稱與通道位移；
+    /// 通道與啟動中的介面重疊時由 `can_app` 位移
+    fn open_interface(
+        &self,
+        log_tx: &Sender<LogEvent>,
+        data_tx: &Sender<CanFrame>,
+    ) -> Result<(String, u32), String> {
+        let app: Box<dyn CanInterface + Send> = match self.api {
+            CanApi::ControlCan => {
+                let channels = self.controlcan_channels.vci_channels();
+                if channels.is_empty() {
+                    return Err("No ControlCAN channel enabled".to_string());
                 }
-                can_app.start_receiving(log_tx.clone(), data_tx.clone());
-                let mut can_app_guard = self.can_app.lock().unwrap();
-                *can_app_guard = Some(Box::new(can_app));
+                Box::new(
+                    CanApp::new(
+                        self.controlcan_dev_type,
+                        self.controlcan_dev_index,
+                        channels,
+                    )
+                    .with_mode(self.controlcan_mode, self.controlcan_self_reception),
+                )
+            }
+            CanApi::Pcan => {
+                let pcan_baud = match self.pcan_timing {
+                    Some(timing) => PcanBaudRate::Custom(timing.to_u16()),
+                    None => {
+                        PcanBaudRate::from_u32(self.pcan_baud).unwrap_or(PcanBaudRate::Baud250K)
+                    }
+                };
+                let fd_bitrate = self
+                    .pcan_fd_bitrate()
+                    .map_err(|e| format!("PCAN FD bitrate invalid: {}", e))?;
+                Box::new(
+                    PcanApp::new(self.pcan_channel, pcan_baud)
+                        .with_options(self.pcan_listen_only, self.pcan_echo)
+                        .with_fd(fd_bitrate),
+                )
+            }
+            CanApi::Slcan => Box::new(
+                SlcanApp::new(self.slcan_port.trim(), self.slcan_baud)
+                    .with_listen_only(self.slcan_listen_only),
+            ),
+            CanApi::GsUsb => Box::new(
+                GsUsbApp::new(self.gsusb_device, self.gsusb_channel, self.gsusb_baud)
+                    .with_listen_only(self.gsusb_listen_only),
+            ),
+            CanApi::J2534 => Box::new(J2534App::new(self.j2534_dll.trim(), self.j2534_baud)),
+            CanApi::Remote => {
+                Box::new(RemoteCanApp::new(&self.remote_address).with_token(&self.remote_token))
+            }
+            CanApi::Sim => Box::new(SimCanApp::new(true).with_traffic(self.sim_traffic.clone())),
+        };
+        let name = self.interface_name();
+        app.open_device(log_tx.clone())
+            .map_err(|e| format!("{}: {}", name, e))?;
+        let id = self.can_app.attach(name.clone(), app);
+        self.can_app
+            .start_receiving(id, log_tx.clone(), data_tx.clone());
+        let base = self
+            .can_app
+            .lock()
+            .iter()
+            .find(|a| a.id == id)
+            .map_or(0, |a| a.base);
+        Ok((name, base))
+    }
+
+    /// 以目前選擇的後端開啟另一個介面，與已啟動的介面同時接收
+    fn add_device(&self) {
+        let senders = self.can_senders.lock().unwrap().clone();
+        let Some((log_tx, data_tx)) = senders.filter(|_| *self.is_receiving.lock().unwrap()) else {
+            tracing::warn!(target: "can", "CAN communication is not running");
+            return;
+        };
+        match self.open_interface(&log_tx, &data_tx) {
+            Ok((name, base)) => {
+                tracing::info!(target: "can", interface = %name, base, "Interface added");
+                self.set_bitrates(base);
+            }
+            Err(err) => tracing::error!(target: "can", error = %err, "Open device failed"),
+        }
+    }
+
+    /// 停止並關閉單一介面，其他介面繼續接收
+    fn remove_device(&self, id: u32) {
+        let Some(interface) = self.can_app.detach(id) else {
+            return;
+        };
+        let log_tx = match *self.can_senders.lock().unwrap() {
+            Some((ref log_tx, _)) => log_tx.clone(),
+            None => unbounded().0,
+        };
+        interface.app.stop_receiving();
+        interface.app.close_device(log_tx);
+        tracing::info!(target: "can", interface = %interface.name, "Interface removed");
+    }
+
+    /// 依轉接器預設填入設定面板
+    fn apply_adapter_preset(&mut self, preset: &AdapterPreset) {
+        match preset.backend {
+            AdapterBackend::ControlCan => {
+                self.api = CanApi::ControlCan;
+                self.controlcan_dev_type = preset.dev_type;
+                self.controlcan_channels
+                    .fill(preset.channels, preset.default_baud);
+            }
+            AdapterBackend::Pcan => {
+                self.api = CanApi::Pcan;
+                self.pcan_baud = preset.default_baud;
+                self.pcan_timing = None;
+                self.pcan_fd = preset.can_fd;
+            }
+            AdapterBackend::Slcan => {
+                self.api = CanApi::Slcan;
+                self.slcan_baud = preset.default_baud;
+            }
+            AdapterBackend::GsUsb => {
+                self.api = CanApi::GsUsb;
+                self.gsusb_baud = preset.default_baud;
+            }
+        }
+        tracing::info!(target: "can", preset = preset.name, dll = preset.dll, "Adapter preset applied");
+    }
+
+    /// 從板卡資訊讀取 ControlCAN 裝置的通道數並調整通道清單
+    fn detect_controlcan_channels(&mut self) {
+        let probe = CanApp::new(
+            self.controlcan_dev_type,
+            self.controlcan_dev_index,
+            Vec::new(),
+        );
+        match probe.probe_channel_count() {
+            Ok(count) => {
+                tracing::info!(target: "can", count, "ControlCAN channels detected");
+                self.controlcan_channels.resize(count as usize);
+            }
+            Err(e) => {
+                tracing::error!(target: "can", error = %e, "ControlCAN channel detection failed")
             }
         }
     }
@@ -173,15 +1076,219 @@ impl CanGui {
         {
             let mut rec = self.is_receiving.lock().unwrap();
             if !*rec {
-                eprintln!("CAN communication is not running.");
+                tracing::warn!(target: "can", "CAN communication is not running");
                 return;
             }
             *rec = false;
         }
         let (log_tx, _) = unbounded();
-        if let Some(ref can_app) = *self.can_app.lock().unwrap() {
-            can_app.stop_receiving();
-            can_app.close_device(log_tx.clone());
+        for interface in self.can_app.detach_all() {
+            interface.app.stop_receiving();
+            interface.app.close_device(log_tx.clone());
+        }
+    }
+
+    /// PCAN FD 位元率設定，未啟用 FD 時為 None
+    fn pcan_fd_bitrate(&self) -> Result<Option<PcanFdBitrate>, String> {
+        self.pcan_fd
+            .then(|| PcanFdBitrate::parse(&self.pcan_fd_bitrate))
+            .transpose()
+    }
+
+    /// 將目前訊號值、各 ID 統計與通道狀態存成帶時間戳記的報告檔
+    fn interface_name(&self) -> String {
+        match self.api {
+            CanApi::ControlCan => format!(
+                "ControlCAN (type {} #{})",
+                self.controlcan_dev_type, self.controlcan_dev_index
+            ),
+            CanApi::Pcan => format!("PCAN ({})", pcan_channel_name(self.pcan_channel)),
+            CanApi::Slcan => format!("SLCAN ({})", self.slcan_port),
+            CanApi::GsUsb => format!("gs_usb #{} ch{}", self.gsusb_device, self.gsusb_channel),
+            CanApi::J2534 => format!("J2534 ({})", J2534App::dll_name(&self.j2534_dll)),
+            CanApi::Remote => format!("Remote ({})", self.remote_address),
+            CanApi::Sim => "Simulated".to_string(),
+        }
+    }
+
+    fn capture_snapshot(&self, now: chrono::DateTime<chrono::Local>) -> Snapshot {
+        let active: Vec<String> = self
+            .can_app
+            .interfaces()
+            .into_iter()
+            .map(|(_, name, _)| name)
+            .collect();
+        let interface = if active.is_empty() {
+            self.interface_name()
+        } else {
+            active.join(", ")
+        };
+        Snapshot::capture(
+            now.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+            now_micros(),
+            &interface,
+            *self.is_receiving.lock().unwrap(),
+            &self.signals.lock().unwrap(),
+            &self.units.lock().unwrap(),
+            &self.stats.lock().unwrap(),
+        )
+        .with_libraries(self.can_app.library_versions())
+        .with_session(self.session.clone())
+    }
+
+    /// 產生擷取報告（HTML），選擇的訊號以資料緩衝區繪圖
+    fn save_report(&self) {
+        let now = chrono::Local::now();
+        let default_name = format!("report_{}.html", now.format("%Y%m%d_%H%M%S"));
+        let Some(path) = FileDialog::new()
+            .add_filter("html", &["html"])
+            .set_file_name(&default_name)
+            .save_file()
+        else {
+            return;
+        };
+        let snapshot = self.capture_snapshot(now);
+        let frames: Vec<CanFrame> = self.data.lock().unwrap().iter().copied().collect();
+        let plots = {
+            let signals = self.signals.lock().unwrap();
+            let units = self.units.lock().unwrap();
+            let silences: Vec<_> = self.silence.lock().unwrap().gaps().copied().collect();
+            self.report_panel
+                .selected()
+                .map(|key| signal_plot(&frames, signals.entries(), key, &units, &silences))
+                .collect()
+        };
+        let config = config_listing(&self.config_paths);
+        let report = CaptureReport {
+            started_us: snapshot.channels.iter().map(|c| c.since_us).min(),
+            snapshot: &snapshot,
+            events: self.events.lock().unwrap().events().cloned().collect(),
+            conformance: self
+                .conformance
+                .lock()
+                .unwrap()
+                .results()
+                .into_iter()
+                .chain(self.sequences.lock().unwrap().results())
+                .collect(),
+            plots,
+            config,
+        };
+        match report.save(&path.to_string_lossy()) {
+            Ok(()) => tracing::info!(target: "report", path = %path.display(), "Saved report"),
+            Err(e) => tracing::error!(target: "report", "{}", e),
+        }
+    }
+
+    fn save_snapshot(&self) {
+        let now = chrono::Local::now();
+        let default_name = format!("snapshot_{}.json", now.format("%Y%m%d_%H%M%S"));
+        let Some(path) = FileDialog::new()
+            .add_filter("json", &["json"])
+            .add_filter("csv", &["csv"])
+            .set_file_name(&default_name)
+            .save_file()
+        else {
+            return;
+        };
+        let snapshot = self.capture_snapshot(now);
+        match snapshot.save(path.to_str().unwrap()) {
+            Ok(()) => tracing::info!(target: "snapshot", path = %path.display(), "Saved snapshot"),
+            Err(e) => tracing::error!(target: "snapshot", error = %e, "Failed to save snapshot"),
+        }
+    }
+
+    /// 將目前記憶體中的資料緩衝區匯出，用於未開啟記錄時保存剛發生的狀況
+    fn export_buffer(&self) {
+        let now = chrono::Local::now();
+        let default_name = format!("buffer_{}.log", now.format("%Y%m%d_%H%M%S"));
+        let Some(path) = FileDialog::new()
+            .add_filter("candump log", &["log"])
+            .add_filter("csv", &["csv"])
+            .add_filter("Vector ASC", &["asc"])
+            .add_filter("PEAK TRC", &["trc"])
+            .add_filter("Vector BLF", &["blf"])
+            .set_file_name(&default_name)
+            .save_file()
+        else {
+            return;
+        };
+        let path = path.to_str().unwrap();
+        // 先複製再寫檔，避免寫檔期間阻塞接收執行緒
+        let frames: Vec<CanFrame> = self
+            .data
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|f| !self.export_filtered || self.trace.matches(f))
+            .copied()
+            .collect();
+        match export_frames(path, &frames, ExportFormat::from_path(path), &self.session) {
+            Ok(count) => tracing::info!(target: "export", path, count, "Exported data buffer"),
+            Err(e) => tracing::error!(target: "export", "{}", e),
+        }
+    }
+
+    /// 將完整擷取匯出為 CSV 或 BLF，包含已超出 Data 緩衝區的訊框
+    fn export_capture(&self, format: ExportFormat) {
+        let now = chrono::Local::now();
+        let (filter, extension) = match format {
+            ExportFormat::Blf => ("Vector BLF", "blf"),
+            _ => ("csv", "csv"),
+        };
+        let default_name = format!("capture_{}.{}", now.format("%Y%m%d_%H%M%S"), extension);
+        let Some(path) = FileDialog::new()
+            .add_filter(filter, &[extension])
+            .set_file_name(&default_name)
+            .save_file()
+        else {
+            return;
+        };
+        let path = path.to_str().unwrap();
+        let (frames, dropped) = {
+            let capture = self.capture.lock().unwrap();
+            let frames: Vec<CanFrame> = capture
+                .frames()
+                .filter(|f| !self.export_filtered || self.trace.matches(f))
+                .copied()
+                .collect();
+            (frames, capture.dropped())
+        };
+        if dropped > 0 {
+            tracing::warn!(target: "export", dropped, "Capture limit reached; oldest frames were discarded");
+        }
+        match export_frames(path, &frames, format, &self.session) {
+            Ok(count) => tracing::info!(target: "export", path, count, "Exported capture"),
+            Err(e) => tracing::error!(target: "export", "{}", e),
+        }
+    }
+
+    /// 以目前的 YAML 訊號與 DBC 解碼完整擷取，匯出為 MDF 4 量測檔
+    fn export_signals_mf4(&self) {
+        let now = chrono::Local::now();
+        let default_name = format!("signals_{}.mf4", now.format("%Y%m%d_%H%M%S"));
+        let Some(path) = FileDialog::new()
+            .add_filter("ASAM MDF 4", &["mf4"])
+            .set_file_name(&default_name)
+            .save_file()
+        else {
+            return;
+        };
+        let path = path.to_str().unwrap();
+        let frames: Vec<CanFrame> = self
+            .capture
+            .lock()
+            .unwrap()
+            .frames()
+            .filter(|f| !self.export_filtered || self.trace.matches(f))
+            .copied()
+            .collect();
+        let entries = self.signals.lock().unwrap().entries().to_vec();
+        let dbc = self.dbc.lock().unwrap().dbc().clone();
+        let series = mdf::decode_signals(&frames, &entries, &dbc, &self.units.lock().unwrap());
+        match mdf::export_mf4(path, &series, &self.session) {
+            Ok(count) => tracing::info!(target: "export", path, count, "Exported decoded signals"),
+            Err(e) => tracing::error!(target: "export", "{}", e),
         }
     }
 }
@@ -195,60 +1302,292 @@ fn main() -> eframe::Result<()> {
 }
 
 impl eframe::App for CanGui {
+    fn on_exit(&mut self, _gl: Option<&eframe::glow::Context>) {
+        self.save_layout();
+    }
+
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        if self.alarms.lock().unwrap().take_sound() {
+            alarm_panel::play_alarm_sound();
+        }
         egui::TopBottomPanel::top("config_panel").show(ctx, |ui| {
             ui.heading("CAN Bus Configuration");
             ui.horizontal(|ui| {
                 ui.label("Select CAN API:");
                 ui.radio_value(&mut self.api, CanApi::ControlCan, "ControlCAN");
                 ui.radio_value(&mut self.api, CanApi::Pcan, "PCAN");
+                ui.radio_value(&mut self.api, CanApi::Slcan, "SLCAN");
+                ui.radio_value(&mut self.api, CanApi::GsUsb, "gs_usb");
+                ui.radio_value(&mut self.api, CanApi::J2534, "J2534");
+                ui.radio_value(&mut self.api, CanApi::Remote, "Remote");
+                ui.radio_value(&mut self.api, CanApi::Sim, "Simulated");
+            });
+            ui.horizontal(|ui| {
+                ui.label("Adapter:");
+                let mut preset = self.adapter_preset;
+                egui::ComboBox::from_id_salt("adapter_preset")
+                    .selected_text(preset.map_or("Custom", |p| p.name))
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(&mut preset, None, "Custom");
+                        for p in ADAPTER_PRESETS {
+                            ui.selectable_value(&mut preset, Some(p), p.name);
+                        }
+                    });
+                if preset != self.adapter_preset {
+                    self.adapter_preset = preset;
+                    if let Some(p) = preset {
+                        self.apply_adapter_preset(p);
+                    }
+                }
+                if let Some(p) = self.adapter_preset {
+                    ui.weak(p.summary());
+                }
             });
             match self.api {
                 CanApi::ControlCan => {
+                    ui.separator();
+                    let running = *self.is_receiving.lock().unwrap();
+                    ui.add_enabled_ui(!running, |ui| {
+                        ui.horizontal(|ui| {
+                            ui.label("Device type:");
+                            let name = CONTROL_CAN_DEV_TYPES
+                                .iter()
+                                .find(|(dev_type, _)| *dev_type == self.controlcan_dev_type)
+                                .map_or("Custom", |(_, name)| name);
+                            egui::ComboBox::from_id_salt("controlcan_dev_type")
+                                .selected_text(format!("{} ({})", name, self.controlcan_dev_type))
+                                .show_ui(ui, |ui| {
+                                    for (dev_type, name) in CONTROL_CAN_DEV_TYPES {
+                                        ui.selectable_value(
+                                            &mut self.controlcan_dev_type,
+                                            dev_type,
+                                            format!("{} ({})", name, dev_type),
+                                        );
+                                    }
+                                });
+                            ui.add(
+                                egui::DragValue::new(&mut self.controlcan_dev_type).range(0..=255),
+                            );
+                            ui.label("Index:");
+                            ui.add(
+                                egui::DragValue::new(&mut self.controlcan_dev_index).range(0..=15),
+                            );
+                        });
+                    });
+                    if self.controlcan_channels.show(ui, running) {
+                        self.detect_controlcan_channels();
+                    }
+                    ui.horizontal(|ui| {
+                        ui.label("Mode:");
+                        egui::ComboBox::from_id_salt("controlcan_mode")
+                            .selected_text(format!("{:?}", self.controlcan_mode))
+                            .show_ui(ui, |ui| {
+                                for mode in [
+                                    ControllerMode::Normal,
+                                    ControllerMode::ListenOnly,
+                                    ControllerMode::Loopback,
+                                ] {
+                                    ui.selectable_value(
+                                        &mut self.controlcan_mode,
+                                        mode,
+                                        format!("{:?}", mode),
+                                    );
+                                }
+                            });
+                        ui.checkbox(&mut self.controlcan_self_reception, "Self-reception");
+                    });
+                }
+                CanApi::Pcan => {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Channel:");
+                        let selected = self
+                            .pcan_channels
+                            .iter()
+                            .find(|c| c.handle == self.pcan_channel)
+                            .map_or_else(|| pcan_channel_name(self.pcan_channel), |c| c.label());
+                        egui::ComboBox::from_id_salt("pcan_channel")
+                            .selected_text(selected)
+                            .show_ui(ui, |ui| {
+                                if self.pcan_channels.is_empty() {
+                                    // 無法列舉時（驅動未安裝或舊版函式庫）仍可手動選擇
+                                    for handle in pcan_channel_handles() {
+                                        ui.selectable_value(
+                                            &mut self.pcan_channel,
+                                            handle,
+                                            pcan_channel_name(handle),
+                                        );
+                                    }
+                                }
+                                for channel in &self.pcan_channels {
+                                    ui.selectable_value(
+                                        &mut self.pcan_channel,
+                                        channel.handle,
+                                        channel.label(),
+                                    );
+                                }
+                            });
+                        if ui.button("Refresh").clicked() {
+                            self.pcan_channels = PcanApp::attached_channels();
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("PCAN Baud Rate:");
+                        if baud_rate_combo(
+                            ui,
+                            "pcan_baud",
+                            &PCAN_BAUD_RATES,
+                            &mut self.pcan_baud,
+                            &mut self.pcan_timing,
+                            ["BTR0", "BTR1"],
+                            |baud| {
+                                Sja1000Timing::from_u16(
+                                    PcanBaudRate::from_u32(baud)
+                                        .unwrap_or(PcanBaudRate::Baud250K)
+                                        .to_u16(),
+                                )
+                            },
+                        ) {
+                            self.pcan_calculator_open = !self.pcan_calculator_open;
+                        }
+                    });
+                    if self.pcan_calculator_open && self.pcan_timing.is_some() {
+                        if let Some(timing) = self.pcan_calculator.show(ui) {
+                            self.pcan_timing = Some(timing);
+                        }
+                    }
+                    ui.horizontal(|ui| {
+                        ui.checkbox(&mut self.pcan_listen_only, "Listen-only");
+                        ui.checkbox(&mut self.pcan_echo, "Echo frames (loopback)");
+                        ui.checkbox(&mut self.pcan_fd, "CAN FD");
+                    });
+                    if self.pcan_fd {
+                        ui.horizontal(|ui| {
+                            ui.label("FD bitrate:");
+                            egui::ComboBox::from_id_salt("pcan_fd_bitrate")
+                                .selected_text("Preset")
+                                .show_ui(ui, |ui| {
+                                    for (label, text) in PCAN_FD_BITRATES {
+                                        if ui
+                                            .selectable_label(self.pcan_fd_bitrate == text, label)
+                                            .clicked()
+                                        {
+                                            self.pcan_fd_bitrate = text.to_string();
+                                        }
+                                    }
+                                });
+                            match self.pcan_fd_bitrate() {
+                                Ok(Some(fd_bitrate)) => ui.weak(format!(
+                                    "{}K / {}K",
+                                    fd_bitrate.nominal / 1000,
+                                    fd_bitrate.data / 1000
+                                )),
+                                Err(err) => ui.colored_label(egui::Color32::RED, err),
+                                Ok(None) => ui.weak(""),
+                            };
+                        });
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.pcan_fd_bitrate)
+                                .desired_rows(2)
+                                .desired_width(f32::INFINITY),
+                        );
+                    }
+                }
+                CanApi::Slcan => {
                     ui.separator();
                     ui.horizontal(|ui| {
-                        ui.label("Channel 1:");
-                        ui.add(egui::DragValue::new(&mut self.controlcan_ch1));
+                        ui.label("Serial port:");
+                        egui::ComboBox::from_id_salt("slcan_port")
+                            .selected_text(if self.slcan_port.is_empty() {
+                                "Select..."
+                            } else {
+                                &self.slcan_port
+                            })
+                            .show_ui(ui, |ui| {
+                                for port in &self.slcan_ports {
+                                    ui.selectable_value(&mut self.slcan_port, port.clone(), port);
+                                }
+                            });
+                        ui.text_edit_singleline(&mut self.slcan_port);
+                        if ui.button("Refresh").clicked() {
+                            self.slcan_ports = SlcanApp::available_ports();
+                        }
+                    });
+                    ui.horizontal(|ui| {
                         ui.label("Baud Rate:");
-                        egui::ComboBox::from_id_salt("baud1")
-                            .selected_text(format!("{}K", self.controlcan_baud1))
+                        egui::ComboBox::from_id_salt("slcan_baud")
+                            .selected_text(format!("{}K", self.slcan_baud))
                             .show_ui(ui, |ui| {
-                                for &rate in CONTROL_CAN_BAUD_RATES.iter() {
+                                for &rate in SLCAN_BAUD_RATES.iter() {
                                     ui.selectable_value(
-                                        &mut self.controlcan_baud1,
+                                        &mut self.slcan_baud,
                                         rate,
                                         format!("{}K", rate),
                                     );
                                 }
                             });
+                        ui.checkbox(&mut self.slcan_listen_only, "Listen-only");
+                    });
+                }
+                CanApi::GsUsb => {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Device:");
+                        egui::ComboBox::from_id_salt("gsusb_device")
+                            .selected_text(
+                                self.gsusb_devices
+                                    .get(self.gsusb_device)
+                                    .map_or("No device found", |d| d.as_str()),
+                            )
+                            .show_ui(ui, |ui| {
+                                for (index, device) in self.gsusb_devices.iter().enumerate() {
+                                    ui.selectable_value(&mut self.gsusb_device, index, device);
+                                }
+                            });
+                        if ui.button("Refresh").clicked() {
+                            self.gsusb_devices = GsUsbApp::list_devices();
+                        }
+                        ui.label("Channel:");
+                        ui.add(egui::DragValue::new(&mut self.gsusb_channel).range(0..=7));
                     });
                     ui.horizontal(|ui| {
-                        ui.label("Channel 2:");
-                        ui.add(egui::DragValue::new(&mut self.controlcan_ch2));
                         ui.label("Baud Rate:");
-                        egui::ComboBox::from_id_salt("baud2")
-                            .selected_text(format!("{}K", self.controlcan_baud2))
+                        egui::ComboBox::from_id_salt("gsusb_baud")
+                            .selected_text(format!("{}K", self.gsusb_baud))
                             .show_ui(ui, |ui| {
-                                for &rate in CONTROL_CAN_BAUD_RATES.iter() {
+                                for &rate in GS_USB_BAUD_RATES.iter() {
                                     ui.selectable_value(
-                                        &mut self.controlcan_baud2,
+                                        &mut self.gsusb_baud,
                                         rate,
                                         format!("{}K", rate),
                                     );
                                 }
                             });
+                        ui.checkbox(&mut self.gsusb_listen_only, "Listen-only");
                     });
                 }
-                CanApi::Pcan => {
+                CanApi::J2534 => {
                     ui.separator();
                     ui.horizontal(|ui| {
-                        ui.label("PCAN Baud Rate:");
-                        egui::ComboBox::from_id_salt("pcan_baud")
-                            .selected_text(format!("{}K", self.pcan_baud))
+                        ui.label("Pass-thru DLL:");
+                        ui.text_edit_singleline(&mut self.j2534_dll)
+                            .on_hover_text("Vendor J2534 library (PassThruSupport.04.04)");
+                        if ui.button("Browse").clicked() {
+                            if let Some(path) =
+                                FileDialog::new().add_filter("dll", &["dll"]).pick_file()
+                            {
+                                self.j2534_dll = path.to_string_lossy().into_owned();
+                            }
+                        }
+                    });
+                    ui.horizontal(|ui| {
+                        ui.label("Baud Rate:");
+                        egui::ComboBox::from_id_salt("j2534_baud")
+                            .selected_text(format!("{}K", self.j2534_baud))
                             .show_ui(ui, |ui| {
-                                for &rate in PCAN_BAUD_RATES.iter() {
+                                for &rate in J2534_BAUD_RATES.iter() {
                                     ui.selectable_value(
-                                        &mut self.pcan_baud,
+                                        &mut self.j2534_baud,
                                         rate,
                                         format!("{}K", rate),
                                     );
@@ -256,25 +1595,78 @@ impl eframe::App for CanGui {
                             });
                     });
                 }
+                CanApi::Remote => {
+                    ui.separator();
+                    ui.horizontal(|ui| {
+                        ui.label("Remote server:");
+                        ui.text_edit_singleline(&mut self.remote_address);
+                        ui.label("Token:");
+                        ui.add(
+                            egui::TextEdit::singleline(&mut self.remote_token)
+                                .password(true)
+                                .desired_width(100.0),
+                        )
+                        .on_hover_text("Required to transmit; leave empty to only receive");
+                    });
+                }
+                CanApi::Sim => {
+                    ui.separator();
+                    let running = *self.is_receiving.lock().unwrap();
+                    ui::sim_panel::show_sim_traffic(ui, &mut self.sim_traffic, running);
+                }
             }
             // 新增「Load YAML Config」按鈕，讓使用者可以選取檔案
-            if ui.button("Load YAML Config").clicked() {
-                if let Some(path) = FileDialog::new().pick_file() {
-                    match config::load_config(path.to_str().unwrap()) {
-                        Ok(cfg) => {
-                            let mut logs = self.logs.lock().unwrap();
-                            logs.push_back(format!("[CONFIG] Loaded: {:?}", cfg));
-                            // 儲存載入的 components 到欄位中
-                            // 這裡只取 components 部分，初始值 0 可在 UI 上顯示
-                            self.yaml_components = Some(cfg.components);
-                        }
-                        Err(e) => {
-                            let mut logs = self.logs.lock().unwrap();
-                            logs.push_back(format!("[CONFIG] Failed to load config: {}", e));
-                        }
+            ui.horizontal(|ui| {
+                if ui.button("Load YAML Config").clicked() {
+                    if let Some(path) = FileDialog::new().pick_file() {
+                        self.load_config_files(vec![path]);
                     }
                 }
-            }
+                // 覆蓋設定疊加在目前的設定檔之上，相同項目以後載入者為準
+                if ui
+                    .add_enabled(
+                        !self.config_paths.is_empty(),
+                        egui::Button::new("Add Overlay"),
+                    )
+                    .on_hover_text("Merge another YAML config on top of the loaded ones")
+                    .clicked()
+                {
+                    if let Some(path) = FileDialog::new().pick_file() {
+                        let mut paths = self.config_paths.clone();
+                        paths.retain(|p| *p != path);
+                        paths.push(path);
+                        self.load_config_files(paths);
+                    }
+                }
+                if !self.config_paths.is_empty() {
+                    let names: Vec<String> = self
+                        .config_paths
+                        .iter()
+                        .filter_map(|p| p.file_name())
+                        .map(|n| n.to_string_lossy().into_owned())
+                        .collect();
+                    ui.weak(names.join(" + "));
+                }
+            });
+            ui.collapsing("Session info", |ui| {
+                egui::Grid::new("session_info")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        ui.label("Operator:");
+                        ui.text_edit_singleline(&mut self.session.operator);
+                        ui.end_row();
+                        ui.label("DUT:");
+                        ui.text_edit_singleline(&mut self.session.dut);
+                        ui.end_row();
+                        ui.label("Description:");
+                        ui.text_edit_singleline(&mut self.session.description);
+                        ui.end_row();
+                        ui.label("Notes:");
+                        ui.text_edit_multiline(&mut self.session.notes);
+                        ui.end_row();
+                    });
+                ui.weak("Embedded in exported logs, snapshots and reports");
+            });
 
             ui.horizontal(|ui| {
                 if ui.button("Start CAN").clicked() {
@@ -283,55 +1675,505 @@ impl eframe::App for CanGui {
                 if ui.button("Stop CAN").clicked() {
                     self.stop_can();
                 }
+                let running = *self.is_receiving.lock().unwrap();
+                if ui
+                    .add_enabled(running, egui::Button::new("Add Device"))
+                    .on_hover_text("Open the selected backend as an additional interface, e.g. a PCAN next to a ControlCAN device")
+                    .clicked()
+                {
+                    self.add_device();
+                }
+                let mut auto_reconnect = self.auto_reconnect.load(Ordering::SeqCst);
+                if ui
+                    .checkbox(&mut auto_reconnect, "Auto reconnect")
+                    .on_hover_text("Reopen the device with the same settings when it stops responding, e.g. after a USB unplug")
+                    .changed()
+                {
+                    self.auto_reconnect.store(auto_reconnect, Ordering::SeqCst);
+                }
+                if ui
+                    .button("Clear RX Buffer")
+                    .on_hover_text("Discard frames still queued in the driver")
+                    .clicked()
+                {
+                    match self.can_app.clear_receive_buffer() {
+                        Ok(()) => tracing::info!(target: "can", "Receive buffer cleared"),
+                        Err(e) => tracing::error!(target: "can", "{}", e),
+                    }
+                }
+                if ui.button("Snapshot").clicked() {
+                    self.save_snapshot();
+                }
+                ui.toggle_value(&mut self.show_report, "Report");
+                show_bus_status(ui, &self.can_app.bus_status());
+            });
+            let interfaces = self.can_app.interfaces();
+            if !interfaces.is_empty() {
+                ui.horizontal_wrapped(|ui| {
+                    ui.label("Interfaces:");
+                    for (id, name, channels) in interfaces {
+                        let channels: Vec<String> = channels.iter().map(u32::to_string).collect();
+                        ui.label(format!("{} [CH {}]", name, channels.join(", ")));
+                        if ui
+                            .small_button("✖")
+                            .on_hover_text("Stop and close this interface")
+                            .clicked()
+                        {
+                            self.remove_device(id);
+                        }
+                    }
+                });
+            }
+            ui.horizontal(|ui| {
+                ui.label("Views:");
+                ui.toggle_value(&mut self.show_latency, "Latency");
+                ui.toggle_value(&mut self.show_gateway, "Gateway");
+                ui.toggle_value(&mut self.show_broadcast, "UDP Broadcast");
+                ui.toggle_value(&mut self.show_id_filter, "ID Filter");
+                ui.toggle_value(&mut self.show_alarms, "Alarms");
+                ui.toggle_value(&mut self.show_auto_response, "Auto Response");
+                ui.toggle_value(&mut self.show_plot, "Plot");
+                ui.toggle_value(&mut self.show_trigger, "Trigger");
+                ui.toggle_value(&mut self.show_blackbox, "Black Box");
+                ui.toggle_value(&mut self.show_capture_db, "Capture DB");
+                ui.toggle_value(&mut self.show_dbc, "DBC Signals");
+                ui.toggle_value(&mut self.show_obd, "OBD-II");
+                ui.toggle_value(&mut self.show_canopen, "CANopen");
+                ui.toggle_value(&mut self.show_remote_server, "Remote Server");
+                ui.toggle_value(&mut self.show_watch, "Watch List");
+                ui.toggle_value(&mut self.show_sampler, "Sampler");
+                ui.toggle_value(&mut self.show_scheduler, "Scheduler");
+                ui.toggle_value(&mut self.show_replay, "Replay");
+                ui.toggle_value(&mut self.show_tx_sequences, "TX Sequences");
+                ui.toggle_value(&mut self.show_self_test, "Self Test");
+                ui.toggle_value(&mut self.show_channel_stats, "Channels");
+                ui.toggle_value(&mut self.show_bus_stats, "Statistics");
+                ui.toggle_value(&mut self.show_id_stats, "ID Timing");
+                ui.toggle_value(&mut self.show_log_viewer, "Offline Log");
+                ui.toggle_value(&mut self.show_conformance, "Conformance");
+                ui.toggle_value(&mut self.show_timeline, "Timeline");
+                if ui
+                    .button("+ Trace")
+                    .on_hover_text("Open another trace with its own display filter")
+                    .clicked()
+                {
+                    self.extra_traces
+                        .push((self.next_trace, true, TraceView::default()));
+                    self.next_trace += 1;
+                }
+                let unacknowledged = self.events.lock().unwrap().unacknowledged();
+                let label = if unacknowledged > 0 {
+                    format!("Events ({})", unacknowledged)
+                } else {
+                    "Events".to_string()
+                };
+                ui.toggle_value(&mut self.show_events, label);
             });
         });
 
+        egui::Window::new("Request/Response Latency")
+            .open(&mut self.show_latency)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                self.latency_panel.show(ui, &self.latency);
+            });
+
+        egui::Window::new("Gateway")
+            .open(&mut self.show_gateway)
+            .default_width(420.0)
+            .show(ctx, |ui| {
+                self.gateway_panel.show(ui, &self.gateway, &self.can_app);
+            });
+
+        egui::Window::new("Watch List")
+            .open(&mut self.show_watch)
+            .default_width(480.0)
+            .show(ctx, |ui| {
+                let alarmed = self.alarms.lock().unwrap().alarmed_signals();
+                ui::watch_panel::show_watch_list(ui, &self.signals, &self.units, &alarmed);
+            });
+
+        egui::Window::new("Conformance Checks")
+            .open(&mut self.show_conformance)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                ui::conformance_panel::show_conformance(ui, &self.conformance, &self.sequences);
+            });
+
+        egui::Window::new("Fixed-Rate Sampler")
+            .open(&mut self.show_sampler)
+            .show(ctx, |ui| {
+                self.sampler_panel.show(ui, &self.signals, &self.units);
+            });
+
+        egui::Window::new("TX Scheduler")
+            .open(&mut self.show_scheduler)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                self.scheduler_panel
+                    .show(ui, &self.can_app, &self.signals, &self.units);
+            });
+
+        egui::Window::new("Log Replay")
+            .open(&mut self.show_replay)
+            .show(ctx, |ui| {
+                self.replay_panel.show(ui, &self.can_app);
+            });
+
+        egui::Window::new("TX Sequences")
+            .open(&mut self.show_tx_sequences)
+            .show(ctx, |ui| {
+                ui::tx_sequence_panel::show_tx_sequences(
+                    ui,
+                    &self.tx_sequences,
+                    &self.tx_sequence,
+                    &self.can_app,
+                );
+            });
+
+        egui::Window::new("Channel Statistics")
+            .open(&mut self.show_channel_stats)
+            .show(ctx, |ui| {
+                ui::channel_stats_panel::show_channel_stats(
+                    ui,
+                    &self.stats,
+                    self.can_app.receive_stats(),
+                );
+                ui.separator();
+                ui::channel_stats_panel::show_silence(ui, &self.silence);
+            });
+
+        egui::Window::new("Bus Statistics")
+            .open(&mut self.show_bus_stats)
+            .show(ctx, |ui| {
+                self.bus_stats_panel.show(ui, &self.stats);
+            });
+
+        egui::Window::new("ID Timing")
+            .open(&mut self.show_id_stats)
+            .default_height(400.0)
+            .show(ctx, |ui| {
+                self.id_stats_panel.show(ui, &self.stats);
+            });
+
+        for (number, open, trace) in &mut self.extra_traces {
+            egui::Window::new(format!("Trace {}", number))
+                .open(open)
+                .default_width(480.0)
+                .default_height(360.0)
+                .show(ctx, |ui| {
+                    let start = self.capture.lock().unwrap().start_time();
+                    let (data, receive_list) = match self.frozen {
+                        Some(ref frozen) => (&frozen.data, &frozen.receive_list),
+                        None => (&*self.data, &*self.receive_list),
+                    };
+                    trace.show(ui, data, receive_list, start)
+                });
+        }
+        self.extra_traces.retain(|(_, open, _)| *open);
+
+        let mut show_report = self.show_report;
+        egui::Window::new("Capture Report")
+            .open(&mut show_report)
+            .show(ctx, |ui| {
+                if self.report_panel.show(ui, &self.signals) {
+                    self.save_report();
+                }
+            });
+        self.show_report = show_report;
+
+        egui::Window::new("Offline Log Viewer")
+            .open(&mut self.show_log_viewer)
+            .default_width(640.0)
+            .default_height(480.0)
+            .show(ctx, |ui| {
+                self.log_viewer.show(ui);
+            });
+
+        egui::Window::new("Self Test")
+            .open(&mut self.show_self_test)
+            .show(ctx, |ui| {
+                self.self_test_panel
+                    .show(ui, &self.self_test, &self.can_app);
+            });
+
+        egui::Window::new("Events")
+            .open(&mut self.show_events)
+            .default_width(560.0)
+            .show(ctx, |ui| {
+                self.events_panel.show(ui, &self.events);
+            });
+
+        egui::Window::new("Remote Server")
+            .open(&mut self.show_remote_server)
+            .show(ctx, |ui| {
+                self.remote_server_panel
+                    .show(ui, &self.remote_server, &self.can_app);
+            });
+
+        egui::Window::new("UDP Broadcast")
+            .open(&mut self.show_broadcast)
+            .show(ctx, |ui| {
+                self.broadcast_panel.show(ui, &self.broadcaster);
+            });
+
+        egui::Window::new("ID Filter Lists")
+            .open(&mut self.show_id_filter)
+            .show(ctx, |ui| {
+                self.id_filter_panel.show(ui, &self.id_filter);
+            });
+
+        egui::Window::new("Signal Alarms")
+            .open(&mut self.show_alarms)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                self.alarm_panel.show(ui, &self.alarms);
+            });
+
+        egui::Window::new("Auto Response")
+            .open(&mut self.show_auto_response)
+            .default_width(720.0)
+            .show(ctx, |ui| {
+                self.auto_response_panel.show(ui, &self.auto_responder);
+            });
+
+        egui::Window::new("Signal Plot")
+            .open(&mut self.show_plot)
+            .default_width(640.0)
+            .show(ctx, |ui| {
+                self.plot_panel
+                    .show(ui, &self.plot, &self.signals, &self.dbc, &self.units);
+            });
+
+        egui::Window::new("Trigger Capture")
+            .open(&mut self.show_trigger)
+            .show(ctx, |ui| {
+                self.trigger_panel.show(ui, &self.trigger, &self.session);
+            });
+
+        egui::Window::new("Black Box")
+            .open(&mut self.show_blackbox)
+            .show(ctx, |ui| {
+                self.blackbox_panel.show(ui, &self.blackbox, &self.session);
+            });
+
+        egui::Window::new("Capture Database")
+            .open(&mut self.show_capture_db)
+            .show(ctx, |ui| {
+                self.capture_db_panel
+                    .show(ui, &self.capture_db, &self.session);
+            });
+
+        egui::Window::new("DBC Signals")
+            .open(&mut self.show_dbc)
+            .show(ctx, |ui| {
+                let alarmed = self.alarms.lock().unwrap().alarmed_signals();
+                self.dbc_panel.show(ui, &self.dbc, &self.can_app, &alarmed);
+            });
+
+        egui::Window::new("OBD-II Live Data")
+            .open(&mut self.show_obd)
+            .show(ctx, |ui| {
+                self.obd_panel.show(ui, &self.obd, &self.can_app);
+            });
+
+        egui::Window::new("CANopen")
+            .open(&mut self.show_canopen)
+            .show(ctx, |ui| {
+                self.canopen_panel
+                    .show(ui, &self.canopen, &self.sdo_client, &self.can_app);
+            });
+
+        egui::SidePanel::right("tx_panel").show(ctx, |ui| {
+            self.tx_panel.show(ui, &self.can_app);
+        });
+
         // 在中央面板中動態生成 YAML 中的 components 對應的 ui label
+        if self.show_timeline {
+            egui::TopBottomPanel::bottom("timeline_panel").show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    ui.strong("Timeline");
+                    ui.weak("frame density, silent periods (gray) and alarms/warnings");
+                    if ui.small_button("Clear").clicked() {
+                        self.timeline.lock().unwrap().clear();
+                    }
+                });
+                let silences: Vec<_> = self.silence.lock().unwrap().gaps().copied().collect();
+                let jump = ui::timeline::show_timeline(
+                    ui,
+                    &self.timeline.lock().unwrap(),
+                    self.events.lock().unwrap().events(),
+                    &silences,
+                );
+                if let Some(timestamp) = jump {
+                    self.trace.jump_to(timestamp);
+                }
+            });
+        }
+
         egui::CentralPanel::default().show(ctx, |ui| {
             if let Some(ref comps) = self.yaml_components {
                 ui.heading("YAML Components");
-                for comp in comps.iter() {
-                    let label_text = match &comp.text {
-                        Some(text) => {
-                            format!("{}: {} {}", text, 0, comp.unit.clone().unwrap_or_default())
-                        }
-                        None => format!(
-                            "{}: {} {}",
-                            comp.key,
-                            0,
-                            comp.unit.clone().unwrap_or_default()
-                        ),
-                    };
-                    ui.label(label_text);
-                }
+                let units = &self.units;
+                let (signals, dbc) = (&self.signals, &self.dbc);
+                self.dashboard.show(
+                    ui,
+                    comps,
+                    |comp| {
+                        // 與接收執行緒相同的上鎖順序：signals 再 dbc
+                        let signals = signals.lock().unwrap();
+                        Reading::of(
+                            comp,
+                            &signals,
+                            &dbc.lock().unwrap(),
+                            &units.lock().unwrap(),
+                        )
+                    },
+                    |ui, comp| {
+                        ui.label("Display unit");
+                        ui::watch_panel::unit_selector(ui, &mut units.lock().unwrap(), &comp.key);
+                    },
+                );
             }
             ui.separator();
+            ui.horizontal(|ui| {
+                let mut frozen = self.frozen.is_some();
+                if ui
+                    .toggle_value(&mut frozen, "Freeze view")
+                    .on_hover_text("Stop the Data and Log panels from updating; capture and logging continue in the background")
+                    .changed()
+                {
+                    self.frozen = frozen.then(|| FrozenView {
+                        at: now_micros(),
+                        data: Mutex::new(self.data.lock().unwrap().clone()),
+                        receive_list: Mutex::new(self.receive_list.lock().unwrap().clone()),
+                        logs: self.logs.lock().unwrap().clone(),
+                    });
+                }
+                if let Some(ref frozen) = self.frozen {
+                    let newer = self
+                        .data
+                        .lock()
+                        .unwrap()
+                        .iter()
+                        .rev()
+                        .take_while(|f| f.timestamp > frozen.at)
+                        .count();
+                    ui.colored_label(
+                        egui::Color32::YELLOW,
+                        format!(
+                            "Frozen at {}, {} frames received since",
+                            ui::format_timestamp(frozen.at),
+                            newer
+                        ),
+                    );
+                }
+            });
             ui.columns(2, |cols| {
                 cols[0].vertical(|ui| {
-                    ui.heading("Log");
+                    ui.horizontal(|ui| {
+                        ui.heading("Log");
+                        egui::ComboBox::from_id_salt("log_level")
+                            .selected_text(self.log_level.as_str())
+                            .show_ui(ui, |ui| {
+                                for level in [
+                                    tracing::Level::ERROR,
+                                    tracing::Level::WARN,
+                                    tracing::Level::INFO,
+                                    tracing::Level::DEBUG,
+                                ] {
+                                    ui.selectable_value(&mut self.log_level, level, level.as_str());
+                                }
+                            });
+                        let mut language = *self.language.lock().unwrap();
+                        egui::ComboBox::from_id_salt("log_language")
+                            .selected_text(language.label())
+                            .show_ui(ui, |ui| {
+                                for option in Language::ALL {
+                                    ui.selectable_value(&mut language, option, option.label());
+                                }
+                            });
+                        *self.language.lock().unwrap() = language;
+                        let mut to_file = self.log_file.is_enabled();
+                        if ui.checkbox(&mut to_file, "Log file").changed() {
+                            match self.log_file.set_enabled(to_file) {
+                                Ok(()) if to_file => tracing::info!(
+                                    target: "log",
+                                    dir = logging::LOG_DIR,
+                                    "Writing daily rolling log files"
+                                ),
+                                Ok(()) => {}
+                                Err(e) => tracing::error!(target: "log", "{}", e),
+                            }
+                        }
+                    });
+                    self.log_terms.show(ui);
                     egui::ScrollArea::vertical()
                         .id_salt("logs_scroll_area")
                         .stick_to_bottom(true)
                         .auto_shrink([false; 2])
                         .show(ui, |ui| {
-                            let logs = self.logs.lock().unwrap();
-                            for log in logs.iter() {
-                                ui.label(log);
+                            let live;
+                            let logs = match self.frozen {
+                                Some(ref frozen) => &frozen.logs,
+                                None => {
+                                    live = self.logs.lock().unwrap();
+                                    &*live
+                                }
+                            };
+                            for entry in logs.iter().filter(|e| {
+                                e.level <= self.log_level && self.log_terms.matches_text(&e.text)
+                            }) {
+                                let text = egui::RichText::new(&entry.text);
+                                let text = match entry.level {
+                                    tracing::Level::ERROR => text.color(egui::Color32::RED),
+                                    tracing::Level::WARN => text.color(egui::Color32::YELLOW),
+                                    tracing::Level::DEBUG => text.weak(),
+                                    _ => text,
+                                };
+                                ui.label(text);
                             }
                         });
                 });
                 cols[1].vertical(|ui| {
-                    ui.heading("Data");
-                    egui::ScrollArea::vertical()
-                        .id_salt("data_scroll_area")
-                        .stick_to_bottom(true)
-                        .auto_shrink([false; 2])
-                        .show(ui, |ui| {
-                            let data = self.data.lock().unwrap();
-                            for line in data.iter() {
-                                ui.label(line);
-                            }
-                        });
+                    ui.horizontal(|ui| {
+                        ui.heading("Data");
+                        if ui.button("Export Buffer...").clicked() {
+                            self.export_buffer();
+                        }
+                        let captured = self.capture.lock().unwrap().len();
+                        let hover = format!(
+                            "Export all {} captured frames, including those no longer in the buffer",
+                            captured
+                        );
+                        if ui.button("Export CSV...").on_hover_text(&hover).clicked() {
+                            self.export_capture(ExportFormat::Csv);
+                        }
+                        if ui.button("Export BLF...").on_hover_text(&hover).clicked() {
+                            self.export_capture(ExportFormat::Blf);
+                        }
+                        if ui
+                            .button("Export MF4...")
+                            .on_hover_text("Export the captured frames as decoded YAML/DBC signals in ASAM MDF 4")
+                            .clicked()
+                        {
+                            self.export_signals_mf4();
+                        }
+                        if ui
+                            .small_button("Clear Capture")
+                            .on_hover_text("Discard the captured frames kept for export")
+                            .clicked()
+                        {
+                            self.capture.lock().unwrap().clear();
+                        }
+                        ui.checkbox(&mut self.export_filtered, "Filtered only");
+                    });
+                    let start = self.capture.lock().unwrap().start_time();
+                    let (data, receive_list) = match self.frozen {
+                        Some(ref frozen) => (&frozen.data, &frozen.receive_list),
+                        None => (&*self.data, &*self.receive_list),
+                    };
+                    self.trace.show(ui, data, receive_list, start);
                 });
             });
         });
@@ -0,0 +1,134 @@
+use crate::can::alarm::{AlarmEngine, AlarmRule};
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// 發出警報提示音；Windows 使用系統警告音，其他平台輸出 BEL 字元
+pub fn play_alarm_sound() {
+    #[cfg(windows)]
+    {
+        // MB_ICONEXCLAMATION
+        type MessageBeep = unsafe extern "system" fn(u32) -> i32;
+        unsafe {
+            if let Ok(user32) = libloading::Library::new("user32.dll") {
+                if let Ok(beep) = user32.get::<MessageBeep>(b"MessageBeep\0") {
+                    beep(0x30);
+                    return;
+                }
+            }
+        }
+    }
+    eprint!("\x07");
+}
+
+/// 訊號警報規則面板：修改後立即套用，警報中的規則以紅色標示
+#[derive(Default)]
+pub struct AlarmPanel {
+    rules: Vec<AlarmRule>,
+    error: Option<String>,
+}
+
+impl AlarmPanel {
+    pub fn rules(&self) -> &[AlarmRule] {
+        &self.rules
+    }
+
+    /// 以 profile 中的規則取代目前的規則並套用
+    pub fn set_rules(&mut self, rules: Vec<AlarmRule>, engine: &Mutex<AlarmEngine>) {
+        self.rules = rules;
+        self.apply(engine);
+    }
+
+    fn apply(&mut self, engine: &Mutex<AlarmEngine>) {
+        self.error = engine.lock().unwrap().set_rules(&self.rules).err();
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, engine: &Mutex<AlarmEngine>) {
+        ui.weak(
+            "Signals are YAML keys or Message.Signal from the DBC, e.g. BatteryTemp > 60 for 2s",
+        );
+        let mut changed = false;
+        let mut remove = None;
+        let active: Vec<bool> = {
+            let engine = engine.lock().unwrap();
+            (0..self.rules.len()).map(|i| engine.is_active(i)).collect()
+        };
+        egui::Grid::new("alarm_rules")
+            .striped(true)
+            .num_columns(9)
+            .show(ui, |ui| {
+                for header in [
+                    "",
+                    "Condition",
+                    "Hyst.",
+                    "Sound",
+                    "Send",
+                    "Ch",
+                    "ID",
+                    "Data",
+                    "",
+                ] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for (index, rule) in self.rules.iter_mut().enumerate() {
+                    changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                    let mut condition =
+                        egui::TextEdit::singleline(&mut rule.expr).desired_width(220.0);
+                    if active[index] {
+                        condition = condition.text_color(egui::Color32::RED);
+                    }
+                    changed |= ui.add(condition).changed();
+                    changed |= ui
+                        .add(egui::DragValue::new(&mut rule.hysteresis).speed(0.1))
+                        .changed();
+                    changed |= ui.checkbox(&mut rule.sound, "").changed();
+                    changed |= ui.checkbox(&mut rule.send, "").changed();
+                    ui.add_enabled_ui(rule.send, |ui| {
+                        changed |= ui
+                            .add(egui::DragValue::new(&mut rule.channel).range(0..=15))
+                            .changed();
+                    });
+                    ui.add_enabled_ui(rule.send, |ui| {
+                        changed |= ui
+                            .add(
+                                egui::DragValue::new(&mut rule.id)
+                                    .range(0..=0x1FFF_FFFF)
+                                    .hexadecimal(3, false, true),
+                            )
+                            .changed();
+                    });
+                    ui.add_enabled_ui(rule.send, |ui| {
+                        changed |= ui
+                            .add(
+                                egui::TextEdit::singleline(&mut rule.data)
+                                    .hint_text("01 02 FF")
+                                    .desired_width(120.0),
+                            )
+                            .changed();
+                    });
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            self.rules.remove(index);
+            changed = true;
+        }
+        if ui.button("Add Rule").clicked() {
+            self.rules.push(AlarmRule::default());
+            changed = true;
+        }
+        if changed {
+            self.apply(engine);
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("{} (previous rules still active)", error),
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::can::autoresponse::{AutoResponder, ResponseRule};
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// 自動回應面板：編輯模擬 ECU 的請求/回應規則，修改後立即套用
+#[derive(Default)]
+pub struct AutoResponsePanel {
+    rules: Vec<ResponseRule>,
+    error: Option<String>,
+}
+
+impl AutoResponsePanel {
+    pub fn rules(&self) -> &[ResponseRule] {
+        &self.rules
+    }
+
+    /// 以 profile 中的規則取代目前的規則並套用
+    pub fn set_rules(&mut self, rules: Vec<ResponseRule>, responder: &Mutex<AutoResponder>) {
+        self.rules = rules;
+        self.apply(responder);
+    }
+
+    fn apply(&mut self, responder: &Mutex<AutoResponder>) {
+        self.error = responder.lock().unwrap().set_rules(&self.rules).err();
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, responder: &Mutex<AutoResponder>) {
+        ui.weak("On receiving the request ID with a matching data prefix, send the response on the same channel");
+        let mut changed = false;
+        let mut remove = None;
+        let hits: Vec<u64> = {
+            let responder = responder.lock().unwrap();
+            (0..self.rules.len()).map(|i| responder.hits(i)).collect()
+        };
+        egui::Grid::new("auto_response_rules")
+            .striped(true)
+            .num_columns(9)
+            .show(ui, |ui| {
+                for header in [
+                    "", "Ch", "Request", "Prefix", "Response", "Data", "Delay", "Hits", "",
+                ] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for (index, rule) in self.rules.iter_mut().enumerate() {
+                    changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                    egui::ComboBox::from_id_salt(("auto_response_channel", index))
+                        .width(70.0)
+                        .selected_text(match rule.channel {
+                            Some(channel) => format!("Ch {}", channel),
+                            None => "All".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            changed |= ui
+                                .selectable_value(&mut rule.channel, None, "All")
+                                .changed();
+                            for channel in 0..16 {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut rule.channel,
+                                        Some(channel),
+                                        format!("Ch {}", channel),
+                                    )
+                                    .changed();
+                            }
+                        });
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut rule.request_id)
+                                .range(0..=0x1FFF_FFFF)
+                                .hexadecimal(3, false, true),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut rule.prefix)
+                                .hint_text("any")
+                                .desired_width(100.0),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut rule.response_id)
+                                .range(0..=0x1FFF_FFFF)
+                                .hexadecimal(3, false, true),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::TextEdit::singleline(&mut rule.response_data)
+                                .hint_text("01 02 FF")
+                                .desired_width(160.0),
+                        )
+                        .changed();
+                    changed |= ui
+                        .add(
+                            egui::DragValue::new(&mut rule.delay_ms)
+                                .range(0..=60_000)
+                                .suffix(" ms"),
+                        )
+                        .changed();
+                    ui.label(hits[index].to_string());
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            self.rules.remove(index);
+            changed = true;
+        }
+        ui.horizontal(|ui| {
+            if ui.button("Add Rule").clicked() {
+                self.rules.push(ResponseRule::default());
+                changed = true;
+            }
+            if ui.button("Reset Hits").clicked() {
+                responder.lock().unwrap().reset_counts();
+            }
+        });
+        if changed {
+            self.apply(responder);
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("{} (previous rules still active)", error),
+            );
+        }
+    }
+}
@@ -0,0 +1,133 @@
+use crate::can::bit_timing::{compute_sja1000_timing, Sja1000Timing};
+
+use eframe::egui;
+
+/// 波特率下拉選單：預設表之外多一個 Custom，選取後可直接輸入暫存器值；
+/// `timing` 為 Some 時表示使用自訂值，`preset_timing` 提供切換到 Custom 時的初始值。
+/// 回傳計算器按鈕是否被按下
+pub fn baud_rate_combo(
+    ui: &mut egui::Ui,
+    id_salt: impl std::hash::Hash,
+    rates: &[u32],
+    baud: &mut u32,
+    timing: &mut Option<Sja1000Timing>,
+    register_names: [&str; 2],
+    preset_timing: impl Fn(u32) -> Sja1000Timing,
+) -> bool {
+    egui::ComboBox::from_id_salt(id_salt)
+        .selected_text(match timing {
+            Some(_) => "Custom".to_string(),
+            None => format!("{}K", baud),
+        })
+        .show_ui(ui, |ui| {
+            for &rate in rates {
+                if ui
+                    .selectable_label(timing.is_none() && *baud == rate, format!("{}K", rate))
+                    .clicked()
+                {
+                    *baud = rate;
+                    *timing = None;
+                }
+            }
+            if ui
+                .selectable_label(timing.is_some(), "Custom")
+                .on_hover_text("Enter the bit timing registers directly")
+                .clicked()
+                && timing.is_none()
+            {
+                *timing = Some(preset_timing(*baud));
+            }
+        });
+    let Some(timing) = timing else {
+        return false;
+    };
+    ui.label(format!("{}:", register_names[0]));
+    ui.add(egui::DragValue::new(&mut timing.btr0).hexadecimal(2, false, true));
+    ui.label(format!("{}:", register_names[1]));
+    ui.add(egui::DragValue::new(&mut timing.btr1).hexadecimal(2, false, true));
+    ui.weak(describe(*timing));
+    ui.button("Calculator")
+        .on_hover_text("Compute the registers from a bitrate and sample point")
+        .clicked()
+}
+
+/// 暫存器值對應的位元率與取樣點
+fn describe(timing: Sja1000Timing) -> String {
+    format!(
+        "{:.3} kbit/s, SP {:.1}%",
+        timing.bitrate() / 1000.0,
+        timing.sample_point()
+    )
+}
+
+/// 依位元率與取樣點計算 SJA1000 相容的 BTR0/BTR1，用於預設表沒有的非標準波特率
+pub struct BitTimingCalculator {
+    /// 位元率（bit/s）
+    bitrate: u32,
+    /// 取樣點（%）
+    sample_point: f64,
+}
+
+impl Default for BitTimingCalculator {
+    fn default() -> Self {
+        Self {
+            bitrate: 500_000,
+            sample_point: 87.5,
+        }
+    }
+}
+
+impl BitTimingCalculator {
+    /// 按下 Apply 時回傳計算結果
+    pub fn show(&mut self, ui: &mut egui::Ui) -> Option<Sja1000Timing> {
+        let mut applied = None;
+        ui.group(|ui| {
+            ui.horizontal(|ui| {
+                ui.label("Bitrate:");
+                ui.add(
+                    egui::DragValue::new(&mut self.bitrate)
+                        .range(1_000..=1_000_000)
+                        .suffix(" bit/s"),
+                );
+                ui.label("Sample point:");
+                ui.add(
+                    egui::DragValue::new(&mut self.sample_point)
+                        .range(50.0..=95.0)
+                        .speed(0.5)
+                        .suffix(" %"),
+                );
+            });
+            let Some(timing) = compute_sja1000_timing(self.bitrate, self.sample_point) else {
+                ui.colored_label(egui::Color32::RED, "No valid timing");
+                return;
+            };
+            let error = (timing.bitrate() - self.bitrate as f64) / self.bitrate as f64 * 100.0;
+            ui.horizontal(|ui| {
+                ui.monospace(format!(
+                    "BTR0 0x{:02X}  BTR1 0x{:02X}",
+                    timing.btr0, timing.btr1
+                ));
+                ui.weak(format!(
+                    "BRP {}, TSEG1 {}, TSEG2 {}, SJW {}",
+                    timing.brp(),
+                    timing.tseg1(),
+                    timing.tseg2(),
+                    timing.sjw()
+                ));
+            });
+            ui.horizontal(|ui| {
+                let text = format!("{}, error {:+.2}%", describe(timing), error);
+                if error.abs() > 1.0 {
+                    ui.colored_label(egui::Color32::YELLOW, text)
+                        .on_hover_text("Bitrate error above 1% may cause bus errors");
+                } else {
+                    ui.label(text);
+                }
+                if ui.button("Apply").clicked() {
+                    applied = Some(timing);
+                }
+            });
+        });
+        applied
+    }
+}
@@ -0,0 +1,115 @@
+use crate::can::blackbox::{BlackBoxConfig, BlackBoxRecorder};
+use crate::can::session::SessionInfo;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::Mutex;
+
+/// 黑盒子記錄設定面板：分段時間/大小、保留期限與目前狀態
+#[derive(Default)]
+pub struct BlackBoxPanel {
+    config: BlackBoxConfig,
+}
+
+impl BlackBoxPanel {
+    pub fn config(&self) -> &BlackBoxConfig {
+        &self.config
+    }
+
+    pub fn set_config(&mut self, config: BlackBoxConfig) {
+        self.config = config;
+    }
+
+    /// 尚未記錄時以目前設定啟動記錄
+    pub fn start_recording(&self, recorder: &mut Option<BlackBoxRecorder>, session: &SessionInfo) {
+        if recorder.is_some() {
+            return;
+        }
+        match BlackBoxRecorder::start(
+            self.config.clone(),
+            session.clone(),
+            move |msg| tracing::info!(target: "blackbox", "{}", msg),
+        ) {
+            Ok(r) => *recorder = Some(r),
+            Err(e) => tracing::error!(target: "blackbox", "{}", e),
+        }
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        recorder: &Mutex<Option<BlackBoxRecorder>>,
+        session: &SessionInfo,
+    ) {
+        let mut recorder = recorder.lock().unwrap();
+        ui.add_enabled_ui(recorder.is_none(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Directory:");
+                ui.label(self.config.dir.display().to_string());
+                if ui.button("Browse...").clicked() {
+                    if let Some(dir) = FileDialog::new().pick_folder() {
+                        self.config.dir = dir;
+                    }
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Segment:");
+                ui.add(
+                    egui::DragValue::new(&mut self.config.segment_minutes)
+                        .range(1..=1440)
+                        .suffix(" min"),
+                );
+                ui.label("or");
+                ui.add(
+                    egui::DragValue::new(&mut self.config.max_segment_mb)
+                        .range(0..=100_000)
+                        .suffix(" MB"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Keep last:");
+                ui.add(
+                    egui::DragValue::new(&mut self.config.retention_hours)
+                        .range(0..=10_000)
+                        .suffix(" h"),
+                );
+                ui.weak("(0 keeps everything)");
+            });
+        });
+        ui.checkbox(
+            &mut self.config.auto_start,
+            "Start recording automatically when CAN starts",
+        );
+
+        match recorder.as_ref() {
+            None => {
+                if ui.button("Start Recording").clicked() {
+                    self.start_recording(&mut recorder, session);
+                }
+            }
+            Some(r) => {
+                let status = r.status();
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!(
+                        "{} frames, {:.1} MB in {} segment(s), {} old segment(s) removed",
+                        status.frames,
+                        status.bytes as f64 / (1024.0 * 1024.0),
+                        status.segments,
+                        status.removed
+                    ));
+                });
+                if let Some(file) = &status.current_file {
+                    ui.weak(file.display().to_string());
+                }
+                if let Some(error) = &status.last_error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                if ui.button("Stop Recording").clicked() {
+                    // Drop 時會寫完剩餘訊框並關閉檔案
+                    *recorder = None;
+                }
+            }
+        }
+    }
+}
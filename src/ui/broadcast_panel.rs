@@ -0,0 +1,54 @@
+use crate::can::broadcast::UdpBroadcaster;
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// UDP multicast 廣播設定面板
+pub struct BroadcastPanel {
+    address: String,
+    ttl: u32,
+}
+
+impl Default for BroadcastPanel {
+    fn default() -> Self {
+        Self {
+            address: "239.255.0.1:5555".to_string(),
+            ttl: 1,
+        }
+    }
+}
+
+impl BroadcastPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, broadcaster: &Mutex<Option<UdpBroadcaster>>) {
+        let mut broadcaster = broadcaster.lock().unwrap();
+        match broadcaster.as_ref() {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.label("Multicast group:");
+                    ui.text_edit_singleline(&mut self.address);
+                });
+                ui.horizontal(|ui| {
+                    ui.label("TTL:");
+                    ui.add(egui::DragValue::new(&mut self.ttl).range(1..=255));
+                });
+                if ui.button("Start Broadcasting").clicked() {
+                    match UdpBroadcaster::new(&self.address, self.ttl) {
+                        Ok(b) => {
+                            tracing::info!(target: "udp", "Broadcasting frames to {}", b.target());
+                            *broadcaster = Some(b);
+                        }
+                        Err(e) => tracing::error!(target: "udp", "{}", e),
+                    }
+                }
+            }
+            Some(b) => {
+                ui.label(format!("Broadcasting to {}", b.target()));
+                ui.label(format!("Sent: {}  Failed: {}", b.sent(), b.failed()));
+                if ui.button("Stop Broadcasting").clicked() {
+                    *broadcaster = None;
+                    tracing::info!(target: "udp", "Broadcasting stopped");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::can::cantypes::now_micros;
+use crate::can::stats::{BusStatistics, ChannelStats, IdStats};
+
+use eframe::egui;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 畫面更新的間隔（微秒）
+const REFRESH_US: u64 = 1_000_000;
+
+fn period_ms(period_us: Option<u64>) -> String {
+    match period_us {
+        Some(us) => format!("{:.1}", us as f64 / 1000.0),
+        None => "-".to_string(),
+    }
+}
+
+/// 匯流排統計面板：各通道的訊框速率、負載與錯誤，以及各 ID 的次數與週期；
+/// 每秒取一次快照，避免數字在每次重繪時跳動
+#[derive(Default)]
+pub struct BusStatsPanel {
+    updated_at: u64,
+    channels: Vec<ChannelStats>,
+    ids: Vec<IdStats>,
+}
+
+impl BusStatsPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, stats: &Mutex<BusStatistics>) {
+        let now = now_micros();
+        if now.saturating_sub(self.updated_at) >= REFRESH_US {
+            let stats = stats.lock().unwrap();
+            self.channels = stats.channels().cloned().collect();
+            self.ids = stats.ids().cloned().collect();
+            self.updated_at = now;
+        }
+        ui.ctx()
+            .request_repaint_after(Duration::from_micros(REFRESH_US));
+
+        if ui.button("Reset").clicked() {
+            stats.lock().unwrap().reset(now);
+            self.updated_at = 0;
+        }
+        if self.channels.is_empty() {
+            ui.label("No traffic yet");
+            return;
+        }
+        egui::Grid::new("bus_stats_channels")
+            .striped(true)
+            .num_columns(6)
+            .show(ui, |ui| {
+                for header in ["Channel", "Frames/s", "Bus Load", "RX", "TX", "Errors"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for c in &self.channels {
+                    ui.label(c.channel.to_string());
+                    ui.label(format!("{:.0}", c.frame_rate));
+                    if c.bitrate > 0 {
+                        ui.add(
+                            egui::ProgressBar::new((c.bus_load / 100.0) as f32)
+                                .desired_width(100.0)
+                                .text(format!("{:.1}%", c.bus_load)),
+                        );
+                    } else {
+                        ui.label("-").on_hover_text("Bitrate unknown");
+                    }
+                    ui.label(c.frames.to_string());
+                    ui.label(c.tx_frames.to_string());
+                    if c.errors > 0 {
+                        ui.colored_label(egui::Color32::RED, c.errors.to_string());
+                    } else {
+                        ui.label("0");
+                    }
+                    ui.end_row();
+                }
+            });
+        ui.separator();
+        ui.label(format!("{} IDs", self.ids.len()));
+        egui::ScrollArea::vertical()
+            .id_salt("bus_stats_ids")
+            .max_height(320.0)
+            .show(ui, |ui| {
+                egui::Grid::new("bus_stats_ids_grid")
+                    .striped(true)
+                    .num_columns(7)
+                    .show(ui, |ui| {
+                        for header in [
+                            "CH",
+                            "ID",
+                            "Count",
+                            "Min (ms)",
+                            "Avg (ms)",
+                            "Max (ms)",
+                            "Last (ms)",
+                        ] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for s in &self.ids {
+                            ui.label(s.channel.to_string());
+                            ui.monospace(format!("0x{:X}", s.id));
+                            ui.label(s.count.to_string());
+                            ui.label(period_ms(s.min_period_us));
+                            ui.label(match s.mean_period_ms() {
+                                Some(ms) => format!("{:.1}", ms),
+                                None => "-".to_string(),
+                            });
+                            ui.label(period_ms(s.max_period_us));
+                            ui.label(period_ms(s.last_period_us));
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
@@ -0,0 +1,305 @@
+use crate::can::canbus::SharedCan;
+use crate::can::canopen::{CanOpenMonitor, NmtState};
+use crate::can::eds::{self, Eds};
+use crate::can::sdo::{SdoClient, SdoStatus, SdoTransfer, SDO_TIMEOUT};
+use crate::ui::{format_timestamp, parse_hex_u32};
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::Mutex;
+
+/// 沒有 EDS 時可選的資料型別，0 表示以十六進位位元組讀寫
+const DATA_TYPES: [u16; 11] = [
+    0,
+    eds::BOOLEAN,
+    eds::INTEGER8,
+    eds::INTEGER16,
+    eds::INTEGER32,
+    eds::UNSIGNED8,
+    eds::UNSIGNED16,
+    eds::UNSIGNED32,
+    eds::REAL32,
+    eds::VISIBLE_STRING,
+    eds::UNSIGNED64,
+];
+
+fn data_type_label(data_type: u16) -> &'static str {
+    if data_type == 0 {
+        "Hex bytes"
+    } else {
+        eds::data_type_name(data_type)
+    }
+}
+
+/// CANopen 畫面：SDO 用戶端與各節點的 NMT 狀態
+pub struct CanOpenPanel {
+    channel: u32,
+    node: u8,
+    index: String,
+    subindex: String,
+    data_type: u16,
+    value: String,
+    /// 已載入的 EDS 與路徑，用於依名稱選擇項目與決定資料型別
+    eds: Option<(Eds, String)>,
+    eds_search: String,
+}
+
+impl Default for CanOpenPanel {
+    fn default() -> Self {
+        Self {
+            channel: 0,
+            node: 1,
+            index: "1000".to_string(),
+            subindex: "0".to_string(),
+            data_type: eds::UNSIGNED32,
+            value: String::new(),
+            eds: None,
+            eds_search: String::new(),
+        }
+    }
+}
+
+impl CanOpenPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        monitor: &Mutex<CanOpenMonitor>,
+        sdo: &Mutex<Option<SdoClient>>,
+        can_app: &SharedCan,
+    ) {
+        egui::CollapsingHeader::new("SDO Client")
+            .id_salt("canopen_sdo")
+            .default_open(true)
+            .show(ui, |ui| self.show_sdo(ui, sdo, can_app));
+        ui.separator();
+        show_canopen_nodes(ui, monitor);
+    }
+
+    fn show_sdo(&mut self, ui: &mut egui::Ui, sdo: &Mutex<Option<SdoClient>>, can_app: &SharedCan) {
+        ui.horizontal(|ui| {
+            if ui.button("Load EDS").clicked() {
+                if let Some(path) = FileDialog::new().add_filter("eds", &["eds"]).pick_file() {
+                    let path = path.to_string_lossy().into_owned();
+                    match Eds::load(&path) {
+                        Ok(eds) => {
+                            tracing::info!(
+                                target: "canopen",
+                                path = %path,
+                                entries = eds.entries.len(),
+                                "Loaded EDS"
+                            );
+                            self.eds = Some((eds, path));
+                        }
+                        Err(e) => tracing::error!(target: "canopen", "Failed to load EDS: {}", e),
+                    }
+                }
+            }
+            match &self.eds {
+                Some((eds, path)) => {
+                    ui.weak(format!("{} ({})", path, eds.product_name));
+                    if ui.small_button("x").on_hover_text("Unload EDS").clicked() {
+                        self.eds = None;
+                    }
+                }
+                None => {
+                    ui.weak("No EDS loaded");
+                }
+            }
+        });
+        if let Some((eds, _)) = &self.eds {
+            ui.horizontal(|ui| {
+                ui.label("Object:");
+                ui.text_edit_singleline(&mut self.eds_search)
+                    .on_hover_text("Filter by name or index");
+                let search = self.eds_search.to_lowercase();
+                egui::ComboBox::from_id_salt("canopen_eds_entry")
+                    .selected_text("Select")
+                    .width(260.0)
+                    .show_ui(ui, |ui| {
+                        for entry in eds.entries.values() {
+                            let label = format!(
+                                "{:04X}:{:02X} {} [{}]",
+                                entry.index, entry.subindex, entry.name, entry.access
+                            );
+                            if !search.is_empty() && !label.to_lowercase().contains(&search) {
+                                continue;
+                            }
+                            if ui.selectable_label(false, label).clicked() {
+                                self.index = format!("{:04X}", entry.index);
+                                self.subindex = format!("{:02X}", entry.subindex);
+                                self.data_type = entry.data_type;
+                                self.value = entry.default_value.clone();
+                            }
+                        }
+                    });
+            });
+        }
+
+        let object = parse_hex_u32(&self.index)
+            .ok()
+            .and_then(|i| u16::try_from(i).ok())
+            .zip(
+                parse_hex_u32(&self.subindex)
+                    .ok()
+                    .and_then(|s| u8::try_from(s).ok()),
+            );
+        let entry = object.and_then(|(index, subindex)| {
+            self.eds
+                .as_ref()
+                .and_then(|(eds, _)| eds.entry(index, subindex))
+        });
+        let running = sdo.lock().unwrap().as_ref().is_some_and(|c| c.is_running());
+        ui.add_enabled_ui(!running, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Channel:");
+                ui.add(egui::DragValue::new(&mut self.channel).range(0..=15));
+                ui.label("Node:");
+                ui.add(egui::DragValue::new(&mut self.node).range(1..=127));
+                ui.label("Index:");
+                ui.add(egui::TextEdit::singleline(&mut self.index).desired_width(50.0));
+                ui.label("Sub:");
+                ui.add(egui::TextEdit::singleline(&mut self.subindex).desired_width(30.0));
+                egui::ComboBox::from_id_salt("canopen_data_type")
+                    .selected_text(data_type_label(self.data_type))
+                    .show_ui(ui, |ui| {
+                        for data_type in DATA_TYPES {
+                            ui.selectable_value(
+                                &mut self.data_type,
+                                data_type,
+                                data_type_label(data_type),
+                            );
+                        }
+                    });
+            });
+            if let Some(entry) = entry {
+                ui.weak(format!(
+                    "{} ({}, {})",
+                    entry.name,
+                    eds::data_type_name(entry.data_type),
+                    entry.access
+                ));
+            }
+            ui.horizontal(|ui| {
+                ui.label("Value:");
+                ui.text_edit_singleline(&mut self.value);
+                let Some((index, subindex)) = object else {
+                    ui.colored_label(egui::Color32::RED, "Invalid index or sub-index");
+                    return;
+                };
+                let transfer = if ui
+                    .add_enabled(
+                        entry.is_none_or(|e| e.readable()),
+                        egui::Button::new("Read"),
+                    )
+                    .clicked()
+                {
+                    Some(Ok(SdoTransfer::upload(
+                        self.channel,
+                        self.node,
+                        index,
+                        subindex,
+                    )))
+                } else if ui
+                    .add_enabled(
+                        entry.is_none_or(|e| e.writable()),
+                        egui::Button::new("Write"),
+                    )
+                    .clicked()
+                {
+                    Some(eds::parse_value(self.data_type, &self.value).map(|data| {
+                        SdoTransfer::download(self.channel, self.node, index, subindex, data)
+                    }))
+                } else {
+                    None
+                };
+                match transfer {
+                    Some(Ok(transfer)) => {
+                        *sdo.lock().unwrap() = Some(SdoClient::start(
+                            transfer,
+                            can_app.clone(),
+                            SDO_TIMEOUT,
+                            |msg| tracing::info!(target: "canopen", "{}", msg),
+                        ));
+                    }
+                    Some(Err(e)) => tracing::error!(target: "canopen", "Invalid value: {}", e),
+                    None => {}
+                }
+            });
+        });
+
+        if let Some(client) = sdo.lock().unwrap().as_ref() {
+            let text = match client.status() {
+                SdoStatus::Running => format!("{}...", client.description()),
+                SdoStatus::Done(data) if client.is_upload() => format!(
+                    "{}: {}",
+                    client.description(),
+                    eds::format_value(self.data_type, &data)
+                ),
+                SdoStatus::Done(_) => format!("{}: OK", client.description()),
+                SdoStatus::Failed(e) => {
+                    ui.colored_label(
+                        egui::Color32::RED,
+                        format!("{}: {}", client.description(), e),
+                    );
+                    return;
+                }
+            };
+            ui.monospace(text);
+        }
+    }
+}
+
+/// 各節點的 NMT 狀態、最後的 EMCY 與 SDO 中止
+pub fn show_canopen_nodes(ui: &mut egui::Ui, monitor: &Mutex<CanOpenMonitor>) {
+    if ui.button("Clear").clicked() {
+        monitor.lock().unwrap().clear();
+    }
+    let monitor = monitor.lock().unwrap();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("canopen_nodes")
+            .striped(true)
+            .num_columns(6)
+            .show(ui, |ui| {
+                for header in ["Node", "State", "Last Heartbeat", "EMCY", "SDO Abort", "N"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for (id, node) in monitor.nodes() {
+                    ui.label(id.to_string());
+                    match node.state {
+                        Some(state) => {
+                            let color = match state {
+                                NmtState::Operational => egui::Color32::GREEN,
+                                NmtState::Stopped => egui::Color32::RED,
+                                _ => egui::Color32::YELLOW,
+                            };
+                            ui.colored_label(color, state.name())
+                        }
+                        None => ui.weak("-"),
+                    };
+                    match node.last_heartbeat {
+                        Some(timestamp) => ui.label(format_timestamp(timestamp)),
+                        None => ui.weak("-"),
+                    };
+                    match node.last_emcy {
+                        Some((code, register)) => ui
+                            .label(format!("0x{:04X} / 0x{:02X}", code, register))
+                            .on_hover_text(format!("{} EMCY messages", node.emcy_count)),
+                        None => ui.weak("-"),
+                    };
+                    match node.last_abort {
+                        Some(abort) => ui
+                            .label(format!("0x{:04X}:{:02X}", abort.index, abort.subindex))
+                            .on_hover_text(format!(
+                                "{} (0x{:08X})",
+                                abort.description(),
+                                abort.code
+                            )),
+                        None => ui.weak("-"),
+                    };
+                    ui.label(node.frames.to_string());
+                    ui.end_row();
+                }
+            });
+    });
+}
@@ -0,0 +1,269 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::capture_db::{self, CaptureDb, CaptureQuery, CaptureSummary};
+use crate::can::logfile::{export_frames, ExportFormat};
+use crate::can::session::SessionInfo;
+use crate::ui::{format_timestamp, parse_hex_u32};
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::Mutex;
+
+/// 介面上最多顯示的查詢結果筆數，匯出時不受限制
+const MAX_DISPLAY_ROWS: usize = 1000;
+
+/// SQLite 擷取資料庫面板：記錄到資料庫，並依 ID、通道與時間範圍查詢
+pub struct CaptureDbPanel {
+    path: String,
+    id: String,
+    channel: String,
+    from: String,
+    to: String,
+    summary: Option<CaptureSummary>,
+    results: Vec<CanFrame>,
+    error: Option<String>,
+}
+
+impl Default for CaptureDbPanel {
+    fn default() -> Self {
+        Self {
+            path: "capture.db".to_string(),
+            id: String::new(),
+            channel: String::new(),
+            from: String::new(),
+            to: String::new(),
+            summary: None,
+            results: Vec::new(),
+            error: None,
+        }
+    }
+}
+
+impl CaptureDbPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        recorder: &Mutex<Option<CaptureDb>>,
+        session: &SessionInfo,
+    ) {
+        let mut recorder = recorder.lock().unwrap();
+        ui.horizontal(|ui| {
+            ui.label("Database:");
+            ui.add_enabled(
+                recorder.is_none(),
+                egui::TextEdit::singleline(&mut self.path).desired_width(260.0),
+            );
+            if ui
+                .add_enabled(recorder.is_none(), egui::Button::new("Browse..."))
+                .clicked()
+            {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("SQLite", &["db", "sqlite"])
+                    .set_file_name(&self.path)
+                    .save_file()
+                {
+                    self.path = path.to_string_lossy().into_owned();
+                    self.summary = None;
+                }
+            }
+        });
+        match recorder.as_ref() {
+            None => {
+                if ui.button("Start Recording").clicked() {
+                    match CaptureDb::start(
+                        self.path.trim(),
+                        session,
+                        |msg| tracing::info!(target: "capture_db", "{}", msg),
+                    ) {
+                        Ok(db) => *recorder = Some(db),
+                        Err(e) => tracing::error!(target: "capture_db", "{}", e),
+                    }
+                }
+            }
+            Some(db) => {
+                let status = db.status();
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!("{} frames written", status.frames));
+                });
+                if let Some(error) = &status.last_error {
+                    ui.colored_label(egui::Color32::RED, error);
+                }
+                if ui.button("Stop Recording").clicked() {
+                    // Drop 時會寫完剩餘訊框
+                    *recorder = None;
+                }
+            }
+        }
+        drop(recorder);
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            if ui.button("Refresh").clicked() {
+                self.refresh_summary();
+            }
+            match &self.summary {
+                Some(summary) => {
+                    let range = summary.first_us.zip(summary.last_us).map_or(
+                        String::new(),
+                        |(first, last)| {
+                            format!(
+                                ", {} to {}",
+                                format_timestamp(first),
+                                format_timestamp(last)
+                            )
+                        },
+                    );
+                    ui.label(format!("{} frames{}", summary.frames, range));
+                }
+                None => {
+                    ui.weak("Not loaded");
+                }
+            }
+        });
+        egui::Grid::new("capture_db_query")
+            .num_columns(4)
+            .show(ui, |ui| {
+                ui.label("ID (hex):");
+                ui.add(egui::TextEdit::singleline(&mut self.id).desired_width(90.0));
+                ui.label("Channel:");
+                ui.add(egui::TextEdit::singleline(&mut self.channel).desired_width(40.0));
+                ui.end_row();
+                ui.label("From:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.from)
+                        .hint_text("YYYY-MM-DD HH:MM:SS")
+                        .desired_width(170.0),
+                );
+                ui.label("To:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.to)
+                        .hint_text("YYYY-MM-DD HH:MM:SS")
+                        .desired_width(170.0),
+                );
+                ui.end_row();
+            });
+        ui.horizontal(|ui| {
+            if ui.button("Query").clicked() {
+                self.run_query();
+            }
+            if ui.button("Export Results...").clicked() {
+                self.export_results(session);
+            }
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if self.results.len() >= MAX_DISPLAY_ROWS {
+            ui.weak(format!(
+                "Showing the first {} frames; export to get all results",
+                MAX_DISPLAY_ROWS
+            ));
+        }
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("capture_db_results")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for header in ["Time", "Ch", "ID", "Data"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for frame in &self.results {
+                        ui.monospace(format_timestamp(frame.timestamp));
+                        ui.monospace(frame.channel.to_string());
+                        ui.monospace(format!("0x{:X}", frame.id));
+                        let data: Vec<String> = frame
+                            .payload()
+                            .iter()
+                            .map(|b| format!("{:02X}", b))
+                            .collect();
+                        ui.monospace(data.join(" "));
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn refresh_summary(&mut self) {
+        match capture_db::summary(self.path.trim()) {
+            Ok(summary) => {
+                // 尚未輸入時間範圍時以資料庫的起訖時間填入
+                if self.from.is_empty() {
+                    if let Some(first) = summary.first_us {
+                        self.from = format_timestamp(first);
+                    }
+                }
+                if self.to.is_empty() {
+                    if let Some(last) = summary.last_us {
+                        self.to = format_timestamp(last);
+                    }
+                }
+                self.summary = Some(summary);
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn build_query(&self, limit: usize) -> Result<CaptureQuery, String> {
+        let optional = |text: &str, parse: &dyn Fn(&str) -> Result<u64, String>| {
+            let text = text.trim();
+            (!text.is_empty()).then(|| parse(text)).transpose()
+        };
+        Ok(CaptureQuery {
+            id: optional(&self.id, &|t| parse_hex_u32(t).map(u64::from))?.map(|id| id as u32),
+            channel: optional(&self.channel, &|t| {
+                t.parse::<u64>()
+                    .map_err(|_| format!("Invalid channel '{}'", t))
+            })?
+            .map(|channel| channel as u32),
+            from_us: optional(&self.from, &capture_db::parse_local_time)?,
+            to_us: optional(&self.to, &capture_db::parse_local_time)?,
+            limit,
+        })
+    }
+
+    fn run_query(&mut self) {
+        let result = self
+            .build_query(MAX_DISPLAY_ROWS)
+            .and_then(|query| capture_db::query(self.path.trim(), &query));
+        match result {
+            Ok(frames) => {
+                self.results = frames;
+                self.error = None;
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    fn export_results(&mut self, session: &SessionInfo) {
+        let query = match self.build_query(0) {
+            Ok(query) => query,
+            Err(e) => {
+                self.error = Some(e);
+                return;
+            }
+        };
+        let Some(path) = FileDialog::new()
+            .add_filter("candump log", &["log"])
+            .add_filter("csv", &["csv"])
+            .add_filter("Vector ASC", &["asc"])
+            .add_filter("PEAK TRC", &["trc"])
+            .add_filter("Vector BLF", &["blf"])
+            .set_file_name("query.log")
+            .save_file()
+        else {
+            return;
+        };
+        let path = path.to_string_lossy().into_owned();
+        let result = capture_db::query(self.path.trim(), &query).and_then(|frames| {
+            export_frames(&path, &frames, ExportFormat::from_path(&path), session)
+        });
+        match result {
+            Ok(count) => {
+                tracing::info!(target: "capture_db", path = %path, count, "Exported query results")
+            }
+            Err(e) => self.error = Some(e),
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use crate::can::bit_timing::Sja1000Timing;
+use crate::can::cantypes::VciCanBaudRate;
+use crate::can::presets::CONTROL_CAN_BAUD_RATES;
+use crate::ui::bit_timing_calc::{baud_rate_combo, BitTimingCalculator};
+
+use eframe::egui;
+
+/// 新偵測到的通道預設波特率（K）
+const DEFAULT_BAUD: u32 = 250;
+
+/// 單一 ControlCAN 通道的設定
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ChannelSetting {
+    pub enabled: bool,
+    /// 波特率（K）
+    pub baud: u32,
+    /// 自訂的 Timing0/Timing1，設定時取代 `baud`
+    pub timing: Option<Sja1000Timing>,
+}
+
+impl ChannelSetting {
+    fn new(enabled: bool, baud: u32) -> Self {
+        Self {
+            enabled,
+            baud,
+            timing: None,
+        }
+    }
+
+    pub fn baud_rate(&self) -> VciCanBaudRate {
+        match self.timing {
+            Some(t) => VciCanBaudRate::Custom {
+                timing0: t.btr0,
+                timing1: t.btr1,
+            },
+            None => VciCanBaudRate::from_u32(self.baud).unwrap_or(VciCanBaudRate::Baud250K),
+        }
+    }
+
+    /// 位元率（bit/s），自訂時序依暫存器換算
+    pub fn bitrate(&self) -> u32 {
+        match self.timing {
+            Some(t) => t.bitrate().round() as u32,
+            None => self.baud * 1000,
+        }
+    }
+}
+
+/// ControlCAN 通道清單，索引即通道編號；通道數可由板卡資訊的 `can_num` 偵測
+pub struct ChannelList {
+    channels: Vec<ChannelSetting>,
+    calculator: BitTimingCalculator,
+    /// 計算器結果要套用的通道
+    calculator_channel: Option<usize>,
+}
+
+impl Default for ChannelList {
+    /// 未偵測前假設為雙通道裝置
+    fn default() -> Self {
+        Self {
+            channels: vec![
+                ChannelSetting::new(true, 250),
+                ChannelSetting::new(true, 500),
+            ],
+            calculator: BitTimingCalculator::default(),
+            calculator_channel: None,
+        }
+    }
+}
+
+impl ChannelList {
+    /// 依裝置回報的通道數調整清單，保留既有通道的設定，新通道預設停用
+    pub fn resize(&mut self, count: usize) {
+        self.channels
+            .resize(count, ChannelSetting::new(false, DEFAULT_BAUD));
+    }
+
+    /// 套用轉接器預設：全部通道啟用並使用相同波特率
+    pub fn fill(&mut self, count: usize, baud: u32) {
+        self.channels = vec![ChannelSetting::new(true, baud); count];
+    }
+
+    pub fn len(&self) -> usize {
+        self.channels.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.channels.is_empty()
+    }
+
+    /// 啟用的通道與其設定
+    pub fn enabled(&self) -> impl Iterator<Item = (u32, &ChannelSetting)> + '_ {
+        self.channels
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.enabled)
+            .map(|(i, c)| (i as u32, c))
+    }
+
+    /// 轉為 `CanApp` 需要的通道清單
+    pub fn vci_channels(&self) -> Vec<(u32, VciCanBaudRate)> {
+        self.enabled()
+            .map(|(channel, setting)| (channel, setting.baud_rate()))
+            .collect()
+    }
+
+    /// 回傳是否按下偵測通道
+    pub fn show(&mut self, ui: &mut egui::Ui, running: bool) -> bool {
+        let mut detect = false;
+        ui.horizontal(|ui| {
+            ui.label(format!("Channels: {}", self.channels.len()));
+            detect = ui
+                .add_enabled(!running, egui::Button::new("Detect"))
+                .on_hover_text("Open the device and read the channel count from the board info")
+                .clicked();
+        });
+        for (i, setting) in self.channels.iter_mut().enumerate() {
+            ui.horizontal(|ui| {
+                ui.add_enabled_ui(!running, |ui| {
+                    ui.checkbox(&mut setting.enabled, format!("CAN {}", i));
+                    ui.label("Baud Rate:");
+                    if baud_rate_combo(
+                        ui,
+                        ("controlcan_baud", i),
+                        &CONTROL_CAN_BAUD_RATES,
+                        &mut setting.baud,
+                        &mut setting.timing,
+                        ["Timing0", "Timing1"],
+                        |baud| {
+                            let (btr0, btr1) = VciCanBaudRate::from_u32(baud)
+                                .unwrap_or(VciCanBaudRate::Baud250K)
+                                .to_timing_values();
+                            Sja1000Timing { btr0, btr1 }
+                        },
+                    ) {
+                        self.calculator_channel =
+                            Some(i).filter(|&c| self.calculator_channel != Some(c));
+                    }
+                });
+            });
+        }
+        if let Some(i) = self.calculator_channel {
+            match self.channels.get_mut(i).filter(|c| c.timing.is_some()) {
+                Some(setting) => {
+                    ui.label(format!("Bit timing calculator for CAN {}", i));
+                    if let Some(timing) = self.calculator.show(ui) {
+                        setting.timing = Some(timing);
+                    }
+                }
+                None => self.calculator_channel = None,
+            }
+        }
+        detect
+    }
+}
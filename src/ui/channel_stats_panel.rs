@@ -0,0 +1,139 @@
+use crate::can::canbus::ReceiveStats;
+use crate::can::cantypes::now_micros;
+use crate::can::silence::{SilenceDetector, SilentGap};
+use crate::can::stats::BusStatistics;
+use crate::ui::format_timestamp;
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// 各通道的收發統計與匯流排負載，每個通道可獨立重設；
+/// `receive` 為介面接收路徑的計數，用於確認是否有漏收
+pub fn show_channel_stats(
+    ui: &mut egui::Ui,
+    stats: &Mutex<BusStatistics>,
+    receive: Option<ReceiveStats>,
+) {
+    if let Some(r) = receive {
+        ui.horizontal(|ui| {
+            ui.label(format!("Received: {}", r.frames));
+            ui.separator();
+            let per_read = if r.reads > 0 {
+                r.frames as f64 / r.reads as f64
+            } else {
+                0.0
+            };
+            ui.label(format!("Reads: {} ({:.1} frames/read)", r.reads, per_read));
+            ui.separator();
+            ui.label(format!("Peak backlog: {}", r.max_pending));
+            ui.separator();
+            if r.overruns > 0 {
+                ui.colored_label(egui::Color32::RED, format!("Overruns: {}", r.overruns));
+            } else {
+                ui.label("Overruns: 0");
+            }
+        });
+        ui.separator();
+    }
+    let mut reset = Vec::new();
+    let mut stats = stats.lock().unwrap();
+    let channels: Vec<u32> = stats.channels().map(|c| c.channel).collect();
+    if channels.is_empty() {
+        ui.label("No traffic yet");
+        return;
+    }
+    if ui.button("Reset All").clicked() {
+        reset.extend(channels.iter().copied());
+    }
+    egui::Grid::new("channel_stats")
+        .striped(true)
+        .num_columns(9)
+        .show(ui, |ui| {
+            for header in [
+                "Channel", "Bitrate", "RX", "RX Bytes", "TX", "Errors", "Bus Load", "Since", "",
+            ] {
+                ui.strong(header);
+            }
+            ui.end_row();
+            for c in stats.channels() {
+                ui.label(c.channel.to_string());
+                if c.bitrate > 0 {
+                    ui.label(format!("{}K", c.bitrate / 1000));
+                } else {
+                    ui.label("-");
+                }
+                ui.label(c.frames.to_string());
+                ui.label(c.bytes.to_string());
+                ui.label(c.tx_frames.to_string());
+                if c.errors > 0 {
+                    ui.colored_label(egui::Color32::RED, c.errors.to_string());
+                } else {
+                    ui.label("0");
+                }
+                if c.bitrate > 0 {
+                    ui.add(
+                        egui::ProgressBar::new((c.bus_load / 100.0) as f32)
+                            .desired_width(100.0)
+                            .text(format!("{:.1}%", c.bus_load)),
+                    );
+                } else {
+                    ui.label("-");
+                }
+                ui.label(format_timestamp(c.since));
+                if ui.small_button("Reset").clicked() {
+                    reset.push(c.channel);
+                }
+                ui.end_row();
+            }
+        });
+    let now = now_micros();
+    for channel in reset {
+        stats.reset_channel(channel, now);
+    }
+}
+
+/// 匯流排靜默偵測：門檻設定與最近的靜默期間
+pub fn show_silence(ui: &mut egui::Ui, silence: &Mutex<SilenceDetector>) {
+    let mut silence = silence.lock().unwrap();
+    ui.horizontal(|ui| {
+        ui.label("Silence threshold:");
+        ui.add(
+            egui::DragValue::new(&mut silence.threshold_ms)
+                .range(10..=600_000)
+                .suffix(" ms"),
+        );
+        if ui.button("Clear").clicked() {
+            silence.clear();
+        }
+    });
+    let now = now_micros();
+    let gaps: Vec<SilentGap> = silence.gaps().rev().copied().collect();
+    if gaps.is_empty() {
+        ui.weak("No silent periods detected");
+        return;
+    }
+    egui::ScrollArea::vertical()
+        .id_salt("silent_gaps")
+        .max_height(160.0)
+        .show(ui, |ui| {
+            egui::Grid::new("silent_gaps_grid")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for header in ["Channel", "From", "Until", "Duration"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for gap in gaps {
+                        ui.label(gap.channel.to_string());
+                        ui.label(format_timestamp(gap.start_us));
+                        match gap.end_us {
+                            Some(end) => ui.label(format_timestamp(end)),
+                            None => ui.colored_label(egui::Color32::YELLOW, "silent"),
+                        };
+                        ui.label(format!("{:.3} s", gap.duration_us(now) as f64 / 1e6));
+                        ui.end_row();
+                    }
+                });
+        });
+}
@@ -0,0 +1,111 @@
+use eframe::egui;
+
+/// 以簡單折線圖繪製數列，並在左側標示最大/最小值
+pub fn line_chart(ui: &mut egui::Ui, values: &[f64], height: f32, color: egui::Color32) {
+    let width = ui.available_width();
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_stroke(
+        rect,
+        0.0,
+        visuals.widgets.noninteractive.bg_stroke,
+        egui::StrokeKind::Inside,
+    );
+    if values.is_empty() {
+        return;
+    }
+
+    let min = values.iter().cloned().fold(f64::INFINITY, f64::min);
+    let max = values.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+    let span = if (max - min).abs() < f64::EPSILON {
+        1.0
+    } else {
+        max - min
+    };
+    let step = if values.len() > 1 {
+        rect.width() / (values.len() - 1) as f32
+    } else {
+        0.0
+    };
+    let points: Vec<egui::Pos2> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| {
+            let y = rect.bottom() - ((v - min) / span) as f32 * rect.height();
+            egui::pos2(rect.left() + i as f32 * step, y)
+        })
+        .collect();
+    painter.add(egui::Shape::line(points, egui::Stroke::new(1.5, color)));
+
+    let font = egui::FontId::monospace(10.0);
+    let text_color = visuals.weak_text_color();
+    painter.text(
+        rect.left_top(),
+        egui::Align2::LEFT_TOP,
+        format!("{:.2}", max),
+        font.clone(),
+        text_color,
+    );
+    painter.text(
+        rect.left_bottom(),
+        egui::Align2::LEFT_BOTTOM,
+        format!("{:.2}", min),
+        font,
+        text_color,
+    );
+}
+
+/// 以長條圖繪製直方圖，每個長條下方標示區間名稱、上方標示筆數
+pub fn bar_chart(ui: &mut egui::Ui, bars: &[(String, u64)], height: f32, color: egui::Color32) {
+    let width = ui.available_width();
+    let (rect, _) = ui.allocate_exact_size(egui::vec2(width, height), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_stroke(
+        rect,
+        0.0,
+        visuals.widgets.noninteractive.bg_stroke,
+        egui::StrokeKind::Inside,
+    );
+    if bars.is_empty() {
+        return;
+    }
+
+    let font = egui::FontId::monospace(10.0);
+    let text_color = visuals.weak_text_color();
+    let label_height = 12.0;
+    let max = bars.iter().map(|(_, n)| *n).max().unwrap_or(0).max(1) as f32;
+    let slot = rect.width() / bars.len() as f32;
+    let plot_height = rect.height() - 2.0 * label_height;
+    for (i, (label, count)) in bars.iter().enumerate() {
+        let left = rect.left() + i as f32 * slot;
+        let center = left + slot / 2.0;
+        let bottom = rect.bottom() - label_height;
+        let top = bottom - *count as f32 / max * plot_height;
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(left + 2.0, top),
+                egui::pos2(left + slot - 2.0, bottom),
+            ),
+            0.0,
+            color,
+        );
+        painter.text(
+            egui::pos2(center, rect.bottom()),
+            egui::Align2::CENTER_BOTTOM,
+            label,
+            font.clone(),
+            text_color,
+        );
+        if *count > 0 {
+            painter.text(
+                egui::pos2(center, top),
+                egui::Align2::CENTER_BOTTOM,
+                count.to_string(),
+                font.clone(),
+                text_color,
+            );
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::can::conformance::{export_results_csv, ConformanceSuite};
+use crate::can::sequence::SequenceValidator;
+use crate::ui::format_timestamp;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::Mutex;
+
+/// 一致性檢查結果表：每項檢查的 PASS/FAIL、檢查筆數與違規次數，
+/// 以及訊息序列的偏差紀錄
+pub fn show_conformance(
+    ui: &mut egui::Ui,
+    suite: &Mutex<ConformanceSuite>,
+    sequences: &Mutex<SequenceValidator>,
+) {
+    let mut results = suite.lock().unwrap().results();
+    results.extend(sequences.lock().unwrap().results());
+    ui.horizontal(|ui| {
+        if ui.button("Reset").clicked() {
+            suite.lock().unwrap().reset();
+            sequences.lock().unwrap().reset();
+        }
+        if ui
+            .add_enabled(!results.is_empty(), egui::Button::new("Export CSV"))
+            .clicked()
+        {
+            if let Some(path) = FileDialog::new()
+                .add_filter("csv", &["csv"])
+                .set_file_name("conformance.csv")
+                .save_file()
+            {
+                match export_results_csv(&path.to_string_lossy(), &results) {
+                    Ok(()) => {
+                        tracing::info!(target: "conformance", path = %path.display(), "Exported results")
+                    }
+                    Err(e) => tracing::error!(target: "conformance", "{}", e),
+                }
+            }
+        }
+        let failed = results.iter().filter(|r| !r.passed()).count();
+        if failed > 0 {
+            ui.colored_label(egui::Color32::RED, format!("{} check(s) failed", failed));
+        }
+    });
+    ui.separator();
+    if results.is_empty() {
+        ui.weak("Load a YAML config to define the checks (canbus_config, counters, cycle_times, checksums, expected_sequences)");
+        return;
+    }
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("conformance_results")
+            .striped(true)
+            .num_columns(6)
+            .show(ui, |ui| {
+                for header in [
+                    "Check",
+                    "Target",
+                    "Result",
+                    "Checked",
+                    "Violations",
+                    "Detail",
+                ] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for r in &results {
+                    ui.label(r.kind.label());
+                    ui.monospace(&r.target);
+                    let color = if r.untested() {
+                        egui::Color32::GRAY
+                    } else if r.passed() {
+                        egui::Color32::GREEN
+                    } else {
+                        egui::Color32::RED
+                    };
+                    ui.colored_label(color, r.verdict());
+                    ui.label(r.checked.to_string());
+                    ui.label(r.violations.to_string());
+                    ui.label(&r.detail);
+                    ui.end_row();
+                }
+            });
+        let sequences = sequences.lock().unwrap();
+        if sequences.deviations().next().is_none() {
+            return;
+        }
+        ui.separator();
+        ui.strong("Sequence deviations");
+        egui::Grid::new("sequence_deviations")
+            .striped(true)
+            .num_columns(4)
+            .show(ui, |ui| {
+                for header in ["Time", "Sequence", "Step", "Deviation"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for d in sequences.deviations().rev() {
+                    ui.monospace(format_timestamp(d.timestamp));
+                    ui.label(&d.sequence);
+                    ui.label(d.step.to_string());
+                    ui.label(&d.message);
+                    ui.end_row();
+                }
+            });
+    });
+}
@@ -0,0 +1,359 @@
+use crate::can::config::{parse_color, Component, ComponentKind};
+use crate::can::dbc::DbcDecoder;
+use crate::can::plot::signal_value;
+use crate::can::signals::SignalTable;
+use crate::can::units::DisplayUnits;
+use crate::ui::chart::line_chart;
+use crate::ui::plot_export::{save_plot, PlotImage};
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::collections::{HashMap, VecDeque};
+
+const ROW_HEIGHT: f32 = 24.0;
+/// 儀表與折線圖的列高
+const TALL_ROW_HEIGHT: f32 = 80.0;
+
+/// 元件目前的數值
+pub struct Reading {
+    /// 設定檔單位的原始值，用於判斷條件格式
+    pub raw: Option<f64>,
+    /// 換算成顯示單位後的值
+    pub value: Option<f64>,
+    pub unit: String,
+    /// 狀態名稱（YAML 的 `values` 或 DBC 的 `VAL_`），取代數值顯示
+    pub label: Option<String>,
+    /// 數值的更新時間（微秒），尚未收到時為 0
+    pub updated_at: u64,
+}
+
+impl Reading {
+    /// 元件 key 對應訊號的目前值：canbus_config 的訊號優先，其次為 `訊息.訊號` 形式的 DBC 訊號
+    pub fn of(
+        comp: &Component,
+        signals: &SignalTable,
+        dbc: &DbcDecoder,
+        units: &DisplayUnits,
+    ) -> Self {
+        let current = signal_value(signals, dbc, &comp.key);
+        let label = match signals.get(&comp.key) {
+            Some(state) => state.label.clone(),
+            None => comp
+                .key
+                .split_once('.')
+                .and_then(|(message, signal)| dbc.value(message, signal))
+                .and_then(|decoded| decoded.label.clone()),
+        };
+        Self {
+            raw: current.map(|(value, _)| value),
+            value: current.map(|(value, _)| units.convert(&comp.key, value)),
+            unit: units.unit(&comp.key).to_string(),
+            label,
+            updated_at: current.map_or(0, |(_, timestamp)| timestamp),
+        }
+    }
+}
+
+/// 顯示值取到小數點後 3 位，去掉多餘的 0
+fn format_value(value: f64) -> String {
+    ((value * 1000.0).round() / 1000.0).to_string()
+}
+
+fn to_color32([r, g, b]: [u8; 3]) -> egui::Color32 {
+    egui::Color32::from_rgb(r, g, b)
+}
+
+fn row_height(kind: &ComponentKind) -> f32 {
+    match kind {
+        ComponentKind::Gauge { .. } | ComponentKind::Plot { .. } => TALL_ROW_HEIGHT,
+        _ => ROW_HEIGHT,
+    }
+}
+
+/// 已決定格位的元件
+struct Cell<'a> {
+    row: u32,
+    col: u32,
+    span: u32,
+    comp: &'a Component,
+}
+
+/// 依 row/col/span 計算格位；未指定 row 的元件依序接在最後一列之後
+fn place<'a>(comps: &[&'a Component]) -> Vec<Cell<'a>> {
+    let mut next_row = comps
+        .iter()
+        .filter_map(|c| c.row)
+        .max()
+        .map_or(0, |r| r + 1);
+    let mut cells: Vec<Cell> = comps
+        .iter()
+        .map(|&comp| {
+            let row = comp.row.unwrap_or_else(|| {
+                next_row += 1;
+                next_row - 1
+            });
+            Cell {
+                row,
+                col: comp.col.unwrap_or(0),
+                span: comp.span.unwrap_or(1).max(1),
+                comp,
+            }
+        })
+        .collect();
+    cells.sort_by_key(|c| (c.row, c.col));
+    cells
+}
+
+/// YAML components 的儀表板，保留折線圖元件的歷史值與最後一筆的更新時間
+#[derive(Default)]
+pub struct Dashboard {
+    history: HashMap<String, (u64, VecDeque<f64>)>,
+}
+
+impl Dashboard {
+    /// 依群組與格位繪製元件，`reading_of` 回傳每個元件的顯示值，
+    /// `menu` 為元件的右鍵選單內容
+    pub fn show<F, M>(&mut self, ui: &mut egui::Ui, comps: &[Component], reading_of: F, menu: M)
+    where
+        F: Fn(&Component) -> Reading,
+        M: Fn(&mut egui::Ui, &Component),
+    {
+        // 依群組首次出現的順序分組，未指定群組的元件在最前面
+        let mut groups: Vec<(Option<&str>, Vec<&Component>)> = Vec::new();
+        for comp in comps {
+            let group = comp.group.as_deref();
+            match groups.iter_mut().find(|(g, _)| *g == group) {
+                Some((_, members)) => members.push(comp),
+                None => groups.push((group, vec![comp])),
+            }
+        }
+        groups.sort_by_key(|(g, _)| g.is_some());
+
+        for (group, members) in groups {
+            match group {
+                Some(name) => {
+                    egui::CollapsingHeader::new(name)
+                        .default_open(true)
+                        .show(ui, |ui| self.show_grid(ui, &members, &reading_of, &menu));
+                }
+                None => self.show_grid(ui, &members, &reading_of, &menu),
+            }
+        }
+    }
+
+    fn show_grid<F, M>(&mut self, ui: &mut egui::Ui, comps: &[&Component], reading_of: &F, menu: &M)
+    where
+        F: Fn(&Component) -> Reading,
+        M: Fn(&mut egui::Ui, &Component),
+    {
+        let cells = place(comps);
+        let columns = cells.iter().map(|c| c.col + c.span).max().unwrap_or(1);
+        let spacing = ui.spacing().item_spacing.x;
+        let col_width =
+            ((ui.available_width() - spacing * (columns - 1) as f32) / columns as f32).max(40.0);
+
+        let mut i = 0;
+        while i < cells.len() {
+            let row = cells[i].row;
+            let end = cells[i..]
+                .iter()
+                .position(|c| c.row != row)
+                .map_or(cells.len(), |n| i + n);
+            let height = cells[i..end]
+                .iter()
+                .map(|c| row_height(&c.comp.kind))
+                .fold(ROW_HEIGHT, f32::max);
+            ui.horizontal(|ui| {
+                let mut next_col = 0;
+                for cell in &cells[i..end] {
+                    // 重疊的格位直接略過，避免覆蓋前一個元件
+                    if cell.col < next_col {
+                        continue;
+                    }
+                    if cell.col > next_col {
+                        ui.add_space(
+                            (col_width + spacing) * (cell.col - next_col) as f32 - spacing,
+                        );
+                    }
+                    let width = col_width * cell.span as f32 + spacing * (cell.span - 1) as f32;
+                    let reading = reading_of(cell.comp);
+                    ui.allocate_ui_with_layout(
+                        egui::vec2(width, height),
+                        egui::Layout::top_down(egui::Align::Min),
+                        |ui| {
+                            ui.set_width(width);
+                            self.show_component(ui, cell.comp, reading)
+                                .context_menu(|ui| {
+                                    if matches!(cell.comp.kind, ComponentKind::Plot { .. })
+                                        && ui.button("Save plot as PNG/SVG...").clicked()
+                                    {
+                                        self.export_plot(cell.comp);
+                                        ui.close_menu();
+                                    }
+                                    menu(ui, cell.comp)
+                                });
+                        },
+                    );
+                    next_col = cell.col + cell.span;
+                }
+            });
+            i = end;
+        }
+    }
+
+    /// 將折線圖元件目前的歷史值輸出為 PNG 或 SVG
+    fn export_plot(&self, comp: &Component) {
+        let Some(path) = FileDialog::new()
+            .add_filter("png", &["png"])
+            .add_filter("svg", &["svg"])
+            .set_file_name(format!("{}.png", comp.key))
+            .save_file()
+        else {
+            return;
+        };
+        let title = comp.text.as_deref().unwrap_or(&comp.key);
+        let unit = comp.unit.as_deref().unwrap_or_default();
+        let values = self.history.get(&comp.key).map(|(_, h)| h);
+        let plot = PlotImage::from_history(title, unit, values.into_iter().flatten().copied());
+        let path = path.to_string_lossy();
+        match save_plot(&path, &plot) {
+            Ok(()) => tracing::info!(target: "plot", path = %path, "Saved plot"),
+            Err(e) => tracing::error!(target: "plot", "{}", e),
+        }
+    }
+
+    /// 依元件種類繪製，回傳可掛右鍵選單的 Response
+    fn show_component(
+        &mut self,
+        ui: &mut egui::Ui,
+        comp: &Component,
+        reading: Reading,
+    ) -> egui::Response {
+        let Reading {
+            raw,
+            value,
+            unit,
+            label,
+            updated_at,
+        } = reading;
+        let title = comp.text.as_deref().unwrap_or(&comp.key);
+        let style = raw.and_then(|v| comp.style_for(v));
+        let style_color = style
+            .and_then(|s| s.color.as_deref())
+            .and_then(parse_color)
+            .map(to_color32);
+        let value_text = match (style.and_then(|s| s.text.as_deref()), label, value) {
+            (Some(text), _, _) => text.to_string(),
+            (None, Some(label), _) => label,
+            (None, None, Some(v)) => format!("{} {}", format_value(v), unit),
+            (None, None, None) => "--".to_string(),
+        };
+        match &comp.kind {
+            ComponentKind::Label => {
+                let text = egui::RichText::new(format!("{}: {}", title, value_text));
+                ui.label(match style_color {
+                    Some(color) => text.color(color),
+                    None => text,
+                })
+            }
+            ComponentKind::Led { color } => {
+                let on_color = style_color
+                    .unwrap_or_else(|| to_color32(parse_color(color).unwrap_or([60, 200, 60])));
+                let on = raw.is_some_and(|v| v != 0.0);
+                ui.horizontal(|ui| {
+                    let size = ui.text_style_height(&egui::TextStyle::Body);
+                    let (rect, _) =
+                        ui.allocate_exact_size(egui::vec2(size, size), egui::Sense::hover());
+                    let fill = if on {
+                        on_color
+                    } else {
+                        egui::Color32::from_gray(60)
+                    };
+                    ui.painter().circle_filled(rect.center(), size * 0.4, fill);
+                    ui.label(title);
+                })
+                .response
+            }
+            ComponentKind::Bar { min, max } => {
+                let fraction = raw.map_or(0.0, |v| ((v - min) / (max - min)).clamp(0.0, 1.0));
+                let mut bar = egui::ProgressBar::new(fraction as f32)
+                    .text(format!("{}: {}", title, value_text));
+                if let Some(color) = style_color {
+                    bar = bar.fill(color);
+                }
+                ui.add(bar)
+            }
+            ComponentKind::Gauge { min, max } => {
+                ui.label(title);
+                let fraction = raw.map_or(0.0, |v| ((v - min) / (max - min)).clamp(0.0, 1.0));
+                gauge(
+                    ui,
+                    fraction as f32,
+                    &value_text,
+                    style_color.unwrap_or(egui::Color32::LIGHT_BLUE),
+                )
+            }
+            ComponentKind::Plot { points } => {
+                let (last_update, history) = self.history.entry(comp.key.clone()).or_default();
+                // 每筆新值只加入一次，不隨重繪重複
+                if let Some(v) = value.filter(|_| updated_at != *last_update) {
+                    history.push_back(v);
+                    *last_update = updated_at;
+                }
+                while history.len() > *points {
+                    history.pop_front();
+                }
+                let response = ui.label(format!("{}: {}", title, value_text));
+                let values: Vec<f64> = history.iter().copied().collect();
+                let height = ui.available_height().max(20.0);
+                line_chart(
+                    ui,
+                    &values,
+                    height,
+                    style_color.unwrap_or(egui::Color32::LIGHT_BLUE),
+                );
+                response
+            }
+        }
+    }
+}
+
+/// 半圓指針儀表，`fraction` 為 0..=1
+fn gauge(ui: &mut egui::Ui, fraction: f32, text: &str, color: egui::Color32) -> egui::Response {
+    let height = ui.available_height().max(30.0);
+    let radius = (height - 4.0)
+        .min(ui.available_width() / 2.0 - 4.0)
+        .max(10.0);
+    let (rect, response) = ui.allocate_exact_size(
+        egui::vec2(ui.available_width(), height),
+        egui::Sense::hover(),
+    );
+    let painter = ui.painter_at(rect);
+    let center = egui::pos2(rect.center().x, rect.bottom() - 2.0);
+    let point = |t: f32, r: f32| {
+        let angle = std::f32::consts::PI * (1.0 - t);
+        center + egui::vec2(angle.cos(), -angle.sin()) * r
+    };
+    let visuals = ui.visuals();
+    let track: Vec<egui::Pos2> = (0..=32).map(|i| point(i as f32 / 32.0, radius)).collect();
+    painter.add(egui::Shape::line(
+        track,
+        egui::Stroke::new(3.0, visuals.widgets.noninteractive.bg_stroke.color),
+    ));
+    let filled: Vec<egui::Pos2> = (0..=32)
+        .map(|i| point(fraction * i as f32 / 32.0, radius))
+        .collect();
+    painter.add(egui::Shape::line(filled, egui::Stroke::new(3.0, color)));
+    painter.line_segment(
+        [center, point(fraction, radius * 0.85)],
+        egui::Stroke::new(2.0, visuals.strong_text_color()),
+    );
+    painter.text(
+        center - egui::vec2(0.0, radius * 0.35),
+        egui::Align2::CENTER_CENTER,
+        text,
+        egui::FontId::proportional(13.0),
+        visuals.text_color(),
+    );
+    response
+}
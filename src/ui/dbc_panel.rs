@@ -0,0 +1,181 @@
+use crate::can::canbus::SharedCan;
+use crate::can::dbc::{Dbc, DbcDecoder};
+use crate::ui::format_frame;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::collections::{HashMap, HashSet};
+use std::sync::Mutex;
+
+/// DBC 訊號畫面：載入 DBC 檔並顯示即時解碼的訊號值，也可依訊號值組出訊框傳送
+#[derive(Default)]
+pub struct DbcPanel {
+    /// 以訊息或訊號名稱過濾（不分大小寫）
+    search: String,
+    tx_message: String,
+    tx_channel: u32,
+    /// 傳送訊息的物理值，以訊號名稱對應；切換訊息時保留同名訊號的值
+    tx_values: HashMap<String, f64>,
+}
+
+impl DbcPanel {
+    /// `alarmed` 中以 `訊息.訊號` 表示的訊號以紅色顯示
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        decoder: &Mutex<DbcDecoder>,
+        can_app: &SharedCan,
+        alarmed: &HashSet<String>,
+    ) {
+        ui.horizontal(|ui| {
+            if ui.button("Load DBC").clicked() {
+                if let Some(path) = FileDialog::new().add_filter("dbc", &["dbc"]).pick_file() {
+                    let path = path.to_string_lossy().into_owned();
+                    match Dbc::load(&path) {
+                        Ok(dbc) => {
+                            tracing::info!(
+                                target: "dbc",
+                                path = %path,
+                                messages = dbc.messages.len(),
+                                signals = dbc.signal_count(),
+                                "Loaded DBC"
+                            );
+                            decoder.lock().unwrap().set_dbc(dbc, Some(path));
+                        }
+                        Err(e) => tracing::error!(target: "dbc", "Failed to load DBC: {}", e),
+                    }
+                }
+            }
+            if ui.button("Clear Values").clicked() {
+                decoder.lock().unwrap().clear_values();
+            }
+        });
+        let decoder = decoder.lock().unwrap();
+        match decoder.path() {
+            Some(path) => ui.weak(format!(
+                "{} ({} messages, {} signals)",
+                path,
+                decoder.dbc().messages.len(),
+                decoder.dbc().signal_count()
+            )),
+            None => ui.weak("No DBC loaded"),
+        };
+        egui::CollapsingHeader::new("Transmit")
+            .id_salt("dbc_transmit")
+            .show(ui, |ui| self.show_transmit(ui, &decoder, can_app));
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            ui.text_edit_singleline(&mut self.search);
+        });
+        ui.separator();
+
+        let search = self.search.to_lowercase();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            egui::Grid::new("dbc_signals")
+                .striped(true)
+                .num_columns(5)
+                .show(ui, |ui| {
+                    for header in ["Message", "Signal", "Value", "Unit", "N"] {
+                        ui.strong(header);
+                    }
+                    ui.end_row();
+                    for (message, signal, decoded) in decoder.values() {
+                        if !search.is_empty()
+                            && !message.to_lowercase().contains(&search)
+                            && !signal.to_lowercase().contains(&search)
+                        {
+                            continue;
+                        }
+                        ui.label(message);
+                        ui.label(signal);
+                        let text = match decoded.label {
+                            Some(ref label) => format!("{} ({})", label, decoded.raw),
+                            None => format!("{:.3}", decoded.value),
+                        };
+                        let mut text = egui::RichText::new(text);
+                        if decoded.label.is_none() {
+                            text = text.monospace();
+                        }
+                        if alarmed.contains(&format!("{}.{}", message, signal)) {
+                            text = text.color(egui::Color32::RED);
+                        }
+                        ui.label(text).on_hover_text(format!("raw {}", decoded.raw));
+                        ui.label(&decoded.unit);
+                        ui.label(decoded.count.to_string());
+                        ui.end_row();
+                    }
+                });
+        });
+    }
+
+    fn show_transmit(&mut self, ui: &mut egui::Ui, decoder: &DbcDecoder, can_app: &SharedCan) {
+        let dbc = decoder.dbc();
+        ui.horizontal(|ui| {
+            ui.label("Message:");
+            egui::ComboBox::from_id_salt("dbc_tx_message")
+                .selected_text(&self.tx_message)
+                .show_ui(ui, |ui| {
+                    for message in &dbc.messages {
+                        ui.selectable_value(
+                            &mut self.tx_message,
+                            message.name.clone(),
+                            format!("{} (0x{:X})", message.name, message.id),
+                        );
+                    }
+                });
+            ui.label("Channel:");
+            ui.add(egui::DragValue::new(&mut self.tx_channel).range(0..=15));
+        });
+        let Some(message) = dbc.message_by_name(&self.tx_message) else {
+            ui.weak("Select a message to transmit");
+            return;
+        };
+        let selector = message.selector(&self.tx_values);
+        egui::Grid::new("dbc_tx_signals")
+            .num_columns(3)
+            .show(ui, |ui| {
+                for signal in &message.signals {
+                    if !message.is_active(signal, selector) {
+                        continue;
+                    }
+                    let value = self
+                        .tx_values
+                        .entry(signal.name.clone())
+                        .or_insert(signal.offset);
+                    ui.label(&signal.name);
+                    if signal.value_names.is_empty() {
+                        let mut drag = egui::DragValue::new(value).speed(signal.factor);
+                        if signal.min < signal.max {
+                            drag = drag.range(signal.min..=signal.max);
+                        }
+                        ui.add(drag);
+                    } else {
+                        let raw = ((*value - signal.offset) / signal.factor).round() as i64;
+                        egui::ComboBox::from_id_salt(("dbc_tx_value", &signal.name))
+                            .selected_text(signal.value_name(raw).unwrap_or("-"))
+                            .show_ui(ui, |ui| {
+                                for (raw, name) in &signal.value_names {
+                                    let physical = *raw as f64 * signal.factor + signal.offset;
+                                    ui.selectable_value(value, physical, name);
+                                }
+                            });
+                    }
+                    ui.label(&signal.unit);
+                    ui.end_row();
+                }
+            });
+        match message.frame(self.tx_channel, &self.tx_values) {
+            Ok(frame) => {
+                ui.monospace(format_frame(&frame));
+                if ui.button("Send").clicked() {
+                    if let Err(e) = can_app.send_frame(&frame) {
+                        tracing::error!(target: "dbc", "Send {} failed: {}", message.name, e);
+                    }
+                }
+            }
+            Err(e) => {
+                ui.colored_label(egui::Color32::RED, e);
+            }
+        }
+    }
+}
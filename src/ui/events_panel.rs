@@ -0,0 +1,119 @@
+use crate::can::events::{EventLog, Severity};
+use crate::ui::format_timestamp;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::Mutex;
+
+/// 警報/事件面板：可依嚴重程度與文字篩選，並提供確認、清除與匯出
+pub struct EventsPanel {
+    filter: String,
+    show_info: bool,
+    show_warning: bool,
+    show_alarm: bool,
+    unacknowledged_only: bool,
+}
+
+impl Default for EventsPanel {
+    fn default() -> Self {
+        Self {
+            filter: String::new(),
+            show_info: true,
+            show_warning: true,
+            show_alarm: true,
+            unacknowledged_only: false,
+        }
+    }
+}
+
+fn severity_color(severity: Severity) -> egui::Color32 {
+    match severity {
+        Severity::Info => egui::Color32::GRAY,
+        Severity::Warning => egui::Color32::YELLOW,
+        Severity::Alarm => egui::Color32::RED,
+    }
+}
+
+impl EventsPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, events: &Mutex<EventLog>) {
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            ui.text_edit_singleline(&mut self.filter);
+            ui.checkbox(&mut self.show_info, "Info");
+            ui.checkbox(&mut self.show_warning, "Warning");
+            ui.checkbox(&mut self.show_alarm, "Alarm");
+            ui.checkbox(&mut self.unacknowledged_only, "Unacknowledged only");
+        });
+        ui.horizontal(|ui| {
+            if ui.button("Acknowledge All").clicked() {
+                events.lock().unwrap().acknowledge_all();
+            }
+            if ui.button("Clear Acknowledged").clicked() {
+                events.lock().unwrap().clear_acknowledged();
+            }
+            if ui.button("Clear All").clicked() {
+                events.lock().unwrap().clear();
+            }
+            if ui.button("Export CSV").clicked() {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("csv", &["csv"])
+                    .set_file_name("events.csv")
+                    .save_file()
+                {
+                    if let Err(e) = events
+                        .lock()
+                        .unwrap()
+                        .export_csv(path.to_str().unwrap(), format_timestamp)
+                    {
+                        tracing::error!(target: "events", "Failed to export events: {}", e);
+                    }
+                }
+            }
+        });
+        ui.separator();
+
+        let filter = self.filter.to_lowercase();
+        let mut acknowledge = None;
+        let log = events.lock().unwrap();
+        egui::ScrollArea::vertical()
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("events_grid")
+                    .striped(true)
+                    .num_columns(5)
+                    .show(ui, |ui| {
+                        for event in log.events().rev() {
+                            let visible = match event.severity {
+                                Severity::Info => self.show_info,
+                                Severity::Warning => self.show_warning,
+                                Severity::Alarm => self.show_alarm,
+                            };
+                            if !visible
+                                || (self.unacknowledged_only && event.acknowledged)
+                                || (!filter.is_empty()
+                                    && !event.message.to_lowercase().contains(&filter))
+                            {
+                                continue;
+                            }
+                            ui.label(format_timestamp(event.timestamp));
+                            ui.colored_label(
+                                severity_color(event.severity),
+                                format!("{:?}", event.severity),
+                            );
+                            ui.label(format!("{:?}", event.category));
+                            ui.label(&event.message);
+                            if event.acknowledged {
+                                ui.weak("ack");
+                            } else if ui.small_button("Ack").clicked() {
+                                acknowledge = Some(event.seq);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+        drop(log);
+        if let Some(seq) = acknowledge {
+            events.lock().unwrap().acknowledge(seq);
+        }
+    }
+}
@@ -0,0 +1,159 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::filter::FrameFilter;
+use crate::ui::format_frame;
+
+use eframe::egui;
+
+/// 追蹤畫面的過濾輸入框，內容變更時重新編譯運算式
+#[derive(Default)]
+pub struct FilterBox {
+    text: String,
+    filter: Option<FrameFilter>,
+    error: Option<String>,
+}
+
+impl FilterBox {
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Filter:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.text)
+                    .hint_text("id == 0x123 && data[0] > 0x80 && ch == 1")
+                    .desired_width(f32::INFINITY),
+            );
+            if response.changed() {
+                self.compile();
+            }
+        });
+        if let Some(ref error) = self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// 設定運算式並重新編譯
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.compile();
+    }
+
+    fn compile(&mut self) {
+        if self.text.trim().is_empty() {
+            self.filter = None;
+            self.error = None;
+            return;
+        }
+        match FrameFilter::compile(&self.text) {
+            Ok(filter) => {
+                self.filter = Some(filter);
+                self.error = None;
+            }
+            // 編譯失敗時保留上一個有效的運算式
+            Err(e) => self.error = Some(e),
+        }
+    }
+
+    /// 未設定過濾條件時所有訊框都符合
+    pub fn matches(&self, frame: &CanFrame) -> bool {
+        self.filter.as_ref().is_none_or(|f| f.matches(frame))
+    }
+}
+
+/// 快速過濾的單一詞彙
+#[derive(Debug, Clone, PartialEq)]
+enum Term {
+    /// `0x` 開頭的十六進位 ID，訊框需 ID 完全相同
+    Id(u32),
+    /// 不分大小寫的文字片段
+    Text(String),
+}
+
+/// 以逗號分隔的快速過濾詞彙，符合任一詞彙的行才顯示，例如 `0x2A1, error`
+#[derive(Default)]
+pub struct TermFilter {
+    text: String,
+    terms: Vec<Term>,
+}
+
+impl TermFilter {
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Show:");
+            let response = ui.add(
+                egui::TextEdit::singleline(&mut self.text)
+                    .hint_text("0x2A1, error")
+                    .desired_width(f32::INFINITY),
+            );
+            if response.changed() {
+                self.parse();
+            }
+        });
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.to_string();
+        self.parse();
+    }
+
+    fn parse(&mut self) {
+        self.terms = self
+            .text
+            .split(',')
+            .map(str::trim)
+            .filter(|term| !term.is_empty())
+            .map(|term| {
+                let hex = term
+                    .strip_prefix("0x")
+                    .or_else(|| term.strip_prefix("0X"))
+                    .and_then(|digits| u32::from_str_radix(digits, 16).ok());
+                match hex {
+                    Some(id) => Term::Id(id),
+                    None => Term::Text(term.to_lowercase()),
+                }
+            })
+            .collect();
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    /// 文字行是否符合；ID 詞彙以不分大小寫的文字比對
+    pub fn matches_text(&self, text: &str) -> bool {
+        if self.terms.is_empty() {
+            return true;
+        }
+        let text = text.to_lowercase();
+        self.terms.iter().any(|term| match term {
+            Term::Id(id) => {
+                // 0x2a1 不應符合 0x2a10
+                let needle = format!("0x{:x}", id);
+                text.match_indices(&needle).any(|(index, _)| {
+                    !text[index + needle.len()..].starts_with(|c: char| c.is_ascii_hexdigit())
+                })
+            }
+            Term::Text(fragment) => text.contains(fragment.as_str()),
+        })
+    }
+
+    /// 訊框是否符合；ID 詞彙比對訊框 ID，文字詞彙比對追蹤畫面的單行格式
+    pub fn matches_frame(&self, frame: &CanFrame) -> bool {
+        if self.terms.is_empty() {
+            return true;
+        }
+        let mut text = None;
+        self.terms.iter().any(|term| match term {
+            Term::Id(id) => frame.id == *id,
+            Term::Text(fragment) => text
+                .get_or_insert_with(|| format_frame(frame).to_lowercase())
+                .contains(fragment.as_str()),
+        })
+    }
+}
@@ -0,0 +1,212 @@
+use crate::can::canbus::SharedCan;
+use crate::can::gateway::{
+    parse_remaps, ForwardLatency, Gateway, GatewayConfig, GatewayDirection, DEFAULT_SCRIPT,
+};
+use crate::can::id_filter::{FilterMode, IdFilterRule};
+use crate::ui::chart::bar_chart;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::{Arc, Mutex};
+
+/// Gateway 模式設定面板：ID 名單與 ID 對應，可選用 rhai 腳本修改/延遲/丟棄轉送的訊框
+pub struct GatewayPanel {
+    channel_a: u32,
+    channel_b: u32,
+    direction: GatewayDirection,
+    /// 只轉送的 ID，空白表示全部
+    pass_ids: String,
+    /// 不轉送的 ID
+    block_ids: String,
+    /// `from=to` 的 ID 對應
+    remaps: String,
+    use_script: bool,
+    script: String,
+    /// 最近一次的轉送延遲統計，Gateway 停止後仍保留
+    latency: Option<ForwardLatency>,
+}
+
+impl Default for GatewayPanel {
+    fn default() -> Self {
+        Self {
+            channel_a: 0,
+            channel_b: 1,
+            direction: GatewayDirection::Both,
+            pass_ids: String::new(),
+            block_ids: String::new(),
+            remaps: String::new(),
+            use_script: false,
+            script: DEFAULT_SCRIPT.to_string(),
+            latency: None,
+        }
+    }
+}
+
+/// 通道欄位，旁邊列出啟動中介面的通道供選擇
+fn channel_field(ui: &mut egui::Ui, channel: &mut u32, can_app: &SharedCan) {
+    ui.add(egui::DragValue::new(channel));
+    ui.menu_button("▾", |ui| {
+        let interfaces = can_app.interfaces();
+        if interfaces.is_empty() {
+            ui.weak("No interface running");
+        }
+        for (_, name, channels) in interfaces {
+            for c in channels {
+                if ui.button(format!("CH{} ({})", c, name)).clicked() {
+                    *channel = c;
+                    ui.close_menu();
+                }
+            }
+        }
+    });
+}
+
+impl GatewayPanel {
+    /// 依面板內容建立設定，ID 名單或對應無法解析時回傳錯誤
+    fn config(&self) -> Result<GatewayConfig, String> {
+        let rule = |mode, ids: &str| IdFilterRule {
+            mode,
+            ids: ids.to_string(),
+            ..Default::default()
+        };
+        Ok(GatewayConfig {
+            channel_a: self.channel_a,
+            channel_b: self.channel_b,
+            direction: self.direction,
+            filter: vec![
+                rule(FilterMode::Pass, &self.pass_ids),
+                rule(FilterMode::Block, &self.block_ids),
+            ],
+            remaps: parse_remaps(&self.remaps)?,
+            script: self.use_script.then(|| self.script.clone()),
+        })
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        gateway: &Arc<Mutex<Option<Gateway>>>,
+        can_app: &SharedCan,
+    ) {
+        let running = gateway.lock().unwrap().is_some();
+        ui.add_enabled_ui(!running, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Channel A:");
+                channel_field(ui, &mut self.channel_a, can_app);
+                egui::ComboBox::from_id_salt("gateway_direction")
+                    .selected_text(self.direction.arrow())
+                    .width(48.0)
+                    .show_ui(ui, |ui| {
+                        for direction in GatewayDirection::ALL {
+                            ui.selectable_value(&mut self.direction, direction, direction.arrow());
+                        }
+                    });
+                ui.label("Channel B:");
+                channel_field(ui, &mut self.channel_b, can_app);
+            });
+            egui::Grid::new("gateway_rules")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Pass IDs:").on_hover_text(
+                        "Only forward these IDs, e.g. 100-1FF, 7DF; empty forwards all",
+                    );
+                    ui.text_edit_singleline(&mut self.pass_ids);
+                    ui.end_row();
+                    ui.label("Block IDs:");
+                    ui.text_edit_singleline(&mut self.block_ids);
+                    ui.end_row();
+                    ui.label("Remap IDs:")
+                        .on_hover_text("Rewrite IDs while forwarding, e.g. 100=200, 7DF=7E0");
+                    ui.text_edit_singleline(&mut self.remaps);
+                    ui.end_row();
+                });
+            ui.horizontal(|ui| {
+                ui.checkbox(&mut self.use_script, "Use rhai hook script");
+                if ui.button("Load Script").clicked() {
+                    if let Some(path) = FileDialog::new().add_filter("rhai", &["rhai"]).pick_file()
+                    {
+                        match std::fs::read_to_string(&path) {
+                            Ok(script) => {
+                                self.script = script;
+                                self.use_script = true;
+                            }
+                            Err(e) => {
+                                tracing::error!(target: "gateway", "Failed to read script: {}", e)
+                            }
+                        }
+                    }
+                }
+            });
+            if self.use_script {
+                egui::ScrollArea::vertical()
+                    .max_height(240.0)
+                    .show(ui, |ui| {
+                        ui.add(
+                            egui::TextEdit::multiline(&mut self.script)
+                                .code_editor()
+                                .desired_width(f32::INFINITY),
+                        );
+                    });
+            }
+        });
+
+        ui.horizontal(|ui| {
+            if !running && ui.button("Start Gateway").clicked() {
+                let started = self.config().and_then(|config| {
+                    Gateway::start(
+                        config,
+                        can_app.clone(),
+                        move |msg| tracing::info!(target: "gateway", "{}", msg),
+                    )
+                });
+                match started {
+                    Ok(gw) => *gateway.lock().unwrap() = Some(gw),
+                    Err(e) => tracing::error!(target: "gateway", "{}", e),
+                }
+            }
+            if running && ui.button("Stop Gateway").clicked() {
+                // Drop 時會停止背景執行緒
+                gateway.lock().unwrap().take();
+            }
+            if ui
+                .add_enabled(self.latency.is_some(), egui::Button::new("Reset Latency"))
+                .clicked()
+            {
+                if let Some(ref gw) = *gateway.lock().unwrap() {
+                    gw.reset_latency();
+                }
+                self.latency = None;
+            }
+        });
+
+        if let Some(ref gw) = *gateway.lock().unwrap() {
+            self.latency = Some(gw.latency());
+        }
+        if let Some(ref latency) = self.latency {
+            self.show_latency(ui, latency);
+        }
+    }
+
+    fn show_latency(&self, ui: &mut egui::Ui, latency: &ForwardLatency) {
+        ui.separator();
+        ui.strong("Forwarding latency (receive → transmit)");
+        if latency.count == 0 {
+            ui.weak("No frames forwarded yet");
+            return;
+        }
+        ui.label(format!(
+            "n={} min={} µs avg={:.0} µs max={} µs",
+            latency.count,
+            latency.min_us,
+            latency.mean_us(),
+            latency.max_us
+        ));
+        let bars: Vec<(String, u64)> = latency
+            .histogram
+            .iter()
+            .enumerate()
+            .map(|(i, &count)| (ForwardLatency::bucket_label(i), count))
+            .collect();
+        bar_chart(ui, &bars, 100.0, egui::Color32::LIGHT_BLUE);
+    }
+}
@@ -0,0 +1,115 @@
+use crate::can::id_filter::{FilterMode, IdFilter, IdFilterRule};
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// 軟體 ID 過濾面板：各通道的白名單/黑名單，修改後立即套用到接收流程
+#[derive(Default)]
+pub struct IdFilterPanel {
+    rules: Vec<IdFilterRule>,
+    error: Option<String>,
+}
+
+impl IdFilterPanel {
+    pub fn rules(&self) -> &[IdFilterRule] {
+        &self.rules
+    }
+
+    /// 以 profile 中的規則取代目前的規則並套用
+    pub fn set_rules(&mut self, rules: Vec<IdFilterRule>, filter: &Mutex<IdFilter>) {
+        self.rules = rules;
+        self.apply(filter);
+    }
+
+    fn apply(&mut self, filter: &Mutex<IdFilter>) {
+        self.error = filter.lock().unwrap().set_rules(&self.rules).err();
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui, filter: &Mutex<IdFilter>) {
+        ui.weak("IDs in hex, separated by commas, e.g. 100-1FF, 7DF");
+        let mut changed = false;
+        let mut remove = None;
+        egui::Grid::new("id_filter_rules")
+            .num_columns(5)
+            .show(ui, |ui| {
+                for (index, rule) in self.rules.iter_mut().enumerate() {
+                    changed |= ui.checkbox(&mut rule.enabled, "").changed();
+                    egui::ComboBox::from_id_salt(("id_filter_channel", index))
+                        .width(70.0)
+                        .selected_text(match rule.channel {
+                            Some(channel) => format!("Ch {}", channel),
+                            None => "All".to_string(),
+                        })
+                        .show_ui(ui, |ui| {
+                            changed |= ui
+                                .selectable_value(&mut rule.channel, None, "All")
+                                .changed();
+                            for channel in 0..16 {
+                                changed |= ui
+                                    .selectable_value(
+                                        &mut rule.channel,
+                                        Some(channel),
+                                        format!("Ch {}", channel),
+                                    )
+                                    .changed();
+                            }
+                        });
+                    egui::ComboBox::from_id_salt(("id_filter_mode", index))
+                        .width(70.0)
+                        .selected_text(match rule.mode {
+                            FilterMode::Pass => "Pass",
+                            FilterMode::Block => "Block",
+                        })
+                        .show_ui(ui, |ui| {
+                            changed |= ui
+                                .selectable_value(&mut rule.mode, FilterMode::Pass, "Pass")
+                                .changed();
+                            changed |= ui
+                                .selectable_value(&mut rule.mode, FilterMode::Block, "Block")
+                                .changed();
+                        });
+                    changed |= ui
+                        .add(egui::TextEdit::singleline(&mut rule.ids).desired_width(220.0))
+                        .changed();
+                    if ui.small_button("Remove").clicked() {
+                        remove = Some(index);
+                    }
+                    ui.end_row();
+                }
+            });
+        if let Some(index) = remove {
+            self.rules.remove(index);
+            changed = true;
+        }
+        if ui.button("Add Rule").clicked() {
+            self.rules.push(IdFilterRule::default());
+            changed = true;
+        }
+        if changed {
+            self.apply(filter);
+        }
+        if let Some(error) = &self.error {
+            ui.colored_label(
+                egui::Color32::RED,
+                format!("{} (previous rules still active)", error),
+            );
+        }
+
+        ui.separator();
+        let mut filter = filter.lock().unwrap();
+        ui.horizontal(|ui| {
+            let blocked: Vec<String> = filter
+                .blocked()
+                .map(|(channel, count)| format!("Ch {}: {}", channel, count))
+                .collect();
+            if blocked.is_empty() {
+                ui.label("No frames blocked");
+            } else {
+                ui.label(format!("Blocked: {}", blocked.join(", ")));
+            }
+            if ui.small_button("Reset").clicked() {
+                filter.reset_counts();
+            }
+        });
+    }
+}
@@ -0,0 +1,156 @@
+use crate::can::cantypes::now_micros;
+use crate::can::stats::{BusStatistics, IdStats};
+use crate::ui::format_timestamp;
+
+use eframe::egui;
+use std::cmp::Ordering;
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// 畫面更新的間隔（微秒）
+const REFRESH_US: u64 = 1_000_000;
+/// 超過平均週期的幾倍未收到即標示為逾時
+const OVERDUE_FACTOR: f64 = 3.0;
+
+/// 可排序的欄位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum IdColumn {
+    Channel,
+    Id,
+    Count,
+    Mean,
+    StdDev,
+    Jitter,
+    LastSeen,
+}
+
+impl IdColumn {
+    const ALL: [(IdColumn, &'static str); 7] = [
+        (IdColumn::Channel, "CH"),
+        (IdColumn::Id, "ID"),
+        (IdColumn::Count, "Count"),
+        (IdColumn::Mean, "Mean (ms)"),
+        (IdColumn::StdDev, "Std Dev (ms)"),
+        (IdColumn::Jitter, "Jitter (ms)"),
+        (IdColumn::LastSeen, "Last Seen"),
+    ];
+
+    fn compare(&self, a: &IdStats, b: &IdStats) -> Ordering {
+        let float =
+            |a: Option<f64>, b: Option<f64>| a.unwrap_or(-1.0).total_cmp(&b.unwrap_or(-1.0));
+        match self {
+            IdColumn::Channel => a.channel.cmp(&b.channel),
+            IdColumn::Id => a.id.cmp(&b.id),
+            IdColumn::Count => a.count.cmp(&b.count),
+            IdColumn::Mean => float(a.mean_period_ms(), b.mean_period_ms()),
+            IdColumn::StdDev => float(a.period_std_ms(), b.period_std_ms()),
+            IdColumn::Jitter => float(a.jitter_ms(), b.jitter_ms()),
+            IdColumn::LastSeen => a.last_seen.cmp(&b.last_seen),
+        }
+    }
+}
+
+fn format_ms(value: Option<f64>) -> String {
+    match value {
+        Some(ms) => format!("{:.2}", ms),
+        None => "-".to_string(),
+    }
+}
+
+/// 各 ID 的週期統計：平均、標準差與抖動（最大減最小週期），
+/// 用於確認 ECU 的週期任務是否準時；超過平均週期數倍未收到的 ID 以黃色標示
+pub struct IdStatsPanel {
+    updated_at: u64,
+    ids: Vec<IdStats>,
+    /// 排序欄位與是否遞減
+    sort: (IdColumn, bool),
+}
+
+impl Default for IdStatsPanel {
+    fn default() -> Self {
+        Self {
+            updated_at: 0,
+            ids: Vec::new(),
+            sort: (IdColumn::Id, false),
+        }
+    }
+}
+
+impl IdStatsPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, stats: &Mutex<BusStatistics>) {
+        let now = now_micros();
+        if now.saturating_sub(self.updated_at) >= REFRESH_US {
+            self.ids = stats.lock().unwrap().ids().cloned().collect();
+            self.updated_at = now;
+        }
+        ui.ctx()
+            .request_repaint_after(Duration::from_micros(REFRESH_US));
+        let (column, descending) = self.sort;
+        self.ids.sort_by(|a, b| {
+            let order = column.compare(a, b);
+            if descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+
+        ui.horizontal(|ui| {
+            ui.label(format!("{} IDs", self.ids.len()));
+            if ui.button("Reset").clicked() {
+                stats.lock().unwrap().reset(now);
+                self.updated_at = 0;
+            }
+        });
+        if self.ids.is_empty() {
+            ui.label("No traffic yet");
+            return;
+        }
+        egui::ScrollArea::vertical()
+            .id_salt("id_stats")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new("id_stats_grid")
+                    .striped(true)
+                    .num_columns(IdColumn::ALL.len())
+                    .show(ui, |ui| {
+                        for (column, label) in IdColumn::ALL {
+                            let sorted = self.sort.0 == column;
+                            let text = match (sorted, self.sort.1) {
+                                (true, false) => format!("{} ⏶", label),
+                                (true, true) => format!("{} ⏷", label),
+                                (false, _) => label.to_string(),
+                            };
+                            if ui
+                                .selectable_label(sorted, egui::RichText::new(text).strong())
+                                .on_hover_text("Click to sort, click again to reverse")
+                                .clicked()
+                            {
+                                self.sort = (column, sorted && !self.sort.1);
+                            }
+                        }
+                        ui.end_row();
+                        for s in &self.ids {
+                            ui.label(s.channel.to_string());
+                            ui.monospace(format!("0x{:X}", s.id));
+                            ui.label(s.count.to_string());
+                            ui.label(format_ms(s.mean_period_ms()));
+                            ui.label(format_ms(s.period_std_ms()));
+                            ui.label(format_ms(s.jitter_ms()));
+                            let age_ms = now.saturating_sub(s.last_seen) as f64 / 1000.0;
+                            let overdue = s
+                                .mean_period_ms()
+                                .is_some_and(|mean| age_ms > mean * OVERDUE_FACTOR);
+                            let last_seen = format_timestamp(s.last_seen);
+                            if overdue {
+                                ui.colored_label(egui::Color32::YELLOW, last_seen)
+                                    .on_hover_text(format!("No frame for {:.0} ms", age_ms));
+                            } else {
+                                ui.label(last_seen);
+                            }
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
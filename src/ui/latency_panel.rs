@@ -0,0 +1,86 @@
+use crate::can::latency::{LatencyPair, LatencyTracker};
+use crate::ui::chart::line_chart;
+use crate::ui::parse_hex_u32;
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// 請求/回應延遲配對設定與圖表
+pub struct LatencyPanel {
+    request_text: String,
+    response_text: String,
+}
+
+impl Default for LatencyPanel {
+    fn default() -> Self {
+        Self {
+            request_text: "0x7E0".to_string(),
+            response_text: "0x7E8".to_string(),
+        }
+    }
+}
+
+impl LatencyPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, tracker: &Mutex<LatencyTracker>) {
+        ui.horizontal(|ui| {
+            ui.label("Request ID:");
+            ui.add(egui::TextEdit::singleline(&mut self.request_text).desired_width(60.0));
+            ui.label("→ Response ID:");
+            ui.add(egui::TextEdit::singleline(&mut self.response_text).desired_width(60.0));
+            if ui.button("Add Pair").clicked() {
+                match (
+                    parse_hex_u32(&self.request_text),
+                    parse_hex_u32(&self.response_text),
+                ) {
+                    (Ok(request_id), Ok(response_id)) => {
+                        tracker.lock().unwrap().add_pair(LatencyPair {
+                            request_id,
+                            response_id,
+                        });
+                    }
+                    (Err(e), _) | (_, Err(e)) => tracing::error!(target: "latency", "{}", e),
+                }
+            }
+            if ui.button("Reset").clicked() {
+                tracker.lock().unwrap().reset();
+            }
+        });
+        ui.separator();
+
+        let mut remove_index = None;
+        let tracker_guard = tracker.lock().unwrap();
+        egui::ScrollArea::vertical().show(ui, |ui| {
+            for (index, stat) in tracker_guard.stats().iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.strong(format!(
+                        "0x{:X} → 0x{:X}",
+                        stat.pair.request_id, stat.pair.response_id
+                    ));
+                    if ui.small_button("🗑").clicked() {
+                        remove_index = Some(index);
+                    }
+                });
+                if stat.count == 0 {
+                    ui.label(format!("No responses yet, timeouts: {}", stat.timeouts));
+                } else {
+                    ui.label(format!(
+                        "n={} last={:.2} ms min={:.2} ms avg={:.2} ms max={:.2} ms timeouts={}",
+                        stat.count,
+                        stat.last_ms,
+                        stat.min_ms,
+                        stat.mean_ms(),
+                        stat.max_ms,
+                        stat.timeouts
+                    ));
+                }
+                let samples: Vec<f64> = stat.history.iter().copied().collect();
+                line_chart(ui, &samples, 60.0, egui::Color32::LIGHT_BLUE);
+                ui.add_space(4.0);
+            }
+        });
+        drop(tracker_guard);
+        if let Some(index) = remove_index {
+            tracker.lock().unwrap().remove_pair(index);
+        }
+    }
+}
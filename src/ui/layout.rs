@@ -0,0 +1,76 @@
+use crate::can::alarm::AlarmRule;
+use crate::can::autoresponse::ResponseRule;
+use crate::can::blackbox::BlackBoxConfig;
+use crate::can::id_filter::IdFilterRule;
+use crate::can::log_event::Language;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs;
+
+/// 介面配置檔，存放在工作目錄
+pub const LAYOUT_FILE: &str = "ui_layout.yaml";
+/// 尚未載入 YAML 設定時使用的 profile 名稱
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// 單一 profile 的介面狀態：開啟的畫面、各畫面的過濾與顯示偏好
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProfileLayout {
+    /// 開啟中的畫面名稱
+    pub open_views: BTreeSet<String>,
+    /// 主追蹤畫面的過濾運算式
+    pub trace_filter: String,
+    /// 主追蹤畫面與 Log 面板的快速過濾詞彙
+    pub trace_terms: String,
+    pub log_terms: String,
+    /// 額外追蹤畫面的過濾運算式，依開啟順序
+    pub extra_traces: Vec<String>,
+    pub export_filtered: bool,
+    pub log_level: String,
+    /// 後端訊息的顯示語言
+    pub language: Language,
+    /// 訊號 key → 選擇的顯示單位
+    pub units: BTreeMap<String, String>,
+    /// 黑盒子記錄設定，包含是否自動啟動
+    pub blackbox: BlackBoxConfig,
+    /// 接收流程的 ID 白名單/黑名單
+    pub id_filters: Vec<IdFilterRule>,
+    /// 訊號警報規則
+    pub alarms: Vec<AlarmRule>,
+    /// 自動回應（ECU 模擬）規則
+    pub auto_responses: Vec<ResponseRule>,
+    /// 即時曲線圖中的訊號
+    pub plot_signals: Vec<String>,
+}
+
+/// 各 profile 的介面狀態；profile 以載入的 YAML 設定檔名區分
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UiLayout {
+    #[serde(default)]
+    profiles: BTreeMap<String, ProfileLayout>,
+}
+
+impl UiLayout {
+    /// 讀取配置檔，檔案不存在時回傳空的配置
+    pub fn load(file_path: &str) -> Result<Self, String> {
+        match fs::read_to_string(file_path) {
+            Ok(text) => serde_yaml::from_str(&text)
+                .map_err(|e| format!("Failed to parse {}: {}", file_path, e)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(format!("Failed to read {}: {}", file_path, e)),
+        }
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), String> {
+        let text = serde_yaml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(file_path, text).map_err(|e| format!("Failed to write {}: {}", file_path, e))
+    }
+
+    pub fn profile(&self, name: &str) -> Option<&ProfileLayout> {
+        self.profiles.get(name)
+    }
+
+    pub fn set_profile(&mut self, name: &str, layout: ProfileLayout) {
+        self.profiles.insert(name.to_string(), layout);
+    }
+}
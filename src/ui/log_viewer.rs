@@ -0,0 +1,209 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::log_index::LogIndex;
+use crate::ui::{format_frame, format_timestamp};
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::collections::VecDeque;
+use std::sync::{
+    atomic::{AtomicBool, AtomicU64, Ordering},
+    Arc, Mutex,
+};
+use std::thread;
+
+/// 最多快取的區塊數
+const CACHE_BLOCKS: usize = 16;
+
+/// 背景建立中的索引
+struct Indexing {
+    total_bytes: u64,
+    progress: Arc<AtomicU64>,
+    cancel: Arc<AtomicBool>,
+    result: Arc<Mutex<Option<Result<LogIndex, String>>>>,
+}
+
+/// 離線紀錄檔檢視器：以索引隨機存取大型 candump 紀錄檔，只讀取畫面上需要的區塊
+#[derive(Default)]
+pub struct LogViewer {
+    indexing: Option<Indexing>,
+    index: Option<LogIndex>,
+    /// 只顯示此 (通道, ID)
+    filter: Option<(u32, u32)>,
+    cache: VecDeque<(u32, Vec<CanFrame>)>,
+}
+
+impl LogViewer {
+    fn open(&mut self, path: std::path::PathBuf) {
+        let total_bytes = std::fs::metadata(&path).map_or(0, |m| m.len());
+        let indexing = Indexing {
+            total_bytes,
+            progress: Arc::new(AtomicU64::new(0)),
+            cancel: Arc::new(AtomicBool::new(false)),
+            result: Arc::new(Mutex::new(None)),
+        };
+        let progress = Arc::clone(&indexing.progress);
+        let cancel = Arc::clone(&indexing.cancel);
+        let result = Arc::clone(&indexing.result);
+        thread::spawn(move || {
+            let index = LogIndex::build(&path, &progress, &cancel);
+            *result.lock().unwrap() = Some(index);
+        });
+        if let Some(previous) = self.indexing.replace(indexing) {
+            previous.cancel.store(true, Ordering::Relaxed);
+        }
+    }
+
+    /// 取得區塊內容，必要時從檔案讀取並放入快取
+    fn block(&mut self, block: u32) -> Option<&[CanFrame]> {
+        let index = self.index.as_ref()?;
+        let position = match self.cache.iter().position(|(b, _)| *b == block) {
+            Some(position) => position,
+            None => match index.read_block(block) {
+                Ok(frames) => {
+                    if self.cache.len() >= CACHE_BLOCKS {
+                        self.cache.pop_front();
+                    }
+                    self.cache.push_back((block, frames));
+                    self.cache.len() - 1
+                }
+                Err(e) => {
+                    tracing::error!(target: "offline", "{}", e);
+                    return None;
+                }
+            },
+        };
+        Some(&self.cache[position].1)
+    }
+
+    fn frame_at(&mut self, row: u64) -> Option<CanFrame> {
+        let (block, offset) = self.index.as_ref()?.locate(row, self.filter)?;
+        let filter = self.filter;
+        let frames = self.block(block)?;
+        match filter {
+            None => frames.get(offset).copied(),
+            Some((channel, id)) => frames
+                .iter()
+                .filter(|f| f.channel == channel && f.id == id)
+                .nth(offset)
+                .copied(),
+        }
+    }
+
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            if ui.button("Open Log...").clicked() {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("candump log", &["log", "txt"])
+                    .pick_file()
+                {
+                    self.open(path);
+                }
+            }
+            if let Some(index) = &self.index {
+                ui.label(format!(
+                    "{} ({} frames)",
+                    index.path().display(),
+                    index.frame_count()
+                ));
+            }
+        });
+
+        if let Some(indexing) = &self.indexing {
+            let finished = indexing.result.lock().unwrap().take();
+            match finished {
+                Some(Ok(index)) => {
+                    tracing::info!(
+                        target: "offline",
+                        frames = index.frame_count(),
+                        path = %index.path().display(),
+                        "Indexed log file"
+                    );
+                    self.index = Some(index);
+                    self.filter = None;
+                    self.cache.clear();
+                    self.indexing = None;
+                }
+                Some(Err(e)) => {
+                    tracing::error!(target: "offline", "{}", e);
+                    self.indexing = None;
+                }
+                None => {
+                    let read = indexing.progress.load(Ordering::Relaxed);
+                    let fraction = read as f32 / indexing.total_bytes.max(1) as f32;
+                    ui.horizontal(|ui| {
+                        ui.add(
+                            egui::ProgressBar::new(fraction)
+                                .text(format!("Indexing {:.0}%", fraction * 100.0)),
+                        );
+                        if ui.button("Cancel").clicked() {
+                            indexing.cancel.store(true, Ordering::Relaxed);
+                        }
+                    });
+                    ui.ctx().request_repaint();
+                }
+            }
+        }
+
+        let Some(index) = &self.index else {
+            ui.label("Open a candump log (e.g. `candump -l` output) to browse it.");
+            return;
+        };
+        if let Some((first, last)) = index.time_range() {
+            ui.label(format!(
+                "{} .. {}",
+                format_timestamp(first),
+                format_timestamp(last)
+            ));
+        }
+        if !index.session().is_empty() {
+            ui.collapsing("Session", |ui| {
+                egui::Grid::new("offline_session")
+                    .num_columns(2)
+                    .show(ui, |ui| {
+                        for (name, value) in index.session().fields() {
+                            ui.strong(name);
+                            ui.label(value);
+                            ui.end_row();
+                        }
+                    });
+            });
+        }
+        let mut filter = self.filter;
+        egui::ComboBox::from_label("ID filter")
+            .selected_text(match filter {
+                None => "All".to_string(),
+                Some((channel, id)) => format!("CH{} 0x{:X}", channel, id),
+            })
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut filter, None, "All");
+                for (&(channel, id), entry) in index.ids() {
+                    ui.selectable_value(
+                        &mut filter,
+                        Some((channel, id)),
+                        format!("CH{} 0x{:X} ({})", channel, id, entry.count),
+                    );
+                }
+            });
+        self.filter = filter;
+
+        let rows = index.row_count(self.filter) as usize;
+        ui.separator();
+        let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+        egui::ScrollArea::vertical()
+            .auto_shrink([false, false])
+            .show_rows(ui, row_height, rows, |ui, range| {
+                for row in range {
+                    let text = match self.frame_at(row as u64) {
+                        Some(frame) => format!(
+                            "{:>10}  {}  {}",
+                            row,
+                            format_timestamp(frame.timestamp),
+                            format_frame(&frame)
+                        ),
+                        None => format!("{:>10}  <unreadable>", row),
+                    };
+                    ui.monospace(text);
+                }
+            });
+    }
+}
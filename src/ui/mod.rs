@@ -0,0 +1,166 @@
+pub mod alarm_panel;
+pub mod autoresponse_panel;
+pub mod bit_timing_calc;
+pub mod blackbox_panel;
+pub mod broadcast_panel;
+pub mod bus_stats_panel;
+pub mod canopen_panel;
+pub mod capture_db_panel;
+pub mod channel_list;
+pub mod channel_stats_panel;
+pub mod chart;
+pub mod conformance_panel;
+pub mod dashboard;
+pub mod dbc_panel;
+pub mod events_panel;
+pub mod filter_box;
+pub mod gateway_panel;
+pub mod id_filter_panel;
+pub mod id_stats_panel;
+pub mod latency_panel;
+pub mod layout;
+pub mod log_viewer;
+pub mod obd_panel;
+pub mod plot_export;
+pub mod plot_panel;
+pub mod remote_panel;
+pub mod replay_panel;
+pub mod report;
+pub mod report_panel;
+pub mod sampler_panel;
+pub mod scheduler_panel;
+pub mod search_bar;
+pub mod selftest_panel;
+pub mod sim_panel;
+pub mod timeline;
+pub mod trace_view;
+pub mod trigger_panel;
+pub mod tx_panel;
+pub mod tx_sequence_panel;
+pub mod watch_panel;
+
+use crate::can::cantypes::{CanFrame, FrameDirection, FrameProtocol};
+use chrono::{Local, TimeZone};
+
+/// 解析十六進位數值，可帶或不帶 "0x" 前綴
+pub fn parse_hex_u32(text: &str) -> Result<u32, String> {
+    let text = text.trim();
+    let digits = text
+        .strip_prefix("0x")
+        .or_else(|| text.strip_prefix("0X"))
+        .unwrap_or(text);
+    u32::from_str_radix(digits, 16).map_err(|e| format!("Invalid hex value '{}': {}", text, e))
+}
+
+/// 解析以空白分隔的十六進位位元組，例如 "01 A2 ff"
+pub fn parse_hex_bytes(text: &str) -> Result<Vec<u8>, String> {
+    text.split_whitespace()
+        .map(|byte| {
+            u8::from_str_radix(byte, 16).map_err(|e| format!("Invalid data byte '{}': {}", byte, e))
+        })
+        .collect()
+}
+
+/// 將訊框格式化為 Data 面板顯示的文字
+pub fn format_frame(frame: &CanFrame) -> String {
+    let marker = match frame.protocol {
+        FrameProtocol::Classic => "",
+        FrameProtocol::Fd if frame.brs => " FD+BRS",
+        FrameProtocol::Fd => " FD",
+        FrameProtocol::Xl => " XL",
+    };
+    let tag = match frame.direction {
+        FrameDirection::Rx => "DATA",
+        FrameDirection::Tx => "TX",
+    };
+    let ext = if frame.extended { " EXT" } else { "" };
+    if frame.rtr {
+        return format!(
+            "[{}] CH={} ID=0x{:X}{} RTR, DLC={}",
+            tag,
+            frame.channel,
+            frame.id,
+            ext,
+            frame.dlc()
+        );
+    }
+    format!(
+        "[{}] CH={} ID=0x{:X}{}{}, Data={:?}",
+        tag,
+        frame.channel,
+        frame.id,
+        ext,
+        marker,
+        frame.payload()
+    )
+}
+
+/// 將微秒時間戳記格式化為本地時間，例如 "2025-03-01 14:02:03.123"
+pub fn format_timestamp(timestamp_us: u64) -> String {
+    match Local.timestamp_micros(timestamp_us as i64) {
+        chrono::LocalResult::Single(time) => time.format("%Y-%m-%d %H:%M:%S%.3f").to_string(),
+        _ => timestamp_us.to_string(),
+    }
+}
+
+/// 追蹤畫面顯示 ID 與資料位元組的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueFormat {
+    #[default]
+    Hex,
+    Decimal,
+    Binary,
+    /// 可列印字元直接顯示，其餘以 `.` 表示；只用於資料
+    Ascii,
+}
+
+impl ValueFormat {
+    pub const ALL: [ValueFormat; 4] = [
+        ValueFormat::Hex,
+        ValueFormat::Decimal,
+        ValueFormat::Binary,
+        ValueFormat::Ascii,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ValueFormat::Hex => "Hex",
+            ValueFormat::Decimal => "Dec",
+            ValueFormat::Binary => "Bin",
+            ValueFormat::Ascii => "ASCII",
+        }
+    }
+
+    pub fn format_byte(&self, byte: u8) -> String {
+        match self {
+            ValueFormat::Hex => format!("{:02X}", byte),
+            ValueFormat::Decimal => byte.to_string(),
+            ValueFormat::Binary => format!("{:08b}", byte),
+            ValueFormat::Ascii if byte.is_ascii_graphic() || byte == b' ' => {
+                (byte as char).to_string()
+            }
+            ValueFormat::Ascii => ".".to_string(),
+        }
+    }
+
+    /// 資料位元組；ASCII 不加分隔，其他格式以空白分隔
+    pub fn format_bytes(&self, bytes: &[u8]) -> String {
+        let separator = if *self == ValueFormat::Ascii { "" } else { " " };
+        bytes
+            .iter()
+            .map(|&b| self.format_byte(b))
+            .collect::<Vec<_>>()
+            .join(separator)
+    }
+
+    /// 訊框 ID；ASCII 對 ID 沒有意義，以十六進位顯示
+    pub fn format_id(&self, id: u32, extended: bool) -> String {
+        match (self, extended) {
+            (ValueFormat::Decimal, _) => id.to_string(),
+            (ValueFormat::Binary, false) => format!("{:011b}", id),
+            (ValueFormat::Binary, true) => format!("{:029b}", id),
+            (_, false) => format!("{:03X}h", id),
+            (_, true) => format!("{:08X}h", id),
+        }
+    }
+}
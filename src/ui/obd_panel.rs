@@ -0,0 +1,94 @@
+use crate::can::canbus::SharedCan;
+use crate::can::obd::{ObdMonitor, ObdPoller, PIDS};
+
+use eframe::egui;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// OBD-II 即時數據面板：勾選 PID 後輪流以 0x7DF 請求，顯示 0x7E8..0x7EF 的回應
+pub struct ObdPanel {
+    channel: u32,
+    /// 相鄰兩個請求的間隔
+    interval_ms: u64,
+    poller: Option<ObdPoller>,
+}
+
+impl Default for ObdPanel {
+    fn default() -> Self {
+        Self {
+            channel: 0,
+            interval_ms: 50,
+            poller: None,
+        }
+    }
+}
+
+impl ObdPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        monitor: &Arc<Mutex<ObdMonitor>>,
+        can_app: &SharedCan,
+    ) {
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(self.poller.is_none(), |ui| {
+                ui.label("Channel:");
+                ui.add(egui::DragValue::new(&mut self.channel).range(0..=15));
+                ui.label("Interval:");
+                ui.add(
+                    egui::DragValue::new(&mut self.interval_ms)
+                        .range(10..=5000)
+                        .suffix(" ms"),
+                );
+            });
+            if self.poller.is_none() {
+                if ui.button("Start Polling").clicked() {
+                    self.poller = Some(ObdPoller::start(
+                        Arc::clone(monitor),
+                        can_app.clone(),
+                        self.channel,
+                        Duration::from_millis(self.interval_ms),
+                        |msg| tracing::info!(target: "obd", "{}", msg),
+                    ));
+                }
+            } else if ui.button("Stop Polling").clicked() {
+                self.poller = None;
+            }
+            if ui.button("Clear").clicked() {
+                monitor.lock().unwrap().clear();
+            }
+        });
+        let mut monitor = monitor.lock().unwrap();
+        ui.weak(format!("{} requests sent", monitor.requests()));
+        ui.separator();
+        egui::Grid::new("obd_pids")
+            .striped(true)
+            .num_columns(5)
+            .show(ui, |ui| {
+                for header in ["Poll", "PID", "Value", "Unit", "ECU"] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for def in PIDS {
+                    let mut selected = monitor.is_selected(def.pid);
+                    if ui.checkbox(&mut selected, "").changed() {
+                        monitor.set_selected(def.pid, selected);
+                    }
+                    ui.label(format!("{:02X} {}", def.pid, def.name));
+                    match monitor.value(def.pid) {
+                        Some(value) => {
+                            ui.monospace(format!("{:.2}", value.value));
+                            ui.label(def.unit);
+                            ui.label(format!("0x{:X}", 0x7E8 + value.ecu as u32));
+                        }
+                        None => {
+                            ui.weak("-");
+                            ui.label(def.unit);
+                            ui.label("");
+                        }
+                    }
+                    ui.end_row();
+                }
+            });
+    }
+}
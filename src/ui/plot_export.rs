@@ -0,0 +1,431 @@
+use ab_glyph::{Font, FontRef, PxScale, ScaleFont};
+use std::fmt::Write as _;
+use std::fs;
+
+/// 匯出圖片的預設尺寸
+pub const DEFAULT_WIDTH: u32 = 960;
+pub const DEFAULT_HEIGHT: u32 = 540;
+
+const MARGIN_LEFT: f32 = 70.0;
+const MARGIN_RIGHT: f32 = 20.0;
+const MARGIN_TOP: f32 = 40.0;
+const MARGIN_BOTTOM: f32 = 50.0;
+const FONT_SIZE: f32 = 13.0;
+const TITLE_SIZE: f32 = 16.0;
+
+const BLACK: [u8; 3] = [0, 0, 0];
+const GRID: [u8; 3] = [220, 220, 220];
+const AXIS: [u8; 3] = [80, 80, 80];
+const SHADE: [u8; 3] = [238, 238, 238];
+/// 數列依序使用的顏色
+const PALETTE: [[u8; 3]; 6] = [
+    [31, 119, 180],
+    [255, 127, 14],
+    [44, 160, 44],
+    [214, 39, 40],
+    [148, 103, 189],
+    [140, 86, 75],
+];
+
+/// 圖中的一條數列，`points` 為 (x, y)
+#[derive(Debug, Clone, Default)]
+pub struct PlotSeries {
+    pub name: String,
+    pub points: Vec<(f64, f64)>,
+}
+
+/// 要輸出成圖片的圖表內容
+#[derive(Debug, Clone, Default)]
+pub struct PlotImage {
+    pub title: String,
+    pub x_label: String,
+    pub y_label: String,
+    pub series: Vec<PlotSeries>,
+    /// 以淺灰底標示的 x 區間，例如匯流排靜默期間
+    pub shaded: Vec<(f64, f64)>,
+}
+
+impl PlotImage {
+    /// 單一數列的歷史值圖表，x 軸為樣本序號；有單位時加在 y 軸標籤
+    pub fn from_history(title: &str, unit: &str, values: impl IntoIterator<Item = f64>) -> Self {
+        Self {
+            title: title.to_string(),
+            x_label: "Sample".to_string(),
+            y_label: if unit.is_empty() {
+                title.to_string()
+            } else {
+                format!("{} [{}]", title, unit)
+            },
+            series: vec![PlotSeries {
+                name: title.to_string(),
+                points: values
+                    .into_iter()
+                    .enumerate()
+                    .map(|(i, v)| (i as f64, v))
+                    .collect(),
+            }],
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Anchor {
+    Start,
+    Middle,
+    End,
+}
+
+/// SVG 與點陣圖共用的繪圖介面；`y` 為文字基線
+trait Canvas {
+    fn line(&mut self, points: &[(f32, f32)], color: [u8; 3], width: f32);
+    fn text(&mut self, x: f32, y: f32, text: &str, size: f32, anchor: Anchor, color: [u8; 3]);
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: [u8; 3]);
+}
+
+/// 以 1/2/5 為級距計算刻度
+fn nice_ticks(min: f64, max: f64, target: usize) -> Vec<f64> {
+    let span = max - min;
+    if span <= 0.0 || !span.is_finite() {
+        return vec![min];
+    }
+    let raw = span / target.max(1) as f64;
+    let magnitude = 10f64.powf(raw.log10().floor());
+    let step = [1.0, 2.0, 5.0, 10.0]
+        .iter()
+        .map(|m| m * magnitude)
+        .find(|s| *s >= raw)
+        .unwrap_or(10.0 * magnitude);
+    let mut ticks = Vec::new();
+    let mut value = (min / step).ceil() * step;
+    while value <= max + step * 1e-9 {
+        ticks.push(if value.abs() < step * 1e-9 {
+            0.0
+        } else {
+            value
+        });
+        value += step;
+    }
+    ticks
+}
+
+fn format_tick(value: f64) -> String {
+    if value.abs() >= 1e5 || (value != 0.0 && value.abs() < 1e-3) {
+        format!("{:.2e}", value)
+    } else {
+        let text = format!("{:.3}", value);
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    }
+}
+
+/// 所有數列的範圍，無資料時回傳 0..1
+fn bounds(plot: &PlotImage) -> ((f64, f64), (f64, f64)) {
+    let mut x = (f64::INFINITY, f64::NEG_INFINITY);
+    let mut y = (f64::INFINITY, f64::NEG_INFINITY);
+    for &(px, py) in plot.series.iter().flat_map(|s| &s.points) {
+        if px.is_finite() && py.is_finite() {
+            x = (x.0.min(px), x.1.max(px));
+            y = (y.0.min(py), y.1.max(py));
+        }
+    }
+    let widen = |(lo, hi): (f64, f64)| {
+        if !lo.is_finite() {
+            (0.0, 1.0)
+        } else if (hi - lo).abs() < f64::EPSILON {
+            (lo - 0.5, hi + 0.5)
+        } else {
+            (lo, hi)
+        }
+    };
+    (widen(x), widen(y))
+}
+
+/// 依版面繪製座標軸、格線、數列與圖例
+fn draw(canvas: &mut dyn Canvas, plot: &PlotImage, width: f32, height: f32) {
+    let (left, right) = (MARGIN_LEFT, width - MARGIN_RIGHT);
+    let (top, bottom) = (MARGIN_TOP, height - MARGIN_BOTTOM);
+    let ((x_min, x_max), (y_min, y_max)) = bounds(plot);
+    let to_x = |x: f64| left + ((x - x_min) / (x_max - x_min)) as f32 * (right - left);
+    let to_y = |y: f64| bottom - ((y - y_min) / (y_max - y_min)) as f32 * (bottom - top);
+
+    for &(start, end) in &plot.shaded {
+        let (x0, x1) = (to_x(start.max(x_min)), to_x(end.min(x_max)));
+        if x1 > x0 {
+            canvas.rect(x0, top, x1 - x0, bottom - top, SHADE);
+        }
+    }
+    for tick in nice_ticks(x_min, x_max, 8) {
+        let x = to_x(tick);
+        canvas.line(&[(x, top), (x, bottom)], GRID, 1.0);
+        let label = format_tick(tick);
+        canvas.text(x, bottom + 16.0, &label, FONT_SIZE, Anchor::Middle, AXIS);
+    }
+    for tick in nice_ticks(y_min, y_max, 6) {
+        let y = to_y(tick);
+        canvas.line(&[(left, y), (right, y)], GRID, 1.0);
+        let label = format_tick(tick);
+        canvas.text(left - 6.0, y + 4.0, &label, FONT_SIZE, Anchor::End, AXIS);
+    }
+    canvas.line(&[(left, top), (left, bottom), (right, bottom)], AXIS, 1.0);
+
+    canvas.text(
+        width / 2.0,
+        24.0,
+        &plot.title,
+        TITLE_SIZE,
+        Anchor::Middle,
+        BLACK,
+    );
+    let x_label_y = height - 12.0;
+    canvas.text(
+        (left + right) / 2.0,
+        x_label_y,
+        &plot.x_label,
+        FONT_SIZE,
+        Anchor::Middle,
+        BLACK,
+    );
+    canvas.text(
+        8.0,
+        top - 10.0,
+        &plot.y_label,
+        FONT_SIZE,
+        Anchor::Start,
+        BLACK,
+    );
+
+    for (i, series) in plot.series.iter().enumerate() {
+        let color = PALETTE[i % PALETTE.len()];
+        let points: Vec<(f32, f32)> = series
+            .points
+            .iter()
+            .filter(|(x, y)| x.is_finite() && y.is_finite())
+            .map(|&(x, y)| (to_x(x), to_y(y)))
+            .collect();
+        canvas.line(&points, color, 1.5);
+
+        // 圖例放在繪圖區右上角
+        let y = top + 16.0 + i as f32 * 18.0;
+        canvas.line(
+            &[(right - 150.0, y - 4.0), (right - 130.0, y - 4.0)],
+            color,
+            2.0,
+        );
+        canvas.text(
+            right - 124.0,
+            y,
+            &series.name,
+            FONT_SIZE,
+            Anchor::Start,
+            BLACK,
+        );
+    }
+}
+
+struct SvgCanvas {
+    body: String,
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+impl Canvas for SvgCanvas {
+    fn line(&mut self, points: &[(f32, f32)], [r, g, b]: [u8; 3], width: f32) {
+        if points.len() < 2 {
+            return;
+        }
+        let coords: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{:.1},{:.1}", x, y))
+            .collect();
+        let _ = writeln!(
+            self.body,
+            r#"<polyline points="{}" fill="none" stroke="rgb({},{},{})" stroke-width="{}"/>"#,
+            coords.join(" "),
+            r,
+            g,
+            b,
+            width
+        );
+    }
+
+    fn text(&mut self, x: f32, y: f32, text: &str, size: f32, anchor: Anchor, [r, g, b]: [u8; 3]) {
+        let anchor = match anchor {
+            Anchor::Start => "start",
+            Anchor::Middle => "middle",
+            Anchor::End => "end",
+        };
+        let _ = writeln!(
+            self.body,
+            r#"<text x="{:.1}" y="{:.1}" font-size="{}" text-anchor="{}" fill="rgb({},{},{})">{}</text>"#,
+            x,
+            y,
+            size,
+            anchor,
+            r,
+            g,
+            b,
+            escape(text)
+        );
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, [r, g, b]: [u8; 3]) {
+        let _ = writeln!(
+            self.body,
+            r#"<rect x="{:.1}" y="{:.1}" width="{:.1}" height="{:.1}" fill="rgb({},{},{})"/>"#,
+            x, y, width, height, r, g, b
+        );
+    }
+}
+
+/// 輸出 SVG 文件
+pub fn render_svg(plot: &PlotImage, width: u32, height: u32) -> String {
+    let mut canvas = SvgCanvas {
+        body: String::new(),
+    };
+    draw(&mut canvas, plot, width as f32, height as f32);
+    format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" "#,
+            r#"viewBox="0 0 {w} {h}" font-family="sans-serif">"#,
+            "\n",
+            r#"<rect width="100%" height="100%" fill="white"/>"#,
+            "\n{body}</svg>\n"
+        ),
+        w = width,
+        h = height,
+        body = canvas.body
+    )
+}
+
+struct PixmapCanvas {
+    pixmap: tiny_skia::Pixmap,
+    font: FontRef<'static>,
+}
+
+impl PixmapCanvas {
+    fn text_width(&self, text: &str, size: f32) -> f32 {
+        let font = self.font.as_scaled(PxScale::from(size));
+        let mut width = 0.0;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(prev) = previous {
+                width += font.kern(prev, id);
+            }
+            width += font.h_advance(id);
+            previous = Some(id);
+        }
+        width
+    }
+}
+
+impl Canvas for PixmapCanvas {
+    fn line(&mut self, points: &[(f32, f32)], [r, g, b]: [u8; 3], width: f32) {
+        let mut builder = tiny_skia::PathBuilder::new();
+        let mut iter = points.iter();
+        let Some(&(x, y)) = iter.next() else {
+            return;
+        };
+        builder.move_to(x, y);
+        for &(x, y) in iter {
+            builder.line_to(x, y);
+        }
+        let Some(path) = builder.finish() else {
+            return;
+        };
+        let mut paint = tiny_skia::Paint::default();
+        paint.set_color_rgba8(r, g, b, 255);
+        paint.anti_alias = true;
+        let stroke = tiny_skia::Stroke {
+            width,
+            ..Default::default()
+        };
+        self.pixmap.stroke_path(
+            &path,
+            &paint,
+            &stroke,
+            tiny_skia::Transform::identity(),
+            None,
+        );
+    }
+
+    fn text(&mut self, x: f32, y: f32, text: &str, size: f32, anchor: Anchor, color: [u8; 3]) {
+        let start = match anchor {
+            Anchor::Start => x,
+            Anchor::Middle => x - self.text_width(text, size) / 2.0,
+            Anchor::End => x - self.text_width(text, size),
+        };
+        let font = self.font.as_scaled(PxScale::from(size));
+        let (width, height) = (self.pixmap.width() as i32, self.pixmap.height() as i32);
+        let data = self.pixmap.data_mut();
+        let mut caret = start;
+        let mut previous = None;
+        for c in text.chars() {
+            let id = font.glyph_id(c);
+            if let Some(prev) = previous {
+                caret += font.kern(prev, id);
+            }
+            let glyph = id.with_scale_and_position(size, ab_glyph::point(caret, y));
+            caret += font.h_advance(id);
+            previous = Some(id);
+            let Some(outline) = font.outline_glyph(glyph) else {
+                continue;
+            };
+            let bounds = outline.px_bounds();
+            outline.draw(|gx, gy, coverage| {
+                let px = bounds.min.x as i32 + gx as i32;
+                let py = bounds.min.y as i32 + gy as i32;
+                if px < 0 || py < 0 || px >= width || py >= height {
+                    return;
+                }
+                // 背景不透明，直接依覆蓋率混色
+                let i = (py * width + px) as usize * 4;
+                let alpha = coverage.clamp(0.0, 1.0);
+                for (channel, &value) in color.iter().enumerate() {
+                    let dst = data[i + channel] as f32;
+                    data[i + channel] = (dst + (value as f32 - dst) * alpha).round() as u8;
+                }
+            });
+        }
+    }
+
+    fn rect(&mut self, x: f32, y: f32, width: f32, height: f32, [r, g, b]: [u8; 3]) {
+        let Some(rect) = tiny_skia::Rect::from_xywh(x, y, width, height) else {
+            return;
+        };
+        let mut paint = tiny_skia::Paint::default();
+        paint.set_color_rgba8(r, g, b, 255);
+        self.pixmap
+            .fill_rect(rect, &paint, tiny_skia::Transform::identity(), None);
+    }
+}
+
+/// 輸出 PNG 圖片
+pub fn render_png(plot: &PlotImage, width: u32, height: u32) -> Result<Vec<u8>, String> {
+    let mut pixmap = tiny_skia::Pixmap::new(width, height)
+        .ok_or_else(|| format!("Invalid image size {}x{}", width, height))?;
+    pixmap.fill(tiny_skia::Color::WHITE);
+    let font = FontRef::try_from_slice(epaint_default_fonts::UBUNTU_LIGHT)
+        .map_err(|e| format!("Failed to load font: {}", e))?;
+    let mut canvas = PixmapCanvas { pixmap, font };
+    draw(&mut canvas, plot, width as f32, height as f32);
+    canvas
+        .pixmap
+        .encode_png()
+        .map_err(|e| format!("Failed to encode PNG: {}", e))
+}
+
+/// 依副檔名輸出圖片：`.svg` 為 SVG，其他為 PNG
+pub fn save_plot(file_path: &str, plot: &PlotImage) -> Result<(), String> {
+    let bytes = if file_path.to_ascii_lowercase().ends_with(".svg") {
+        render_svg(plot, DEFAULT_WIDTH, DEFAULT_HEIGHT).into_bytes()
+    } else {
+        render_png(plot, DEFAULT_WIDTH, DEFAULT_HEIGHT)?
+    };
+    fs::write(file_path, bytes).map_err(|e| format!("Failed to write {}: {}", file_path, e))
+}
@@ -0,0 +1,245 @@
+use crate::can::cantypes::now_micros;
+use crate::can::dbc::DbcDecoder;
+use crate::can::plot::{PlotTrace, SignalPlot};
+use crate::can::signals::SignalTable;
+use crate::can::units::DisplayUnits;
+
+use eframe::egui;
+use egui_plot::{Line, Plot, PlotBounds, PlotPoints};
+use std::sync::Mutex;
+
+const PLOT_HEIGHT: f32 = 260.0;
+/// 曲線依序使用的顏色
+const PALETTE: [egui::Color32; 6] = [
+    egui::Color32::from_rgb(31, 119, 180),
+    egui::Color32::from_rgb(255, 127, 14),
+    egui::Color32::from_rgb(44, 160, 44),
+    egui::Color32::from_rgb(214, 39, 40),
+    egui::Color32::from_rgb(148, 103, 189),
+    egui::Color32::from_rgb(140, 86, 75),
+];
+
+/// 畫面中的一條曲線，時間為相對於右端的秒數，數值已換算為顯示單位
+struct VisibleTrace {
+    key: String,
+    unit: String,
+    points: Vec<[f64; 2]>,
+}
+
+/// 即時訊號曲線圖：可加入多個 YAML 或 DBC 訊號，暫停時凍結畫面但資料持續記錄；
+/// 即時顯示時範圍跟隨最新資料，暫停後可拖曳、縮放檢視
+pub struct PlotPanel {
+    /// 顯示的時間長度（秒）
+    span_s: f64,
+    autoscale: bool,
+    y_min: f64,
+    y_max: f64,
+    /// 暫停時凍結的右端時間與曲線
+    frozen: Option<(u64, Vec<PlotTrace>)>,
+    /// 下一次繪製時套用 span 與 Y 範圍，暫停後只在剛暫停或按下 Reset view 時設定
+    reset_view: bool,
+    selected: String,
+}
+
+impl Default for PlotPanel {
+    fn default() -> Self {
+        Self {
+            span_s: 30.0,
+            autoscale: true,
+            y_min: 0.0,
+            y_max: 100.0,
+            frozen: None,
+            reset_view: true,
+            selected: String::new(),
+        }
+    }
+}
+
+impl PlotPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        plot: &Mutex<SignalPlot>,
+        signals: &Mutex<SignalTable>,
+        dbc: &Mutex<DbcDecoder>,
+        units: &Mutex<DisplayUnits>,
+    ) {
+        ui.horizontal(|ui| {
+            // 同一個 key 可能對應多筆 canbus_config 設定
+            let mut available: Vec<String> = Vec::new();
+            for entry in signals.lock().unwrap().entries() {
+                if !available.contains(&entry.key) {
+                    available.push(entry.key.clone());
+                }
+            }
+            for message in &dbc.lock().unwrap().dbc().messages {
+                for signal in &message.signals {
+                    available.push(format!("{}.{}", message.name, signal.name));
+                }
+            }
+            egui::ComboBox::from_id_salt("plot_signal")
+                .width(220.0)
+                .selected_text(if self.selected.is_empty() {
+                    "Select signal"
+                } else {
+                    &self.selected
+                })
+                .show_ui(ui, |ui| {
+                    for key in available {
+                        ui.selectable_value(&mut self.selected, key.clone(), key);
+                    }
+                });
+            if ui
+                .add_enabled(!self.selected.is_empty(), egui::Button::new("Add"))
+                .clicked()
+            {
+                plot.lock().unwrap().add(&self.selected);
+            }
+            let mut paused = self.frozen.is_some();
+            if ui.toggle_value(&mut paused, "Pause").changed() {
+                self.frozen =
+                    paused.then(|| (now_micros(), plot.lock().unwrap().traces().to_vec()));
+                self.reset_view = true;
+            }
+            if paused && ui.button("Reset view").clicked() {
+                self.reset_view = true;
+            }
+            if ui.button("Clear").clicked() {
+                plot.lock().unwrap().clear();
+                self.frozen = None;
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.label("Span:");
+            ui.add(
+                egui::DragValue::new(&mut self.span_s)
+                    .range(1.0..=3600.0)
+                    .suffix(" s"),
+            );
+            ui.checkbox(&mut self.autoscale, "Autoscale");
+            ui.add_enabled_ui(!self.autoscale, |ui| {
+                ui.label("Y:");
+                ui.add(egui::DragValue::new(&mut self.y_min).speed(0.1));
+                ui.label("to");
+                ui.add(egui::DragValue::new(&mut self.y_max).speed(0.1));
+            });
+        });
+
+        let traces = {
+            let plot = plot.lock().unwrap();
+            let (end, traces) = match self.frozen {
+                Some((end, ref traces)) => (end, traces.as_slice()),
+                None => (now_micros(), plot.traces()),
+            };
+            self.visible(end, traces, &dbc.lock().unwrap(), &units.lock().unwrap())
+        };
+        if self.autoscale {
+            let values = traces.iter().flat_map(|t| t.points.iter().map(|&[_, y]| y));
+            let (min, max) = values.fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), y| {
+                (min.min(y), max.max(y))
+            });
+            if min.is_finite() {
+                let margin = ((max - min) * 0.05).max(0.5);
+                self.y_min = min - margin;
+                self.y_max = max + margin;
+            }
+        }
+        let live = self.frozen.is_none();
+        let bounds = PlotBounds::from_min_max([-self.span_s, self.y_min], [0.0, self.y_max]);
+        let reset_view = std::mem::take(&mut self.reset_view) || live;
+        let cursor = Plot::new("signal_plot")
+            .height(PLOT_HEIGHT)
+            .x_axis_label("Time [s]")
+            .allow_drag(!live)
+            .allow_zoom(!live)
+            .allow_scroll(!live)
+            .allow_boxed_zoom(!live)
+            .show(ui, |plot_ui| {
+                if reset_view {
+                    plot_ui.set_plot_bounds(bounds);
+                }
+                for (i, trace) in traces.iter().enumerate() {
+                    plot_ui.line(
+                        Line::new(PlotPoints::from(trace.points.clone()))
+                            .color(PALETTE[i % PALETTE.len()])
+                            .name(&trace.key),
+                    );
+                }
+                plot_ui.pointer_coordinate().map(|point| point.x)
+            })
+            .inner;
+
+        // 圖例：游標在圖上時顯示該時間的值，否則顯示最新值
+        let mut remove = None;
+        for (i, trace) in traces.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.colored_label(PALETTE[i % PALETTE.len()], "■");
+                let value = match cursor {
+                    Some(x) => trace
+                        .points
+                        .iter()
+                        .rev()
+                        .find(|&&[t, _]| t <= x)
+                        .map(|&[_, y]| y),
+                    None => trace.points.last().map(|&[_, y]| y),
+                };
+                ui.label(match value {
+                    Some(value) => format!("{}: {:.3} {}", trace.key, value, trace.unit),
+                    None => format!("{}: -", trace.key),
+                });
+                if ui.small_button("✖").on_hover_text("Remove").clicked() {
+                    remove = Some(trace.key.clone());
+                }
+            });
+        }
+        if let Some(key) = remove {
+            plot.lock().unwrap().remove(&key);
+            if let Some((_, ref mut traces)) = self.frozen {
+                traces.retain(|t| t.key != key);
+            }
+        }
+        if traces.is_empty() {
+            ui.weak("Add YAML signals or DBC Message.Signal to plot their values over time");
+        }
+    }
+
+    /// 取出時間範圍內的樣本，換算為相對時間與顯示單位
+    fn visible(
+        &self,
+        end: u64,
+        traces: &[PlotTrace],
+        dbc: &DbcDecoder,
+        units: &DisplayUnits,
+    ) -> Vec<VisibleTrace> {
+        let start = end.saturating_sub((self.span_s * 1_000_000.0) as u64);
+        traces
+            .iter()
+            .map(|trace| {
+                let first = trace.samples.partition_point(|&(t, _)| t < start);
+                let points = trace
+                    .samples
+                    .range(first..)
+                    .take_while(|&&(t, _)| t <= end)
+                    .map(|&(t, value)| {
+                        let x = (t as f64 - end as f64) / 1_000_000.0;
+                        [x, units.convert(&trace.key, value)]
+                    })
+                    .collect();
+                let unit = match units.unit(&trace.key) {
+                    "" => trace
+                        .key
+                        .split_once('.')
+                        .and_then(|(message, signal)| dbc.value(message, signal))
+                        .map(|d| d.unit.clone())
+                        .unwrap_or_default(),
+                    unit => unit.to_string(),
+                };
+                VisibleTrace {
+                    key: trace.key.clone(),
+                    unit,
+                    points,
+                }
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,75 @@
+use crate::can::canbus::SharedCan;
+use crate::can::remote::{RemoteServer, DEFAULT_BIND_ADDR};
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// 遠端伺服器設定面板，讓其他實例透過網路使用本機的介面卡
+pub struct RemoteServerPanel {
+    bind_address: String,
+    /// 允許用戶端送出訊框，需搭配 token
+    allow_transmit: bool,
+    token: String,
+}
+
+impl Default for RemoteServerPanel {
+    fn default() -> Self {
+        Self {
+            bind_address: DEFAULT_BIND_ADDR.to_string(),
+            allow_transmit: false,
+            token: String::new(),
+        }
+    }
+}
+
+impl RemoteServerPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        server: &Mutex<Option<RemoteServer>>,
+        can_app: &SharedCan,
+    ) {
+        let mut server = server.lock().unwrap();
+        match server.as_ref() {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.label("Listen on:");
+                    ui.text_edit_singleline(&mut self.bind_address);
+                })
+                .response
+                .on_hover_text("Use 0.0.0.0 to accept connections from other machines");
+                ui.horizontal(|ui| {
+                    ui.checkbox(&mut self.allow_transmit, "Allow remote transmit")
+                        .on_hover_text("Clients must send this token before transmitting");
+                    if self.allow_transmit {
+                        ui.label("Token:");
+                        ui.add(egui::TextEdit::singleline(&mut self.token).password(true));
+                    }
+                });
+                if ui.button("Start Server").clicked() {
+                    match RemoteServer::start(
+                        &self.bind_address,
+                        can_app.clone(),
+                        self.allow_transmit.then(|| self.token.clone()),
+                        move |msg| tracing::info!(target: "remote", "{}", msg),
+                    ) {
+                        Ok(s) => *server = Some(s),
+                        Err(e) => tracing::error!(target: "remote", "{}", e),
+                    }
+                }
+            }
+            Some(s) => {
+                ui.label(format!("Listening on {}", s.local_addr()));
+                ui.label(format!("Connected clients: {}", s.client_count()));
+                ui.label(if s.allows_transmit() {
+                    "Remote transmit: enabled (token)"
+                } else {
+                    "Remote transmit: disabled"
+                });
+                if ui.button("Stop Server").clicked() {
+                    *server = None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use crate::can::replay::{self, LogReplay, ReplayState};
+
+use eframe::egui;
+use rfd::FileDialog;
+
+/// 紀錄檔重播面板：載入 candump 或 BLF 紀錄，依原始時間重新送出
+pub struct ReplayPanel {
+    frames: Vec<CanFrame>,
+    file_name: String,
+    /// 改由指定通道送出，否則使用紀錄中的通道
+    override_channel: bool,
+    channel: u32,
+    speed: f64,
+    replay: Option<LogReplay>,
+}
+
+impl Default for ReplayPanel {
+    fn default() -> Self {
+        Self {
+            frames: Vec::new(),
+            file_name: String::new(),
+            override_channel: false,
+            channel: 0,
+            speed: 1.0,
+            replay: None,
+        }
+    }
+}
+
+impl ReplayPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, can_app: &SharedCan) {
+        if self.replay.as_ref().is_some_and(|r| !r.is_running()) {
+            self.replay = None;
+        }
+        let idle = self.replay.is_none();
+        ui.horizontal(|ui| {
+            if ui
+                .add_enabled(idle, egui::Button::new("Open Log..."))
+                .clicked()
+            {
+                if let Some(path) = FileDialog::new()
+                    .add_filter("candump log", &["log", "txt"])
+                    .add_filter("Vector BLF", &["blf"])
+                    .pick_file()
+                {
+                    match replay::load_log(&path.to_string_lossy()) {
+                        Ok(frames) => {
+                            self.file_name = path
+                                .file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            self.frames = frames;
+                        }
+                        Err(e) => tracing::error!(target: "replay", "{}", e),
+                    }
+                }
+            }
+            if let (Some(first), Some(last)) = (self.frames.first(), self.frames.last()) {
+                ui.label(format!(
+                    "{}: {} frames over {:.3} s",
+                    self.file_name,
+                    self.frames.len(),
+                    (last.timestamp - first.timestamp) as f64 / 1_000_000.0
+                ));
+            }
+        });
+        ui.horizontal(|ui| {
+            ui.add_enabled_ui(idle, |ui| {
+                ui.checkbox(&mut self.override_channel, "Send on channel");
+                ui.add_enabled(
+                    self.override_channel,
+                    egui::DragValue::new(&mut self.channel).range(0..=15),
+                );
+            });
+            ui.label("Speed:");
+            let changed = ui
+                .add(
+                    egui::Slider::new(&mut self.speed, replay::MIN_SPEED..=replay::MAX_SPEED)
+                        .logarithmic(true)
+                        .suffix("x"),
+                )
+                .changed();
+            if changed {
+                if let Some(replay) = self.replay.as_ref() {
+                    replay.set_speed(self.speed);
+                }
+            }
+        });
+
+        match self.replay {
+            None => {
+                if ui
+                    .add_enabled(!self.frames.is_empty(), egui::Button::new("Play"))
+                    .clicked()
+                {
+                    self.replay = Some(LogReplay::start(
+                        self.frames.clone(),
+                        can_app.clone(),
+                        self.override_channel.then_some(self.channel),
+                        self.speed,
+                        |msg| tracing::info!(target: "replay", "{}", msg),
+                    ));
+                }
+            }
+            Some(ref replay) => {
+                let (sent, total) = replay.progress();
+                let (position, duration) = replay.position();
+                ui.horizontal(|ui| {
+                    match replay.state() {
+                        ReplayState::Paused => {
+                            if ui.button("Resume").clicked() {
+                                replay.set_paused(false);
+                            }
+                        }
+                        _ => {
+                            if ui.button("Pause").clicked() {
+                                replay.set_paused(true);
+                            }
+                        }
+                    }
+                    if ui.button("Stop").clicked() {
+                        replay.stop();
+                    }
+                    ui.add(
+                        egui::ProgressBar::new(sent as f32 / total.max(1) as f32).text(format!(
+                            "{}/{}  {:.1}/{:.1} s",
+                            sent,
+                            total,
+                            position as f64 / 1_000_000.0,
+                            duration as f64 / 1_000_000.0
+                        )),
+                    );
+                });
+            }
+        }
+    }
+}
@@ -0,0 +1,329 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::config::CanbusConfigEntry;
+use crate::can::conformance::CheckResult;
+use crate::can::events::BusEvent;
+use crate::can::signals::extract_value;
+use crate::can::silence::SilentGap;
+use crate::can::snapshot::Snapshot;
+use crate::can::units::DisplayUnits;
+use crate::ui::format_timestamp;
+use crate::ui::plot_export::{render_svg, PlotImage, PlotSeries};
+
+use std::fmt::Write as _;
+use std::fs;
+use std::path::PathBuf;
+
+const PLOT_WIDTH: u32 = 800;
+const PLOT_HEIGHT: u32 = 320;
+
+/// 擷取報告的內容
+pub struct CaptureReport<'a> {
+    pub snapshot: &'a Snapshot,
+    /// 擷取開始時間（微秒），未知時為 None
+    pub started_us: Option<u64>,
+    pub events: Vec<BusEvent>,
+    /// 一致性檢查結果，未設定檢查時為空
+    pub conformance: Vec<CheckResult>,
+    pub plots: Vec<PlotImage>,
+    /// 使用的設定檔路徑與內容
+    pub config: Option<(String, String)>,
+}
+
+/// 從訊框緩衝區解碼一個訊號的時間序列，x 為相對於第一筆訊框的秒數；
+/// `silences` 中的靜默期間會以底色標示
+pub fn signal_plot(
+    frames: &[CanFrame],
+    entries: &[CanbusConfigEntry],
+    key: &str,
+    units: &DisplayUnits,
+    silences: &[SilentGap],
+) -> PlotImage {
+    let start = frames.first().map_or(0, |f| f.timestamp);
+    let end = frames.last().map_or(0, |f| f.timestamp);
+    let mut points = Vec::new();
+    for frame in frames {
+        for entry in entries.iter().filter(|e| e.key == key && e.id == frame.id) {
+            if let Some(value) = extract_value(entry, frame.payload()) {
+                let time_s = frame.timestamp.saturating_sub(start) as f64 / 1e6;
+                points.push((time_s, units.convert(key, value)));
+            }
+        }
+    }
+    let unit = units.unit(key);
+    PlotImage {
+        title: key.to_string(),
+        x_label: "Time [s]".to_string(),
+        y_label: if unit.is_empty() {
+            key.to_string()
+        } else {
+            format!("{} [{}]", key, unit)
+        },
+        series: vec![PlotSeries {
+            name: key.to_string(),
+            points,
+        }],
+        shaded: silences
+            .iter()
+            .filter(|g| g.start_us < end && g.end_us.is_none_or(|e| e > start))
+            .map(|g| {
+                let to_s = |us: u64| us.saturating_sub(start) as f64 / 1e6;
+                (to_s(g.start_us), to_s(g.end_us.unwrap_or(end)))
+            })
+            .collect(),
+    }
+}
+
+/// 報告中的設定檔段落：(路徑, 內容)，多個檔案時以標頭分隔；未載入設定時回傳 None
+pub fn config_listing(paths: &[PathBuf]) -> Option<(String, String)> {
+    if paths.is_empty() {
+        return None;
+    }
+    let names: Vec<String> = paths.iter().map(|p| p.display().to_string()).collect();
+    let mut text = String::new();
+    for p in paths {
+        match fs::read_to_string(p) {
+            Ok(content) if paths.len() > 1 => {
+                text.push_str(&format!("# ==> {} <==\n{}\n", p.display(), content));
+            }
+            Ok(content) => text.push_str(&content),
+            Err(e) => tracing::warn!(target: "report", "Failed to read {}: {}", p.display(), e),
+        }
+    }
+    Some((names.join(" + "), text))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn opt(value: Option<f64>) -> String {
+    value.map_or_else(|| "-".to_string(), |v| format!("{:.3}", v))
+}
+
+fn format_duration(us: u64) -> String {
+    let secs = us / 1_000_000;
+    format!(
+        "{:02}:{:02}:{:02}.{:03}",
+        secs / 3600,
+        secs / 60 % 60,
+        secs % 60,
+        us / 1000 % 1000
+    )
+}
+
+impl CaptureReport<'_> {
+    /// 產生單一 HTML 文件，圖表以內嵌 SVG 呈現，可直接以瀏覽器列印成 PDF
+    pub fn render_html(&self) -> String {
+        let s = self.snapshot;
+        let mut html = String::new();
+        let _ = write!(
+            html,
+            concat!(
+                "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n",
+                "<title>CAN Capture Report {}</title>\n<style>\n",
+                "body {{ font-family: sans-serif; margin: 2em; }}\n",
+                "table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n",
+                "th, td {{ border: 1px solid #ccc; padding: 2px 8px; text-align: right; }}\n",
+                "th {{ background: #eee; }}\n",
+                "td.text {{ text-align: left; }}\n",
+                "pre {{ background: #f6f6f6; padding: 1em; }}\n",
+                "@media print {{ section {{ page-break-inside: avoid; }} }}\n",
+                "</style>\n</head>\n<body>\n<h1>CAN Capture Report</h1>\n"
+            ),
+            escape(&s.taken_at)
+        );
+
+        let _ = writeln!(html, "<section>\n<h2>Summary</h2>\n<table>");
+        let mut summary = vec![
+            ("Generated", s.taken_at.clone()),
+            ("Interface", s.interface.clone()),
+        ];
+        for (name, value) in [
+            ("Operator", &s.session.operator),
+            ("DUT", &s.session.dut),
+            ("Description", &s.session.description),
+        ] {
+            if !value.trim().is_empty() {
+                summary.push((name, value.clone()));
+            }
+        }
+        for library in &s.libraries {
+            summary.push((library.library.as_str(), library.version.clone()));
+        }
+        if let Some(started) = self.started_us {
+            summary.push(("Capture start", format_timestamp(started)));
+            summary.push((
+                "Duration",
+                format_duration(s.timestamp_us.saturating_sub(started)),
+            ));
+        }
+        let frames: u64 = s.channels.iter().map(|c| c.frames).sum();
+        let tx_frames: u64 = s.channels.iter().map(|c| c.tx_frames).sum();
+        let errors: u64 = s.channels.iter().map(|c| c.errors).sum();
+        summary.push(("Frames received", frames.to_string()));
+        summary.push(("Frames sent", tx_frames.to_string()));
+        summary.push(("Errors", errors.to_string()));
+        summary.push(("Events", self.events.len().to_string()));
+        for (name, value) in summary {
+            let _ = writeln!(
+                html,
+                "<tr><th>{}</th><td class=\"text\">{}</td></tr>",
+                name,
+                escape(&value)
+            );
+        }
+        let _ = writeln!(html, "</table>\n</section>");
+        if !s.session.notes.trim().is_empty() {
+            let _ = writeln!(
+                html,
+                "<section>\n<h2>Notes</h2>\n<pre>{}</pre>\n</section>",
+                escape(&s.session.notes)
+            );
+        }
+
+        let _ = writeln!(
+            html,
+            concat!(
+                "<section>\n<h2>Channels</h2>\n<table>\n",
+                "<tr><th>Channel</th><th>RX</th><th>RX Bytes</th><th>TX</th>",
+                "<th>Errors</th><th>Bus Load</th></tr>"
+            )
+        );
+        for c in &s.channels {
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{:.1}%</td></tr>",
+                c.channel, c.frames, c.bytes, c.tx_frames, c.errors, c.bus_load_percent
+            );
+        }
+        let _ = writeln!(html, "</table>\n</section>");
+
+        let _ = writeln!(
+            html,
+            concat!(
+                "<section>\n<h2>Message IDs</h2>\n<table>\n",
+                "<tr><th>Channel</th><th>ID</th><th>Count</th><th>Mean Period (ms)</th>",
+                "<th>Last Seen</th><th>Last Data</th></tr>"
+            )
+        );
+        for id in &s.ids {
+            let data: Vec<String> = id.last_data.iter().map(|b| format!("{:02X}", b)).collect();
+            let _ = writeln!(
+                html,
+                "<tr><td>{}</td><td>0x{:X}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"text\">{}</td></tr>",
+                id.channel,
+                id.id,
+                id.count,
+                opt(id.mean_period_ms),
+                format_timestamp(id.last_seen_us),
+                data.join(" ")
+            );
+        }
+        let _ = writeln!(html, "</table>\n</section>");
+
+        let _ = writeln!(
+            html,
+            concat!(
+                "<section>\n<h2>Signals</h2>\n<table>\n",
+                "<tr><th>Signal</th><th>Unit</th><th>Value</th><th>Min</th><th>Max</th>",
+                "<th>Avg</th><th>Samples</th></tr>"
+            )
+        );
+        for signal in &s.signals {
+            let _ = writeln!(
+                html,
+                "<tr><td class=\"text\">{}</td><td class=\"text\">{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape(&signal.key),
+                escape(&signal.unit),
+                opt(signal.value),
+                opt(signal.min),
+                opt(signal.max),
+                opt(signal.avg),
+                signal.samples
+            );
+        }
+        let _ = writeln!(html, "</table>\n</section>");
+
+        let _ = writeln!(html, "<section>\n<h2>Alarms and Events</h2>");
+        if self.events.is_empty() {
+            let _ = writeln!(html, "<p>No events recorded.</p>");
+        } else {
+            let _ = writeln!(
+                html,
+                "<table>\n<tr><th>Time</th><th>Severity</th><th>Category</th><th>Message</th></tr>"
+            );
+            for event in &self.events {
+                let _ = writeln!(
+                    html,
+                    "<tr><td>{}</td><td class=\"text\">{:?}</td><td class=\"text\">{:?}</td><td class=\"text\">{}</td></tr>",
+                    format_timestamp(event.timestamp),
+                    event.severity,
+                    event.category,
+                    escape(&event.message)
+                );
+            }
+            let _ = writeln!(html, "</table>");
+        }
+        let _ = writeln!(html, "</section>");
+
+        if !self.conformance.is_empty() {
+            let _ = writeln!(
+                html,
+                concat!(
+                    "<section>\n<h2>Conformance Checks</h2>\n<table>\n",
+                    "<tr><th>Check</th><th>Target</th><th>Result</th><th>Checked</th>",
+                    "<th>Violations</th><th>Detail</th></tr>"
+                )
+            );
+            for r in &self.conformance {
+                let color = if r.untested() {
+                    "#888"
+                } else if r.passed() {
+                    "#080"
+                } else {
+                    "#c00"
+                };
+                let _ = writeln!(
+                    html,
+                    "<tr><td class=\"text\">{}</td><td class=\"text\">{}</td><td class=\"text\" style=\"color: {}\">{}</td><td>{}</td><td>{}</td><td class=\"text\">{}</td></tr>",
+                    r.kind.label(),
+                    escape(&r.target),
+                    color,
+                    r.verdict(),
+                    r.checked,
+                    r.violations,
+                    escape(&r.detail)
+                );
+            }
+            let _ = writeln!(html, "</table>\n</section>");
+        }
+
+        if !self.plots.is_empty() {
+            let _ = writeln!(html, "<section>\n<h2>Signal Plots</h2>");
+            for plot in &self.plots {
+                html.push_str(&render_svg(plot, PLOT_WIDTH, PLOT_HEIGHT));
+            }
+            let _ = writeln!(html, "</section>");
+        }
+
+        let _ = writeln!(html, "<section>\n<h2>Configuration</h2>");
+        match &self.config {
+            Some((path, text)) => {
+                let _ = writeln!(html, "<p>{}</p>\n<pre>{}</pre>", escape(path), escape(text));
+            }
+            None => {
+                let _ = writeln!(html, "<p>No configuration loaded.</p>");
+            }
+        }
+        let _ = writeln!(html, "</section>\n</body>\n</html>");
+        html
+    }
+
+    pub fn save(&self, file_path: &str) -> Result<(), String> {
+        fs::write(file_path, self.render_html())
+            .map_err(|e| format!("Failed to write {}: {}", file_path, e))
+    }
+}
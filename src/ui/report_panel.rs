@@ -0,0 +1,51 @@
+use crate::can::signals::SignalTable;
+
+use eframe::egui;
+use std::collections::BTreeSet;
+use std::sync::Mutex;
+
+/// 擷取報告面板：選擇要繪圖的訊號
+#[derive(Default)]
+pub struct ReportPanel {
+    selected: BTreeSet<String>,
+}
+
+impl ReportPanel {
+    /// 已選擇要繪圖的訊號
+    pub fn selected(&self) -> impl Iterator<Item = &str> {
+        self.selected.iter().map(|s| s.as_str())
+    }
+
+    /// 回傳是否按下產生報告
+    pub fn show(&mut self, ui: &mut egui::Ui, signals: &Mutex<SignalTable>) -> bool {
+        ui.label("Summarizes the capture: duration, channel and ID statistics, events, plots and the configuration used.");
+        ui.label("Open the HTML in a browser and print it to get a PDF.");
+        ui.separator();
+        ui.label("Signals to plot (from the data buffer):");
+        let keys: Vec<String> = signals
+            .lock()
+            .unwrap()
+            .signals()
+            .map(|s| s.key.clone())
+            .collect();
+        if keys.is_empty() {
+            ui.weak("Load a YAML config to plot signals");
+        }
+        egui::ScrollArea::vertical()
+            .max_height(200.0)
+            .show(ui, |ui| {
+                for key in keys {
+                    let mut checked = self.selected.contains(&key);
+                    if ui.checkbox(&mut checked, &key).changed() {
+                        if checked {
+                            self.selected.insert(key);
+                        } else {
+                            self.selected.remove(&key);
+                        }
+                    }
+                }
+            });
+        ui.separator();
+        ui.button("Generate HTML Report...").clicked()
+    }
+}
@@ -0,0 +1,75 @@
+use crate::can::sampler::FixedRateSampler;
+use crate::can::signals::SignalTable;
+use crate::can::units::DisplayUnits;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::{Arc, Mutex};
+
+/// 固定頻率訊號取樣面板
+pub struct SamplerPanel {
+    rate_hz: f64,
+    sampler: Option<FixedRateSampler>,
+}
+
+impl Default for SamplerPanel {
+    fn default() -> Self {
+        Self {
+            rate_hz: 10.0,
+            sampler: None,
+        }
+    }
+}
+
+impl SamplerPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        signals: &Arc<Mutex<SignalTable>>,
+        units: &Mutex<DisplayUnits>,
+    ) {
+        if self.sampler.as_ref().is_some_and(|s| !s.is_running()) {
+            self.sampler = None;
+        }
+        match self.sampler.as_ref() {
+            None => {
+                ui.horizontal(|ui| {
+                    ui.label("Rate:");
+                    ui.add(
+                        egui::DragValue::new(&mut self.rate_hz)
+                            .range(0.1..=1000.0)
+                            .suffix(" Hz"),
+                    );
+                });
+                if ui.button("Start Sampling").clicked() {
+                    if let Some(path) = FileDialog::new()
+                        .add_filter("csv", &["csv"])
+                        .set_file_name("samples.csv")
+                        .save_file()
+                    {
+                        match FixedRateSampler::start(
+                            path.to_str().unwrap(),
+                            self.rate_hz,
+                            Arc::clone(signals),
+                            units.lock().unwrap().clone(),
+                            move |msg| tracing::info!(target: "sampler", "{}", msg),
+                        ) {
+                            Ok(sampler) => self.sampler = Some(sampler),
+                            Err(e) => tracing::error!(target: "sampler", "{}", e),
+                        }
+                    }
+                }
+            }
+            Some(sampler) => {
+                ui.label(format!(
+                    "Sampling at {} Hz, {} rows written",
+                    self.rate_hz,
+                    sampler.rows()
+                ));
+                if ui.button("Stop Sampling").clicked() {
+                    self.sampler = None;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,342 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::CanFrame;
+use crate::can::config::CanbusConfigEntry;
+use crate::can::csv_schedule::{self, SchedulePlayback, ScheduledFrame};
+use crate::can::scheduler::{PeriodicMessage, RateLimit, TxScheduler};
+use crate::can::signals::{encode_value, value_range, SignalTable};
+use crate::can::units::DisplayUnits;
+use crate::ui::{parse_hex_bytes, parse_hex_u32};
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::{Arc, Mutex};
+
+/// 速率限制的編輯欄位
+fn limit_editor(ui: &mut egui::Ui, limit: &mut RateLimit) {
+    ui.label("Max:");
+    ui.add(
+        egui::DragValue::new(&mut limit.max_fps)
+            .range(0.0..=20000.0)
+            .suffix(" fps"),
+    );
+    ui.label("Burst:");
+    ui.add(egui::DragValue::new(&mut limit.burst).range(1..=1000));
+    ui.label("Gap:");
+    ui.add(
+        egui::DragValue::new(&mut limit.min_gap_ms)
+            .range(0.0..=10000.0)
+            .speed(0.1)
+            .suffix(" ms"),
+    );
+}
+
+/// 週期傳送排程面板，含個別與全域速率限制
+pub struct SchedulerPanel {
+    messages: Arc<Mutex<Vec<PeriodicMessage>>>,
+    global_limit: Arc<Mutex<RateLimit>>,
+    scheduler: Option<TxScheduler>,
+    channel: u32,
+    id_text: String,
+    data_text: String,
+    period_ms: u64,
+    csv_rows: Vec<ScheduledFrame>,
+    csv_name: String,
+    playback: Option<SchedulePlayback>,
+    signal_key: String,
+    /// 以目前顯示單位表示的設定值
+    signal_value: f64,
+    /// 拖動時立即寫入排程中的訊息
+    signal_live: bool,
+}
+
+impl Default for SchedulerPanel {
+    fn default() -> Self {
+        Self {
+            messages: Arc::new(Mutex::new(Vec::new())),
+            global_limit: Arc::new(Mutex::new(RateLimit::default())),
+            scheduler: None,
+            channel: 0,
+            id_text: "0x100".to_string(),
+            data_text: "00 00 00 00 00 00 00 00".to_string(),
+            period_ms: 100,
+            csv_rows: Vec::new(),
+            csv_name: String::new(),
+            playback: None,
+            signal_key: String::new(),
+            signal_value: 0.0,
+            signal_live: false,
+        }
+    }
+}
+
+impl SchedulerPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        can_app: &SharedCan,
+        signals: &Mutex<SignalTable>,
+        units: &Mutex<DisplayUnits>,
+    ) {
+        ui.horizontal(|ui| {
+            match self.scheduler {
+                None => {
+                    if ui.button("Start").clicked() {
+                        self.scheduler = Some(TxScheduler::start(
+                            Arc::clone(&self.messages),
+                            Arc::clone(&self.global_limit),
+                            can_app.clone(),
+                            move |msg| tracing::info!(target: "sched", "{}", msg),
+                        ));
+                    }
+                }
+                Some(ref scheduler) => {
+                    if ui.button("Stop").clicked() {
+                        self.scheduler = None;
+                        return;
+                    }
+                    ui.label(format!(
+                        "Sent: {}  Throttled: {}",
+                        scheduler.sent(),
+                        scheduler.throttled()
+                    ));
+                }
+            };
+        });
+        ui.horizontal(|ui| {
+            ui.strong("Global limit");
+            limit_editor(ui, &mut self.global_limit.lock().unwrap());
+        });
+        ui.separator();
+
+        let mut remove_index = None;
+        {
+            let mut messages = self.messages.lock().unwrap();
+            egui::Grid::new("scheduler_messages")
+                .striped(true)
+                .num_columns(4)
+                .show(ui, |ui| {
+                    for (index, message) in messages.iter_mut().enumerate() {
+                        ui.checkbox(&mut message.enabled, "");
+                        ui.monospace(format!(
+                            "CH{} 0x{:X} {:02X?}",
+                            message.frame.channel,
+                            message.frame.id,
+                            message.frame.payload()
+                        ));
+                        ui.horizontal(|ui| {
+                            ui.label("Period:");
+                            ui.add(
+                                egui::DragValue::new(&mut message.period_ms)
+                                    .range(1..=60000)
+                                    .suffix(" ms"),
+                            );
+                            limit_editor(ui, &mut message.limit);
+                        });
+                        if ui.small_button("🗑").clicked() {
+                            remove_index = Some(index);
+                        }
+                        ui.end_row();
+                    }
+                });
+            if let Some(index) = remove_index {
+                messages.remove(index);
+            }
+        }
+
+        ui.separator();
+        ui.horizontal(|ui| {
+            ui.label("CH:");
+            ui.add(egui::DragValue::new(&mut self.channel));
+            ui.label("ID:");
+            ui.add(egui::TextEdit::singleline(&mut self.id_text).desired_width(60.0));
+            ui.label("Data:");
+            ui.add(egui::TextEdit::singleline(&mut self.data_text).desired_width(160.0));
+            ui.label("Period:");
+            ui.add(
+                egui::DragValue::new(&mut self.period_ms)
+                    .range(1..=60000)
+                    .suffix(" ms"),
+            );
+            if ui.button("Add").clicked() {
+                match (
+                    parse_hex_u32(&self.id_text),
+                    parse_hex_bytes(&self.data_text),
+                ) {
+                    (Ok(id), Ok(data)) if data.len() <= 8 => {
+                        self.messages.lock().unwrap().push(PeriodicMessage {
+                            frame: CanFrame::new(self.channel, id, &data),
+                            period_ms: self.period_ms,
+                            enabled: true,
+                            limit: RateLimit::default(),
+                        });
+                    }
+                    (Ok(_), Ok(_)) => {
+                        tracing::warn!(target: "sched", "Data must be at most 8 bytes")
+                    }
+                    (Err(e), _) | (_, Err(e)) => tracing::error!(target: "sched", "{}", e),
+                }
+            }
+        });
+
+        ui.separator();
+        self.show_signal_writer(ui, signals, units);
+        ui.separator();
+        self.show_csv_schedule(ui, can_app);
+    }
+
+    /// 依訊號數值寫回：編碼到所屬訊息的資料位元組，由週期排程送出
+    fn show_signal_writer(
+        &mut self,
+        ui: &mut egui::Ui,
+        signals: &Mutex<SignalTable>,
+        units: &Mutex<DisplayUnits>,
+    ) {
+        ui.strong("Signal write-back");
+        let entries = signals.lock().unwrap().entries().to_vec();
+        let Some(first) = entries.first() else {
+            ui.weak("Load a YAML config to set signals by value");
+            return;
+        };
+        if !entries.iter().any(|e| e.key == self.signal_key) {
+            self.signal_key = first.key.clone();
+        }
+        let units = units.lock().unwrap();
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("write_signal")
+                .selected_text(&self.signal_key)
+                .show_ui(ui, |ui| {
+                    for entry in &entries {
+                        ui.selectable_value(&mut self.signal_key, entry.key.clone(), &entry.key);
+                    }
+                });
+            let Some(entry) = entries.iter().find(|e| e.key == self.signal_key) else {
+                return;
+            };
+            let (min, max) = value_range(entry);
+            let (a, b) = (
+                units.convert(&entry.key, min),
+                units.convert(&entry.key, max),
+            );
+            let unit = units.unit(&entry.key);
+            let changed = ui
+                .add(
+                    egui::Slider::new(&mut self.signal_value, a.min(b)..=a.max(b))
+                        .suffix(format!(" {}", unit)),
+                )
+                .changed();
+            ui.checkbox(&mut self.signal_live, "Live");
+            if ui.button("Apply").clicked() || (changed && self.signal_live) {
+                let raw = units.to_raw(&entry.key, self.signal_value);
+                if let Err(e) = self.write_signal(entry, &entries, raw) {
+                    tracing::error!(target: "sched", "{}", e);
+                }
+            }
+        });
+        ui.weak(format!(
+            "Encodes into CH{} periodic message (added with {} ms period if missing)",
+            self.channel, self.period_ms
+        ));
+    }
+
+    fn write_signal(
+        &mut self,
+        entry: &CanbusConfigEntry,
+        entries: &[CanbusConfigEntry],
+        raw: f64,
+    ) -> Result<(), String> {
+        let mut messages = self.messages.lock().unwrap();
+        let index = match messages
+            .iter()
+            .position(|m| m.frame.id == entry.id && m.frame.channel == self.channel)
+        {
+            Some(index) => index,
+            None => {
+                // 新訊息的長度涵蓋同一 ID 所有訊號
+                let len = entries
+                    .iter()
+                    .filter(|e| e.id == entry.id)
+                    .map(CanbusConfigEntry::byte_end)
+                    .max()
+                    .unwrap_or(0)
+                    .min(8);
+                messages.push(PeriodicMessage {
+                    frame: CanFrame::new(self.channel, entry.id, &vec![0; len]),
+                    period_ms: self.period_ms,
+                    enabled: true,
+                    limit: RateLimit::default(),
+                });
+                messages.len() - 1
+            }
+        };
+        let frame = &mut messages[index].frame;
+        let mut data = frame.payload().to_vec();
+        encode_value(entry, raw, &mut data)?;
+        frame.set_payload(&data);
+        tracing::info!(target: "sched", "0x{:X} {} = {}", entry.id, entry.key, raw);
+        Ok(())
+    }
+
+    /// 一次性 CSV 排程：依 time_offset 送出每一列
+    fn show_csv_schedule(&mut self, ui: &mut egui::Ui, can_app: &SharedCan) {
+        ui.strong("One-shot CSV schedule");
+        if self.playback.as_ref().is_some_and(|p| !p.is_running()) {
+            self.playback = None;
+        }
+        ui.horizontal(|ui| {
+            let idle = self.playback.is_none();
+            if ui
+                .add_enabled(idle, egui::Button::new("Load CSV..."))
+                .clicked()
+            {
+                if let Some(path) = FileDialog::new().add_filter("csv", &["csv"]).pick_file() {
+                    match csv_schedule::load_csv_schedule(path.to_str().unwrap(), self.channel) {
+                        Ok(rows) => {
+                            self.csv_name = path
+                                .file_name()
+                                .map(|n| n.to_string_lossy().to_string())
+                                .unwrap_or_default();
+                            self.csv_rows = rows;
+                        }
+                        Err(e) => tracing::error!(target: "sched", "{}", e),
+                    }
+                }
+            }
+            if !self.csv_rows.is_empty() {
+                let duration_s =
+                    self.csv_rows.last().map_or(0, |r| r.offset_us) as f64 / 1_000_000.0;
+                ui.label(format!(
+                    "{}: {} frames over {:.3} s",
+                    self.csv_name,
+                    self.csv_rows.len(),
+                    duration_s
+                ));
+            }
+        });
+        match self.playback {
+            None => {
+                if ui
+                    .add_enabled(!self.csv_rows.is_empty(), egui::Button::new("Play"))
+                    .clicked()
+                {
+                    self.playback = Some(SchedulePlayback::start(
+                        self.csv_rows.clone(),
+                        can_app.clone(),
+                        move |msg| tracing::info!(target: "sched", "{}", msg),
+                    ));
+                }
+            }
+            Some(ref playback) => {
+                let (sent, total) = playback.progress();
+                ui.horizontal(|ui| {
+                    if ui.button("Stop").clicked() {
+                        playback.stop();
+                    }
+                    ui.add(
+                        egui::ProgressBar::new(sent as f32 / total.max(1) as f32)
+                            .text(format!("{}/{}", sent, total)),
+                    );
+                });
+            }
+        }
+    }
+}
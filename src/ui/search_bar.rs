@@ -0,0 +1,207 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::search::SearchQuery;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+
+/// 檔案搜尋結果的顯示上限
+const MAX_FILE_RESULTS: usize = 500;
+
+#[derive(Clone, Copy)]
+enum Jump {
+    Next,
+    Prev,
+}
+
+/// 追蹤畫面的搜尋列：位元組樣式（可用 ? 萬用字元）或正規表示式，
+/// 可在符合的訊框之間跳轉，也可搜尋紀錄檔
+#[derive(Default)]
+pub struct SearchBar {
+    text: String,
+    regex_mode: bool,
+    query: Option<SearchQuery>,
+    error: Option<String>,
+    /// 目前所在的符合訊框（以時間戳記識別，緩衝區捲動時仍能對應）
+    current: Option<u64>,
+    jump: Option<Jump>,
+    match_count: usize,
+    match_position: Option<usize>,
+    file_results: Vec<String>,
+}
+
+impl SearchBar {
+    pub fn show(&mut self, ui: &mut egui::Ui) {
+        ui.horizontal(|ui| {
+            ui.label("Search:");
+            let mut changed = ui
+                .add(
+                    egui::TextEdit::singleline(&mut self.text)
+                        .hint_text(if self.regex_mode {
+                            "regex, e.g. ID=0x1[0-9A-F]{2}"
+                        } else {
+                            "bytes, e.g. 12 ?? 3? AB"
+                        })
+                        .desired_width(200.0),
+                )
+                .changed();
+            changed |= ui.checkbox(&mut self.regex_mode, "Regex").changed();
+            if changed {
+                self.compile();
+            }
+            if ui.button("◀").on_hover_text("Previous match").clicked() {
+                self.jump = Some(Jump::Prev);
+            }
+            if ui.button("▶").on_hover_text("Next match").clicked() {
+                self.jump = Some(Jump::Next);
+            }
+            if self.query.is_some() {
+                match self.match_position {
+                    Some(pos) => ui.label(format!("{}/{}", pos + 1, self.match_count)),
+                    None => ui.label(format!("{} matches", self.match_count)),
+                };
+            }
+            if ui
+                .add_enabled(self.query.is_some(), egui::Button::new("Search File..."))
+                .clicked()
+            {
+                self.search_file();
+            }
+        });
+        if let Some(ref error) = self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+        if !self.file_results.is_empty() {
+            ui.horizontal(|ui| {
+                egui::CollapsingHeader::new(format!("File matches ({})", self.file_results.len()))
+                    .show(ui, |ui| {
+                        egui::ScrollArea::vertical()
+                            .id_salt("file_search_results")
+                            .max_height(150.0)
+                            .show(ui, |ui| {
+                                for line in &self.file_results {
+                                    ui.monospace(line);
+                                }
+                            });
+                    });
+                if ui.small_button("Clear").clicked() {
+                    self.file_results.clear();
+                }
+            });
+        }
+    }
+
+    fn compile(&mut self) {
+        self.current = None;
+        if self.text.trim().is_empty() {
+            self.query = None;
+            self.error = None;
+            return;
+        }
+        let query = if self.regex_mode {
+            SearchQuery::regex(&self.text)
+        } else {
+            SearchQuery::bytes(&self.text)
+        };
+        match query {
+            Ok(query) => {
+                self.query = Some(query);
+                self.error = None;
+            }
+            Err(e) => {
+                self.query = None;
+                self.error = Some(e);
+            }
+        }
+    }
+
+    /// 是否有有效的搜尋條件
+    pub fn is_active(&self) -> bool {
+        self.query.is_some()
+    }
+
+    /// 搜尋文字與是否為正規表示式，內容改變時符合的訊框需要重新計算
+    pub fn pattern(&self) -> (String, bool) {
+        (self.text.clone(), self.regex_mode)
+    }
+
+    /// 訊框是否符合；`text` 為該訊框的顯示文字
+    pub fn matches(&self, frame: &CanFrame, text: &str) -> bool {
+        self.query.as_ref().is_some_and(|q| q.matches(frame, text))
+    }
+
+    /// 依本次畫面中所有符合訊框的時間戳記處理跳轉，回傳需要捲動到的訊框
+    pub fn navigate(&mut self, matches: &[u64]) -> Option<u64> {
+        self.match_count = matches.len();
+        let target = match self.jump.take() {
+            Some(Jump::Next) => self
+                .current
+                .and_then(|cur| matches.iter().find(|&&t| t > cur))
+                .or(matches.first())
+                .copied(),
+            Some(Jump::Prev) => self
+                .current
+                .and_then(|cur| matches.iter().rev().find(|&&t| t < cur))
+                .or(matches.last())
+                .copied(),
+            None => None,
+        };
+        if target.is_some() {
+            self.current = target;
+        }
+        self.match_position = self
+            .current
+            .and_then(|cur| matches.iter().position(|&t| t == cur));
+        target
+    }
+
+    /// 目前選取的符合訊框
+    pub fn current(&self) -> Option<u64> {
+        self.current
+    }
+
+    /// 逐行搜尋文字紀錄檔；位元組樣式比對每行中的十六進位位元組
+    fn search_file(&mut self) {
+        let Some(query) = self.query.as_ref() else {
+            return;
+        };
+        let Some(path) = FileDialog::new().pick_file() else {
+            return;
+        };
+        self.file_results.clear();
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) => {
+                self.error = Some(format!("Failed to open {}: {}", path.display(), e));
+                return;
+            }
+        };
+        for (number, line) in BufReader::new(file).lines().enumerate() {
+            let Ok(line) = line else {
+                break;
+            };
+            let found = match query {
+                SearchQuery::Text(regex) => regex.is_match(&line),
+                SearchQuery::Bytes(pattern) => pattern.find_in(&line_bytes(&line)),
+            };
+            if found {
+                self.file_results.push(format!("{}: {}", number + 1, line));
+                if self.file_results.len() >= MAX_FILE_RESULTS {
+                    break;
+                }
+            }
+        }
+        if self.file_results.is_empty() {
+            self.error = Some(format!("No matches in {}", path.display()));
+        }
+    }
+}
+
+/// 取出一行文字中由兩位十六進位數字組成的位元組
+fn line_bytes(line: &str) -> Vec<u8> {
+    line.split(|c: char| !c.is_ascii_hexdigit())
+        .filter(|token| token.len() == 2)
+        .filter_map(|token| u8::from_str_radix(token, 16).ok())
+        .collect()
+}
@@ -0,0 +1,65 @@
+use crate::can::canbus::SharedCan;
+use crate::can::selftest::{self, SelfTest, SelfTestStatus};
+
+use eframe::egui;
+use std::sync::{Arc, Mutex};
+
+/// 介面自測面板
+pub struct SelfTestPanel {
+    channel: u32,
+    count: u8,
+}
+
+impl Default for SelfTestPanel {
+    fn default() -> Self {
+        Self {
+            channel: 0,
+            count: 20,
+        }
+    }
+}
+
+impl SelfTestPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, test: &Arc<Mutex<SelfTest>>, can_app: &SharedCan) {
+        ui.label("Sends a set of known frames and verifies they are received back unchanged.");
+        ui.label("Use loopback mode or self-reception when no other node is on the bus.");
+        let (status, (received, total)) = {
+            let test = test.lock().unwrap();
+            (test.status().clone(), test.progress())
+        };
+        let running = status == SelfTestStatus::Running;
+        ui.horizontal(|ui| {
+            ui.label("Channel:");
+            ui.add(egui::DragValue::new(&mut self.channel));
+            ui.label("Frames:");
+            ui.add(egui::DragValue::new(&mut self.count).range(1..=255));
+            if ui
+                .add_enabled(!running, egui::Button::new("Run Self Test"))
+                .clicked()
+            {
+                selftest::run_self_test(
+                    Arc::clone(test),
+                    can_app.clone(),
+                    self.channel,
+                    self.count,
+                    move |msg| tracing::info!(target: "selftest", "{}", msg),
+                );
+            }
+        });
+        match status {
+            SelfTestStatus::Idle => {}
+            SelfTestStatus::Running => {
+                ui.label(format!("Running... {}/{} received", received, total));
+            }
+            SelfTestStatus::Passed => {
+                ui.colored_label(
+                    egui::Color32::GREEN,
+                    format!("PASSED ({}/{} frames)", received, total),
+                );
+            }
+            SelfTestStatus::Failed(reason) => {
+                ui.colored_label(egui::Color32::RED, format!("FAILED: {}", reason));
+            }
+        }
+    }
+}
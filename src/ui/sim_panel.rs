@@ -0,0 +1,63 @@
+use crate::can::sim::{PayloadPattern, SimMessage};
+
+use eframe::egui;
+
+/// 模擬介面產生的訊息清單；接收中不可修改，下次啟動時生效
+pub fn show_sim_traffic(ui: &mut egui::Ui, messages: &mut Vec<SimMessage>, running: bool) {
+    ui.add_enabled_ui(!running, |ui| {
+        let mut remove = None;
+        egui::Grid::new("sim_traffic").striped(true).show(ui, |ui| {
+            ui.strong("Ch");
+            ui.strong("ID");
+            ui.strong("Period");
+            ui.strong("Len");
+            ui.strong("Pattern");
+            ui.end_row();
+            for (index, message) in messages.iter_mut().enumerate() {
+                ui.add(egui::DragValue::new(&mut message.channel).range(0..=15));
+                ui.add(
+                    egui::DragValue::new(&mut message.id)
+                        .range(0..=0x1FFF_FFFF)
+                        .hexadecimal(3, false, true)
+                        .prefix("0x"),
+                );
+                ui.add(
+                    egui::DragValue::new(&mut message.period_ms)
+                        .range(1..=60_000)
+                        .suffix(" ms"),
+                );
+                ui.add(egui::DragValue::new(&mut message.len).range(0..=8));
+                ui.horizontal(|ui| {
+                    egui::ComboBox::from_id_salt(("sim_pattern", index))
+                        .selected_text(format!("{:?}", message.pattern))
+                        .show_ui(ui, |ui| {
+                            for pattern in PayloadPattern::ALL {
+                                ui.selectable_value(
+                                    &mut message.pattern,
+                                    pattern,
+                                    format!("{:?}", pattern),
+                                );
+                            }
+                        });
+                    if message.pattern == PayloadPattern::Constant {
+                        for byte in message.data.iter_mut().take(message.len) {
+                            ui.add(egui::DragValue::new(byte).hexadecimal(2, false, true));
+                        }
+                    }
+                    if ui.small_button("🗑").clicked() {
+                        remove = Some(index);
+                    }
+                });
+                ui.end_row();
+            }
+        });
+        if let Some(index) = remove {
+            messages.remove(index);
+        }
+        if ui.button("+ Message").clicked() {
+            let id = messages.iter().map(|m| m.id + 1).max().unwrap_or(0x100);
+            messages.push(SimMessage::new(id, 100, PayloadPattern::Counter));
+        }
+    });
+    ui.weak("Transmitted frames are looped back to the receive side");
+}
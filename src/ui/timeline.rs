@@ -0,0 +1,110 @@
+use crate::can::events::{BusEvent, Severity};
+use crate::can::silence::SilentGap;
+use crate::can::timeline::TrafficTimeline;
+use crate::ui::format_timestamp;
+
+use eframe::egui;
+
+const STRIP_HEIGHT: f32 = 40.0;
+
+/// 擷取時間軸：訊框密度長條、靜默區間與事件標記；
+/// 回傳點選位置的時間（微秒），供追蹤畫面跳轉
+pub fn show_timeline<'a>(
+    ui: &mut egui::Ui,
+    timeline: &TrafficTimeline,
+    events: impl IntoIterator<Item = &'a BusEvent>,
+    silences: &[SilentGap],
+) -> Option<u64> {
+    let (Some(start), Some(end)) = (timeline.start_us(), timeline.end_us()) else {
+        ui.weak("No traffic yet");
+        return None;
+    };
+    let width = ui.available_width();
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(width, STRIP_HEIGHT), egui::Sense::click());
+    let painter = ui.painter_at(rect);
+    let visuals = ui.visuals();
+    painter.rect_filled(rect, 0.0, visuals.extreme_bg_color);
+    let span = (end - start).max(1) as f32;
+    let to_x = |us: u64| rect.left() + us.saturating_sub(start) as f32 / span * rect.width();
+    let to_us = |x: f32| start + ((x - rect.left()) / rect.width() * span).max(0.0) as u64;
+
+    for gap in silences {
+        let (x0, x1) = (to_x(gap.start_us), to_x(gap.end_us.unwrap_or(end)));
+        painter.rect_filled(
+            egui::Rect::from_x_y_ranges(x0..=x1.max(x0 + 1.0), rect.y_range()),
+            0.0,
+            egui::Color32::from_gray(70),
+        );
+    }
+
+    let counts = timeline.counts();
+    let max = counts.iter().copied().max().unwrap_or(1).max(1) as f32;
+    let bar_width = rect.width() / counts.len().max(1) as f32;
+    for (i, &count) in counts.iter().enumerate().filter(|(_, c)| **c > 0) {
+        let height = (count as f32 / max) * (rect.height() - 4.0);
+        let x = rect.left() + i as f32 * bar_width;
+        painter.rect_filled(
+            egui::Rect::from_min_max(
+                egui::pos2(x, rect.bottom() - height),
+                egui::pos2(x + bar_width.max(1.0), rect.bottom()),
+            ),
+            0.0,
+            egui::Color32::from_rgb(70, 130, 230),
+        );
+    }
+
+    for event in events {
+        let color = match event.severity {
+            Severity::Alarm => egui::Color32::RED,
+            Severity::Warning => egui::Color32::YELLOW,
+            Severity::Info => continue,
+        };
+        let x = to_x(event.timestamp);
+        painter.line_segment(
+            [egui::pos2(x, rect.top()), egui::pos2(x, rect.bottom())],
+            egui::Stroke::new(1.5, color),
+        );
+    }
+    painter.rect_stroke(
+        rect,
+        0.0,
+        visuals.widgets.noninteractive.bg_stroke,
+        egui::StrokeKind::Inside,
+    );
+
+    let font = egui::FontId::monospace(10.0);
+    painter.text(
+        rect.left_top() + egui::vec2(2.0, 1.0),
+        egui::Align2::LEFT_TOP,
+        format_timestamp(start),
+        font.clone(),
+        visuals.weak_text_color(),
+    );
+    painter.text(
+        rect.right_top() + egui::vec2(-2.0, 1.0),
+        egui::Align2::RIGHT_TOP,
+        format_timestamp(end),
+        font,
+        visuals.weak_text_color(),
+    );
+
+    let response = response.on_hover_ui_at_pointer(|ui| {
+        if let Some(pos) = ui.ctx().pointer_hover_pos() {
+            let t = to_us(pos.x);
+            let index = (t.saturating_sub(start) / timeline.bucket_us()) as usize;
+            ui.label(format_timestamp(t));
+            ui.label(format!(
+                "{} frames / {} ms",
+                counts.get(index).copied().unwrap_or(0),
+                timeline.bucket_us() / 1000
+            ));
+            ui.weak("Click to jump the trace to this time");
+        }
+    });
+    if response.clicked() {
+        response.interact_pointer_pos().map(|pos| to_us(pos.x))
+    } else {
+        None
+    }
+}
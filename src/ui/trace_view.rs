@@ -0,0 +1,713 @@
+use crate::can::canopen;
+use crate::can::cantypes::{now_micros, CanFrame, FrameDirection};
+use crate::can::j1939::{self, TransportReassembler};
+use crate::can::nmea2000::{self, FastPacketReassembler};
+use crate::can::receive_list::ReceiveList;
+use crate::ui::filter_box::{FilterBox, TermFilter};
+use crate::ui::format_frame;
+use crate::ui::format_timestamp;
+use crate::ui::search_bar::SearchBar;
+use crate::ui::ValueFormat;
+
+use eframe::egui;
+use egui_extras::{Column, TableBuilder};
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::Mutex;
+
+/// 追蹤畫面解讀訊框內容所用的協定
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ProtocolMode {
+    #[default]
+    Raw,
+    /// 拆解 29-bit ID 並解碼已知的 SPN
+    J1939,
+    /// 以 J1939 的 ID 格式解碼標準 PGN 欄位
+    Nmea2000,
+    /// 依 COB-ID 顯示服務與內容
+    CanOpen,
+}
+
+impl ProtocolMode {
+    const ALL: [ProtocolMode; 4] = [
+        ProtocolMode::Raw,
+        ProtocolMode::J1939,
+        ProtocolMode::Nmea2000,
+        ProtocolMode::CanOpen,
+    ];
+
+    fn label(&self) -> &'static str {
+        match self {
+            ProtocolMode::Raw => "Raw",
+            ProtocolMode::J1939 => "J1939",
+            ProtocolMode::Nmea2000 => "NMEA 2000",
+            ProtocolMode::CanOpen => "CANopen",
+        }
+    }
+
+    /// 依協定解讀單筆訊框
+    fn describe(&self, frame: &CanFrame) -> Option<String> {
+        match self {
+            ProtocolMode::Raw => None,
+            ProtocolMode::J1939 => j1939::describe_frame(frame),
+            ProtocolMode::Nmea2000 => nmea2000::describe_frame(frame),
+            ProtocolMode::CanOpen => canopen::describe_frame(frame),
+        }
+    }
+}
+
+/// 分組顯示時標示位元組變化的時間長度
+const CHANGE_HIGHLIGHT_US: u64 = 500_000;
+
+/// 追蹤表格可排序的欄位
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortColumn {
+    Time,
+    Channel,
+    Id,
+    Dlc,
+    Data,
+}
+
+impl SortColumn {
+    fn compare(&self, a: &CanFrame, b: &CanFrame) -> Ordering {
+        match self {
+            SortColumn::Time => a.timestamp.cmp(&b.timestamp),
+            SortColumn::Channel => a.channel.cmp(&b.channel),
+            SortColumn::Id => a.id.cmp(&b.id),
+            SortColumn::Dlc => a.dlc().cmp(&b.dlc()),
+            SortColumn::Data => a.payload().cmp(b.payload()),
+        }
+    }
+}
+
+/// 複製到剪貼簿的格式
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CopyFormat {
+    /// 每筆一行，與記錄檔相同的單行格式，適合貼到郵件
+    #[default]
+    Text,
+    /// 含標題列，依畫面目前的 ID 與資料格式，適合貼到試算表
+    Csv,
+}
+
+impl CopyFormat {
+    const ALL: [CopyFormat; 2] = [CopyFormat::Text, CopyFormat::Csv];
+
+    fn label(&self) -> &'static str {
+        match self {
+            CopyFormat::Text => "Text",
+            CopyFormat::Csv => "CSV",
+        }
+    }
+}
+
+/// CSV 欄位含逗號、引號或換行時加上引號
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+/// 將訊框轉為複製到剪貼簿的文字，每筆一行
+pub fn frames_to_clipboard(
+    frames: &[&CanFrame],
+    format: CopyFormat,
+    id_format: ValueFormat,
+    data_format: ValueFormat,
+) -> String {
+    let mut lines = Vec::with_capacity(frames.len() + 1);
+    match format {
+        CopyFormat::Text => {
+            for frame in frames {
+                lines.push(format!(
+                    "{} {}",
+                    format_timestamp(frame.timestamp),
+                    format_frame(frame)
+                ));
+            }
+        }
+        CopyFormat::Csv => {
+            lines.push("Time,Dir,CH,ID,DLC,Data".to_string());
+            for frame in frames {
+                let direction = match frame.direction {
+                    FrameDirection::Rx => "RX",
+                    FrameDirection::Tx => "TX",
+                };
+                lines.push(format!(
+                    "{},{},{},{},{},{}",
+                    format_timestamp(frame.timestamp),
+                    direction,
+                    frame.channel,
+                    csv_field(&id_format.format_id(frame.id, frame.extended)),
+                    frame.dlc(),
+                    csv_field(&format_data(frame, data_format))
+                ));
+            }
+        }
+    }
+    let mut text = lines.join("\n");
+    text.push('\n');
+    text
+}
+
+/// 表格中的一列；協定解讀只在繪製可見的列時才產生
+struct TraceRow {
+    /// 在資料緩衝區中的位置
+    index: usize,
+    /// 重組完成的訊息，取代單筆訊框的解讀
+    message: Option<String>,
+    /// 與同通道同 ID 前一筆訊框的時間差（微秒）
+    delta_us: Option<u64>,
+    hit: bool,
+}
+
+/// 決定過濾、排序結果的條件；任何一項改變才重建列表
+#[derive(PartialEq)]
+struct RowsKey {
+    /// 緩衝區長度與頭尾訊框，緩衝區滿時捲動長度不變但頭尾會改變
+    len: usize,
+    first: Option<u64>,
+    last: Option<u64>,
+    filter: String,
+    terms: String,
+    search: (String, bool),
+    protocol: ProtocolMode,
+    reassemble: bool,
+    sort: Option<(SortColumn, bool)>,
+}
+
+/// 過濾、排序後的列，重繪時沿用，避免每個畫面都持有緩衝區鎖重新過濾與格式化
+#[derive(Default)]
+struct RowsCache {
+    key: Option<RowsKey>,
+    rows: Vec<TraceRow>,
+    /// 符合搜尋的訊框時間戳記，依收發順序
+    hits: Vec<u64>,
+}
+
+fn format_data(frame: &CanFrame, format: ValueFormat) -> String {
+    if frame.rtr {
+        return "RTR".to_string();
+    }
+    format.format_bytes(frame.payload())
+}
+
+/// 訊框追蹤畫面，每個畫面有自己的顯示過濾與搜尋；
+/// 過濾只影響顯示，接收與紀錄的緩衝區保持完整
+#[derive(Default)]
+pub struct TraceView {
+    filter: FilterBox,
+    /// 逗號分隔的 ID 或文字，與過濾運算式同時套用
+    terms: TermFilter,
+    search: SearchBar,
+    /// 下一次繪製時要跳到的時間
+    jump: Option<u64>,
+    /// 最後跳到的訊框，持續標示
+    marked: Option<u64>,
+    protocol: ProtocolMode,
+    /// 以重組後的訊息取代 J1939 的 TP.CM / TP.DT 或 NMEA 2000 的 fast packet 訊框
+    reassemble: bool,
+    /// 每個 ID 一列，只顯示最新的資料，取代捲動的紀錄
+    grouped: bool,
+    /// 排序欄位與是否遞減，None 為收發順序
+    sort: Option<(SortColumn, bool)>,
+    id_format: ValueFormat,
+    data_format: ValueFormat,
+    /// 選取的訊框，以時間戳記識別
+    selected: HashSet<u64>,
+    /// Shift 點選時範圍選取的起點
+    anchor: Option<u64>,
+    copy_format: CopyFormat,
+    cache: RowsCache,
+}
+
+impl TraceView {
+    /// 訊框是否通過此畫面的顯示過濾
+    pub fn matches(&self, frame: &CanFrame) -> bool {
+        self.filter.matches(frame) && self.terms.matches_frame(frame)
+    }
+
+    pub fn filter_text(&self) -> &str {
+        self.filter.text()
+    }
+
+    pub fn set_filter_text(&mut self, text: &str) {
+        self.filter.set_text(text);
+    }
+
+    pub fn terms_text(&self) -> &str {
+        self.terms.text()
+    }
+
+    pub fn set_terms_text(&mut self, text: &str) {
+        self.terms.set_text(text);
+    }
+
+    /// 捲動到指定時間之後的第一筆訊框（例如從時間軸點選）
+    pub fn jump_to(&mut self, timestamp: u64) {
+        self.jump = Some(timestamp);
+    }
+
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        data: &Mutex<VecDeque<CanFrame>>,
+        receive_list: &Mutex<ReceiveList>,
+        capture_start: Option<u64>,
+    ) {
+        self.filter.show(ui);
+        self.terms.show(ui);
+        if self.grouped {
+            ui.horizontal(|ui| {
+                ui.toggle_value(&mut self.grouped, "Group by ID");
+                self.show_format(ui);
+                if ui.small_button("Reset").clicked() {
+                    receive_list.lock().unwrap().clear();
+                }
+            });
+            self.show_grouped(ui, &receive_list.lock().unwrap());
+            return;
+        }
+        self.search.show(ui);
+        ui.horizontal(|ui| {
+            ui.toggle_value(&mut self.grouped, "Group by ID");
+            self.show_format(ui);
+            ui.label("Protocol:");
+            egui::ComboBox::from_id_salt(ui.id().with("protocol"))
+                .selected_text(self.protocol.label())
+                .show_ui(ui, |ui| {
+                    for mode in ProtocolMode::ALL {
+                        ui.selectable_value(&mut self.protocol, mode, mode.label());
+                    }
+                });
+            let label = match self.protocol {
+                ProtocolMode::J1939 => Some("Reassemble TP"),
+                ProtocolMode::Nmea2000 => Some("Reassemble fast packets"),
+                _ => None,
+            };
+            if let Some(label) = label {
+                ui.checkbox(&mut self.reassemble, label);
+            }
+        });
+        // 複製需要過濾後的列，先記下按下的按鈕
+        let mut copy_visible = None;
+        ui.horizontal(|ui| {
+            if ui
+                .button("Copy Selected")
+                .on_hover_text("Click rows to select, Ctrl+click to toggle, Shift+click for a range; Ctrl+C also copies")
+                .clicked()
+            {
+                copy_visible = Some(false);
+            }
+            if ui.button("Copy Visible").clicked() {
+                copy_visible = Some(true);
+            }
+            egui::ComboBox::from_id_salt(ui.id().with("copy_format"))
+                .width(60.0)
+                .selected_text(self.copy_format.label())
+                .show_ui(ui, |ui| {
+                    for format in CopyFormat::ALL {
+                        ui.selectable_value(&mut self.copy_format, format, format.label());
+                    }
+                });
+            if !self.selected.is_empty() && ui.small_button("Clear Selection").clicked() {
+                self.selected.clear();
+                self.anchor = None;
+            }
+        });
+        // 沒有文字框取得焦點時，Ctrl+C 複製選取的列
+        let copy_event = ui.memory(|m| m.focused().is_none())
+            && ui.input(|i| i.events.iter().any(|e| matches!(e, egui::Event::Copy)));
+        if copy_event && !self.selected.is_empty() {
+            copy_visible = Some(false);
+        }
+        let data = data.lock().unwrap();
+        let key = RowsKey {
+            len: data.len(),
+            first: data.front().map(|f| f.timestamp),
+            last: data.back().map(|f| f.timestamp),
+            filter: self.filter.text().to_string(),
+            terms: self.terms.text().to_string(),
+            search: self.search.pattern(),
+            protocol: self.protocol,
+            reassemble: self.reassemble,
+            sort: self.sort,
+        };
+        if self.cache.key.as_ref() != Some(&key) {
+            self.rebuild_rows(&data);
+            self.cache.key = Some(key);
+        }
+        let rows = &self.cache.rows;
+        let hits = &self.cache.hits;
+        let mut scroll_to = self.search.navigate(hits);
+        if let Some(target) = self.jump.take() {
+            match rows.iter().find(|row| data[row.index].timestamp >= target) {
+                Some(row) if data[rows[0].index].timestamp <= target => {
+                    self.marked = Some(data[row.index].timestamp);
+                    scroll_to = Some(data[row.index].timestamp);
+                }
+                _ => tracing::info!(
+                    target: "trace",
+                    "No frames at {} in the data buffer",
+                    format_timestamp(target)
+                ),
+            }
+        }
+        if let Some(visible) = copy_visible {
+            let frames: Vec<&CanFrame> = rows
+                .iter()
+                .map(|row| &data[row.index])
+                .filter(|frame| visible || self.selected.contains(&frame.timestamp))
+                .collect();
+            let text =
+                frames_to_clipboard(&frames, self.copy_format, self.id_format, self.data_format);
+            ui.ctx().copy_text(text);
+            tracing::info!(target: "trace", "Copied {} frames to the clipboard", frames.len());
+        }
+        let scroll_row =
+            scroll_to.and_then(|time| rows.iter().position(|r| data[r.index].timestamp == time));
+        let current = self.search.current();
+        let marked = self.marked;
+        let protocol = self.protocol;
+        let show_detail = protocol != ProtocolMode::Raw;
+        let (id_format, data_format) = (self.id_format, self.data_format);
+        let row_height = ui.text_style_height(&egui::TextStyle::Body) + 4.0;
+        let modifiers = ui.input(|i| i.modifiers);
+        let selected = &self.selected;
+        let mut clicked_row = None;
+
+        let mut table = TableBuilder::new(ui)
+            .id_salt("data_table")
+            .striped(true)
+            .resizable(true)
+            // 依收發順序顯示時跟隨最新的訊框
+            .stick_to_bottom(self.sort.is_none())
+            .auto_shrink([false; 2])
+            .sense(egui::Sense::click())
+            .cell_layout(egui::Layout::left_to_right(egui::Align::Center))
+            .column(Column::initial(180.0).at_least(60.0))
+            .column(Column::initial(90.0).at_least(40.0))
+            .column(Column::initial(70.0).at_least(40.0))
+            .column(Column::auto())
+            .column(Column::auto())
+            .column(Column::initial(90.0).at_least(40.0))
+            .column(Column::auto());
+        table = if show_detail {
+            table
+                .column(Column::initial(200.0).at_least(40.0))
+                .column(Column::remainder().at_least(60.0))
+        } else {
+            table.column(Column::remainder().at_least(60.0))
+        };
+        if let Some(row) = scroll_row {
+            table = table.scroll_to_row(row, Some(egui::Align::Center));
+        }
+        table
+            .header(row_height + 2.0, |mut header| {
+                let columns = [
+                    ("Time", Some(SortColumn::Time)),
+                    ("Rel. (s)", None),
+                    ("Δt ID (ms)", None),
+                    ("Dir", None),
+                    ("CH", Some(SortColumn::Channel)),
+                    ("ID", Some(SortColumn::Id)),
+                    ("DLC", Some(SortColumn::Dlc)),
+                    ("Data", Some(SortColumn::Data)),
+                    ("Detail", None),
+                ];
+                let count = if show_detail { 9 } else { 8 };
+                for (label, column) in columns.into_iter().take(count) {
+                    header.col(|ui| match column {
+                        Some(column) => {
+                            let sorted = self.sort.filter(|(c, _)| *c == column);
+                            let text = match sorted {
+                                Some((_, false)) => format!("{} ⏶", label),
+                                Some((_, true)) => format!("{} ⏷", label),
+                                None => label.to_string(),
+                            };
+                            if ui
+                                .selectable_label(sorted.is_some(), egui::RichText::new(text).strong())
+                                .on_hover_text("Click to sort, click again to reverse, third click restores arrival order")
+                                .clicked()
+                            {
+                                self.sort = match sorted {
+                                    None => Some((column, false)),
+                                    Some((_, false)) => Some((column, true)),
+                                    Some((_, true)) => None,
+                                };
+                            }
+                        }
+                        None => {
+                            ui.strong(label);
+                        }
+                    });
+                }
+            })
+            .body(|body| {
+                body.rows(row_height, rows.len(), |mut table_row| {
+                    let row = &rows[table_row.index()];
+                    let frame = &data[row.index];
+                    table_row.set_selected(
+                        Some(frame.timestamp) == current
+                            || Some(frame.timestamp) == marked
+                            || selected.contains(&frame.timestamp),
+                    );
+                    let cell = |text: String| {
+                        let text = egui::RichText::new(text);
+                        if row.hit {
+                            text.background_color(egui::Color32::from_rgb(40, 40, 90))
+                        } else {
+                            text
+                        }
+                    };
+                    table_row.col(|ui| {
+                        let response = ui.label(cell(format_timestamp(frame.timestamp)));
+                        if let Some(device_us) = frame.device_timestamp {
+                            response.on_hover_text(format!(
+                                "Hardware timestamp: {:.3} ms",
+                                device_us as f64 / 1000.0
+                            ));
+                        }
+                    });
+                    table_row.col(|ui| {
+                        let relative = capture_start.map(|start| {
+                            format!(
+                                "{:.6}",
+                                frame.timestamp.saturating_sub(start) as f64 / 1_000_000.0
+                            )
+                        });
+                        ui.label(cell(relative.unwrap_or_default()));
+                    });
+                    table_row.col(|ui| {
+                        let delta = row
+                            .delta_us
+                            .map(|delta| format!("{:.3}", delta as f64 / 1000.0));
+                        ui.label(cell(delta.unwrap_or_default()));
+                    });
+                    table_row.col(|ui| {
+                        ui.label(cell(
+                            match frame.direction {
+                                FrameDirection::Rx => "RX",
+                                FrameDirection::Tx => "TX",
+                            }
+                            .to_string(),
+                        ));
+                    });
+                    table_row.col(|ui| {
+                        ui.label(cell(frame.channel.to_string()));
+                    });
+                    table_row.col(|ui| {
+                        ui.label(cell(id_format.format_id(frame.id, frame.extended)).monospace());
+                    });
+                    table_row.col(|ui| {
+                        ui.label(cell(frame.dlc().to_string()));
+                    });
+                    table_row.col(|ui| {
+                        ui.label(cell(format_data(frame, data_format)).monospace());
+                    });
+                    if show_detail {
+                        table_row.col(|ui| {
+                            let detail = row.message.clone().or_else(|| protocol.describe(frame));
+                            ui.label(cell(detail.unwrap_or_default()));
+                        });
+                    }
+                    if table_row.response().clicked() {
+                        clicked_row = Some(table_row.index());
+                    }
+                });
+            });
+        if let Some(index) = clicked_row {
+            let timestamps: Vec<u64> = rows.iter().map(|row| data[row.index].timestamp).collect();
+            self.select(&timestamps, index, modifiers);
+        }
+    }
+
+    /// 依目前的條件重新過濾、解讀與排序整個緩衝區
+    fn rebuild_rows(&mut self, data: &VecDeque<CanFrame>) {
+        let reassemble = self.reassemble;
+        // 重組需要看到所有封包，不受顯示過濾影響；完成的訊息顯示在最後一個封包的位置
+        let mut transport = TransportReassembler::default();
+        let mut fast_packets = FastPacketReassembler::default();
+        // 時間差同樣以所有訊框計算，過濾掉的訊框仍算前一筆
+        let mut previous: HashMap<(u32, bool, u32), &CanFrame> = HashMap::new();
+        let mut rows: Vec<TraceRow> = data
+            .iter()
+            .enumerate()
+            .filter_map(|(index, f)| {
+                let delta_us = previous
+                    .insert((f.channel, f.extended, f.id), f)
+                    .map(|prev| f.delta_us(prev));
+                let message = match self.protocol {
+                    ProtocolMode::J1939 if reassemble => transport
+                        .process(f)
+                        .map(|message| j1939::describe_message(&message)),
+                    ProtocolMode::Nmea2000 if reassemble => fast_packets
+                        .process(f)
+                        .map(|message| nmea2000::describe_message(&message)),
+                    _ => None,
+                };
+                if !self.matches(f) {
+                    return None;
+                }
+                let hidden = reassemble
+                    && match self.protocol {
+                        ProtocolMode::J1939 => j1939::is_transport(f),
+                        ProtocolMode::Nmea2000 => nmea2000::is_fast_packet(f),
+                        _ => false,
+                    };
+                if message.is_none() && hidden {
+                    return None;
+                }
+                // 搜尋比對的文字與單行格式相同，沒有搜尋時省略格式化
+                let hit = self.search.is_active() && {
+                    let text = match message.clone().or_else(|| self.protocol.describe(f)) {
+                        Some(detail) => format!("{} | {}", format_frame(f), detail),
+                        None => format_frame(f),
+                    };
+                    self.search.matches(f, &text)
+                };
+                Some(TraceRow {
+                    index,
+                    message,
+                    delta_us,
+                    hit,
+                })
+            })
+            .collect();
+        self.cache.hits = rows
+            .iter()
+            .filter(|row| row.hit)
+            .map(|row| data[row.index].timestamp)
+            .collect();
+        if let Some((column, descending)) = self.sort {
+            // 穩定排序，相同鍵值的訊框維持收發順序
+            rows.sort_by(|a, b| {
+                let order = column.compare(&data[a.index], &data[b.index]);
+                if descending {
+                    order.reverse()
+                } else {
+                    order
+                }
+            });
+        }
+        self.cache.rows = rows;
+    }
+
+    /// 點選一列：一般點選只選這列，Ctrl 切換，Shift 選取到上次點選的列；
+    /// `rows` 為目前顯示順序的時間戳記
+    fn select(&mut self, rows: &[u64], index: usize, modifiers: egui::Modifiers) {
+        let timestamp = rows[index];
+        let anchor = self
+            .anchor
+            .and_then(|anchor| rows.iter().position(|&t| t == anchor));
+        match anchor {
+            Some(anchor) if modifiers.shift => {
+                if !modifiers.command {
+                    self.selected.clear();
+                }
+                let range = anchor.min(index)..=anchor.max(index);
+                self.selected.extend(&rows[range]);
+                return;
+            }
+            _ if modifiers.command => {
+                if !self.selected.remove(&timestamp) {
+                    self.selected.insert(timestamp);
+                }
+            }
+            _ => {
+                self.selected.clear();
+                self.selected.insert(timestamp);
+            }
+        }
+        self.anchor = Some(timestamp);
+    }
+
+    fn show_format(&mut self, ui: &mut egui::Ui) {
+        ui.label("ID:");
+        egui::ComboBox::from_id_salt(ui.id().with("id_format"))
+            .width(60.0)
+            .selected_text(self.id_format.label())
+            .show_ui(ui, |ui| {
+                for format in &ValueFormat::ALL[..3] {
+                    ui.selectable_value(&mut self.id_format, *format, format.label());
+                }
+            });
+        ui.label("Data:");
+        egui::ComboBox::from_id_salt(ui.id().with("data_format"))
+            .width(60.0)
+            .selected_text(self.data_format.label())
+            .show_ui(ui, |ui| {
+                for format in ValueFormat::ALL {
+                    ui.selectable_value(&mut self.data_format, format, format.label());
+                }
+            });
+    }
+
+    /// 類似 PCAN-View 的接收清單：每個 ID 一列，剛改變的位元組短暫標示
+    fn show_grouped(&self, ui: &mut egui::Ui, list: &ReceiveList) {
+        let now = now_micros();
+        let highlight = egui::Color32::from_rgb(90, 70, 0);
+        egui::ScrollArea::vertical()
+            .id_salt("grouped_scroll_area")
+            .auto_shrink([false; 2])
+            .show(ui, |ui| {
+                egui::Grid::new(ui.id().with("grouped"))
+                    .striped(true)
+                    .num_columns(7)
+                    .show(ui, |ui| {
+                        for header in ["", "CH", "ID", "DLC", "Data", "Count", "Cycle (ms)"] {
+                            ui.strong(header);
+                        }
+                        ui.end_row();
+                        for entry in list.entries().filter(|e| self.matches(&e.frame)) {
+                            let frame = &entry.frame;
+                            ui.label(match frame.direction {
+                                FrameDirection::Rx => "RX",
+                                FrameDirection::Tx => "TX",
+                            });
+                            ui.label(frame.channel.to_string());
+                            ui.monospace(self.id_format.format_id(frame.id, frame.extended));
+                            ui.label(frame.dlc().to_string());
+                            if frame.rtr {
+                                ui.weak("RTR");
+                            } else {
+                                ui.horizontal(|ui| {
+                                    // ASCII 字元連續顯示，較易辨識字串
+                                    ui.spacing_mut().item_spacing.x =
+                                        if self.data_format == ValueFormat::Ascii {
+                                            0.0
+                                        } else {
+                                            4.0
+                                        };
+                                    for (index, byte) in frame.payload().iter().enumerate() {
+                                        let text = egui::RichText::new(
+                                            self.data_format.format_byte(*byte),
+                                        )
+                                        .monospace();
+                                        let text = if entry.byte_changed(
+                                            index,
+                                            now,
+                                            CHANGE_HIGHLIGHT_US,
+                                        ) {
+                                            text.background_color(highlight)
+                                        } else {
+                                            text
+                                        };
+                                        ui.label(text);
+                                    }
+                                });
+                            }
+                            ui.label(entry.count.to_string());
+                            ui.label(match entry.cycle_us {
+                                Some(cycle) => format!("{:.1}", cycle as f64 / 1000.0),
+                                None => String::new(),
+                            });
+                            ui.end_row();
+                        }
+                    });
+            });
+    }
+}
@@ -0,0 +1,142 @@
+use crate::can::cantypes::CanFrame;
+use crate::can::logfile::{export_frames, ExportFormat};
+use crate::can::session::SessionInfo;
+use crate::can::trigger::{TriggerCapture, TriggerConfig, TriggerState};
+use crate::ui::format_timestamp;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::Mutex;
+
+/// 觸發擷取面板：設定觸發條件、觸發前緩衝與停止條件，並匯出擷取結果
+#[derive(Default)]
+pub struct TriggerPanel {
+    config: TriggerConfig,
+    error: Option<String>,
+}
+
+impl TriggerPanel {
+    pub fn show(
+        &mut self,
+        ui: &mut egui::Ui,
+        trigger: &Mutex<TriggerCapture>,
+        session: &SessionInfo,
+    ) {
+        let mut trigger = trigger.lock().unwrap();
+        let state = trigger.state();
+        let editable = matches!(state, TriggerState::Idle | TriggerState::Done);
+        ui.add_enabled_ui(editable, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Trigger:");
+                ui.add(
+                    egui::TextEdit::singleline(&mut self.config.condition)
+                        .hint_text("id == 0x123 && data[0] == 0xFF")
+                        .desired_width(f32::INFINITY),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Keep before trigger:");
+                ui.add(
+                    egui::DragValue::new(&mut self.config.pre_trigger)
+                        .range(0..=1_000_000)
+                        .suffix(" frames"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Stop after:");
+                ui.add(
+                    egui::DragValue::new(&mut self.config.stop_frames)
+                        .range(0..=1_000_000)
+                        .suffix(" frames"),
+                );
+                ui.label("or");
+                ui.add(
+                    egui::DragValue::new(&mut self.config.stop_seconds)
+                        .range(0.0..=86_400.0)
+                        .speed(0.1)
+                        .suffix(" s"),
+                );
+                ui.weak("(0 = no limit)");
+            });
+        });
+        if let Some(error) = &self.error {
+            ui.colored_label(egui::Color32::RED, error);
+        }
+
+        ui.separator();
+        let mut save = false;
+        match state {
+            TriggerState::Idle | TriggerState::Done => {
+                if state == TriggerState::Done {
+                    let captured = trigger.captured().len();
+                    let (time, index) = trigger.trigger().unwrap_or_default();
+                    ui.label(format!(
+                        "Triggered at {}: {} frames ({} before trigger)",
+                        format_timestamp(time),
+                        captured,
+                        index
+                    ));
+                }
+                ui.horizontal(|ui| {
+                    if ui.button("Arm").clicked() {
+                        self.error = trigger.arm(self.config.clone()).err();
+                    }
+                    save = state == TriggerState::Done && ui.button("Save...").clicked();
+                });
+            }
+            TriggerState::Armed => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!(
+                        "Waiting for trigger, {} frames buffered",
+                        trigger.pre_trigger_len()
+                    ));
+                });
+                if ui.button("Disarm").clicked() {
+                    trigger.disarm();
+                }
+            }
+            TriggerState::Triggered => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label(format!(
+                        "Triggered, {} frames recorded",
+                        trigger.captured().len()
+                    ));
+                });
+                if ui.button("Stop").clicked() {
+                    trigger.stop();
+                }
+            }
+        }
+        if save {
+            // 選擇檔案期間不佔用鎖，避免阻塞接收執行緒
+            let frames = trigger.captured().to_vec();
+            drop(trigger);
+            save_capture(&frames, session);
+        }
+    }
+}
+
+fn save_capture(frames: &[CanFrame], session: &SessionInfo) {
+    let now = chrono::Local::now();
+    let default_name = format!("trigger_{}.log", now.format("%Y%m%d_%H%M%S"));
+    let Some(path) = FileDialog::new()
+        .add_filter("candump log", &["log"])
+        .add_filter("csv", &["csv"])
+        .add_filter("Vector ASC", &["asc"])
+        .add_filter("PEAK TRC", &["trc"])
+        .add_filter("Vector BLF", &["blf"])
+        .set_file_name(&default_name)
+        .save_file()
+    else {
+        return;
+    };
+    let path = path.to_string_lossy();
+    match export_frames(&path, frames, ExportFormat::from_path(&path), session) {
+        Ok(count) => {
+            tracing::info!(target: "trigger", path = %path, count, "Saved triggered capture")
+        }
+        Err(e) => tracing::error!(target: "trigger", "{}", e),
+    }
+}
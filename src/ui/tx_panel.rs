@@ -0,0 +1,234 @@
+use crate::can::canbus::SharedCan;
+use crate::can::cantypes::{dlc_to_len, CanFrame, MAX_STANDARD_ID};
+use crate::can::txmacro::{self, MacroRecorder, TxMacro};
+use crate::ui::{parse_hex_bytes, parse_hex_u32};
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+/// 手動傳送與巨集錄製/重播面板
+pub struct TxPanel {
+    channel: u32,
+    id_text: String,
+    /// 資料不足 DLC 長度時補 0
+    dlc: u8,
+    data_text: String,
+    /// 29-bit 延伸 ID；ID 超過 11 bits 時一律以延伸 ID 送出
+    extended: bool,
+    rtr: bool,
+    fd: bool,
+    brs: bool,
+    recorder: Option<MacroRecorder>,
+    macro_name: String,
+    macros: Vec<TxMacro>,
+    playing: Option<Arc<AtomicBool>>,
+}
+
+impl Default for TxPanel {
+    fn default() -> Self {
+        Self {
+            channel: 0,
+            id_text: "0x100".to_string(),
+            dlc: 8,
+            data_text: "00 00 00 00 00 00 00 00".to_string(),
+            extended: false,
+            rtr: false,
+            fd: false,
+            brs: false,
+            recorder: None,
+            macro_name: String::new(),
+            macros: Vec::new(),
+            playing: None,
+        }
+    }
+}
+
+impl TxPanel {
+    pub fn show(&mut self, ui: &mut egui::Ui, can_app: &SharedCan) {
+        ui.heading("Send Frame");
+        ui.horizontal(|ui| {
+            ui.label("Channel:");
+            ui.add(egui::DragValue::new(&mut self.channel));
+        });
+        ui.horizontal(|ui| {
+            ui.label("ID (hex):");
+            ui.text_edit_singleline(&mut self.id_text);
+            ui.checkbox(&mut self.extended, "Extended");
+        });
+        ui.horizontal(|ui| {
+            ui.label("DLC:");
+            let max_dlc = if self.fd { 15 } else { 8 };
+            self.dlc = self.dlc.min(max_dlc);
+            ui.add(egui::DragValue::new(&mut self.dlc).range(0..=max_dlc));
+            if self.dlc > 8 {
+                ui.weak(format!("= {} bytes", dlc_to_len(self.dlc)));
+            }
+        });
+        ui.add_enabled_ui(!self.rtr, |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Data (hex):");
+                ui.text_edit_singleline(&mut self.data_text);
+            });
+        });
+        ui.horizontal(|ui| {
+            // CAN FD 沒有遠端訊框
+            if self.fd {
+                self.rtr = false;
+            }
+            ui.add_enabled(!self.fd, egui::Checkbox::new(&mut self.rtr, "RTR"));
+            ui.checkbox(&mut self.fd, "CAN FD");
+            ui.add_enabled(self.fd, egui::Checkbox::new(&mut self.brs, "BRS"));
+        });
+        if ui.button("Send").clicked() {
+            self.send_manual(can_app);
+        }
+
+        ui.separator();
+        self.show_macros(ui, can_app);
+    }
+
+    fn send_manual(&mut self, can_app: &SharedCan) {
+        let len = dlc_to_len(self.dlc);
+        let data = if self.rtr {
+            Ok(Vec::new())
+        } else {
+            parse_hex_bytes(&self.data_text)
+        };
+        let mut frame = match (parse_hex_u32(&self.id_text), data) {
+            (Ok(id), _) if id > 0x1FFF_FFFF => {
+                tracing::error!(target: "tx", "ID 0x{:X} exceeds 29 bits", id);
+                return;
+            }
+            (Ok(id), _) if self.rtr => CanFrame::new_remote(self.channel, id, self.dlc),
+            (Ok(id), Ok(mut data)) if data.len() <= len => {
+                data.resize(len, 0);
+                if self.fd {
+                    CanFrame::new_fd(self.channel, id, &data, self.brs)
+                } else {
+                    CanFrame::new(self.channel, id, &data)
+                }
+            }
+            (Ok(_), Ok(data)) => {
+                tracing::error!(
+                    target: "tx",
+                    "{} data bytes do not fit DLC {} ({} bytes)",
+                    data.len(),
+                    self.dlc,
+                    len
+                );
+                return;
+            }
+            (Err(e), _) | (_, Err(e)) => {
+                tracing::error!(target: "tx", "{}", e);
+                return;
+            }
+        };
+        frame.extended = self.extended || frame.id > MAX_STANDARD_ID;
+        match can_app.send_frame(&frame) {
+            Ok(()) => {
+                if let Some(recorder) = self.recorder.as_mut() {
+                    recorder.record(frame);
+                }
+            }
+            Err(e) => tracing::error!(target: "tx", "Send failed: {}", e),
+        }
+    }
+
+    fn show_macros(&mut self, ui: &mut egui::Ui, can_app: &SharedCan) {
+        ui.heading("TX Macros");
+        let recorded = self.recorder.as_ref().map(|r| r.len());
+        match recorded {
+            None => {
+                if ui.button("Start Recording").clicked() {
+                    self.recorder = Some(MacroRecorder::default());
+                }
+            }
+            Some(count) => {
+                ui.label(format!("Recording... {} frame(s)", count));
+                ui.horizontal(|ui| {
+                    ui.label("Name:");
+                    ui.text_edit_singleline(&mut self.macro_name);
+                });
+                ui.horizontal(|ui| {
+                    if ui.button("Save Macro").clicked() {
+                        if let Some(recorder) = self.recorder.take() {
+                            if recorder.is_empty() {
+                                tracing::warn!(target: "macro", "Nothing recorded");
+                            } else {
+                                let name = if self.macro_name.trim().is_empty() {
+                                    format!("Macro {}", self.macros.len() + 1)
+                                } else {
+                                    self.macro_name.trim().to_string()
+                                };
+                                self.macros.push(recorder.finish(&name));
+                                self.macro_name.clear();
+                            }
+                        }
+                    }
+                    if ui.button("Discard").clicked() {
+                        self.recorder = None;
+                    }
+                });
+            }
+        }
+
+        let is_playing = self
+            .playing
+            .as_ref()
+            .is_some_and(|flag| flag.load(Ordering::SeqCst));
+        if is_playing && ui.button("Stop Playback").clicked() {
+            if let Some(flag) = self.playing.as_ref() {
+                flag.store(false, Ordering::SeqCst);
+            }
+        }
+
+        let mut remove_index = None;
+        for (index, tx_macro) in self.macros.iter().enumerate() {
+            ui.horizontal(|ui| {
+                let play = ui.add_enabled(!is_playing, egui::Button::new("▶"));
+                if play.clicked() {
+                    self.playing = Some(txmacro::play_macro(
+                        tx_macro.clone(),
+                        can_app.clone(),
+                        move |msg| tracing::info!(target: "macro", "{}", msg),
+                    ));
+                }
+                ui.label(format!(
+                    "{} ({} steps)",
+                    tx_macro.name,
+                    tx_macro.steps.len()
+                ));
+                if ui.small_button("🗑").clicked() {
+                    remove_index = Some(index);
+                }
+            });
+        }
+        if let Some(index) = remove_index {
+            self.macros.remove(index);
+        }
+
+        ui.horizontal(|ui| {
+            if ui.button("Save Macros").clicked() {
+                if let Some(path) = FileDialog::new().save_file() {
+                    if let Err(e) = txmacro::save_macros(path.to_str().unwrap(), &self.macros) {
+                        tracing::error!(target: "macro", "Failed to save macros: {}", e);
+                    }
+                }
+            }
+            if ui.button("Load Macros").clicked() {
+                if let Some(path) = FileDialog::new().pick_file() {
+                    match txmacro::load_macros(path.to_str().unwrap()) {
+                        Ok(macros) => self.macros.extend(macros),
+                        Err(e) => {
+                            tracing::error!(target: "macro", "Failed to load macros: {}", e);
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
@@ -0,0 +1,57 @@
+use crate::can::canbus::SharedCan;
+use crate::can::txsequence::{SequencePlayback, TxSequence};
+
+use eframe::egui;
+use std::sync::Mutex;
+
+/// 設定檔 `sequences` 區塊中的傳送序列清單，可逐一啟動或停止
+pub fn show_tx_sequences(
+    ui: &mut egui::Ui,
+    sequences: &[TxSequence],
+    playback: &Mutex<Option<SequencePlayback>>,
+    can_app: &SharedCan,
+) {
+    let mut playback = playback.lock().unwrap();
+    let running = playback.as_ref().is_some_and(|p| p.is_running());
+    if let Some(ref current) = *playback {
+        ui.horizontal(|ui| {
+            let (sent, total) = current.progress();
+            if running {
+                ui.spinner();
+                ui.label(format!("{}: {}/{} frames", current.name(), sent, total));
+                if ui.button("Stop").clicked() {
+                    current.stop();
+                }
+            } else {
+                ui.label(format!(
+                    "Last run '{}': {}/{} frames sent",
+                    current.name(),
+                    sent,
+                    total
+                ));
+            }
+        });
+        ui.separator();
+    }
+    if sequences.is_empty() {
+        ui.weak("Define TX sequences in the YAML config (sequences: send, wait, wait_for, repeat)");
+        return;
+    }
+    for sequence in sequences {
+        ui.horizontal(|ui| {
+            if ui.add_enabled(!running, egui::Button::new("▶")).clicked() {
+                *playback = Some(SequencePlayback::start(
+                    sequence.clone(),
+                    can_app.clone(),
+                    move |msg| tracing::info!(target: "sequence", "{}", msg),
+                ));
+            }
+            ui.label(format!(
+                "{} ({} steps, {} frames)",
+                sequence.name,
+                sequence.steps.len(),
+                sequence.frame_count()
+            ));
+        });
+    }
+}
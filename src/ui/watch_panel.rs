@@ -0,0 +1,110 @@
+use crate::can::cantypes::now_micros;
+use crate::can::signals::SignalTable;
+use crate::can::units::DisplayUnits;
+
+use eframe::egui;
+use rfd::FileDialog;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+fn format_value(value: Option<f64>) -> String {
+    value
+        .map(|v| format!("{:.3}", v))
+        .unwrap_or_else(|| "-".to_string())
+}
+
+/// 單位選單，選擇結果寫回 `units`
+pub fn unit_selector(ui: &mut egui::Ui, units: &mut DisplayUnits, key: &str) {
+    let options: Vec<String> = units.options(key).iter().map(|s| s.to_string()).collect();
+    if options.len() < 2 {
+        ui.label(units.unit(key));
+        return;
+    }
+    let mut current = units.unit(key).to_string();
+    egui::ComboBox::from_id_salt(("unit", key))
+        .selected_text(&current)
+        .show_ui(ui, |ui| {
+            for option in options {
+                ui.selectable_value(&mut current, option.clone(), option);
+            }
+        });
+    if current != units.unit(key) {
+        units.select(key, &current);
+    }
+}
+
+/// 訊號監看清單：顯示目前值與 min/max/avg 統計，數值以選擇的單位顯示；
+/// `alarmed` 中的訊號以紅色顯示
+pub fn show_watch_list(
+    ui: &mut egui::Ui,
+    signals: &Mutex<SignalTable>,
+    units: &Mutex<DisplayUnits>,
+    alarmed: &HashSet<String>,
+) {
+    ui.horizontal(|ui| {
+        if ui.button("Reset All Stats").clicked() {
+            signals.lock().unwrap().reset_all(now_micros());
+        }
+        if ui.button("Export CSV").clicked() {
+            if let Some(path) = FileDialog::new()
+                .add_filter("csv", &["csv"])
+                .set_file_name("signals.csv")
+                .save_file()
+            {
+                if let Err(e) = signals
+                    .lock()
+                    .unwrap()
+                    .export_csv(path.to_str().unwrap(), &units.lock().unwrap())
+                {
+                    tracing::error!(target: "watch", "Failed to export CSV: {}", e);
+                }
+            }
+        }
+    });
+    ui.separator();
+
+    let mut reset_key = None;
+    let table = signals.lock().unwrap();
+    let mut units = units.lock().unwrap();
+    egui::ScrollArea::vertical().show(ui, |ui| {
+        egui::Grid::new("watch_list")
+            .striped(true)
+            .num_columns(8)
+            .show(ui, |ui| {
+                for header in ["Signal", "Value", "Unit", "Min", "Max", "Avg", "N", ""] {
+                    ui.strong(header);
+                }
+                ui.end_row();
+                for state in table.signals() {
+                    let convert = |v: f64| units.convert(&state.key, v);
+                    let (a, b) = (convert(state.min), convert(state.max));
+                    let value = state.value.map(convert);
+                    let mean = state.mean().map(convert);
+                    ui.label(&state.key);
+                    let text = match state.label {
+                        Some(ref label) => format!("{} ({})", label, format_value(value)),
+                        None => format_value(value),
+                    };
+                    if alarmed.contains(&state.key) {
+                        ui.colored_label(egui::Color32::RED, text);
+                    } else {
+                        ui.label(text);
+                    }
+                    unit_selector(ui, &mut units, &state.key);
+                    ui.label(format_value((state.count > 0).then_some(a.min(b))));
+                    ui.label(format_value((state.count > 0).then_some(a.max(b))));
+                    ui.label(format_value(mean));
+                    ui.label(state.count.to_string());
+                    if ui.small_button("Reset").clicked() {
+                        reset_key = Some(state.key.clone());
+                    }
+                    ui.end_row();
+                }
+            });
+    });
+    drop(units);
+    drop(table);
+    if let Some(key) = reset_key {
+        signals.lock().unwrap().reset(&key, now_micros());
+    }
+}
@@ -0,0 +1,4408 @@
+//! 以模擬介面驅動完整流程：接收 → 解碼 → 統計 → 匯出
+
+use can_tool::can::alarm::{AlarmEngine, AlarmRule};
+use can_tool::can::autoresponse::{AutoResponder, ResponseRule};
+use can_tool::can::bit_timing::{compute_sja1000_timing, Sja1000Timing};
+use can_tool::can::blackbox::{BlackBoxConfig, BlackBoxRecorder};
+use can_tool::can::blf;
+use can_tool::can::broadcast::UdpBroadcaster;
+use can_tool::can::bus_state::{BusState, BusStatusBoard, ControllerStatus};
+use can_tool::can::canbus::{
+    pcan_bus_state, pcan_error_kind, CanInterface, SharedCan, CHANNEL_BLOCK,
+};
+use can_tool::can::canopen::{self, CanOpenMonitor, CobKind, NmtState};
+use can_tool::can::cantypes::{
+    pcan_channel_handles, pcan_channel_name, CanFrame, FrameDirection, PcanBaudRate,
+    PcanChannelInformation, PcanFdBitrate, PcanTimestamp, VciCanBaudRate,
+};
+use can_tool::can::capture::CaptureStore;
+use can_tool::can::capture_db::{self, CaptureDb, CaptureQuery};
+use can_tool::can::config::{self, ByteOrder, CanbusConfigEntry, ChecksumKind};
+use can_tool::can::conformance::{checksum, CheckKind, CheckResult, ConformanceSuite};
+use can_tool::can::csv_schedule::load_csv_schedule;
+use can_tool::can::dbc::{Dbc, DbcDecoder, Multiplex};
+use can_tool::can::eds::{self, Eds};
+use can_tool::can::events::{EventCategory, EventLog, Severity};
+use can_tool::can::filter::FrameFilter;
+use can_tool::can::gateway::{
+    parse_remaps, ForwardLatency, Gateway, GatewayConfig, GatewayDirection, LATENCY_BUCKETS_US,
+};
+use can_tool::can::gsusb::{self, BitTimingConst};
+use can_tool::can::id_filter::{self, FilterMode, IdFilter, IdFilterRule};
+use can_tool::can::j1939::{self, J1939Id, TransportReassembler};
+use can_tool::can::j2534;
+use can_tool::can::latency::{LatencyPair, LatencyTracker};
+use can_tool::can::log_event::{Language, LogEvent};
+use can_tool::can::log_index::LogIndex;
+use can_tool::can::logfile::{
+    self, export_frames, format_candump_line, parse_candump_line, ExportFormat,
+};
+use can_tool::can::mdf;
+use can_tool::can::monitor::BusMonitor;
+use can_tool::can::nmea2000::{self, FastPacketReassembler};
+use can_tool::can::obd::{self, ObdMonitor};
+use can_tool::can::plot::{self, SignalPlot};
+use can_tool::can::presets::PCAN_FD_BITRATES;
+use can_tool::can::receive_list::ReceiveList;
+use can_tool::can::reconnect::ReconnectWatchdog;
+use can_tool::can::remote::{RemoteCanApp, RemoteServer, CLIENT_QUEUE_LEN};
+use can_tool::can::replay::{self, LogReplay, ReplayState};
+use can_tool::can::sampler::FixedRateSampler;
+use can_tool::can::scheduler::{RateLimit, RateLimiter};
+use can_tool::can::sdo::{SdoStep, SdoTransfer};
+use can_tool::can::search::{BytePattern, SearchQuery};
+use can_tool::can::selftest::{run_self_test, SelfTest, SelfTestStatus, SELF_TEST_ID};
+use can_tool::can::sequence::SequenceValidator;
+use can_tool::can::session::SessionInfo;
+use can_tool::can::signals::{encode_value, extract_value, SignalTable};
+use can_tool::can::silence::{SilenceDetector, SilentGap};
+use can_tool::can::sim::{PayloadPattern, SimCanApp, SimMessage, TrafficGenerator};
+use can_tool::can::slcan::{self, LineSplitter};
+use can_tool::can::snapshot::Snapshot;
+use can_tool::can::stats::{frame_bits, BusStatistics};
+use can_tool::can::timeline::TrafficTimeline;
+use can_tool::can::timesync::TimestampCorrector;
+use can_tool::can::trigger::{TriggerCapture, TriggerConfig, TriggerState};
+use can_tool::can::txmacro::{
+    load_macros, play_macro, save_macros, MacroRecorder, MacroStep, TxMacro,
+};
+use can_tool::can::txsequence::SequencePlayback;
+use can_tool::can::units::DisplayUnits;
+use can_tool::can::version::LibraryVersion;
+use can_tool::can::watch::{WatchExpr, WatchState, WatchTransition};
+use can_tool::can::wire::{decode_datagram, encode_datagram};
+use can_tool::ui::dashboard::Reading;
+use can_tool::ui::filter_box::TermFilter;
+use can_tool::ui::layout::ProfileLayout;
+use can_tool::ui::plot_export::{render_png, render_svg, save_plot, PlotImage};
+use can_tool::ui::report::{config_listing, signal_plot, CaptureReport};
+use can_tool::ui::trace_view::{frames_to_clipboard, CopyFormat};
+use can_tool::ui::{format_timestamp, ValueFormat};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+const CONFIG: &str = r#"
+version: 2
+components:
+  - type: Label
+    key: speed
+    unit: "km/h"
+  - type: Gauge
+    key: temp
+    min: -40
+    max: 120
+canbus_config:
+  - key: speed
+    id: 0x100
+    index: 0
+    len: 2
+    endian: little
+    type: uint16
+  - key: temp
+    id: 0x100
+    index: 2
+    len: 1
+    endian: big
+    type: int8
+"#;
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("can_tool_{}_{}", std::process::id(), name))
+}
+
+fn load_config(yaml: &str, name: &str) -> (config::Config, Vec<String>) {
+    let path = temp_path(name);
+    fs::write(&path, yaml).unwrap();
+    let loaded = config::load_config(path.to_str().unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+    loaded
+}
+
+/// 啟動模擬介面並回傳共用介面、注入端與接收端
+fn start_sim() -> (
+    SharedCan,
+    flume::Sender<CanFrame>,
+    flume::Receiver<CanFrame>,
+) {
+    let (log_tx, _log_rx) = flume::unbounded();
+    let (data_tx, data_rx) = flume::unbounded();
+    let sim = SimCanApp::new(true);
+    sim.open_device(log_tx.clone()).unwrap();
+    sim.start_receiving(log_tx, data_tx);
+    let injector = sim.injector();
+    let can_app = SharedCan::default();
+    can_app.attach("Simulated", Box::new(sim));
+    (can_app, injector, data_rx)
+}
+
+fn receive(data_rx: &flume::Receiver<CanFrame>, count: usize) -> Vec<CanFrame> {
+    (0..count)
+        .map(|_| data_rx.recv_timeout(Duration::from_secs(2)).unwrap())
+        .collect()
+}
+
+#[test]
+fn decodes_signals_and_counts_statistics() {
+    let (cfg, warnings) = load_config(CONFIG, "decode.yaml");
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let mut units = DisplayUnits::default();
+    units.load(&cfg.components);
+    let stats = Arc::new(Mutex::new(BusStatistics::default()));
+
+    let (can_app, injector, data_rx) = start_sim();
+    {
+        let stats = Arc::clone(&stats);
+        can_app.on_transmit(move |frame| stats.lock().unwrap().record_tx(frame));
+    }
+    // 0x0064 = 100 km/h，0xF6 = -10 °C
+    injector
+        .send(CanFrame::new(0, 0x100, &[0x64, 0x00, 0xF6]))
+        .unwrap();
+    can_app
+        .send_frame(&CanFrame::new(0, 0x100, &[0xC8, 0x00, 0x14]))
+        .unwrap();
+
+    for frame in receive(&data_rx, 2) {
+        signals.process(&frame);
+        stats.lock().unwrap().process(&frame);
+    }
+    can_app.lock()[0].app.stop_receiving();
+
+    let speed = signals.get("speed").unwrap();
+    assert_eq!(speed.value, Some(200.0));
+    assert_eq!(speed.min, 100.0);
+    assert_eq!(speed.count, 2);
+    assert_eq!(signals.get("temp").unwrap().min, -10.0);
+
+    units.select("speed", "mph");
+    let mph = units.convert("speed", 200.0);
+    assert!((mph - 124.27).abs() < 0.01, "{}", mph);
+
+    let stats = stats.lock().unwrap();
+    let channel = stats.channels().next().unwrap();
+    assert_eq!(channel.frames, 2);
+    assert_eq!(channel.tx_frames, 1);
+    assert_eq!(stats.ids().next().unwrap().count, 2);
+}
+
+#[test]
+fn watch_list_tracks_min_max_avg_since_reset() {
+    let (cfg, _) = load_config(CONFIG, "watch.yaml");
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let units = DisplayUnits::default();
+    let feed = |signals: &mut SignalTable, speed: u8, temp: i8, timestamp: u64| {
+        let mut frame = CanFrame::new(0, 0x100, &[speed, 0x00, temp as u8]);
+        frame.timestamp = timestamp;
+        signals.process(&frame);
+    };
+    let export = |signals: &SignalTable| {
+        let path = temp_path("watch_stats.csv");
+        signals.export_csv(path.to_str().unwrap(), &units).unwrap();
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        text
+    };
+    // 尚未收到值時統計為空白
+    assert_eq!(
+        export(&signals),
+        "key,unit,value,min,max,avg,samples,stats_since_us\nspeed,,,,,,0,0\ntemp,,,,,,0,0\n"
+    );
+
+    feed(&mut signals, 100, -10, 1_000);
+    feed(&mut signals, 40, 20, 2_000);
+    feed(&mut signals, 70, 5, 3_000);
+    let speed = signals.get("speed").unwrap();
+    assert_eq!(
+        (speed.value, speed.min, speed.max),
+        (Some(70.0), 40.0, 100.0)
+    );
+    assert_eq!(
+        (speed.mean(), speed.count, speed.stats_since),
+        (Some(70.0), 3, 1_000)
+    );
+    assert_eq!(
+        export(&signals),
+        "key,unit,value,min,max,avg,samples,stats_since_us\n\
+         speed,,70,40,100,70,3,1000\n\
+         temp,,5,-10,20,5,3,1000\n"
+    );
+
+    // 單一訊號重設後自重設時間起算，保留目前值；其他訊號不受影響
+    signals.reset("speed", 5_000);
+    let speed = signals.get("speed").unwrap();
+    assert_eq!(
+        (speed.value, speed.mean(), speed.count),
+        (Some(70.0), None, 0)
+    );
+    feed(&mut signals, 10, 0, 6_000);
+    let speed = signals.get("speed").unwrap();
+    assert_eq!(
+        (speed.min, speed.max, speed.count, speed.stats_since),
+        (10.0, 10.0, 1, 5_000)
+    );
+    assert_eq!(signals.get("temp").unwrap().count, 4);
+    signals.reset_all(7_000);
+    assert!(signals
+        .signals()
+        .all(|s| s.count == 0 && s.stats_since == 7_000));
+}
+
+#[test]
+fn watch_expressions_need_duration_and_clear_with_hysteresis() {
+    let expr = WatchExpr::parse("temp>90 and rpm >= 6000 || oil < 1.5 for 2s").unwrap();
+    assert_eq!(expr.min_duration_us, 2_000_000);
+    assert_eq!(expr.keys().collect::<Vec<_>>(), ["temp", "rpm", "oil"]);
+    let values = |temp: f64, rpm: f64, oil: f64| {
+        move |key: &str| match key {
+            "temp" => Some(temp),
+            "rpm" => Some(rpm),
+            "oil" => Some(oil),
+            _ => None,
+        }
+    };
+    // and 優先於 or
+    assert!(expr.eval_with(&values(95.0, 6000.0, 3.0), 0.0));
+    assert!(!expr.eval_with(&values(95.0, 5000.0, 3.0), 0.0));
+    assert!(expr.eval_with(&values(20.0, 0.0, 1.0), 0.0));
+    // 缺少的訊號視為不成立
+    assert!(!WatchExpr::parse("missing != 0")
+        .unwrap()
+        .eval_with(&values(0.0, 0.0, 0.0), 0.0));
+
+    for (text, error) in [
+        ("temp >", "Expected '<signal> <op> <value>' in 'temp >'"),
+        ("temp => 5", "Unknown operator '=' in 'temp => 5'"),
+        ("temp > hot", "Invalid number 'hot' in 'temp > hot'"),
+        (
+            "temp > 1 xor rpm < 2",
+            "Unexpected 'xor' in 'temp > 1 xor rpm < 2'",
+        ),
+        ("temp > 1 for 5h", "Unknown duration unit 'h'"),
+    ] {
+        assert_eq!(WatchExpr::parse(text).unwrap_err(), error);
+    }
+
+    // 持續 500 ms 才觸發；觸發後要降到 90 − 2 以下才解除
+    let mut state = WatchState::new(WatchExpr::parse("temp > 90 for 500ms").unwrap(), -2.0);
+    let temp = |value: f64| move |_: &str| Some(value);
+    assert_eq!(state.update_with(&temp(95.0), 0), None);
+    assert_eq!(state.update_with(&temp(80.0), 300_000), None);
+    assert_eq!(state.update_with(&temp(95.0), 400_000), None);
+    assert_eq!(state.update_with(&temp(95.0), 800_000), None);
+    assert_eq!(
+        state.update_with(&temp(95.0), 900_000),
+        Some(WatchTransition::Raised)
+    );
+    assert!(state.is_active());
+    assert_eq!(state.update_with(&temp(89.0), 1_000_000), None);
+    assert_eq!(
+        state.update_with(&temp(87.9), 1_100_000),
+        Some(WatchTransition::Cleared)
+    );
+    assert!(!state.is_active());
+    // 解除後重新計時
+    assert_eq!(state.update_with(&temp(95.0), 1_200_000), None);
+    assert_eq!(
+        state.update_with(&temp(95.0), 1_700_000),
+        Some(WatchTransition::Raised)
+    );
+}
+
+#[test]
+fn exports_signal_csv_in_display_units() {
+    let (cfg, _) = load_config(CONFIG, "csv.yaml");
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let mut units = DisplayUnits::default();
+    units.load(&cfg.components);
+    units.select("speed", "mph");
+
+    let mut frame = CanFrame::new(0, 0x100, &[0xA1, 0x00, 0x00]);
+    frame.timestamp = 1_000_000;
+    signals.process(&frame);
+
+    let path = temp_path("signals.csv");
+    signals.export_csv(path.to_str().unwrap(), &units).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let mut lines = text.lines();
+    assert_eq!(
+        lines.next(),
+        Some("key,unit,value,min,max,avg,samples,stats_since_us")
+    );
+    let speed: Vec<&str> = lines
+        .find(|l| l.starts_with("speed,"))
+        .unwrap()
+        .split(',')
+        .collect();
+    assert_eq!(speed[1], "mph");
+    assert!((speed[2].parse::<f64>().unwrap() - 100.04).abs() < 0.01);
+    assert_eq!(speed[6], "1");
+}
+
+#[test]
+fn exported_candump_log_round_trips_through_index() {
+    let (can_app, injector, data_rx) = start_sim();
+    let mut sent = Vec::new();
+    for i in 0..3000u32 {
+        let id = if i % 3 == 0 { 0x18FEF100 } else { 0x123 };
+        let mut frame = CanFrame::new(1, id, &i.to_le_bytes());
+        frame.timestamp = 1_700_000_000_000_000 + i as u64 * 1000;
+        sent.push(frame);
+        injector.send(frame).unwrap();
+    }
+    injector
+        .send(CanFrame::new_fd(1, 0x200, &[0xAA; 12], true))
+        .unwrap();
+    let received = receive(&data_rx, sent.len() + 1);
+    can_app.lock()[0].app.stop_receiving();
+    assert_eq!(&received[..sent.len()], &sent[..]);
+
+    let path = temp_path("trace.log");
+    let session = SessionInfo {
+        operator: "tester".to_string(),
+        notes: "first line\nsecond line".to_string(),
+        ..Default::default()
+    };
+    let written = export_frames(
+        path.to_str().unwrap(),
+        &received,
+        ExportFormat::Candump,
+        &session,
+    )
+    .unwrap();
+    assert_eq!(written, received.len());
+
+    let text = fs::read_to_string(&path).unwrap();
+    let parsed: Vec<CanFrame> = text.lines().filter_map(parse_candump_line).collect();
+    assert_eq!(parsed, received);
+
+    let index = LogIndex::build(&path, &AtomicU64::new(0), &AtomicBool::new(false)).unwrap();
+    assert_eq!(index.frame_count(), received.len() as u64);
+    assert_eq!(index.session(), &session);
+    assert_eq!(index.row_count(Some((1, 0x18FEF100))), 1000);
+    // 過濾後的第 500 筆應為第 1500 筆原始訊框
+    let (block, offset) = index.locate(500, Some((1, 0x18FEF100))).unwrap();
+    let frame = index
+        .read_block(block)
+        .unwrap()
+        .into_iter()
+        .filter(|f| f.id == 0x18FEF100)
+        .nth(offset)
+        .unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(frame, sent[1500]);
+}
+
+#[test]
+fn capture_store_exports_frames_beyond_the_buffer() {
+    let mut capture = CaptureStore::new(3000);
+    for i in 0..3500u32 {
+        capture.record(&CanFrame::new(0, 0x100 + i % 4, &i.to_le_bytes()));
+    }
+    assert_eq!(capture.len(), 3000);
+    assert_eq!(capture.dropped(), 500);
+    assert_eq!(
+        capture.frames().next().unwrap().payload(),
+        &500u32.to_le_bytes()
+    );
+
+    let path = temp_path("capture.csv");
+    let written = export_frames(
+        path.to_str().unwrap(),
+        capture.frames(),
+        ExportFormat::Csv,
+        &SessionInfo::default(),
+    )
+    .unwrap();
+    assert_eq!(written, 3000);
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let rows: Vec<&str> = text.lines().filter(|l| !l.starts_with('#')).collect();
+    assert_eq!(
+        rows[0],
+        "timestamp_us,channel,dir,id,protocol,brs,ext,rtr,len,data"
+    );
+    assert_eq!(rows.len(), 3001);
+    assert!(rows[1].ends_with(",0x100,Classic,false,false,false,4,F4 01 00 00"));
+
+    capture.clear();
+    assert!(capture.is_empty());
+    assert_eq!(capture.dropped(), 0);
+}
+
+#[test]
+fn exports_vector_asc_and_peak_trc_traces() {
+    let mut tx = CanFrame::new(1, 0x18FEF100, &[0xAA, 0xBB]);
+    tx.direction = FrameDirection::Tx;
+    tx.timestamp = 1_700_000_001_500_250;
+    let mut first = CanFrame::new(0, 0x123, &[1, 2, 3]);
+    first.timestamp = 1_700_000_000_000_000;
+    let mut remote = CanFrame::new_remote(0, 0x7DF, 8);
+    remote.timestamp = first.timestamp + 10_000;
+    let mut fd = CanFrame::new_fd(0, 0x200, &[0x11; 12], true);
+    fd.timestamp = first.timestamp + 20_000;
+    let frames = [first, remote, fd, tx];
+
+    assert_eq!(ExportFormat::from_path("trace.ASC"), ExportFormat::Asc);
+    assert_eq!(ExportFormat::from_path("trace.trc"), ExportFormat::Trc);
+
+    let base = first.timestamp;
+    assert_eq!(
+        logfile::format_asc_line(&first, base),
+        "   0.000000 1  123             Rx   d 3 01 02 03"
+    );
+    assert_eq!(
+        logfile::format_asc_line(&remote, base),
+        "   0.010000 1  7DF             Rx   r 8"
+    );
+    assert_eq!(
+        logfile::format_asc_line(&tx, base),
+        "   1.500250 2  18FEF100x       Tx   d 2 AA BB"
+    );
+    let asc_fd = logfile::format_asc_line(&fd, base);
+    assert!(asc_fd.starts_with("   0.020000 CANFD   1 Rx        200"));
+    assert!(asc_fd.contains(" 1 0 9 12 11 11 11 11 11 11 11 11 11 11 11 11 "));
+    assert!(asc_fd.contains("    3000 "));
+
+    assert_eq!(
+        logfile::format_trc_line(1, &first, base),
+        "      1             0.000 DT 1      0123 Rx -  3    01 02 03"
+    );
+    assert_eq!(
+        logfile::format_trc_line(2, &remote, base),
+        "      2            10.000 RR 1      07DF Rx -  8"
+    );
+    assert!(logfile::format_trc_line(3, &fd, base).contains(" FB 1      0200 Rx -  9    11 "));
+    assert_eq!(
+        logfile::format_trc_line(4, &tx, base),
+        "      4          1500.250 DT 2  18FEF100 Tx -  2    AA BB"
+    );
+
+    let session = SessionInfo {
+        operator: "tester".to_string(),
+        ..Default::default()
+    };
+    for (name, header, footer) in [
+        ("trace.asc", "date ", "End TriggerBlock"),
+        ("trace.trc", ";$FILEVERSION=2.1", "AA BB"),
+    ] {
+        let path = temp_path(name);
+        let path_text = path.to_str().unwrap();
+        let written = export_frames(
+            path_text,
+            &frames,
+            ExportFormat::from_path(path_text),
+            &session,
+        )
+        .unwrap();
+        assert_eq!(written, frames.len());
+        let text = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(text.starts_with(header));
+        assert!(text.trim_end().ends_with(footer));
+        assert!(text.contains("operator: tester"));
+    }
+}
+
+#[test]
+fn exports_and_reads_back_blf_files() {
+    let mut frames = Vec::new();
+    for i in 0..20_000u32 {
+        let mut frame = CanFrame::new(i % 2, 0x100 + i % 16, &i.to_le_bytes());
+        frame.timestamp = 1_700_000_000_123_456 + i as u64 * 250;
+        frames.push(frame);
+    }
+    let mut tx = CanFrame::new(1, 0x18FEF100, &[0xAA, 0xBB]);
+    tx.direction = FrameDirection::Tx;
+    let mut remote = CanFrame::new_remote(0, 0x7DF, 3);
+    let mut fd = CanFrame::new_fd(2, 0x1ABCDEF, &[0x11; 20], true);
+    fd.esi = true;
+    for (i, frame) in [&mut tx, &mut remote, &mut fd].into_iter().enumerate() {
+        frame.timestamp = 1_700_000_010_000_000 + i as u64;
+    }
+    frames.extend([tx, remote, fd]);
+
+    let path = temp_path("trace.blf");
+    let path_text = path.to_str().unwrap();
+    assert_eq!(ExportFormat::from_path(path_text), ExportFormat::Blf);
+    let written = export_frames(
+        path_text,
+        &frames,
+        ExportFormat::Blf,
+        &SessionInfo::default(),
+    )
+    .unwrap();
+    assert_eq!(written, frames.len());
+    let bytes = fs::read(&path).unwrap();
+    assert_eq!(&bytes[..4], b"LOGG");
+    assert_eq!(
+        u64::from_le_bytes(bytes[16..24].try_into().unwrap()),
+        bytes.len() as u64
+    );
+    assert_eq!(
+        u32::from_le_bytes(bytes[32..36].try_into().unwrap()),
+        frames.len() as u32
+    );
+    // 壓縮後應遠小於 candump 文字檔
+    assert!(bytes.len() < frames.len() * 10);
+
+    let read = blf::read_blf(path_text).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(read, frames);
+}
+
+#[test]
+fn extended_and_remote_frames_keep_their_flags() {
+    let mut low_extended = CanFrame::new(0, 0x123, &[1, 2]);
+    low_extended.extended = true;
+    let frames = [
+        CanFrame::new(0, 0x123, &[1, 2]),
+        low_extended,
+        CanFrame::new(1, 0x18FEF100, &[3]),
+        CanFrame::new_remote(0, 0x456, 4),
+        CanFrame::new_remote(0, 0x1ABCDE, 0),
+    ];
+    assert!(!frames[0].extended && frames[2].extended && frames[4].extended);
+    for frame in &frames {
+        let line = format_candump_line(frame);
+        assert_eq!(parse_candump_line(&line).as_ref(), Some(frame), "{}", line);
+        assert_eq!(
+            decode_datagram(&encode_datagram(frame)).as_ref(),
+            Some(frame)
+        );
+    }
+    assert!(format_candump_line(&frames[3]).ends_with("456#R4"));
+    assert_eq!(frames[3].dlc(), 4);
+}
+
+#[test]
+fn clearing_receive_buffer_drops_queued_frames() {
+    let sim = SimCanApp::new(false);
+    let injector = sim.injector();
+    let (log_tx, _log_rx) = flume::unbounded();
+    sim.open_device(log_tx.clone()).unwrap();
+    for i in 0..10u8 {
+        injector.send(CanFrame::new(0, 0x100, &[i])).unwrap();
+    }
+    sim.clear_receive_buffer().unwrap();
+    let (data_tx, data_rx) = flume::unbounded();
+    sim.start_receiving(log_tx, data_tx);
+    injector.send(CanFrame::new(0, 0x200, &[0xFF])).unwrap();
+    let received = receive(&data_rx, 1);
+    sim.stop_receiving();
+    assert_eq!(received[0].id, 0x200);
+    assert!(data_rx.is_empty());
+}
+
+#[test]
+fn parses_pcan_fd_bitrate_strings() {
+    let rates: Vec<(u32, u32)> = PCAN_FD_BITRATES
+        .iter()
+        .map(|(_, text)| PcanFdBitrate::parse(text).unwrap())
+        .map(|rate| (rate.nominal, rate.data))
+        .collect();
+    assert_eq!(
+        rates,
+        [
+            (500_000, 2_000_000),
+            (500_000, 4_000_000),
+            (1_000_000, 5_000_000),
+            (250_000, 2_000_000)
+        ]
+    );
+    let rate = PcanFdBitrate::parse(
+        "f_clock=40000000, nom_brp=1, nom_tseg1=59, nom_tseg2=20, nom_sjw=20, \
+         data_brp=1, data_tseg1=14, data_tseg2=5, data_sjw=5",
+    )
+    .unwrap();
+    assert_eq!((rate.nominal, rate.data), (500_000, 2_000_000));
+    assert!(PcanFdBitrate::parse("f_clock_mhz=80, nom_brp=2").is_err());
+    assert!(PcanFdBitrate::parse("nom_brp=2, nom_tseg1=63").is_err());
+    assert!(PcanFdBitrate::parse("f_clock_mhz=eighty").is_err());
+
+    let frame = CanFrame::new_fd(0, 0x123, &[0xAA; 20], true);
+    assert_eq!((frame.dlc(), frame.payload().len()), (11, 20));
+}
+
+#[test]
+fn names_pcan_channel_handles() {
+    assert_eq!(pcan_channel_name(0x51), "PCAN_USBBUS1");
+    assert_eq!(pcan_channel_name(0x509), "PCAN_USBBUS9");
+    assert_eq!(pcan_channel_name(0x510), "PCAN_USBBUS16");
+    assert_eq!(pcan_channel_name(0x41), "PCAN_PCIBUS1");
+    assert_eq!(pcan_channel_name(0x801), "PCAN_LANBUS1");
+    assert_eq!(pcan_channel_name(0x99), "PCAN 0x99");
+    let handles = pcan_channel_handles();
+    assert_eq!(handles.len(), 32);
+    assert!(handles
+        .iter()
+        .all(|&h| !pcan_channel_name(h).starts_with("PCAN 0x")));
+    // TPCANChannelInformation 的 C 佈局
+    assert_eq!(std::mem::size_of::<PcanChannelInformation>(), 52);
+}
+
+#[test]
+fn slcan_frames_round_trip_through_serial_lines() {
+    let frames = [
+        CanFrame::new(0, 0x123, &[0xDE, 0xAD, 0xBE, 0xEF]),
+        CanFrame::new(0, 0x18FEF100, &[]),
+        CanFrame::new_remote(0, 0x7FF, 8),
+        CanFrame::new_remote(0, 0x1ABCDE, 2),
+    ];
+    let mut stream = Vec::new();
+    for frame in &frames {
+        stream.extend_from_slice(slcan::encode_frame(frame).unwrap().as_bytes());
+        stream.push(b'\r');
+    }
+    assert!(stream.starts_with(b"t1234DEADBEEF\rT18FEF1000\r"));
+    // 轉接器的 ACK（`z`）與錯誤回應（BEL）混在資料流中
+    stream.extend_from_slice(b"z\r\x07t12");
+    let mut splitter = LineSplitter::default();
+    let (head, tail) = stream.split_at(7);
+    let mut lines = splitter.push(head);
+    lines.extend(splitter.push(tail));
+    let parsed: Vec<CanFrame> = lines.iter().filter_map(|l| slcan::parse_frame(l)).collect();
+    assert_eq!(parsed, frames);
+    assert!(slcan::encode_frame(&CanFrame::new_fd(0, 0x100, &[0; 12], false)).is_err());
+}
+
+#[test]
+fn gs_usb_host_frames_and_bit_timing() {
+    // candleLight（STM32F072）：48 MHz CAN 時脈
+    let limits = BitTimingConst {
+        fclk_can: 48_000_000,
+        tseg1_min: 1,
+        tseg1_max: 16,
+        tseg2_min: 1,
+        tseg2_max: 8,
+        sjw_max: 4,
+        brp_min: 1,
+        brp_max: 1024,
+        brp_inc: 1,
+        ..Default::default()
+    };
+    for kbps in [125, 250, 500, 1000] {
+        let t = gsusb::compute_bit_timing(&limits, kbps * 1000).unwrap();
+        let tq = 1 + t.prop_seg + t.phase_seg1 + t.phase_seg2;
+        assert_eq!(48_000_000 / (t.brp * tq), kbps * 1000);
+        assert_eq!(
+            (1 + t.prop_seg + t.phase_seg1) * 1000 / tq,
+            875,
+            "{}K",
+            kbps
+        );
+    }
+
+    let mut remote = CanFrame::new_remote(1, 0x1ABCDE, 3);
+    remote.direction = FrameDirection::Tx;
+    for frame in [CanFrame::new(1, 0x123, &[1, 2, 3]), remote] {
+        let buf = gsusb::encode_host_frame(&frame, 1, 7).unwrap();
+        assert_eq!(buf.len(), 20);
+        // echo_id 7 代表本機送出訊框的回送
+        let (decoded, timestamp) = gsusb::decode_host_frame(&buf).unwrap();
+        let mut expected = frame;
+        expected.direction = FrameDirection::Tx;
+        assert_eq!(decoded, expected);
+        assert_eq!(timestamp, None);
+    }
+    let mut rx = gsusb::encode_host_frame(&CanFrame::new(0, 0x100, &[9]), 0, u32::MAX).unwrap();
+    rx.extend_from_slice(&1234u32.to_le_bytes());
+    let (decoded, timestamp) = gsusb::decode_host_frame(&rx).unwrap();
+    assert_eq!(decoded.direction, FrameDirection::Rx);
+    assert_eq!(timestamp, Some(1234));
+}
+
+#[test]
+fn encodes_and_decodes_j2534_messages() {
+    for frame in [
+        CanFrame::new(0, 0x7E0, &[2, 0x01, 0x0C]),
+        CanFrame::new(0, 0x18DAF110, &[1, 2, 3, 4, 5, 6, 7, 8]),
+    ] {
+        let msg = j2534::encode_msg(&frame).unwrap();
+        assert_eq!(msg.data_size as usize, 4 + frame.payload().len());
+        assert_eq!(&msg.data[..4], &frame.id.to_be_bytes());
+        assert_eq!(j2534::decode_msg(&msg, 0), Some(frame));
+    }
+    assert!(j2534::encode_msg(&CanFrame::new_fd(0, 0x100, &[0; 12], true)).is_err());
+    assert!(j2534::encode_msg(&CanFrame::new_remote(0, 0x100, 2)).is_err());
+
+    // TX_MSG_TYPE（0x01）為本機送出訊框的回送
+    let mut echo = j2534::encode_msg(&CanFrame::new(0, 0x100, &[1])).unwrap();
+    echo.rx_status = 0x01;
+    assert_eq!(j2534::decode_msg(&echo, 0), None);
+}
+
+#[test]
+fn simulated_traffic_follows_configured_periods() {
+    let mut constant = SimMessage::new(0x300, 50, PayloadPattern::Constant);
+    constant.len = 3;
+    constant.data[..3].copy_from_slice(&[0xAA, 0xBB, 0xCC]);
+    let mut generator = TrafficGenerator::new(vec![
+        SimMessage::new(0x100, 10, PayloadPattern::Counter),
+        constant,
+    ]);
+    let frames = generator.poll(100_000);
+    let counters: Vec<&CanFrame> = frames.iter().filter(|f| f.id == 0x100).collect();
+    assert_eq!(counters.len(), 11);
+    assert_eq!(counters[5].payload(), &5u64.to_le_bytes());
+    assert_eq!(counters[5].timestamp, 50_000);
+    assert_eq!(frames.iter().filter(|f| f.id == 0x300).count(), 3);
+    assert!(frames.windows(2).all(|w| w[0].timestamp <= w[1].timestamp));
+    assert_eq!(frames.last().unwrap().payload(), &[0xAA, 0xBB, 0xCC]);
+    // 已送出的訊框不會重複產生
+    assert_eq!(generator.poll(105_000).len(), 0);
+    assert_eq!(generator.next_due_us(), Some(110_000));
+
+    let (log_tx, _log_rx) = flume::unbounded();
+    let (data_tx, data_rx) = flume::unbounded();
+    let sim =
+        SimCanApp::new(true).with_traffic(vec![SimMessage::new(0x123, 5, PayloadPattern::Random)]);
+    sim.open_device(log_tx.clone()).unwrap();
+    sim.start_receiving(log_tx, data_tx);
+    let received = receive(&data_rx, 3);
+    sim.stop_receiving();
+    assert!(received
+        .iter()
+        .all(|f| f.id == 0x123 && f.payload().len() == 8));
+}
+
+const TEST_DBC: &str = r#"VERSION ""
+
+BU_: ECU Tester
+
+BO_ 256 EngineData: 8 ECU
+ SG_ EngineSpeed : 0|16@1+ (0.25,0) [0|16383.75] "rpm" Tester
+ SG_ CoolantTemp : 16|8@1- (1,-40) [-168|87] "degC" Tester
+ SG_ Gear : 31|4@0+ (1,0) [0|15] "" Tester
+
+BO_ 2566844926 Diag: 8 Tester
+ SG_ Mode M : 0|8@1+ (1,0) [0|255] "" ECU
+ SG_ Voltage m1 : 8|16@1+ (0.001,0) [0|65.535] "V" ECU
+ SG_ Current m2 : 8|16@1- (0.01,0) [-327.68|327.67] "A" ECU
+
+CM_ SG_ 256 EngineSpeed "Crankshaft speed,
+measured at the flywheel";
+VAL_ 256 Gear 0 "Neutral" 1 "First" 2 "Second" ;
+"#;
+
+#[test]
+fn decodes_signals_from_dbc() {
+    let dbc = Dbc::parse(TEST_DBC).unwrap();
+    assert_eq!(dbc.messages.len(), 2);
+    assert_eq!(dbc.signal_count(), 6);
+    let diag = dbc.message_by_name("Diag").unwrap();
+    assert_eq!((diag.id, diag.extended), (0x18FE_F1FE, true));
+    assert_eq!(
+        diag.signal("Voltage").unwrap().multiplex,
+        Multiplex::Multiplexed(1)
+    );
+
+    let mut decoder = DbcDecoder::default();
+    decoder.set_dbc(dbc, None);
+    // 3000 rpm、-5 degC、Motorola 4-bit 的 Gear = 2（bit 31..28）
+    let mut frame = CanFrame::new(0, 0x100, &[0xE0, 0x2E, 35, 0x20, 0, 0, 0, 0]);
+    frame.timestamp = 42;
+    decoder.process(&frame);
+    let speed = decoder.value("EngineData", "EngineSpeed").unwrap();
+    assert_eq!((speed.value, speed.unit.as_str()), (3000.0, "rpm"));
+    let temp = decoder.value("EngineData", "CoolantTemp").unwrap();
+    assert_eq!(temp.value, -5.0);
+    let gear = decoder.value("EngineData", "Gear").unwrap();
+    assert_eq!((gear.raw, gear.label.as_deref()), (2, Some("Second")));
+
+    // 多工：只有選擇值相符的訊號會被解碼
+    decoder.process(&CanFrame::new(
+        0,
+        0x18FE_F1FE,
+        &[2, 0x18, 0xFC, 0, 0, 0, 0, 0],
+    ));
+    assert_eq!(decoder.value("Diag", "Current").unwrap().value, -10.0);
+    assert!(decoder.value("Diag", "Voltage").is_none());
+    decoder.process(&CanFrame::new(
+        0,
+        0x18FE_F1FE,
+        &[1, 0x88, 0x13, 0, 0, 0, 0, 0],
+    ));
+    assert_eq!(decoder.value("Diag", "Voltage").unwrap().value, 5.0);
+    assert_eq!(decoder.values().count(), 6);
+
+    assert!(Dbc::parse(" SG_ Orphan : 0|8@1+ (1,0) [0|255] \"\" ECU").is_err());
+    assert!(Dbc::parse("BO_ 1 Bad: 8 ECU\n SG_ X : 0|8@2+ (1,0) [0|1] \"\" ECU").is_err());
+}
+
+#[test]
+fn encodes_dbc_messages_from_physical_values() {
+    let dbc = Dbc::parse(TEST_DBC).unwrap();
+    let engine = dbc.message_by_name("EngineData").unwrap();
+    let values: HashMap<String, f64> = [
+        ("EngineSpeed".to_string(), 3000.0),
+        ("CoolantTemp".to_string(), -5.0),
+        ("Gear".to_string(), 2.0),
+    ]
+    .into_iter()
+    .collect();
+    let frame = engine.frame(1, &values).unwrap();
+    assert_eq!(frame.payload(), &[0xE0, 0x2E, 35, 0x20, 0, 0, 0, 0]);
+    assert_eq!((frame.channel, frame.id, frame.extended), (1, 0x100, false));
+    let decoded: Vec<(String, f64)> = engine
+        .decode(frame.payload())
+        .into_iter()
+        .map(|(signal, value, _)| (signal.name.clone(), value))
+        .collect();
+    assert_eq!(decoded.len(), 3);
+    assert!(decoded.iter().all(|(name, value)| values[name] == *value));
+
+    // 多工：只寫入選擇值相符的訊號
+    let diag = dbc.message_by_name("Diag").unwrap();
+    let mut values: HashMap<String, f64> = [
+        ("Mode".to_string(), 2.0),
+        ("Voltage".to_string(), 5.0),
+        ("Current".to_string(), -10.0),
+    ]
+    .into_iter()
+    .collect();
+    let frame = diag.frame(0, &values).unwrap();
+    assert!(frame.extended);
+    assert_eq!(&frame.payload()[..3], &[2, 0x18, 0xFC]);
+    values.insert("Mode".to_string(), 1.0);
+    assert_eq!(&diag.encode(&values).unwrap()[..3], &[1, 0x88, 0x13]);
+
+    values.insert("Voltage".to_string(), 70.0);
+    assert!(diag.encode(&values).is_err());
+}
+
+#[test]
+fn decodes_j1939_ids_and_reassembles_transport_messages() {
+    // EEC1 來自位址 0x00，優先權 3，引擎轉速 1200 rpm
+    let mut eec1 = CanFrame::new(
+        0,
+        0x0CF0_0400,
+        &[0xF0, 0x7D, 0x7D, 0x80, 0x25, 0xFF, 0xFF, 0xFF],
+    );
+    eec1.extended = true;
+    let id = J1939Id::from_can_id(eec1.id);
+    assert_eq!(
+        (id.priority, id.pgn, id.source, id.destination),
+        (3, 0xF004, 0, None)
+    );
+    let request = J1939Id::from_can_id(0x18EA_00F9);
+    assert_eq!((request.pgn, request.destination), (0xEA00, Some(0x00)));
+    assert_eq!(j1939::pgn_name(0xF004), Some("EEC1"));
+    let spns = j1939::decode_spns(id.pgn, eec1.payload());
+    let speed = spns.iter().find(|(def, _)| def.spn == 190).unwrap();
+    assert_eq!(speed.1, 1200.0);
+    // 0xFF 為「無效」，不輸出
+    assert!(spns.iter().all(|(def, _)| def.spn != 1483));
+    let text = j1939::describe_frame(&eec1).unwrap();
+    assert!(
+        text.starts_with("P=3 PGN=0xF004 (EEC1) SA=0x00"),
+        "{}",
+        text
+    );
+    assert!(text.contains("Engine Speed=1200 rpm"), "{}", text);
+
+    // BAM 傳送 10 bytes 的 DM1（PGN 0xFECA），分兩個 TP.DT 封包
+    let payload: Vec<u8> = (1..=10).collect();
+    let bam = [32, 10, 0, 2, 0xFF, 0xCA, 0xFE, 0x00];
+    let mut frames = vec![CanFrame::new(0, 0x1CEC_FF00, &bam)];
+    for (sequence, chunk) in payload.chunks(7).enumerate() {
+        let mut data = vec![sequence as u8 + 1];
+        data.extend_from_slice(chunk);
+        data.resize(8, 0xFF);
+        frames.push(CanFrame::new(0, 0x1CEB_FF00, &data));
+    }
+    let mut reassembler = TransportReassembler::default();
+    let messages: Vec<_> = frames
+        .iter()
+        .enumerate()
+        .filter_map(|(i, frame)| {
+            let mut frame = *frame;
+            frame.timestamp = i as u64 * 50_000;
+            assert!(j1939::is_transport(&frame));
+            reassembler.process(&frame)
+        })
+        .collect();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(
+        (messages[0].pgn, messages[0].source, messages[0].destination),
+        (0xFECA, 0x00, 0xFF)
+    );
+    assert_eq!(messages[0].data, payload);
+    assert!(j1939::describe_message(&messages[0]).contains("(DM1)"));
+
+    // 封包間隔超過 750 ms 時放棄重組
+    let mut late = frames.clone();
+    late[2].timestamp = 2_000_000;
+    let mut reassembler = TransportReassembler::default();
+    assert!(late
+        .iter()
+        .all(|frame| reassembler.process(frame).is_none()));
+}
+
+#[test]
+fn decodes_nmea2000_pgns_and_fast_packets() {
+    // PGN 127250 Vessel Heading，優先權 2，來源 0x01
+    let heading = CanFrame::new(
+        0,
+        0x09F1_1201,
+        &[0x00, 0x5C, 0x3D, 0xFF, 0x7F, 0x9C, 0xFF, 0xFF],
+    );
+    assert!(!nmea2000::is_fast_packet(&heading));
+    let fields = nmea2000::decode_fields(127250, heading.payload());
+    let names: Vec<&str> = fields.iter().map(|(def, _)| def.name).collect();
+    // 偏差為「無資料」，不解碼
+    assert_eq!(names, ["Heading", "Variation"]);
+    assert!((fields[0].1 - 90.0).abs() < 0.001);
+    assert!((fields[1].1 + 0.573).abs() < 0.001);
+    let text = nmea2000::describe_frame(&heading).unwrap();
+    assert!(text.starts_with("P=2 PGN=127250 (Vessel Heading) SA=0x01 | Heading=90.00021 deg"));
+
+    // PGN 129029 GNSS Position Data，43 bytes 分成 7 個 fast packet 封包
+    let mut payload = vec![0x01];
+    payload.extend_from_slice(&19000u16.to_le_bytes());
+    payload.extend_from_slice(&432_000_000u32.to_le_bytes());
+    payload.extend_from_slice(&(25 * 10i64.pow(16)).to_le_bytes());
+    payload.extend_from_slice(&(1215 * 10i64.pow(15)).to_le_bytes());
+    payload.extend_from_slice(&10_000_000i64.to_le_bytes());
+    payload.resize(43, 0xFF);
+    let id = 0x0DF8_0502;
+    let sequence = 3 << 5;
+    let mut frames = vec![{
+        let mut data = vec![sequence, 43];
+        data.extend_from_slice(&payload[..6]);
+        CanFrame::new(0, id, &data)
+    }];
+    for (counter, chunk) in payload[6..].chunks(7).enumerate() {
+        let mut data = vec![sequence | (counter as u8 + 1)];
+        data.extend_from_slice(chunk);
+        data.resize(8, 0xFF);
+        frames.push(CanFrame::new(0, id, &data));
+    }
+    assert_eq!(frames.len(), 7);
+    assert!(nmea2000::describe_frame(&frames[1])
+        .unwrap()
+        .ends_with("fast packet seq 3 frame 1"));
+
+    let mut reassembler = FastPacketReassembler::default();
+    // 其他來源的單一訊框夾在中間不影響重組
+    let mut messages: Vec<_> = frames[..3]
+        .iter()
+        .chain([&heading])
+        .chain(&frames[3..])
+        .filter_map(|frame| reassembler.process(frame))
+        .collect();
+    assert_eq!(messages.len(), 1);
+    let message = messages.pop().unwrap();
+    assert_eq!((message.pgn, message.source), (129029, 0x02));
+    assert_eq!(message.data, payload);
+    let fields = nmea2000::decode_fields(message.pgn, &message.data);
+    let value = |name| fields.iter().find(|(def, _)| def.name == name).unwrap().1;
+    assert_eq!(value("Date"), 19000.0);
+    assert!((value("Latitude") - 25.0).abs() < 1e-9);
+    assert!((value("Longitude") - 121.5).abs() < 1e-9);
+    assert_eq!(value("Altitude"), 10.0);
+    assert!(nmea2000::describe_message(&message).contains("Latitude=25 deg"));
+
+    // 漏掉一個封包的訊息不會完成
+    let missing: Vec<_> = frames
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| *i != 4)
+        .filter_map(|(_, frame)| reassembler.process(frame))
+        .collect();
+    assert!(missing.is_empty());
+}
+
+#[test]
+fn decodes_obd_mode_01_responses() {
+    let request = obd::request_frame(0, 0x0C);
+    assert_eq!(request.id, 0x7DF);
+    assert_eq!(&request.payload()[..3], &[0x02, 0x01, 0x0C]);
+
+    let mut monitor = ObdMonitor::default();
+    // 引擎 ECU 回應 RPM = (0x1A * 256 + 0xF8) / 4 = 1726
+    assert!(monitor.process(&CanFrame::new(
+        0,
+        0x7E8,
+        &[0x04, 0x41, 0x0C, 0x1A, 0xF8, 0x55, 0x55, 0x55]
+    )));
+    // 變速箱 ECU 回應車速 60 km/h
+    assert!(monitor.process(&CanFrame::new(
+        0,
+        0x7E9,
+        &[0x03, 0x41, 0x0D, 60, 0, 0, 0, 0]
+    )));
+    assert!(monitor.process(&CanFrame::new(
+        0,
+        0x7E8,
+        &[0x03, 0x41, 0x05, 130, 0, 0, 0, 0]
+    )));
+    // 其他 ID、負面回應與長度不足的回應不處理
+    assert!(!monitor.process(&CanFrame::new(0, 0x123, &[0x04, 0x41, 0x0C, 0x1A, 0xF8])));
+    assert!(!monitor.process(&CanFrame::new(
+        0,
+        0x7E8,
+        &[0x03, 0x7F, 0x01, 0x12, 0, 0, 0, 0]
+    )));
+    assert!(!monitor.process(&CanFrame::new(
+        0,
+        0x7E8,
+        &[0x03, 0x41, 0x0C, 0x1A, 0, 0, 0, 0]
+    )));
+
+    assert_eq!(monitor.value(0x0C).unwrap().value, 1726.0);
+    let speed = monitor.value(0x0D).unwrap();
+    assert_eq!((speed.value, speed.ecu), (60.0, 1));
+    assert_eq!(monitor.value(0x05).unwrap().value, 90.0);
+    let names: Vec<&str> = monitor.values().map(|(def, _)| def.name).collect();
+    assert_eq!(
+        names,
+        ["Coolant Temperature", "Engine Speed", "Vehicle Speed"]
+    );
+
+    monitor.set_selected(0x0C, false);
+    monitor.set_selected(0x42, true);
+    assert!(!monitor.is_selected(0x0C) && monitor.pids().ends_with(&[0x42]));
+}
+
+#[test]
+fn classifies_canopen_frames_and_tracks_nodes() {
+    let frame = |id, data: &[u8]| CanFrame::new(0, id, data);
+    assert_eq!(
+        canopen::classify(&frame(0x000, &[1, 0])),
+        Some(CobKind::Nmt)
+    );
+    assert_eq!(canopen::classify(&frame(0x080, &[])), Some(CobKind::Sync));
+    assert_eq!(
+        canopen::classify(&frame(0x085, &[0; 8])),
+        Some(CobKind::Emcy(5))
+    );
+    assert_eq!(
+        canopen::classify(&frame(0x285, &[0; 8])),
+        Some(CobKind::Tpdo(2, 5))
+    );
+    assert_eq!(
+        canopen::classify(&frame(0x505, &[0; 8])),
+        Some(CobKind::Rpdo(4, 5))
+    );
+    assert_eq!(
+        canopen::classify(&frame(0x585, &[0; 8])),
+        Some(CobKind::SdoResponse(5))
+    );
+    assert_eq!(
+        canopen::classify(&frame(0x705, &[5])),
+        Some(CobKind::Heartbeat(5))
+    );
+    assert_eq!(
+        canopen::classify(&frame(0x7E5, &[0; 8])),
+        Some(CobKind::LssMaster)
+    );
+    assert_eq!(canopen::classify(&frame(0x580, &[0; 8])), None);
+    assert_eq!(canopen::classify(&frame(0x18FF_0005, &[0; 8])), None);
+
+    let mut monitor = CanOpenMonitor::default();
+    monitor.process(&frame(0x705, &[0x00]));
+    assert_eq!(monitor.node(5).unwrap().state, Some(NmtState::Initializing));
+    monitor.process(&frame(0x705, &[0x7F]));
+    monitor.process(&frame(0x706, &[0x05]));
+    assert_eq!(
+        monitor.node(5).unwrap().state,
+        Some(NmtState::PreOperational)
+    );
+    // NMT 指令推定狀態，節點 0 代表所有節點
+    monitor.process(&frame(0x000, &[0x01, 0x00]));
+    assert!(monitor
+        .nodes()
+        .all(|(_, node)| node.state == Some(NmtState::Operational)));
+    monitor.process(&frame(0x000, &[0x02, 0x06]));
+    assert_eq!(monitor.node(6).unwrap().state, Some(NmtState::Stopped));
+
+    // 讀取不存在的子索引 0x1018:05 被中止
+    let abort = frame(0x585, &[0x80, 0x18, 0x10, 0x05, 0x11, 0x00, 0x09, 0x06]);
+    monitor.process(&abort);
+    let node = monitor.node(5).unwrap();
+    let last_abort = node.last_abort.unwrap();
+    assert_eq!(
+        (last_abort.index, last_abort.subindex, last_abort.code),
+        (0x1018, 0x05, 0x0609_0011)
+    );
+    assert_eq!(last_abort.description(), "Sub-index does not exist");
+    assert_eq!(node.heartbeats, 2);
+    assert!(canopen::describe_frame(&abort)
+        .unwrap()
+        .contains("abort 0x1018:05: Sub-index does not exist"));
+
+    monitor.process(&frame(0x085, &[0x10, 0x81, 0x11, 0, 0, 0, 0, 0]));
+    assert_eq!(monitor.node(5).unwrap().last_emcy, Some((0x8110, 0x11)));
+
+    // 快速上傳回應：0x1000:00 = 0x00020192
+    let upload = frame(0x585, &[0x43, 0x00, 0x10, 0x00, 0x92, 0x01, 0x02, 0x00]);
+    assert_eq!(
+        canopen::describe_frame(&upload).unwrap(),
+        "SDO node 5 upload 0x1000:00 = 0x20192"
+    );
+    assert_eq!(
+        canopen::describe_frame(&frame(0x000, &[0x81, 0x00])).unwrap(),
+        "NMT Reset Node all nodes"
+    );
+    assert_eq!(
+        canopen::describe_frame(&frame(0x706, &[0x05])).unwrap(),
+        "Heartbeat node 6 Operational"
+    );
+}
+
+const TEST_EDS: &str = r#"
+[DeviceInfo]
+ProductName=Test Drive
+
+[1000]
+ParameterName=Device Type
+ObjectType=0x7
+DataType=0x0007
+AccessType=ro
+DefaultValue=0x00020192
+
+[1008]
+ParameterName=Manufacturer Device Name
+DataType=0x0009
+AccessType=const
+
+[1018]
+ParameterName=Identity Object
+ObjectType=0x9
+SubNumber=2
+
+[1018sub0]
+ParameterName=Number of entries
+DataType=0x0005
+AccessType=ro
+
+[1018sub1]
+ParameterName=Vendor-ID
+DataType=0x0007
+AccessType=ro
+
+[6040]
+ParameterName=Controlword
+DataType=0x0006
+AccessType=rww
+"#;
+
+#[test]
+fn runs_sdo_transfers_with_eds_lookup() {
+    let response = |data: &[u8]| CanFrame::new(0, 0x585, data);
+    let sent = |step: Option<SdoStep>| match step {
+        Some(SdoStep::Send(frame)) => frame,
+        other => panic!("expected a request, got {:?}", other),
+    };
+
+    // 快速上傳：0x1000:00 回應 4 bytes
+    let mut read = SdoTransfer::upload(0, 5, 0x1000, 0);
+    let request = read.start();
+    assert_eq!(request.id, 0x605);
+    assert_eq!(request.payload(), &[0x40, 0x00, 0x10, 0x00, 0, 0, 0, 0]);
+    // 其他節點的回應不處理
+    assert_eq!(
+        read.handle(&CanFrame::new(0, 0x586, &[0x43, 0, 0x10, 0, 1, 2, 3, 4])),
+        None
+    );
+    assert_eq!(
+        read.handle(&response(&[0x43, 0x00, 0x10, 0x00, 0x92, 0x01, 0x02, 0x00])),
+        Some(SdoStep::Done(vec![0x92, 0x01, 0x02, 0x00]))
+    );
+
+    // 分段上傳：0x1008:00 = "Test Drive"（10 bytes，兩段）
+    let mut read = SdoTransfer::upload(0, 5, 0x1008, 0);
+    read.start();
+    let segment = sent(read.handle(&response(&[0x41, 0x08, 0x10, 0x00, 10, 0, 0, 0])));
+    assert_eq!(segment.payload()[0], 0x60);
+    let segment = sent(read.handle(&response(b"\x00Test Dr")));
+    assert_eq!(segment.payload()[0], 0x70);
+    // 最後一段：toggle=1、3 bytes 有效（n=4）、c=1
+    let done = read.handle(&response(&[0x19, b'i', b'v', b'e', 0, 0, 0, 0]));
+    let Some(SdoStep::Done(data)) = done else {
+        panic!("expected completion, got {:?}", done);
+    };
+    assert_eq!(
+        eds::format_value(eds::VISIBLE_STRING, &data),
+        "\"Test Drive\""
+    );
+
+    // toggle bit 錯誤時由用戶端中止
+    let mut read = SdoTransfer::upload(0, 5, 0x1008, 0);
+    read.start();
+    read.handle(&response(&[0x41, 0x08, 0x10, 0x00, 10, 0, 0, 0]));
+    match read.handle(&response(&[0x10, 0, 0, 0, 0, 0, 0, 0])) {
+        Some(SdoStep::Failed(Some(abort), reason)) => {
+            assert_eq!(
+                abort.payload(),
+                &[0x80, 0x08, 0x10, 0x00, 0x00, 0x00, 0x03, 0x05]
+            );
+            assert!(reason.contains("Toggle bit"));
+        }
+        other => panic!("expected an abort, got {:?}", other),
+    }
+
+    // 快速下載 2 bytes：n=2
+    let mut write = SdoTransfer::download(0, 5, 0x6040, 0, vec![0x0F, 0x00]);
+    assert_eq!(
+        write.start().payload(),
+        &[0x2B, 0x40, 0x60, 0x00, 0x0F, 0x00, 0, 0]
+    );
+    assert_eq!(
+        write.handle(&response(&[0x60, 0x40, 0x60, 0x00, 0, 0, 0, 0])),
+        Some(SdoStep::Done(Vec::new()))
+    );
+
+    // 分段下載 9 bytes：7 + 2
+    let mut write = SdoTransfer::download(0, 5, 0x2000, 1, (1..=9).collect());
+    assert_eq!(
+        write.start().payload(),
+        &[0x21, 0x00, 0x20, 0x01, 9, 0, 0, 0]
+    );
+    let first = sent(write.handle(&response(&[0x60, 0x00, 0x20, 0x01, 0, 0, 0, 0])));
+    assert_eq!(first.payload(), &[0x00, 1, 2, 3, 4, 5, 6, 7]);
+    let last = sent(write.handle(&response(&[0x20, 0, 0, 0, 0, 0, 0, 0])));
+    assert_eq!(last.payload(), &[0x1B, 8, 9, 0, 0, 0, 0, 0]);
+    assert_eq!(
+        write.handle(&response(&[0x30, 0, 0, 0, 0, 0, 0, 0])),
+        Some(SdoStep::Done(Vec::new()))
+    );
+
+    // 節點中止
+    let mut write = SdoTransfer::download(0, 5, 0x1000, 0, vec![1, 0, 0, 0]);
+    write.start();
+    match write.handle(&response(&[0x80, 0x00, 0x10, 0x00, 0x02, 0x00, 0x01, 0x06])) {
+        Some(SdoStep::Failed(None, reason)) => {
+            assert!(reason.contains("Attempt to write a read only object"))
+        }
+        other => panic!("expected an abort, got {:?}", other),
+    }
+
+    let eds = Eds::parse(TEST_EDS).unwrap();
+    assert_eq!(eds.product_name, "Test Drive");
+    assert_eq!(eds.entries.len(), 5);
+    let vendor = eds.entry(0x1018, 1).unwrap();
+    assert_eq!(vendor.name, "Identity Object: Vendor-ID");
+    assert_eq!(vendor.data_type, eds::UNSIGNED32);
+    assert!(vendor.readable() && !vendor.writable());
+    let controlword = eds.entry(0x6040, 0).unwrap();
+    assert!(controlword.writable());
+    assert_eq!(
+        eds::parse_value(controlword.data_type, "0x0F").unwrap(),
+        [0x0F, 0x00]
+    );
+    assert_eq!(
+        eds::parse_value(eds::INTEGER16, "-2").unwrap(),
+        [0xFE, 0xFF]
+    );
+    assert_eq!(
+        eds::format_value(eds::UNSIGNED32, &[0x92, 0x01, 0x02, 0x00]),
+        "131474 (0x20192)"
+    );
+    assert!(eds.entry(0x1018, 0).is_some() && eds.entry(0x1018, 2).is_none());
+}
+
+#[test]
+fn migrates_version_1_config() {
+    let v1 = CONFIG
+        .replace("version: 2\n", "")
+        .replace("endian: little", "endian: 0")
+        .replace("endian: big", "endian: 1");
+    let (cfg, warnings) = load_config(&v1, "v1.yaml");
+    assert_eq!(cfg.version, config::CONFIG_VERSION);
+    assert_eq!(cfg.canbus_config[0].endian, ByteOrder::Little);
+    assert_eq!(cfg.canbus_config[1].endian, ByteOrder::Big);
+    assert!(warnings.iter().any(|w| w.contains("endian")));
+}
+
+#[test]
+fn rejects_unknown_component_kind() {
+    let path = temp_path("bad.yaml");
+    fs::write(&path, CONFIG.replace("type: Gauge", "type: Dial")).unwrap();
+    let result = config::load_config(path.to_str().unwrap());
+    fs::remove_file(&path).unwrap();
+    let error = result.err().unwrap().to_string();
+    assert!(error.contains("Dial"), "{}", error);
+}
+
+#[test]
+fn backend_reports_stable_event_codes() {
+    let (log_tx, log_rx) = flume::unbounded();
+    let (data_tx, _data_rx) = flume::unbounded();
+    let sim = SimCanApp::new(false);
+    sim.open_device(log_tx.clone()).unwrap();
+    sim.start_receiving(log_tx.clone(), data_tx);
+    sim.stop_receiving();
+    sim.close_device(log_tx);
+    let codes: Vec<&str> = log_rx.drain().map(|event| event.code()).collect();
+    assert_eq!(codes, ["I101", "I104", "I105", "I102"]);
+}
+
+#[test]
+fn watch_alarm_waits_for_duration_and_clears_with_hysteresis() {
+    let yaml = format!(
+        "{}watches:\n  - expr: \"temp > 90 for 5s\"\n    hysteresis: 5\n",
+        CONFIG
+    );
+    let (cfg, _) = load_config(&yaml, "watch.yaml");
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let mut monitor = BusMonitor::default();
+    monitor.load(
+        &cfg.heartbeats,
+        &cfg.counters,
+        &cfg.thresholds,
+        &cfg.watches,
+    );
+    let mut events = EventLog::default();
+
+    // (時間 秒, 溫度)：3 秒的尖峰不觸發；持續 5 秒後觸發；88 仍在遲滯範圍內；84 解除
+    for (second, temp) in [
+        (0, 95),
+        (3, 80),
+        (10, 95),
+        (12, 96),
+        (15, 95),
+        (16, 88),
+        (17, 84),
+    ] {
+        let mut frame = CanFrame::new(0, 0x100, &[0, 0, temp]);
+        frame.timestamp = second * 1_000_000;
+        signals.process(&frame);
+        monitor.check_watches(&signals, frame.timestamp, &mut events);
+    }
+    let log: Vec<(u64, Severity)> = events
+        .events()
+        .map(|e| (e.timestamp / 1_000_000, e.severity))
+        .collect();
+    assert_eq!(log, [(15, Severity::Alarm), (17, Severity::Info)]);
+
+    let path = temp_path("watch_bad.yaml");
+    fs::write(
+        &path,
+        format!("{}watches:\n  - expr: \"pressure > 1\"\n", CONFIG),
+    )
+    .unwrap();
+    let err = config::load_config(path.to_str().unwrap()).unwrap_err();
+    fs::remove_file(&path).unwrap();
+    assert!(err.to_string().contains("pressure"), "{}", err);
+}
+
+#[test]
+fn conformance_suite_reports_violations() {
+    assert_eq!(checksum(ChecksumKind::Crc8, b"123456789", usize::MAX), 0x4B);
+
+    let yaml = format!(
+        "{}cycle_times:\n  - id: 0x100\n    period_ms: 10\ncounters:\n  - id: 0x200\n    index: 0\nchecksums:\n  - id: 0x200\n    index: 1\n    kind: xor\n",
+        CONFIG
+    );
+    let (cfg, _) = load_config(&yaml, "conformance.yaml");
+    let mut suite = ConformanceSuite::default();
+    suite.load(&cfg);
+
+    let frames = [
+        (0, 0x100, vec![0x64, 0x00, 0xF6]),
+        (10_000, 0x100, vec![0x64, 0x00]),
+        (35_000, 0x100, vec![0x64, 0x00, 0xF6]),
+        (36_000, 0x200, vec![0x01, 0x01 ^ 0xAA, 0xAA]),
+        (37_000, 0x200, vec![0x03, 0x00, 0xAA]),
+        (38_000, 0x7FF, vec![]),
+    ];
+    for (timestamp, id, payload) in frames {
+        let mut frame = CanFrame::new(0, id, &payload);
+        frame.timestamp = timestamp;
+        suite.process(&frame);
+    }
+    let summary: Vec<(&str, String, &str, u64)> = suite
+        .results()
+        .iter()
+        .map(|r| (r.kind.label(), r.target.clone(), r.verdict(), r.violations))
+        .collect();
+    let expect =
+        |label, target: &str, verdict, violations| (label, target.to_string(), verdict, violations);
+    assert_eq!(
+        summary,
+        [
+            expect("DLC", "0x100", "FAIL", 1),
+            expect("Undefined IDs", "all", "FAIL", 1),
+            expect("Cycle time", "0x100", "FAIL", 1),
+            expect("Counter", "0x200", "FAIL", 1),
+            expect("Checksum", "0x200", "FAIL", 1),
+        ]
+    );
+}
+
+#[test]
+fn sequence_validator_reports_deviations() {
+    let yaml = format!(
+        "{}expected_sequences:\n  - name: wake-up\n    steps:\n      - id: 0x100\n        label: wake-up\n      - id: 0x200\n        within_ms: 50\n      - id: 0x700\n        within_ms: 1000\n",
+        CONFIG
+    );
+    let (cfg, _) = load_config(&yaml, "sequence.yaml");
+    let mut validator = SequenceValidator::default();
+    validator.load(&cfg.expected_sequences);
+    let mut events = EventLog::default();
+
+    let frames = [
+        // 完整且準時
+        (0, 0x100),
+        (20_000, 0x200),
+        (20_500, 0x200),
+        (500_000, 0x700),
+        // 跳過 0x200
+        (1_000_000, 0x100),
+        (1_010_000, 0x700),
+        // 0x200 太晚
+        (2_000_000, 0x100),
+        (2_100_000, 0x200),
+        // 開始後等不到 0x200
+        (3_000_000, 0x100),
+    ];
+    for (timestamp, id) in frames {
+        let mut frame = CanFrame::new(0, id, &[]);
+        frame.timestamp = timestamp;
+        validator.process(&frame, &mut events);
+    }
+    validator.poll(3_040_000, &mut events);
+    assert_eq!(validator.deviations().count(), 2);
+    validator.poll(3_060_000, &mut events);
+
+    let deviations: Vec<(usize, &str)> = validator
+        .deviations()
+        .map(|d| (d.step, d.message.as_str()))
+        .collect();
+    assert_eq!(
+        deviations,
+        [
+            (2, "expected step 2 0x200, got 0x700"),
+            (2, "step 2 0x200 arrived after 100.0 ms (limit 50 ms)"),
+            (2, "step 2 0x200 not seen within 50 ms"),
+        ]
+    );
+    assert_eq!(events.events().count(), 3);
+    let result = &validator.results()[0];
+    assert_eq!((result.checked, result.violations), (4, 3));
+    assert_eq!(result.verdict(), "FAIL");
+}
+
+#[test]
+fn tx_sequence_sends_steps_and_waits_for_response() {
+    let yaml = format!(
+        "{}sequences:\n  - name: wake-up\n    steps:\n      - send: {{ id: 0x100, data: \"01\" }}\n      - wait_for: {{ id: 0x200, timeout_ms: 2000 }}\n      - repeat:\n          count: 2\n          steps:\n            - send: {{ id: 0x300, data: \"AA BB\" }}\n            - wait: 5\n",
+        CONFIG
+    );
+    let (cfg, _) = load_config(&yaml, "tx_sequence.yaml");
+    let sequence = cfg.sequences[0].clone();
+    assert_eq!(sequence.frame_count(), 3);
+
+    let (can_app, injector, data_rx) = start_sim();
+    let playback = SequencePlayback::start(sequence, can_app, |_| {});
+    assert_eq!(receive(&data_rx, 1)[0].id, 0x100);
+    // 回應到達前不會繼續送出
+    std::thread::sleep(Duration::from_millis(50));
+    assert_eq!(playback.progress(), (1, 3));
+
+    injector.send(CanFrame::new(0, 0x200, &[0x01])).unwrap();
+    let response = receive(&data_rx, 1)[0];
+    playback.observe(&response);
+    let ids: Vec<u32> = receive(&data_rx, 2).iter().map(|f| f.id).collect();
+    assert_eq!(ids, [0x300, 0x300]);
+    for _ in 0..100 {
+        if !playback.is_running() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(!playback.is_running());
+    assert_eq!(playback.progress(), (3, 3));
+}
+
+#[test]
+fn replays_logs_with_original_timing() {
+    let path = temp_path("replay.log");
+    fs::write(
+        &path,
+        "# operator: tester\n(1700000000.200000) can0 300#03\n(1700000000.000000) can0 100#01\n(1700000000.100000) can1 200#02\n",
+    )
+    .unwrap();
+    let frames = replay::load_log(path.to_str().unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+    let ids: Vec<u32> = frames.iter().map(|f| f.id).collect();
+    assert_eq!(ids, [0x100, 0x200, 0x300]);
+
+    let (can_app, _injector, data_rx) = start_sim();
+    let started = Instant::now();
+    let playback = LogReplay::start(frames.clone(), can_app.clone(), Some(1), 2.0, |_| {});
+    let sent = receive(&data_rx, 3);
+    // 200 ms 的紀錄以 2 倍速約 100 ms 播完
+    assert!(started.elapsed() >= Duration::from_millis(90));
+    assert!(sent.iter().all(|f| f.channel == 1));
+    assert_eq!(sent.iter().map(|f| f.id).collect::<Vec<_>>(), ids);
+
+    let playback_done = (0..100).any(|_| {
+        std::thread::sleep(Duration::from_millis(10));
+        !playback.is_running()
+    });
+    assert!(playback_done);
+    assert_eq!(playback.progress(), (3, 3));
+    assert_eq!(playback.position(), (200_000, 200_000));
+
+    let paused = LogReplay::start(frames, can_app, None, 100.0, |_| {});
+    assert_eq!(paused.speed(), replay::MAX_SPEED);
+    paused.set_paused(true);
+    assert_eq!(paused.state(), ReplayState::Paused);
+    std::thread::sleep(Duration::from_millis(100));
+    assert!(paused.progress().0 <= 1);
+    paused.set_paused(false);
+    let sent = receive(&data_rx, 3);
+    assert_eq!(sent[1].channel, 1);
+    paused.stop();
+}
+
+#[test]
+fn encoded_signals_decode_to_the_same_value() {
+    let (cfg, _) = load_config(CONFIG, "encode.yaml");
+    let mut data = Vec::new();
+    for (entry, value) in cfg.canbus_config.iter().zip([1234.4, -40.0]) {
+        encode_value(entry, value, &mut data).unwrap();
+    }
+    assert_eq!(data, [0xD2, 0x04, 0xD8]);
+    let decoded: Vec<f64> = cfg
+        .canbus_config
+        .iter()
+        .map(|entry| extract_value(entry, &data).unwrap())
+        .collect();
+    assert_eq!(decoded, [1234.0, -40.0]);
+    assert!(encode_value(&cfg.canbus_config[1], 128.0, &mut data).is_err());
+}
+
+#[test]
+fn overlay_config_overrides_matching_entries() {
+    let base = temp_path("base.yaml");
+    let overlay = temp_path("overlay.yaml");
+    fs::write(
+        &base,
+        format!(
+            "{}heartbeats:\n  - id: 0x700\n    timeout_ms: 500\n",
+            CONFIG
+        ),
+    )
+    .unwrap();
+    fs::write(
+        &overlay,
+        "version: 2\ncanbus_config:\n  - key: temp\n    id: 0x101\n    index: 0\n    len: 1\n    endian: big\n    type: int8\n  - key: rpm\n    id: 0x102\n    index: 0\n    len: 2\n    endian: little\n    type: uint16\nheartbeats:\n  - id: 1792\n    timeout_ms: 500\n",
+    )
+    .unwrap();
+    let result = config::load_configs(&[base.to_str().unwrap(), overlay.to_str().unwrap()]);
+    fs::remove_file(&base).unwrap();
+    fs::remove_file(&overlay).unwrap();
+    let (cfg, warnings) = result.unwrap();
+
+    let signals: Vec<(&str, u32)> = cfg
+        .canbus_config
+        .iter()
+        .map(|e| (e.key.as_str(), e.id))
+        .collect();
+    assert_eq!(signals, [("speed", 0x100), ("temp", 0x101), ("rpm", 0x102)]);
+    assert_eq!(cfg.components.len(), 2);
+    assert_eq!(cfg.heartbeats.len(), 1);
+    // 只有內容不同的 temp 產生衝突警告，十進位寫法的相同心跳不算
+    assert_eq!(warnings.len(), 1, "{:?}", warnings);
+    assert!(
+        warnings[0].contains("canbus_config 'temp' overrides"),
+        "{}",
+        warnings[0]
+    );
+}
+
+#[test]
+fn blackbox_records_segments_and_prunes_old_files() {
+    let dir = temp_path("blackbox");
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    // 超過保留期限的舊分段應被刪除，其他檔案不受影響
+    let old = dir.join("blackbox_20000101_000000_000.log");
+    fs::write(&old, "").unwrap();
+    fs::File::options()
+        .write(true)
+        .open(&old)
+        .unwrap()
+        .set_modified(SystemTime::now() - Duration::from_secs(3 * 3600))
+        .unwrap();
+    let other = dir.join("notes.log");
+    fs::write(&other, "").unwrap();
+
+    let session = SessionInfo {
+        dut: "SN-42".to_string(),
+        ..Default::default()
+    };
+    let config = BlackBoxConfig {
+        dir: dir.clone(),
+        retention_hours: 1,
+        ..Default::default()
+    };
+    let mut recorder = BlackBoxRecorder::start(config, session.clone(), |_| {}).unwrap();
+    let frames: Vec<CanFrame> = (0..100u32)
+        .map(|i| CanFrame::new(0, 0x100 + i % 4, &i.to_le_bytes()))
+        .collect();
+    for frame in &frames {
+        recorder.record(frame);
+    }
+    recorder.stop();
+    let status = recorder.status();
+    assert_eq!(status.frames, 100);
+    assert_eq!(status.segments, 1);
+    assert_eq!(status.removed, 1);
+    assert!(!old.exists());
+    assert!(other.exists());
+
+    let segment = fs::read_dir(&dir)
+        .unwrap()
+        .flatten()
+        .map(|e| e.path())
+        .find(|p| p != &other)
+        .unwrap();
+    let index = LogIndex::build(&segment, &AtomicU64::new(0), &AtomicBool::new(false)).unwrap();
+    assert_eq!(index.session(), &session);
+    let text = fs::read_to_string(&segment).unwrap();
+    let parsed: Vec<CanFrame> = text.lines().filter_map(parse_candump_line).collect();
+    fs::remove_dir_all(&dir).unwrap();
+    assert_eq!(parsed, frames);
+
+    // 自動記錄的設定隨 profile 保存，舊的配置檔沒有此欄位時使用預設值
+    let layout = ProfileLayout {
+        blackbox: BlackBoxConfig {
+            segment_minutes: 60,
+            auto_start: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let yaml = serde_yaml::to_string(&layout).unwrap();
+    assert_eq!(
+        serde_yaml::from_str::<ProfileLayout>(&yaml).unwrap(),
+        layout
+    );
+    let old: ProfileLayout = serde_yaml::from_str("trace_filter: id == 0x100\n").unwrap();
+    assert_eq!(old.blackbox, BlackBoxConfig::default());
+}
+
+#[test]
+fn records_frames_to_sqlite_and_queries_by_id_and_time() {
+    let path = temp_path("capture.db");
+    let _ = fs::remove_file(&path);
+    let path = path.to_string_lossy().into_owned();
+    let session = SessionInfo {
+        dut: "SN-42".to_string(),
+        ..Default::default()
+    };
+    let start = capture_db::parse_local_time("2024-03-01 12:00:00").unwrap();
+    let frames: Vec<CanFrame> = (0..1000u32)
+        .map(|i| {
+            let mut frame = if i % 10 == 9 {
+                CanFrame::new_fd(1, 0x18FF_0000 + i % 4, &[i as u8; 12], true)
+            } else {
+                CanFrame::new(i % 2, 0x100 + i % 4, &i.to_le_bytes())
+            };
+            frame.timestamp = start + i as u64 * 1000;
+            frame
+        })
+        .collect();
+    let mut db = CaptureDb::start(&path, &session, |_| {}).unwrap();
+    for frame in &frames {
+        db.record(frame);
+    }
+    db.stop();
+    assert_eq!(db.status().frames, 1000);
+    assert!(db.status().last_error.is_none());
+
+    let summary = capture_db::summary(&path).unwrap();
+    assert_eq!(summary.frames, 1000);
+    assert_eq!(summary.first_us, Some(start));
+    assert_eq!(summary.last_us, Some(start + 999_000));
+
+    let all = capture_db::query(&path, &CaptureQuery::default()).unwrap();
+    assert_eq!(all, frames);
+
+    // ID 與時間範圍（含終點），只回傳符合的列
+    let query = CaptureQuery {
+        id: Some(0x101),
+        from_us: Some(start + 100_000),
+        to_us: capture_db::parse_local_time("2024-03-01 12:00:00.2").ok(),
+        ..Default::default()
+    };
+    let found = capture_db::query(&path, &query).unwrap();
+    let expected: Vec<CanFrame> = frames
+        .iter()
+        .filter(|f| f.id == 0x101 && (100..=200).contains(&((f.timestamp - start) / 1000)))
+        .copied()
+        .collect();
+    assert_eq!(found, expected);
+    assert_eq!(found.len(), 20);
+
+    let limited = capture_db::query(
+        &path,
+        &CaptureQuery {
+            channel: Some(1),
+            limit: 10,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+    assert_eq!(limited.len(), 10);
+    assert!(limited.iter().all(|f| f.channel == 1));
+    assert!(capture_db::parse_local_time("yesterday").is_err());
+    let _ = fs::remove_file(&path);
+}
+
+#[test]
+fn exports_decoded_signals_to_mdf4() {
+    let entries = [CanbusConfigEntry {
+        key: "pack_voltage".to_string(),
+        id: 0x300,
+        index: 0,
+        len: 2,
+        endian: ByteOrder::Little,
+        start_bit: None,
+        bit_length: None,
+        data_type: "uint16".to_string(),
+        scale: 1.0,
+        offset: 0.0,
+        signed: None,
+        values: Default::default(),
+    }];
+    let dbc = Dbc::parse(TEST_DBC).unwrap();
+    let start = 1_700_000_000_000_000;
+    let mut frames = Vec::new();
+    for i in 0..500u32 {
+        let mut voltage = CanFrame::new(0, 0x300, &(3000 + i as u16).to_le_bytes());
+        voltage.timestamp = start + i as u64 * 10_000;
+        // EngineSpeed = i * 0.25 rpm
+        let mut engine = CanFrame::new(0, 0x100, &[i as u8, (i >> 8) as u8, 80, 0, 0, 0, 0, 0]);
+        engine.timestamp = voltage.timestamp + 5_000;
+        frames.extend([voltage, engine]);
+    }
+    // 多工訊號只在選擇值相符時有樣本
+    let mut diag = CanFrame::new(0, 0x18FE_F1FE, &[1, 0x88, 0x13, 0, 0, 0, 0, 0]);
+    diag.timestamp = start + 1_000_000;
+    frames.push(diag);
+
+    let series = mdf::decode_signals(&frames, &entries, &dbc, &DisplayUnits::default());
+    let names: Vec<&str> = series.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "pack_voltage",
+            "EngineData.EngineSpeed",
+            "EngineData.CoolantTemp",
+            "EngineData.Gear",
+            "Diag.Mode",
+            "Diag.Voltage"
+        ]
+    );
+    assert_eq!(series[0].samples[10], (start + 100_000, 3010.0));
+    assert_eq!(series[1].samples[8], (start + 85_000, 2.0));
+    assert_eq!(series[1].unit, "rpm");
+    assert_eq!(series[5].samples, vec![(start + 1_000_000, 5.0)]);
+
+    let path = temp_path("signals.mf4");
+    let path_text = path.to_str().unwrap();
+    let session = SessionInfo {
+        dut: "SN-42 <rev B>".to_string(),
+        ..Default::default()
+    };
+    assert_eq!(mdf::export_mf4(path_text, &series, &session).unwrap(), 6);
+    let bytes = fs::read(&path).unwrap();
+    assert_eq!(&bytes[..8], b"MDF     ");
+    assert_eq!(u16::from_le_bytes([bytes[28], bytes[29]]), 410);
+    assert_eq!(&bytes[64..68], b"##HD");
+    let read = mdf::read_mf4(path_text).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(read, series);
+    assert!(mdf::export_mf4(path_text, &[], &session).is_err());
+}
+
+#[test]
+fn id_filter_lists_pass_and_block_per_channel() {
+    assert_eq!(
+        id_filter::parse_id_ranges(" 100-1FF, 0x7DF ,").unwrap(),
+        vec![(0x100, 0x1FF), (0x7DF, 0x7DF)]
+    );
+    assert!(id_filter::parse_id_ranges("200-100").is_err());
+    assert!(id_filter::parse_id_ranges("12G").is_err());
+
+    let rules = vec![
+        IdFilterRule {
+            channel: Some(0),
+            ids: "100-1FF".to_string(),
+            ..Default::default()
+        },
+        IdFilterRule {
+            mode: FilterMode::Block,
+            ids: "150, 700-7FF".to_string(),
+            ..Default::default()
+        },
+        IdFilterRule {
+            enabled: false,
+            channel: Some(1),
+            ids: "1".to_string(),
+            ..Default::default()
+        },
+        // 尚未輸入 ID 的新規則不影響過濾
+        IdFilterRule::default(),
+    ];
+    let mut filter = IdFilter::default();
+    filter.set_rules(&rules).unwrap();
+    let accepted: Vec<(u32, u32)> = [
+        (0, 0x100),
+        (0, 0x150),
+        (0, 0x200),
+        (1, 0x200),
+        (1, 0x150),
+        (1, 0x7DF),
+        (2, 0x1),
+    ]
+    .into_iter()
+    .filter(|&(channel, id)| filter.accept(&CanFrame::new(channel, id, &[])))
+    .collect();
+    assert_eq!(accepted, vec![(0, 0x100), (1, 0x200), (2, 0x1)]);
+    assert_eq!(filter.blocked().collect::<Vec<_>>(), vec![(0, 2), (1, 2)]);
+
+    // 無法解析時保留原本的規則
+    let mut invalid = rules.clone();
+    invalid[0].ids = "100-".to_string();
+    assert!(filter.set_rules(&invalid).is_err());
+    assert_eq!(filter.rules(), rules.as_slice());
+    assert!(!filter.accept(&CanFrame::new(0, 0x200, &[])));
+    filter.reset_counts();
+    assert_eq!(filter.blocked().count(), 0);
+
+    let layout = ProfileLayout {
+        id_filters: rules,
+        ..Default::default()
+    };
+    let yaml = serde_yaml::to_string(&layout).unwrap();
+    assert_eq!(
+        serde_yaml::from_str::<ProfileLayout>(&yaml).unwrap(),
+        layout
+    );
+}
+
+#[test]
+fn trigger_capture_keeps_pre_trigger_frames_and_stops() {
+    let frame = |i: u32, id: u32, byte: u8| {
+        let mut frame = CanFrame::new(0, id, &[byte]);
+        frame.timestamp = 1_000_000 + i as u64 * 1000;
+        frame
+    };
+    let mut trigger = TriggerCapture::default();
+    assert!(trigger
+        .arm(TriggerConfig {
+            condition: "id ==".to_string(),
+            ..Default::default()
+        })
+        .is_err());
+    assert_eq!(trigger.state(), TriggerState::Idle);
+
+    trigger
+        .arm(TriggerConfig {
+            condition: "id == 0x7E8 && data[0] == 0xFF".to_string(),
+            pre_trigger: 5,
+            stop_frames: 4,
+            stop_seconds: 0.0,
+        })
+        .unwrap();
+    for i in 0..20 {
+        trigger.process(&frame(i, 0x100, i as u8));
+    }
+    // 資料不符合時不觸發
+    trigger.process(&frame(20, 0x7E8, 0));
+    assert_eq!(trigger.state(), TriggerState::Armed);
+    assert_eq!(trigger.pre_trigger_len(), 5);
+    trigger.process(&frame(21, 0x7E8, 0xFF));
+    assert_eq!(trigger.state(), TriggerState::Triggered);
+    for i in 22..30 {
+        trigger.process(&frame(i, 0x200, 0));
+    }
+    assert_eq!(trigger.state(), TriggerState::Done);
+    let captured: Vec<u64> = trigger
+        .captured()
+        .iter()
+        .map(|f| (f.timestamp - 1_000_000) / 1000)
+        .collect();
+    assert_eq!(captured, vec![16, 17, 18, 19, 20, 21, 22, 23, 24]);
+    assert_eq!(trigger.trigger(), Some((1_021_000, 5)));
+
+    // 時間限制：匯流排安靜時由 poll 結束
+    trigger
+        .arm(TriggerConfig {
+            condition: "id == 0x7E8".to_string(),
+            pre_trigger: 0,
+            stop_frames: 0,
+            stop_seconds: 0.5,
+        })
+        .unwrap();
+    trigger.process(&frame(0, 0x100, 0));
+    trigger.process(&frame(1, 0x7E8, 0));
+    trigger.process(&frame(300, 0x100, 0));
+    trigger.poll(1_400_000);
+    assert_eq!(trigger.state(), TriggerState::Triggered);
+    trigger.poll(1_501_000);
+    assert_eq!(trigger.state(), TriggerState::Done);
+    trigger.process(&frame(600, 0x100, 0));
+    assert_eq!(trigger.captured().len(), 2);
+    trigger.disarm();
+    assert!(trigger.captured().is_empty());
+}
+
+#[test]
+fn signal_alarms_use_yaml_and_dbc_values() {
+    let mut signals = SignalTable::default();
+    signals.load(&[CanbusConfigEntry {
+        key: "pack_voltage".to_string(),
+        id: 0x300,
+        index: 0,
+        len: 2,
+        endian: ByteOrder::Little,
+        start_bit: None,
+        bit_length: None,
+        data_type: "uint16".to_string(),
+        scale: 1.0,
+        offset: 0.0,
+        signed: None,
+        values: Default::default(),
+    }]);
+    let mut dbc = DbcDecoder::default();
+    dbc.set_dbc(Dbc::parse(TEST_DBC).unwrap(), None);
+    let rules = vec![
+        AlarmRule {
+            expr: "pack_voltage > 3100 for 10ms".to_string(),
+            hysteresis: 50.0,
+            send: true,
+            channel: 1,
+            id: 0x18FF_0001,
+            data: "01 ff".to_string(),
+            ..Default::default()
+        },
+        AlarmRule {
+            expr: "EngineData.CoolantTemp > 60".to_string(),
+            sound: true,
+            ..Default::default()
+        },
+    ];
+    let mut alarms = AlarmEngine::default();
+    alarms.set_rules(&rules).unwrap();
+    let mut events = EventLog::default();
+    let mut step = |frame: CanFrame, alarms: &mut AlarmEngine, events: &mut EventLog| {
+        signals.process(&frame);
+        dbc.process(&frame);
+        alarms.check(&signals, &dbc, frame.timestamp, events)
+    };
+    let voltage = |value: u16, time: u64| {
+        let mut frame = CanFrame::new(0, 0x300, &value.to_le_bytes());
+        frame.timestamp = time;
+        frame
+    };
+
+    assert!(step(voltage(3200, 0), &mut alarms, &mut events).is_empty());
+    let sent = step(voltage(3200, 20_000), &mut alarms, &mut events);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(
+        (
+            sent[0].channel,
+            sent[0].id,
+            sent[0].extended,
+            sent[0].payload()
+        ),
+        (1, 0x18FF_0001, true, &[0x01, 0xFF][..])
+    );
+    assert!(alarms.is_active(0));
+    // 遲滯範圍內不解除，也不重複送出
+    assert!(step(voltage(3080, 30_000), &mut alarms, &mut events).is_empty());
+    assert!(alarms.is_active(0));
+    assert!(!alarms.take_sound());
+
+    // CoolantTemp = 110 - 40 = 70 degC
+    let mut engine = CanFrame::new(0, 0x100, &[0, 0, 110, 0, 0, 0, 0, 0]);
+    engine.timestamp = 40_000;
+    assert!(step(engine, &mut alarms, &mut events).is_empty());
+    assert!(alarms.take_sound());
+    assert!(!alarms.take_sound());
+    let alarmed = alarms.alarmed_signals();
+    assert!(alarmed.contains("pack_voltage") && alarmed.contains("EngineData.CoolantTemp"));
+
+    step(voltage(3000, 50_000), &mut alarms, &mut events);
+    assert!(!alarms.is_active(0));
+    let messages: Vec<&str> = events.events().map(|e| e.message.as_str()).collect();
+    assert_eq!(
+        messages,
+        [
+            "pack_voltage > 3100 for 10ms (alarm raised)",
+            "EngineData.CoolantTemp > 60 (alarm raised)",
+            "pack_voltage > 3100 for 10ms (alarm cleared)"
+        ]
+    );
+
+    // 無法解析時保留原本的規則
+    let mut invalid = rules.clone();
+    invalid[0].data = "1G".to_string();
+    assert!(alarms.set_rules(&invalid).is_err());
+    assert_eq!(alarms.rules(), rules.as_slice());
+}
+
+#[test]
+fn auto_responder_answers_requests_after_delay() {
+    let rules = vec![
+        ResponseRule {
+            request_id: 0x7E0,
+            prefix: "02 10".to_string(),
+            response_id: 0x7E8,
+            response_data: "06 50 01 00 32 01 F4".to_string(),
+            ..Default::default()
+        },
+        ResponseRule {
+            channel: Some(1),
+            request_id: 0x100,
+            response_id: 0x18FF0001,
+            response_data: "AA".to_string(),
+            delay_ms: 50,
+            ..Default::default()
+        },
+    ];
+    let mut responder = AutoResponder::default();
+    responder.set_rules(&rules).unwrap();
+
+    let request = |channel: u32, id: u32, data: &[u8], time: u64| {
+        let mut frame = CanFrame::new(channel, id, data);
+        frame.timestamp = time;
+        frame
+    };
+    responder.process(&request(0, 0x7E0, &[0x02, 0x10, 0x01], 1_000_000));
+    // 資料開頭不符、通道不符與自己送出的訊框都不回應
+    responder.process(&request(0, 0x7E0, &[0x02, 0x3E], 1_000_000));
+    responder.process(&request(0, 0x100, &[], 1_000_000));
+    let mut echo = request(0, 0x7E0, &[0x02, 0x10, 0x03], 1_000_000);
+    echo.direction = FrameDirection::Tx;
+    responder.process(&echo);
+    responder.process(&request(1, 0x100, &[0x01], 1_010_000));
+
+    let sent = responder.poll(1_000_000);
+    assert_eq!(sent.len(), 1);
+    assert_eq!(sent[0].channel, 0);
+    assert_eq!(sent[0].id, 0x7E8);
+    assert_eq!(
+        sent[0].payload(),
+        &[0x06, 0x50, 0x01, 0x00, 0x32, 0x01, 0xF4]
+    );
+    assert_eq!(responder.next_due(), Some(1_060_000));
+    assert!(responder.poll(1_059_999).is_empty());
+    let sent = responder.poll(1_060_000);
+    assert_eq!(sent.len(), 1);
+    assert_eq!((sent[0].channel, sent[0].id), (1, 0x18FF0001));
+    assert!(sent[0].extended);
+    assert_eq!((responder.hits(0), responder.hits(1)), (1, 1));
+
+    // 無法解析時保留原本的規則
+    let mut invalid = rules.clone();
+    invalid[1].response_data = "GG".to_string();
+    assert!(responder.set_rules(&invalid).is_err());
+    assert_eq!(responder.rules(), rules.as_slice());
+    responder.reset_counts();
+    assert_eq!(responder.hits(0), 0);
+
+    let layout = ProfileLayout {
+        auto_responses: rules,
+        ..Default::default()
+    };
+    let yaml = serde_yaml::to_string(&layout).unwrap();
+    assert_eq!(
+        serde_yaml::from_str::<ProfileLayout>(&yaml).unwrap(),
+        layout
+    );
+}
+
+#[test]
+fn receive_list_groups_frames_by_id() {
+    let frame = |channel: u32, id: u32, data: &[u8], time: u64| {
+        let mut frame = CanFrame::new(channel, id, data);
+        frame.timestamp = time;
+        frame
+    };
+    let mut list = ReceiveList::default();
+    list.process(&frame(0, 0x200, &[1, 2, 3], 1_000_000));
+    list.process(&frame(0, 0x100, &[0], 1_005_000));
+    list.process(&frame(0, 0x200, &[1, 9, 3], 1_010_000));
+    list.process(&frame(1, 0x100, &[0], 1_020_000));
+    list.process(&frame(0, 0x200, &[1, 9, 3, 4], 1_030_000));
+
+    let entries: Vec<_> = list.entries().collect();
+    assert_eq!(list.len(), 3);
+    let keys: Vec<(u32, u32, u64)> = entries
+        .iter()
+        .map(|e| (e.frame.channel, e.frame.id, e.count))
+        .collect();
+    assert_eq!(keys, vec![(0, 0x100, 1), (0, 0x200, 3), (1, 0x100, 1)]);
+
+    let entry = entries[1];
+    assert_eq!(entry.frame.payload(), &[1, 9, 3, 4]);
+    assert_eq!(entry.cycle_us, Some(20_000));
+    assert_eq!(entries[0].cycle_us, None);
+    // 第 1 個位元組在 1.01 s 改變，新增的第 3 個位元組在 1.03 s 出現
+    assert!(!entry.byte_changed(0, 1_030_000, 500_000));
+    assert!(entry.byte_changed(1, 1_030_000, 500_000));
+    assert!(!entry.byte_changed(1, 1_510_000, 500_000));
+    assert!(!entry.byte_changed(2, 1_030_000, 500_000));
+    assert!(entry.byte_changed(3, 1_100_000, 500_000));
+
+    list.clear();
+    assert!(list.is_empty());
+}
+
+#[test]
+fn frame_timestamps_relative_to_capture_start_and_per_id_delta() {
+    // TPCANTimestamp：毫秒計數溢位後仍連續
+    let timestamp = PcanTimestamp {
+        millis: 1500,
+        millis_overflow: 1,
+        micros: 250,
+    };
+    assert_eq!(timestamp.total_micros(), ((1u64 << 32) + 1500) * 1000 + 250);
+
+    let frame = |id: u32, host: u64, device: Option<u64>| {
+        let mut frame = CanFrame::new(0, id, &[]);
+        frame.timestamp = host;
+        frame.device_timestamp = device;
+        frame
+    };
+    let mut capture = CaptureStore::new(2);
+    assert_eq!(capture.start_time(), None);
+    capture.record(&frame(0x100, 5_000_000, None));
+    capture.record(&frame(0x100, 5_010_000, None));
+    capture.record(&frame(0x100, 5_020_000, None));
+    // 超過上限丟棄最舊的訊框時起點不變
+    assert_eq!(capture.start_time(), Some(5_000_000));
+    capture.clear();
+    assert_eq!(capture.start_time(), None);
+
+    // 兩筆都有硬體時間戳記時以硬體時間計算時間差，不受主機批次讀取影響
+    let first = frame(0x100, 5_000_000, Some(1_000));
+    let second = frame(0x100, 5_000_900, Some(11_000));
+    assert_eq!(second.delta_us(&first), 10_000);
+    assert_eq!(frame(0x100, 5_000_900, None).delta_us(&first), 900);
+}
+
+#[test]
+fn value_format_renders_ids_and_bytes() {
+    let data = [0x41, 0x42, 0x00, 0x7F, 0x20];
+    assert_eq!(ValueFormat::Hex.format_bytes(&data), "41 42 00 7F 20");
+    assert_eq!(ValueFormat::Decimal.format_bytes(&data), "65 66 0 127 32");
+    assert_eq!(ValueFormat::Binary.format_byte(0x05), "00000101");
+    assert_eq!(ValueFormat::Ascii.format_bytes(&data), "AB.. ");
+
+    assert_eq!(ValueFormat::Hex.format_id(0x7DF, false), "7DFh");
+    assert_eq!(ValueFormat::Hex.format_id(0x18DAF110, true), "18DAF110h");
+    assert_eq!(ValueFormat::Decimal.format_id(0x100, false), "256");
+    assert_eq!(ValueFormat::Binary.format_id(0x5, false), "00000000101");
+    assert_eq!(ValueFormat::Ascii.format_id(0x123, false), "123h");
+}
+
+#[test]
+fn signal_plot_records_updates_of_selected_signals() {
+    let mut signals = SignalTable::default();
+    signals.load(&[CanbusConfigEntry {
+        key: "pack_voltage".to_string(),
+        id: 0x300,
+        index: 0,
+        len: 2,
+        endian: ByteOrder::Little,
+        start_bit: None,
+        bit_length: None,
+        data_type: "uint16".to_string(),
+        scale: 1.0,
+        offset: 0.0,
+        signed: None,
+        values: Default::default(),
+    }]);
+    let mut dbc = DbcDecoder::default();
+    dbc.set_dbc(Dbc::parse(TEST_DBC).unwrap(), None);
+    let mut plot = SignalPlot::default();
+    assert!(plot.add("pack_voltage"));
+    assert!(plot.add("EngineData.CoolantTemp"));
+    assert!(!plot.add("pack_voltage"));
+
+    let frames = [
+        (0x300, vec![0x10, 0x0C], 1_000),
+        (0x100, vec![0, 0, 100, 0, 0, 0, 0, 0], 2_000),
+        (0x123, vec![0], 3_000),
+        (0x300, vec![0x20, 0x0C], 4_000),
+    ];
+    for (id, data, time) in frames {
+        let mut frame = CanFrame::new(0, id, &data);
+        frame.timestamp = time;
+        signals.process(&frame);
+        dbc.process(&frame);
+        plot.record(&signals, &dbc, frame.timestamp);
+    }
+    // 只記錄被該筆訊框更新的訊號，不重複記錄沒有變化的時間點
+    let traces = plot.traces();
+    assert_eq!(
+        traces[0].samples.iter().copied().collect::<Vec<_>>(),
+        vec![(1_000, 3088.0), (4_000, 3104.0)]
+    );
+    assert_eq!(
+        traces[1].samples.iter().copied().collect::<Vec<_>>(),
+        vec![(2_000, 60.0)]
+    );
+    assert_eq!(
+        plot::signal_value(&signals, &dbc, "EngineData.CoolantTemp"),
+        Some((60.0, 2_000))
+    );
+    assert_eq!(plot::signal_value(&signals, &dbc, "Missing.Signal"), None);
+
+    plot.set_keys(&[
+        "EngineData.CoolantTemp".to_string(),
+        "Diag.Voltage".to_string(),
+    ]);
+    assert_eq!(plot.keys(), vec!["EngineData.CoolantTemp", "Diag.Voltage"]);
+    assert_eq!(plot.traces()[0].samples.len(), 1);
+    plot.clear();
+    assert!(plot.traces()[0].samples.is_empty());
+}
+
+#[test]
+fn frame_filter_compiles_and_matches_expressions() {
+    let filter = |text: &str| FrameFilter::compile(text).unwrap();
+    let mut frame = CanFrame::new(1, 0x123, &[0x81, 0x22]);
+
+    let example = filter("id == 0x123 && data[0] > 0x80 && ch == 1");
+    assert!(example.matches(&frame));
+    frame.channel = 0;
+    assert!(!example.matches(&frame));
+    frame.channel = 1;
+    assert!(!example.matches(&CanFrame::new(1, 0x123, &[0x80, 0x22])));
+
+    let list = filter("id in [0x100, 0x123, 291] && !tx");
+    assert!(list.matches(&frame));
+    assert!(!list.matches(&CanFrame::new(1, 0x124, &[])));
+    frame.direction = FrameDirection::Tx;
+    assert!(!list.matches(&frame));
+    frame.direction = FrameDirection::Rx;
+
+    // && 優先於 ||，括號可改變順序；位元運算優先於比較
+    assert!(filter("id == 0x123 || id == 0x200 && ch == 5").matches(&frame));
+    assert!(!filter("(id == 0x123 || id == 0x200) && ch == 5").matches(&frame));
+    assert!(filter("data[0] & 0x0F == 0x01 && !(dlc > 2)").matches(&frame));
+    assert!(filter("ID == 0X123 && data[ch] == 0x22").matches(&frame));
+
+    // 超出訊框長度的 data[n] 使比較不成立，不會當成 0
+    assert!(filter("data[1] == 0x22").matches(&frame));
+    assert!(!filter("data[2] == 0").matches(&frame));
+    assert!(!filter("data[2] != 0").matches(&frame));
+    assert!(!filter("data[8] in [0, 1]").matches(&frame));
+
+    // 錯誤訊息指出出錯的位置
+    let error = |text: &str| FrameFilter::compile(text).unwrap_err();
+    assert_eq!(
+        error("id == 0x123 && data[0] >"),
+        "Unexpected end of expression at 24"
+    );
+    assert_eq!(error("id == 1 && foo == 2"), "Unknown field 'foo' at 11");
+    assert_eq!(error("id = 1"), "Unexpected '=' at 3");
+    assert_eq!(error("(id == 1"), "Expected ')' at 8");
+    assert_eq!(error("id in [1, 2"), "Expected ']' at 11");
+    assert_eq!(error("id == 1 2"), "Unexpected input at 8");
+    assert_eq!(error("  "), "Empty filter expression");
+}
+
+#[test]
+fn term_filter_matches_ids_and_text() {
+    let mut terms = TermFilter::default();
+    assert!(terms.matches_text("anything"));
+    assert!(terms.matches_frame(&CanFrame::new(0, 0x100, &[])));
+
+    terms.set_text(" 0x2A1, Error ,");
+    assert!(!terms.is_empty());
+    assert!(terms.matches_frame(&CanFrame::new(0, 0x2A1, &[1])));
+    assert!(!terms.matches_frame(&CanFrame::new(0, 0x2A10, &[1])));
+    assert!(!terms.matches_frame(&CanFrame::new(0, 0x100, &[1])));
+    assert!(terms.matches_text("Bus ERROR on CH0"));
+    assert!(terms.matches_text("Sent ID=0x2a1"));
+    assert!(!terms.matches_text("Sent ID=0x2A10"));
+    assert!(!terms.matches_text("Channel started"));
+
+    // 文字詞彙比對追蹤畫面的單行格式
+    terms.set_text("[tx]");
+    let mut frame = CanFrame::new(1, 0x7E0, &[2]);
+    assert!(!terms.matches_frame(&frame));
+    frame.direction = FrameDirection::Tx;
+    assert!(terms.matches_frame(&frame));
+}
+
+#[test]
+fn copied_frames_render_as_text_or_csv() {
+    let mut first = CanFrame::new(0, 0x123, &[0x01, 0xAB]);
+    first.timestamp = 1_700_000_000_000_000;
+    let mut second = CanFrame::new(1, 0x2C, &[0x2C, 0x22]);
+    second.timestamp = 1_700_000_000_500_000;
+    second.direction = FrameDirection::Tx;
+    let frames = [&first, &second];
+
+    let text = frames_to_clipboard(
+        &frames,
+        CopyFormat::Text,
+        ValueFormat::Hex,
+        ValueFormat::Hex,
+    );
+    let lines: Vec<&str> = text.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].ends_with("[DATA] CH=0 ID=0x123, Data=[1, 171]"));
+    assert!(lines[1].ends_with("[TX] CH=1 ID=0x2C, Data=[44, 34]"));
+
+    let csv = frames_to_clipboard(&frames, CopyFormat::Csv, ValueFormat::Hex, ValueFormat::Hex);
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines[0], "Time,Dir,CH,ID,DLC,Data");
+    assert!(lines[1].ends_with(",RX,0,123h,2,01 AB"));
+    assert!(lines[2].ends_with(",TX,1,02Ch,2,2C 22"));
+
+    // ASCII 資料中的逗號與引號需加上引號
+    let csv = frames_to_clipboard(
+        &frames,
+        CopyFormat::Csv,
+        ValueFormat::Decimal,
+        ValueFormat::Ascii,
+    );
+    assert!(csv
+        .lines()
+        .nth(2)
+        .unwrap()
+        .ends_with(",TX,1,44,2,\",\"\"\""));
+}
+
+#[test]
+fn bus_statistics_track_rate_load_and_cycle_times() {
+    let mut stats = BusStatistics::default();
+    stats.set_bitrate(0, 500_000);
+    let start = 10_000_000;
+    // 0x100 每 10 ms 一筆，最後一筆晚 5 ms
+    let mut times: Vec<u64> = (0..99).map(|i| start + i * 10_000).collect();
+    times.push(start + 98 * 10_000 + 15_000);
+    for &time in &times {
+        let mut frame = CanFrame::new(0, 0x100, &[0; 8]);
+        frame.timestamp = time;
+        stats.process(&frame);
+    }
+    stats.poll(start + 1_000_000);
+
+    let channel = stats.channels().next().unwrap();
+    assert_eq!(channel.frames, 100);
+    assert!(
+        (channel.frame_rate - 100.0).abs() < 1e-9,
+        "{}",
+        channel.frame_rate
+    );
+    let expected_load = 100.0 * frame_bits(&CanFrame::new(0, 0x100, &[0; 8])) as f64 / 5_000.0;
+    assert!(
+        (channel.bus_load - expected_load).abs() < 0.1,
+        "{}",
+        channel.bus_load
+    );
+
+    let id = stats.ids().next().unwrap();
+    assert_eq!(id.min_period_us, Some(10_000));
+    assert_eq!(id.max_period_us, Some(15_000));
+    assert!((id.mean_period_ms().unwrap() - 995.0 / 99.0).abs() < 1e-9);
+    assert!((id.jitter_ms().unwrap() - 5.0).abs() < 1e-9);
+    // 98 個 10 ms 與一個 15 ms 的樣本標準差
+    let mean = 995.0 / 99.0;
+    let variance = (98.0 * (10.0 - mean) * (10.0 - mean) + (15.0 - mean) * (15.0 - mean)) / 98.0;
+    let std = id.period_std_ms().unwrap();
+    assert!((std - f64::sqrt(variance)).abs() < 1e-9, "{}", std);
+
+    stats.reset(start + 2_000_000);
+    assert_eq!(stats.ids().count(), 0);
+    assert_eq!(stats.channels().next().unwrap().frames, 0);
+}
+
+#[test]
+fn bus_status_reports_error_state_transitions() {
+    assert_eq!(BusState::from_counters(0, 95, false), BusState::ErrorActive);
+    assert_eq!(
+        BusState::from_counters(96, 0, false),
+        BusState::ErrorWarning
+    );
+    assert_eq!(
+        BusState::from_counters(10, 128, false),
+        BusState::ErrorPassive
+    );
+    assert_eq!(BusState::from_counters(0, 0, true), BusState::BusOff);
+
+    let board = BusStatusBoard::default();
+    let status = |tec: u8, rec: u8, bus_off: bool| ControllerStatus {
+        state: BusState::from_counters(tec, rec, bus_off),
+        tec: Some(tec),
+        rec: Some(rec),
+    };
+    // 狀態不變時不重複回報
+    assert_eq!(board.update(0, status(5, 0, false)), None);
+    let event = board.update(0, status(130, 2, false)).unwrap();
+    assert_eq!(event.code(), "W207");
+    assert_eq!(
+        event.format(Language::English),
+        "Channel 0 controller is Error Passive (TEC=130, REC=2)"
+    );
+    assert_eq!(board.update(0, status(140, 2, false)), None);
+    assert_eq!(board.update(0, status(0, 0, true)).unwrap().code(), "E307");
+    assert_eq!(board.update(0, status(0, 0, false)).unwrap().code(), "I112");
+    assert_eq!(
+        board.update(1, status(100, 0, false)).unwrap().code(),
+        "W207"
+    );
+    let snapshot = board.snapshot();
+    assert_eq!(snapshot.len(), 2);
+    assert_eq!(snapshot[1].1.state, BusState::ErrorWarning);
+}
+
+#[test]
+fn decodes_pcan_status_codes_and_error_frames() {
+    assert_eq!(pcan_bus_state(0x0000), BusState::ErrorActive);
+    // 接收佇列空不是匯流排錯誤
+    assert_eq!(pcan_bus_state(0x0020), BusState::ErrorActive);
+    assert_eq!(pcan_bus_state(0x0004), BusState::ErrorWarning);
+    assert_eq!(pcan_bus_state(0x0008), BusState::ErrorWarning);
+    assert_eq!(pcan_bus_state(0x40008), BusState::ErrorPassive);
+    assert_eq!(pcan_bus_state(0x0018), BusState::BusOff);
+
+    assert_eq!(pcan_error_kind(4), "stuff error");
+    assert_eq!(pcan_error_kind(16), "other error");
+    let event = LogEvent::ErrorFrames {
+        channel: 0x51,
+        count: 12,
+        last: pcan_error_kind(1).to_string(),
+    };
+    assert_eq!(event.code(), "W208");
+    assert_eq!(
+        event.format(Language::English),
+        "Channel 81 received 12 error frames (last: bit error)"
+    );
+}
+
+/// 可模擬拔除與重新插入的介面，記錄開啟與關閉次數
+#[derive(Clone, Default)]
+struct UnpluggableCan {
+    unplugged: Arc<AtomicBool>,
+    opens: Arc<AtomicU64>,
+    closes: Arc<AtomicU64>,
+}
+
+impl CanInterface for UnpluggableCan {
+    fn open_device(&self, _log_tx: flume::Sender<LogEvent>) -> Result<(), String> {
+        if self.unplugged.load(Ordering::SeqCst) {
+            return Err("device not found".to_string());
+        }
+        self.opens.fetch_add(1, Ordering::SeqCst);
+        Ok(())
+    }
+    fn close_device(&self, _log_tx: flume::Sender<LogEvent>) {
+        self.closes.fetch_add(1, Ordering::SeqCst);
+    }
+    fn start_receiving(&self, _log_tx: flume::Sender<LogEvent>, _data_tx: flume::Sender<CanFrame>) {
+    }
+    fn stop_receiving(&self) {}
+    fn read_board_info(&self, _log_tx: flume::Sender<LogEvent>) {}
+    fn send_frame(&self, _frame: &CanFrame) -> Result<(), String> {
+        Ok(())
+    }
+    fn check_device(&self) -> Result<(), String> {
+        if self.unplugged.load(Ordering::SeqCst) {
+            Err("device unplugged".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[test]
+fn watchdog_reopens_unplugged_device() {
+    let device = UnpluggableCan::default();
+    let can = SharedCan::default();
+    can.attach("Unpluggable", Box::new(device.clone()));
+    let (log_tx, log_rx) = flume::unbounded();
+    let (data_tx, _data_rx) = flume::unbounded();
+    let running = Arc::new(AtomicBool::new(true));
+    let watchdog = {
+        let (can, running) = (can.clone(), Arc::clone(&running));
+        std::thread::spawn(move || {
+            let watchdog = ReconnectWatchdog {
+                check_interval: Duration::from_millis(10),
+                retry_interval: Duration::from_millis(10),
+            };
+            let enabled = AtomicBool::new(true);
+            watchdog.run(&can, &log_tx, &data_tx, &enabled, || {
+                running.load(Ordering::SeqCst)
+            });
+        })
+    };
+
+    device.unplugged.store(true, Ordering::SeqCst);
+    let lost = log_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+    assert_eq!(lost.code(), "E308");
+    // 拔除期間持續重試
+    let failed = log_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+    assert_eq!(failed.code(), "W209");
+    device.unplugged.store(false, Ordering::SeqCst);
+    let reconnected = log_rx.iter().find(|event| event.code() != "W209").unwrap();
+    assert_eq!(reconnected.code(), "I113");
+    assert_eq!(device.opens.load(Ordering::SeqCst), 1);
+    assert!(!can.is_empty());
+    assert!(can.failed_devices().is_empty());
+
+    running.store(false, Ordering::SeqCst);
+    watchdog.join().unwrap();
+}
+
+#[test]
+fn bit_timing_calculator_matches_preset_registers() {
+    let timing = |bitrate, sample_point| compute_sja1000_timing(bitrate, sample_point).unwrap();
+    assert_eq!(
+        timing(500_000, 87.5),
+        Sja1000Timing {
+            btr0: 0x00,
+            btr1: 0x1C
+        }
+    );
+    let (timing0, timing1) = VciCanBaudRate::Baud125K.to_timing_values();
+    assert_eq!(
+        timing(125_000, 87.5),
+        Sja1000Timing {
+            btr0: timing0,
+            btr1: timing1
+        }
+    );
+    assert_eq!(
+        timing(250_000, 87.5).to_u16(),
+        PcanBaudRate::Baud250K.to_u16()
+    );
+    assert_eq!(
+        timing(1_000_000, 75.0).to_u16(),
+        PcanBaudRate::Baud1M.to_u16()
+    );
+    assert!(compute_sja1000_timing(0, 87.5).is_none());
+
+    // 預設表沒有的速率
+    let odd = timing(33_333, 87.5);
+    assert!((odd.bitrate() - 33_333.0).abs() < 1.0);
+    assert_eq!(odd.sample_point(), 87.5);
+
+    let preset = Sja1000Timing::from_u16(PcanBaudRate::Baud100K.to_u16());
+    assert_eq!((preset.brp(), preset.tseg1(), preset.tseg2()), (4, 16, 3));
+    assert_eq!(preset.sjw(), 2);
+    assert_eq!(preset.bitrate(), 100_000.0);
+    assert_eq!(preset.sample_point(), 85.0);
+
+    // 自訂值原樣傳給 API
+    let custom = VciCanBaudRate::Custom {
+        timing0: 0x0E,
+        timing1: 0x1C,
+    };
+    assert_eq!(custom.to_timing_values(), (0x0E, 0x1C));
+    assert_eq!(PcanBaudRate::Custom(0x0E1C).to_u16(), 0x0E1C);
+}
+
+/// 記錄送出訊框的假介面，通道可自訂；開始接收時送出 `received` 中的訊框
+#[derive(Clone, Default)]
+struct RecordingCan {
+    channels: Vec<u32>,
+    sent: Arc<Mutex<Vec<CanFrame>>>,
+    received: Vec<CanFrame>,
+}
+
+impl CanInterface for RecordingCan {
+    fn open_device(&self, _log_tx: flume::Sender<LogEvent>) -> Result<(), String> {
+        Ok(())
+    }
+    fn close_device(&self, _log_tx: flume::Sender<LogEvent>) {}
+    fn start_receiving(&self, _log_tx: flume::Sender<LogEvent>, data_tx: flume::Sender<CanFrame>) {
+        for frame in &self.received {
+            data_tx.send(*frame).unwrap();
+        }
+    }
+    fn stop_receiving(&self) {}
+    fn read_board_info(&self, _log_tx: flume::Sender<LogEvent>) {}
+    fn send_frame(&self, frame: &CanFrame) -> Result<(), String> {
+        self.sent.lock().unwrap().push(*frame);
+        Ok(())
+    }
+    fn channels(&self) -> Vec<u32> {
+        self.channels.clone()
+    }
+}
+
+#[test]
+fn shared_can_routes_frames_to_the_interface_owning_the_channel() {
+    let controlcan = RecordingCan {
+        channels: vec![0, 1],
+        ..Default::default()
+    };
+    let pcan = RecordingCan {
+        channels: vec![0x51],
+        ..Default::default()
+    };
+    let can = SharedCan::default();
+    let first = can.attach("ControlCAN", Box::new(controlcan.clone()));
+    let second = can.attach("PCAN", Box::new(pcan.clone()));
+    assert_ne!(first, second);
+
+    can.send_frame(&CanFrame::new(1, 0x100, &[1])).unwrap();
+    can.send_frame(&CanFrame::new(0x51, 0x200, &[2])).unwrap();
+    assert!(can.send_frame(&CanFrame::new(7, 0x300, &[3])).is_err());
+    assert_eq!(controlcan.sent.lock().unwrap()[0].id, 0x100);
+    assert_eq!(pcan.sent.lock().unwrap()[0].id, 0x200);
+    let names: Vec<String> = can.interfaces().into_iter().map(|(_, n, _)| n).collect();
+    assert_eq!(names, ["ControlCAN", "PCAN"]);
+
+    // 只剩一個介面時不論通道都由它送出
+    assert!(can.detach(first).is_some());
+    can.send_frame(&CanFrame::new(0, 0x400, &[4])).unwrap();
+    assert_eq!(pcan.sent.lock().unwrap().len(), 2);
+    assert_eq!(can.detach_all().len(), 1);
+    assert!(can.send_frame(&CanFrame::new(0, 0x400, &[4])).is_err());
+}
+
+#[test]
+fn gateway_forwards_one_way_with_filters_and_remapping() {
+    let controlcan = RecordingCan {
+        channels: vec![0],
+        ..Default::default()
+    };
+    let pcan = RecordingCan {
+        channels: vec![0x51],
+        ..Default::default()
+    };
+    let can = SharedCan::default();
+    can.attach("ControlCAN", Box::new(controlcan.clone()));
+    can.attach("PCAN", Box::new(pcan.clone()));
+    let config = GatewayConfig {
+        channel_a: 0,
+        channel_b: 0x51,
+        direction: GatewayDirection::AToB,
+        filter: vec![
+            IdFilterRule {
+                ids: "100-1FF".to_string(),
+                ..Default::default()
+            },
+            IdFilterRule {
+                mode: FilterMode::Block,
+                ids: "150".to_string(),
+                ..Default::default()
+            },
+        ],
+        remaps: parse_remaps("120=18DAF110\n7DF = 7E0").unwrap(),
+        script: None,
+    };
+    assert!(parse_remaps("120").is_err());
+    let mut gateway = Gateway::start(config, can.clone(), |_| {}).unwrap();
+    for (channel, id) in [
+        (0, 0x100),
+        (0, 0x150),
+        (0, 0x300),
+        (0, 0x120),
+        (0x51, 0x101),
+    ] {
+        gateway.forward(&CanFrame::new(channel, id, &[1, 2]));
+    }
+    let deadline = Instant::now() + Duration::from_secs(2);
+    while pcan.sent.lock().unwrap().len() < 2 && Instant::now() < deadline {
+        std::thread::sleep(Duration::from_millis(5));
+    }
+    gateway.stop();
+
+    let sent = pcan.sent.lock().unwrap();
+    let ids: Vec<(u32, u32, bool)> = sent.iter().map(|f| (f.channel, f.id, f.extended)).collect();
+    assert_eq!(ids, [(0x51, 0x100, false), (0x51, 0x18DAF110, true)]);
+    // B -> A 方向不轉送
+    assert!(controlcan.sent.lock().unwrap().is_empty());
+}
+
+#[test]
+fn gateway_script_results_drop_forward_modify_and_delay() {
+    let pcan = RecordingCan {
+        channels: vec![1],
+        ..Default::default()
+    };
+    let can = SharedCan::default();
+    can.attach("PCAN", Box::new(pcan.clone()));
+    let script = r#"
+fn on_frame(frame) {
+    switch frame.id {
+        0x100 => (),
+        0x101 => true,
+        0x102 => false,
+        0x103 => { frame.id = 0x18FF0000; frame.data[0] = 0xAA; frame }
+        0x104 => { frame.delay_ms = 300; frame }
+        0x105 => { frame.data = [256]; frame }
+        0x106 => { frame.data = [-1]; frame }
+        0x107 => { frame.id = 0x20000000; frame }
+        0x108 => { frame.data = [0, 1, 2, 3, 4, 5, 6, 7, 8]; frame }
+        0x109 => { loop {} }
+        _ => frame
+    }
+}
+"#;
+    let config = GatewayConfig {
+        channel_a: 0,
+        channel_b: 1,
+        script: Some(script.to_string()),
+        ..Default::default()
+    };
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let log_sink = Arc::clone(&logs);
+    let mut gateway = Gateway::start(config, can.clone(), move |line| {
+        log_sink.lock().unwrap().push(line)
+    })
+    .unwrap();
+    let delayed_at = Instant::now();
+    for id in 0x100..=0x10A {
+        gateway.forward(&CanFrame::new(0, id, &[1, 2]));
+    }
+    assert!(wait_until(|| pcan.sent.lock().unwrap().len() == 4));
+    let delay = delayed_at.elapsed();
+    gateway.stop();
+
+    // 延遲的訊框最後才送出；無窮迴圈達到運算上限後丟棄，後面的訊框照常轉送
+    let sent: Vec<(u32, u32, bool, Vec<u8>)> = pcan
+        .sent
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|f| (f.channel, f.id, f.extended, f.payload().to_vec()))
+        .collect();
+    assert_eq!(
+        sent,
+        [
+            (1, 0x101, false, vec![1, 2]),
+            (1, 0x18FF0000, true, vec![0xAA, 2]),
+            (1, 0x10A, false, vec![1, 2]),
+            (1, 0x104, false, vec![1, 2]),
+        ]
+    );
+    assert!(delay >= Duration::from_millis(300), "{:?}", delay);
+
+    let logs = logs.lock().unwrap();
+    let errors: Vec<&String> = logs
+        .iter()
+        .filter(|line| line.starts_with("Gateway script runtime error"))
+        .collect();
+    assert_eq!(errors.len(), 5, "{:?}", logs);
+    for expected in [
+        "frame.data value 256 is outside 0..=255",
+        "frame.data value -1 is outside 0..=255",
+        "frame.id 536870912 is outside 0..=0x1FFFFFFF",
+        "frame.data has 9 bytes, this frame holds at most 8",
+        "operations",
+    ] {
+        assert!(
+            errors.iter().any(|line| line.contains(expected)),
+            "missing {:?} in {:?}",
+            expected,
+            errors
+        );
+    }
+}
+
+#[test]
+fn gateway_routes_by_direction_and_remaps_ids() {
+    let forwarded = |direction: GatewayDirection| {
+        let bus = RecordingCan {
+            channels: vec![0, 1],
+            ..Default::default()
+        };
+        let can = SharedCan::default();
+        can.attach("ControlCAN", Box::new(bus.clone()));
+        let config = GatewayConfig {
+            channel_a: 0,
+            channel_b: 1,
+            direction,
+            remaps: parse_remaps("100=7E8").unwrap(),
+            ..Default::default()
+        };
+        let mut gateway = Gateway::start(config, can, |_| {}).unwrap();
+        for channel in [0, 1, 2] {
+            gateway.forward(&CanFrame::new(channel, 0x100 + channel, &[]));
+        }
+        let expected = if direction == GatewayDirection::Both {
+            2
+        } else {
+            1
+        };
+        assert!(wait_until(|| bus.sent.lock().unwrap().len() == expected));
+        // 給不該轉送的訊框一點時間，確認沒有多送
+        std::thread::sleep(Duration::from_millis(50));
+        gateway.stop();
+        let sent = bus.sent.lock().unwrap();
+        sent.iter().map(|f| (f.channel, f.id)).collect::<Vec<_>>()
+    };
+    assert_eq!(forwarded(GatewayDirection::Both), [(1, 0x7E8), (0, 0x101)]);
+    assert_eq!(forwarded(GatewayDirection::AToB), [(1, 0x7E8)]);
+    assert_eq!(forwarded(GatewayDirection::BToA), [(0, 0x101)]);
+}
+
+#[test]
+fn gateway_remaps_parse_hex_pairs() {
+    let remaps = parse_remaps(" 100=200, 0x7DF = 7E0\n\n18DAF110=0X18DA10F1,").unwrap();
+    let pairs: Vec<(u32, u32)> = remaps.iter().map(|r| (r.from, r.to)).collect();
+    assert_eq!(
+        pairs,
+        [(0x100, 0x200), (0x7DF, 0x7E0), (0x18DAF110, 0x18DA10F1)]
+    );
+    assert!(parse_remaps("").unwrap().is_empty());
+
+    assert_eq!(
+        parse_remaps("100=200, 300").unwrap_err(),
+        "Invalid ID remap '300', expected from=to"
+    );
+    assert_eq!(parse_remaps("100=xyz").unwrap_err(), "Invalid ID 'xyz'");
+    assert_eq!(
+        parse_remaps("100=20000000").unwrap_err(),
+        "Invalid ID remap '100=20000000', target exceeds 29 bits"
+    );
+}
+
+#[test]
+fn forward_latency_counts_histogram_buckets() {
+    let mut latency = ForwardLatency::default();
+    assert_eq!(latency.mean_us(), 0.0);
+    // 區間為 [下界, 上界)，剛好等於上界的值落在下一格
+    for us in [10, 49, 50, 99, 100, 750, 1_000, 49_999, 50_000, 2_000_000] {
+        latency.record(us);
+    }
+    assert_eq!(latency.count, 10);
+    assert_eq!((latency.min_us, latency.max_us), (10, 2_000_000));
+    assert_eq!(latency.mean_us(), 2_102_057.0 / 10.0);
+    assert_eq!(latency.histogram, [2, 2, 1, 0, 1, 1, 0, 0, 0, 1, 2]);
+    assert_eq!(latency.histogram.iter().sum::<u64>(), latency.count);
+    assert_eq!(ForwardLatency::bucket_label(0), "<50 µs");
+    assert_eq!(ForwardLatency::bucket_label(4), "<1 ms");
+    assert_eq!(
+        ForwardLatency::bucket_label(LATENCY_BUCKETS_US.len()),
+        ">=50 ms"
+    );
+}
+
+#[test]
+fn config_signals_apply_scale_offset_and_float_types() {
+    let yaml = r#"
+version: 2
+components: []
+canbus_config:
+  - key: coolant
+    id: 0x200
+    index: 0
+    len: 2
+    endian: little
+    type: uint16
+    scale: 0.1
+    offset: -40
+  - key: torque
+    id: 0x200
+    index: 2
+    len: 2
+    endian: big
+    type: uint16
+    signed: true
+    scale: 0.5
+  - key: current
+    id: 0x201
+    index: 0
+    len: 4
+    endian: little
+    type: float
+  - key: energy
+    id: 0x202
+    index: 0
+    len: 8
+    endian: big
+    type: double
+"#;
+    let (cfg, warnings) = load_config(yaml, "scaled.yaml");
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    let entry = |key: &str| cfg.canbus_config.iter().find(|e| e.key == key).unwrap();
+
+    // 1234 × 0.1 − 40 = 83.4 °C；0xFF38 = −200 × 0.5 = −100 Nm
+    let data = [0xD2, 0x04, 0xFF, 0x38];
+    let coolant = extract_value(entry("coolant"), &data).unwrap();
+    assert!((coolant - 83.4).abs() < 1e-9);
+    assert_eq!(extract_value(entry("torque"), &data), Some(-100.0));
+    assert_eq!(
+        extract_value(entry("current"), &12.5f32.to_le_bytes()),
+        Some(12.5)
+    );
+    assert_eq!(
+        extract_value(entry("energy"), &1234.5678f64.to_be_bytes()),
+        Some(1234.5678)
+    );
+
+    let mut encoded = Vec::new();
+    encode_value(entry("coolant"), 83.4, &mut encoded).unwrap();
+    encode_value(entry("torque"), -100.0, &mut encoded).unwrap();
+    assert_eq!(encoded, data);
+    // 原始值 0..=65535 對應 −40..=6513.5
+    assert!(encode_value(entry("coolant"), -41.0, &mut encoded).is_err());
+    let mut float = Vec::new();
+    encode_value(entry("current"), -3.25, &mut float).unwrap();
+    assert_eq!(float, (-3.25f32).to_le_bytes());
+
+    let load_err = |bad: String| {
+        let path = temp_path("bad_float.yaml");
+        fs::write(&path, bad).unwrap();
+        let err = config::load_config(path.to_str().unwrap()).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        err.to_string()
+    };
+    let err = load_err(yaml.replace("type: double", "type: float"));
+    assert!(err.contains("needs len 4"), "{}", err);
+    // index/len 的位置同樣受 64 位元與 64-byte 訊框限制，不會載入後永遠解不出值
+    let err = load_err(yaml.replace("    len: 8\n", "    len: 9\n"));
+    assert!(err.contains("'energy': len must be 1..=8"), "{}", err);
+    let err = load_err(yaml.replace("    index: 0\n    len: 8\n", "    index: 60\n    len: 8\n"));
+    assert!(
+        err.contains("'energy': index + len is outside a 64-byte frame"),
+        "{}",
+        err
+    );
+    let (cfg, warnings) = load_config(
+        &yaml.replace("    index: 0\n    len: 8\n", "    index: 56\n    len: 8\n"),
+        "last_bytes.yaml",
+    );
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    assert_eq!(cfg.canbus_config[3].byte_end(), 64);
+}
+
+#[test]
+fn config_signals_support_bit_level_positions() {
+    let yaml = r#"
+version: 2
+components: []
+canbus_config:
+  - key: gear
+    id: 0x300
+    start_bit: 4
+    bit_length: 3
+    endian: little
+    type: uint8
+  - key: brake
+    id: 0x300
+    start_bit: 8
+    bit_length: 1
+    endian: little
+    type: uint8
+  - key: speed
+    id: 0x300
+    start_bit: 12
+    bit_length: 12
+    endian: little
+    type: uint16
+    scale: 0.1
+  - key: torque
+    id: 0x300
+    start_bit: 39
+    bit_length: 12
+    endian: big
+    type: int16
+"#;
+    let (cfg, warnings) = load_config(yaml, "bits.yaml");
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    let entry = |key: &str| cfg.canbus_config.iter().find(|e| e.key == key).unwrap();
+
+    // gear 在 byte 0 的 bit 4..7；speed 0xABC 跨 byte 1 高 4 位元與 byte 2；
+    // torque 為 Motorola，由 byte 4 的 bit 7 往下共 12 位元，0xFFD = −3
+    let data = [0x50, 0xC1, 0xAB, 0x00, 0xFF, 0xD0];
+    assert_eq!(extract_value(entry("gear"), &data), Some(5.0));
+    assert_eq!(extract_value(entry("brake"), &data), Some(1.0));
+    let speed = extract_value(entry("speed"), &data).unwrap();
+    assert!((speed - 274.8).abs() < 1e-9);
+    assert_eq!(extract_value(entry("torque"), &data), Some(-3.0));
+    assert_eq!(entry("torque").byte_end(), 6);
+    assert_eq!(extract_value(entry("torque"), &data[..5]), None);
+
+    // 共用位元組的訊號各自寫入，不會覆蓋彼此
+    let mut encoded = Vec::new();
+    encode_value(entry("torque"), -3.0, &mut encoded).unwrap();
+    encode_value(entry("speed"), 274.8, &mut encoded).unwrap();
+    encode_value(entry("brake"), 1.0, &mut encoded).unwrap();
+    encode_value(entry("gear"), 5.0, &mut encoded).unwrap();
+    assert_eq!(encoded, data);
+    assert!(encode_value(entry("gear"), 8.0, &mut encoded).is_err());
+
+    let load_err = |bad: String| {
+        let path = temp_path("bad_bits.yaml");
+        fs::write(&path, bad).unwrap();
+        let err = config::load_config(path.to_str().unwrap()).unwrap_err();
+        fs::remove_file(&path).unwrap();
+        err.to_string()
+    };
+    let err = load_err(yaml.replacen("    bit_length: 3\n", "", 1));
+    assert!(err.contains("given together"), "{}", err);
+    // 沒有 len 也沒有 start_bit 的訊號不能默默載入成零長度
+    let err = load_err(yaml.replacen("    start_bit: 4\n    bit_length: 3\n", "", 1));
+    assert!(err.contains("'gear': needs len"), "{}", err);
+    // 過大的起始位元在計算位元範圍前就被拒絕，不會溢位
+    for start_bit in ["512", "4294967295"] {
+        let err = load_err(yaml.replacen("start_bit: 39", &format!("start_bit: {}", start_bit), 1));
+        assert!(err.contains("outside a 64-byte frame"), "{}", err);
+    }
+}
+
+#[test]
+fn dashboard_components_read_live_decoded_values() {
+    let yaml = CONFIG.replace(
+        "canbus_config:",
+        "  - type: Label\n    key: EngineData.CoolantTemp\n  - type: Led\n    key: missing\ncanbus_config:",
+    );
+    let (cfg, warnings) = load_config(&yaml, "live.yaml");
+    assert_eq!(
+        warnings,
+        ["Component 'missing' has no canbus_config entry and will show no value"]
+    );
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let mut dbc = DbcDecoder::default();
+    dbc.set_dbc(Dbc::parse(TEST_DBC).unwrap(), None);
+    let mut units = DisplayUnits::default();
+    units.load(&cfg.components);
+    let reading = |key: &str, signals: &SignalTable, dbc: &DbcDecoder, units: &DisplayUnits| {
+        let comp = cfg.components.iter().find(|c| c.key == key).unwrap();
+        Reading::of(comp, signals, dbc, units)
+    };
+
+    // 收到訊框前沒有數值
+    let speed = reading("speed", &signals, &dbc, &units);
+    assert_eq!((speed.raw, speed.updated_at), (None, 0));
+    assert_eq!(speed.unit, "km/h");
+
+    let mut frame = CanFrame::new(0, 0x100, &[0x64, 0x00, 0xF6]);
+    frame.timestamp = 5_000;
+    signals.process(&frame);
+    dbc.process(&frame);
+    let speed = reading("speed", &signals, &dbc, &units);
+    assert_eq!((speed.raw, speed.value), (Some(100.0), Some(100.0)));
+    assert_eq!(speed.updated_at, 5_000);
+    assert_eq!(reading("temp", &signals, &dbc, &units).raw, Some(-10.0));
+    // 同一筆訊框也以 DBC 解碼：0xF6 = −10，−10 − 40 = −50 °C
+    assert_eq!(
+        reading("EngineData.CoolantTemp", &signals, &dbc, &units).raw,
+        Some(-50.0)
+    );
+    assert_eq!(reading("missing", &signals, &dbc, &units).raw, None);
+
+    units.select("speed", "mph");
+    let speed = reading("speed", &signals, &dbc, &units);
+    assert_eq!(speed.raw, Some(100.0));
+    assert!((speed.value.unwrap() - 62.14).abs() < 0.01);
+    assert_eq!(speed.unit, "mph");
+}
+
+#[test]
+fn config_signal_values_map_states_to_names() {
+    let yaml = r#"
+version: 2
+components:
+  - type: Label
+    key: charger
+canbus_config:
+  - key: charger
+    id: 0x400
+    start_bit: 0
+    bit_length: 2
+    endian: little
+    type: uint8
+    values:
+      0: "Off"
+      1: "Charging"
+      2: "Fault"
+  - key: level
+    id: 0x400
+    index: 1
+    len: 1
+    endian: little
+    type: uint8
+    scale: 0.5
+    values:
+      255: "Invalid"
+"#;
+    let (cfg, warnings) = load_config(yaml, "values.yaml");
+    assert!(warnings.is_empty(), "{:?}", warnings);
+    let level = cfg.canbus_config.iter().find(|e| e.key == "level").unwrap();
+    // 以原始值對應：0xFF × 0.5 = 127.5
+    assert_eq!(level.value_name(127.5), Some("Invalid"));
+    assert_eq!(level.value_name(127.25), None);
+
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let mut units = DisplayUnits::default();
+    units.load(&cfg.components);
+    let dbc = DbcDecoder::default();
+    let comp = &cfg.components[0];
+
+    signals.process(&CanFrame::new(0, 0x400, &[0x01, 0xFF]));
+    let reading = Reading::of(comp, &signals, &dbc, &units);
+    assert_eq!(reading.raw, Some(1.0));
+    assert_eq!(reading.label.as_deref(), Some("Charging"));
+    assert_eq!(
+        signals.get("level").unwrap().label.as_deref(),
+        Some("Invalid")
+    );
+
+    // 沒有定義名稱的值顯示數字
+    signals.process(&CanFrame::new(0, 0x400, &[0x03, 0x10]));
+    let reading = Reading::of(comp, &signals, &dbc, &units);
+    assert_eq!((reading.raw, reading.label), (Some(3.0), None));
+    assert_eq!(signals.get("level").unwrap().label, None);
+
+    // 儲存後再載入保留 values
+    let saved = serde_yaml::to_string(&cfg.canbus_config[0]).unwrap();
+    assert!(saved.contains("Charging"), "{}", saved);
+    let empty = serde_yaml::to_string(&CanbusConfigEntry {
+        values: Default::default(),
+        ..cfg.canbus_config[0].clone()
+    })
+    .unwrap();
+    assert!(!empty.contains("values"), "{}", empty);
+}
+
+#[test]
+fn shared_can_offsets_overlapping_channels() {
+    // 兩個 ControlCAN 轉接器與一個 SLCAN 都從通道 0 開始編號
+    let adapter = |received: Vec<CanFrame>| RecordingCan {
+        channels: vec![0, 1],
+        received,
+        ..Default::default()
+    };
+    let first = adapter(vec![CanFrame::new(1, 0x100, &[1])]);
+    let second = adapter(vec![CanFrame::new(1, 0x200, &[2])]);
+    let slcan = RecordingCan {
+        channels: vec![0],
+        received: vec![CanFrame::new(0, 0x300, &[3])],
+        ..Default::default()
+    };
+    let can = SharedCan::default();
+    let (log_tx, _log_rx) = flume::unbounded();
+    let (data_tx, data_rx) = flume::unbounded();
+    for (name, app) in [
+        ("USBCAN-1", &first),
+        ("USBCAN-2", &second),
+        ("SLCAN", &slcan),
+    ] {
+        let id = can.attach(name, Box::new(app.clone()));
+        can.start_receiving(id, log_tx.clone(), data_tx.clone());
+    }
+    let channels: Vec<Vec<u32>> = can.interfaces().into_iter().map(|(_, _, c)| c).collect();
+    assert_eq!(
+        channels,
+        [
+            vec![0, 1],
+            vec![CHANNEL_BLOCK, CHANNEL_BLOCK + 1],
+            vec![2 * CHANNEL_BLOCK]
+        ]
+    );
+
+    // 接收的訊框加上介面的位移
+    let mut received: Vec<(u32, u32)> = receive(&data_rx, 3)
+        .into_iter()
+        .map(|f| (f.id, f.channel))
+        .collect();
+    received.sort();
+    assert_eq!(
+        received,
+        [
+            (0x100, 1),
+            (0x200, CHANNEL_BLOCK + 1),
+            (0x300, 2 * CHANNEL_BLOCK)
+        ]
+    );
+
+    // 傳送時扣除位移，交給後端自己的通道
+    can.send_frame(&CanFrame::new(CHANNEL_BLOCK + 1, 0x7E0, &[1]))
+        .unwrap();
+    can.send_frame(&CanFrame::new(2 * CHANNEL_BLOCK, 0x7E1, &[2]))
+        .unwrap();
+    can.send_frame(&CanFrame::new(0, 0x7E2, &[3])).unwrap();
+    let sent = |app: &RecordingCan| -> Vec<(u32, u32)> {
+        app.sent
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|f| (f.id, f.channel))
+            .collect()
+    };
+    assert_eq!(sent(&second), [(0x7E0, 1)]);
+    assert_eq!(sent(&slcan), [(0x7E1, 0)]);
+    assert_eq!(sent(&first), [(0x7E2, 0)]);
+    assert!(can.send_frame(&CanFrame::new(5, 0x7E3, &[4])).is_err());
+}
+
+#[test]
+fn watchdog_reopens_only_the_failed_interface() {
+    let healthy = UnpluggableCan::default();
+    let failing = UnpluggableCan::default();
+    let can = SharedCan::default();
+    can.attach("Healthy", Box::new(healthy.clone()));
+    let failing_id = can.attach("Failing", Box::new(failing.clone()));
+    let (log_tx, log_rx) = flume::unbounded();
+    let (data_tx, _data_rx) = flume::unbounded();
+    let running = Arc::new(AtomicBool::new(true));
+    let watchdog = {
+        let (can, running) = (can.clone(), Arc::clone(&running));
+        std::thread::spawn(move || {
+            let watchdog = ReconnectWatchdog {
+                check_interval: Duration::from_millis(10),
+                retry_interval: Duration::from_millis(10),
+            };
+            let enabled = AtomicBool::new(true);
+            watchdog.run(&can, &log_tx, &data_tx, &enabled, || {
+                running.load(Ordering::SeqCst)
+            });
+        })
+    };
+
+    failing.unplugged.store(true, Ordering::SeqCst);
+    let lost = log_rx.recv_timeout(Duration::from_secs(2)).unwrap();
+    assert_eq!(lost.code(), "E308");
+    assert!(lost.format(Language::English).contains("Failing"));
+    assert_eq!(can.failed_devices().len(), 1);
+    // 重試期間另一個介面仍在清單中且可傳送
+    assert!(can
+        .interfaces()
+        .iter()
+        .any(|(_, name, _)| name == "Healthy"));
+    failing.unplugged.store(false, Ordering::SeqCst);
+    let reconnected = log_rx.iter().find(|event| event.code() != "W209").unwrap();
+    assert_eq!(reconnected.code(), "I113");
+    running.store(false, Ordering::SeqCst);
+    watchdog.join().unwrap();
+
+    assert_eq!(failing.opens.load(Ordering::SeqCst), 1);
+    // 健康的介面沒有被關閉或重新開啟
+    assert_eq!(healthy.opens.load(Ordering::SeqCst), 0);
+    assert_eq!(healthy.closes.load(Ordering::SeqCst), 0);
+    assert!(log_rx.try_iter().all(|event| event.code() != "E308"));
+    assert_eq!(can.interfaces().len(), 2);
+    assert!(can.failed_devices().is_empty());
+    assert!(can.lock().iter().any(|a| a.id == failing_id));
+}
+
+/// 等待條件成立，最多 2 秒
+fn wait_until(condition: impl Fn() -> bool) -> bool {
+    let start = std::time::Instant::now();
+    while start.elapsed() < Duration::from_secs(2) {
+        if condition() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    false
+}
+
+#[test]
+fn remote_transmit_requires_opt_in_and_token() {
+    let recorder = RecordingCan::default();
+    let can = SharedCan::default();
+    can.attach("Local", Box::new(recorder.clone()));
+    // 用戶端以 token 連線並送出一筆訊框，回傳伺服器回報的錯誤
+    let transmit = |server: &RemoteServer, token: &str| -> Option<String> {
+        let client = RemoteCanApp::new(&server.local_addr().to_string()).with_token(token);
+        let (log_tx, log_rx) = flume::unbounded();
+        let (data_tx, _data_rx) = flume::unbounded();
+        client.open_device(log_tx.clone()).unwrap();
+        client.start_receiving(log_tx.clone(), data_tx);
+        client
+            .send_frame(&CanFrame::new(0, 0x123, &[1, 2]))
+            .unwrap();
+        let error = std::iter::from_fn(|| log_rx.recv_timeout(Duration::from_secs(2)).ok())
+            .find_map(|event| match event {
+                LogEvent::RemoteError { detail } => Some(Some(detail)),
+                LogEvent::RemoteConnectionLost { .. } => Some(None),
+                _ => None,
+            })
+            .flatten();
+        client.stop_receiving();
+        client.close_device(log_tx);
+        error
+    };
+
+    let receive_only = RemoteServer::start("127.0.0.1:0", can.clone(), None, |_| {}).unwrap();
+    assert!(!receive_only.allows_transmit());
+    assert_eq!(
+        transmit(&receive_only, "").as_deref(),
+        Some("Remote transmit is disabled on this server")
+    );
+    drop(receive_only);
+    assert!(RemoteServer::start("127.0.0.1:0", can.clone(), Some(String::new()), |_| {}).is_err());
+
+    let server = RemoteServer::start(
+        "127.0.0.1:0",
+        can.clone(),
+        Some("s3cret".to_string()),
+        |_| {},
+    )
+    .unwrap();
+    assert_eq!(
+        transmit(&server, "").as_deref(),
+        Some("Remote transmit requires a token")
+    );
+    assert_eq!(
+        transmit(&server, "guess").as_deref(),
+        Some("Remote transmit token rejected")
+    );
+    assert!(recorder.sent.lock().unwrap().is_empty());
+
+    let client = RemoteCanApp::new(&server.local_addr().to_string()).with_token("s3cret");
+    let (log_tx, _log_rx) = flume::unbounded();
+    client.open_device(log_tx.clone()).unwrap();
+    client
+        .send_frame(&CanFrame::new(0, 0x123, &[1, 2]))
+        .unwrap();
+    assert!(wait_until(|| recorder.sent.lock().unwrap().len() == 1));
+    assert_eq!(recorder.sent.lock().unwrap()[0].id, 0x123);
+    client.close_device(log_tx);
+}
+
+#[test]
+fn remote_server_drops_a_stalled_client_without_blocking() {
+    let server = RemoteServer::start("127.0.0.1:0", SharedCan::default(), None, |_| {}).unwrap();
+    // 連線後從不讀取
+    let _stalled = std::net::TcpStream::connect(server.local_addr()).unwrap();
+    assert!(wait_until(|| server.client_count() == 1));
+
+    let frame = CanFrame::new_fd(0, 0x100, &[0xAA; 64], true);
+    let start = std::time::Instant::now();
+    for _ in 0..CLIENT_QUEUE_LEN * 100 {
+        server.publish(&frame);
+    }
+    // 推送只放入佇列，不等待網路寫入
+    assert!(
+        start.elapsed() < Duration::from_secs(2),
+        "{:?}",
+        start.elapsed()
+    );
+    assert_eq!(server.client_count(), 0);
+}
+
+#[test]
+fn rate_limiter_bursts_refills_and_caps_globally() {
+    let t0 = Instant::now();
+    let ms = |n: u64| t0 + Duration::from_millis(n);
+    let send = |limiter: &mut RateLimiter, now: Instant| {
+        let ready = limiter.ready(now);
+        if ready {
+            limiter.consume(now);
+        }
+        ready
+    };
+
+    // 10 fps、突發 3 筆：一開始可連送 3 筆，之後每 100 ms 補一個 token
+    let limit = RateLimit {
+        max_fps: 10.0,
+        burst: 3,
+        min_gap_ms: 0.0,
+    };
+    let mut limiter = RateLimiter::new(limit, t0);
+    assert!((0..3).all(|_| send(&mut limiter, t0)));
+    assert!(!send(&mut limiter, t0));
+    assert!(!send(&mut limiter, ms(50)));
+    assert!(send(&mut limiter, ms(100)));
+    assert!(!send(&mut limiter, ms(100)));
+    // 閒置很久也只累積到 burst
+    assert_eq!(
+        (0..10).filter(|_| send(&mut limiter, ms(10_000))).count(),
+        3
+    );
+    // 調低 burst 時捨去多出的 token
+    assert!(!limiter.ready(ms(10_000)));
+    limiter.set_limit(RateLimit { burst: 1, ..limit });
+    assert_eq!((0..3).filter(|_| send(&mut limiter, ms(20_000))).count(), 1);
+
+    // 最小間隔
+    let mut limiter = RateLimiter::new(
+        RateLimit {
+            min_gap_ms: 20.0,
+            ..RateLimit::default()
+        },
+        t0,
+    );
+    assert!(send(&mut limiter, t0));
+    assert!(!send(&mut limiter, ms(19)));
+    assert!(send(&mut limiter, ms(20)));
+
+    // 與排程器相同的順序：各訊息不限速，全域 125 fps、突發 2 筆，
+    // 5 則訊息每 1 ms 都想送，一秒內總共只送出 2 + 125 筆
+    let mut global = RateLimiter::new(
+        RateLimit {
+            max_fps: 125.0,
+            burst: 2,
+            min_gap_ms: 0.0,
+        },
+        t0,
+    );
+    let mut messages: Vec<RateLimiter> = (0..5)
+        .map(|_| RateLimiter::new(RateLimit::default(), t0))
+        .collect();
+    let mut sent = 0;
+    for tick in 0..=1000 {
+        let now = ms(tick);
+        for message in &mut messages {
+            if message.ready(now) && global.ready(now) {
+                message.consume(now);
+                global.consume(now);
+                sent += 1;
+            }
+        }
+    }
+    assert_eq!(sent, 127);
+}
+
+#[test]
+fn timestamp_corrector_unwraps_counter_and_tracks_host_drift() {
+    // ControlCAN 計數器（32 位元、0.1 ms）從回繞前 5 秒開始，每 10 ms 一筆；
+    // 主機時鐘比裝置快 100 ppm，接收延遲為 0..200 µs 的固定亂數
+    let mut corrector = TimestampCorrector::controlcan();
+    let start_raw = u32::MAX - 50_000;
+    let host_start = 1_700_000_000_000_000u64;
+    let mut seed = 1u64;
+    let mut previous = 0;
+    for i in 0..60_000u32 {
+        let ticks = i * 100;
+        let raw = start_raw.wrapping_add(ticks);
+        let true_host = host_start + (ticks as f64 * 100.0 * (1.0 + 100e-6)) as u64;
+        seed = seed
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        let host = true_host + (seed >> 33) % 200;
+
+        let corrected = corrector.correct(raw, host);
+        assert!(
+            corrected >= previous,
+            "timestamp went backwards at sample {}",
+            i
+        );
+        previous = corrected;
+        // 一分鐘後相位與速率都已收斂：誤差只剩平均延遲附近，不會累積 100 ppm 的漂移
+        if i >= 6_000 {
+            let error = corrected as i64 - true_host as i64;
+            assert!(
+                (50..=150).contains(&error),
+                "error {} µs at sample {}",
+                error,
+                i
+            );
+            let rate = corrector.rate_ppm();
+            assert!(
+                (80.0..=120.0).contains(&rate),
+                "rate {} ppm at sample {}",
+                rate,
+                i
+            );
+        }
+    }
+    // 回繞後裝置時間持續遞增，沒有被當成重置
+    assert_eq!(
+        corrector.device_micros(),
+        (start_raw as u64 + 59_999 * 100) * 100
+    );
+    assert_eq!(corrector.resyncs(), 0);
+
+    // 裝置重置造成的大幅跳動會重新對齊主機時間，輸出仍不倒退
+    let host = host_start + 700_000_000;
+    let corrected = corrector.correct(5, host);
+    assert_eq!(corrector.resyncs(), 1);
+    assert!(corrected >= previous && corrected.abs_diff(host) < 1_000);
+}
+
+#[test]
+fn csv_schedule_detects_the_header_after_comments() {
+    let path = temp_path("schedule.csv");
+    let load = |text: &str| {
+        fs::write(&path, text).unwrap();
+        load_csv_schedule(path.to_str().unwrap(), 2)
+    };
+
+    // 註解與空行之後的標題列仍會被辨識，且 "_ms" 結尾改以毫秒解讀
+    let rows = load("# exported schedule\n\n time_ms , id, data, channel\n250,0x123,01 02\n100.5,0x7E0,AABBCC,1\n")
+        .unwrap();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(rows[0].offset_us, 100_500);
+    assert_eq!((rows[0].frame.channel, rows[0].frame.id), (1, 0x7E0));
+    assert_eq!(rows[0].frame.payload(), [0xAA, 0xBB, 0xCC]);
+    assert_eq!(rows[1].offset_us, 250_000);
+    assert_eq!(rows[1].frame.channel, 2);
+
+    // 沒有標題列時以秒解讀
+    let rows = load("# no header\n0.25,0x100,00\n").unwrap();
+    assert_eq!(rows[0].offset_us, 250_000);
+
+    // 資料列之後的文字不是標題列
+    let err = load("time,id,data\n0,0x100,00\ntime,id,data\n").unwrap_err();
+    assert_eq!(err, "Line 3: invalid time 'time'");
+    fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn udp_broadcaster_accepts_only_ipv4_multicast() {
+    let broadcaster = UdpBroadcaster::new("239.255.0.1:5555", 1).unwrap();
+    assert_eq!(broadcaster.target().to_string(), "239.255.0.1:5555");
+    let error = |target: &str| UdpBroadcaster::new(target, 1).err().unwrap();
+    assert_eq!(
+        error("192.168.1.10:5555"),
+        "192.168.1.10 is not a multicast address"
+    );
+    assert_eq!(
+        error("[ff02::1]:5555"),
+        "ff02::1 is an IPv6 address; only IPv4 multicast (224.0.0.0/4) is supported"
+    );
+    assert!(error("239.255.0.1").starts_with("Invalid multicast address"));
+}
+
+#[test]
+fn latency_tracker_pairs_requests_with_responses() {
+    let mut tracker = LatencyTracker::default();
+    tracker.add_pair(LatencyPair {
+        request_id: 0x7E0,
+        response_id: 0x7E8,
+    });
+    let mut observe = |id: u32, timestamp_ms: u64| {
+        let mut frame = CanFrame::new(0, id, &[]);
+        frame.timestamp = timestamp_ms * 1000;
+        tracker.observe(&frame);
+    };
+    // 沒有請求的回應不計；其他 ID 不影響
+    observe(0x7E8, 0);
+    observe(0x7E0, 10);
+    observe(0x123, 11);
+    observe(0x7E8, 15);
+    // 重送請求時以最後一筆重新計時
+    observe(0x7E0, 100);
+    observe(0x7E0, 120);
+    observe(0x7E8, 140);
+    // 逾時（1 s）在下一筆訊框時判定，之後的回應不計
+    observe(0x7E0, 200);
+    observe(0x123, 1_300);
+    observe(0x7E8, 1_310);
+    observe(0x7E0, 2_000);
+    observe(0x7E8, 2_002);
+
+    let stats = &tracker.stats()[0];
+    assert_eq!((stats.count, stats.timeouts), (3, 1));
+    assert_eq!(
+        (stats.min_ms, stats.max_ms, stats.last_ms),
+        (2.0, 20.0, 2.0)
+    );
+    assert_eq!(stats.mean_ms(), 9.0);
+    assert_eq!(Vec::from(stats.history.clone()), [5.0, 20.0, 2.0]);
+
+    tracker.reset();
+    let stats = &tracker.stats()[0];
+    assert_eq!((stats.count, stats.timeouts, stats.mean_ms()), (0, 0, 0.0));
+    assert!(stats.history.is_empty());
+}
+
+#[test]
+fn latency_config_reload_replaces_only_config_pairs() {
+    let pair = |request_id, response_id| LatencyPair {
+        request_id,
+        response_id,
+    };
+    let pairs = |tracker: &LatencyTracker| {
+        tracker
+            .stats()
+            .iter()
+            .map(|s| (s.pair.request_id, s.pair.response_id))
+            .collect::<Vec<_>>()
+    };
+    let mut tracker = LatencyTracker::default();
+    tracker.add_pair(pair(0x7DF, 0x7E8));
+    tracker.set_config_pairs(&[pair(0x7E0, 0x7E8), pair(0x7E1, 0x7E9)]);
+    let mut request = CanFrame::new(0, 0x7E1, &[]);
+    request.timestamp = 1_000;
+    let mut response = CanFrame::new(0, 0x7E9, &[]);
+    response.timestamp = 3_000;
+    tracker.observe(&request);
+    tracker.observe(&response);
+
+    // 重新載入：移除的配對消失，留下的配對保留量測，手動加入的配對不受影響
+    tracker.set_config_pairs(&[pair(0x7E1, 0x7E9), pair(0x18DA00F1, 0x18DAF100)]);
+    assert_eq!(
+        pairs(&tracker),
+        [(0x7DF, 0x7E8), (0x7E1, 0x7E9), (0x18DA00F1, 0x18DAF100)]
+    );
+    assert_eq!(tracker.stats()[1].count, 1);
+    tracker.reset();
+    tracker.set_config_pairs(&[]);
+    assert_eq!(pairs(&tracker), [(0x7DF, 0x7E8)]);
+}
+
+#[test]
+fn tx_macros_record_round_trip_and_play_in_order() {
+    let mut recorder = MacroRecorder::default();
+    assert!(recorder.is_empty());
+    recorder.record(CanFrame::new(0, 0x100, &[1]));
+    std::thread::sleep(Duration::from_millis(30));
+    recorder.record(CanFrame::new(0, 0x101, &[2]));
+    let recorded = recorder.finish("recorded");
+    assert_eq!(recorded.steps[0].delay_ms, 0);
+    assert!(recorded.steps[1].delay_ms >= 30, "{:?}", recorded.steps);
+
+    // 存成 YAML 再讀回內容相同；格式錯誤時回報錯誤
+    let path = temp_path("macros.yaml");
+    let file = path.to_str().unwrap();
+    save_macros(file, std::slice::from_ref(&recorded)).unwrap();
+    let loaded = load_macros(file).unwrap();
+    assert_eq!(loaded.len(), 1);
+    assert_eq!(loaded[0].name, "recorded");
+    let steps = |m: &TxMacro| {
+        m.steps
+            .iter()
+            .map(|s| (s.delay_ms, s.frame))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(steps(&loaded[0]), steps(&recorded));
+    fs::write(&path, "- name: broken\n  steps: 5\n").unwrap();
+    assert!(load_macros(file).is_err());
+    fs::write(&path, "- name: [unclosed\n").unwrap();
+    assert!(load_macros(file).is_err());
+    fs::remove_file(&path).unwrap();
+
+    // 依序送出，每一步等待自己的延遲
+    let bus = RecordingCan {
+        channels: vec![0],
+        ..Default::default()
+    };
+    let can = SharedCan::default();
+    can.attach("ControlCAN", Box::new(bus.clone()));
+    let step = |delay_ms, id| MacroStep {
+        delay_ms,
+        frame: CanFrame::new(0, id, &[]),
+    };
+    let tx_macro = TxMacro {
+        name: "wake".to_string(),
+        steps: vec![step(0, 0x200), step(150, 0x201), step(0, 0x202)],
+    };
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let log_sink = Arc::clone(&logs);
+    let started = Instant::now();
+    let running = play_macro(tx_macro.clone(), can, move |line| {
+        log_sink.lock().unwrap().push(line)
+    });
+    assert!(wait_until(|| bus.sent.lock().unwrap().len() == 1));
+    assert!(started.elapsed() < Duration::from_millis(150));
+    assert!(wait_until(|| !running.load(Ordering::SeqCst)));
+    assert!(started.elapsed() >= Duration::from_millis(150));
+    let ids: Vec<u32> = bus.sent.lock().unwrap().iter().map(|f| f.id).collect();
+    assert_eq!(ids, [0x200, 0x201, 0x202]);
+    assert_eq!(
+        *logs.lock().unwrap(),
+        ["Macro 'wake' started", "Macro 'wake' finished"]
+    );
+
+    // 送出失敗時中止並指出步驟
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let log_sink = Arc::clone(&logs);
+    let running = play_macro(tx_macro, SharedCan::default(), move |line| {
+        log_sink.lock().unwrap().push(line)
+    });
+    assert!(wait_until(|| !running.load(Ordering::SeqCst)));
+    assert_eq!(
+        logs.lock().unwrap()[1],
+        "Macro 'wake' aborted at step 1: CAN not started"
+    );
+}
+
+#[test]
+fn fixed_rate_sampler_writes_evenly_spaced_last_values() {
+    let (cfg, _) = load_config(CONFIG, "sampler.yaml");
+    let signals = Arc::new(Mutex::new(SignalTable::default()));
+    let path = temp_path("sampler.csv");
+    let file = path.to_str().unwrap();
+    let start = |rate_hz: f64, signals: &Arc<Mutex<SignalTable>>| {
+        FixedRateSampler::start(
+            file,
+            rate_hz,
+            Arc::clone(signals),
+            DisplayUnits::default(),
+            |_| {},
+        )
+    };
+    assert_eq!(
+        start(100.0, &signals).err().unwrap(),
+        "No signals defined; load a YAML config first"
+    );
+    signals.lock().unwrap().load(&cfg.canbus_config);
+    for rate_hz in [0.0, -1.0, 1000.5, f64::NAN] {
+        assert!(start(rate_hz, &signals).is_err(), "{}", rate_hz);
+    }
+
+    // 訊框約每 1 ms 一筆，以 50 Hz 取樣時每列只保留當下最後一筆值
+    let sampler = start(50.0, &signals).unwrap();
+    let mut fed = 0;
+    for speed in 1..=250u16 {
+        signals
+            .lock()
+            .unwrap()
+            .process(&CanFrame::new(0, 0x100, &[speed as u8, 0, 0]));
+        fed += 1;
+        std::thread::sleep(Duration::from_millis(1));
+    }
+    assert!(sampler.is_running());
+    drop(sampler);
+    let text = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let mut lines = text.lines();
+    assert_eq!(lines.next(), Some("time_s,timestamp_us,speed,temp"));
+    let rows: Vec<Vec<&str>> = lines.map(|l| l.split(',').collect()).collect();
+    assert!(
+        rows.len() >= 5 && rows.len() < fed / 10,
+        "{} rows",
+        rows.len()
+    );
+    let mut last_speed = 0.0;
+    for (i, row) in rows.iter().enumerate() {
+        // 時間欄位依排程等間隔，不受寫入時間影響
+        assert_eq!(row[0], format!("{:.6}", i as f64 * 0.02));
+        if let Ok(speed) = row[2].parse::<f64>() {
+            assert!(speed >= last_speed, "row {}: {:?}", i, row);
+            last_speed = speed;
+            assert_eq!(row[3], "0");
+        }
+    }
+    assert!(last_speed > 0.0);
+}
+
+#[test]
+fn snapshot_round_trips_through_json_and_writes_csv_sections() {
+    let (cfg, _) = load_config(CONFIG, "snapshot.yaml");
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let mut units = DisplayUnits::default();
+    units.load(&cfg.components);
+    let mut stats = BusStatistics::default();
+    for (i, speed) in [80u8, 100, 90].into_iter().enumerate() {
+        let mut frame = CanFrame::new(0, 0x100, &[speed, 0x00, 0xF6]);
+        frame.timestamp = 1_000_000 + i as u64 * 10_000;
+        signals.process(&frame);
+        stats.process(&frame);
+    }
+    let session = SessionInfo {
+        operator: "QA".to_string(),
+        notes: "bench \"A\"".to_string(),
+        ..Default::default()
+    };
+    let snapshot = Snapshot::capture(
+        "2026-10-16 12:00:00.000".to_string(),
+        1_020_000,
+        "Simulated",
+        true,
+        &signals,
+        &units,
+        &stats,
+    )
+    .with_libraries(vec![LibraryVersion::new("PCANBasic", "4.8.0")])
+    .with_session(session.clone());
+    let speed = &snapshot.signals[0];
+    assert_eq!(
+        (speed.value, speed.min, speed.max, speed.avg, speed.samples),
+        (Some(90.0), Some(80.0), Some(100.0), Some(90.0), 3)
+    );
+    assert_eq!(snapshot.ids[0].mean_period_ms, Some(10.0));
+
+    // JSON 儲存後讀回完全相同
+    let path = temp_path("snapshot.json");
+    snapshot.save(path.to_str().unwrap()).unwrap();
+    let restored = Snapshot::load(path.to_str().unwrap()).unwrap();
+    fs::remove_file(&path).unwrap();
+    assert_eq!(restored, snapshot);
+    assert_eq!(restored.session, session);
+
+    // .csv 寫成分段 CSV
+    let path = temp_path("snapshot.csv");
+    snapshot.save(path.to_str().unwrap()).unwrap();
+    let text = fs::read_to_string(&path).unwrap();
+    assert!(Snapshot::load(path.to_str().unwrap()).is_err());
+    fs::remove_file(&path).unwrap();
+    let sections: Vec<&str> = text.lines().filter(|l| l.starts_with("# ")).collect();
+    assert_eq!(
+        sections,
+        [
+            "# snapshot",
+            "# libraries",
+            "# session",
+            "# channels",
+            "# signals",
+            "# ids"
+        ]
+    );
+    assert!(text.contains("\nnotes,\"bench \"\"A\"\"\"\n"), "{}", text);
+    assert!(
+        text.contains("\nspeed,km/h,90,1020000,80,100,90,3\n"),
+        "{}",
+        text
+    );
+    assert!(
+        text.contains("\n0,0x100,3,10,10,1020000,5A 00 F6\n"),
+        "{}",
+        text
+    );
+}
+
+#[test]
+fn search_matches_ids_data_and_wildcards() {
+    let frame = CanFrame::new(0, 0x1A0, &[0x11, 0x12, 0x3C, 0xAB, 0x00]);
+    let text = "0.001000 CH0 RX 0x1A0 [5] 11 12 3C AB 00";
+
+    // 位元組樣式可出現在任一位置，`?` 為任意半位元組
+    for pattern in ["12 3C", "?23?AB", "?1,12", "ab 00", "11 12 3C AB 00"] {
+        let query = SearchQuery::bytes(pattern).unwrap();
+        assert!(query.matches(&frame, ""), "{}", pattern);
+    }
+    for pattern in ["12 AB", "3? AC", "11 12 3C AB 00 00", "FF"] {
+        let query = SearchQuery::bytes(pattern).unwrap();
+        assert!(!query.matches(&frame, text), "{}", pattern);
+    }
+    let pattern = BytePattern::parse("?? 00").unwrap();
+    assert!(pattern.find_in(&[0xAB, 0x00]));
+    assert!(!pattern.find_in(&[0x00]));
+    assert!(!pattern.find_in(&[]));
+
+    // 正規表示式比對顯示文字，例如依 ID 搜尋
+    let by_id = SearchQuery::regex(r"0x1A0\b").unwrap();
+    assert!(by_id.matches(&frame, text));
+    assert!(!by_id.matches(&frame, "0.001000 CH0 RX 0x1A01 [5] 11 12 3C AB 00"));
+    let other = SearchQuery::regex("0x7DF").unwrap();
+    assert!(!other.matches(&frame, text));
+
+    assert_eq!(SearchQuery::bytes(" , ").unwrap_err(), "Empty byte pattern");
+    assert_eq!(
+        SearchQuery::bytes("12 3").unwrap_err(),
+        "Byte pattern must contain whole bytes (two hex digits each)"
+    );
+    assert_eq!(
+        SearchQuery::bytes("1G").unwrap_err(),
+        "Invalid hex digit 'G'"
+    );
+    assert!(SearchQuery::regex("0x(1A0")
+        .unwrap_err()
+        .starts_with("Invalid regex: "));
+}
+
+/// 把收到的訊框交給自測，直到自測結束
+fn finish_self_test(test: &Arc<Mutex<SelfTest>>, data_rx: &flume::Receiver<CanFrame>) {
+    let deadline = Instant::now() + Duration::from_secs(3);
+    while *test.lock().unwrap().status() == SelfTestStatus::Running {
+        assert!(Instant::now() < deadline, "self test did not finish");
+        if let Ok(frame) = data_rx.recv_timeout(Duration::from_millis(20)) {
+            test.lock().unwrap().observe(&frame);
+        }
+    }
+}
+
+#[test]
+fn self_test_reports_pass_and_fail_against_sim() {
+    let logs = Arc::new(Mutex::new(Vec::new()));
+    let log = {
+        let logs = logs.clone();
+        move |line: String| logs.lock().unwrap().push(line)
+    };
+
+    // 開啟自我接收時每一筆都會收回
+    let (can_app, _injector, data_rx) = start_sim();
+    let test = Arc::new(Mutex::new(SelfTest::default()));
+    run_self_test(test.clone(), can_app.clone(), 0, 4, log.clone());
+    assert_eq!(test.lock().unwrap().progress(), (0, 4));
+    finish_self_test(&test, &data_rx);
+    assert_eq!(*test.lock().unwrap().status(), SelfTestStatus::Passed);
+    assert_eq!(test.lock().unwrap().progress(), (4, 4));
+
+    // 沒有自我接收：一筆都收不回來
+    let (log_tx, _log_rx) = flume::unbounded();
+    let (data_tx, data_rx) = flume::unbounded();
+    let sim = SimCanApp::new(false);
+    sim.open_device(log_tx.clone()).unwrap();
+    sim.start_receiving(log_tx, data_tx);
+    let injector = sim.injector();
+    let can_app = SharedCan::default();
+    can_app.attach("Simulated", Box::new(sim));
+    let test = Arc::new(Mutex::new(SelfTest::default()));
+    run_self_test(test.clone(), can_app.clone(), 0, 3, log.clone());
+    finish_self_test(&test, &data_rx);
+    assert_eq!(
+        *test.lock().unwrap().status(),
+        SelfTestStatus::Failed(
+            "No test frames received back (0/3); enable loopback or self-reception".to_string()
+        )
+    );
+
+    // 收回內容被改動的訊框算作損毀
+    let test = Arc::new(Mutex::new(SelfTest::default()));
+    run_self_test(test.clone(), can_app.clone(), 0, 3, log.clone());
+    injector
+        .send(CanFrame::new(
+            0,
+            SELF_TEST_ID,
+            &[1, 0xFE, 0x55, 0xAA, 0x5B, 0x00, 0xFF, 0xC2],
+        ))
+        .unwrap();
+    finish_self_test(&test, &data_rx);
+    assert_eq!(
+        *test.lock().unwrap().status(),
+        SelfTestStatus::Failed("0/3 frames received, 1 corrupted".to_string())
+    );
+
+    // 送出失敗立即結束
+    let test = Arc::new(Mutex::new(SelfTest::default()));
+    run_self_test(test.clone(), SharedCan::default(), 1, 2, log);
+    assert!(wait_until(
+        || *test.lock().unwrap().status() != SelfTestStatus::Running
+    ));
+    assert_eq!(
+        *test.lock().unwrap().status(),
+        SelfTestStatus::Failed("Transmit failed: CAN not started".to_string())
+    );
+
+    let logs = logs.lock().unwrap();
+    assert_eq!(
+        logs.iter()
+            .filter(|l| l.starts_with("Self test started"))
+            .count(),
+        4
+    );
+    assert!(logs.contains(&"Self test started on CH0 (4 frames)".to_string()));
+    assert!(logs.contains(&"Self test passed".to_string()));
+    assert!(logs.contains(&"Self test failed: transmit failed: CAN not started".to_string()));
+}
+
+#[test]
+fn silence_detector_warns_once_per_gap_and_rearms_on_traffic() {
+    let mut detector = SilenceDetector::default();
+    detector.threshold_ms = 100;
+    let mut events = EventLog::default();
+    let frame = |channel: u32, timestamp: u64| {
+        let mut frame = CanFrame::new(channel, 0x100, &[0]);
+        frame.timestamp = timestamp;
+        frame
+    };
+    let messages = |events: &EventLog| -> Vec<(u64, Severity, String)> {
+        events
+            .events()
+            .filter(|e| e.category == EventCategory::Silence)
+            .map(|e| (e.timestamp, e.severity, e.message.clone()))
+            .collect()
+    };
+
+    // 尚未收過訊框的通道不算靜默
+    detector.poll(5_000_000, &mut events);
+    assert!(messages(&events).is_empty());
+
+    detector.process(&frame(0, 1_000_000), &mut events);
+    detector.poll(1_100_000, &mut events);
+    assert!(messages(&events).is_empty(), "exactly at the threshold");
+    detector.process(&frame(1, 1_150_000), &mut events);
+    detector.poll(1_100_001, &mut events);
+    detector.poll(1_200_000, &mut events);
+    assert_eq!(
+        messages(&events),
+        [(
+            1_100_001,
+            Severity::Warning,
+            "CAN 0 silent: no frames for more than 100 ms".to_string()
+        )]
+    );
+
+    detector.process(&frame(0, 1_500_000), &mut events);
+    detector.process(&frame(0, 1_510_000), &mut events);
+    detector.poll(1_550_000, &mut events);
+    assert_eq!(messages(&events).len(), 3, "{:?}", messages(&events));
+    assert_eq!(
+        messages(&events)[1],
+        (
+            1_500_000,
+            Severity::Info,
+            "CAN 0 traffic resumed after 0.500 s of silence".to_string()
+        )
+    );
+    assert_eq!(
+        messages(&events)[2].2,
+        "CAN 1 silent: no frames for more than 100 ms"
+    );
+
+    // 恢復後重新計時，下一次靜默另起一個區間
+    detector.poll(1_700_000, &mut events);
+    let gaps: Vec<SilentGap> = detector.gaps().copied().collect();
+    assert_eq!(
+        gaps,
+        [
+            SilentGap {
+                channel: 0,
+                start_us: 1_000_000,
+                end_us: Some(1_500_000),
+            },
+            SilentGap {
+                channel: 1,
+                start_us: 1_150_000,
+                end_us: None,
+            },
+            SilentGap {
+                channel: 0,
+                start_us: 1_510_000,
+                end_us: None,
+            },
+        ]
+    );
+    assert_eq!(gaps[0].duration_us(9_999_999), 500_000);
+    assert_eq!(gaps[2].duration_us(1_700_000), 190_000);
+    assert_eq!(messages(&events).len(), 4);
+
+    detector.clear();
+    detector.poll(9_000_000, &mut events);
+    assert_eq!(detector.gaps().count(), 0);
+    assert_eq!(messages(&events).len(), 4);
+}
+
+#[test]
+fn traffic_timeline_buckets_frames_and_coarsens_when_full() {
+    let start = 10_000_000;
+    let mut timeline = TrafficTimeline::default();
+    assert_eq!((timeline.start_us(), timeline.end_us()), (None, None));
+
+    // 起點之後的訊框不論到達順序都計入所屬區段，早於起點的計入第一個
+    let mut in_order = TrafficTimeline::default();
+    for offset in [0, 50_000, 99_999, 100_000, 1_000_000] {
+        in_order.record(start + offset);
+    }
+    for t in [
+        start,
+        start + 1_000_000,
+        start + 100_000,
+        start - 5,
+        start + 50_000,
+    ] {
+        timeline.record(t);
+    }
+    assert_eq!(timeline.start_us(), Some(start));
+    assert_eq!(timeline.bucket_us(), 100_000);
+    assert_eq!(timeline.counts(), [3, 1, 0, 0, 0, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(in_order.counts(), timeline.counts());
+    assert_eq!(timeline.end_us(), Some(start + 1_100_000));
+
+    // 超過 512 個區段時合併相鄰區段，總數不變
+    timeline.record(start + 51_100_000);
+    assert_eq!(timeline.bucket_us(), 100_000);
+    assert_eq!(timeline.counts().len(), 512);
+    timeline.record(start + 51_200_000);
+    assert_eq!(timeline.bucket_us(), 200_000);
+    assert_eq!(timeline.counts().len(), 257);
+    assert_eq!(timeline.counts()[..6], [4, 0, 0, 0, 0, 1]);
+    assert_eq!(timeline.counts()[255..], [1, 1]);
+    assert_eq!(timeline.counts().iter().sum::<u32>(), 7);
+    assert_eq!(timeline.end_us(), Some(start + 257 * 200_000));
+
+    // 一次跳很遠時連續合併直到放得下
+    timeline.record(start + 400_000_000);
+    assert_eq!(timeline.bucket_us(), 800_000);
+    assert_eq!(timeline.counts().len(), 501);
+    assert_eq!(timeline.counts()[..2], [4, 1]);
+    assert_eq!(timeline.counts().iter().sum::<u32>(), 8);
+
+    timeline.clear();
+    assert_eq!(timeline.start_us(), None);
+    assert_eq!(timeline.bucket_us(), 100_000);
+    assert!(timeline.counts().is_empty());
+}
+
+#[test]
+fn library_versions_format_parse_and_warn() {
+    // ControlCAN 板卡版本欄位為 BCD 形式
+    let firmware = LibraryVersion::from_vci("ControlCAN", 0x0230);
+    assert_eq!(firmware.version, "V2.30");
+    assert_eq!(firmware.major_minor(), Some((2, 30)));
+    assert_eq!(
+        LibraryVersion::from_vci("ControlCAN", 0x0105).version,
+        "V1.05"
+    );
+    assert_eq!(
+        LibraryVersion::from_vci("ControlCAN", 0x0A0B).version,
+        "VA.0B"
+    );
+
+    let parse = |version: &str| LibraryVersion::new("PCANBasic", version).major_minor();
+    assert_eq!(parse("4.8.0.32"), Some((4, 8)));
+    assert_eq!(parse("v5"), Some((5, 0)));
+    assert_eq!(parse("4,3"), Some((4, 3)));
+    assert_eq!(parse("unknown"), None);
+    assert_eq!(parse("4.x"), None);
+
+    let issue = |library: &str, version: &str| LibraryVersion::new(library, version).known_issue();
+    assert!(issue("PCANBasic", "3.9.1")
+        .unwrap()
+        .starts_with("no CAN FD support"));
+    assert!(issue("PCANBasic", "4.3.2")
+        .unwrap()
+        .starts_with("PCAN_ALLOW_ECHO_FRAMES"));
+    assert_eq!(issue("PCANBasic", "4.4.0"), None);
+    assert_eq!(issue("PCANBasic", "unknown"), None);
+    assert_eq!(issue("ControlCAN", "V2.30"), None);
+
+    // 回報版本，有已知問題時多一筆警告
+    let (log_tx, log_rx) = flume::unbounded();
+    firmware.report(&log_tx);
+    LibraryVersion::new("PCANBasic", "4.3.2").report(&log_tx);
+    let lines: Vec<(&str, String, String)> = log_rx
+        .try_iter()
+        .map(|e| {
+            (
+                e.code(),
+                e.format(Language::English),
+                e.format(Language::Chinese),
+            )
+        })
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            (
+                "I107",
+                "ControlCAN library version: V2.30".to_string(),
+                "ControlCAN 函式庫版本：V2.30".to_string()
+            ),
+            (
+                "I107",
+                "PCANBasic library version: 4.3.2".to_string(),
+                "PCANBasic 函式庫版本：4.3.2".to_string()
+            ),
+            (
+                "W206",
+                "PCANBasic version 4.3.2 has known issues: PCAN_ALLOW_ECHO_FRAMES is not supported; \
+                 transmitted frames are echoed locally"
+                    .to_string(),
+                "PCANBasic 版本 4.3.2 有已知問題：PCAN_ALLOW_ECHO_FRAMES is not supported; \
+                 transmitted frames are echoed locally"
+                    .to_string()
+            ),
+        ]
+    );
+}
+
+#[test]
+fn plot_export_renders_history_as_svg_and_png() {
+    let plot = PlotImage::from_history("Speed <front> & rear", "km/h", [0.0, 5.0, 10.0]);
+    assert_eq!(plot.x_label, "Sample");
+    assert_eq!(plot.y_label, "Speed <front> & rear [km/h]");
+    assert_eq!(plot.series[0].points, [(0.0, 0.0), (1.0, 5.0), (2.0, 10.0)]);
+    assert_eq!(PlotImage::from_history("rpm", "", []).y_label, "rpm");
+
+    // 數列依資料範圍填滿繪圖區（960x540 扣除邊界）
+    let svg = render_svg(&plot, 960, 540);
+    assert!(svg.starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="960" height="540" "#));
+    assert!(svg.ends_with("</svg>\n"));
+    assert!(
+        svg.contains(
+            r#"<polyline points="70.0,490.0 505.0,265.0 940.0,40.0" fill="none" stroke="rgb(31,119,180)" stroke-width="1.5"/>"#
+        ),
+        "{}",
+        svg
+    );
+    assert!(svg.contains(">Speed &lt;front&gt; &amp; rear</text>"));
+    assert!(svg.contains(">Speed &lt;front&gt; &amp; rear [km/h]</text>"));
+    for tick in [">0</text>", ">0.5</text>", ">2</text>", ">10</text>"] {
+        assert!(svg.contains(tick), "{}", tick);
+    }
+
+    // 沒有資料時仍輸出座標軸但沒有數列線
+    let empty = render_svg(&PlotImage::from_history("rpm", "", []), 320, 200);
+    assert!(!empty.contains("stroke=\"rgb(31,119,180)\" stroke-width=\"1.5\""));
+    assert!(empty.contains(">rpm</text>"));
+
+    let png = render_png(&plot, 320, 200).unwrap();
+    assert_eq!(png[..8], [0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A]);
+    assert_eq!(png[12..24], *b"IHDR\0\0\x01\x40\0\0\0\xC8");
+    assert_eq!(
+        render_png(&plot, 0, 200).unwrap_err(),
+        "Invalid image size 0x200"
+    );
+
+    // 依副檔名選擇格式
+    let svg_path = temp_path("plot.SVG");
+    save_plot(svg_path.to_str().unwrap(), &plot).unwrap();
+    assert_eq!(
+        fs::read_to_string(&svg_path).unwrap(),
+        render_svg(&plot, 960, 540)
+    );
+    fs::remove_file(&svg_path).unwrap();
+    let png_path = temp_path("plot.png");
+    save_plot(png_path.to_str().unwrap(), &plot).unwrap();
+    let png = fs::read(&png_path).unwrap();
+    fs::remove_file(&png_path).unwrap();
+    assert_eq!(png[16..24], [0, 0, 0x03, 0xC0, 0, 0, 0x02, 0x1C]);
+}
+
+#[test]
+fn capture_report_renders_snapshot_events_plots_and_config() {
+    let (cfg, _) = load_config(CONFIG, "report.yaml");
+    let mut signals = SignalTable::default();
+    signals.load(&cfg.canbus_config);
+    let mut units = DisplayUnits::default();
+    units.load(&cfg.components);
+    let mut stats = BusStatistics::default();
+    let mut frames = Vec::new();
+    for (i, speed) in [80u8, 100, 90].into_iter().enumerate() {
+        let mut frame = CanFrame::new(0, 0x100, &[speed, 0x00, 0xF6]);
+        frame.timestamp = 1_000_000 + i as u64 * 10_000;
+        signals.process(&frame);
+        stats.process(&frame);
+        frames.push(frame);
+    }
+    let snapshot = Snapshot::capture(
+        "2026-10-16 12:00:00.000".to_string(),
+        1_020_000,
+        "Simulated",
+        true,
+        &signals,
+        &units,
+        &stats,
+    )
+    .with_libraries(vec![LibraryVersion::new("PCANBasic", "4.8.0")])
+    .with_session(SessionInfo {
+        operator: "QA".to_string(),
+        notes: "check <wake-up>".to_string(),
+        ..Default::default()
+    });
+
+    // 多個設定檔以標頭分隔
+    let first = temp_path("report_a.yaml");
+    let second = temp_path("report_b.yaml");
+    fs::write(&first, "version: 2\n").unwrap();
+    fs::write(&second, "components: []\n").unwrap();
+    let config = config_listing(&[first.clone(), second.clone()]).unwrap();
+    fs::remove_file(&first).unwrap();
+    fs::remove_file(&second).unwrap();
+    assert_eq!(
+        config.0,
+        format!("{} + {}", first.display(), second.display())
+    );
+    assert_eq!(
+        config.1,
+        format!(
+            "# ==> {} <==\nversion: 2\n\n# ==> {} <==\ncomponents: []\n\n",
+            first.display(),
+            second.display()
+        )
+    );
+    assert_eq!(config_listing(&[]), None);
+
+    let mut events = EventLog::default();
+    events.push(
+        1_010_000,
+        Severity::Alarm,
+        EventCategory::Alarm,
+        "speed > 95".to_string(),
+    );
+    let plot = signal_plot(&frames, &cfg.canbus_config, "speed", &units, &[]);
+    assert_eq!(
+        plot.series[0].points,
+        [(0.0, 80.0), (0.01, 100.0), (0.02, 90.0)]
+    );
+    let report = CaptureReport {
+        snapshot: &snapshot,
+        started_us: Some(1_000_000),
+        events: events.events().cloned().collect(),
+        conformance: vec![CheckResult {
+            kind: CheckKind::CycleTime,
+            target: "0x100".to_string(),
+            checked: 3,
+            violations: 1,
+            detail: "period 10 ms > 5 ms".to_string(),
+        }],
+        plots: vec![plot],
+        config: Some(config),
+    };
+    let html = report.render_html();
+    for row in [
+        "<title>CAN Capture Report 2026-10-16 12:00:00.000</title>",
+        "<tr><th>Interface</th><td class=\"text\">Simulated</td></tr>",
+        "<tr><th>Operator</th><td class=\"text\">QA</td></tr>",
+        "<tr><th>PCANBasic</th><td class=\"text\">4.8.0</td></tr>",
+        "<tr><th>Duration</th><td class=\"text\">00:00:00.020</td></tr>",
+        "<tr><th>Frames received</th><td class=\"text\">3</td></tr>",
+        "<tr><th>Events</th><td class=\"text\">1</td></tr>",
+        "<pre>check &lt;wake-up&gt;</pre>",
+        "<tr><td>0</td><td>3</td><td>9</td><td>0</td><td>0</td><td>0.0%</td></tr>",
+        "<td class=\"text\">speed</td><td class=\"text\">km/h</td><td>90.000</td><td>80.000</td><td>100.000</td><td>90.000</td><td>3</td>",
+        "<td class=\"text\">Alarm</td><td class=\"text\">Alarm</td><td class=\"text\">speed &gt; 95</td>",
+        "<td class=\"text\">Cycle time</td><td class=\"text\">0x100</td><td class=\"text\" style=\"color: #c00\">FAIL</td><td>3</td><td>1</td><td class=\"text\">period 10 ms &gt; 5 ms</td>",
+        "<h2>Signal Plots</h2>\n<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"800\" height=\"320\"",
+        "<pre># ==&gt; ",
+    ] {
+        assert!(html.contains(row), "missing {}\n{}", row, html);
+    }
+    assert!(html.contains(&format!(
+        "<td>0x100</td><td>3</td><td>10.000</td><td>{}</td><td class=\"text\">5A 00 F6</td>",
+        format_timestamp(1_020_000)
+    )));
+    assert!(!html.contains("<th>DUT</th>"));
+    assert!(html.ends_with("</section>\n</body>\n</html>\n"));
+
+    // 沒有事件、檢查、圖表與設定時顯示對應說明
+    let empty = CaptureReport {
+        snapshot: &snapshot,
+        started_us: None,
+        events: Vec::new(),
+        conformance: Vec::new(),
+        plots: Vec::new(),
+        config: None,
+    }
+    .render_html();
+    assert!(empty.contains("<p>No events recorded.</p>"));
+    assert!(empty.contains("<p>No configuration loaded.</p>"));
+    assert!(!empty.contains("Conformance Checks"));
+    assert!(!empty.contains("Signal Plots"));
+    assert!(!empty.contains("<th>Duration</th>"));
+}
+
+#[test]
+fn remote_send_times_out_when_server_stops_reading() {
+    // 接受連線但從不讀取的伺服器，寫入緩衝區填滿後傳送會逾時
+    let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let client = RemoteCanApp::new(&listener.local_addr().unwrap().to_string());
+    let (log_tx, _log_rx) = flume::unbounded();
+    client.open_device(log_tx).unwrap();
+    let (_peer, _) = listener.accept().unwrap();
+
+    let frame = CanFrame::new(0, 0x123, &[0xAA; 64]);
+    let started = Instant::now();
+    let error = loop {
+        if let Err(e) = client.send_frame(&frame) {
+            break e;
+        }
+        assert!(
+            started.elapsed() < Duration::from_secs(30),
+            "send never blocked"
+        );
+    };
+    assert_eq!(
+        error,
+        format!(
+            "Remote transmit timed out after 2 s; disconnected from {}",
+            listener.local_addr().unwrap()
+        )
+    );
+    assert_eq!(
+        client.send_frame(&frame).unwrap_err(),
+        "Remote server not connected"
+    );
+}